@@ -1,6 +1,11 @@
 //! CLI argument parsing and command definitions
 
-use clap::{Args, Parser, Subcommand};
+// The action enums hold start args far larger than their other variants; they
+// are parsed once per run, so the size doesn't matter
+#![allow(clippy::large_enum_variant)]
+
+use clap::{ArgGroup, Args, Parser, Subcommand, ValueEnum};
+use serde::{Deserialize, Serialize};
 
 #[derive(Parser, Debug)]
 #[command(
@@ -16,6 +21,58 @@ pub struct Cli {
     /// Enable verbose output
     #[arg(short, long, global = true)]
     pub verbose: bool,
+
+    /// Print the Docker operations a start, stop, cleanup or deploy would run, without running them
+    #[arg(long, global = true)]
+    pub dry_run: bool,
+
+    /// Prefix for generated names (acme-redis-basic-1); also scopes cleanup to matching instances
+    #[arg(long, global = true, env = "REDIS_UP_PREFIX")]
+    pub prefix: Option<String>,
+
+    /// Namespace for instances and their state (defaults to $USER); generated names
+    /// carry it unless --prefix is given
+    #[arg(long, global = true, env = "REDIS_UP_NAMESPACE")]
+    pub namespace: Option<String>,
+
+    /// Apply a flag bundle from the `profiles` section of settings.yaml to a start command;
+    /// flags given on the command line win. With deploy, apply the deployment file's profile
+    #[arg(long, global = true, value_name = "NAME")]
+    pub profile: Option<String>,
+
+    /// Set an environment variable in the containers this run creates; a bare NAME
+    /// copies it from this shell, like `docker build --build-arg` (e.g. --pass-env HTTPS_PROXY)
+    #[arg(long, global = true, value_name = "NAME[=VALUE]")]
+    pub pass_env: Vec<String>,
+
+    /// Extra `docker run` option for the containers this run creates, applied after the
+    /// generated ones; repeatable, joined with '=' (--docker-arg=--ulimit=nofile=65535:65535)
+    #[arg(long, global = true, value_name = "OPTION")]
+    pub docker_arg: Vec<String>,
+
+    /// Disable colored output (also disabled by the NO_COLOR environment variable)
+    #[arg(long, global = true)]
+    pub no_color: bool,
+
+    /// Format of diagnostic log lines; json goes to stderr, one object per line
+    #[arg(long, global = true, value_enum, default_value_t = LogFormat::Text)]
+    pub log_format: LogFormat,
+
+    /// Export spans for this command to an OTLP/HTTP collector (e.g., http://localhost:4318)
+    #[arg(
+        long,
+        global = true,
+        env = "OTEL_EXPORTER_OTLP_ENDPOINT",
+        value_name = "URL"
+    )]
+    pub otel_endpoint: Option<String>,
+}
+
+/// Formats for `--log-format`
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum LogFormat {
+    Text,
+    Json,
 }
 
 #[derive(Subcommand, Debug)]
@@ -45,20 +102,39 @@ pub enum Commands {
         #[command(subcommand)]
         action: EnterpriseAction,
     },
+    /// Manage a shared RedisInsight for all instances
+    Insight {
+        #[command(subcommand)]
+        action: InsightAction,
+    },
+    /// Attach instances to existing Docker networks
+    Network {
+        #[command(subcommand)]
+        action: NetworkAction,
+    },
     /// List all running Redis instances
-    List {
-        /// Filter by instance type
-        #[arg(short, long)]
-        r#type: Option<String>,
+    List(ListArgs),
+    /// Clean up Redis instances (all, or those matching the selectors)
+    Cleanup(CleanupArgs),
+    /// Re-create the instances removed by the last cleanup
+    RestoreState(RestoreStateArgs),
+    /// Start the stopped containers of registered instances, e.g. after a reboot
+    Revive(ReviveArgs),
+    /// Add notes or change the description of an instance
+    Annotate(AnnotateArgs),
+    /// Unlock encrypted secrets for a while, keeping the key in the OS keyring
+    Unlock(UnlockArgs),
+    /// Lock encrypted secrets again
+    Lock,
+    /// Manage named start flag bundles used with --profile
+    Profile {
+        #[command(subcommand)]
+        action: ProfileAction,
     },
-    /// Clean up all Redis instances
-    Cleanup {
-        /// Skip confirmation prompt
-        #[arg(short, long)]
-        force: bool,
-        /// Only cleanup instances of specific type
-        #[arg(short, long)]
-        r#type: Option<String>,
+    /// Export or import the instance registry, e.g. when moving machines
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
     },
     /// View logs for Redis instances
     Logs {
@@ -73,20 +149,164 @@ pub enum Commands {
         /// Show timestamps
         #[arg(short, long)]
         timestamps: bool,
+        /// Only cluster nodes currently in this role (resolved live)
+        #[arg(short, long, value_enum)]
+        role: Option<NodeRole>,
+    },
+    /// Record INFO and docker stats samples over time, or summarize a recording
+    Record(RecordArgs),
+    /// Compare the keyspaces of two instances
+    Diff(DiffArgs),
+    /// Rewrite sensitive values with fake data using a rules file
+    Anonymize(AnonymizeArgs),
+    /// Proxy an instance and delay specific commands, to test per-operation timeouts
+    Slow(SlowArgs),
+    /// Show key counts per logical database
+    Dbsize(DbsizeArgs),
+    /// Open redis-cli on any instance, resolving its port, password and mode
+    Shell(ShellArgs),
+    /// Open a shell inside one of an instance's containers
+    Sh(ShArgs),
+    /// Show configuration that differs from Redis defaults or from what redis-up set
+    ConfigDiff(ConfigDiffArgs),
+    /// CONFIG SET on every node, optionally tracked so it survives restarts
+    ConfigSet(ConfigSetArgs),
+    /// Print code that connects to an instance with a language's usual client
+    ConnectSnippet(ConnectSnippetArgs),
+    /// Print connection environment variables, e.g. for `eval $(redis-up env my-redis)`
+    Env(EnvArgs),
+    /// Compare master and replica offsets in sentinel and cluster instances
+    Replication(ReplicationArgs),
+    /// Probe instances and exit non-zero if any is unhealthy, e.g. as a CI gate
+    Health(HealthArgs),
+    /// Generate realistic traffic against an instance and report latency
+    Load(LoadArgs),
+    /// Run a command against a basic instance of each listed Redis version
+    Matrix(MatrixArgs),
+    /// Run scripted fault scenarios against cluster and sentinel instances
+    Chaos {
+        #[command(subcommand)]
+        action: ChaosAction,
+    },
+    /// Load, run or live-reload Lua scripts against an instance
+    Script {
+        #[command(subcommand)]
+        action: ScriptAction,
+    },
+    /// Deploy and manage Redis 7 function libraries
+    Functions {
+        #[command(subcommand)]
+        action: FunctionsAction,
+    },
+    /// Watch managed containers and notify when one exits unexpectedly
+    Watch(WatchArgs),
+    /// Check the local environment for common problems
+    Doctor,
+    /// Run a Prometheus + Grafana bundle that scrapes managed instances
+    Observability {
+        #[command(subcommand)]
+        action: ObservabilityAction,
+    },
+    /// Pre-fetch or inspect the Docker images redis-up uses
+    Images {
+        #[command(subcommand)]
+        action: ImagesAction,
+    },
+    /// Start and stop instances as CI job steps, e.g. in place of a services container
+    Ci {
+        #[command(subcommand)]
+        action: CiAction,
     },
     /// Deploy Redis instances from YAML configuration
     Deploy {
         /// Path to YAML configuration file
         file: std::path::PathBuf,
     },
-    /// Generate example YAML configuration files
+    /// Pull the images a deployment file or profile needs, optionally creating its
+    /// instances stopped so the next start is near-instant
+    Warm(WarmArgs),
+    /// Answer a few questions and write a deployment file
+    Init(InitArgs),
+    /// Report starts, lifetimes, common flags and volume usage from local history
+    Usage(UsageArgs),
+    /// Forward an instance on a remote Docker host to this machine over SSH
+    Tunnel(TunnelArgs),
+    /// Show which host ports managed instances hold, by reserved range
+    Ports {
+        /// Print the map as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// List the exit codes redis-up uses, for scripts that branch on failures
+    ExitCodes {
+        /// Print the codes as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Generate example YAML configuration files, or their JSON schema
     Examples {
         /// Directory to create example files in
         #[arg(default_value = "./examples")]
         dir: std::path::PathBuf,
+
+        /// yaml writes the examples; json prints a JSON schema of deployment files for editors
+        #[arg(long, value_enum, default_value = "yaml")]
+        format: ExampleFormat,
     },
 }
 
+#[derive(Subcommand, Debug)]
+pub enum InsightAction {
+    /// Start the shared RedisInsight container
+    Start(InsightStartArgs),
+    /// Stop the shared RedisInsight container
+    Stop,
+    /// Show the shared RedisInsight and its registered databases
+    Status,
+}
+
+#[derive(Args, Debug)]
+pub struct InsightStartArgs {
+    /// RedisInsight port (default: 8001)
+    #[arg(short, long, default_value = "8001")]
+    pub port: u16,
+
+    #[command(flatten)]
+    pub pull: PullArgs,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ImagesAction {
+    /// Pull every image redis-up may need, for offline use
+    Pull(ImagesArgs),
+    /// Show which images are available locally
+    List(ImagesArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct ImagesArgs {
+    /// Include the Prometheus, Grafana and redis_exporter images
+    #[arg(long)]
+    pub observability: bool,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum NetworkAction {
+    /// Connect an instance's containers to a Docker network
+    Attach(NetworkAttachArgs),
+    /// Disconnect an instance's containers from a Docker network
+    Detach(NetworkAttachArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct NetworkAttachArgs {
+    /// Instance name
+    pub name: String,
+
+    /// Docker network name (e.g., a docker-compose project network)
+    pub network: String,
+}
+
 #[derive(Subcommand, Debug)]
 pub enum RedisAction {
     /// Start a basic Redis instance
@@ -124,7 +344,11 @@ pub enum SentinelAction {
     /// Stop a Redis Sentinel setup
     Stop(StopArgs),
     /// Get info about a Redis Sentinel setup
-    Info(InfoArgs),
+    Info(SentinelInfoArgs),
+    /// Print client connection config listing every sentinel
+    Url(SentinelUrlArgs),
+    /// Change quorum and failover timing of running sentinels (SENTINEL SET)
+    Tune(SentinelTuneArgs),
 }
 
 #[derive(Subcommand, Debug)]
@@ -133,32 +357,171 @@ pub enum EnterpriseAction {
     Start(EnterpriseStartArgs),
     /// Stop a Redis Enterprise cluster
     Stop(StopArgs),
-    /// Get info about a Redis Enterprise cluster
-    Info(InfoArgs),
+    /// Get info about a Redis Enterprise cluster, live from its REST API when reachable
+    Info(EnterpriseInfoArgs),
+    /// Call the Enterprise REST API with the instance's stored credentials
+    Api(EnterpriseApiArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct EnterpriseInfoArgs {
+    #[command(flatten)]
+    pub info: InfoArgs,
+
+    /// List every database endpoint with its port and password
+    #[arg(long)]
+    pub databases: bool,
 }
 
 #[derive(Args, Debug)]
+pub struct EnterpriseApiArgs {
+    /// Instance name
+    pub name: String,
+
+    /// HTTP method (GET, POST, PUT, DELETE, ...)
+    pub method: String,
+
+    /// API path (e.g., /v1/bdbs)
+    pub path: String,
+
+    /// JSON request body, or @file to read it from a file
+    #[arg(short, long)]
+    pub data: Option<String>,
+
+    /// Admin username (defaults to the one stored for the instance)
+    #[arg(long, env = "REDIS_ENTERPRISE_USERNAME")]
+    pub username: Option<String>,
+
+    /// Admin password (defaults to the one stored for the instance)
+    #[arg(long, env = "REDIS_ENTERPRISE_PASSWORD")]
+    pub password: Option<String>,
+
+    /// Print the response body without pretty-printing
+    #[arg(long)]
+    pub raw: bool,
+}
+
+#[derive(Args, Debug, Clone)]
 pub struct BasicStartArgs {
     /// Instance name (auto-generated if not provided)
     #[arg(short, long)]
     pub name: Option<String>,
 
+    /// Redis version to run, e.g. 7.2 (uses the redis:<VERSION>-alpine image)
+    #[arg(long, value_name = "VERSION")]
+    pub redis_version: Option<String>,
+
+    /// Free-form description shown in list and info
+    #[arg(long)]
+    pub description: Option<String>,
+
     /// Redis port (default: 6379)
     #[arg(short, long, default_value = "6379")]
     pub port: u16,
 
+    /// Start N instances on consecutive ports (with --name, named NAME-1 to NAME-N)
+    #[arg(long, value_name = "N", default_value_t = 1,
+          value_parser = clap::value_parser!(u16).range(1..),
+          conflicts_with_all = ["data_volume", "shell"])]
+    pub count: u16,
+
+    /// With --count, give every instance the same generated password instead of one each
+    #[arg(long, requires = "count", conflicts_with_all = ["password", "no_auth"])]
+    pub shared_password: bool,
+
     /// Set a password for Redis
     #[arg(long)]
     pub password: Option<String>,
 
+    /// Run without a password; the port is published on 127.0.0.1 unless --bind says otherwise
+    #[arg(long, conflicts_with = "password")]
+    pub no_auth: bool,
+
     /// Enable persistence
     #[arg(long)]
     pub persist: bool,
 
+    /// Persistence mode (rdb, aof, both, none)
+    #[arg(long, value_enum)]
+    pub persistence: Option<PersistenceMode>,
+
+    /// RDB save points (e.g., "60 1000"), requires rdb or both persistence
+    #[arg(long)]
+    pub save: Option<String>,
+
+    /// AOF fsync policy, requires aof or both persistence
+    #[arg(long, value_enum)]
+    pub appendfsync: Option<AppendFsync>,
+
+    /// Number of logical databases (redis-server defaults to 16)
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    pub databases: Option<u32>,
+
+    /// Preload an RDB file into the data volume before first boot (implies --persist)
+    #[arg(long, value_name = "FILE")]
+    pub load_rdb: Option<std::path::PathBuf>,
+
+    /// Start against an existing data volume, such as one kept by `stop --keep-data` (implies --persist)
+    #[arg(long, value_name = "VOLUME", conflicts_with = "load_rdb")]
+    pub data_volume: Option<String>,
+
+    /// Attach to an existing Docker network (can be repeated)
+    #[arg(long = "network", value_name = "NETWORK")]
+    pub networks: Vec<String>,
+
+    /// Host address to bind the published port to (e.g., 0.0.0.0 for all interfaces)
+    #[arg(long, visible_alias = "host-ip", value_name = "IP")]
+    pub bind: Option<std::net::IpAddr>,
+
+    /// Allow --no-auth on a non-loopback --bind address
+    #[arg(long = "i-know-what-im-doing", requires = "no_auth")]
+    pub allow_exposed: bool,
+
+    /// Run on the host's network instead of publishing a port (Linux), for the
+    /// lowest latency in benchmarks
+    #[arg(
+        long,
+        value_enum,
+        value_name = "MODE",
+        default_value_t = NetworkMode::Bridge,
+        conflicts_with_all = ["ipv6", "networks"]
+    )]
+    pub network_mode: NetworkMode,
+
+    /// Put the instance on an IPv6-enabled network and record its ::1 address
+    #[arg(long)]
+    pub ipv6: bool,
+
     /// Memory limit (e.g., "256m", "1g")
     #[arg(long)]
     pub memory: Option<String>,
 
+    #[command(flatten)]
+    pub limits: LimitArgs,
+
+    #[command(flatten)]
+    pub commands: CommandArgs,
+
+    #[command(flatten)]
+    pub pull: PullArgs,
+
+    /// Retry a failed start N times with exponential backoff (default: settings file, else 0)
+    #[arg(long, value_name = "N")]
+    pub retries: Option<u32>,
+
+    /// If an instance with this name already runs with the requested settings, print
+    /// its connection details and succeed instead of failing
+    #[arg(long, requires = "name")]
+    pub reuse: bool,
+
+    /// With --reuse, stop and recreate an instance whose settings differ instead of failing
+    #[arg(long, requires = "reuse", conflicts_with = "warn_on_drift")]
+    pub recreate_on_drift: bool,
+
+    /// With --reuse, warn about differing settings and reuse the instance anyway
+    #[arg(long, requires = "reuse")]
+    pub warn_on_drift: bool,
+
     /// Connect to redis-cli shell after starting
     #[arg(long)]
     pub shell: bool,
@@ -172,12 +535,16 @@ pub struct BasicStartArgs {
     pub insight_port: u16,
 }
 
-#[derive(Args, Debug)]
+#[derive(Args, Debug, Clone)]
 pub struct StackStartArgs {
     /// Instance name (auto-generated if not provided)
     #[arg(short, long)]
     pub name: Option<String>,
 
+    /// Free-form description shown in list and info
+    #[arg(long)]
+    pub description: Option<String>,
+
     /// Redis port (default: 6379)
     #[arg(short, long, default_value = "6379")]
     pub port: u16,
@@ -186,14 +553,99 @@ pub struct StackStartArgs {
     #[arg(long)]
     pub password: Option<String>,
 
+    /// Run without a password; the port is published on 127.0.0.1 unless --bind says otherwise
+    #[arg(long, conflicts_with = "password")]
+    pub no_auth: bool,
+
     /// Enable persistence
     #[arg(long)]
     pub persist: bool,
 
+    /// Persistence mode (rdb, aof, both, none)
+    #[arg(long, value_enum)]
+    pub persistence: Option<PersistenceMode>,
+
+    /// RDB save points (e.g., "60 1000"), requires rdb or both persistence
+    #[arg(long)]
+    pub save: Option<String>,
+
+    /// AOF fsync policy, requires aof or both persistence
+    #[arg(long, value_enum)]
+    pub appendfsync: Option<AppendFsync>,
+
+    /// Number of logical databases (redis-server defaults to 16)
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    pub databases: Option<u32>,
+
+    /// Preload an RDB file into the data volume before first boot (implies --persist)
+    #[arg(long, value_name = "FILE")]
+    pub load_rdb: Option<std::path::PathBuf>,
+
+    /// Start against an existing data volume, such as one kept by `stop --keep-data` (implies --persist)
+    #[arg(long, value_name = "VOLUME", conflicts_with = "load_rdb")]
+    pub data_volume: Option<String>,
+
+    /// Attach to an existing Docker network (can be repeated)
+    #[arg(long = "network", value_name = "NETWORK")]
+    pub networks: Vec<String>,
+
+    /// Host address to bind the published port to (e.g., 0.0.0.0 for all interfaces)
+    #[arg(long, visible_alias = "host-ip", value_name = "IP")]
+    pub bind: Option<std::net::IpAddr>,
+
+    /// Allow --no-auth on a non-loopback --bind address
+    #[arg(long = "i-know-what-im-doing", requires = "no_auth")]
+    pub allow_exposed: bool,
+
+    /// Run on the host's network instead of publishing a port (Linux), for the
+    /// lowest latency in benchmarks
+    #[arg(
+        long,
+        value_enum,
+        value_name = "MODE",
+        default_value_t = NetworkMode::Bridge,
+        conflicts_with_all = ["ipv6", "networks", "with_insight"]
+    )]
+    pub network_mode: NetworkMode,
+
+    /// Put the instance on an IPv6-enabled network and record its ::1 address
+    #[arg(long)]
+    pub ipv6: bool,
+
     /// Memory limit (e.g., "256m", "1g")
     #[arg(long)]
     pub memory: Option<String>,
 
+    #[command(flatten)]
+    pub limits: LimitArgs,
+
+    #[command(flatten)]
+    pub commands: CommandArgs,
+
+    #[command(flatten)]
+    pub pull: PullArgs,
+
+    /// Retry a failed start N times with exponential backoff (default: settings file, else 0)
+    #[arg(long, value_name = "N")]
+    pub retries: Option<u32>,
+
+    /// If an instance with this name already runs with the requested settings, print
+    /// its connection details and succeed instead of failing
+    #[arg(long, requires = "name")]
+    pub reuse: bool,
+
+    /// With --reuse, stop and recreate an instance whose settings differ instead of failing
+    #[arg(long, requires = "reuse", conflicts_with = "warn_on_drift")]
+    pub recreate_on_drift: bool,
+
+    /// With --reuse, warn about differing settings and reuse the instance anyway
+    #[arg(long, requires = "reuse")]
+    pub warn_on_drift: bool,
+
+    /// Image family: redis-stack, or redis:8 with the modules built in
+    #[arg(long, value_enum, default_value = "stack")]
+    pub flavor: StackFlavor,
+
     /// Enable RedisJSON module
     #[arg(long)]
     pub with_json: bool,
@@ -206,7 +658,7 @@ pub struct StackStartArgs {
     #[arg(long)]
     pub with_timeseries: bool,
 
-    /// Enable RedisGraph module
+    /// Enable RedisGraph module (deprecated, not in Redis 8)
     #[arg(long)]
     pub with_graph: bool,
 
@@ -231,12 +683,16 @@ pub struct StackStartArgs {
     pub shell: bool,
 }
 
-#[derive(Args, Debug)]
+#[derive(Args, Debug, Clone)]
 pub struct ClusterStartArgs {
     /// Cluster name (auto-generated if not provided)
     #[arg(short, long)]
     pub name: Option<String>,
 
+    /// Free-form description shown in list and info
+    #[arg(long)]
+    pub description: Option<String>,
+
     /// Number of master nodes (minimum 3)
     #[arg(short, long, default_value = "3")]
     pub masters: usize,
@@ -257,14 +713,82 @@ pub struct ClusterStartArgs {
     #[arg(long)]
     pub persist: bool,
 
+    /// Persistence mode (rdb, aof, both, none)
+    #[arg(long, value_enum)]
+    pub persistence: Option<PersistenceMode>,
+
+    /// RDB save points (e.g., "60 1000"), requires rdb or both persistence
+    #[arg(long)]
+    pub save: Option<String>,
+
+    /// AOF fsync policy, requires aof or both persistence
+    #[arg(long, value_enum)]
+    pub appendfsync: Option<AppendFsync>,
+
     /// Memory limit per node (e.g., "256m", "1g")
     #[arg(long)]
     pub memory: Option<String>,
 
+    /// Start even if the nodes need more memory than the host has available
+    #[arg(long)]
+    pub force: bool,
+
+    #[command(flatten)]
+    pub limits: LimitArgs,
+
+    #[command(flatten)]
+    pub pull: PullArgs,
+
+    /// Retry a failed start N times with exponential backoff (default: settings file, else 0)
+    #[arg(long, value_name = "N")]
+    pub retries: Option<u32>,
+
+    /// If an instance with this name already runs with the requested settings, print
+    /// its connection details and succeed instead of failing
+    #[arg(long, requires = "name")]
+    pub reuse: bool,
+
+    /// With --reuse, stop and recreate an instance whose settings differ instead of failing
+    #[arg(long, requires = "reuse", conflicts_with = "warn_on_drift")]
+    pub recreate_on_drift: bool,
+
+    /// With --reuse, warn about differing settings and reuse the instance anyway
+    #[arg(long, requires = "reuse")]
+    pub warn_on_drift: bool,
+
     /// Use Redis Stack instead of basic Redis
     #[arg(long)]
     pub stack: bool,
 
+    /// Mirror a managed service's constraints: TLS-only clients, no CONFIG and other admin commands
+    #[arg(long, value_enum, value_name = "SERVICE", conflicts_with = "stack")]
+    pub emulate: Option<Emulation>,
+
+    /// Address nodes announce in CLUSTER SLOTS and MOVED redirections instead of
+    /// their container IPs, e.g. the host's LAN IP; containers must reach it too
+    #[arg(long, value_name = "IP", conflicts_with = "emulate")]
+    pub announce_ip: Option<String>,
+
+    /// Host name clients are redirected to instead of the announced IP, e.g.
+    /// localhost or host.docker.internal (nodes still talk over --announce-ip)
+    #[arg(long, value_name = "HOST", requires = "announce_ip")]
+    pub announce_hostname: Option<String>,
+
+    /// Run nodes on the host's network instead of publishing ports (Linux): the
+    /// lowest latency, and MOVED redirections point at addresses the host can reach
+    #[arg(
+        long,
+        value_enum,
+        value_name = "MODE",
+        default_value_t = NetworkMode::Bridge,
+        conflicts_with_all = ["emulate", "announce_ip", "with_insight"]
+    )]
+    pub network_mode: NetworkMode,
+
+    /// Not supported for clusters; refused with the reason
+    #[arg(long, hide = true)]
+    pub ipv6: bool,
+
     /// Start RedisInsight GUI
     #[arg(long)]
     pub with_insight: bool,
@@ -276,22 +800,50 @@ pub struct ClusterStartArgs {
     /// Connect to redis-cli shell after starting
     #[arg(long)]
     pub shell: bool,
+
+    /// Progress format: spinner/text, or JSON lines on stderr
+    #[arg(long, value_enum, default_value = "text")]
+    pub output: OutputFormat,
 }
 
-#[derive(Args, Debug)]
+#[derive(Args, Debug, Clone)]
 pub struct SentinelStartArgs {
     /// Sentinel setup name (auto-generated if not provided)
     #[arg(short, long)]
     pub name: Option<String>,
 
+    /// Free-form description shown in list and info
+    #[arg(long)]
+    pub description: Option<String>,
+
     /// Number of Redis masters to monitor
     #[arg(short, long, default_value = "1")]
     pub masters: usize,
 
+    /// Name Sentinel monitors a master under, once per master in order (default: master-N)
+    #[arg(long = "master-name", value_name = "NAME")]
+    pub master_names: Vec<String>,
+
     /// Number of Sentinel nodes
     #[arg(short, long, default_value = "3")]
     pub sentinels: usize,
 
+    /// Sentinels that must agree a master is down (default: a majority)
+    #[arg(long, value_name = "N")]
+    pub quorum: Option<usize>,
+
+    /// Milliseconds a master must be unreachable before it is considered down
+    #[arg(long, value_name = "MS", default_value = "5000")]
+    pub down_after_ms: u64,
+
+    /// Failover timeout in milliseconds
+    #[arg(long, value_name = "MS", default_value = "10000")]
+    pub failover_timeout: u64,
+
+    /// Replicas resynced with a new master at the same time after a failover
+    #[arg(long, value_name = "N", default_value = "1")]
+    pub parallel_syncs: u32,
+
     /// Base port for Redis masters (default: 6379)
     #[arg(long, default_value = "6379")]
     pub redis_port_base: u16,
@@ -312,6 +864,41 @@ pub struct SentinelStartArgs {
     #[arg(long)]
     pub memory: Option<String>,
 
+    #[command(flatten)]
+    pub limits: LimitArgs,
+
+    #[command(flatten)]
+    pub pull: PullArgs,
+
+    /// Retry a failed start N times with exponential backoff (default: settings file, else 0)
+    #[arg(long, value_name = "N")]
+    pub retries: Option<u32>,
+
+    /// If an instance with this name already runs with the requested settings, print
+    /// its connection details and succeed instead of failing
+    #[arg(long, requires = "name")]
+    pub reuse: bool,
+
+    /// With --reuse, stop and recreate an instance whose settings differ instead of failing
+    #[arg(long, requires = "reuse", conflicts_with = "warn_on_drift")]
+    pub recreate_on_drift: bool,
+
+    /// With --reuse, warn about differing settings and reuse the instance anyway
+    #[arg(long, requires = "reuse")]
+    pub warn_on_drift: bool,
+
+    /// Create the Sentinel network with IPv6 enabled and record ::1 addresses
+    #[arg(long)]
+    pub ipv6: bool,
+
+    /// Add a read-only replica per master on its own host port (listed as replica-read-N)
+    #[arg(long)]
+    pub with_readonly_port: bool,
+
+    /// Front each master with HAProxy on a stable port that follows failovers
+    #[arg(long)]
+    pub with_lb: bool,
+
     /// Start RedisInsight GUI
     #[arg(long)]
     pub with_insight: bool,
@@ -321,12 +908,16 @@ pub struct SentinelStartArgs {
     pub insight_port: u16,
 }
 
-#[derive(Args, Debug)]
+#[derive(Args, Debug, Clone)]
 pub struct EnterpriseStartArgs {
     /// Enterprise cluster name (auto-generated if not provided)
     #[arg(short, long)]
     pub name: Option<String>,
 
+    /// Free-form description shown in list and info
+    #[arg(long)]
+    pub description: Option<String>,
+
     /// Number of Enterprise nodes
     #[arg(short = 'N', long, default_value = "3")]
     pub nodes: usize,
@@ -347,6 +938,33 @@ pub struct EnterpriseStartArgs {
     #[arg(long)]
     pub memory: Option<String>,
 
+    /// Start even if the nodes need more memory than the host has available
+    #[arg(long)]
+    pub force: bool,
+
+    #[command(flatten)]
+    pub limits: LimitArgs,
+
+    #[command(flatten)]
+    pub pull: PullArgs,
+
+    /// Retry a failed start N times with exponential backoff (default: settings file, else 0)
+    #[arg(long, value_name = "N")]
+    pub retries: Option<u32>,
+
+    /// If an instance with this name already runs with the requested settings, print
+    /// its connection details and succeed instead of failing
+    #[arg(long, requires = "name")]
+    pub reuse: bool,
+
+    /// With --reuse, stop and recreate an instance whose settings differ instead of failing
+    #[arg(long, requires = "reuse", conflicts_with = "warn_on_drift")]
+    pub recreate_on_drift: bool,
+
+    /// With --reuse, warn about differing settings and reuse the instance anyway
+    #[arg(long, requires = "reuse")]
+    pub warn_on_drift: bool,
+
     /// Enable persistence
     #[arg(long)]
     pub persist: bool,
@@ -355,6 +973,18 @@ pub struct EnterpriseStartArgs {
     #[arg(long)]
     pub containers_only: bool,
 
+    /// Start several single-node clusters joined by an Active-Active (CRDB) database
+    #[arg(long, conflicts_with = "containers_only")]
+    pub active_active: bool,
+
+    /// Number of participating clusters for --active-active
+    #[arg(long, default_value = "2", requires = "active_active", value_parser = clap::value_parser!(u16).range(2..=5))]
+    pub participants: u16,
+
+    /// Not supported for Enterprise; refused with the reason
+    #[arg(long, hide = true)]
+    pub ipv6: bool,
+
     /// Start RedisInsight GUI
     #[arg(long)]
     pub with_insight: bool,
@@ -362,20 +992,1128 @@ pub struct EnterpriseStartArgs {
     /// RedisInsight port (default: 8001)
     #[arg(long, default_value = "8001")]
     pub insight_port: u16,
+
+    /// Progress format: spinner/text, or JSON lines on stderr
+    #[arg(long, value_enum, default_value = "text")]
+    pub output: OutputFormat,
+}
+
+/// CPU and block IO limits and restart policy shared by all start commands
+#[derive(Args, Debug, Clone, Default)]
+pub struct LimitArgs {
+    /// CPU limit in cores (e.g., 0.5)
+    #[arg(long)]
+    pub cpus: Option<f64>,
+
+    /// Relative CPU weight (default: 1024)
+    #[arg(long)]
+    pub cpu_shares: Option<u32>,
+
+    /// Block IO weight, 10-1000 (relative to other containers)
+    #[arg(long, value_parser = clap::value_parser!(u16).range(10..=1000))]
+    pub blkio_limit: Option<u16>,
+
+    /// Docker restart policy, e.g. unless-stopped to come back after a daemon or machine restart
+    #[arg(long, value_enum)]
+    pub restart_policy: Option<RestartPolicy>,
+}
+
+/// Command renaming and disabling shared by basic and stack starts
+#[derive(Args, Debug, Clone, Default)]
+pub struct CommandArgs {
+    /// Rename a command, or disable it with an empty name (e.g., CONFIG=CFG_X9, FLUSHALL=)
+    #[arg(long = "rename-command", value_name = "CMD=NEW")]
+    pub rename_commands: Vec<String>,
+
+    /// Disable FLUSHALL, FLUSHDB, KEYS and DEBUG, and rename CONFIG to a generated name
+    #[arg(long)]
+    pub hardened: bool,
+}
+
+/// Image pull and platform behaviour shared by all start commands
+#[derive(Args, Debug, Clone, Default)]
+pub struct PullArgs {
+    /// When to pull images (default: settings file, else missing)
+    #[arg(long, value_enum, value_name = "POLICY")]
+    pub pull: Option<PullPolicy>,
+
+    /// Never pull; fail fast if an image is not available locally
+    #[arg(long, conflicts_with = "pull")]
+    pub offline: bool,
+
+    /// Image platform, e.g. linux/amd64 or linux/arm64 (default: settings file, else native)
+    #[arg(long, value_name = "PLATFORM")]
+    pub platform: Option<String>,
+}
+
+/// When to pull images before starting containers
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PullPolicy {
+    /// Pull every image, even if it is already present
+    Always,
+    /// Pull only images that are not present (Docker's default)
+    #[default]
+    Missing,
+    /// Never pull; fail if an image is not present
+    Never,
 }
 
 #[derive(Args, Debug)]
-pub struct StopArgs {
-    /// Instance name (uses auto-generated name if not provided)
+pub struct RecordArgs {
+    /// Instance name to record
+    #[arg(required_unless_present = "report")]
     pub name: Option<String>,
+
+    /// Time between samples (e.g., 5s, 1m)
+    #[arg(short, long, default_value = "5s", value_parser = humantime::parse_duration)]
+    pub interval: std::time::Duration,
+
+    /// JSONL file to append samples to
+    #[arg(short, long, default_value = "stats.jsonl")]
+    pub output: std::path::PathBuf,
+
+    /// Stop recording after this long (default: until Ctrl+C)
+    #[arg(short, long, value_parser = humantime::parse_duration)]
+    pub duration: Option<std::time::Duration>,
+
+    /// INFO sections to sample
+    #[arg(
+        long,
+        value_delimiter = ',',
+        default_value = "memory,stats,clients,keyspace"
+    )]
+    pub sections: Vec<String>,
+
+    /// Summarize an existing recording instead of sampling
+    #[arg(long, value_name = "FILE", conflicts_with = "name")]
+    pub report: Option<std::path::PathBuf>,
 }
 
 #[derive(Args, Debug)]
-pub struct InfoArgs {
-    /// Instance name (uses auto-generated name if not provided)
+pub struct DbsizeArgs {
+    /// Instance name
+    pub name: String,
+
+    /// List every logical database, including empty ones
+    #[arg(long)]
+    pub all_dbs: bool,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum CiAction {
+    /// Start an instance and publish its connection details
+    Start(CiStartArgs),
+    /// Remove instances started with `ci start` (succeeds when there are none)
+    Stop(CiStopArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct CiStartArgs {
+    /// Kind of instance to start
+    #[arg(value_enum)]
+    pub kind: CiKind,
+
+    /// Instance name (default: ci-<KIND>)
+    #[arg(short, long)]
     pub name: Option<String>,
 
-    /// Output format
-    #[arg(short, long, default_value = "table")]
-    pub format: String,
+    /// Wait until every node answers PING (and the cluster state is ok)
+    #[arg(long)]
+    pub wait: bool,
+
+    /// How long --wait waits
+    #[arg(long, default_value = "60s", value_parser = humantime::parse_duration)]
+    pub wait_timeout: std::time::Duration,
+
+    /// Where connection details go
+    #[arg(short, long, value_enum, default_value = "env")]
+    pub output: CiOutput,
+
+    /// With --output env, append KEY=value lines to this file instead of printing them
+    #[arg(long, value_name = "FILE")]
+    pub env_file: Option<std::path::PathBuf>,
+
+    /// Extra options for the start command, after `--` (e.g. -- --port 6380)
+    #[arg(last = true)]
+    pub start_args: Vec<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct CiStopArgs {
+    /// Only stop this instance (default: every instance started with `ci start`)
+    #[arg(short, long)]
+    pub name: Option<String>,
+
+    /// Seconds each container gets to exit before it is killed
+    #[arg(long)]
+    pub timeout: Option<u32>,
+}
+
+/// Instance kinds `ci start` supports
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum CiKind {
+    Basic,
+    Stack,
+    Cluster,
+    Sentinel,
+}
+
+impl std::fmt::Display for CiKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CiKind::Basic => write!(f, "basic"),
+            CiKind::Stack => write!(f, "stack"),
+            CiKind::Cluster => write!(f, "cluster"),
+            CiKind::Sentinel => write!(f, "sentinel"),
+        }
+    }
+}
+
+/// Destinations for `ci start` connection details
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum CiOutput {
+    /// Step outputs and job environment via $GITHUB_OUTPUT and $GITHUB_ENV
+    Github,
+    /// KEY=value lines on stdout or in --env-file
+    Env,
+}
+
+#[derive(Args, Debug)]
+pub struct ListArgs {
+    /// Filter by instance type
+    #[arg(short, long)]
+    pub r#type: Option<String>,
+
+    /// One block per instance with address, containers and notes
+    #[arg(short, long, conflicts_with = "quiet")]
+    pub wide: bool,
+
+    /// Order of instances (within each type group)
+    #[arg(short, long, value_enum, default_value = "created")]
+    pub sort: ListSort,
+
+    /// Only show instances matching KEY=VALUE: status (running, partial, stopped,
+    /// missing), type, or name (`*` and `?` wildcards); repeat to combine
+    #[arg(short, long, value_name = "KEY=VALUE")]
+    pub filter: Vec<String>,
+
+    /// Print only instance names, one per line (e.g. for xargs)
+    #[arg(short, long)]
+    pub quiet: bool,
+
+    /// List instances of every namespace, not just the current one
+    #[arg(long)]
+    pub all_namespaces: bool,
+
+    /// Print every host port held by instances and sidecars, sorted, with its owner
+    #[arg(long, conflicts_with_all = ["wide", "quiet"])]
+    pub ports: bool,
+}
+
+/// Sort orders for `list`
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum ListSort {
+    /// Alphabetical by name
+    Name,
+    /// Newest first
+    Created,
+    /// Grouped by type, then by name
+    Type,
+    /// Lowest port first
+    Port,
+}
+
+#[derive(Args, Debug)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+pub struct TunnelArgs {
+    #[command(subcommand)]
+    pub action: Option<TunnelAction>,
+
+    /// Instance name, as redis-up knows it on the remote host (or here, if adopted)
+    #[arg(required = true)]
+    pub name: Option<String>,
+
+    /// SSH destination where the instance runs, e.g. user@devbox
+    #[arg(long, required = true, value_name = "DESTINATION")]
+    pub via: Option<String>,
+
+    /// Local port for single-port instances (default: the remote port); instances
+    /// with several ports keep their port numbers
+    #[arg(long)]
+    pub local_port: Option<u16>,
+}
+
+#[derive(Args, Debug)]
+pub struct WarmArgs {
+    /// Deployment file to warm up for
+    #[arg(required_unless_present = "kind")]
+    pub file: Option<std::path::PathBuf>,
+
+    /// Instance type to warm up for instead, with the flags of the global --profile
+    #[arg(
+        long = "type",
+        value_name = "TYPE",
+        conflicts_with = "file",
+        value_parser = ["basic", "stack", "cluster", "sentinel", "enterprise"]
+    )]
+    pub kind: Option<String>,
+
+    /// Instance name, with --type
+    #[arg(long, requires = "kind")]
+    pub name: Option<String>,
+
+    /// Also create the instances and leave them stopped; starting one with the same
+    /// name reuses its containers
+    #[arg(long)]
+    pub create: bool,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum TunnelAction {
+    /// Close a tunnel opened by `redis-up tunnel`
+    Stop {
+        /// Instance name
+        #[arg(required_unless_present = "all")]
+        name: Option<String>,
+
+        /// Close every tunnel
+        #[arg(long, conflicts_with = "name")]
+        all: bool,
+    },
+    /// List open tunnels
+    List,
+}
+
+#[derive(Args, Debug)]
+pub struct UsageArgs {
+    /// Only count history from this far back (e.g. 7d, 24h)
+    #[arg(long, value_parser = humantime::parse_duration)]
+    pub since: Option<std::time::Duration>,
+}
+
+#[derive(Args, Debug)]
+pub struct MatrixArgs {
+    /// Redis versions to test, comma-separated (e.g. 6.2,7.2,7.4)
+    #[arg(long, value_delimiter = ',', required = true)]
+    pub versions: Vec<String>,
+
+    /// Shell command to run against each version, with REDIS_URL and friends set
+    #[arg(long)]
+    pub run: String,
+
+    /// Run the command against every version at once instead of one by one
+    #[arg(long)]
+    pub parallel: bool,
+
+    /// First host port; each version gets the next one
+    #[arg(long, default_value = "6390")]
+    pub port_base: u16,
+
+    /// Leave the instances running afterwards
+    #[arg(long)]
+    pub keep: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct ShellArgs {
+    /// Instance name
+    pub name: String,
+
+    /// Node to connect to, counting from 0 (cluster nodes, or sentinel masters then sentinels)
+    #[arg(long)]
+    pub node: Option<usize>,
+
+    /// Follow cluster redirects (always on for clusters)
+    #[arg(short, long)]
+    pub cluster_mode: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct ShArgs {
+    /// Instance name
+    pub name: String,
+
+    /// Container to enter, counting from 0 (cluster nodes, or sentinel masters then sentinels)
+    #[arg(long)]
+    pub node: Option<usize>,
+
+    /// Shell to run in the container
+    #[arg(long, default_value = "/bin/sh")]
+    pub shell: String,
+}
+
+#[derive(Args, Debug)]
+pub struct ConfigDiffArgs {
+    /// Instance name
+    pub name: String,
+
+    /// Node to inspect, counting from 0 (cluster nodes)
+    #[arg(long)]
+    pub node: Option<usize>,
+}
+
+#[derive(Args, Debug)]
+pub struct ConfigSetArgs {
+    /// Instance name
+    pub name: String,
+
+    /// Parameter to set, e.g. maxmemory-policy
+    #[arg(required_unless_present = "reapply")]
+    pub key: Option<String>,
+
+    /// New value
+    #[arg(required_unless_present_any = ["reapply", "forget"])]
+    pub value: Option<String>,
+
+    /// Track the change so it is applied again after restarts
+    #[arg(long, conflicts_with_all = ["reapply", "forget"])]
+    pub persist: bool,
+
+    /// Apply every tracked override again, e.g. after a container restart
+    #[arg(long, conflicts_with = "forget")]
+    pub reapply: bool,
+
+    /// Stop tracking KEY, leaving the running value alone
+    #[arg(long)]
+    pub forget: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct ConnectSnippetArgs {
+    /// Instance name
+    pub name: String,
+
+    /// Language to write the snippet in
+    #[arg(short, long, value_enum)]
+    pub lang: SnippetLang,
+}
+
+/// Languages `connect-snippet` writes client code for
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum SnippetLang {
+    /// redis-rs
+    Rust,
+    /// redis-py
+    Python,
+    /// ioredis
+    Node,
+    /// Lettuce
+    Java,
+    /// go-redis
+    Go,
+}
+
+#[derive(Args, Debug)]
+pub struct EnvArgs {
+    /// Instance name
+    pub name: String,
+
+    /// Output syntax (default: shell, or dotenv with --write)
+    #[arg(short, long, value_enum)]
+    pub format: Option<EnvFormat>,
+
+    /// Insert or update the variables in this dotenv file instead of printing them
+    #[arg(short, long, value_name = "FILE")]
+    pub write: Option<std::path::PathBuf>,
+
+    /// Prepend this to every variable name, e.g. CACHE_ for CACHE_REDIS_URL
+    #[arg(long)]
+    pub var_prefix: Option<String>,
+}
+
+/// Syntaxes `env` can print variables in
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum EnvFormat {
+    /// `export KEY='value'` lines for eval
+    Shell,
+    /// `KEY=value` lines for .env files, quoted where needed
+    Dotenv,
+    /// `KEY=value` lines for docker --env-file
+    Docker,
+}
+
+#[derive(Args, Debug)]
+pub struct HealthArgs {
+    /// Instance name
+    #[arg(required_unless_present = "all", conflicts_with = "all")]
+    pub name: Option<String>,
+
+    /// Check every instance
+    #[arg(long)]
+    pub all: bool,
+
+    /// How long each server gets to answer a probe
+    #[arg(long, default_value = "2s", value_parser = humantime::parse_duration)]
+    pub timeout: std::time::Duration,
+}
+
+#[derive(Args, Debug)]
+pub struct ReplicationArgs {
+    /// Instance name
+    pub name: String,
+
+    /// Flag replicas more than this many bytes behind their master
+    #[arg(long, value_name = "BYTES", default_value = "1048576")]
+    pub max_lag_bytes: u64,
+
+    /// Flag replicas that have not acknowledged for this many seconds
+    #[arg(long, value_name = "SECS", default_value = "5")]
+    pub max_lag_secs: u64,
+}
+
+#[derive(Args, Debug)]
+pub struct LoadArgs {
+    /// Instance name
+    pub name: String,
+
+    /// Traffic shape to generate
+    #[arg(short, long, value_enum, default_value = "cache")]
+    pub pattern: LoadPattern,
+
+    /// Target operations per second
+    #[arg(short, long, default_value = "1000")]
+    pub rate: u32,
+
+    /// How long to run (e.g., 30s, 5m)
+    #[arg(short, long, default_value = "30s", value_parser = humantime::parse_duration)]
+    pub duration: std::time::Duration,
+
+    /// Concurrent workers sharing the rate
+    #[arg(short, long, default_value = "4")]
+    pub workers: usize,
+
+    /// Number of distinct keys to spread traffic over
+    #[arg(long, default_value = "10000")]
+    pub keyspace: u64,
+}
+
+/// Workloads for `redis-up load`
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum LoadPattern {
+    /// Mostly GETs with TTL'd SETs and pipelined write bursts
+    Cache,
+    /// List pushes and pops, like a job queue
+    Queue,
+    /// Hash writes with expiry, reads and logouts
+    Session,
+}
+
+impl std::fmt::Display for LoadPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoadPattern::Cache => write!(f, "cache"),
+            LoadPattern::Queue => write!(f, "queue"),
+            LoadPattern::Session => write!(f, "session"),
+        }
+    }
+}
+
+/// Cluster node roles for `redis-up logs --role`
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum NodeRole {
+    Master,
+    Replica,
+}
+
+impl std::fmt::Display for NodeRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NodeRole::Master => write!(f, "master"),
+            NodeRole::Replica => write!(f, "replica"),
+        }
+    }
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ChaosAction {
+    /// Execute a chaos plan and report what happened
+    Run(ChaosRunArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct ChaosRunArgs {
+    /// Instance name
+    pub name: String,
+
+    /// YAML plan of timed events (kill, restart, pause, latency, failover)
+    #[arg(short, long)]
+    pub plan: std::path::PathBuf,
+
+    /// Print the timeline without injecting anything
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ScriptAction {
+    /// Load a script into the script cache and print its SHA1
+    Load(ScriptArgs),
+    /// Evaluate a script once
+    Run(ScriptArgs),
+    /// Evaluate a script now and again every time the file is saved
+    Watch(ScriptArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct ScriptArgs {
+    /// Instance name
+    pub name: String,
+
+    /// Lua script file
+    pub file: std::path::PathBuf,
+
+    /// Keys passed to the script as KEYS (comma-separated or repeated)
+    #[arg(long, value_delimiter = ',')]
+    pub keys: Vec<String>,
+
+    /// Arguments passed to the script as ARGV (comma-separated or repeated)
+    #[arg(long, value_delimiter = ',')]
+    pub args: Vec<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct SentinelInfoArgs {
+    #[command(flatten)]
+    pub info: InfoArgs,
+
+    /// Only show this monitored master
+    #[arg(long)]
+    pub master: Option<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct SentinelUrlArgs {
+    /// Sentinel setup name (uses auto-generated name if not provided)
+    pub name: Option<String>,
+
+    /// Monitored master to connect to (default: the first)
+    #[arg(long)]
+    pub master: Option<String>,
+
+    /// Only print config for this client (default: all)
+    #[arg(short, long, value_enum)]
+    pub client: Option<SentinelClient>,
+}
+
+#[derive(Args, Debug)]
+#[command(group(
+    ArgGroup::new("tunables")
+        .required(true)
+        .multiple(true)
+        .args(["quorum", "down_after_ms", "failover_timeout", "parallel_syncs"])
+))]
+pub struct SentinelTuneArgs {
+    /// Sentinel setup name (uses auto-generated name if not provided)
+    pub name: Option<String>,
+
+    /// Only tune this monitored master (default: all)
+    #[arg(long)]
+    pub master: Option<String>,
+
+    /// Sentinels that must agree a master is down
+    #[arg(long, value_name = "N")]
+    pub quorum: Option<usize>,
+
+    /// Milliseconds a master must be unreachable before it is considered down
+    #[arg(long, value_name = "MS")]
+    pub down_after_ms: Option<u64>,
+
+    /// Failover timeout in milliseconds
+    #[arg(long, value_name = "MS")]
+    pub failover_timeout: Option<u64>,
+
+    /// Replicas resynced with a new master at the same time after a failover
+    #[arg(long, value_name = "N")]
+    pub parallel_syncs: Option<u32>,
+}
+
+/// Sentinel-aware clients `sentinel url` has config for
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum SentinelClient {
+    /// Node.js ioredis
+    Ioredis,
+    /// Java Lettuce
+    Lettuce,
+    /// Go go-redis
+    GoRedis,
+    /// Python redis-py
+    RedisPy,
+}
+
+impl std::fmt::Display for SentinelClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SentinelClient::Ioredis => write!(f, "ioredis"),
+            SentinelClient::Lettuce => write!(f, "lettuce"),
+            SentinelClient::GoRedis => write!(f, "go-redis"),
+            SentinelClient::RedisPy => write!(f, "redis-py"),
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct UnlockArgs {
+    /// How long secrets stay unlocked
+    #[arg(long, default_value = "8h", value_parser = humantime::parse_duration)]
+    pub ttl: std::time::Duration,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ProfileAction {
+    /// List profiles and their flags
+    List,
+    /// Show one profile and the flags it adds
+    Show {
+        /// Profile name
+        name: String,
+    },
+    /// Save a profile to settings.yaml, replacing one with the same name
+    /// (the file is rewritten, so comments in it are not kept)
+    Add {
+        /// Profile name
+        name: String,
+        /// Start flags, e.g. --memory 256m --persist
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true, required = true)]
+        flags: Vec<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigAction {
+    /// Write the instance registry as JSON
+    Export(ConfigExportArgs),
+    /// Load a registry written by `config export`
+    Import(ConfigImportArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct ConfigExportArgs {
+    /// Write to this file instead of stdout
+    #[arg(short, long)]
+    pub output: Option<std::path::PathBuf>,
+
+    /// Encrypt passwords and connection URLs with a passphrase
+    #[arg(long, requires = "passphrase")]
+    pub encrypt_secrets: bool,
+
+    /// Passphrase for encrypted secrets
+    #[arg(long, env = "REDIS_UP_PASSPHRASE", hide_env_values = true)]
+    pub passphrase: Option<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct ConfigImportArgs {
+    /// File written by `config export`
+    pub file: std::path::PathBuf,
+
+    /// Add the imported instances to the current registry instead of replacing it
+    #[arg(long)]
+    pub merge: bool,
+
+    /// Replace a registry that still has instances
+    #[arg(short, long, conflicts_with = "merge")]
+    pub force: bool,
+
+    /// Passphrase for encrypted secrets
+    #[arg(long, env = "REDIS_UP_PASSPHRASE", hide_env_values = true)]
+    pub passphrase: Option<String>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum FunctionsAction {
+    /// Load (or replace) a library with FUNCTION LOAD and track it
+    Deploy(FunctionsDeployArgs),
+    /// List loaded libraries and their functions
+    List {
+        /// Instance name
+        name: String,
+    },
+    /// Delete a library and stop tracking it
+    Delete(FunctionsDeleteArgs),
+    /// Load every tracked library again, e.g. after a restart
+    Restore {
+        /// Instance name
+        name: String,
+    },
+}
+
+#[derive(Args, Debug)]
+pub struct FunctionsDeployArgs {
+    /// Instance name
+    pub name: String,
+
+    /// Library source file, starting with a shebang such as `#!lua name=mylib`
+    pub file: std::path::PathBuf,
+}
+
+#[derive(Args, Debug)]
+pub struct FunctionsDeleteArgs {
+    /// Instance name
+    pub name: String,
+
+    /// Library name
+    pub library: String,
+}
+
+#[derive(Args, Debug)]
+pub struct DiffArgs {
+    /// First instance name
+    pub left: String,
+
+    /// Second instance name
+    pub right: String,
+
+    /// Only compare keys matching this pattern
+    #[arg(short, long, default_value = "*")]
+    pub pattern: String,
+
+    /// Maximum number of differing keys to list
+    #[arg(long, default_value = "50")]
+    pub limit: usize,
+}
+
+#[derive(Args, Debug)]
+pub struct AnonymizeArgs {
+    /// Instance name
+    pub name: String,
+
+    /// YAML file of key patterns and the values to fake under them
+    #[arg(short, long)]
+    pub rules: std::path::PathBuf,
+
+    /// Seed for fake values, so repeated runs produce the same output
+    #[arg(long)]
+    pub seed: Option<u64>,
+}
+
+#[derive(Args, Debug)]
+pub struct SlowArgs {
+    /// Instance name
+    pub name: String,
+
+    /// Command to delay (repeatable, case-insensitive)
+    #[arg(short, long, required = true)]
+    pub command: Vec<String>,
+
+    /// Delay before each matching command reaches Redis, in milliseconds
+    #[arg(long, default_value = "100")]
+    pub ms: u64,
+
+    /// How long to run the proxy (default: until Ctrl-C)
+    #[arg(short, long, value_parser = humantime::parse_duration)]
+    pub duration: Option<std::time::Duration>,
+
+    /// Local port for the proxy (default: any free port)
+    #[arg(short, long, default_value = "0")]
+    pub port: u16,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ObservabilityAction {
+    /// Start redis_exporter, Prometheus and Grafana for managed instances
+    Start(ObservabilityStartArgs),
+    /// Stop and remove the observability bundle
+    Stop,
+}
+
+#[derive(Args, Debug)]
+pub struct ObservabilityStartArgs {
+    /// Instances to scrape, comma-separated (default: all managed instances)
+    #[arg(short, long, value_delimiter = ',')]
+    pub instances: Vec<String>,
+
+    /// Grafana port (default: 3000)
+    #[arg(long, default_value = "3000")]
+    pub grafana_port: u16,
+
+    /// Prometheus port (default: 9090)
+    #[arg(long, default_value = "9090")]
+    pub prometheus_port: u16,
+
+    #[command(flatten)]
+    pub pull: PullArgs,
+}
+
+#[derive(Args, Debug)]
+pub struct WatchArgs {
+    /// URL to POST a notification to when a container exits unexpectedly
+    #[arg(long, value_name = "URL")]
+    pub webhook: Option<String>,
+
+    /// Payload format for the webhook
+    #[arg(long, value_enum, default_value = "json")]
+    pub format: WebhookFormat,
+
+    /// Number of log lines to include in the notification
+    #[arg(short, long, default_value = "20")]
+    pub lines: usize,
+}
+
+#[derive(Args, Debug)]
+pub struct InitArgs {
+    /// Deployment file to write
+    #[arg(short, long, default_value = "redis-up.yaml")]
+    pub file: std::path::PathBuf,
+
+    /// Overwrite the file if it already exists
+    #[arg(long)]
+    pub force: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct CleanupArgs {
+    /// Skip confirmation prompt
+    #[arg(short, long)]
+    pub force: bool,
+
+    /// Only cleanup instances of specific type
+    #[arg(short, long)]
+    pub r#type: Option<String>,
+
+    /// Only cleanup these instances (repeatable)
+    #[arg(short, long)]
+    pub name: Vec<String>,
+
+    /// Only cleanup instances whose names match a glob, e.g. 'redis-basic-*'
+    #[arg(long)]
+    pub pattern: Option<String>,
+
+    /// Only cleanup instances created longer ago than this (e.g. 2d, 12h)
+    #[arg(long, value_parser = humantime::parse_duration)]
+    pub older_than: Option<std::time::Duration>,
+
+    /// Only cleanup instances whose containers no longer exist in Docker
+    #[arg(long)]
+    pub orphans_only: bool,
+
+    /// Only cleanup instances whose containers have all been exited for longer
+    /// than this (default 24h)
+    #[arg(long, value_name = "AGE", num_args = 0..=1, default_missing_value = "24h",
+          value_parser = humantime::parse_duration)]
+    pub stale: Option<std::time::Duration>,
+
+    /// Keep data volumes of every instance, not just those started with --persist
+    #[arg(long)]
+    pub keep_data: bool,
+
+    /// Seconds each container gets to exit before it is killed (Docker's default is 10)
+    #[arg(long)]
+    pub timeout: Option<u32>,
+
+    /// Clean up instances of every namespace, not just the current one
+    #[arg(long)]
+    pub all_namespaces: bool,
+
+    /// How many instances to tear down at once
+    #[arg(short, long, value_name = "N", default_value_t = 4)]
+    pub jobs: usize,
+}
+
+#[derive(Args, Debug)]
+pub struct RestoreStateArgs {
+    /// Only restore these instances (defaults to all in the snapshot)
+    pub names: Vec<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct ReviveArgs {
+    /// Only revive these instances (defaults to all)
+    pub names: Vec<String>,
+}
+
+#[derive(Args, Debug)]
+#[command(arg_required_else_help = true)]
+pub struct AnnotateArgs {
+    /// Instance name
+    pub name: String,
+
+    /// Append a dated note
+    #[arg(short, long)]
+    pub note: Option<String>,
+
+    /// Replace the description (an empty string removes it)
+    #[arg(short, long)]
+    pub description: Option<String>,
+
+    /// Remove all notes before adding any new one
+    #[arg(long)]
+    pub clear: bool,
+}
+
+/// What `examples` produces
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum ExampleFormat {
+    /// Example deployment files
+    Yaml,
+    /// JSON schema of deployment files, on stdout
+    Json,
+}
+
+/// How progress is reported during long startups
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+    /// Spinner on a terminal, plain lines otherwise
+    Text,
+    /// One JSON object per progress event
+    Json,
+}
+
+/// Webhook payload formats
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum WebhookFormat {
+    /// Structured JSON event
+    Json,
+    /// Slack incoming webhook message
+    Slack,
+}
+
+#[derive(Args, Debug)]
+pub struct StopArgs {
+    /// Instance name (uses auto-generated name if not provided)
+    pub name: Option<String>,
+
+    /// Keep data volumes so the next start with this name reattaches them (default with --persist)
+    #[arg(long)]
+    pub keep_data: bool,
+
+    /// Seconds each container gets to exit before it is killed (Docker's default is 10)
+    #[arg(long)]
+    pub timeout: Option<u32>,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct InfoArgs {
+    /// Instance name (uses auto-generated name if not provided)
+    pub name: Option<String>,
+
+    /// Output format
+    #[arg(short, long, default_value = "table")]
+    pub format: String,
+
+    /// Keep refreshing with live stats from the instance (default every 2s)
+    #[arg(
+        short,
+        long,
+        value_name = "INTERVAL",
+        num_args = 0..=1,
+        default_missing_value = "2s",
+        value_parser = humantime::parse_duration
+    )]
+    pub watch: Option<std::time::Duration>,
+}
+
+/// Redis persistence modes
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum PersistenceMode {
+    /// RDB snapshots only
+    Rdb,
+    /// Append-only file only
+    Aof,
+    /// RDB snapshots and append-only file
+    Both,
+    /// No persistence
+    None,
+}
+
+impl std::fmt::Display for PersistenceMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PersistenceMode::Rdb => write!(f, "rdb"),
+            PersistenceMode::Aof => write!(f, "aof"),
+            PersistenceMode::Both => write!(f, "both"),
+            PersistenceMode::None => write!(f, "none"),
+        }
+    }
+}
+
+/// Images `stack start` can run
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum StackFlavor {
+    /// redis/redis-stack with the Stack modules
+    Stack,
+    /// redis:8, which ships JSON, Search, TimeSeries and Bloom natively
+    Redis8,
+}
+
+impl std::fmt::Display for StackFlavor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StackFlavor::Stack => write!(f, "stack"),
+            StackFlavor::Redis8 => write!(f, "redis8"),
+        }
+    }
+}
+
+/// AOF fsync policies
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum AppendFsync {
+    Always,
+    Everysec,
+    No,
+}
+
+impl std::fmt::Display for AppendFsync {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppendFsync::Always => write!(f, "always"),
+            AppendFsync::Everysec => write!(f, "everysec"),
+            AppendFsync::No => write!(f, "no"),
+        }
+    }
+}
+
+/// Docker restart policies
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum RestartPolicy {
+    /// Restart unless stopped with `redis-up ... stop` or `docker stop`
+    UnlessStopped,
+    /// Restart only when the server exits with an error
+    OnFailure,
+    /// Never restart (Docker's default)
+    No,
+}
+
+impl std::fmt::Display for RestartPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RestartPolicy::UnlessStopped => write!(f, "unless-stopped"),
+            RestartPolicy::OnFailure => write!(f, "on-failure"),
+            RestartPolicy::No => write!(f, "no"),
+        }
+    }
+}
+
+/// How a server's container is networked
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Default)]
+pub enum NetworkMode {
+    /// A Docker network with published ports (the default)
+    #[default]
+    Bridge,
+    /// The host's network stack: servers listen on host ports directly (Linux only)
+    Host,
+}
+
+impl std::fmt::Display for NetworkMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NetworkMode::Bridge => write!(f, "bridge"),
+            NetworkMode::Host => write!(f, "host"),
+        }
+    }
+}
+
+/// Managed services whose constraints a cluster can emulate
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum Emulation {
+    /// AWS ElastiCache/MemoryDB: TLS-only clients, restricted admin commands
+    Elasticache,
+}
+
+impl std::fmt::Display for Emulation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Emulation::Elasticache => write!(f, "elasticache"),
+        }
+    }
 }
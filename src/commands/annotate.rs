@@ -0,0 +1,141 @@
+//! Free-form descriptions and notes on instances
+
+use anyhow::{Context, Result};
+use colored::*;
+use serde::{Deserialize, Serialize};
+
+use crate::cli::AnnotateArgs;
+use crate::config::{Config, InstanceInfo};
+
+/// A note kept in the `notes` metadata entry
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Note {
+    pub at: String,
+    pub text: String,
+}
+
+pub async fn handle_annotate(args: AnnotateArgs) -> Result<()> {
+    let mut config = Config::load()?;
+    let instance = config
+        .get_instance_mut(&args.name)
+        .with_context(|| format!("Instance '{}' not found", args.name))?;
+
+    if let Some(description) = args.description {
+        set_description(instance, Some(&description));
+    }
+    if args.clear {
+        instance.metadata.remove("notes");
+    }
+    if let Some(text) = args.note {
+        let mut notes = notes(instance);
+        notes.push(Note {
+            at: chrono::Utc::now().to_rfc3339(),
+            text,
+        });
+        instance
+            .metadata
+            .insert("notes".to_string(), serde_json::to_value(notes)?);
+    }
+
+    let lines = annotations(instance);
+    config.save()?;
+
+    println!("{} {}", "Annotated:".bold().cyan(), args.name.bold());
+    if lines.is_empty() {
+        println!("  No description or notes");
+    }
+    for (label, value) in lines {
+        println!("  {}: {}", label.bold(), value);
+    }
+    Ok(())
+}
+
+/// Record a description, or remove it when empty
+pub fn set_description(instance: &mut InstanceInfo, description: Option<&str>) {
+    match description.map(str::trim) {
+        Some(text) if !text.is_empty() => {
+            instance
+                .metadata
+                .insert("description".to_string(), serde_json::json!(text));
+        }
+        _ => {
+            instance.metadata.remove("description");
+        }
+    }
+}
+
+pub fn description(instance: &InstanceInfo) -> Option<&str> {
+    instance
+        .metadata
+        .get("description")
+        .and_then(|v| v.as_str())
+}
+
+pub fn notes(instance: &InstanceInfo) -> Vec<Note> {
+    instance
+        .metadata
+        .get("notes")
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+        .unwrap_or_default()
+}
+
+/// Label/value lines for info output, description first, then notes oldest first
+pub fn annotations(instance: &InstanceInfo) -> Vec<(&'static str, String)> {
+    let mut lines = Vec::new();
+    if let Some(description) = description(instance) {
+        lines.push(("Description", description.to_string()));
+    }
+    for note in notes(instance) {
+        // Notes only need the date to be useful
+        let date = note.at.get(..10).unwrap_or(&note.at);
+        lines.push(("Note", format!("{} {}", date, note.text)));
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ConnectionInfo, InstanceType};
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_annotations() {
+        let mut instance = InstanceInfo {
+            name: "checkout".to_string(),
+            instance_type: InstanceType::Basic,
+            created_at: "2024-01-01T00:00:00Z".to_string(),
+            ports: vec![6379],
+            containers: vec!["checkout".to_string()],
+            connection_info: ConnectionInfo {
+                host: "localhost".to_string(),
+                port: 6379,
+                password: None,
+                url: "redis://localhost:6379".to_string(),
+                additional_ports: HashMap::new(),
+                ipv6_host: None,
+                ipv6_url: None,
+            },
+            metadata: HashMap::new(),
+            started_at: None,
+            last_seen: None,
+        };
+        assert!(annotations(&instance).is_empty());
+
+        set_description(&mut instance, Some(" session cache for checkout "));
+        instance.metadata.insert(
+            "notes".to_string(),
+            serde_json::json!([{"at": "2024-03-05T10:00:00+00:00", "text": "flushed after load test"}]),
+        );
+        assert_eq!(
+            annotations(&instance),
+            vec![
+                ("Description", "session cache for checkout".to_string()),
+                ("Note", "2024-03-05 flushed after load test".to_string()),
+            ]
+        );
+
+        set_description(&mut instance, Some(""));
+        assert_eq!(description(&instance), None);
+    }
+}
@@ -0,0 +1,588 @@
+//! Rewrite sensitive values in an instance with fake data
+//!
+//! Rules are a YAML list of key patterns and what to replace under them:
+//!
+//! ```yaml
+//! rules:
+//!   - pattern: "user:*"
+//!     fields:
+//!       email: email
+//!       name: name
+//!   - pattern: "order:*"
+//!     json:
+//!       $.customer.phone: phone
+//!       $.items[*].note: text
+//!   - pattern: "session:*"
+//!     value: redact
+//! ```
+//!
+//! `fields` rewrites hash fields, `json` rewrites paths inside RedisJSON keys
+//! and strings holding JSON, and `value` replaces a whole string. Fake values
+//! are derived from the original, so the same email in two keys stays the same
+//! email after anonymizing. Pass `--seed` to get identical output across runs
+//! and redis-up builds.
+
+use anyhow::{Context, Result};
+use colored::*;
+use redis::aio::MultiplexedConnection;
+use ring::digest;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+
+use crate::cli::AnonymizeArgs;
+use crate::commands::client;
+use crate::config::Config;
+
+const FIRST_NAMES: &[&str] = &[
+    "Alex", "Sam", "Jordan", "Taylor", "Morgan", "Casey", "Riley", "Jamie", "Avery", "Quinn",
+    "Robin", "Drew", "Parker", "Rowan", "Sasha", "Kai", "Noor", "Ari", "Emery", "Reese", "Jules",
+    "Dana", "Micah", "Hayden", "Sky", "Remy", "Charlie", "Finley", "Lee", "Nico",
+];
+const LAST_NAMES: &[&str] = &[
+    "Smith",
+    "Garcia",
+    "Chen",
+    "Okafor",
+    "Novak",
+    "Silva",
+    "Kim",
+    "Larsen",
+    "Haddad",
+    "Moreau",
+    "Patel",
+    "Nguyen",
+    "Kowalski",
+    "Mensah",
+    "Rossi",
+    "Tanaka",
+    "Ivanova",
+    "Murphy",
+    "Costa",
+    "Schmidt",
+    "Ali",
+    "Andersen",
+    "Dubois",
+    "Reyes",
+    "Sato",
+    "Byrne",
+    "Horvat",
+    "Lindqvist",
+    "Adeyemi",
+    "Fischer",
+];
+const WORDS: &[&str] = &[
+    "lorem",
+    "ipsum",
+    "dolor",
+    "sit",
+    "amet",
+    "consectetur",
+    "adipiscing",
+    "elit",
+    "sed",
+    "do",
+    "eiusmod",
+    "tempor",
+    "incididunt",
+    "labore",
+    "magna",
+    "aliqua",
+];
+
+/// A rules file
+#[derive(Debug, Deserialize)]
+pub struct Rules {
+    pub rules: Vec<Rule>,
+}
+
+/// What to rewrite under one key pattern
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Rule {
+    pub pattern: String,
+    /// Hash field to fake value kind
+    #[serde(default)]
+    pub fields: BTreeMap<String, Fake>,
+    /// JSON path (e.g., `$.user.email`) to fake value kind
+    #[serde(default)]
+    pub json: BTreeMap<String, Fake>,
+    /// Replace the whole string value
+    pub value: Option<Fake>,
+}
+
+/// Kinds of fake data
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Fake {
+    Email,
+    Name,
+    Phone,
+    Uuid,
+    Text,
+    Number,
+    Redact,
+}
+
+/// One step of a JSON path
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Key(String),
+    Index(usize),
+    Each,
+}
+
+/// Counts for one rule
+#[derive(Debug, Default)]
+struct Summary {
+    matched: usize,
+    changed: usize,
+    values: usize,
+}
+
+pub async fn handle_anonymize(args: AnonymizeArgs, verbose: bool) -> Result<()> {
+    let rules = load_rules(&args.rules)?;
+    let paths = rules
+        .rules
+        .iter()
+        .map(|rule| {
+            rule.json
+                .iter()
+                .map(|(path, fake)| Ok((parse_path(path)?, *fake)))
+                .collect::<Result<Vec<_>>>()
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let config = Config::load()?;
+    let instance = config
+        .get_instance(&args.name)
+        .with_context(|| format!("Instance '{}' not found", args.name))?;
+
+    let faker = Faker {
+        salt: args.seed.unwrap_or_else(rand::random),
+    };
+    let mut connections = client::primaries(instance).await?;
+
+    println!(
+        "{} Anonymizing {} with {} rule(s)",
+        "Anonymize:".bold().cyan(),
+        args.name.bold(),
+        rules.rules.len()
+    );
+
+    let mut failed = 0;
+    for (rule, paths) in rules.rules.iter().zip(&paths) {
+        let mut summary = Summary::default();
+        for (endpoint, con) in connections.iter_mut() {
+            for key in scan(con, &rule.pattern)
+                .await
+                .with_context(|| format!("SCAN failed on {}", endpoint))?
+            {
+                summary.matched += 1;
+                match rewrite_key(con, &key, rule, paths, &faker).await {
+                    Ok(0) => {}
+                    Ok(values) => {
+                        summary.changed += 1;
+                        summary.values += values;
+                    }
+                    Err(e) => {
+                        failed += 1;
+                        eprintln!(
+                            "{} {}: {}",
+                            "Warning:".yellow(),
+                            String::from_utf8_lossy(&key),
+                            e
+                        );
+                    }
+                }
+                if verbose && summary.matched % 1000 == 0 {
+                    println!(
+                        "  {} key(s) scanned for '{}'",
+                        summary.matched, rule.pattern
+                    );
+                }
+            }
+        }
+        println!(
+            "  {} {} matched, {} changed, {} value(s) replaced",
+            rule.pattern.bold(),
+            summary.matched,
+            summary.changed,
+            summary.values
+        );
+    }
+
+    if failed > 0 {
+        anyhow::bail!("{} key(s) could not be anonymized", failed);
+    }
+
+    println!();
+    println!("{} Anonymized {}", "Success:".green(), args.name.bold());
+    Ok(())
+}
+
+fn load_rules(path: &Path) -> Result<Rules> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read anonymize rules: {}", path.display()))?;
+    let rules: Rules = serde_yaml::from_str(&content)
+        .with_context(|| format!("Failed to parse anonymize rules: {}", path.display()))?;
+    for rule in &rules.rules {
+        if rule.fields.is_empty() && rule.json.is_empty() && rule.value.is_none() {
+            anyhow::bail!(
+                "Rule '{}' needs at least one of fields, json or value",
+                rule.pattern
+            );
+        }
+    }
+    Ok(rules)
+}
+
+/// Collect every key matching a pattern on one node
+async fn scan(con: &mut MultiplexedConnection, pattern: &str) -> Result<Vec<Vec<u8>>> {
+    // SCAN can return a key more than once; collect first so each is rewritten once
+    let mut keys = HashSet::new();
+    let mut cursor: u64 = 0;
+    loop {
+        let (next, batch): (u64, Vec<Vec<u8>>) = redis::cmd("SCAN")
+            .arg(cursor)
+            .arg("MATCH")
+            .arg(pattern)
+            .arg("COUNT")
+            .arg(500)
+            .query_async(con)
+            .await?;
+        keys.extend(batch);
+        cursor = next;
+        if cursor == 0 {
+            break;
+        }
+    }
+    let mut keys: Vec<_> = keys.into_iter().collect();
+    keys.sort();
+    Ok(keys)
+}
+
+/// Apply a rule to one key, returning how many values were replaced
+async fn rewrite_key(
+    con: &mut MultiplexedConnection,
+    key: &[u8],
+    rule: &Rule,
+    paths: &[(Vec<Segment>, Fake)],
+    faker: &Faker,
+) -> Result<usize> {
+    let key_type: String = redis::cmd("TYPE").arg(key).query_async(con).await?;
+
+    match key_type.as_str() {
+        "hash" if !rule.fields.is_empty() => {
+            let mut replaced = Vec::new();
+            for (field, fake) in &rule.fields {
+                let value: Option<String> = redis::cmd("HGET")
+                    .arg(key)
+                    .arg(field)
+                    .query_async(con)
+                    .await?;
+                if let Some(value) = value {
+                    replaced.push((field, faker.fake(*fake, &value)));
+                }
+            }
+            if !replaced.is_empty() {
+                let mut cmd = redis::cmd("HSET");
+                cmd.arg(key);
+                for (field, value) in &replaced {
+                    cmd.arg(field).arg(value);
+                }
+                cmd.query_async::<()>(con).await?;
+            }
+            Ok(replaced.len())
+        }
+        "string" if rule.value.is_some() || !paths.is_empty() => {
+            let value: Option<String> = redis::cmd("GET").arg(key).query_async(con).await?;
+            let Some(value) = value else {
+                return Ok(0);
+            };
+            let (new, count) = match rule.value {
+                Some(fake) => (faker.fake(fake, &value), 1),
+                None => {
+                    // Strings that aren't JSON are left alone
+                    let Ok(mut doc) = serde_json::from_str::<Value>(&value) else {
+                        return Ok(0);
+                    };
+                    let count = rewrite_document(&mut doc, paths, faker);
+                    (doc.to_string(), count)
+                }
+            };
+            if count > 0 {
+                redis::cmd("SET")
+                    .arg(key)
+                    .arg(new)
+                    .arg("KEEPTTL")
+                    .query_async::<()>(con)
+                    .await?;
+            }
+            Ok(count)
+        }
+        "ReJSON-RL" if !paths.is_empty() => {
+            let value: Option<String> = redis::cmd("JSON.GET").arg(key).query_async(con).await?;
+            let Some(value) = value else {
+                return Ok(0);
+            };
+            let mut doc: Value = serde_json::from_str(&value)?;
+            let count = rewrite_document(&mut doc, paths, faker);
+            if count > 0 {
+                redis::cmd("JSON.SET")
+                    .arg(key)
+                    .arg("$")
+                    .arg(doc.to_string())
+                    .query_async::<()>(con)
+                    .await?;
+            }
+            Ok(count)
+        }
+        _ => Ok(0),
+    }
+}
+
+/// Parse a JSONPath subset: `$.a.b`, `$.items[0]`, `$.items[*].name`
+fn parse_path(path: &str) -> Result<Vec<Segment>> {
+    let rest = path
+        .strip_prefix('$')
+        .with_context(|| format!("JSON path '{}' must start with $", path))?;
+    let mut segments = Vec::new();
+    let mut chars = rest.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '.' => {
+                let mut key = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c == '.' || c == '[' {
+                        break;
+                    }
+                    key.push(c);
+                    chars.next();
+                }
+                if key.is_empty() {
+                    anyhow::bail!("Empty key in JSON path '{}'", path);
+                }
+                segments.push(Segment::Key(key));
+            }
+            '[' => {
+                let inner: String = chars.by_ref().take_while(|&c| c != ']').collect();
+                segments.push(match inner.as_str() {
+                    "*" => Segment::Each,
+                    index => Segment::Index(index.parse().with_context(|| {
+                        format!("Invalid index '{}' in JSON path '{}'", index, path)
+                    })?),
+                });
+            }
+            _ => anyhow::bail!("Unexpected '{}' in JSON path '{}'", c, path),
+        }
+    }
+    if segments.is_empty() {
+        anyhow::bail!("JSON path '{}' doesn't select anything", path);
+    }
+    Ok(segments)
+}
+
+fn rewrite_document(doc: &mut Value, paths: &[(Vec<Segment>, Fake)], faker: &Faker) -> usize {
+    paths
+        .iter()
+        .map(|(path, fake)| rewrite_path(doc, path, *fake, faker))
+        .sum()
+}
+
+/// Replace every value a path selects, returning how many were replaced
+fn rewrite_path(value: &mut Value, path: &[Segment], fake: Fake, faker: &Faker) -> usize {
+    let Some((segment, rest)) = path.split_first() else {
+        return match value {
+            Value::Null | Value::Object(_) | Value::Array(_) => 0,
+            Value::String(s) => {
+                *s = faker.fake(fake, s);
+                1
+            }
+            other => {
+                let original = other.to_string();
+                let new = faker.fake(fake, &original);
+                // Keep numbers numeric when the fake is too
+                *other = serde_json::from_str::<serde_json::Number>(&new)
+                    .map(Value::Number)
+                    .unwrap_or(Value::String(new));
+                1
+            }
+        };
+    };
+
+    match (segment, value) {
+        (Segment::Key(key), Value::Object(map)) => map
+            .get_mut(key)
+            .map_or(0, |child| rewrite_path(child, rest, fake, faker)),
+        (Segment::Index(index), Value::Array(items)) => items
+            .get_mut(*index)
+            .map_or(0, |child| rewrite_path(child, rest, fake, faker)),
+        (Segment::Each, Value::Array(items)) => items
+            .iter_mut()
+            .map(|child| rewrite_path(child, rest, fake, faker))
+            .sum(),
+        (Segment::Each, Value::Object(map)) => map
+            .values_mut()
+            .map(|child| rewrite_path(child, rest, fake, faker))
+            .sum(),
+        _ => 0,
+    }
+}
+
+/// Derives fake values from originals, so equal inputs give equal outputs
+///
+/// Values come from SHA-256 rather than `DefaultHasher` or `StdRng`, whose
+/// output may change between Rust or rand releases, so a `--seed` gives the
+/// same data from any build.
+struct Faker {
+    salt: u64,
+}
+
+impl Faker {
+    fn fake(&self, kind: Fake, original: &str) -> String {
+        let mut rng = Digits::new(self.salt, kind, original);
+
+        match kind {
+            Fake::Email => {
+                let (first, last) = (rng.pick(FIRST_NAMES), rng.pick(LAST_NAMES));
+                format!(
+                    "{}.{}{:08}@example.com",
+                    first.to_lowercase(),
+                    last.to_lowercase(),
+                    rng.below(100_000_000)
+                )
+            }
+            Fake::Name => format!(
+                "{} {}. {}",
+                rng.pick(FIRST_NAMES),
+                char::from(b'A' + rng.below(26) as u8),
+                rng.pick(LAST_NAMES)
+            ),
+            // 555-0100 through 555-0199 are reserved for fiction in every area code
+            Fake::Phone => format!("+1-{}-555-01{:02}", 200 + rng.below(800), rng.below(100)),
+            Fake::Uuid => {
+                let mut bytes = [0u8; 16];
+                bytes[..8].copy_from_slice(&rng.next().to_be_bytes());
+                bytes[8..].copy_from_slice(&rng.next().to_be_bytes());
+                bytes[6] = (bytes[6] & 0x0f) | 0x40;
+                bytes[8] = (bytes[8] & 0x3f) | 0x80;
+                let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+                format!(
+                    "{}-{}-{}-{}-{}",
+                    &hex[..8],
+                    &hex[8..12],
+                    &hex[12..16],
+                    &hex[16..20],
+                    &hex[20..]
+                )
+            }
+            Fake::Text => {
+                let words = original.split_whitespace().count().max(1);
+                (0..words)
+                    .map(|_| rng.pick(WORDS))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            }
+            Fake::Number => {
+                let digits = original.chars().filter(char::is_ascii_digit).count().max(1);
+                let mut number = (1 + rng.below(9)).to_string();
+                for _ in 1..digits {
+                    number.push(char::from(b'0' + rng.below(10) as u8));
+                }
+                number
+            }
+            Fake::Redact => "REDACTED".to_string(),
+        }
+    }
+}
+
+/// A stable stream of numbers for one original: SHA-256 of the salt, kind,
+/// value and a counter
+struct Digits {
+    seed: Vec<u8>,
+    counter: u64,
+}
+
+impl Digits {
+    fn new(salt: u64, kind: Fake, original: &str) -> Self {
+        let mut seed = salt.to_be_bytes().to_vec();
+        seed.extend(format!("{:?}", kind).as_bytes());
+        seed.push(0);
+        seed.extend(original.as_bytes());
+        Digits { seed, counter: 0 }
+    }
+
+    fn next(&mut self) -> u64 {
+        let mut context = digest::Context::new(&digest::SHA256);
+        context.update(&self.seed);
+        context.update(&self.counter.to_be_bytes());
+        self.counter += 1;
+        let hash = context.finish();
+        u64::from_be_bytes(hash.as_ref()[..8].try_into().unwrap())
+    }
+
+    /// A number in `0..n`
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+
+    fn pick(&mut self, list: &[&'static str]) -> &'static str {
+        list[self.below(list.len() as u64) as usize]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rewrite_path() {
+        let faker = Faker { salt: 7 };
+        let mut doc: Value = serde_json::from_str(
+            r#"{"customer": {"email": "a@b.com", "id": 42}, "items": [{"note": "hi there"}, {"note": "x"}, {}]}"#,
+        )
+        .unwrap();
+
+        let paths = vec![
+            (parse_path("$.customer.email").unwrap(), Fake::Email),
+            (parse_path("$.customer.id").unwrap(), Fake::Number),
+            (parse_path("$.items[*].note").unwrap(), Fake::Text),
+            (parse_path("$.missing").unwrap(), Fake::Name),
+        ];
+        assert_eq!(rewrite_document(&mut doc, &paths, &faker), 4);
+
+        let email = doc["customer"]["email"].as_str().unwrap();
+        assert!(email.ends_with("@example.com"));
+        assert_eq!(email, faker.fake(Fake::Email, "a@b.com"));
+        assert!(doc["customer"]["id"].is_u64());
+        assert_eq!(
+            doc["items"][0]["note"].as_str().unwrap().split(' ').count(),
+            2
+        );
+
+        assert_eq!(
+            parse_path("$.a[2]").unwrap(),
+            vec![Segment::Key("a".into()), Segment::Index(2)]
+        );
+        assert!(parse_path("a.b").is_err());
+    }
+
+    #[test]
+    fn test_seeded_output_is_pinned() {
+        // `--seed` promises the same data from any build, so these must never change
+        let faker = Faker { salt: 42 };
+        let original = "ada@example.org";
+        assert_eq!(
+            faker.fake(Fake::Email, original),
+            "reese.horvat27480856@example.com"
+        );
+        assert_eq!(faker.fake(Fake::Name, original), "Jamie C. Schmidt");
+        assert_eq!(faker.fake(Fake::Phone, original), "+1-627-555-0173");
+        assert_eq!(
+            faker.fake(Fake::Uuid, original),
+            "6e81c4ea-7ade-4430-99f7-04b30e8a0936"
+        );
+    }
+}
@@ -0,0 +1,138 @@
+//! Host memory checks before starting multi-node deployments
+//!
+//! A start estimates what its containers need (nodes × `--memory`, or a
+//! typical footprint per node when no limit is given) and compares that with
+//! the memory the host has available. Starts that would oversubscribe the host
+//! are refused unless `--force` is given; tight fits get a warning.
+
+use anyhow::{Context, Result};
+use colored::*;
+use sysinfo::System;
+
+use crate::commands::record::format_bytes;
+
+const MIB: u64 = 1024 * 1024;
+
+/// Typical footprint of an idle node, used when no memory limit is given
+pub const REDIS_NODE: u64 = 64 * MIB;
+pub const STACK_NODE: u64 = 256 * MIB;
+/// Redis Enterprise documents 2GB per node as the practical minimum
+pub const ENTERPRISE_NODE: u64 = 2048 * MIB;
+
+/// Warn when a start would take more than this share of available memory
+const WARN_PERCENT: u64 = 75;
+
+/// Refuse a start that needs more memory than the host has available
+///
+/// `limit` is the per-node `--memory` value; without one, `typical` bytes per
+/// node are assumed.
+pub fn check(
+    what: &str,
+    nodes: usize,
+    limit: Option<&str>,
+    typical: u64,
+    force: bool,
+) -> Result<()> {
+    let per_node = match limit {
+        Some(limit) => parse_size(limit)?,
+        None => typical,
+    };
+    let mut system = System::new();
+    system.refresh_memory();
+    let available = system.available_memory();
+    // Some platforms report nothing; don't block starts on a missing number
+    if available == 0 {
+        return Ok(());
+    }
+
+    let needed = per_node.saturating_mul(nodes as u64);
+    let estimate = format!(
+        "{} {} node(s) need about {} ({} each{}), {} is available",
+        nodes,
+        what,
+        format_bytes(needed as f64),
+        format_bytes(per_node as f64),
+        if limit.is_some() {
+            ""
+        } else {
+            ", no --memory limit"
+        },
+        format_bytes(available as f64)
+    );
+
+    if needed > available {
+        if !force {
+            anyhow::bail!(
+                "{}; start fewer nodes, set a smaller --memory, or pass --force",
+                estimate
+            );
+        }
+        println!("{} {} (continuing, --force)", "Warning:".yellow(), estimate);
+    } else if needed > available / 100 * WARN_PERCENT {
+        println!("{} {}", "Warning:".yellow(), estimate);
+    }
+    Ok(())
+}
+
+/// Parse a Docker-style memory size: bytes, or a number with a k, m, g, t or p
+/// suffix, optionally followed by `i` and/or `b` (`512m`, `1.5g`, `512mib`)
+pub fn parse_size(value: &str) -> Result<u64> {
+    let invalid = || format!("Invalid memory size '{}' (e.g., 256m, 1.5g)", value);
+    let lower = value.trim().to_ascii_lowercase();
+    let end = lower
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(lower.len());
+    let (number, suffix) = lower.split_at(end);
+    let suffix = suffix.strip_prefix(' ').unwrap_or(suffix);
+    let suffix = suffix.strip_suffix('b').unwrap_or(suffix);
+    let suffix = suffix.strip_suffix('i').unwrap_or(suffix);
+    let unit: u64 = match suffix {
+        "" => 1,
+        "k" => 1024,
+        "m" => MIB,
+        "g" => 1024 * MIB,
+        "t" => 1024 * 1024 * MIB,
+        "p" => 1024 * 1024 * 1024 * MIB,
+        _ => anyhow::bail!(invalid()),
+    };
+    let (whole, fraction) = number.split_once('.').unwrap_or((number, ""));
+    if whole.is_empty() || !fraction.chars().all(|c| c.is_ascii_digit()) {
+        anyhow::bail!(invalid());
+    }
+    let whole: u64 = whole.parse().with_context(invalid)?;
+    // Like Docker, a fraction of the unit is truncated to whole bytes
+    let fraction = format!("0.{}", fraction)
+        .parse::<f64>()
+        .with_context(invalid)?;
+    whole
+        .checked_mul(unit)
+        .and_then(|bytes| bytes.checked_add((fraction * unit as f64) as u64))
+        .with_context(|| format!("Memory size '{}' is too large", value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("512").unwrap(), 512);
+        assert_eq!(parse_size("256m").unwrap(), 256 * MIB);
+        assert_eq!(parse_size("1g").unwrap(), 1024 * MIB);
+        assert_eq!(parse_size("2GB").unwrap(), 2048 * MIB);
+        assert_eq!(parse_size("64k").unwrap(), 64 * 1024);
+        assert!(parse_size("lots").is_err());
+        assert_eq!(parse_size("1.5g").unwrap(), 1536 * MIB);
+        assert_eq!(parse_size("512mib").unwrap(), 512 * MIB);
+        assert_eq!(parse_size("2Ti").unwrap(), 2 * 1024 * 1024 * MIB);
+        assert_eq!(parse_size("1p").unwrap(), 1024 * 1024 * 1024 * MIB);
+        assert_eq!(parse_size("1 g").unwrap(), 1024 * MIB);
+        assert!(parse_size("1mg").is_err());
+        assert!(parse_size(".5g").is_err());
+        assert!(parse_size("1.2.3g").is_err());
+        assert!(parse_size("99999999p")
+            .unwrap_err()
+            .to_string()
+            .contains("too large"));
+    }
+}
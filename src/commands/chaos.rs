@@ -0,0 +1,491 @@
+//! Scripted fault scenarios for cluster and sentinel instances
+//!
+//! A plan is a YAML list of timed events:
+//!
+//! ```yaml
+//! events:
+//!   - at: 30s
+//!     action: kill
+//!     node: 2
+//!   - at: 45s
+//!     action: latency
+//!     node: 1
+//!     delay: 100ms
+//!     duration: 60s
+//!   - at: 2m
+//!     action: failover
+//! ```
+//!
+//! `node` is the number in the container name (`my-cluster-node-2`,
+//! `my-ha-master-1`). Timed faults are reverted when their duration ends, or
+//! when the run is interrupted.
+
+use anyhow::{Context, Result};
+use colored::*;
+use docker_wrapper::{
+    DockerCommand, KillCommand, PauseCommand, RunCommand, StartCommand, UnpauseCommand,
+};
+use serde::Deserialize;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::cli::{ChaosAction, ChaosRunArgs};
+use crate::commands::{images, sentinel, server};
+use crate::config::{Config, InstanceInfo, InstanceType};
+
+/// A chaos plan file
+#[derive(Debug, Deserialize)]
+pub struct ChaosPlan {
+    pub events: Vec<ChaosEvent>,
+}
+
+/// One event in a plan
+#[derive(Debug, Deserialize)]
+pub struct ChaosEvent {
+    /// Offset from the start of the run (e.g., 30s)
+    pub at: String,
+    #[serde(flatten)]
+    pub fault: Fault,
+}
+
+/// Faults a plan can inject
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(tag = "action", rename_all = "lowercase")]
+pub enum Fault {
+    /// SIGKILL a node
+    Kill { node: u32 },
+    /// Start a stopped or killed node
+    Restart { node: u32 },
+    /// Freeze a node for a while
+    Pause { node: u32, duration: String },
+    /// Delay a node's network traffic for a while
+    Latency {
+        node: u32,
+        delay: String,
+        duration: String,
+    },
+    /// Promote a replica (cluster) or ask the sentinels to fail a master over
+    Failover {
+        #[serde(default)]
+        node: Option<u32>,
+    },
+}
+
+/// A single action on the timeline, after timed faults are split into
+/// their start and revert
+#[derive(Debug, Clone, PartialEq)]
+enum Step {
+    Kill(u32),
+    Start(u32),
+    Pause(u32),
+    Unpause(u32),
+    AddLatency(u32, Duration),
+    ClearLatency(u32),
+    Failover(Option<u32>),
+}
+
+impl Step {
+    /// Steps that undo a fault, run early if the scenario is interrupted
+    fn is_revert(&self) -> bool {
+        matches!(self, Step::Unpause(_) | Step::ClearLatency(_))
+    }
+}
+
+impl std::fmt::Display for Step {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Step::Kill(n) => write!(f, "kill node {}", n),
+            Step::Start(n) => write!(f, "restart node {}", n),
+            Step::Pause(n) => write!(f, "pause node {}", n),
+            Step::Unpause(n) => write!(f, "unpause node {}", n),
+            Step::AddLatency(n, delay) => write!(
+                f,
+                "add {} latency on node {}",
+                humantime::format_duration(*delay),
+                n
+            ),
+            Step::ClearLatency(n) => write!(f, "clear latency on node {}", n),
+            Step::Failover(Some(n)) => write!(f, "failover via node {}", n),
+            Step::Failover(None) => write!(f, "failover"),
+        }
+    }
+}
+
+/// What happened when a step ran
+struct Outcome {
+    at: Duration,
+    step: Step,
+    result: Result<String>,
+}
+
+/// The instance a scenario runs against
+struct Target<'a> {
+    instance: &'a InstanceInfo,
+    /// Container name prefix for data nodes, e.g. `my-cluster-node-`
+    prefix: String,
+    /// Valid node numbers
+    nodes: Vec<u32>,
+}
+
+impl Target<'_> {
+    fn container(&self, node: u32) -> String {
+        format!("{}{}", self.prefix, node)
+    }
+
+    fn password(&self) -> Option<&str> {
+        self.instance.connection_info.password.as_deref()
+    }
+}
+
+pub async fn handle_action(action: ChaosAction, verbose: bool) -> Result<()> {
+    match action {
+        ChaosAction::Run(args) => run(args, verbose).await,
+    }
+}
+
+async fn run(args: ChaosRunArgs, verbose: bool) -> Result<()> {
+    let config = Config::load()?;
+    let instance = config
+        .get_instance(&args.name)
+        .with_context(|| format!("Instance '{}' not found", args.name))?;
+    let target = target(instance)?;
+
+    let plan = load_plan(&args.plan)?;
+    let steps = timeline(&plan)?;
+    for (_, step) in &steps {
+        let node = match step {
+            Step::Failover(node) => *node,
+            Step::Kill(n)
+            | Step::Start(n)
+            | Step::Pause(n)
+            | Step::Unpause(n)
+            | Step::AddLatency(n, _)
+            | Step::ClearLatency(n) => Some(*n),
+        };
+        if let Some(node) = node {
+            if !target.nodes.contains(&node) {
+                anyhow::bail!(
+                    "Plan refers to node {} but {} has nodes {}",
+                    node,
+                    args.name,
+                    target
+                        .nodes
+                        .iter()
+                        .map(|n| n.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                );
+            }
+        }
+    }
+
+    println!(
+        "{} {} against {} ({} steps)",
+        "Chaos:".bold().cyan(),
+        args.plan.display(),
+        args.name.bold(),
+        steps.len()
+    );
+    for (at, step) in &steps {
+        println!(
+            "  {:>8}  {}",
+            format!("t+{}", humantime::format_duration(*at)).dimmed(),
+            step
+        );
+    }
+    if args.dry_run {
+        return Ok(());
+    }
+    println!();
+
+    let started = Instant::now();
+    let mut outcomes = Vec::new();
+    let mut remaining = steps.into_iter().peekable();
+
+    while let Some((at, _)) = remaining.peek() {
+        let wait = at.saturating_sub(started.elapsed());
+        tokio::select! {
+            _ = tokio::time::sleep(wait) => {}
+            _ = tokio::signal::ctrl_c() => break,
+        }
+        if let Some((_, step)) = remaining.next() {
+            outcomes.push(execute(&target, step, started, verbose).await);
+        }
+    }
+
+    if remaining.peek().is_some() {
+        println!("{} Interrupted, reverting active faults", "Chaos:".yellow());
+        for (_, step) in remaining.filter(|(_, step)| step.is_revert()) {
+            outcomes.push(execute(&target, step, started, verbose).await);
+        }
+    }
+
+    report(&target, &outcomes).await;
+    Ok(())
+}
+
+fn target(instance: &InstanceInfo) -> Result<Target<'_>> {
+    let count = |key: &str, default: u64| {
+        instance
+            .metadata
+            .get(key)
+            .and_then(|v| v.as_u64())
+            .unwrap_or(default) as u32
+    };
+
+    match instance.instance_type {
+        InstanceType::Cluster => {
+            let nodes = count("masters", 3) * (count("replicas", 0) + 1);
+            Ok(Target {
+                instance,
+                prefix: format!("{}-node-", instance.name),
+                nodes: (0..nodes).collect(),
+            })
+        }
+        InstanceType::Sentinel => Ok(Target {
+            instance,
+            prefix: format!("{}-master-", instance.name),
+            nodes: (1..=count("masters", 1)).collect(),
+        }),
+        _ => anyhow::bail!(
+            "Chaos plans apply to cluster and sentinel instances, '{}' is {}",
+            instance.name,
+            instance.instance_type
+        ),
+    }
+}
+
+fn load_plan(path: &Path) -> Result<ChaosPlan> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read chaos plan: {}", path.display()))?;
+    serde_yaml::from_str(&content)
+        .with_context(|| format!("Failed to parse chaos plan: {}", path.display()))
+}
+
+/// Flatten a plan into steps ordered by when they run
+fn timeline(plan: &ChaosPlan) -> Result<Vec<(Duration, Step)>> {
+    let parse = |value: &str| {
+        humantime::parse_duration(value).with_context(|| format!("Invalid duration '{}'", value))
+    };
+
+    let mut steps = Vec::new();
+    for event in &plan.events {
+        let at = parse(&event.at)?;
+        match &event.fault {
+            Fault::Kill { node } => steps.push((at, Step::Kill(*node))),
+            Fault::Restart { node } => steps.push((at, Step::Start(*node))),
+            Fault::Pause { node, duration } => {
+                steps.push((at, Step::Pause(*node)));
+                steps.push((at + parse(duration)?, Step::Unpause(*node)));
+            }
+            Fault::Latency {
+                node,
+                delay,
+                duration,
+            } => {
+                steps.push((at, Step::AddLatency(*node, parse(delay)?)));
+                steps.push((at + parse(duration)?, Step::ClearLatency(*node)));
+            }
+            Fault::Failover { node } => steps.push((at, Step::Failover(*node))),
+        }
+    }
+
+    // Stable, so events at the same offset keep their plan order
+    steps.sort_by_key(|(at, _)| *at);
+    Ok(steps)
+}
+
+async fn execute(target: &Target<'_>, step: Step, started: Instant, verbose: bool) -> Outcome {
+    let at = started.elapsed();
+    let result = apply(target, &step).await;
+
+    let stamp = format!("t+{}", humantime::format_duration(round(at))).dimmed();
+    match result {
+        Ok(ref detail) => {
+            print!("  {} {} {}", stamp, "✓".green(), step);
+            if verbose && !detail.is_empty() {
+                print!(" ({})", detail);
+            }
+            println!();
+        }
+        Err(ref e) => println!("  {} {} {}: {}", stamp, "✗".red(), step, e),
+    }
+
+    Outcome { at, step, result }
+}
+
+async fn apply(target: &Target<'_>, step: &Step) -> Result<String> {
+    match step {
+        Step::Kill(n) => {
+            KillCommand::new(target.container(*n)).execute().await?;
+            Ok(String::new())
+        }
+        Step::Start(n) => {
+            StartCommand::new(target.container(*n)).execute().await?;
+            Ok(String::new())
+        }
+        Step::Pause(n) => {
+            PauseCommand::new(target.container(*n)).execute().await?;
+            Ok(String::new())
+        }
+        Step::Unpause(n) => {
+            UnpauseCommand::new(target.container(*n)).execute().await?;
+            Ok(String::new())
+        }
+        Step::AddLatency(n, delay) => {
+            let delay = format!("{}ms", delay.as_millis());
+            netem(
+                target,
+                *n,
+                &["replace", "dev", "eth0", "root", "netem", "delay", &delay],
+            )
+            .await
+        }
+        Step::ClearLatency(n) => netem(target, *n, &["del", "dev", "eth0", "root"]).await,
+        Step::Failover(node) => failover(target, *node).await,
+    }
+}
+
+/// Run `tc qdisc` in a helper container sharing the node's network namespace,
+/// since the Redis images ship without iproute2
+async fn netem(target: &Target<'_>, node: u32, args: &[&str]) -> Result<String> {
+    let mut command = vec!["tc".to_string(), "qdisc".to_string()];
+    command.extend(args.iter().map(|a| a.to_string()));
+
+    RunCommand::new(images::resolve(images::NETSHOOT_IMAGE))
+        .remove()
+        .network(format!("container:{}", target.container(node)))
+        .cap_add("NET_ADMIN")
+        .cmd(command)
+        .execute()
+        .await
+        .context("tc failed")?;
+    Ok(String::new())
+}
+
+async fn failover(target: &Target<'_>, node: Option<u32>) -> Result<String> {
+    if target.instance.instance_type == InstanceType::Sentinel {
+        let index = node.unwrap_or(1).saturating_sub(1) as usize;
+        let master = sentinel::monitored_names(target.instance)
+            .get(index)
+            .cloned()
+            .with_context(|| format!("No master {}", index + 1))?;
+        let sentinel = format!("{}-sentinel-1", target.instance.name);
+        let port = target
+            .instance
+            .connection_info
+            .additional_ports
+            .get("sentinel_base")
+            .copied()
+            .unwrap_or(26379)
+            .to_string();
+        server::redis_cli(
+            &sentinel,
+            None,
+            &["-p", &port, "SENTINEL", "FAILOVER", &master],
+        )
+        .await?;
+        return Ok(format!("{} via {}", master, sentinel));
+    }
+
+    // CLUSTER FAILOVER must be sent to the replica taking over
+    let replica = match node {
+        Some(node) => node,
+        None => {
+            let mut found = None;
+            for n in &target.nodes {
+                if role(target, *n).await.as_deref() == Some("slave") {
+                    found = Some(*n);
+                    break;
+                }
+            }
+            found.context("No replica available to fail over to")?
+        }
+    };
+    let container = target.container(replica);
+    server::redis_cli(&container, target.password(), &["CLUSTER", "FAILOVER"]).await?;
+    Ok(format!("promoted {}", container))
+}
+
+/// First line of ROLE, or None if the node is unreachable
+async fn role(target: &Target<'_>, node: u32) -> Option<String> {
+    server::redis_cli(&target.container(node), target.password(), &["ROLE"])
+        .await
+        .ok()
+        .and_then(|out| out.lines().next().map(|l| l.trim().to_string()))
+        .filter(|role| !role.is_empty())
+}
+
+async fn report(target: &Target<'_>, outcomes: &[Outcome]) {
+    let failed = outcomes.iter().filter(|o| o.result.is_err()).count();
+
+    println!();
+    println!("{}", "Report:".bold().underline());
+    for outcome in outcomes {
+        let status = match outcome.result {
+            Ok(_) => "ok".green(),
+            Err(_) => "failed".red(),
+        };
+        println!(
+            "  {:>8}  {:<32} {}",
+            format!("t+{}", humantime::format_duration(round(outcome.at))),
+            outcome.step.to_string(),
+            status
+        );
+    }
+    println!(
+        "  {} step(s) run, {} failed",
+        outcomes.len(),
+        if failed > 0 {
+            failed.to_string().red()
+        } else {
+            "0".green()
+        }
+    );
+
+    println!();
+    println!("{}", "Nodes now:".bold().underline());
+    for node in &target.nodes {
+        let role = match role(target, *node).await {
+            Some(role) if role == "slave" => "replica".normal(),
+            Some(role) => role.normal(),
+            None => "unreachable".red(),
+        };
+        println!("  {:<28} {}", target.container(*node), role);
+    }
+}
+
+/// Whole seconds are enough for the report
+fn round(d: Duration) -> Duration {
+    Duration::from_secs(d.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timeline() {
+        let plan: ChaosPlan = serde_yaml::from_str(
+            "events:\n\
+             - at: 30s\n  action: kill\n  node: 2\n\
+             - at: 10s\n  action: latency\n  node: 1\n  delay: 100ms\n  duration: 60s\n\
+             - at: 40s\n  action: failover\n",
+        )
+        .unwrap();
+
+        let steps = timeline(&plan).unwrap();
+        assert_eq!(
+            steps,
+            vec![
+                (
+                    Duration::from_secs(10),
+                    Step::AddLatency(1, Duration::from_millis(100))
+                ),
+                (Duration::from_secs(30), Step::Kill(2)),
+                (Duration::from_secs(40), Step::Failover(None)),
+                (Duration::from_secs(70), Step::ClearLatency(1)),
+            ]
+        );
+    }
+}
@@ -0,0 +1,201 @@
+//! CI helper: start an instance as a job step and hand its connection details on
+//!
+//! Instances started here are marked with `ci` metadata so `redis-up ci stop`
+//! can always find and remove them, even when earlier steps failed.
+
+use anyhow::{Context, Result};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::cli::{CiAction, CiOutput, CiStartArgs, CiStopArgs, CleanupArgs, EnvFormat};
+use crate::commands::{cleanup, client, env, restore};
+use crate::config::{Config, InstanceInfo, InstanceType};
+use crate::error::Failure;
+
+pub async fn handle_action(action: CiAction, verbose: bool) -> Result<()> {
+    // Job logs don't render ANSI colors reliably
+    colored::control::set_override(false);
+    match action {
+        CiAction::Start(args) => start(args, verbose).await,
+        CiAction::Stop(args) => stop(args, verbose).await,
+    }
+}
+
+async fn start(args: CiStartArgs, verbose: bool) -> Result<()> {
+    let name = args
+        .name
+        .clone()
+        .unwrap_or_else(|| format!("ci-{}", args.kind));
+
+    let mut argv = vec![
+        "redis-up".to_string(),
+        args.kind.to_string(),
+        "start".to_string(),
+        "--name".to_string(),
+        name.clone(),
+    ];
+    argv.extend(args.start_args.iter().cloned());
+    restore::run_start(&argv, verbose).await?;
+
+    let mut config = Config::load()?;
+    let instance = config
+        .get_instance_mut(&name)
+        .with_context(|| format!("Instance '{}' not found after start", name))?;
+    instance
+        .metadata
+        .insert("ci".to_string(), serde_json::Value::Bool(true));
+    let instance = instance.clone();
+    config.save()?;
+
+    if args.wait {
+        wait_ready(&instance, args.wait_timeout).await?;
+        println!("Ready: {}", name);
+    }
+
+    let vars = env::variables(&instance)?;
+    match args.output {
+        CiOutput::Github => github_output(&vars)?,
+        CiOutput::Env => match args.env_file {
+            Some(ref path) => append(path, &vars)?,
+            None => {
+                for (key, value) in &vars {
+                    println!("{}", env::format_line(EnvFormat::Docker, key, value));
+                }
+            }
+        },
+    }
+    Ok(())
+}
+
+/// Remove instances started with `ci start`; succeeds when there is nothing to stop
+async fn stop(args: CiStopArgs, verbose: bool) -> Result<()> {
+    let config = Config::load()?;
+    let names: Vec<String> = match args.name {
+        Some(name) => config
+            .get_instance(&name)
+            .map(|i| vec![i.name.clone()])
+            .unwrap_or_default(),
+        None => {
+            let mut names: Vec<String> = config
+                .list_instances()
+                .into_iter()
+                .filter(|i| i.metadata.get("ci").and_then(|v| v.as_bool()) == Some(true))
+                .map(|i| i.name.clone())
+                .collect();
+            names.sort();
+            names
+        }
+    };
+    if names.is_empty() {
+        println!("Info: No CI instances to stop");
+        return Ok(());
+    }
+
+    let cleanup_args = CleanupArgs {
+        force: true,
+        r#type: None,
+        name: names,
+        pattern: None,
+        older_than: None,
+        orphans_only: false,
+        stale: None,
+        keep_data: false,
+        timeout: args.timeout,
+        all_namespaces: false,
+        jobs: 4,
+    };
+    cleanup::handle_cleanup(cleanup_args, None, verbose).await
+}
+
+/// Wait until every node answers PING, and clusters report a healthy state
+#[tracing::instrument(name = "readiness_wait", skip_all, fields(name = %instance.name))]
+async fn wait_ready(instance: &InstanceInfo, timeout: Duration) -> Result<()> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        match check_ready(instance).await {
+            Ok(()) => return Ok(()),
+            Err(e) if Instant::now() >= deadline => {
+                return Err(e).context(Failure::Timeout(format!(
+                    "'{}' was not ready within {}",
+                    instance.name,
+                    humantime::format_duration(timeout)
+                )))
+            }
+            Err(_) => tokio::time::sleep(Duration::from_millis(500)).await,
+        }
+    }
+}
+
+async fn check_ready(instance: &InstanceInfo) -> Result<()> {
+    for endpoint in client::endpoints(instance) {
+        let mut con = client::connect(&endpoint).await?;
+        redis::cmd("PING")
+            .query_async::<String>(&mut con)
+            .await
+            .with_context(|| format!("PING failed on {}", endpoint))?;
+        if instance.instance_type == InstanceType::Cluster {
+            let info: String = redis::cmd("CLUSTER")
+                .arg("INFO")
+                .query_async(&mut con)
+                .await?;
+            if !info.contains("cluster_state:ok") {
+                anyhow::bail!("Cluster state is not ok on {}", endpoint);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Step outputs (`steps.<id>.outputs.url`, ...) plus job environment variables
+fn github_output(vars: &[(String, String)]) -> Result<()> {
+    let output = std::env::var("GITHUB_OUTPUT")
+        .context("GITHUB_OUTPUT is not set; use --output env outside GitHub Actions")?;
+    let job_env = std::env::var("GITHUB_ENV")
+        .context("GITHUB_ENV is not set; use --output env outside GitHub Actions")?;
+
+    if let Some((_, password)) = vars.iter().find(|(k, _)| k == "REDIS_PASSWORD") {
+        // Keeps the password out of the rest of the job log
+        println!("::add-mask::{}", password);
+    }
+
+    let outputs: Vec<(String, String)> = vars
+        .iter()
+        .map(|(key, value)| (output_name(key), value.clone()))
+        .collect();
+    append(Path::new(&output), &outputs)?;
+    append(Path::new(&job_env), vars)
+}
+
+/// `REDIS_CLUSTER_NODES` becomes the output `cluster-nodes`
+fn output_name(key: &str) -> String {
+    key.trim_start_matches("REDIS_")
+        .to_lowercase()
+        .replace('_', "-")
+}
+
+fn append(path: &Path, vars: &[(String, String)]) -> Result<()> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    for (key, value) in vars {
+        writeln!(file, "{}", env::format_line(EnvFormat::Docker, key, value))
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_output_name() {
+        assert_eq!(output_name("REDIS_URL"), "url");
+        assert_eq!(output_name("REDIS_CLUSTER_NODES"), "cluster-nodes");
+        assert_eq!(output_name("REDIS_SENTINEL_MASTER"), "sentinel-master");
+    }
+}
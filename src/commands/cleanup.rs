@@ -1,40 +1,61 @@
-//! Cleanup all Redis instances
+//! Cleanup all Redis instances, or those matching the selectors
 
-use anyhow::Result;
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
 use colored::*;
-use docker_wrapper::DockerCommand;
+use docker_wrapper::{DockerCommand, PsCommand};
 use std::io::{self, Write};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use tracing::Instrument;
 
-use crate::config::{Config, InstanceType};
-
-pub async fn handle_cleanup(force: bool, filter_type: Option<String>, verbose: bool) -> Result<()> {
-    let mut config = Config::load()?;
-
-    let instances = if let Some(type_filter) = &filter_type {
-        let instance_type = match type_filter.to_lowercase().as_str() {
-            "basic" => InstanceType::Basic,
-            "stack" => InstanceType::Stack,
-            "cluster" => InstanceType::Cluster,
-            "sentinel" => InstanceType::Sentinel,
-            "enterprise" => InstanceType::Enterprise,
-            _ => {
-                println!("{} Invalid type filter: {}. Valid types: basic, stack, cluster, sentinel, enterprise", 
-                    "Warning:".yellow(), type_filter.red());
-                return Ok(());
-            }
-        };
-        config.list_instances_by_type(&instance_type)
-    } else {
-        config.list_instances()
-    }.into_iter().cloned().collect::<Vec<_>>();
+use crate::cli::CleanupArgs;
+use crate::commands::{history, restore, shutdown, tls, uptime, volume};
+use crate::config::{self, Config, InstanceInfo, InstanceType};
+use crate::error::Failure;
+use crate::settings;
+
+pub async fn handle_cleanup(args: CleanupArgs, prefix: Option<&str>, verbose: bool) -> Result<()> {
+    if !args.all_namespaces {
+        return cleanup_namespace(&args, prefix, verbose).await;
+    }
+
+    let current = settings::namespace();
+    let mut result = Ok(());
+    for namespace in config::namespaces()? {
+        settings::set_namespace(&namespace);
+        println!("{} {}", "Namespace:".bold(), namespace.cyan());
+        result = cleanup_namespace(&args, prefix, verbose).await;
+        if result.is_err() {
+            break;
+        }
+        println!();
+    }
+    if let Some(current) = current {
+        settings::set_namespace(&current);
+    }
+    result
+}
+
+async fn cleanup_namespace(args: &CleanupArgs, prefix: Option<&str>, verbose: bool) -> Result<()> {
+    let config = Config::load()?;
+    let CleanupArgs {
+        force,
+        keep_data,
+        timeout,
+        jobs,
+        ..
+    } = *args;
+
+    let instances = select_instances(&config, args, prefix).await?;
 
     if instances.is_empty() {
-        let filter_msg = if let Some(ref t) = filter_type {
-            format!(" of type '{}'", t)
-        } else {
-            String::new()
-        };
-        println!("{} No Redis instances found{}", "Info:".blue(), filter_msg);
+        println!(
+            "{} No Redis instances found{}",
+            "Info:".blue(),
+            describe_filters(args, prefix)
+        );
         return Ok(());
     }
 
@@ -91,123 +112,435 @@ pub async fn handle_cleanup(force: bool, filter_type: Option<String>, verbose: b
         }
     }
 
+    // Lets `redis-up restore-state` undo this cleanup
+    if let Err(e) = restore::save_snapshot(&instances) {
+        println!(
+            "{} Could not save cleanup snapshot: {}",
+            "Warning:".yellow(),
+            e
+        );
+    }
+
     println!("{} Cleaning up instances...", "Cleaning:".bold().yellow());
     println!();
 
-    let mut cleaned_count = 0;
+    let mut cleaned = Vec::new();
     let mut error_count = 0;
+    let mut failed = Vec::new();
+    let mut panicked = 0;
 
+    // Instances are independent, so several are torn down at once
+    let permits = Arc::new(Semaphore::new(jobs.max(1)));
+    let mut set = JoinSet::new();
     for instance in instances {
+        let permits = permits.clone();
+        set.spawn(
+            async move {
+                let _permit = permits.acquire_owned().await;
+                teardown(instance, keep_data, timeout, verbose).await
+            }
+            .in_current_span(),
+        );
+    }
+
+    while let Some(joined) = set.join_next().await {
+        // A panicked task's instance stays registered; keep saving the others
+        let done = match joined {
+            Ok(done) => done,
+            Err(e) => {
+                println!("{} Cleanup task panicked: {}", "Error:".red(), e);
+                panicked += 1;
+                continue;
+            }
+        };
         if verbose {
             println!(
                 "{} Cleaning up: {}",
                 "Processing".cyan(),
-                instance.name.bold()
+                done.instance.name.bold()
             );
-        }
-
-        // Stop and remove all containers for this instance
-        for container in &instance.containers {
-            // Stop container
-            if let Err(e) = docker_wrapper::StopCommand::new(container).execute().await {
-                if verbose {
-                    println!(
-                        "  {} Failed to stop {}: {}",
-                        "Warning:".yellow(),
-                        container,
-                        e
-                    );
-                }
-                error_count += 1;
-                continue;
-            }
-
-            // Remove container
-            if let Err(e) = docker_wrapper::RmCommand::new(container)
-                .force()
-                .volumes()
-                .execute()
-                .await
-            {
-                if verbose {
-                    println!(
-                        "  {} Failed to remove {}: {}",
-                        "Warning:".yellow(),
-                        container,
-                        e
-                    );
-                }
-                error_count += 1;
-                continue;
-            }
-
-            if verbose {
-                println!(
-                    "  {} Removed container: {}",
-                    "Removed:".green(),
-                    container.dimmed()
-                );
+            for line in &done.log {
+                println!("{}", line);
             }
         }
 
-        // For cluster instances, also clean up networks
-        if instance.instance_type == InstanceType::Cluster {
-            let network_name = format!("{}-network", instance.name);
-            if let Err(e) = docker_wrapper::NetworkRmCommand::new(&network_name)
-                .execute()
-                .await
-            {
+        // Containers that survived are still tracked, so another cleanup can retry
+        if done.errors > 0 {
+            error_count += done.errors;
+            println!(
+                "{} Failed to clean up: {} ({} errors; kept in the config{})",
+                "Error:".red(),
+                done.instance.name.bold(),
+                done.errors,
                 if verbose {
-                    println!(
-                        "  {} Failed to remove network {}: {}",
-                        "Warning:".yellow(),
-                        network_name,
-                        e
-                    );
+                    ""
+                } else {
+                    ", run with --verbose for details"
                 }
-                // Don't count network removal failures as critical
-            } else if verbose {
-                println!(
-                    "  {} Removed network: {}",
-                    "Removed:".green(),
-                    network_name.dimmed()
-                );
-            }
+            );
+            failed.push(done.instance.name);
+            continue;
         }
 
-        // Remove from config
-        config.remove_instance(&instance.name);
-        cleaned_count += 1;
-
         println!(
             "{} Cleaned up: {}",
             "Success:".green(),
-            instance.name.bold().green()
+            done.instance.name.bold().green()
         );
+        if verbose {
+            volume::report_kept(&done.instance.name, done.keep_data);
+        }
+        cleaned.push(done.instance.name);
     }
 
-    // Save updated config
-    config.save()?;
+    // Teardown can take minutes; drop only what was cleaned from the current file
+    Config::update(|fresh| {
+        for name in &cleaned {
+            if let Some(removed) = fresh.remove_instance(name) {
+                history::record_stop(&removed);
+            }
+        }
+        Ok(!cleaned.is_empty())
+    })?;
+    let cleaned_count = cleaned.len();
 
     println!();
-    if error_count > 0 {
+    let not_cleaned = failed.len() + panicked;
+    if not_cleaned > 0 {
         println!(
             "{} Cleanup completed with {} errors. {} instances cleaned up.",
             "Warning:".yellow(),
-            error_count.to_string().red(),
-            cleaned_count.to_string().green()
-        );
-    } else {
-        println!(
-            "{} All {} instances cleaned up successfully!",
-            "Success:".bold().green(),
+            (error_count + panicked).to_string().red(),
             cleaned_count.to_string().green()
         );
+        return Err(Failure::Partial(format!(
+            "{} of {} instance(s) could not be cleaned up{}",
+            not_cleaned,
+            not_cleaned + cleaned_count,
+            if failed.is_empty() {
+                String::new()
+            } else {
+                format!(": {}", failed.join(", "))
+            }
+        ))
+        .into());
     }
+    println!(
+        "{} All {} instances cleaned up successfully!",
+        "Success:".bold().green(),
+        cleaned_count.to_string().green()
+    );
 
     Ok(())
 }
 
+/// What tearing down one instance did
+struct Teardown {
+    instance: InstanceInfo,
+    keep_data: bool,
+    /// Verbose progress, printed in one piece once the instance is done
+    log: Vec<String>,
+    errors: usize,
+}
+
+/// Stop and remove an instance's containers, network and certificates
+async fn teardown(
+    instance: InstanceInfo,
+    keep_data: bool,
+    timeout: Option<u32>,
+    verbose: bool,
+) -> Teardown {
+    let keep_data = volume::keep_data(&instance, keep_data);
+    let mut log = Vec::new();
+    let mut errors = 0;
+
+    // Stop in shutdown order, then remove the containers that stopped
+    let failed = shutdown::stop_containers(&instance, timeout, verbose).await;
+    for (container, e) in &failed {
+        log.push(format!(
+            "  {} Failed to stop {}: {}",
+            "Warning:".yellow(),
+            container,
+            e
+        ));
+        errors += 1;
+    }
+
+    for container in &instance.containers {
+        if failed.iter().any(|(stopped, _)| stopped == container) {
+            continue;
+        }
+
+        // Remove container
+        if let Err(e) = volume::remove_command(container, keep_data).execute().await {
+            log.push(format!(
+                "  {} Failed to remove {}: {}",
+                "Warning:".yellow(),
+                container,
+                e
+            ));
+            errors += 1;
+            continue;
+        }
+
+        log.push(format!(
+            "  {} Removed container: {}",
+            "Removed:".green(),
+            container.dimmed()
+        ));
+    }
+
+    // Clean up networks for clusters and instances that created their own
+    if instance.instance_type == InstanceType::Cluster || instance.metadata.contains_key("network")
+    {
+        crate::commands::insight::detach_shared(&instance).await;
+        let network_name = format!("{}-network", instance.name);
+        if let Err(e) = docker_wrapper::NetworkRmCommand::new(&network_name)
+            .execute()
+            .await
+        {
+            // Don't count network removal failures as critical
+            log.push(format!(
+                "  {} Failed to remove network {}: {}",
+                "Warning:".yellow(),
+                network_name,
+                e
+            ));
+        } else {
+            log.push(format!(
+                "  {} Removed network: {}",
+                "Removed:".green(),
+                network_name.dimmed()
+            ));
+        }
+    }
+
+    tls::remove(&instance.name).ok();
+
+    Teardown {
+        instance,
+        keep_data,
+        log,
+        errors,
+    }
+}
+
+/// Tear down one instance and drop it from the config, for a start that replaces it
+///
+/// Data volumes of a persisted instance stay, as on a stop.
+pub async fn remove_instance(instance: InstanceInfo, verbose: bool) -> Result<()> {
+    let done = teardown(instance, false, None, verbose).await;
+    if verbose {
+        for line in &done.log {
+            println!("{}", line);
+        }
+    }
+    if done.errors > 0 {
+        bail!(
+            "Failed to remove '{}' ({} errors); run with --verbose for details",
+            done.instance.name,
+            done.errors
+        );
+    }
+    Config::update(|config| {
+        let removed = config.remove_instance(&done.instance.name);
+        if let Some(ref removed) = removed {
+            history::record_stop(removed);
+        }
+        Ok(removed.is_some())
+    })?;
+    Ok(())
+}
+
+/// Instances to clean up: every selector given must match, and with `prefix` the name
+/// must start with `{prefix}-`
+pub async fn select_instances(
+    config: &Config,
+    args: &CleanupArgs,
+    prefix: Option<&str>,
+) -> Result<Vec<InstanceInfo>> {
+    for name in &args.name {
+        if config.get_instance(name).is_some() || args.all_namespaces {
+            continue;
+        }
+        match owning_namespace(name) {
+            Some(namespace) => anyhow::bail!(
+                "Instance '{}' belongs to namespace '{}'; use --namespace {} or --all-namespaces",
+                name,
+                namespace,
+                namespace
+            ),
+            None => anyhow::bail!("Instance '{}' not found", name),
+        }
+    }
+
+    let instances = match args.r#type.as_deref() {
+        Some(type_filter) => {
+            let instance_type = match type_filter.to_lowercase().as_str() {
+                "basic" => InstanceType::Basic,
+                "stack" => InstanceType::Stack,
+                "cluster" => InstanceType::Cluster,
+                "sentinel" => InstanceType::Sentinel,
+                "enterprise" => InstanceType::Enterprise,
+                _ => anyhow::bail!(
+                    "Invalid type filter: {}. Valid types: basic, stack, cluster, sentinel, enterprise",
+                    type_filter.red()
+                ),
+            };
+            config.list_instances_by_type(&instance_type)
+        }
+        None => config.list_instances(),
+    };
+    let now = Utc::now();
+    let mut selected: Vec<InstanceInfo> = instances
+        .into_iter()
+        .filter(|instance| prefix.is_none_or(|prefix| has_prefix(&instance.name, prefix)))
+        .filter(|instance| matches_selectors(instance, args, now))
+        .cloned()
+        .collect();
+
+    if args.orphans_only {
+        let existing = existing_containers().await?;
+        selected.retain(|instance| is_orphan(instance, &existing));
+    }
+    if let Some(stale) = args.stale {
+        let containers: Vec<String> = selected.iter().flat_map(|i| i.containers.clone()).collect();
+        let states = uptime::inspect(&containers).await?;
+        let stale = chrono::Duration::from_std(stale)?;
+        selected.retain(|instance| {
+            uptime::exited_at(instance, &states).is_some_and(|at| now - at >= stale)
+        });
+    }
+
+    Ok(selected)
+}
+
+/// Name, pattern and age selectors; an instance with an unreadable creation time is never
+/// considered old
+fn matches_selectors(instance: &InstanceInfo, args: &CleanupArgs, now: DateTime<Utc>) -> bool {
+    if !args.name.is_empty() && !args.name.contains(&instance.name) {
+        return false;
+    }
+    if let Some(pattern) = &args.pattern {
+        if !glob_match(pattern, &instance.name) {
+            return false;
+        }
+    }
+    if let Some(older_than) = args.older_than {
+        let created = DateTime::parse_from_rfc3339(&instance.created_at);
+        let old_enough = match (created, chrono::Duration::from_std(older_than)) {
+            (Ok(created), Ok(age)) => now - created.with_timezone(&Utc) >= age,
+            _ => false,
+        };
+        if !old_enough {
+            return false;
+        }
+    }
+    true
+}
+
+/// Another namespace that has an instance with this name
+fn owning_namespace(name: &str) -> Option<String> {
+    let current = settings::namespace();
+    config::namespaces()
+        .ok()?
+        .into_iter()
+        .filter(|namespace| current.as_ref() != Some(namespace))
+        .find(|namespace| {
+            Config::load_namespace(namespace).is_ok_and(|c| c.get_instance(name).is_some())
+        })
+}
+
+/// Match `*` (any run of characters) and `?` (one character)
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Position of the last `*` and the text position it was tried against
+    let mut star: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = star {
+            p = star_p + 1;
+            t = star_t + 1;
+            star = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// (full ID, name) of every container Docker knows about, running or not
+async fn existing_containers() -> Result<Vec<(String, String)>> {
+    let output = PsCommand::new()
+        .all()
+        .no_trunc()
+        .format_template("{{.ID}} {{.Names}}")
+        .execute()
+        .await?;
+    Ok(output
+        .stdout
+        .lines()
+        .filter_map(|line| line.split_once(' '))
+        .map(|(id, name)| (id.to_string(), name.trim().to_string()))
+        .collect())
+}
+
+/// An instance is orphaned when none of its containers exist any more; containers are
+/// recorded by name or by (possibly short) ID
+fn is_orphan(instance: &InstanceInfo, existing: &[(String, String)]) -> bool {
+    !instance.containers.iter().any(|container| {
+        existing
+            .iter()
+            .any(|(id, name)| name == container || id.starts_with(container.as_str()))
+    })
+}
+
+/// Describe the active selectors for the "nothing found" message
+fn describe_filters(args: &CleanupArgs, prefix: Option<&str>) -> String {
+    let mut parts = Vec::new();
+    if let Some(t) = &args.r#type {
+        parts.push(format!(" of type '{}'", t));
+    }
+    if let Some(prefix) = prefix {
+        parts.push(format!(" with prefix '{}'", prefix));
+    }
+    if !args.name.is_empty() {
+        parts.push(format!(" named {}", args.name.join(", ")));
+    }
+    if let Some(pattern) = &args.pattern {
+        parts.push(format!(" matching '{}'", pattern));
+    }
+    if let Some(older_than) = args.older_than {
+        parts.push(format!(
+            " older than {}",
+            humantime::format_duration(older_than)
+        ));
+    }
+    if args.orphans_only {
+        parts.push(" without containers".to_string());
+    }
+    if let Some(stale) = args.stale {
+        parts.push(format!(
+            " exited for over {}",
+            humantime::format_duration(stale)
+        ));
+    }
+    parts.concat()
+}
+
+fn has_prefix(name: &str, prefix: &str) -> bool {
+    name.strip_prefix(prefix)
+        .is_some_and(|rest| rest.starts_with('-'))
+}
+
 fn get_type_icon(instance_type: &InstanceType) -> &'static str {
     match instance_type {
         InstanceType::Basic => "[B]",
@@ -217,3 +550,61 @@ fn get_type_icon(instance_type: &InstanceType) -> &'static str {
         InstanceType::Enterprise => "[E]",
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[derive(Parser)]
+    struct Cli {
+        #[command(flatten)]
+        args: CleanupArgs,
+    }
+
+    fn instance(name: &str, created_at: &str) -> InstanceInfo {
+        serde_json::from_value(serde_json::json!({
+            "name": name,
+            "instance_type": "basic",
+            "created_at": created_at,
+            "ports": [6379],
+            "containers": [name],
+            "connection_info": {
+                "host": "localhost",
+                "port": 6379,
+                "password": null,
+                "url": "redis://localhost:6379",
+                "additional_ports": {}
+            },
+            "metadata": {}
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_selectors() {
+        let now = DateTime::parse_from_rfc3339("2024-03-10T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let old = instance("redis-basic-1", "2024-03-01T00:00:00Z");
+        let new = instance("cache", "2024-03-09T12:00:00Z");
+
+        let args = Cli::parse_from(["cleanup", "--pattern", "redis-basic-*"]).args;
+        assert!(matches_selectors(&old, &args, now));
+        assert!(!matches_selectors(&new, &args, now));
+
+        let args = Cli::parse_from(["cleanup", "--older-than", "2d"]).args;
+        assert!(matches_selectors(&old, &args, now));
+        assert!(!matches_selectors(&new, &args, now));
+
+        let args = Cli::parse_from(["cleanup", "--name", "cache"]).args;
+        assert!(!matches_selectors(&old, &args, now));
+        assert!(matches_selectors(&new, &args, now));
+
+        assert!(glob_match("redis-?asic-*", "redis-basic-12"));
+        assert!(!glob_match("redis-*-1", "redis-basic-12"));
+        let existing = vec![("4f2a9c".to_string(), "cache".to_string())];
+        assert!(is_orphan(&old, &existing));
+        assert!(!is_orphan(&new, &existing));
+    }
+}
@@ -0,0 +1,95 @@
+//! Direct Redis connections to managed instances
+
+use anyhow::{Context, Result};
+use redis::aio::MultiplexedConnection;
+
+use crate::config::{InstanceInfo, InstanceType};
+
+/// A host-side address of one redis-server belonging to an instance
+#[derive(Debug, Clone, PartialEq)]
+pub struct Endpoint {
+    pub host: String,
+    pub port: u16,
+    pub password: Option<String>,
+}
+
+impl Endpoint {
+    /// `redis://` URL, with the password as the default user's
+    pub fn url(&self) -> String {
+        let host = if self.host.contains(':') {
+            format!("[{}]", self.host)
+        } else {
+            self.host.clone()
+        };
+        match self.password {
+            Some(ref password) => format!("redis://default:{}@{}:{}", password, host, self.port),
+            None => format!("redis://{}:{}", host, self.port),
+        }
+    }
+}
+
+impl std::fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.host, self.port)
+    }
+}
+
+/// Redis endpoints for an instance
+///
+/// Clusters return every node so callers can scan the whole keyspace; other
+/// deployments return their primary connection address.
+pub fn endpoints(instance: &InstanceInfo) -> Vec<Endpoint> {
+    let info = &instance.connection_info;
+    let password = match instance.instance_type {
+        // The stored Enterprise password belongs to the admin user, not the database
+        InstanceType::Enterprise => None,
+        _ => info.password.clone(),
+    };
+
+    let ports = match instance.instance_type {
+        InstanceType::Cluster => instance.ports.clone(),
+        _ => vec![info.port],
+    };
+
+    ports
+        .into_iter()
+        .map(|port| Endpoint {
+            host: info.host.clone(),
+            port,
+            password: password.clone(),
+        })
+        .collect()
+}
+
+/// Open a connection to an endpoint
+pub async fn connect(endpoint: &Endpoint) -> Result<MultiplexedConnection> {
+    let client = redis::Client::open(endpoint.url())
+        .with_context(|| format!("Invalid Redis address {}", endpoint))?;
+    client
+        .get_multiplexed_async_connection()
+        .await
+        .with_context(|| format!("Failed to connect to Redis at {}", endpoint))
+}
+
+/// Connections to the endpoints that accept writes
+///
+/// Cluster replicas are skipped so commands that must run once per shard,
+/// such as FUNCTION LOAD, only reach masters.
+pub async fn primaries(instance: &InstanceInfo) -> Result<Vec<(Endpoint, MultiplexedConnection)>> {
+    let mut connections = Vec::new();
+    for endpoint in endpoints(instance) {
+        let mut con = connect(&endpoint).await?;
+        if instance.instance_type == InstanceType::Cluster {
+            let replication: String = redis::cmd("INFO")
+                .arg("replication")
+                .query_async(&mut con)
+                .await
+                .with_context(|| format!("INFO failed on {}", endpoint))?;
+            if !replication.contains("role:master") {
+                continue;
+            }
+        }
+        connections.push((endpoint, con));
+    }
+    Ok(connections)
+}
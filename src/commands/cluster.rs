@@ -2,28 +2,203 @@
 
 use anyhow::{Context, Result};
 use colored::*;
-use docker_wrapper::{DockerCommand, RedisClusterConnection, RedisClusterTemplate, Template};
+use docker_wrapper::{
+    DockerCommand, PsCommand, RedisClusterConnection, RedisClusterTemplate, Template,
+};
 use std::collections::HashMap;
+use std::time::{Duration, Instant};
 use tokio::process::Command as ProcessCommand;
 
-use crate::cli::{ClusterAction, ClusterStartArgs, InfoArgs, StopArgs};
-use crate::config::{generate_password, Config, ConnectionInfo, InstanceInfo, InstanceType};
+use crate::cli::{ClusterAction, ClusterStartArgs, InfoArgs, NetworkMode, StopArgs};
+use crate::commands::insight::{self, Insight};
+use crate::commands::plan::{Operation, Plan};
+use crate::commands::progress::{Progress, ProgressEvent};
+use crate::commands::retry::RetryPolicy;
+use crate::commands::{
+    annotate, capacity, emulate, formation, history, images, limits, live, locks, names, ports,
+    reuse, server, shutdown, tls, uptime, volume,
+};
+use crate::config::{
+    generate_password, Config, ConnectionInfo, InstanceInfo, InstanceType, PortPool,
+};
+use crate::error::Failure;
+
+/// How long a formed cluster gets to cover every slot
+const CLUSTER_OK_TIMEOUT: Duration = Duration::from_secs(30);
 
 pub async fn handle_action(action: ClusterAction, verbose: bool) -> Result<()> {
     match action {
-        ClusterAction::Start(args) => start_cluster(args, verbose).await,
+        ClusterAction::Start(args) => {
+            names::validate_opt(args.name.as_deref())?;
+            let _lock = locks::start(args.name.as_deref(), &InstanceType::Cluster).await?;
+            let reused = reuse::existing(
+                args.name.as_deref(),
+                reuse::Mode::new(args.reuse, args.recreate_on_drift, args.warn_on_drift),
+                InstanceType::Cluster,
+                |instance| reuse::cluster(&args, instance),
+                verbose,
+            )
+            .await?;
+            if reused {
+                return Ok(());
+            }
+            locks::ensure_not_running(args.name.as_deref()).await?;
+            let _formation = locks::formation().await?;
+            start_cluster(args, verbose).await
+        }
         ClusterAction::Stop(args) => stop_cluster(args, verbose).await,
-        ClusterAction::Info(args) => info_cluster(args, verbose).await,
+        ClusterAction::Info(args) => {
+            live::info(args, InstanceType::Cluster, verbose, info_cluster).await
+        }
     }
 }
 
-async fn start_cluster(args: ClusterStartArgs, verbose: bool) -> Result<()> {
-    let mut config = Config::load()?;
+/// A cluster start resolved down to the Docker commands it runs
+///
+/// Built once by [`prepare`], then run by `start_cluster` or printed by `--dry-run`.
+struct Launch {
+    name: String,
+    password: String,
+    total_nodes: usize,
+    host_network: bool,
+    image: &'static str,
+    platform: Option<String>,
+    formation: formation::Formation,
+    insight: Option<Insight>,
+}
+
+/// Validate a start and resolve its name, ports, password and commands
+///
+/// Nothing is created; `args` is updated with the resolved ports.
+async fn prepare(args: &mut ClusterStartArgs, config: &mut Config) -> Result<Launch> {
+    if args.ipv6 {
+        anyhow::bail!(
+            "--ipv6 is not supported for clusters: nodes advertise IPv4 addresses in CLUSTER SLOTS and MOVED redirections, so IPv6 clients would be sent back to IPv4. Use basic, stack or sentinel for IPv6 testing"
+        );
+    }
+    let directives =
+        server::persistence_directives(args.persistence, args.save.as_deref(), args.appendfsync)?;
+    if let Some(ref ip) = args.announce_ip {
+        formation::validate_announce_ip(ip)?;
+    }
+    capacity::check(
+        "cluster",
+        args.masters * (args.replicas + 1),
+        args.memory.as_deref(),
+        if args.stack {
+            capacity::STACK_NODE
+        } else {
+            capacity::REDIS_NODE
+        },
+        args.force,
+    )?;
+    let host_network = args.network_mode == NetworkMode::Host;
+    let image = if args.stack {
+        images::STACK_SERVER_IMAGE
+    } else {
+        images::REDIS_IMAGE
+    };
+    let mut required = vec![image];
+    if args.with_insight {
+        required.push(images::INSIGHT_IMAGE);
+    }
+    let platform = images::resolve_platform(&args.pull, "cluster", &required)?;
 
     // Generate name if not provided
-    let name = args
-        .name
-        .unwrap_or_else(|| config.generate_name(&InstanceType::Cluster));
+    let name = match args.name {
+        Some(ref name) => name.clone(),
+        None => config.reserve_name(&InstanceType::Cluster),
+    };
+    let total_nodes = args.masters + (args.masters * args.replicas);
+    args.port_base = ports::reserve(
+        config,
+        PortPool::Cluster,
+        args.port_base,
+        total_nodes,
+        "Cluster",
+    )?;
+
+    // Generate password if not provided
+    let password = args.password.clone().unwrap_or_else(generate_password);
+
+    let formation = formation::NodeSpec {
+        name: &name,
+        image,
+        masters: args.masters,
+        replicas: args.replicas,
+        port_base: args.port_base,
+        password: &password,
+        memory: args.memory.as_deref(),
+        persist: args.persist,
+        platform: platform.as_deref(),
+        directives: &directives,
+        emulate: args.emulate,
+        announce: args.announce_ip.as_deref().map(|ip| formation::Announce {
+            ip,
+            hostname: args.announce_hostname.as_deref(),
+        }),
+        host_network,
+        limits: &args.limits,
+    }
+    .formation()?;
+
+    // Insight joins the cluster network so it can follow MOVED redirects to every node
+    let insight = if args.with_insight {
+        Some(
+            Insight::resolve(
+                config,
+                &name,
+                args.insight_port,
+                Some(format!("{}-network", name)),
+                platform.as_deref(),
+            )
+            .await,
+        )
+    } else {
+        None
+    };
+
+    Ok(Launch {
+        name,
+        password,
+        total_nodes,
+        host_network,
+        image,
+        platform,
+        formation,
+        insight,
+    })
+}
+
+async fn start_cluster(mut args: ClusterStartArgs, verbose: bool) -> Result<()> {
+    let policy = RetryPolicy::resolve(args.retries)?;
+    let progress = Progress::new(args.output, verbose);
+    let mut config = Config::load()?;
+    let Launch {
+        name,
+        password,
+        total_nodes,
+        host_network,
+        image,
+        platform,
+        formation,
+        insight,
+    } = prepare(&mut args, &mut config).await?;
+    if host_network {
+        server::warn_host_network();
+    }
+    let mut required = vec![image];
+    if matches!(insight, Some(Insight::Own(_))) {
+        required.push(images::INSIGHT_IMAGE);
+    }
+    images::ensure_images(
+        &required,
+        &args.pull,
+        platform.as_deref(),
+        Some(&progress),
+        verbose,
+    )
+    .await?;
 
     if verbose {
         println!(
@@ -35,40 +210,22 @@ async fn start_cluster(args: ClusterStartArgs, verbose: bool) -> Result<()> {
             "  Masters: {}, Replicas: {}, Total nodes: {}",
             args.masters.to_string().green(),
             args.replicas.to_string().blue(),
-            (args.masters + (args.masters * args.replicas))
-                .to_string()
-                .yellow()
+            total_nodes.to_string().yellow()
         );
     }
 
-    // Generate password if not provided
-    let password = args.password.unwrap_or_else(generate_password);
+    if args.emulate.is_some() {
+        tls::generate(&name, &formation::node_hosts(&name, total_nodes)).await?;
+    }
 
-    // Create Redis Cluster template
-    let mut template = RedisClusterTemplate::new(&name)
+    // The template only knows the nodes' names, for removing them and for the
+    // connection URL
+    let template = RedisClusterTemplate::new(&name)
         .num_masters(args.masters)
         .num_replicas(args.replicas)
         .port_base(args.port_base)
         .password(&password);
 
-    if args.persist {
-        template = template.with_persistence(format!("{}-data", name));
-    }
-
-    if let Some(memory) = &args.memory {
-        template = template.memory_limit(memory);
-    }
-
-    if args.stack {
-        template = template.with_redis_stack();
-    }
-
-    if args.with_insight {
-        template = template
-            .with_redis_insight()
-            .redis_insight_port(args.insight_port);
-    }
-
     // Start the cluster
     if verbose {
         println!(
@@ -77,13 +234,28 @@ async fn start_cluster(args: ClusterStartArgs, verbose: bool) -> Result<()> {
         );
     }
 
-    let result = match template.start().await {
+    // A failed attempt can leave nodes and the cluster network behind
+    let started = policy.run(
+        "Cluster formation",
+        || formation.start(),
+        || async {
+            template.remove().await.ok();
+        },
+    );
+    let result = tokio::select! {
+        result = started => result,
+        _ = report_nodes(&name, total_nodes, &progress) => unreachable!(),
+    };
+    let result = match result {
+        Ok(result) => wait_for_cluster_ok(&name, &password).await.map(|()| result),
+        Err(e) => Err(e),
+    };
+    let result = match result {
         Ok(result) => result,
         Err(e) => {
             let error_msg = format!("{}", e);
 
             // Clean up any failed containers that might have been created
-            let total_nodes = args.masters + (args.masters * args.replicas);
             for i in 0..total_nodes {
                 let container_name = format!("{}-node-{}", name, i);
                 if let Err(cleanup_err) = docker_wrapper::RmCommand::new(&container_name)
@@ -120,40 +292,29 @@ async fn start_cluster(args: ClusterStartArgs, verbose: bool) -> Result<()> {
                 }
             }
 
-            // Rollback counter since we failed
-            config
-                .counters
-                .entry(InstanceType::Cluster.to_string())
-                .and_modify(|c| {
-                    if *c > 0 {
-                        *c -= 1;
-                    }
-                });
-            config.save()?;
+            if args.emulate.is_some() {
+                tls::remove(&name).ok();
+            }
 
             if error_msg.contains("is already in use by container")
                 || error_msg.contains("Conflict")
             {
-                return Err(anyhow::anyhow!(
+                return Err(Failure::NameConflict(format!(
                     "Failed to start Redis Cluster '{}': Container name already exists. Use --name to specify a different name or run 'redis-up cleanup' to clean up old instances.",
                     name
-                ));
+                )).into());
             } else if error_msg.contains("port is already allocated")
                 || error_msg.contains("bind")
                 || error_msg.contains("Bind for")
                 || error_msg.contains("failed to set up container networking")
                 || error_msg.contains("address already in use")
             {
-                return Err(anyhow::anyhow!(
+                return Err(Failure::PortConflict(format!(
                     "Failed to start Redis Cluster '{}': Port range starting at {} is already in use. Stop other Redis instances or use --port-base to specify a different starting port.",
                     name, args.port_base
-                ));
+                )).into());
             } else {
-                return Err(anyhow::anyhow!(
-                    "Failed to start Redis Cluster '{}': {}",
-                    name,
-                    e
-                ));
+                return Err(e.context(format!("Failed to start Redis Cluster '{}'", name)));
             }
         }
     };
@@ -162,18 +323,45 @@ async fn start_cluster(args: ClusterStartArgs, verbose: bool) -> Result<()> {
         println!("{} {}", "Success:".green(), result);
     }
 
-    // Get connection info
-    let connection = RedisClusterConnection::from_template(&template);
-
     // Build container list (node containers + optional insight)
     let mut containers = Vec::new();
-    let total_nodes = args.masters + (args.masters * args.replicas);
     for i in 0..total_nodes {
         containers.push(format!("{}-node-{}", name, i));
     }
-    if args.with_insight {
-        containers.push(format!("{}-insight", name));
+
+    // Get connection info
+    let connection = RedisClusterConnection::from_template(&template);
+    let cluster_url = if args.emulate.is_some() {
+        connection
+            .cluster_url()
+            .replacen("redis-cluster://", "rediss-cluster://", 1)
+    } else {
+        connection.cluster_url()
+    };
+
+    let mut insight_port = None;
+    match insight {
+        Some(Insight::Shared(port)) => insight_port = Some(port),
+        Some(Insight::Own(ref insight_config)) => {
+            args.insight_port = insight_config.port;
+            match insight::start_insight(insight_config, verbose).await {
+                Ok(_) => {
+                    insight_port = Some(args.insight_port);
+                    containers.push(format!("{}-insight", name));
+                }
+                Err(e) => println!(
+                    "{} RedisInsight failed to start: {}",
+                    "Warning:".yellow(),
+                    e
+                ),
+            }
+        }
+        None => {}
     }
+    let shared_insight = match insight {
+        Some(Insight::Shared(port)) => Some(port),
+        _ => None,
+    };
 
     // Build ports list
     let mut ports = Vec::new();
@@ -181,14 +369,20 @@ async fn start_cluster(args: ClusterStartArgs, verbose: bool) -> Result<()> {
         ports.push(args.port_base + i as u16);
     }
 
+    let client_bundle = if args.emulate.is_some() {
+        Some(tls::write_client_bundle(&name, &ports, true)?)
+    } else {
+        None
+    };
+
     // Build additional ports info
     let mut additional_ports = HashMap::new();
-    if args.with_insight {
-        additional_ports.insert("redisinsight".to_string(), args.insight_port);
+    if let Some(port) = insight_port {
+        additional_ports.insert("redisinsight".to_string(), port);
     }
 
     // Store instance info
-    let instance_info = InstanceInfo {
+    let mut instance_info = InstanceInfo {
         name: name.clone(),
         instance_type: InstanceType::Cluster,
         created_at: chrono::Utc::now().to_rfc3339(),
@@ -198,8 +392,10 @@ async fn start_cluster(args: ClusterStartArgs, verbose: bool) -> Result<()> {
             host: "localhost".to_string(),
             port: args.port_base, // Primary port
             password: Some(password.clone()),
-            url: connection.cluster_url(),
+            url: cluster_url.clone(),
             additional_ports,
+            ipv6_host: None,
+            ipv6_url: None,
         },
         metadata: {
             let mut map = HashMap::new();
@@ -221,20 +417,63 @@ async fn start_cluster(args: ClusterStartArgs, verbose: bool) -> Result<()> {
             );
             map.insert("persist".to_string(), serde_json::Value::Bool(args.persist));
             map.insert("stack".to_string(), serde_json::Value::Bool(args.stack));
+            if let Some(ref ip) = args.announce_ip {
+                map.insert("announce_ip".to_string(), serde_json::json!(ip));
+            }
+            if host_network {
+                map.insert(
+                    "network_mode".to_string(),
+                    serde_json::json!(args.network_mode.to_string()),
+                );
+            }
+            if let Some(ref hostname) = args.announce_hostname {
+                map.insert("announce_hostname".to_string(), serde_json::json!(hostname));
+            }
+            if let Some(mode) = args.emulate {
+                map.insert(
+                    "emulate".to_string(),
+                    serde_json::Value::String(mode.to_string()),
+                );
+                map.insert("tls".to_string(), serde_json::Value::Bool(true));
+                server::record_renames(&mut map, &emulate::renames(mode));
+            }
+            if let Some(ref bundle) = client_bundle {
+                map.insert(
+                    "tls_client_bundle".to_string(),
+                    serde_json::json!(bundle.display().to_string()),
+                );
+            }
+            limits::record_limits(&mut map, &args.limits);
+            server::record_persistence(
+                &mut map,
+                args.persistence,
+                args.save.as_deref(),
+                args.appendfsync,
+            );
             map.insert(
                 "insight".to_string(),
-                serde_json::Value::Bool(args.with_insight),
+                serde_json::Value::Bool(insight_port.is_some()),
             );
+            if shared_insight.is_some() {
+                map.insert("shared_insight".to_string(), serde_json::Value::Bool(true));
+            }
             if let Some(memory) = args.memory {
                 map.insert("memory".to_string(), serde_json::Value::String(memory));
             }
             map
         },
+        started_at: None,
+        last_seen: None,
     };
 
+    annotate::set_description(&mut instance_info, args.description.as_deref());
+    history::record_start(&instance_info);
     config.add_instance(instance_info);
     config.save()?;
 
+    progress.emit(ProgressEvent::Ready { name: name.clone() });
+    progress.finish();
+
     // Display connection info
     println!();
     println!("{} Redis Cluster started:", "Success:".bold().green());
@@ -253,21 +492,35 @@ async fn start_cluster(args: ClusterStartArgs, verbose: bool) -> Result<()> {
         (args.port_base + total_nodes as u16 - 1).to_string().cyan()
     );
     println!("  {}: {}", "Password".bold(), password.yellow());
-    println!(
-        "  {}: {}",
-        "Cluster URL".bold(),
-        connection.cluster_url().blue()
-    );
+    println!("  {}: {}", "Cluster URL".bold(), cluster_url.blue());
     println!(
         "  {}: {}",
         "Nodes".bold(),
         connection.nodes_string().purple()
     );
 
+    if host_network {
+        println!("  {}: {}", "Network".bold(), "host".cyan());
+    }
+    if let Some(ref ip) = args.announce_ip {
+        let host = args.announce_hostname.as_deref().unwrap_or(ip);
+        println!(
+            "  {}: {}:{}-{} in CLUSTER SLOTS and redirections",
+            "Announced".bold(),
+            host.cyan(),
+            args.port_base,
+            args.port_base + total_nodes as u16 - 1
+        );
+    }
+
     if args.persist {
         println!("  {}: {}-data-*", "Data Volumes".bold(), name.purple());
     }
 
+    if let Some(mode) = args.persistence {
+        println!("  {}: {}", "Persistence".bold(), mode.to_string().purple());
+    }
+
     if args.stack {
         println!(
             "  {}: Redis Stack (JSON, Search, Graph, TimeSeries, Bloom)",
@@ -275,13 +528,44 @@ async fn start_cluster(args: ClusterStartArgs, verbose: bool) -> Result<()> {
         );
     }
 
-    if args.with_insight {
+    if let Some(mode) = args.emulate {
         println!(
-            "  {}: http://localhost:{}",
-            "RedisInsight".bold(),
-            args.insight_port.to_string().magenta()
+            "  {}: {} (TLS only, disabled: {})",
+            "Emulating".bold(),
+            mode.to_string().yellow(),
+            emulate::disabled_commands(mode).join(", ").dimmed()
+        );
+        println!(
+            "  {}: {}",
+            "CA Certificate".bold(),
+            tls::dir(&name)?.join(tls::CA_CERT).display()
         );
     }
+    if let Some(ref bundle) = client_bundle {
+        println!(
+            "  {}: {} (mTLS: {})",
+            "Client Bundle".bold(),
+            bundle.display(),
+            bundle.join("redis-cli.sh").display().to_string().green()
+        );
+    }
+
+    if let Some(port) = insight_port {
+        let connections = vec![insight::create_redis_connection(
+            name.clone(),
+            format!("{}-node-0", name),
+            6379,
+            Some(password.clone()),
+            insight::ConnectionType::Cluster,
+        )];
+        if shared_insight.is_some() {
+            let network_name = format!("{}-network", name);
+            insight::register_with_shared(port, Some(&network_name), &[], connections, verbose)
+                .await;
+        } else {
+            insight::provision_connections(port, connections, verbose).await;
+        }
+    }
 
     // Connect to Redis cluster shell if requested (connect to first master node)
     if args.shell {
@@ -292,19 +576,20 @@ async fn start_cluster(args: ClusterStartArgs, verbose: bool) -> Result<()> {
         );
         println!();
 
-        let status = ProcessCommand::new("redis-cli")
-            .args([
-                "-h",
-                "localhost",
-                "-p",
-                &args.port_base.to_string(),
-                "-a",
-                &password,
-                "-c", // Enable cluster mode
-            ])
-            .status()
-            .await
-            .context("Failed to start redis-cli")?;
+        let mut shell = ProcessCommand::new("redis-cli");
+        shell.args([
+            "-h",
+            "localhost",
+            "-p",
+            &args.port_base.to_string(),
+            "-a",
+            &password,
+            "-c", // Enable cluster mode
+        ]);
+        if args.emulate.is_some() {
+            shell.args(emulate::client_tls_args(&name)?);
+        }
+        let status = shell.status().await.context("Failed to start redis-cli")?;
 
         if !status.success() {
             println!("{} redis-cli exited with error", "Warning:".yellow());
@@ -314,6 +599,72 @@ async fn start_cluster(args: ClusterStartArgs, verbose: bool) -> Result<()> {
     Ok(())
 }
 
+/// Operations `start_cluster` would perform
+pub async fn plan_start(args: &ClusterStartArgs, config: &mut Config) -> Result<Plan> {
+    let mut args = args.clone();
+    let launch = prepare(&mut args, config).await?;
+    let mut plan = Plan::new(format!("start cluster {}", launch.name));
+    plan.platform = launch.platform.clone();
+    plan.hide(&launch.password);
+
+    launch.formation.plan(&mut plan);
+    if let Some(ref insight) = launch.insight {
+        let network = format!("{}-network", launch.name);
+        insight.plan(&mut plan, Some(&network), &[]);
+    }
+    plan.push(Operation::SaveState {
+        description: format!("add {} to instances.json", launch.name),
+    });
+    Ok(plan)
+}
+
+/// Report node containers as they are created
+///
+/// Never completes; it is raced against cluster formation.
+async fn report_nodes(name: &str, total: usize, progress: &Progress) {
+    let mut reported = 0;
+    loop {
+        if reported < total {
+            let created = PsCommand::new()
+                .all()
+                .filter(format!("name={}-node-", name))
+                .quiet()
+                .execute()
+                .await
+                .map(|o| o.stdout.lines().filter(|l| !l.trim().is_empty()).count())
+                .unwrap_or(reported);
+
+            while reported < created.min(total) {
+                reported += 1;
+                progress.emit(ProgressEvent::CreatingNode {
+                    node: reported,
+                    total,
+                });
+            }
+            if reported == total {
+                progress.emit(ProgressEvent::WaitingForCluster);
+            }
+        }
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+}
+
+/// Wait until every slot is covered and the cluster reports `cluster_state:ok`
+#[tracing::instrument(name = "readiness_wait", skip(password))]
+async fn wait_for_cluster_ok(name: &str, password: &str) -> Result<()> {
+    let node = format!("{}-node-0", name);
+    let started = Instant::now();
+    while started.elapsed() < CLUSTER_OK_TIMEOUT {
+        if let Ok(info) = server::redis_cli(&node, Some(password), &["CLUSTER", "INFO"]).await {
+            if info.lines().any(|l| l.trim() == "cluster_state:ok") {
+                return Ok(());
+            }
+        }
+        tokio::time::sleep(formation::POLL_INTERVAL).await;
+    }
+    Err(Failure::Timeout("Cluster did not reach cluster_state:ok within 30s".to_string()).into())
+}
+
 async fn stop_cluster(args: StopArgs, verbose: bool) -> Result<()> {
     let mut config = Config::load()?;
 
@@ -344,6 +695,22 @@ async fn stop_cluster(args: StopArgs, verbose: bool) -> Result<()> {
         );
     }
 
+    // Replicas go down before masters so no node triggers a failover, RedisInsight last
+    if let Some((container, e)) = shutdown::stop_containers(instance, args.timeout, verbose)
+        .await
+        .into_iter()
+        .next()
+    {
+        return Err(e.context(format!("Failed to stop Redis Cluster node: {}", container)));
+    }
+
+    // RedisInsight is started outside the template, so remove it separately
+    let insight_name = format!("{}-insight", name);
+    if instance.containers.contains(&insight_name) {
+        insight::stop_insight(&name).await?;
+    }
+    insight::detach_shared(instance).await;
+
     // Create template to use its stop/remove methods
     let template = RedisClusterTemplate::new(&name); // Basic template for cleanup
 
@@ -353,13 +720,29 @@ async fn stop_cluster(args: StopArgs, verbose: bool) -> Result<()> {
         .await
         .with_context(|| format!("Failed to stop Redis Cluster: {}", name))?;
 
-    template
-        .remove()
-        .await
-        .with_context(|| format!("Failed to remove Redis Cluster: {}", name))?;
+    let keep_data = volume::keep_data(instance, args.keep_data);
+    if keep_data {
+        // The template always removes volumes, so take the nodes down one by one
+        for container in &instance.containers {
+            volume::remove_command(container, true).execute().await.ok();
+        }
+        docker_wrapper::NetworkRmCommand::new(format!("{}-network", name))
+            .execute()
+            .await
+            .ok();
+    } else {
+        template
+            .remove()
+            .await
+            .with_context(|| format!("Failed to remove Redis Cluster: {}", name))?;
+    }
+
+    tls::remove(&name)?;
 
     // Remove from config
-    config.remove_instance(&name);
+    if let Some(removed) = config.remove_instance(&name) {
+        history::record_stop(&removed);
+    }
     config.save()?;
 
     println!(
@@ -367,12 +750,23 @@ async fn stop_cluster(args: StopArgs, verbose: bool) -> Result<()> {
         "Success:".green(),
         name.bold()
     );
+    volume::report_kept(&name, keep_data);
 
     Ok(())
 }
 
+/// Address the nodes advertise, when announced
+fn announced(instance: &InstanceInfo) -> Option<String> {
+    let text = |key| instance.metadata.get(key).and_then(|v| v.as_str());
+    let ip = text("announce_ip")?;
+    Some(match text("announce_hostname") {
+        Some(hostname) => format!("{} (nodes use {})", hostname, ip),
+        None => ip.to_string(),
+    })
+}
+
 async fn info_cluster(args: InfoArgs, verbose: bool) -> Result<()> {
-    let config = Config::load()?;
+    let mut config = Config::load()?;
 
     // Get instance name
     let name = if let Some(name) = args.name {
@@ -386,6 +780,8 @@ async fn info_cluster(args: InfoArgs, verbose: bool) -> Result<()> {
         }
     };
 
+    let uptime_lines = uptime::info_lines(&mut config, &name).await;
+
     // Get instance info
     let instance = config.get_instance(&name).context("Instance not found")?;
 
@@ -406,6 +802,12 @@ async fn info_cluster(args: InfoArgs, verbose: bool) -> Result<()> {
             );
             println!("  {}: {}", "Type".bold(), "Redis Cluster".yellow());
             println!("  {}: {}", "Created".bold(), instance.created_at.dimmed());
+            for (label, value) in &uptime_lines {
+                println!("  {}: {}", label.bold(), value);
+            }
+            for (label, value) in annotate::annotations(instance) {
+                println!("  {}: {}", label.bold(), value);
+            }
 
             // Extract topology info from metadata
             let masters = instance
@@ -448,6 +850,10 @@ async fn info_cluster(args: InfoArgs, verbose: bool) -> Result<()> {
                 println!("  {}: {}", "Password".bold(), password.yellow());
             }
 
+            if let Some(announced) = announced(instance) {
+                println!("  {}: {}", "Announced".bold(), announced.cyan());
+            }
+
             println!(
                 "  {}: {}",
                 "Cluster URL".bold(),
@@ -479,6 +885,17 @@ async fn info_cluster(args: InfoArgs, verbose: bool) -> Result<()> {
                 }
             }
 
+            if let Some(mode) = instance
+                .metadata
+                .get("persistence")
+                .and_then(|v| v.as_str())
+            {
+                println!("  {}: {}", "Persistence".bold(), mode.purple());
+            }
+            for (label, value) in limits::limit_lines(&instance.metadata) {
+                println!("  {}: {}", label.bold(), value.purple());
+            }
+
             if instance
                 .metadata
                 .get("stack")
@@ -488,6 +905,22 @@ async fn info_cluster(args: InfoArgs, verbose: bool) -> Result<()> {
                 println!("  {}: Redis Stack enabled", "Modules".bold());
             }
 
+            if let Some(mode) = instance.metadata.get("emulate").and_then(|v| v.as_str()) {
+                println!("  {}: {} (TLS only)", "Emulating".bold(), mode.yellow());
+                println!(
+                    "  {}: {}",
+                    "CA Certificate".bold(),
+                    tls::dir(&name)?.join(tls::CA_CERT).display()
+                );
+            }
+            if let Some(bundle) = instance
+                .metadata
+                .get("tls_client_bundle")
+                .and_then(|v| v.as_str())
+            {
+                println!("  {}: {}", "Client Bundle".bold(), bundle);
+            }
+
             if verbose {
                 println!("  {}: {:?}", "All Metadata".bold(), instance.metadata);
             }
@@ -0,0 +1,191 @@
+//! Export and import the instance registry
+//!
+//! An export is the registry itself (`instances.json`), optionally with
+//! passwords and connection URLs encrypted, so it can be carried to another
+//! machine or kept around before resetting `~/.config`. Encrypted exports
+//! carry their own key, encrypted with the export's passphrase.
+
+use anyhow::{Context, Result};
+use colored::*;
+use std::fs;
+
+use crate::cli::{ConfigAction, ConfigExportArgs, ConfigImportArgs};
+use crate::config::Config;
+use crate::secrets::{self, Cipher};
+
+/// Field of an encrypted export holding its passphrase-encrypted key
+const KEY_FIELD: &str = "secrets_key";
+
+pub async fn handle_action(action: ConfigAction, verbose: bool) -> Result<()> {
+    match action {
+        ConfigAction::Export(args) => export(args, verbose),
+        ConfigAction::Import(args) => import(args, verbose),
+    }
+}
+
+fn export(args: ConfigExportArgs, verbose: bool) -> Result<()> {
+    let mut config = Config::load()?;
+
+    let mut key = None;
+    if args.encrypt_secrets {
+        let passphrase = args.passphrase.as_deref().unwrap_or_default();
+        let cipher = Cipher::generate();
+        for instance in config.instances.values_mut() {
+            secrets::encrypt_instance(instance, &cipher)?;
+        }
+        key = Some(cipher.wrap(passphrase)?);
+    }
+
+    let mut export = serde_json::to_value(&config).context("Failed to serialize config")?;
+    if let Some(key) = key {
+        export[KEY_FIELD] = key.into();
+    }
+
+    let content = serde_json::to_string_pretty(&export).context("Failed to serialize config")?;
+
+    match &args.output {
+        Some(path) => {
+            fs::write(path, content + "\n")
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            // Status goes to stderr so it never ends up in a redirected export
+            eprintln!(
+                "{} {} instances to {}",
+                "Exported:".green(),
+                config.instances.len(),
+                path.display()
+            );
+        }
+        None => println!("{}", content),
+    }
+
+    if verbose && !args.encrypt_secrets {
+        eprintln!(
+            "{} Passwords are in plain text; use --encrypt-secrets to protect them",
+            "Note:".dimmed()
+        );
+    }
+
+    Ok(())
+}
+
+fn import(args: ConfigImportArgs, verbose: bool) -> Result<()> {
+    let content = fs::read_to_string(&args.file)
+        .with_context(|| format!("Failed to read {}", args.file.display()))?;
+    let mut imported = Config::parse(&content)
+        .with_context(|| format!("{} is not a redis-up export", args.file.display()))?;
+
+    if imported.instances.values().any(secrets::has_encrypted) {
+        let passphrase = args.passphrase.as_deref().context(
+            "The export has encrypted secrets; pass --passphrase or set REDIS_UP_PASSPHRASE",
+        )?;
+        let export: serde_json::Value = serde_json::from_str(&content)?;
+        let wrapped = export[KEY_FIELD]
+            .as_str()
+            .context("The export has encrypted secrets but no key for them")?;
+        let cipher = Cipher::unwrap_with(wrapped, passphrase)?;
+        for instance in imported.instances.values_mut() {
+            secrets::decrypt_instance(instance, &cipher)?;
+        }
+    }
+
+    let mut config = Config::load()?;
+    let (added, skipped) = if args.merge {
+        merge(&mut config, imported)
+    } else {
+        if !config.instances.is_empty() && !args.force {
+            anyhow::bail!(
+                "The registry already has {} instances; use --merge to add to it or --force to replace it",
+                config.instances.len()
+            );
+        }
+        let added = imported.instances.keys().cloned().collect();
+        config = imported;
+        (added, Vec::new())
+    };
+    config.save()?;
+
+    println!(
+        "{} {} instances from {}",
+        "Imported:".green(),
+        added.len(),
+        args.file.display()
+    );
+    if verbose {
+        for name in &added {
+            println!("  {}", name);
+        }
+    }
+    for name in &skipped {
+        println!(
+            "{} Skipped '{}': an instance with that name already exists",
+            "Warning:".yellow(),
+            name
+        );
+    }
+
+    Ok(())
+}
+
+/// Add instances that don't exist yet, returning (added, skipped) names
+///
+/// Counters keep the higher value so generated names never repeat.
+fn merge(config: &mut Config, imported: Config) -> (Vec<String>, Vec<String>) {
+    let mut added = Vec::new();
+    let mut skipped = Vec::new();
+    for (name, instance) in imported.instances {
+        if config.instances.contains_key(&name) {
+            skipped.push(name);
+        } else {
+            config.add_instance(instance);
+            added.push(name);
+        }
+    }
+    for (kind, counter) in imported.counters {
+        let current = config.counters.entry(kind).or_insert(0);
+        *current = (*current).max(counter);
+    }
+    added.sort();
+    skipped.sort();
+    (added, skipped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(names: &[&str], counter: u32) -> Config {
+        let mut config = Config::default();
+        for name in names {
+            config.add_instance(
+                serde_json::from_value(serde_json::json!({
+                    "name": name,
+                    "instance_type": "basic",
+                    "created_at": "2024-01-01T00:00:00Z",
+                    "ports": [6379],
+                    "containers": [name],
+                    "connection_info": {
+                        "host": "localhost",
+                        "port": 6379,
+                        "password": "secret",
+                        "url": "redis://:secret@localhost:6379",
+                        "additional_ports": {}
+                    },
+                    "metadata": {}
+                }))
+                .unwrap(),
+            );
+        }
+        config.counters.insert("basic".to_string(), counter);
+        config
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut current = config(&["cache", "queue"], 5);
+        let (added, skipped) = merge(&mut current, config(&["queue", "search"], 3));
+        assert_eq!(added, vec!["search"]);
+        assert_eq!(skipped, vec!["queue"]);
+        assert_eq!(current.instances.len(), 3);
+        assert_eq!(current.counters["basic"], 5);
+    }
+}
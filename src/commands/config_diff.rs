@@ -0,0 +1,314 @@
+//! Compare a running instance's configuration with Redis and redis-up defaults
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use colored::*;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::cli::{AppendFsync, ConfigDiffArgs, PersistenceMode};
+use crate::commands::server;
+use crate::commands::{client, config_set};
+use crate::config::{Config, InstanceInfo, InstanceType};
+
+/// Redis 7 defaults for the parameters worth comparing, as CONFIG GET reports them
+///
+/// Parameters missing here have no meaningful default (paths, generated
+/// names) or are rarely touched, and are left out of the diff.
+const REDIS_DEFAULTS: &[(&str, &str)] = &[
+    ("activedefrag", "no"),
+    ("active-expire-effort", "1"),
+    ("aof-use-rdb-preamble", "yes"),
+    ("appendfilename", "appendonly.aof"),
+    ("appendfsync", "everysec"),
+    ("appendonly", "no"),
+    ("auto-aof-rewrite-min-size", "67108864"),
+    ("auto-aof-rewrite-percentage", "100"),
+    ("busy-reply-threshold", "5000"),
+    (
+        "client-output-buffer-limit",
+        "normal 0 0 0 slave 268435456 67108864 60 pubsub 33554432 8388608 60",
+    ),
+    ("cluster-allow-reads-when-down", "no"),
+    ("cluster-enabled", "no"),
+    ("cluster-node-timeout", "15000"),
+    ("cluster-require-full-coverage", "yes"),
+    ("databases", "16"),
+    ("dbfilename", "dump.rdb"),
+    ("dynamic-hz", "yes"),
+    ("hash-max-listpack-entries", "128"),
+    ("hash-max-listpack-value", "64"),
+    ("hz", "10"),
+    ("io-threads", "1"),
+    ("latency-monitor-threshold", "0"),
+    ("latency-tracking", "yes"),
+    ("lazyfree-lazy-eviction", "no"),
+    ("lazyfree-lazy-expire", "no"),
+    ("lazyfree-lazy-server-del", "no"),
+    ("lazyfree-lazy-user-del", "no"),
+    ("lazyfree-lazy-user-flush", "no"),
+    ("list-max-listpack-size", "-2"),
+    ("loglevel", "notice"),
+    ("maxclients", "10000"),
+    ("maxmemory", "0"),
+    ("maxmemory-clients", "0"),
+    ("maxmemory-policy", "noeviction"),
+    ("maxmemory-samples", "5"),
+    ("min-replicas-max-lag", "10"),
+    ("min-replicas-to-write", "0"),
+    ("no-appendfsync-on-rewrite", "no"),
+    ("notify-keyspace-events", ""),
+    ("port", "6379"),
+    ("protected-mode", "yes"),
+    ("rdbchecksum", "yes"),
+    ("rdbcompression", "yes"),
+    ("repl-backlog-size", "1048576"),
+    ("repl-diskless-sync", "yes"),
+    ("repl-diskless-sync-delay", "5"),
+    ("repl-timeout", "60"),
+    ("replica-lazy-flush", "no"),
+    ("replica-read-only", "yes"),
+    ("replica-serve-stale-data", "yes"),
+    ("save", "3600 1 300 100 60 10000"),
+    ("set-max-intset-entries", "512"),
+    ("slowlog-log-slower-than", "10000"),
+    ("slowlog-max-len", "128"),
+    ("stop-writes-on-bgsave-error", "yes"),
+    ("stream-node-max-bytes", "4096"),
+    ("stream-node-max-entries", "100"),
+    ("tcp-backlog", "511"),
+    ("tcp-keepalive", "300"),
+    ("timeout", "0"),
+    ("zset-max-listpack-entries", "128"),
+    ("zset-max-listpack-value", "64"),
+];
+
+/// Parameters never shown, since their values are secrets
+const HIDDEN: &[&str] = &["requirepass", "masterauth", "masteruser"];
+
+/// Why a parameter shows up in the diff
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Origin {
+    /// redis-up set a value at start and it has since changed
+    Changed,
+    /// Differs from the Redis default and redis-up didn't set it
+    NonDefault,
+    /// Set by redis-up at start and unchanged
+    RedisUp,
+}
+
+#[derive(Debug, PartialEq)]
+struct Difference {
+    param: String,
+    current: String,
+    baseline: String,
+    origin: Origin,
+}
+
+pub async fn handle_config_diff(args: ConfigDiffArgs, verbose: bool) -> Result<()> {
+    let config = Config::load()?;
+    let instance = config
+        .get_instance(&args.name)
+        .with_context(|| format!("Instance '{}' not found", args.name))?;
+    if instance.instance_type == InstanceType::Enterprise {
+        anyhow::bail!(
+            "Redis Enterprise databases don't expose CONFIG GET; use the admin UI or API"
+        );
+    }
+
+    let endpoints = client::endpoints(instance);
+    let node = args.node.unwrap_or(0);
+    let endpoint = endpoints.get(node).with_context(|| {
+        format!(
+            "'{}' has no node {} (it has {}, counting from 0)",
+            instance.name,
+            node,
+            endpoints.len()
+        )
+    })?;
+
+    let mut con = client::connect(endpoint).await?;
+    let current: HashMap<String, String> = redis::cmd(&server::command_name(instance, "CONFIG")?)
+        .arg("GET")
+        .arg("*")
+        .query_async(&mut con)
+        .await
+        .with_context(|| format!("CONFIG GET failed on {}", endpoint))?;
+
+    let differences = compare(&current, &expected(instance));
+
+    println!(
+        "{} {} ({})",
+        "Config diff:".bold().cyan(),
+        instance.name.bold(),
+        endpoint
+    );
+    let sections = [
+        (Origin::Changed, "Changed since start", "redis-up set"),
+        (Origin::NonDefault, "Differs from Redis defaults", "default"),
+        (Origin::RedisUp, "Set by redis-up", "default"),
+    ];
+    let mut shown = false;
+    for (origin, title, baseline_label) in sections {
+        let rows: Vec<&Difference> = differences.iter().filter(|d| d.origin == origin).collect();
+        if rows.is_empty() || (origin == Origin::RedisUp && !verbose) {
+            continue;
+        }
+        shown = true;
+        println!("\n  {}", title.bold());
+        let width = rows.iter().map(|d| d.param.len()).max().unwrap_or(0);
+        for d in rows {
+            let current = quoted(&d.current);
+            let current = if origin == Origin::RedisUp {
+                current.normal()
+            } else {
+                current.yellow()
+            };
+            println!(
+                "    {:<width$}  {}  {}",
+                d.param,
+                current,
+                format!("({}: {})", baseline_label, quoted(&d.baseline)).dimmed(),
+                width = width
+            );
+        }
+    }
+
+    if !shown {
+        println!(
+            "  {} matches Redis and redis-up defaults",
+            "No differences:".green()
+        );
+    }
+    if !verbose {
+        let set_by_us = differences
+            .iter()
+            .filter(|d| d.origin == Origin::RedisUp)
+            .count();
+        if set_by_us > 0 {
+            println!(
+                "\n  {}",
+                format!(
+                    "{} more set by redis-up at start; use --verbose to list them",
+                    set_by_us
+                )
+                .dimmed()
+            );
+        }
+    }
+
+    Ok(())
+}
+
+fn quoted(value: &str) -> String {
+    if value.is_empty() {
+        "\"\"".to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+/// Values redis-up passed to redis-server at start, plus tracked config-set overrides
+fn expected(instance: &InstanceInfo) -> HashMap<String, String> {
+    let meta = &instance.metadata;
+    let text = |key: &str| meta.get(key).and_then(|v| v.as_str());
+    let mut values = HashMap::new();
+
+    if instance.instance_type == InstanceType::Cluster {
+        // Set by the cluster template on every node
+        for (key, value) in [
+            ("cluster-enabled", "yes"),
+            ("cluster-config-file", "nodes.conf"),
+            ("cluster-node-timeout", "5000"),
+            ("appendonly", "yes"),
+        ] {
+            values.insert(key.to_string(), value.to_string());
+        }
+    }
+
+    let mode = text("persistence").and_then(|m| PersistenceMode::from_str(m, true).ok());
+    let appendfsync = text("appendfsync").and_then(|m| AppendFsync::from_str(m, true).ok());
+    if let Ok(directives) = server::persistence_directives(mode, text("save"), appendfsync) {
+        values.extend(directives);
+    }
+    let databases = meta
+        .get("databases")
+        .and_then(|v| v.as_u64())
+        .map(|n| n as u32);
+    values.extend(server::databases_directive(databases));
+    values.extend(config_set::tracked(instance));
+
+    values
+}
+
+/// Parameters whose value differs from what redis-up set or, failing that, the Redis default
+fn compare(
+    current: &HashMap<String, String>,
+    expected: &HashMap<String, String>,
+) -> Vec<Difference> {
+    let defaults: HashMap<&str, &str> = REDIS_DEFAULTS.iter().copied().collect();
+    let mut differences = BTreeMap::new();
+
+    for (param, value) in current {
+        if HIDDEN.contains(&param.as_str()) {
+            continue;
+        }
+        let default = defaults.get(param.as_str()).copied();
+        let (baseline, origin) = match (expected.get(param), default) {
+            (Some(set), _) if set != value => (set.as_str(), Origin::Changed),
+            (Some(set), Some(default)) if set != default => (default, Origin::RedisUp),
+            (Some(_), _) => continue,
+            (None, Some(default)) if default != value => (default, Origin::NonDefault),
+            (None, _) => continue,
+        };
+        differences.insert(
+            param.clone(),
+            Difference {
+                param: param.clone(),
+                current: value.clone(),
+                baseline: baseline.to_string(),
+                origin,
+            },
+        );
+    }
+
+    differences.into_values().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare() {
+        let current: HashMap<String, String> = [
+            ("appendonly", "yes"),
+            ("appendfsync", "always"),
+            ("maxmemory-policy", "allkeys-lru"),
+            ("hz", "10"),
+            ("requirepass", "secret"),
+            ("dir", "/data"),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+        let expected: HashMap<String, String> =
+            [("appendonly", "yes"), ("appendfsync", "everysec")]
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect();
+
+        let differences = compare(&current, &expected);
+        let found: Vec<(&str, Origin)> = differences
+            .iter()
+            .map(|d| (d.param.as_str(), d.origin))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("appendfsync", Origin::Changed),
+                ("appendonly", Origin::RedisUp),
+                ("maxmemory-policy", Origin::NonDefault),
+            ]
+        );
+    }
+}
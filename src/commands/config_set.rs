@@ -0,0 +1,206 @@
+//! Runtime CONFIG SET with optional tracking
+//!
+//! Overrides applied with `--persist` are kept in the `config_overrides`
+//! metadata entry so they can be applied again once a container restart or
+//! `restore-state` has reset the server to its boot configuration.
+
+use anyhow::{Context, Result};
+use colored::*;
+use std::collections::BTreeMap;
+
+use crate::cli::ConfigSetArgs;
+use crate::commands::client::{self, Endpoint};
+use crate::commands::server;
+use crate::config::{Config, InstanceInfo, InstanceType};
+
+pub async fn handle_config_set(args: ConfigSetArgs, verbose: bool) -> Result<()> {
+    let mut config = Config::load()?;
+    let instance = config
+        .get_instance(&args.name)
+        .cloned()
+        .with_context(|| format!("Instance '{}' not found", args.name))?;
+    if instance.instance_type == InstanceType::Enterprise {
+        anyhow::bail!(
+            "Redis Enterprise databases don't support CONFIG SET; use the admin UI or API"
+        );
+    }
+
+    if args.reapply {
+        let overrides = tracked(&instance);
+        if overrides.is_empty() {
+            println!("{} No overrides tracked for {}", "Info:".blue(), args.name);
+            return Ok(());
+        }
+        apply(&instance, &overrides, verbose).await?;
+        println!(
+            "{} Re-applied {} override(s) on {}",
+            "Success:".green(),
+            overrides.len(),
+            args.name.bold()
+        );
+        return Ok(());
+    }
+
+    // clap requires a key without --reapply, and a value without --forget too
+    let key = args
+        .key
+        .context("A parameter name is required")?
+        .to_lowercase();
+
+    if args.forget {
+        let mut overrides = tracked(&instance);
+        if overrides.remove(&key).is_none() {
+            println!(
+                "{} '{}' is not tracked for {}",
+                "Info:".blue(),
+                key,
+                args.name
+            );
+            return Ok(());
+        }
+        save_tracked(&mut config, &args.name, &overrides)?;
+        println!(
+            "{} Stopped tracking '{}' on {}; the running value is unchanged",
+            "Success:".green(),
+            key,
+            args.name.bold()
+        );
+        return Ok(());
+    }
+
+    let value = args.value.context("A value is required")?;
+    let change = BTreeMap::from([(key.clone(), value.clone())]);
+    apply(&instance, &change, verbose).await?;
+
+    if args.persist {
+        let mut overrides = tracked(&instance);
+        overrides.insert(key.clone(), value.clone());
+        save_tracked(&mut config, &args.name, &overrides)?;
+    }
+
+    println!(
+        "{} {} = {} on {}{}",
+        "Success:".green(),
+        key.bold(),
+        value,
+        args.name.bold(),
+        if args.persist {
+            " (tracked, re-applied with --reapply and restore-state)"
+        } else {
+            ""
+        }
+    );
+    Ok(())
+}
+
+/// Overrides recorded for an instance
+pub fn tracked(instance: &InstanceInfo) -> BTreeMap<String, String> {
+    instance
+        .metadata
+        .get("config_overrides")
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+        .unwrap_or_default()
+}
+
+fn save_tracked(
+    config: &mut Config,
+    name: &str,
+    overrides: &BTreeMap<String, String>,
+) -> Result<()> {
+    let instance = config
+        .get_instance_mut(name)
+        .with_context(|| format!("Instance '{}' not found", name))?;
+    if overrides.is_empty() {
+        instance.metadata.remove("config_overrides");
+    } else {
+        instance.metadata.insert(
+            "config_overrides".to_string(),
+            serde_json::to_value(overrides)?,
+        );
+    }
+    config.save()
+}
+
+/// CONFIG SET each override on every Redis server of the instance
+pub async fn apply(
+    instance: &InstanceInfo,
+    overrides: &BTreeMap<String, String>,
+    verbose: bool,
+) -> Result<()> {
+    let command = server::command_name(instance, "CONFIG")?;
+    for endpoint in servers(instance) {
+        let mut con = client::connect(&endpoint).await?;
+        for (key, value) in overrides {
+            redis::cmd(&command)
+                .arg("SET")
+                .arg(key)
+                .arg(value)
+                .query_async::<()>(&mut con)
+                .await
+                .with_context(|| format!("Failed to set '{}' on {}", key, endpoint))?;
+            if verbose {
+                println!("  {} {} {} = {}", "✓".green(), endpoint, key, value);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Every redis-server of an instance, replicas and sentinel masters included
+fn servers(instance: &InstanceInfo) -> Vec<Endpoint> {
+    match instance.instance_type {
+        InstanceType::Sentinel => {
+            let masters = instance
+                .metadata
+                .get("masters")
+                .and_then(|v| v.as_u64())
+                .unwrap_or(1) as usize;
+            instance
+                .ports
+                .iter()
+                .take(masters)
+                .map(|port| Endpoint {
+                    host: instance.connection_info.host.clone(),
+                    port: *port,
+                    password: instance.connection_info.password.clone(),
+                })
+                .collect()
+        }
+        _ => client::endpoints(instance),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tracked() {
+        let mut instance: InstanceInfo = serde_json::from_value(serde_json::json!({
+            "name": "ha",
+            "instance_type": "sentinel",
+            "created_at": "",
+            "ports": [8000, 8001, 26379],
+            "containers": [],
+            "connection_info": {
+                "host": "localhost",
+                "port": 8000,
+                "password": "pw",
+                "url": "",
+                "additional_ports": {}
+            },
+            "metadata": {"masters": 2}
+        }))
+        .unwrap();
+        assert!(tracked(&instance).is_empty());
+
+        instance.metadata.insert(
+            "config_overrides".to_string(),
+            serde_json::json!({"maxmemory-policy": "allkeys-lru"}),
+        );
+        assert_eq!(tracked(&instance)["maxmemory-policy"], "allkeys-lru");
+
+        let ports: Vec<u16> = servers(&instance).iter().map(|e| e.port).collect();
+        assert_eq!(ports, vec![8000, 8001]);
+    }
+}
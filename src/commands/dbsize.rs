@@ -0,0 +1,115 @@
+//! Key counts per logical database
+
+use anyhow::{Context, Result};
+use colored::*;
+use std::collections::BTreeMap;
+
+use crate::cli::DbsizeArgs;
+use crate::commands::{client, server};
+use crate::config::Config;
+
+pub async fn handle_dbsize(args: DbsizeArgs, verbose: bool) -> Result<()> {
+    let config = Config::load()?;
+    let instance = config
+        .get_instance(&args.name)
+        .with_context(|| format!("Instance '{}' not found", args.name))?;
+
+    let mut counts: BTreeMap<u32, u64> = BTreeMap::new();
+    let mut databases = None;
+
+    // Cluster replicas hold copies of their master's keys
+    for (endpoint, mut con) in client::primaries(instance).await? {
+        let keyspace: String = redis::cmd("INFO")
+            .arg("keyspace")
+            .query_async(&mut con)
+            .await
+            .with_context(|| format!("INFO failed on {}", endpoint))?;
+        for (db, keys) in parse_keyspace(&keyspace) {
+            *counts.entry(db).or_default() += keys;
+        }
+
+        let config_command = server::command_name(instance, "CONFIG");
+        if let (true, None, Ok(config_command)) = (args.all_dbs, databases, &config_command) {
+            // CONFIG may still be disabled or renamed outside redis-up; fall back to the non-empty databases
+            let reply: redis::RedisResult<Vec<String>> = redis::cmd(config_command)
+                .arg("GET")
+                .arg("databases")
+                .query_async(&mut con)
+                .await;
+            match reply {
+                Ok(reply) => databases = reply.get(1).and_then(|n| n.parse::<u32>().ok()),
+                Err(e) if verbose => {
+                    println!("{} CONFIG GET databases failed: {}", "Warning:".yellow(), e)
+                }
+                Err(_) => {}
+            }
+        }
+    }
+
+    println!("{} {}", "Keyspace:".bold().cyan(), instance.name.bold());
+
+    if !args.all_dbs {
+        let keys = counts.get(&0).copied().unwrap_or(0);
+        println!("  {:<6} {:>12} keys", "db0", keys);
+        let others = counts.keys().filter(|db| **db != 0).count();
+        if others > 0 {
+            println!(
+                "  {}",
+                format!(
+                    "{} other database(s) hold keys; use --all-dbs to list them",
+                    others
+                )
+                .dimmed()
+            );
+        }
+        return Ok(());
+    }
+
+    if let Some(databases) = databases {
+        for db in 0..databases {
+            counts.entry(db).or_default();
+        }
+    }
+    for (db, keys) in &counts {
+        let line = format!("  {:<6} {:>12} keys", format!("db{}", db), keys);
+        if *keys == 0 {
+            println!("{}", line.dimmed());
+        } else {
+            println!("{}", line);
+        }
+    }
+    println!(
+        "  {:<6} {:>12} keys",
+        "total".bold(),
+        counts.values().sum::<u64>()
+    );
+
+    Ok(())
+}
+
+/// Key counts from the `# Keyspace` section of INFO, e.g. `db0:keys=12,expires=0,avg_ttl=0`
+fn parse_keyspace(info: &str) -> BTreeMap<u32, u64> {
+    info.lines()
+        .filter_map(|line| {
+            let (db, stats) = line.trim().strip_prefix("db")?.split_once(':')?;
+            let keys = stats
+                .split(',')
+                .find_map(|stat| stat.strip_prefix("keys="))?;
+            Some((db.parse().ok()?, keys.parse().ok()?))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_keyspace() {
+        let info =
+            "# Keyspace\r\ndb0:keys=12,expires=2,avg_ttl=0\r\ndb3:keys=1,expires=0,avg_ttl=0\r\n";
+        let counts = parse_keyspace(info);
+        assert_eq!(counts, BTreeMap::from([(0, 12), (3, 1)]));
+        assert!(parse_keyspace("# Keyspace\r\n").is_empty());
+    }
+}
@@ -0,0 +1,286 @@
+//! Keyspace comparison between two managed instances
+
+use anyhow::{Context, Result};
+use colored::*;
+use redis::aio::MultiplexedConnection;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeMap;
+use std::hash::{Hash, Hasher};
+
+use crate::cli::DiffArgs;
+use crate::commands::client;
+use crate::config::Config;
+
+/// Type and content fingerprint of a single key
+#[derive(Debug, Clone, PartialEq)]
+struct KeyDigest {
+    key_type: String,
+    hash: u64,
+}
+
+/// How a key differs between the two instances
+#[derive(Debug, Clone, PartialEq)]
+enum Difference {
+    Missing,
+    Extra,
+    Type(String, String),
+    Value,
+}
+
+pub async fn handle_diff(args: DiffArgs, verbose: bool) -> Result<()> {
+    let config = Config::load()?;
+    let left = config
+        .get_instance(&args.left)
+        .with_context(|| format!("Instance '{}' not found", args.left))?;
+    let right = config
+        .get_instance(&args.right)
+        .with_context(|| format!("Instance '{}' not found", args.right))?;
+
+    if verbose {
+        println!(
+            "{} Scanning {} and {} for '{}'",
+            "Diff:".cyan(),
+            args.left.bold(),
+            args.right.bold(),
+            args.pattern
+        );
+    }
+
+    let left_keys = scan_instance(&client::endpoints(left), &args.pattern).await?;
+    let right_keys = scan_instance(&client::endpoints(right), &args.pattern).await?;
+
+    let differences = compare(&left_keys, &right_keys);
+    let identical = left_keys.len()
+        - differences
+            .iter()
+            .filter(|(_, d)| *d != Difference::Extra)
+            .count();
+
+    println!(
+        "{} {} ({} keys) vs {} ({} keys), pattern '{}'",
+        "Diff:".bold().cyan(),
+        args.left.bold(),
+        left_keys.len(),
+        args.right.bold(),
+        right_keys.len(),
+        args.pattern
+    );
+
+    for (key, difference) in differences.iter().take(args.limit) {
+        let key = String::from_utf8_lossy(key);
+        match difference {
+            Difference::Missing => println!("  {} {} (only in {})", "-".red(), key, args.left),
+            Difference::Extra => println!("  {} {} (only in {})", "+".green(), key, args.right),
+            Difference::Type(a, b) => {
+                println!("  {} {} (type {} vs {})", "~".yellow(), key, a, b)
+            }
+            Difference::Value => println!("  {} {} (value differs)", "~".yellow(), key),
+        }
+    }
+    if differences.len() > args.limit {
+        println!(
+            "  {} more difference(s) not shown (use --limit)",
+            differences.len() - args.limit
+        );
+    }
+
+    let count = |kind: fn(&Difference) -> bool| differences.iter().filter(|(_, d)| kind(d)).count();
+    println!();
+    println!(
+        "  {}: {}",
+        format!("Only in {}", args.left).bold(),
+        count(|d| *d == Difference::Missing)
+    );
+    println!(
+        "  {}: {}",
+        format!("Only in {}", args.right).bold(),
+        count(|d| *d == Difference::Extra)
+    );
+    println!(
+        "  {}: {}",
+        "Type differs".bold(),
+        count(|d| matches!(d, Difference::Type(_, _)))
+    );
+    println!(
+        "  {}: {}",
+        "Value differs".bold(),
+        count(|d| *d == Difference::Value)
+    );
+    println!("  {}: {}", "Identical".bold(), identical);
+
+    if !differences.is_empty() {
+        anyhow::bail!("{} key(s) differ", differences.len());
+    }
+
+    println!();
+    println!("{} Keyspaces match", "Success:".green());
+    Ok(())
+}
+
+/// Scan every endpoint of an instance and fingerprint matching keys
+async fn scan_instance(
+    endpoints: &[client::Endpoint],
+    pattern: &str,
+) -> Result<BTreeMap<Vec<u8>, KeyDigest>> {
+    let mut keys = BTreeMap::new();
+
+    for endpoint in endpoints {
+        let mut con = client::connect(endpoint).await?;
+
+        // Allow reads on cluster replicas; harmless elsewhere
+        let _: redis::RedisResult<()> = redis::cmd("READONLY").query_async(&mut con).await;
+
+        let mut cursor: u64 = 0;
+        loop {
+            let (next, batch): (u64, Vec<Vec<u8>>) = redis::cmd("SCAN")
+                .arg(cursor)
+                .arg("MATCH")
+                .arg(pattern)
+                .arg("COUNT")
+                .arg(500)
+                .query_async(&mut con)
+                .await
+                .with_context(|| format!("SCAN failed on {}", endpoint))?;
+
+            for key in batch {
+                if keys.contains_key(&key) {
+                    continue;
+                }
+                // Keys can expire between SCAN and the read
+                if let Some(digest) = digest_key(&mut con, &key).await? {
+                    keys.insert(key, digest);
+                }
+            }
+
+            cursor = next;
+            if cursor == 0 {
+                break;
+            }
+        }
+    }
+
+    Ok(keys)
+}
+
+/// Fingerprint a key's type and value
+async fn digest_key(con: &mut MultiplexedConnection, key: &[u8]) -> Result<Option<KeyDigest>> {
+    let key_type: String = redis::cmd("TYPE").arg(key).query_async(con).await?;
+    let mut hasher = DefaultHasher::new();
+
+    match key_type.as_str() {
+        "none" => return Ok(None),
+        "string" => {
+            let value: Vec<u8> = redis::cmd("GET").arg(key).query_async(con).await?;
+            value.hash(&mut hasher);
+        }
+        "list" => {
+            let value: Vec<Vec<u8>> = redis::cmd("LRANGE")
+                .arg(key)
+                .arg(0)
+                .arg(-1)
+                .query_async(con)
+                .await?;
+            value.hash(&mut hasher);
+        }
+        "set" => {
+            let mut value: Vec<Vec<u8>> = redis::cmd("SMEMBERS").arg(key).query_async(con).await?;
+            value.sort();
+            value.hash(&mut hasher);
+        }
+        "zset" => {
+            let value: Vec<Vec<u8>> = redis::cmd("ZRANGE")
+                .arg(key)
+                .arg(0)
+                .arg(-1)
+                .arg("WITHSCORES")
+                .query_async(con)
+                .await?;
+            value.hash(&mut hasher);
+        }
+        "hash" => {
+            let mut value: Vec<(Vec<u8>, Vec<u8>)> =
+                redis::cmd("HGETALL").arg(key).query_async(con).await?;
+            value.sort();
+            value.hash(&mut hasher);
+        }
+        _ => {
+            // Streams and module types: compare the serialized form
+            let value: Option<Vec<u8>> = redis::cmd("DUMP").arg(key).query_async(con).await?;
+            value.hash(&mut hasher);
+        }
+    }
+
+    Ok(Some(KeyDigest {
+        key_type,
+        hash: hasher.finish(),
+    }))
+}
+
+/// Compare two fingerprinted keyspaces, ordered by key
+fn compare(
+    left: &BTreeMap<Vec<u8>, KeyDigest>,
+    right: &BTreeMap<Vec<u8>, KeyDigest>,
+) -> Vec<(Vec<u8>, Difference)> {
+    let mut differences = Vec::new();
+
+    for (key, a) in left {
+        match right.get(key) {
+            None => differences.push((key.clone(), Difference::Missing)),
+            Some(b) if a.key_type != b.key_type => differences.push((
+                key.clone(),
+                Difference::Type(a.key_type.clone(), b.key_type.clone()),
+            )),
+            Some(b) if a.hash != b.hash => differences.push((key.clone(), Difference::Value)),
+            Some(_) => {}
+        }
+    }
+    for key in right.keys() {
+        if !left.contains_key(key) {
+            differences.push((key.clone(), Difference::Extra));
+        }
+    }
+
+    differences.sort_by(|a, b| a.0.cmp(&b.0));
+    differences
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digest(key_type: &str, hash: u64) -> KeyDigest {
+        KeyDigest {
+            key_type: key_type.to_string(),
+            hash,
+        }
+    }
+
+    #[test]
+    fn test_compare_keyspaces() {
+        let mut left = BTreeMap::new();
+        left.insert(b"same".to_vec(), digest("string", 1));
+        left.insert(b"changed".to_vec(), digest("string", 1));
+        left.insert(b"retyped".to_vec(), digest("string", 1));
+        left.insert(b"missing".to_vec(), digest("hash", 1));
+
+        let mut right = BTreeMap::new();
+        right.insert(b"same".to_vec(), digest("string", 1));
+        right.insert(b"changed".to_vec(), digest("string", 2));
+        right.insert(b"retyped".to_vec(), digest("list", 1));
+        right.insert(b"extra".to_vec(), digest("set", 1));
+
+        let differences = compare(&left, &right);
+        assert_eq!(
+            differences,
+            vec![
+                (b"changed".to_vec(), Difference::Value),
+                (b"extra".to_vec(), Difference::Extra),
+                (b"missing".to_vec(), Difference::Missing),
+                (
+                    b"retyped".to_vec(),
+                    Difference::Type("string".to_string(), "list".to_string())
+                ),
+            ]
+        );
+    }
+}
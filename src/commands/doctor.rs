@@ -0,0 +1,246 @@
+//! Environment diagnostics for redis-up
+
+use anyhow::Result;
+use colored::*;
+use docker_wrapper::{
+    DockerCommand, NetworkCreateCommand, NetworkInspectCommand, NetworkRmCommand,
+};
+use std::net::IpAddr;
+use tokio::process::Command as ProcessCommand;
+
+use crate::cli::PullPolicy;
+use crate::commands::images;
+use crate::config::{get_config_path, Config};
+use crate::settings::Settings;
+
+/// Outcome of a single diagnostic check
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Status {
+    Ok,
+    Warn,
+    Fail,
+}
+
+/// A single diagnostic check result
+struct Check {
+    name: &'static str,
+    status: Status,
+    detail: String,
+}
+
+impl Check {
+    fn new(name: &'static str, status: Status, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            status,
+            detail: detail.into(),
+        }
+    }
+}
+
+pub async fn handle_doctor(verbose: bool) -> Result<()> {
+    println!("{} Checking redis-up environment", "Doctor:".bold().cyan());
+    println!();
+
+    let mut checks = Vec::new();
+
+    let docker = check_docker().await;
+    let docker_ok = docker.status == Status::Ok;
+    checks.push(docker);
+    checks.push(check_config());
+
+    // Everything else needs a running daemon
+    if docker_ok {
+        checks.push(check_bridge_ipv6().await);
+        checks.push(check_ipv6_networks(verbose).await);
+        checks.push(check_exposure().await);
+        checks.push(check_registry().await);
+    }
+
+    for check in &checks {
+        let label = match check.status {
+            Status::Ok => "OK".green(),
+            Status::Warn => "WARN".yellow(),
+            Status::Fail => "FAIL".red(),
+        };
+        println!("  [{}] {}: {}", label, check.name.bold(), check.detail);
+    }
+
+    let failures = checks.iter().filter(|c| c.status == Status::Fail).count();
+    println!();
+    if failures > 0 {
+        anyhow::bail!("{} check(s) failed", failures);
+    }
+
+    println!("{} Environment looks good", "Success:".green());
+    Ok(())
+}
+
+async fn check_docker() -> Check {
+    match docker_wrapper::ensure_docker().await {
+        Ok(info) if info.daemon_running => Check::new(
+            "Docker",
+            Status::Ok,
+            format!(
+                "{} ({}/{})",
+                info.server_version
+                    .map(|v| v.version)
+                    .unwrap_or(info.version.version),
+                info.os,
+                info.architecture
+            ),
+        ),
+        Ok(_) => Check::new("Docker", Status::Fail, "Docker daemon is not running"),
+        Err(e) => Check::new("Docker", Status::Fail, e.to_string()),
+    }
+}
+
+fn check_config() -> Check {
+    let path = match get_config_path() {
+        Ok(path) => path,
+        Err(e) => return Check::new("Config", Status::Fail, e.to_string()),
+    };
+
+    match Config::load() {
+        Ok(config) => Check::new(
+            "Config",
+            Status::Ok,
+            format!(
+                "{} tracked instance(s) in {}",
+                config.instances.len(),
+                path.display()
+            ),
+        ),
+        Err(e) => Check::new("Config", Status::Fail, format!("{:#}", e)),
+    }
+}
+
+/// Report whether the default bridge network has IPv6 enabled
+async fn check_bridge_ipv6() -> Check {
+    let enabled = NetworkInspectCommand::new("bridge")
+        .run()
+        .await
+        .ok()
+        .and_then(|output| output.json)
+        .and_then(|json| json.get(0)?.get("EnableIPv6")?.as_bool());
+
+    match enabled {
+        Some(true) => Check::new("IPv6 bridge", Status::Ok, "Default bridge has IPv6 enabled"),
+        Some(false) => Check::new(
+            "IPv6 bridge",
+            Status::Warn,
+            "Default bridge has IPv6 disabled (only needed for containers outside redis-up networks)",
+        ),
+        None => Check::new(
+            "IPv6 bridge",
+            Status::Warn,
+            "Could not inspect the default bridge network",
+        ),
+    }
+}
+
+/// Verify that IPv6-enabled networks can be created, as `--ipv6` requires
+async fn check_ipv6_networks(verbose: bool) -> Check {
+    let probe = "redis-up-doctor-ipv6";
+    match NetworkCreateCommand::new(probe).ipv6().execute().await {
+        Ok(_) => {
+            if let Err(e) = NetworkRmCommand::new(probe).execute().await {
+                if verbose {
+                    println!(
+                        "{} Failed to remove probe network {}: {}",
+                        "Warning:".yellow(),
+                        probe,
+                        e
+                    );
+                }
+            }
+            Check::new("IPv6 networks", Status::Ok, "IPv6 networks can be created")
+        }
+        Err(e) => Check::new(
+            "IPv6 networks",
+            Status::Warn,
+            format!(
+                "Cannot create IPv6 networks, --ipv6 will not work. Enable \"ipv6\" and \"ip6tables\" in the Docker daemon config ({})",
+                e.to_string().lines().last().unwrap_or_default().trim()
+            ),
+        ),
+    }
+}
+
+/// Flag passwordless instances whose ports are published beyond loopback
+async fn check_exposure() -> Check {
+    let Ok(config) = Config::load() else {
+        return Check::new("Exposure", Status::Warn, "Could not read the instances");
+    };
+    let mut names: Vec<&String> = config.instances.keys().collect();
+    names.sort();
+
+    let mut exposed = Vec::new();
+    for name in names {
+        let instance = &config.instances[name];
+        if instance.connection_info.password.is_some() {
+            continue;
+        }
+        let mut addresses = Vec::new();
+        for container in &instance.containers {
+            if let Ok(output) = ProcessCommand::new("docker")
+                .args(["port", container])
+                .output()
+                .await
+            {
+                addresses.extend(exposed_addresses(&String::from_utf8_lossy(&output.stdout)));
+            }
+        }
+        addresses.dedup();
+        if !addresses.is_empty() {
+            exposed.push(format!("{} on {}", name, addresses.join(", ")));
+        }
+    }
+
+    if exposed.is_empty() {
+        return Check::new(
+            "Exposure",
+            Status::Ok,
+            "No instance without a password is reachable beyond loopback",
+        );
+    }
+    Check::new(
+        "Exposure",
+        Status::Warn,
+        format!(
+            "Reachable from the network without a password: {}. Restart them with --bind 127.0.0.1 or a password",
+            exposed.join("; ")
+        ),
+    )
+}
+
+/// Pull a tiny image, through any configured mirror, to prove the daemon can
+/// reach the registry before a start fails on it
+async fn check_registry() -> Check {
+    if Settings::load().is_ok_and(|settings| settings.pull == Some(PullPolicy::Never)) {
+        return Check::new("Registry", Status::Ok, "Skipped, pulling is disabled");
+    }
+    let image = images::resolve(images::PROBE_IMAGE);
+    match images::pull(&image, None).await {
+        Ok(()) => Check::new("Registry", Status::Ok, format!("Pulled {}", image)),
+        Err(e) => Check::new("Registry", Status::Fail, format!("{:#}", e)),
+    }
+}
+
+/// Non-loopback host addresses in `docker port` output (`6379/tcp -> 0.0.0.0:6379`)
+fn exposed_addresses(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| line.split_once("->"))
+        .map(|(_, host)| host.trim())
+        .filter(|host| {
+            let address = host
+                .rsplit_once(':')
+                .map_or(*host, |(address, _)| address)
+                .trim_start_matches('[')
+                .trim_end_matches(']');
+            !address.parse::<IpAddr>().is_ok_and(|ip| ip.is_loopback())
+        })
+        .map(String::from)
+        .collect()
+}
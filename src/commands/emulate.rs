@@ -0,0 +1,85 @@
+//! Managed-service emulation for `cluster start --emulate`
+//!
+//! Clients reach each emulated node only over TLS, and the admin commands the
+//! service withholds are renamed away so calling them fails the way it would
+//! there. Each node keeps a plain port inside the cluster network, never
+//! published, which cluster formation and redis-up's own checks use.
+
+use anyhow::Result;
+use std::collections::BTreeMap;
+
+use crate::cli::Emulation;
+use crate::commands::tls;
+
+/// Container port clients reach over TLS; 6379 stays plain and unpublished
+pub const TLS_PORT: u16 = 6380;
+
+/// Commands the service doesn't let clients run
+pub fn disabled_commands(mode: Emulation) -> &'static [&'static str] {
+    match mode {
+        // SYNC and PSYNC are restricted too, but replicas need them
+        Emulation::Elasticache => &[
+            "BGREWRITEAOF",
+            "BGSAVE",
+            "CONFIG",
+            "DEBUG",
+            "MIGRATE",
+            "MODULE",
+            "REPLICAOF",
+            "SAVE",
+            "SHUTDOWN",
+            "SLAVEOF",
+        ],
+    }
+}
+
+/// The disabled commands as renames to the empty name
+pub fn renames(mode: Emulation) -> BTreeMap<String, String> {
+    disabled_commands(mode)
+        .iter()
+        .map(|command| (command.to_string(), String::new()))
+        .collect()
+}
+
+/// Server arguments that make a node take clients over TLS only
+pub fn tls_args() -> Vec<String> {
+    vec![
+        "--tls-port".to_string(),
+        TLS_PORT.to_string(),
+        "--tls-cluster".to_string(),
+        "yes".to_string(),
+        "--tls-replication".to_string(),
+        "yes".to_string(),
+        // Certificates are checked when presented, as with the client bundle
+        "--tls-auth-clients".to_string(),
+        "optional".to_string(),
+        "--tls-cert-file".to_string(),
+        tls::container_path(tls::SERVER_CERT),
+        "--tls-key-file".to_string(),
+        tls::container_path(tls::SERVER_KEY),
+        "--tls-ca-cert-file".to_string(),
+        tls::container_path(tls::CA_CERT),
+    ]
+}
+
+/// redis-cli arguments for connecting to an emulated node from the host
+pub fn client_tls_args(name: &str) -> Result<Vec<String>> {
+    Ok(vec![
+        "--tls".to_string(),
+        "--cacert".to_string(),
+        tls::dir(name)?.join(tls::CA_CERT).display().to_string(),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_renames() {
+        let renames = renames(Emulation::Elasticache);
+        assert_eq!(renames.get("CONFIG").map(String::as_str), Some(""));
+        assert!(!renames.contains_key("PSYNC"));
+        assert_eq!(tls_args()[..2], ["--tls-port", "6380"]);
+    }
+}
@@ -1,28 +1,217 @@
 //! Redis Enterprise instance management
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use colored::*;
-use docker_wrapper::{DockerCommand, RedisEnterpriseTemplate};
+use docker_wrapper::{
+    DockerCommand, NetworkConnectCommand, NetworkCreateCommand, NetworkRmCommand, RmCommand,
+    RunCommand, VolumeRmCommand,
+};
 use std::collections::HashMap;
+use std::time::Duration;
 
-use crate::cli::{EnterpriseAction, EnterpriseStartArgs, InfoArgs, StopArgs};
+use crate::cli::{EnterpriseAction, EnterpriseApiArgs, EnterpriseStartArgs, InfoArgs, StopArgs};
+use crate::commands::plan::{Operation, Plan};
+use crate::commands::progress::{Progress, ProgressEvent};
+use crate::commands::retry::{remove_container, RetryPolicy};
+use crate::commands::{
+    annotate, capacity, enterprise_status, history, images, limits, live, locks, names,
+    passthrough, ports, reuse, server, shutdown, uptime, volume,
+};
 use crate::config::{Config, ConnectionInfo, InstanceInfo, InstanceType};
+use crate::error::Failure;
 
 pub async fn handle_action(action: EnterpriseAction, verbose: bool) -> Result<()> {
     match action {
-        EnterpriseAction::Start(args) => start_enterprise(args, verbose).await,
+        EnterpriseAction::Start(args) => {
+            names::validate_opt(args.name.as_deref())?;
+            let _lock = locks::start(args.name.as_deref(), &InstanceType::Enterprise).await?;
+            let reused = reuse::existing(
+                args.name.as_deref(),
+                reuse::Mode::new(args.reuse, args.recreate_on_drift, args.warn_on_drift),
+                InstanceType::Enterprise,
+                |instance| reuse::enterprise(&args, instance),
+                verbose,
+            )
+            .await?;
+            if reused {
+                return Ok(());
+            }
+            locks::ensure_not_running(args.name.as_deref()).await?;
+            start_enterprise(args, verbose).await
+        }
         EnterpriseAction::Stop(args) => stop_enterprise(args, verbose).await,
-        EnterpriseAction::Info(args) => info_enterprise(args, verbose).await,
+        EnterpriseAction::Info(args) => {
+            let databases = args.databases;
+            live::info(
+                args.info,
+                InstanceType::Enterprise,
+                verbose,
+                |args, verbose| info_enterprise(args, databases, verbose),
+            )
+            .await
+        }
+        EnterpriseAction::Api(args) => api_enterprise(args, verbose).await,
     }
 }
 
-async fn start_enterprise(args: EnterpriseStartArgs, verbose: bool) -> Result<()> {
-    let mut config = Config::load()?;
+/// One Enterprise node, running a single-node cluster of its own
+struct Node {
+    /// Prefix of the node's container and volumes
+    name: String,
+    cluster: String,
+    ui_port: u16,
+    api_port: u16,
+    run_cmd: RunCommand,
+    /// Joins the Active-Active network under the cluster's FQDN
+    connect: Option<NetworkConnectCommand>,
+}
+
+/// An Enterprise start resolved down to the Docker commands it runs
+///
+/// Built once by [`prepare`], then run by `start_enterprise` or printed by `--dry-run`.
+struct Launch {
+    name: String,
+    /// The network Active-Active participants meet on
+    network: Option<NetworkCreateCommand>,
+    nodes: Vec<Node>,
+}
+
+/// Validate a start and resolve its name, ports and commands
+///
+/// Nothing is created; `args` is updated with the resolved platform.
+fn prepare(args: &mut EnterpriseStartArgs, config: &mut Config) -> Result<Launch> {
+    if args.ipv6 {
+        bail!(
+            "--ipv6 is not supported for Enterprise: nodes are created without IPv6 networking and databases advertise IPv4 endpoints. Use basic, stack or sentinel for IPv6 testing"
+        );
+    }
+    // Active-Active runs one single-node cluster per participant
+    let nodes = if args.active_active {
+        usize::from(args.participants)
+    } else {
+        args.nodes
+    };
+    capacity::check(
+        "Enterprise",
+        nodes,
+        args.memory.as_deref(),
+        capacity::ENTERPRISE_NODE,
+        args.force,
+    )?;
+    ports::check_free(
+        config,
+        &host_ports(args),
+        "Enterprise",
+        "--port-base and --db-port",
+    )?;
+    // Resolved once so every node below gets the same platform
+    args.pull.platform =
+        images::resolve_platform(&args.pull, "enterprise", &[images::ENTERPRISE_IMAGE])?;
 
     // Generate name if not provided
-    let name = args
-        .name
-        .unwrap_or_else(|| config.generate_name(&InstanceType::Enterprise));
+    let name = match args.name {
+        Some(ref name) => name.clone(),
+        None => config.reserve_name(&InstanceType::Enterprise),
+    };
+
+    if !args.active_active {
+        let node = Node {
+            cluster: format!("{}-cluster", name),
+            ui_port: args.port_base,
+            api_port: args.port_base + 1000, // API port is typically 1000 higher
+            run_cmd: node_command(
+                &name,
+                args.port_base,
+                args.port_base + 1000,
+                args.db_port,
+                args,
+            ),
+            connect: None,
+            name: name.clone(),
+        };
+        return Ok(Launch {
+            name,
+            network: None,
+            nodes: vec![node],
+        });
+    }
+
+    // Syncers reach the other participants through their cluster FQDN
+    let network = format!("{}-network", name);
+    let nodes = plan_participants(&name, args)
+        .into_iter()
+        .map(|participant| Node {
+            run_cmd: node_command(
+                &participant.name,
+                participant.ui_port,
+                participant.api_port,
+                participant.db_port,
+                args,
+            ),
+            connect: Some(
+                NetworkConnectCommand::new(&network, format!("{}-enterprise", participant.name))
+                    .alias(&participant.fqdn),
+            ),
+            name: participant.name,
+            cluster: participant.fqdn,
+            ui_port: participant.ui_port,
+            api_port: participant.api_port,
+        })
+        .collect();
+    Ok(Launch {
+        name,
+        network: Some(NetworkCreateCommand::new(&network)),
+        nodes,
+    })
+}
+
+/// `docker run` for one node, with the ports and volumes the Enterprise image expects
+///
+/// UI and API ports publish 8443 and 9443; the ten database ports keep their numbers.
+/// Volumes are only mounted with `--persist`.
+fn node_command(
+    name: &str,
+    ui_port: u16,
+    api_port: u16,
+    db_port: u16,
+    args: &EnterpriseStartArgs,
+) -> RunCommand {
+    let mut cmd = passthrough::apply(RunCommand::new(images::resolve(images::ENTERPRISE_IMAGE)))
+        .name(format!("{}-enterprise", name))
+        .port(ui_port, 8443)
+        .port(api_port, 9443)
+        .detach();
+    for port in db_port..db_port + 10 {
+        cmd = cmd.port(port, port);
+    }
+    if args.persist {
+        cmd = cmd
+            .volume(format!("{}-persistent", name), "/var/opt/redislabs/persist")
+            .volume(format!("{}-ephemeral", name), "/var/opt/redislabs/tmp");
+    }
+    if let Some(ref memory) = args.memory {
+        cmd = cmd.memory(memory);
+    }
+    cmd = cmd.cap_add("SYS_RESOURCE");
+    if let Some(ref platform) = args.pull.platform {
+        cmd = cmd.platform(platform);
+    }
+    limits::apply_to_run(cmd, &args.limits)
+}
+
+async fn start_enterprise(mut args: EnterpriseStartArgs, verbose: bool) -> Result<()> {
+    let mut config = Config::load()?;
+    let launch = prepare(&mut args, &mut config)?;
+    let progress = Progress::new(args.output, verbose);
+    images::ensure_images(
+        &[images::ENTERPRISE_IMAGE],
+        &args.pull,
+        args.pull.platform.as_deref(),
+        Some(&progress),
+        verbose,
+    )
+    .await?;
+    let name = launch.name.clone();
 
     if verbose {
         println!(
@@ -32,6 +221,10 @@ async fn start_enterprise(args: EnterpriseStartArgs, verbose: bool) -> Result<()
         );
     }
 
+    if args.active_active {
+        return start_active_active(args, launch, config, progress, verbose).await;
+    }
+
     // Note for multi-node support: In a full implementation, we would need to:
     // 1. Create a Docker network for the nodes to communicate
     // 2. Start multiple containers with proper networking
@@ -45,30 +238,9 @@ async fn start_enterprise(args: EnterpriseStartArgs, verbose: bool) -> Result<()
         );
     }
 
-    // Create Redis Enterprise template
-    let mut enterprise = RedisEnterpriseTemplate::new(&name)
-        .cluster_name(format!("{}-cluster", name))
-        .accept_eula()
-        .ui_port(args.port_base)
-        .api_port(args.port_base + 1000) // API port is typically 1000 higher
-        .database_port_start(args.db_port);
-
-    // Set memory limit if specified
-    if let Some(ref memory) = args.memory {
-        enterprise = enterprise.memory_limit(memory);
-    }
-
-    // Set persistence volumes
-    if args.persist {
-        enterprise = enterprise
-            .persistent_path(format!("{}-persistent", name))
-            .ephemeral_path(format!("{}-ephemeral", name));
-    }
-
-    // Add initial database if requested
-    if let Some(ref db_name) = args.create_db {
-        enterprise = enterprise.with_database(db_name);
-    }
+    let policy = RetryPolicy::resolve(args.retries)?;
+    let node = &launch.nodes[0];
+    let container_name = format!("{}-enterprise", name);
 
     // Start the Enterprise cluster (unless containers-only mode)
     let connection_info = if args.containers_only {
@@ -78,35 +250,12 @@ async fn start_enterprise(args: EnterpriseStartArgs, verbose: bool) -> Result<()
         );
 
         // Just start the container without bootstrapping
-        use docker_wrapper::RunCommand;
-        let container_name = format!("{}-enterprise", name);
-        let mut cmd = RunCommand::new("redislabs/redis:latest")
-            .name(&container_name)
-            .port(args.port_base, 8443)
-            .port(args.port_base + 1000, 9443)
-            .detach()
-            .cap_add("SYS_RESOURCE");
-
-        // Add database ports
-        for i in 0..10 {
-            let port = args.db_port + i;
-            cmd = cmd.port(port, port);
-        }
-
-        // Add volumes
-        if args.persist {
-            cmd = cmd
-                .volume(format!("{}-persistent", name), "/var/opt/redislabs/persist")
-                .volume(format!("{}-ephemeral", name), "/var/opt/redislabs/tmp");
-        }
-
-        // Add memory limit
-        if let Some(ref memory) = args.memory {
-            cmd = cmd.memory(memory);
-        }
-
-        let container_id = cmd
-            .execute()
+        let container_id = policy
+            .run(
+                "Enterprise container start",
+                || async { Ok(server::run_container(&node.run_cmd, None).await?) },
+                || remove_container(&container_name),
+            )
             .await
             .context("Failed to start Enterprise container")?;
 
@@ -118,23 +267,19 @@ async fn start_enterprise(args: EnterpriseStartArgs, verbose: bool) -> Result<()
             "  Access the UI at https://localhost:{} to complete setup",
             args.port_base
         );
-        println!("  Container ID: {}", container_id.0);
+        println!("  Container ID: {}", container_id);
 
         // Return basic connection info
         docker_wrapper::RedisEnterpriseConnectionInfo {
-            name: name.clone(),
-            container_name,
-            cluster_name: format!("{}-cluster", name),
-            ui_url: format!("https://localhost:{}", args.port_base),
-            api_url: format!("https://localhost:{}", args.port_base + 1000),
-            username: "admin@redis.local".to_string(),
             password: "<set during UI setup>".to_string(),
-            database_port: None,
+            ..connection_info(node)
         }
     } else {
         // Full automatic cluster formation
-        let conn_info = enterprise
-            .start()
+        progress.emit(ProgressEvent::BootstrappingEnterprise {
+            cluster: node.cluster.clone(),
+        });
+        let mut conn_info = start_node(node, &policy)
             .await
             .context("Failed to start Redis Enterprise cluster")?;
 
@@ -143,13 +288,44 @@ async fn start_enterprise(args: EnterpriseStartArgs, verbose: bool) -> Result<()
                 "  {} Enterprise cluster bootstrapped successfully",
                 "Success".green()
             );
-            if args.create_db.is_some() {
-                println!(
-                    "  {} Database '{}' created on port {}",
-                    "Database".green(),
-                    args.create_db.as_ref().unwrap(),
-                    args.db_port
-                );
+        }
+
+        // The cluster took minutes to boot, so a database that won't come up
+        // is a warning rather than a failed start
+        if let Some(ref db_name) = args.create_db {
+            let api = EnterpriseApi::new(
+                args.port_base + 1000,
+                &conn_info.username,
+                &conn_info.password,
+            )?;
+            progress.emit(ProgressEvent::WaitingForEnterprise {
+                cluster: conn_info.cluster_name.clone(),
+            });
+            match create_database(&api, db_name, args.db_port, &progress).await {
+                Ok(()) => {
+                    conn_info.database_port = Some(args.db_port);
+                    if verbose {
+                        println!(
+                            "  {} Database '{}' created on port {}",
+                            "Database".green(),
+                            db_name,
+                            args.db_port
+                        );
+                    }
+                }
+                Err(e) => {
+                    println!(
+                        "{} Failed to create database '{}': {:#}",
+                        "Warning:".yellow(),
+                        db_name,
+                        e
+                    );
+                    println!(
+                        "  Retry with: redis-up enterprise api {} POST /v1/bdbs -d '{}'",
+                        name,
+                        database_body(db_name, args.db_port)
+                    );
+                }
             }
         }
 
@@ -159,6 +335,7 @@ async fn start_enterprise(args: EnterpriseStartArgs, verbose: bool) -> Result<()
     // Save instance information
     let mut metadata = HashMap::new();
     metadata.insert("nodes".to_string(), serde_json::json!(1));
+    metadata.insert("persist".to_string(), serde_json::json!(args.persist));
     metadata.insert("ui_port".to_string(), serde_json::json!(args.port_base));
     metadata.insert(
         "api_port".to_string(),
@@ -178,8 +355,16 @@ async fn start_enterprise(args: EnterpriseStartArgs, verbose: bool) -> Result<()
     if let Some(ref db_name) = args.create_db {
         metadata.insert("database_name".to_string(), serde_json::json!(db_name));
     }
+    if let Some(ref memory) = args.memory {
+        metadata.insert("memory".to_string(), serde_json::json!(memory));
+    }
+    limits::record_limits(&mut metadata, &args.limits);
+    metadata.insert(
+        "username".to_string(),
+        serde_json::json!(connection_info.username.clone()),
+    );
 
-    let instance = InstanceInfo {
+    let mut instance = InstanceInfo {
         name: name.clone(),
         instance_type: InstanceType::Enterprise,
         created_at: chrono::Utc::now().to_rfc3339(),
@@ -200,13 +385,22 @@ async fn start_enterprise(args: EnterpriseStartArgs, verbose: bool) -> Result<()
                 ports.insert("api".to_string(), args.port_base + 1000);
                 ports
             },
+            ipv6_host: None,
+            ipv6_url: None,
         },
         metadata,
+        started_at: None,
+        last_seen: None,
     };
 
+    annotate::set_description(&mut instance, args.description.as_deref());
+    history::record_start(&instance);
     config.add_instance(instance);
     config.save()?;
 
+    progress.emit(ProgressEvent::Ready { name: name.clone() });
+    progress.finish();
+
     // Display success message
     println!(
         "\n{} Redis Enterprise cluster started successfully!",
@@ -239,6 +433,652 @@ async fn start_enterprise(args: EnterpriseStartArgs, verbose: bool) -> Result<()
     Ok(())
 }
 
+/// One single-node cluster taking part in an Active-Active deployment
+#[derive(Debug, Clone, PartialEq)]
+struct Participant {
+    name: String,
+    fqdn: String,
+    ui_port: u16,
+    api_port: u16,
+    db_port: u16,
+}
+
+/// Names and host ports for each participating cluster
+///
+/// Clusters get consecutive UI/API ports and their own block of ten database ports.
+fn plan_participants(name: &str, args: &EnterpriseStartArgs) -> Vec<Participant> {
+    (0..args.participants)
+        .map(|i| Participant {
+            name: format!("{}-{}", name, i + 1),
+            fqdn: format!("{}-{}.redis.local", name, i + 1),
+            ui_port: args.port_base + i,
+            api_port: args.port_base + 1000 + i,
+            db_port: args.db_port + i * 10,
+        })
+        .collect()
+}
+
+/// Host ports a start publishes: UI, API and ten database ports per node
+fn host_ports(args: &EnterpriseStartArgs) -> Vec<u16> {
+    let nodes = if args.active_active {
+        plan_participants("", args)
+            .into_iter()
+            .map(|p| (p.ui_port, p.api_port, p.db_port))
+            .collect()
+    } else {
+        vec![(args.port_base, args.port_base + 1000, args.db_port)]
+    };
+    nodes
+        .into_iter()
+        .flat_map(|(ui, api, db)| [ui, api].into_iter().chain(db..db + 10))
+        .collect()
+}
+
+/// Operations `start_enterprise` would perform
+pub async fn plan_start(args: &EnterpriseStartArgs, config: &mut Config) -> Result<Plan> {
+    let mut args = args.clone();
+    let launch = prepare(&mut args, config)?;
+    let mut plan = Plan::new(format!("start Enterprise cluster {}", launch.name));
+    plan.platform = args.pull.platform.clone();
+
+    if let Some(ref network) = launch.network {
+        plan.docker(network);
+    }
+    for node in &launch.nodes {
+        plan.run(images::ENTERPRISE_IMAGE, &node.run_cmd, None);
+        // Active-Active always bootstraps its participants
+        if args.containers_only && !args.active_active {
+            continue;
+        }
+        plan.push(Operation::Api {
+            method: "POST".to_string(),
+            url: format!(
+                "https://localhost:{}/v1/bootstrap/create_cluster",
+                node.api_port
+            ),
+        });
+        if let Some(ref connect) = node.connect {
+            plan.docker(connect);
+        }
+    }
+    let api_port = launch.nodes[0].api_port;
+    if args.active_active {
+        plan.push(Operation::Api {
+            method: "POST".to_string(),
+            url: format!("https://localhost:{}/v1/crdbs", api_port),
+        });
+    } else if args.create_db.is_some() && !args.containers_only {
+        plan.push(Operation::Api {
+            method: "POST".to_string(),
+            url: format!("https://localhost:{}/v1/bdbs", api_port),
+        });
+    }
+
+    plan.push(Operation::SaveState {
+        description: format!("add {} to instances.json", launch.name),
+    });
+    Ok(plan)
+}
+
+async fn start_active_active(
+    args: EnterpriseStartArgs,
+    launch: Launch,
+    mut config: Config,
+    progress: Progress,
+    verbose: bool,
+) -> Result<()> {
+    let name = launch.name;
+    let participants = plan_participants(&name, &args);
+    let network = format!("{}-network", name);
+    let crdb_name = args.create_db.clone().unwrap_or_else(|| "crdb".to_string());
+    let policy = RetryPolicy::resolve(args.retries)?;
+
+    if let Some(ref create) = launch.network {
+        create
+            .execute()
+            .await
+            .with_context(|| format!("Failed to create network {}", network))?;
+    }
+
+    // Each participant is a full single-node cluster; a failure rolls back all of them
+    let mut started: Vec<docker_wrapper::RedisEnterpriseConnectionInfo> = Vec::new();
+    for node in &launch.nodes {
+        progress.emit(ProgressEvent::BootstrappingEnterprise {
+            cluster: node.cluster.clone(),
+        });
+
+        match start_node(node, &policy).await {
+            Ok(info) => started.push(info),
+            Err(e) => {
+                for info in &started {
+                    RmCommand::new(&info.container_name)
+                        .force()
+                        .volumes()
+                        .execute()
+                        .await
+                        .ok();
+                }
+                RmCommand::new(format!("{}-enterprise", node.name))
+                    .force()
+                    .execute()
+                    .await
+                    .ok();
+                NetworkRmCommand::new(&network).execute().await.ok();
+                return Err(e.context(format!("Failed to start Enterprise cluster {}", node.name)));
+            }
+        }
+    }
+
+    let containers: Vec<String> = started.iter().map(|i| i.container_name.clone()).collect();
+
+    let username = started[0].username.clone();
+    let password = started[0].password.clone();
+
+    // Clusters take minutes to boot, so keep them if only the CRDB step fails
+    progress.emit(ProgressEvent::CreatingDatabase {
+        database: crdb_name.clone(),
+    });
+    let crdb_created =
+        match create_crdb(&participants, &crdb_name, &username, &password, verbose).await {
+            Ok(()) => true,
+            Err(e) => {
+                println!(
+                    "{} Failed to create Active-Active database: {:#}",
+                    "Warning:".yellow(),
+                    e
+                );
+                println!(
+                    "  Retry with: redis-up enterprise api {} POST /v1/crdbs -d @crdb.json",
+                    name
+                );
+                false
+            }
+        };
+
+    let first = &participants[0];
+    let mut metadata = HashMap::new();
+    metadata.insert("nodes".to_string(), serde_json::json!(1));
+    metadata.insert("persist".to_string(), serde_json::json!(args.persist));
+    metadata.insert("active_active".to_string(), serde_json::json!(true));
+    metadata.insert("network".to_string(), serde_json::json!(network));
+    metadata.insert(
+        "participants".to_string(),
+        serde_json::json!(participants
+            .iter()
+            .map(|p| serde_json::json!({
+                "name": p.name,
+                "fqdn": p.fqdn,
+                "ui_port": p.ui_port,
+                "api_port": p.api_port,
+                "db_port": p.db_port,
+            }))
+            .collect::<Vec<_>>()),
+    );
+    metadata.insert("ui_port".to_string(), serde_json::json!(first.ui_port));
+    metadata.insert("api_port".to_string(), serde_json::json!(first.api_port));
+    metadata.insert("cluster_name".to_string(), serde_json::json!(first.fqdn));
+    metadata.insert(
+        "container_name".to_string(),
+        serde_json::json!(containers[0].clone()),
+    );
+    if crdb_created {
+        metadata.insert("crdb_name".to_string(), serde_json::json!(crdb_name));
+        metadata.insert("database_name".to_string(), serde_json::json!(crdb_name));
+        metadata.insert(
+            "database_port".to_string(),
+            serde_json::json!(first.db_port),
+        );
+    }
+    if let Some(ref memory) = args.memory {
+        metadata.insert("memory".to_string(), serde_json::json!(memory));
+    }
+    limits::record_limits(&mut metadata, &args.limits);
+    metadata.insert("username".to_string(), serde_json::json!(username));
+
+    let mut ports = Vec::new();
+    for participant in &participants {
+        ports.extend([
+            participant.ui_port,
+            participant.api_port,
+            participant.db_port,
+        ]);
+    }
+
+    let mut instance = InstanceInfo {
+        name: name.clone(),
+        instance_type: InstanceType::Enterprise,
+        created_at: chrono::Utc::now().to_rfc3339(),
+        ports,
+        containers,
+        connection_info: ConnectionInfo {
+            host: "localhost".to_string(),
+            port: first.db_port,
+            password: Some(password.clone()),
+            url: if crdb_created {
+                format!("redis://localhost:{}", first.db_port)
+            } else {
+                "<pending database creation>".to_string()
+            },
+            additional_ports: {
+                let mut ports = HashMap::new();
+                ports.insert("ui".to_string(), first.ui_port);
+                ports.insert("api".to_string(), first.api_port);
+                ports
+            },
+            ipv6_host: None,
+            ipv6_url: None,
+        },
+        metadata,
+        started_at: None,
+        last_seen: None,
+    };
+
+    annotate::set_description(&mut instance, args.description.as_deref());
+    history::record_start(&instance);
+    config.add_instance(instance);
+    config.save()?;
+
+    progress.emit(ProgressEvent::Ready { name: name.clone() });
+    progress.finish();
+
+    println!(
+        "\n{} Redis Enterprise Active-Active deployment started successfully!",
+        "Success:".green().bold()
+    );
+    println!("\n{}", "Participants:".bold().underline());
+    for participant in &participants {
+        println!(
+            "  {} UI https://localhost:{}, API https://localhost:{}",
+            format!("{}:", participant.fqdn).cyan(),
+            participant.ui_port,
+            participant.api_port
+        );
+    }
+    println!("  {} {}", "Username:".cyan(), username);
+    println!("  {} {}", "Password:".cyan(), password);
+
+    if crdb_created {
+        println!("\n{}", "Active-Active Database:".bold().underline());
+        println!("  {} {}", "Name:".cyan(), crdb_name);
+        for participant in &participants {
+            println!(
+                "  {} redis-cli -p {}",
+                format!("{}:", participant.fqdn).yellow(),
+                participant.db_port
+            );
+        }
+    }
+
+    println!("\n{}", "Quick Commands:".bold().underline());
+    println!("  {} redis-up enterprise stop {}", "Stop:".yellow(), name);
+    println!("  {} redis-up enterprise info {}", "Info:".yellow(), name);
+
+    Ok(())
+}
+
+/// Start a node and bootstrap its cluster, then join the Active-Active network if it has one
+#[tracing::instrument(name = "enterprise_bootstrap", skip_all, fields(cluster = %node.cluster))]
+async fn start_node(
+    node: &Node,
+    policy: &RetryPolicy,
+) -> Result<docker_wrapper::RedisEnterpriseConnectionInfo> {
+    let info = connection_info(node);
+    let api = EnterpriseApi::new(node.api_port, &info.username, &info.password)?;
+    policy
+        .run(
+            &format!("Bootstrap of {}", node.name),
+            || async {
+                server::run_container(&node.run_cmd, None).await?;
+                bootstrap(&api, &node.cluster).await
+            },
+            || async {
+                remove_container(&info.container_name).await;
+                remove_volumes(&node.name).await;
+            },
+        )
+        .await?;
+
+    if let Some(ref connect) = node.connect {
+        connect
+            .execute()
+            .await
+            .with_context(|| format!("Failed to attach {} to its network", info.container_name))?;
+    }
+
+    Ok(info)
+}
+
+/// Addresses and the bootstrap credentials of a node's cluster
+fn connection_info(node: &Node) -> docker_wrapper::RedisEnterpriseConnectionInfo {
+    docker_wrapper::RedisEnterpriseConnectionInfo {
+        name: node.name.clone(),
+        container_name: format!("{}-enterprise", node.name),
+        cluster_name: node.cluster.clone(),
+        ui_url: format!("https://localhost:{}", node.ui_port),
+        api_url: format!("https://localhost:{}", node.api_port),
+        username: DEFAULT_ADMIN_USER.to_string(),
+        password: DEFAULT_ADMIN_PASSWORD.to_string(),
+        database_port: None,
+    }
+}
+
+/// Create a CRDB spanning every participant and wait for the task to finish
+#[tracing::instrument(name = "crdb_create", skip(participants, username, password, verbose))]
+async fn create_crdb(
+    participants: &[Participant],
+    crdb_name: &str,
+    username: &str,
+    password: &str,
+    verbose: bool,
+) -> Result<()> {
+    let instances: Vec<serde_json::Value> = participants
+        .iter()
+        .map(|p| {
+            serde_json::json!({
+                "cluster": {
+                    "url": format!("https://{}:9443", p.fqdn),
+                    "credentials": { "username": username, "password": password },
+                    "name": p.fqdn,
+                },
+                "db_config": { "port": p.db_port },
+                "compression": 6,
+            })
+        })
+        .collect();
+    let body = serde_json::json!({
+        "name": crdb_name,
+        "default_db_config": {
+            "name": crdb_name,
+            "memory_size": 100 * 1024 * 1024,
+            "replication": false,
+        },
+        "instances": instances,
+        "encryption": false,
+    });
+
+    for participant in participants {
+        let api = EnterpriseApi::new(participant.api_port, username, password)?;
+        wait_for_cluster(&api)
+            .await
+            .with_context(|| format!("{} is not ready", participant.fqdn))?;
+    }
+
+    let api = EnterpriseApi::new(participants[0].api_port, username, password)?;
+    let response = api
+        .request(reqwest::Method::POST, "/v1/crdbs", Some(body))
+        .await?;
+    let status = response.status();
+    let task: serde_json::Value = response
+        .json()
+        .await
+        .context("Failed to parse CRDB response")?;
+    if !status.is_success() {
+        anyhow::bail!("CRDB creation rejected ({}): {}", status, task);
+    }
+
+    let task_id = task
+        .get("id")
+        .and_then(|v| v.as_str())
+        .context("CRDB response did not include a task id")?;
+    if verbose {
+        println!("  {} CRDB task {}", "Waiting".cyan(), task_id);
+    }
+
+    for _ in 0..90 {
+        let task: serde_json::Value = api
+            .request(
+                reqwest::Method::GET,
+                &format!("/v1/crdb_tasks/{}", task_id),
+                None,
+            )
+            .await?
+            .json()
+            .await
+            .context("Failed to parse CRDB task status")?;
+
+        match task.get("status").and_then(|v| v.as_str()) {
+            Some("finished") => return Ok(()),
+            Some("failed") => anyhow::bail!(
+                "CRDB task failed: {}",
+                task.get("errors").cloned().unwrap_or_default()
+            ),
+            _ => tokio::time::sleep(Duration::from_secs(2)).await,
+        }
+    }
+
+    Err(Failure::Timeout(format!("Timed out waiting for CRDB task {}", task_id)).into())
+}
+
+/// How long a new node's API gets to start answering
+const API_READY_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Attempts at the bootstrap request; the pause between them grows by 5s each time
+const BOOTSTRAP_ATTEMPTS: u32 = 3;
+
+/// Create a single-node cluster on a freshly started node and wait until it is active
+async fn bootstrap(api: &EnterpriseApi, cluster: &str) -> Result<()> {
+    wait_for_api(api).await?;
+
+    let mut attempt = 1;
+    loop {
+        let reason = match api
+            .request(
+                reqwest::Method::POST,
+                "/v1/bootstrap/create_cluster",
+                Some(bootstrap_body(cluster)),
+            )
+            .await
+        {
+            // A conflict means an earlier attempt got through
+            Ok(response)
+                if response.status().is_success()
+                    || response.status() == reqwest::StatusCode::CONFLICT =>
+            {
+                break
+            }
+            Ok(response) if response.status() == reqwest::StatusCode::BAD_REQUEST => {
+                bail!(
+                    "Bootstrap rejected: {}",
+                    response.text().await.unwrap_or_default()
+                );
+            }
+            Ok(response) => format!("API returned {}", response.status()),
+            Err(e) => format!("{:#}", e),
+        };
+        if attempt == BOOTSTRAP_ATTEMPTS {
+            bail!("Bootstrap failed after {} attempts: {}", attempt, reason);
+        }
+        tokio::time::sleep(Duration::from_secs(5 * u64::from(attempt))).await;
+        attempt += 1;
+    }
+
+    wait_for_cluster(api).await
+}
+
+/// Poll until the API answers at all; before bootstrap it reports `no_cluster`
+async fn wait_for_api(api: &EnterpriseApi) -> Result<()> {
+    let deadline = tokio::time::Instant::now() + API_READY_TIMEOUT;
+    while tokio::time::Instant::now() < deadline {
+        if let Ok(response) = api.request(reqwest::Method::GET, "/", None).await {
+            let success = response.status().is_success();
+            let text = response.text().await.unwrap_or_default();
+            if success || text.contains("no_cluster") || text.contains("error_code") {
+                // Give it a bit more time to fully initialize
+                tokio::time::sleep(Duration::from_secs(2)).await;
+                return Ok(());
+            }
+        }
+        tokio::time::sleep(Duration::from_secs(2)).await;
+    }
+
+    Err(Failure::Timeout(format!(
+        "Enterprise API at {} did not answer within {}s",
+        api.base_url,
+        API_READY_TIMEOUT.as_secs()
+    ))
+    .into())
+}
+
+fn bootstrap_body(cluster: &str) -> serde_json::Value {
+    serde_json::json!({
+        "action": "create_cluster",
+        "cluster": { "name": cluster },
+        "node": {
+            "paths": {
+                "persistent_path": "/var/opt/redislabs/persist",
+                "ephemeral_path": "/var/opt/redislabs/tmp",
+            },
+        },
+        "credentials": {
+            "username": DEFAULT_ADMIN_USER,
+            "password": DEFAULT_ADMIN_PASSWORD,
+        },
+    })
+}
+
+/// How long a freshly bootstrapped cluster gets to report every node active
+const READY_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Attempts at creating the `--create-db` database, and the pause between them
+const CREATE_DB_ATTEMPTS: u32 = 6;
+const CREATE_DB_DELAY: Duration = Duration::from_secs(5);
+
+/// Poll the API until the cluster answers and every node is active
+#[tracing::instrument(name = "readiness_wait", skip_all, fields(api = %api.base_url))]
+async fn wait_for_cluster(api: &EnterpriseApi) -> Result<()> {
+    let deadline = tokio::time::Instant::now() + READY_TIMEOUT;
+    let mut last = "no answer yet".to_string();
+    while tokio::time::Instant::now() < deadline {
+        match api.request(reqwest::Method::GET, "/v1/nodes", None).await {
+            Ok(response) if response.status().is_success() => {
+                let nodes: Vec<serde_json::Value> = response.json().await.unwrap_or_default();
+                let pending = nodes
+                    .iter()
+                    .filter(|node| node.get("status").and_then(|v| v.as_str()) != Some("active"))
+                    .count();
+                if !nodes.is_empty() && pending == 0 {
+                    return Ok(());
+                }
+                last = format!("{} of {} node(s) not active", pending, nodes.len());
+            }
+            Ok(response) => last = format!("API returned {}", response.status()),
+            Err(e) => last = format!("{:#}", e),
+        }
+        tokio::time::sleep(Duration::from_secs(2)).await;
+    }
+
+    Err(Failure::Timeout(format!(
+        "Cluster not ready after {}s: {}",
+        READY_TIMEOUT.as_secs(),
+        last
+    ))
+    .into())
+}
+
+fn database_body(db_name: &str, port: u16) -> serde_json::Value {
+    serde_json::json!({
+        "name": db_name,
+        "port": port,
+        "memory_size": 100 * 1024 * 1024,
+    })
+}
+
+/// Whether a rejected database request may succeed once the cluster settles
+fn retryable(status: reqwest::StatusCode) -> bool {
+    status.is_server_error()
+        || status == reqwest::StatusCode::CONFLICT
+        || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+}
+
+/// Create a database once the cluster is ready, retrying while it turns requests away
+#[tracing::instrument(name = "database_create", skip(api, progress))]
+async fn create_database(
+    api: &EnterpriseApi,
+    db_name: &str,
+    port: u16,
+    progress: &Progress,
+) -> Result<()> {
+    wait_for_cluster(api).await?;
+
+    let mut attempt = 1;
+    loop {
+        progress.emit(ProgressEvent::CreatingDatabase {
+            database: db_name.to_string(),
+        });
+        let reason = match api
+            .request(
+                reqwest::Method::POST,
+                "/v1/bdbs",
+                Some(database_body(db_name, port)),
+            )
+            .await
+        {
+            Ok(response) if response.status().is_success() => return Ok(()),
+            Ok(response) => {
+                let status = response.status();
+                let body = response.text().await.unwrap_or_default();
+                if !retryable(status) {
+                    anyhow::bail!("database creation rejected ({}): {}", status, body);
+                }
+                format!("API returned {}", status)
+            }
+            Err(e) => format!("{:#}", e),
+        };
+
+        // A request that timed out may still have created the database
+        if database_exists(api, db_name).await {
+            return Ok(());
+        }
+        if attempt == CREATE_DB_ATTEMPTS {
+            anyhow::bail!("gave up after {} attempts: {}", attempt, reason);
+        }
+        progress.emit(ProgressEvent::RetryingDatabase {
+            database: db_name.to_string(),
+            attempt: attempt + 1,
+            attempts: CREATE_DB_ATTEMPTS,
+            reason,
+        });
+        tokio::time::sleep(CREATE_DB_DELAY).await;
+        attempt += 1;
+    }
+}
+
+async fn database_exists(api: &EnterpriseApi, db_name: &str) -> bool {
+    let Ok(response) = api.request(reqwest::Method::GET, "/v1/bdbs", None).await else {
+        return false;
+    };
+    let databases: Vec<serde_json::Value> = response.json().await.unwrap_or_default();
+    databases
+        .iter()
+        .any(|db| db.get("name").and_then(|v| v.as_str()) == Some(db_name))
+}
+
+/// Remove the volumes a node of a cluster mounts with `--persist`
+async fn remove_volumes(cluster: &str) {
+    for suffix in ["persistent", "ephemeral"] {
+        VolumeRmCommand::new(format!("{}-{}", cluster, suffix))
+            .force()
+            .execute()
+            .await
+            .ok();
+    }
+}
+
+/// Node names whose volumes belong to an instance
+pub fn cluster_names(instance: &InstanceInfo) -> Vec<String> {
+    match instance
+        .metadata
+        .get("participants")
+        .and_then(|v| v.as_array())
+    {
+        Some(participants) => participants
+            .iter()
+            .filter_map(|p| p.get("name")?.as_str().map(String::from))
+            .collect(),
+        None => vec![instance.name.clone()],
+    }
+}
+
 async fn stop_enterprise(args: StopArgs, verbose: bool) -> Result<()> {
     let mut config = Config::load()?;
 
@@ -265,33 +1105,29 @@ async fn stop_enterprise(args: StopArgs, verbose: bool) -> Result<()> {
         );
     }
 
-    // Stop and remove containers
-    use docker_wrapper::{RmCommand, StopCommand};
+    // Stop and remove containers, ignoring errors for already stopped ones
+    use docker_wrapper::RmCommand;
+    shutdown::stop_containers(&instance, args.timeout, verbose).await;
     for container in &instance.containers {
-        StopCommand::new(container).execute().await.ok(); // Ignore errors for already stopped containers
-
         RmCommand::new(container).force().execute().await.ok();
     }
 
-    // Remove volumes if they exist
-    use docker_wrapper::VolumeRmCommand;
-    let persistent_volume = format!("{}-persistent", name);
-    let ephemeral_volume = format!("{}-ephemeral", name);
-
-    VolumeRmCommand::new(&persistent_volume)
-        .force()
-        .execute()
-        .await
-        .ok();
+    // Remove volumes if they exist (one pair per cluster for Active-Active)
+    let keep_data = volume::keep_data(&instance, args.keep_data);
+    if !keep_data {
+        for cluster in cluster_names(&instance) {
+            remove_volumes(&cluster).await;
+        }
+    }
 
-    VolumeRmCommand::new(&ephemeral_volume)
-        .force()
-        .execute()
-        .await
-        .ok();
+    if let Some(network) = instance.metadata.get("network").and_then(|v| v.as_str()) {
+        NetworkRmCommand::new(network).execute().await.ok();
+    }
 
     // Remove from config
-    config.instances.remove(&name);
+    if let Some(removed) = config.remove_instance(&name) {
+        history::record_stop(&removed);
+    }
     config.save()?;
 
     println!(
@@ -299,12 +1135,13 @@ async fn stop_enterprise(args: StopArgs, verbose: bool) -> Result<()> {
         "Success:".green().bold(),
         name
     );
+    volume::report_kept(&name, keep_data);
 
     Ok(())
 }
 
-async fn info_enterprise(args: InfoArgs, verbose: bool) -> Result<()> {
-    let config = Config::load()?;
+async fn info_enterprise(args: InfoArgs, databases: bool, verbose: bool) -> Result<()> {
+    let mut config = Config::load()?;
 
     // Find the instance
     let name = args.name.or_else(|| {
@@ -315,6 +1152,7 @@ async fn info_enterprise(args: InfoArgs, verbose: bool) -> Result<()> {
 
     let name = name.context("No Enterprise instance found. Specify a name or start one first.")?;
 
+    let uptime_lines = uptime::info_lines(&mut config, &name).await;
     let instance = config
         .instances
         .get(&name)
@@ -323,6 +1161,12 @@ async fn info_enterprise(args: InfoArgs, verbose: bool) -> Result<()> {
     println!("{}", "Redis Enterprise Information".bold().underline());
     println!("{} {}", "Name:".cyan(), instance.name);
     println!("{} {}", "Created:".cyan(), instance.created_at);
+    for (label, value) in &uptime_lines {
+        println!("{} {}", format!("{}:", label).cyan(), value);
+    }
+    for (label, value) in annotate::annotations(instance) {
+        println!("{} {}", format!("{}:", label).cyan(), value);
+    }
     println!(
         "{} {}",
         "Cluster Name:".cyan(),
@@ -362,6 +1206,43 @@ async fn info_enterprise(args: InfoArgs, verbose: bool) -> Result<()> {
         }
     }
 
+    if let Some(participants) = instance
+        .metadata
+        .get("participants")
+        .and_then(|v| v.as_array())
+    {
+        println!("\n{}", "Active-Active:".bold().underline());
+        println!(
+            "  {} {}",
+            "CRDB:".cyan(),
+            instance
+                .metadata
+                .get("crdb_name")
+                .and_then(|v| v.as_str())
+                .unwrap_or("<not created>")
+        );
+        for participant in participants {
+            let field = |key: &str| participant.get(key).cloned().unwrap_or_default();
+            println!(
+                "  {} UI https://localhost:{}, API https://localhost:{}, DB port {}",
+                format!("{}:", field("fqdn").as_str().unwrap_or("unknown")).cyan(),
+                field("ui_port"),
+                field("api_port"),
+                field("db_port")
+            );
+        }
+    }
+
+    enterprise_status::print(instance, databases).await;
+
+    let limit_lines = limits::limit_lines(&instance.metadata);
+    if !limit_lines.is_empty() {
+        println!("\n{}", "Limits:".bold().underline());
+        for (label, value) in limit_lines {
+            println!("  {} {}", format!("{}:", label).cyan(), value);
+        }
+    }
+
     if verbose {
         println!("\n{}", "Containers:".bold().underline());
         for container in &instance.containers {
@@ -389,3 +1270,202 @@ async fn info_enterprise(args: InfoArgs, verbose: bool) -> Result<()> {
 
     Ok(())
 }
+
+/// Admin credentials clusters are bootstrapped with
+const DEFAULT_ADMIN_USER: &str = "admin@redis.local";
+const DEFAULT_ADMIN_PASSWORD: &str = "Redis123!";
+
+/// REST API access for a managed Enterprise instance
+pub struct EnterpriseApi {
+    client: reqwest::Client,
+    base_url: String,
+    username: String,
+    password: String,
+}
+
+impl EnterpriseApi {
+    /// Build an API client for the API published on a local port
+    pub fn new(api_port: u16, username: &str, password: &str) -> Result<Self> {
+        // Enterprise ships with a self-signed certificate, on a local port a
+        // corporate HTTP(S)_PROXY can't reach
+        let client = reqwest::Client::builder()
+            .no_proxy()
+            .danger_accept_invalid_certs(true)
+            .timeout(std::time::Duration::from_secs(30))
+            .build()
+            .context("Failed to create HTTP client")?;
+
+        Ok(Self {
+            client,
+            base_url: format!("https://localhost:{}", api_port),
+            username: username.to_string(),
+            password: password.to_string(),
+        })
+    }
+
+    /// Build an API client from the instance's stored address and credentials
+    pub fn for_instance(instance: &InstanceInfo) -> Result<Self> {
+        let api_port = instance
+            .connection_info
+            .additional_ports
+            .get("api")
+            .copied()
+            .context("Instance has no recorded API port")?;
+        let username = instance
+            .metadata
+            .get("username")
+            .and_then(|v| v.as_str())
+            .unwrap_or(DEFAULT_ADMIN_USER);
+        let password = instance
+            .connection_info
+            .password
+            .as_deref()
+            .unwrap_or_default();
+
+        Self::new(api_port, username, password)
+    }
+
+    /// Override the stored credentials
+    pub fn credentials(mut self, username: Option<String>, password: Option<String>) -> Self {
+        if let Some(username) = username {
+            self.username = username;
+        }
+        if let Some(password) = password {
+            self.password = password;
+        }
+        self
+    }
+
+    /// Send a request to an API path such as `/v1/bdbs`
+    pub async fn request(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        body: Option<serde_json::Value>,
+    ) -> Result<reqwest::Response> {
+        let url = format!("{}/{}", self.base_url, path.trim_start_matches('/'));
+        let mut request = self
+            .client
+            .request(method.clone(), &url)
+            .basic_auth(&self.username, Some(&self.password));
+        if let Some(body) = body {
+            request = request.json(&body);
+        }
+
+        request
+            .send()
+            .await
+            .with_context(|| format!("{} {} failed", method, url))
+    }
+}
+
+async fn api_enterprise(args: EnterpriseApiArgs, verbose: bool) -> Result<()> {
+    let config = Config::load()?;
+    let instance = config
+        .instances
+        .get(&args.name)
+        .context(format!("Enterprise instance '{}' not found", args.name))?;
+
+    if instance.instance_type != InstanceType::Enterprise {
+        anyhow::bail!(
+            "Instance '{}' is not a Redis Enterprise instance",
+            args.name
+        );
+    }
+
+    let method: reqwest::Method = args
+        .method
+        .to_uppercase()
+        .parse()
+        .with_context(|| format!("Invalid HTTP method: {}", args.method))?;
+
+    let body = match args.data {
+        Some(ref data) => {
+            let text = match data.strip_prefix('@') {
+                Some(path) => std::fs::read_to_string(path)
+                    .with_context(|| format!("Failed to read request body from {}", path))?,
+                None => data.clone(),
+            };
+            Some(serde_json::from_str(&text).context("Request body is not valid JSON")?)
+        }
+        None => None,
+    };
+
+    let api = EnterpriseApi::for_instance(instance)?.credentials(args.username, args.password);
+    if verbose {
+        println!(
+            "{} {} {}{}",
+            "Request:".cyan(),
+            method,
+            api.base_url,
+            args.path
+        );
+    }
+
+    let response = api.request(method, &args.path, body).await?;
+    let status = response.status();
+    let text = response
+        .text()
+        .await
+        .context("Failed to read response body")?;
+
+    if verbose {
+        println!("{} {}", "Status:".cyan(), status);
+    }
+
+    match serde_json::from_str::<serde_json::Value>(&text) {
+        Ok(json) if !args.raw => println!("{}", serde_json::to_string_pretty(&json)?),
+        _ => println!("{}", text),
+    }
+
+    if !status.is_success() {
+        anyhow::bail!("API request failed with status {}", status);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[test]
+    fn test_plan_participants() {
+        let cli = crate::cli::Cli::parse_from([
+            "redis-up",
+            "enterprise",
+            "start",
+            "--active-active",
+            "--participants",
+            "3",
+        ]);
+        let args = match cli.command {
+            Some(crate::cli::Commands::Enterprise {
+                action: EnterpriseAction::Start(args),
+            }) => args,
+            _ => unreachable!(),
+        };
+
+        let participants = plan_participants("aa", &args);
+        assert_eq!(participants.len(), 3);
+        assert_eq!(
+            participants[2],
+            Participant {
+                name: "aa-3".to_string(),
+                fqdn: "aa-3.redis.local".to_string(),
+                ui_port: 8445,
+                api_port: 9445,
+                db_port: 12020,
+            }
+        );
+    }
+
+    #[test]
+    fn test_retryable() {
+        assert!(retryable(reqwest::StatusCode::SERVICE_UNAVAILABLE));
+        assert!(retryable(reqwest::StatusCode::CONFLICT));
+        assert!(!retryable(reqwest::StatusCode::BAD_REQUEST));
+        assert!(!retryable(reqwest::StatusCode::UNAUTHORIZED));
+    }
+}
@@ -0,0 +1,269 @@
+//! Live cluster status for `enterprise info`
+//!
+//! When the cluster's REST API answers, `info` shows what the cluster reports
+//! (nodes, databases, shard placement, license) rather than only what redis-up
+//! recorded at start. An unreachable API is not an error: the recorded details
+//! are still printed, with a note saying why the live ones are missing.
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use colored::*;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer};
+use std::time::Duration;
+
+use crate::commands::enterprise::EnterpriseApi;
+use crate::config::InstanceInfo;
+
+/// How long to wait on the REST API before falling back to recorded details
+const API_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Deserialize)]
+struct Node {
+    #[serde(deserialize_with = "id")]
+    uid: String,
+    #[serde(default)]
+    addr: String,
+    #[serde(default)]
+    status: String,
+    #[serde(default)]
+    shard_count: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct Database {
+    #[serde(deserialize_with = "id")]
+    uid: String,
+    name: String,
+    #[serde(default)]
+    status: String,
+    #[serde(default)]
+    port: Option<u16>,
+    #[serde(default)]
+    authentication_redis_pass: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Shard {
+    #[serde(deserialize_with = "id")]
+    bdb_uid: String,
+    #[serde(deserialize_with = "id")]
+    node_uid: String,
+    #[serde(default)]
+    role: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct License {
+    #[serde(default)]
+    expired: bool,
+    #[serde(default)]
+    expiration_date: Option<String>,
+    #[serde(default)]
+    shards_limit: Option<u64>,
+}
+
+/// What the cluster reports about itself
+struct Status {
+    nodes: Vec<Node>,
+    databases: Vec<Database>,
+    shards: Vec<Shard>,
+    license: Option<License>,
+}
+
+/// Print the live status section, or a note when the API can't be reached
+pub async fn print(instance: &InstanceInfo, databases: bool) {
+    println!("\n{}", "Cluster Status:".bold().underline());
+
+    let status = match EnterpriseApi::for_instance(instance) {
+        Ok(api) => tokio::time::timeout(API_TIMEOUT, fetch(&api))
+            .await
+            .unwrap_or_else(|_| Err(anyhow::anyhow!("no answer within {:?}", API_TIMEOUT))),
+        Err(e) => Err(e),
+    };
+    let status = match status {
+        Ok(status) => status,
+        Err(e) => {
+            println!("  {} {:#}", "Unreachable:".yellow(), e);
+            println!(
+                "  {}",
+                "Showing the details recorded at start; is the cluster running?".dimmed()
+            );
+            return;
+        }
+    };
+
+    println!("  {}", "Nodes:".cyan());
+    for node in &status.nodes {
+        println!(
+            "    node {:<3} {:<15} {:<8} {} shard(s)",
+            node.uid,
+            node.addr,
+            colored_status(&node.status),
+            node.shard_count
+        );
+    }
+
+    println!("  {}", "Databases:".cyan());
+    if status.databases.is_empty() {
+        println!("    <none>");
+    }
+    for db in &status.databases {
+        println!(
+            "    {:<16} {:<8} port {:<6} shards: {}",
+            db.name,
+            colored_status(&db.status),
+            db.port.map_or("-".to_string(), |port| port.to_string()),
+            placement(&status.shards, &db.uid)
+        );
+    }
+
+    if let Some(ref license) = status.license {
+        println!(
+            "  {} {}",
+            "License:".cyan(),
+            license_line(license, Utc::now())
+        );
+    }
+
+    if databases {
+        println!("\n{}", "Database Endpoints:".bold().underline());
+        if status.databases.is_empty() {
+            println!("  <none>");
+        }
+        for db in &status.databases {
+            let Some(port) = db.port else {
+                continue;
+            };
+            let password = db.authentication_redis_pass.as_deref().unwrap_or_default();
+            println!("  {} localhost:{}", format!("{}:", db.name).cyan(), port);
+            if password.is_empty() {
+                println!("    {} redis://localhost:{}", "URL:".cyan(), port);
+            } else {
+                println!("    {} {}", "Password:".cyan(), password);
+                println!(
+                    "    {} redis://default:{}@localhost:{}",
+                    "URL:".cyan(),
+                    password,
+                    port
+                );
+            }
+        }
+    }
+}
+
+async fn fetch(api: &EnterpriseApi) -> Result<Status> {
+    let (nodes, databases, shards, license) = tokio::join!(
+        get::<Vec<Node>>(api, "/v1/nodes"),
+        get::<Vec<Database>>(api, "/v1/bdbs"),
+        get::<Vec<Shard>>(api, "/v1/shards"),
+        get::<License>(api, "/v1/license"),
+    );
+    Ok(Status {
+        nodes: nodes?,
+        databases: databases?,
+        shards: shards?,
+        // Older clusters and restricted users can't read the license
+        license: license.ok(),
+    })
+}
+
+async fn get<T: DeserializeOwned>(api: &EnterpriseApi, path: &str) -> Result<T> {
+    let response = api.request(reqwest::Method::GET, path, None).await?;
+    let status = response.status();
+    if !status.is_success() {
+        bail!("GET {} returned {}", path, status);
+    }
+    response
+        .json()
+        .await
+        .with_context(|| format!("Unexpected response from GET {}", path))
+}
+
+/// Where a database's shards live, e.g. `node 1 (master), node 2 (slave)`
+fn placement(shards: &[Shard], bdb_uid: &str) -> String {
+    let mut placed: Vec<(&str, &str)> = shards
+        .iter()
+        .filter(|shard| shard.bdb_uid == bdb_uid)
+        .map(|shard| (shard.node_uid.as_str(), shard.role.as_str()))
+        .collect();
+    placed.sort();
+    if placed.is_empty() {
+        return "-".to_string();
+    }
+    placed
+        .iter()
+        .map(|(node, role)| format!("node {} ({})", node, role))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn license_line(license: &License, now: DateTime<Utc>) -> String {
+    let expiry = license
+        .expiration_date
+        .as_deref()
+        .and_then(|date| DateTime::parse_from_rfc3339(date).ok())
+        .map(|date| date.with_timezone(&Utc));
+    let mut line = match (license.expired, expiry) {
+        (true, _) => "expired".red().to_string(),
+        (false, Some(expiry)) => {
+            let days = (expiry - now).num_days();
+            let text = format!("expires {} (in {} days)", expiry.format("%Y-%m-%d"), days);
+            if days < 7 {
+                text.yellow().to_string()
+            } else {
+                text
+            }
+        }
+        (false, None) => "valid".to_string(),
+    };
+    if let Some(limit) = license.shards_limit {
+        line.push_str(&format!(", {} shard limit", limit));
+    }
+    line
+}
+
+fn colored_status(status: &str) -> ColoredString {
+    match status {
+        "active" => status.green(),
+        "" => "unknown".normal(),
+        _ => status.yellow(),
+    }
+}
+
+/// The API returns some ids as numbers and others as strings
+fn id<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<String, D::Error> {
+    Ok(match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::String(id) => id,
+        other => other.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_placement_and_license() {
+        let shards: Vec<Shard> = serde_json::from_str(
+            r#"[{"uid": "2", "bdb_uid": 1, "node_uid": "2", "role": "slave"},
+                {"uid": "1", "bdb_uid": 1, "node_uid": "1", "role": "master"},
+                {"uid": "3", "bdb_uid": 2, "node_uid": "1", "role": "master"}]"#,
+        )
+        .unwrap();
+        assert_eq!(placement(&shards, "1"), "node 1 (master), node 2 (slave)");
+        assert_eq!(placement(&shards, "9"), "-");
+
+        let license: License = serde_json::from_str(
+            r#"{"expired": false, "expiration_date": "2026-11-16T00:00:00Z", "shards_limit": 4}"#,
+        )
+        .unwrap();
+        let now = DateTime::parse_from_rfc3339("2026-10-17T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(
+            license_line(&license, now),
+            "expires 2026-11-16 (in 30 days), 4 shard limit"
+        );
+    }
+}
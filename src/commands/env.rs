@@ -0,0 +1,240 @@
+//! Connection details as environment variables
+
+use anyhow::{Context, Result};
+use clap::parser::ValueSource;
+use clap::ArgMatches;
+use colored::*;
+use std::fs;
+
+use crate::cli::{EnvArgs, EnvFormat};
+use crate::commands::{client, sentinel};
+use crate::config::{Config, InstanceInfo, InstanceType};
+
+/// Refuse the global `--prefix` on `env`, where it would look like a variable prefix
+///
+/// `--prefix` names instances, so `env cache --prefix CACHE_` would otherwise
+/// write unprefixed variables without a word. A prefix from `REDIS_UP_PREFIX`
+/// is left alone.
+pub fn check_args(matches: &ArgMatches) -> Result<()> {
+    let Some(("env", _)) = matches.subcommand() else {
+        return Ok(());
+    };
+    if matches.value_source("prefix") == Some(ValueSource::CommandLine) {
+        anyhow::bail!(
+            "--prefix is the global instance-name prefix; use --var-prefix to prefix the variable names (redis-up env NAME --var-prefix CACHE_)"
+        );
+    }
+    Ok(())
+}
+
+pub async fn handle_env(args: EnvArgs) -> Result<()> {
+    let config = Config::load()?;
+    let instance = config
+        .get_instance(&args.name)
+        .with_context(|| format!("Instance '{}' not found", args.name))?;
+
+    let prefix = args.var_prefix.unwrap_or_default();
+    let vars: Vec<(String, String)> = variables(instance)?
+        .into_iter()
+        .map(|(key, value)| (format!("{}{}", prefix, key), value))
+        .collect();
+
+    let Some(path) = args.write else {
+        let format = args.format.unwrap_or(EnvFormat::Shell);
+        for (key, value) in &vars {
+            println!("{}", format_line(format, key, value));
+        }
+        return Ok(());
+    };
+
+    let format = args.format.unwrap_or(EnvFormat::Dotenv);
+    let current = if path.exists() {
+        fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?
+    } else {
+        String::new()
+    };
+    let (patched, added, updated) = patch(&current, &vars, format);
+    if patched == current {
+        println!("{} {} is up to date", "Info:".blue(), path.display());
+        return Ok(());
+    }
+    fs::write(&path, patched).with_context(|| format!("Failed to write {}", path.display()))?;
+    println!(
+        "{} {} ({} added, {} updated)",
+        "Wrote:".green(),
+        path.display(),
+        added,
+        updated
+    );
+    Ok(())
+}
+
+/// Set the variables in dotenv content, returning it with (added, updated) counts
+///
+/// Existing assignments are rewritten in place and any later duplicates
+/// dropped; missing ones are appended. Comments and other lines are kept.
+fn patch(content: &str, vars: &[(String, String)], format: EnvFormat) -> (String, usize, usize) {
+    let mut seen = Vec::new();
+    let mut updated = 0;
+    let mut lines = Vec::new();
+
+    for line in content.lines() {
+        let key = line
+            .trim_start()
+            .trim_start_matches("export ")
+            .split_once('=')
+            .map(|(key, _)| key.trim());
+        match key.and_then(|key| vars.iter().find(|(k, _)| k == key)) {
+            Some((key, _)) if seen.contains(key) => {}
+            Some((key, value)) => {
+                let new = format_line(format, key, value);
+                if new != line {
+                    updated += 1;
+                }
+                lines.push(new);
+                seen.push(key.clone());
+            }
+            None => lines.push(line.to_string()),
+        }
+    }
+
+    let mut added = 0;
+    for (key, value) in vars {
+        if !seen.contains(key) {
+            lines.push(format_line(format, key, value));
+            added += 1;
+        }
+    }
+
+    let mut patched = lines.join("\n");
+    if !patched.is_empty() {
+        patched.push('\n');
+    }
+    (patched, added, updated)
+}
+
+/// Variables an app needs to reach an instance
+///
+/// Every instance gets `REDIS_URL`, `REDIS_HOST`, `REDIS_PORT` and, when set,
+/// `REDIS_PASSWORD`. Clusters add `REDIS_CLUSTER_NODES`; sentinel setups add
+/// `REDIS_SENTINELS` and `REDIS_SENTINEL_MASTER`.
+pub fn variables(instance: &InstanceInfo) -> Result<Vec<(String, String)>> {
+    let endpoints = client::endpoints(instance);
+    let primary = endpoints
+        .first()
+        .context("Instance has no Redis endpoint")?;
+
+    let mut vars = vec![
+        ("REDIS_URL".to_string(), primary.url()),
+        ("REDIS_HOST".to_string(), primary.host.clone()),
+        ("REDIS_PORT".to_string(), primary.port.to_string()),
+    ];
+    if let Some(ref password) = primary.password {
+        vars.push(("REDIS_PASSWORD".to_string(), password.clone()));
+    }
+
+    match instance.instance_type {
+        InstanceType::Cluster => {
+            let nodes: Vec<String> = endpoints.iter().map(|e| e.to_string()).collect();
+            vars.push(("REDIS_CLUSTER_NODES".to_string(), nodes.join(",")));
+        }
+        InstanceType::Sentinel => {
+            let target = sentinel::sentinel_target(instance, None)?;
+            let sentinels: Vec<String> = target
+                .sentinels
+                .iter()
+                .map(|(host, port)| format!("{}:{}", host, port))
+                .collect();
+            vars.push(("REDIS_SENTINELS".to_string(), sentinels.join(",")));
+            vars.push(("REDIS_SENTINEL_MASTER".to_string(), target.master_name));
+        }
+        _ => {}
+    }
+
+    Ok(vars)
+}
+
+/// One variable in the requested syntax
+pub fn format_line(format: EnvFormat, key: &str, value: &str) -> String {
+    match format {
+        EnvFormat::Shell => format!("export {}='{}'", key, value.replace('\'', "'\\''")),
+        EnvFormat::Dotenv if value.contains([' ', '#', '"', '\'', '\\', '$']) => format!(
+            "{}=\"{}\"",
+            key,
+            value
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('$', "\\$")
+        ),
+        // docker --env-file takes values verbatim, quotes included
+        EnvFormat::Dotenv | EnvFormat::Docker => format!("{}={}", key, value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::Cli;
+    use clap::CommandFactory;
+
+    #[test]
+    fn test_check_args() {
+        let check = |args: &[&str]| check_args(&Cli::command().try_get_matches_from(args).unwrap());
+        let err = check(&[
+            "redis-up", "env", "cache", "--write", ".env", "--prefix", "CACHE_",
+        ])
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("--var-prefix"), "{}", err);
+        assert!(check(&[
+            "redis-up",
+            "env",
+            "cache",
+            "--write",
+            ".env",
+            "--var-prefix",
+            "CACHE_"
+        ])
+        .is_ok());
+        assert!(check(&["redis-up", "--prefix", "acme", "list"]).is_ok());
+    }
+
+    #[test]
+    fn test_format_line() {
+        assert_eq!(
+            format_line(EnvFormat::Shell, "REDIS_PASSWORD", "it's"),
+            "export REDIS_PASSWORD='it'\\''s'"
+        );
+        assert_eq!(
+            format_line(EnvFormat::Dotenv, "REDIS_PORT", "6379"),
+            "REDIS_PORT=6379"
+        );
+        assert_eq!(
+            format_line(EnvFormat::Dotenv, "REDIS_PASSWORD", "a b$c"),
+            "REDIS_PASSWORD=\"a b\\$c\""
+        );
+        assert_eq!(
+            format_line(EnvFormat::Docker, "REDIS_PASSWORD", "a b"),
+            "REDIS_PASSWORD=a b"
+        );
+    }
+
+    #[test]
+    fn test_patch() {
+        let vars = vec![
+            (
+                "CACHE_REDIS_URL".to_string(),
+                "redis://localhost:6380".to_string(),
+            ),
+            ("CACHE_REDIS_PORT".to_string(), "6380".to_string()),
+        ];
+        let current = "# app\nDEBUG=1\nCACHE_REDIS_PORT=6379\nCACHE_REDIS_PORT=6378\n";
+        let (patched, added, updated) = patch(current, &vars, EnvFormat::Dotenv);
+        assert_eq!(
+            patched,
+            "# app\nDEBUG=1\nCACHE_REDIS_PORT=6380\nCACHE_REDIS_URL=redis://localhost:6380\n"
+        );
+        assert_eq!((added, updated), (1, 1));
+        assert_eq!(patch(&patched, &vars, EnvFormat::Dotenv), (patched, 0, 0));
+    }
+}
@@ -0,0 +1,637 @@
+//! `redis-up examples`: example deployment files and their JSON schema
+//!
+//! Besides a few small files to start from, `examples` writes
+//! `kitchen-sink.yaml`, which lists every key of every deployment type with
+//! the flag's help as a comment. Keys, help and defaults come from the start
+//! commands, and every file goes through the checks `deploy` runs before it is
+//! written, so an example can't drift from what `deploy` accepts.
+//!
+//! `--format json` prints a JSON schema of deployment files instead, for
+//! editors that complete and check YAML against one.
+
+use anyhow::{Context, Result};
+use clap::CommandFactory;
+use colored::*;
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use tokio::fs;
+
+use crate::cli::{Cli, ExampleFormat};
+use crate::commands::yaml::{
+    self, BasicConfig, ClusterConfig, Deployment, DeploymentConfig, EnterpriseConfig,
+    SentinelConfig, StackConfig,
+};
+
+const KITCHEN_SINK_HEADER: &str = "\
+# Every key a deployment file takes, with one deployment per type.
+#
+# Keys are the long flags of `redis-up <type> start` and mean the same thing; a
+# key left out gets the flag's default. Uncomment what you need: the values are
+# examples, and keys whose flags conflict can't be used together.
+#
+# Generated by `redis-up examples`; `redis-up examples --format json` prints
+# the JSON schema for editors.
+
+";
+
+pub async fn handle_examples(dir: &Path, format: ExampleFormat) -> Result<()> {
+    match format {
+        ExampleFormat::Yaml => generate(dir).await,
+        ExampleFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&schema())?);
+            Ok(())
+        }
+    }
+}
+
+/// Write the example files, each checked the way `deploy` would check it
+async fn generate(dir: &Path) -> Result<()> {
+    // Create directory if it doesn't exist
+    fs::create_dir_all(dir)
+        .await
+        .with_context(|| format!("Failed to create directory: {}", dir.display()))?;
+
+    for (filename, content) in files()? {
+        validate(&content).with_context(|| format!("Example {} is invalid", filename))?;
+        let path = dir.join(filename);
+        fs::write(&path, content)
+            .await
+            .with_context(|| format!("Failed to write example: {}", path.display()))?;
+        println!("  {} Created: {}", "✓".green(), path.display());
+    }
+
+    println!();
+    println!(
+        "{} Example YAML files created in {}",
+        "Success:".bold().green(),
+        dir.display()
+    );
+
+    Ok(())
+}
+
+/// The example files and their contents
+fn files() -> Result<Vec<(&'static str, String)>> {
+    // Basic example
+    let basic_example = r#"api-version: v1
+deployments:
+  - name: my-redis
+    type: basic
+    port: 6379
+    persist: true
+    memory: "512m"
+    with-insight: true
+"#;
+
+    // Stack example
+    let stack_example = r#"api-version: v1
+deployments:
+  - name: my-stack
+    type: stack
+    port: 6380
+    persist: true
+    memory: "1g"
+    with-insight: true
+    insight-port: 8002
+"#;
+
+    // Cluster example
+    let cluster_example = r#"api-version: v1
+deployments:
+  - name: my-cluster
+    type: cluster
+    masters: 3
+    replicas: 1
+    port-base: 7000
+    persist: true
+    memory: "256m"
+    stack: false
+    with-insight: true
+"#;
+
+    // Sentinel example
+    let sentinel_example = r#"api-version: v1
+deployments:
+  - name: my-sentinel
+    type: sentinel
+    sentinels: 3
+    redis-port-base: 6379
+    sentinel-port-base: 26379
+    persist: true
+    memory: "512m"
+"#;
+
+    // Enterprise example
+    let enterprise_example = r#"api-version: v1
+deployments:
+  - name: my-enterprise
+    type: enterprise
+    nodes: 3
+    port-base: 8443
+    create-db: "mydb"
+    db-port: 12000
+    memory: "4g"
+    persist: false
+    with-insight: true
+"#;
+
+    // Multi-deployment example
+    let multi_example = r#"api-version: v1
+deployments:
+  - name: cache-redis
+    type: basic
+    port: 6379
+    memory: "256m"
+    
+  - name: analytics-stack
+    type: stack
+    port: 6380
+    persist: true
+    memory: "1g"
+    with-insight: true
+    
+  - name: session-cluster
+    type: cluster
+    masters: 3
+    replicas: 1
+    port-base: 7000
+    memory: "512m"
+"#;
+
+    Ok(vec![
+        ("basic.yaml", basic_example.to_string()),
+        ("stack.yaml", stack_example.to_string()),
+        ("cluster.yaml", cluster_example.to_string()),
+        ("sentinel.yaml", sentinel_example.to_string()),
+        ("enterprise.yaml", enterprise_example.to_string()),
+        ("multi-deployment.yaml", multi_example.to_string()),
+        ("kitchen-sink.yaml", kitchen_sink(true)?),
+    ])
+}
+
+/// Parse a file and build each deployment's start command, as `deploy` does
+fn validate(content: &str) -> Result<()> {
+    let (config, _) = yaml::parse_yaml(serde_yaml::from_str(content)?, None)?;
+    for deployment in &config.deployments {
+        yaml::start_request(deployment)?;
+    }
+    Ok(())
+}
+
+/// Every key of every type, annotated with the flag's help
+///
+/// With `commented`, keys are commented out so the file deploys with defaults;
+/// without, every key is set, which only the schema checks accept.
+fn kitchen_sink(commented: bool) -> Result<String> {
+    let cli = Cli::command();
+    let prefix = if commented { "# " } else { "" };
+    let mut out = String::from(KITCHEN_SINK_HEADER);
+    out.push_str("api-version: v1\n");
+    out.push_str("# Deployments of shared files, overridden by same-named ones below\n");
+    out.push_str("# include: [team.yaml]\n");
+    out.push_str("deployments:\n");
+
+    for (i, deployment) in every_field().iter().enumerate() {
+        let kind = deployment.config.kind();
+        out.push_str(&format!(
+            "  - name: {}\n    type: {}\n",
+            deployment.name, kind
+        ));
+        if let Some(ref description) = deployment.description {
+            out.push_str(&format!("    description: {}\n", description));
+        }
+        if i == 0 {
+            out.push_str("    # Start from another deployment: NAME, FILE or FILE#NAME\n");
+            out.push_str("    # extends: team.yaml#cache\n");
+        }
+
+        let values = serde_yaml::to_value(&deployment.config)?;
+        let start = yaml::start_command(&cli, kind);
+        for key in yaml::start_keys(start) {
+            let Some(value) = values.get(key.as_str()) else {
+                continue;
+            };
+            if let Some(help) = start
+                .get_arguments()
+                .find(|arg| key_of(arg) == key)
+                .and_then(annotation)
+            {
+                out.push_str(&format!("    # {}\n", help));
+            }
+            let mut single = serde_yaml::Mapping::new();
+            single.insert(key.as_str().into(), value.clone());
+            for line in serde_yaml::to_string(&single)?.lines() {
+                out.push_str(&format!("    {}{}\n", prefix, line));
+            }
+        }
+
+        if i == 0 {
+            out.push_str(concat!(
+                "    # Redis commands, quoted like redis-cli, and Lua scripts run once it is up\n",
+                "    post-start:\n",
+                "      - SET greeting \"hello world\"\n",
+                "      # - script: seed.lua\n",
+                "      #   keys: [counter]\n",
+                "    # Keys to override with `redis-up deploy FILE --profile NAME`\n",
+                "    profiles:\n",
+                "      ci: {memory: 128m}\n",
+            ));
+        }
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+/// YAML key of a start argument
+fn key_of(arg: &clap::Arg) -> String {
+    arg.get_id().as_str().replace('_', "-")
+}
+
+/// The flag's help, with its default
+fn annotation(arg: &clap::Arg) -> Option<String> {
+    let help = arg.get_help()?.to_string().replace('\n', " ");
+    Some(match default(arg) {
+        Some(default) if !help.contains("default") => format!("{} (default: {})", help, default),
+        _ => help,
+    })
+}
+
+/// Default of a flag that takes a value; switches default to off
+fn default(arg: &clap::Arg) -> Option<String> {
+    if !arg.get_action().takes_values() {
+        return None;
+    }
+    let defaults: Vec<String> = arg
+        .get_default_values()
+        .iter()
+        .map(|value| value.to_string_lossy().to_string())
+        .collect();
+    (!defaults.is_empty()).then(|| defaults.join(", "))
+}
+
+/// JSON schema of deployment files
+pub fn schema() -> serde_json::Value {
+    let cli = Cli::command();
+    let post_start = json!({
+        "description": "Redis commands and Lua scripts to run once the deployment is up",
+        "type": "array",
+        "items": {
+            "anyOf": [
+                {"type": "string", "description": "A command, quoted like redis-cli"},
+                {
+                    "type": "object",
+                    "required": ["script"],
+                    "properties": {
+                        "script": {"type": "string", "description": "Lua script, relative to this file"},
+                        "keys": {"type": "array", "items": {"type": "string"}},
+                        "args": {"type": "array", "items": {"type": "string"}}
+                    },
+                    "additionalProperties": false
+                }
+            ]
+        }
+    });
+
+    let mut variants = Vec::new();
+    for deployment in every_field() {
+        let kind = deployment.config.kind();
+        let start = yaml::start_command(&cli, kind);
+        let values = serde_yaml::to_value(&deployment.config).unwrap_or_default();
+
+        let mut keys = serde_json::Map::new();
+        keys.insert(
+            "description".to_string(),
+            json!({"type": "string", "description": "Shown in list and info"}),
+        );
+        keys.insert("post-start".to_string(), post_start.clone());
+        for key in yaml::start_keys(start) {
+            let Some(arg) = start.get_arguments().find(|arg| key_of(arg) == key) else {
+                continue;
+            };
+            let example = values.get(key.as_str()).cloned().unwrap_or_default();
+            keys.insert(key, key_schema(arg, &example));
+        }
+
+        let mut properties = keys.clone();
+        properties.insert(
+            "name".to_string(),
+            json!({"type": "string", "description": "Instance name"}),
+        );
+        properties.insert("type".to_string(), json!({"const": kind}));
+        properties.insert(
+            "extends".to_string(),
+            json!({
+                "type": "string",
+                "description": "Deployment to start from: NAME, FILE or FILE#NAME"
+            }),
+        );
+        properties.insert(
+            "profiles".to_string(),
+            json!({
+                "type": "object",
+                "description": "Keys to override with `redis-up deploy FILE --profile NAME`",
+                "additionalProperties": {
+                    "type": "object",
+                    "properties": keys,
+                    "additionalProperties": false
+                }
+            }),
+        );
+        variants.push(json!({
+            "title": format!("{} deployment", kind),
+            "type": "object",
+            "required": ["name"],
+            "properties": properties,
+            "additionalProperties": false
+        }));
+    }
+
+    json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "redis-up deployment file",
+        "type": "object",
+        "properties": {
+            "api-version": {"enum": ["v1"], "default": "v1"},
+            "include": {
+                "type": "array",
+                "description": "Files whose deployments this file builds on",
+                "items": {"type": "string"}
+            },
+            "deployments": {"type": "array", "items": {"anyOf": variants}}
+        },
+        "additionalProperties": false
+    })
+}
+
+/// Schema of one key, typed after its example value
+fn key_schema(arg: &clap::Arg, example: &serde_yaml::Value) -> serde_json::Value {
+    let kind = match example {
+        serde_yaml::Value::Bool(_) => "boolean",
+        serde_yaml::Value::Number(number) if number.is_f64() => "number",
+        serde_yaml::Value::Number(_) => "integer",
+        serde_yaml::Value::Sequence(_) => "array",
+        _ => "string",
+    };
+    let choices: Vec<String> = arg
+        .get_possible_values()
+        .iter()
+        .map(|value| value.get_name().to_string())
+        .collect();
+
+    let mut value = json!({"type": if kind == "array" { "string" } else { kind }});
+    if !choices.is_empty() && kind != "boolean" {
+        value["enum"] = json!(choices);
+    }
+    let mut schema = if kind == "array" {
+        json!({"type": "array", "items": value})
+    } else {
+        value
+    };
+    if let Some(help) = arg.get_help() {
+        schema["description"] = json!(help.to_string());
+    }
+    if let Some(default) = default(arg) {
+        schema["default"] = match kind {
+            "integer" => default.parse::<i64>().map_or(json!(default), |n| json!(n)),
+            "number" => default.parse::<f64>().map_or(json!(default), |n| json!(n)),
+            _ => json!(default),
+        };
+    }
+    schema
+}
+
+fn deployment(name: &str, config: DeploymentConfig) -> Deployment {
+    Deployment {
+        name: name.to_string(),
+        description: Some(format!("{} deployment", name)),
+        post_start: Vec::new(),
+        config,
+    }
+}
+
+/// One deployment per type with every field set
+///
+/// The kitchen sink takes its example values from here and the schema its types.
+pub fn every_field() -> Vec<Deployment> {
+    vec![
+        deployment(
+            "cache",
+            DeploymentConfig::Basic(BasicConfig {
+                port: Some(6380),
+                redis_version: Some("7.2".to_string()),
+                count: Some(2),
+                shared_password: true,
+                password: Some("secret".to_string()),
+                no_auth: true,
+                persist: true,
+                persistence: Some("aof".to_string()),
+                save: Some("60 1000".to_string()),
+                appendfsync: Some("always".to_string()),
+                databases: Some(4),
+                load_rdb: Some(PathBuf::from("dump.rdb")),
+                data_volume: Some("cache-data".to_string()),
+                networks: vec!["app".to_string()],
+                bind: Some("0.0.0.0".to_string()),
+                allow_exposed: true,
+                network_mode: Some("host".to_string()),
+                ipv6: true,
+                memory: Some("256m".to_string()),
+                cpus: Some(0.5),
+                cpu_shares: Some(512),
+                blkio_limit: Some(500),
+                restart_policy: Some("unless-stopped".to_string()),
+                rename_commands: vec!["FLUSHALL=".to_string()],
+                hardened: true,
+                pull: Some("always".to_string()),
+                offline: true,
+                platform: Some("linux/amd64".to_string()),
+                retries: Some(2),
+                reuse: true,
+                recreate_on_drift: true,
+                warn_on_drift: true,
+                shell: true,
+                with_insight: true,
+                insight_port: Some(8002),
+            }),
+        ),
+        deployment(
+            "search",
+            DeploymentConfig::Stack(StackConfig {
+                port: Some(6381),
+                password: Some("secret".to_string()),
+                no_auth: true,
+                persist: true,
+                persistence: Some("rdb".to_string()),
+                save: Some("60 1000".to_string()),
+                appendfsync: Some("everysec".to_string()),
+                databases: Some(2),
+                load_rdb: Some(PathBuf::from("dump.rdb")),
+                data_volume: Some("search-data".to_string()),
+                networks: vec!["app".to_string()],
+                bind: Some("127.0.0.1".to_string()),
+                allow_exposed: true,
+                network_mode: Some("bridge".to_string()),
+                ipv6: true,
+                memory: Some("1g".to_string()),
+                cpus: Some(1.0),
+                cpu_shares: Some(1024),
+                blkio_limit: Some(100),
+                restart_policy: Some("always".to_string()),
+                rename_commands: vec!["CONFIG=CFG".to_string()],
+                hardened: true,
+                pull: Some("missing".to_string()),
+                offline: true,
+                platform: Some("linux/arm64".to_string()),
+                retries: Some(1),
+                reuse: true,
+                recreate_on_drift: true,
+                warn_on_drift: true,
+                flavor: Some("redis8".to_string()),
+                with_json: true,
+                with_search: true,
+                with_timeseries: true,
+                with_graph: true,
+                with_bloom: true,
+                demo_bundle: Some(true),
+                with_insight: true,
+                insight_port: Some(8003),
+                shell: true,
+            }),
+        ),
+        deployment(
+            "orders",
+            DeploymentConfig::Cluster(ClusterConfig {
+                masters: Some(6),
+                replicas: Some(2),
+                port_base: Some(7100),
+                password: Some("secret".to_string()),
+                persist: true,
+                persistence: Some("both".to_string()),
+                save: Some("900 1".to_string()),
+                appendfsync: Some("no".to_string()),
+                memory: Some("128m".to_string()),
+                force: true,
+                cpus: Some(0.25),
+                cpu_shares: Some(256),
+                blkio_limit: Some(10),
+                restart_policy: Some("on-failure".to_string()),
+                pull: Some("never".to_string()),
+                offline: true,
+                platform: Some("linux/amd64".to_string()),
+                retries: Some(3),
+                reuse: true,
+                recreate_on_drift: true,
+                warn_on_drift: true,
+                stack: true,
+                emulate: Some("elasticache".to_string()),
+                announce_ip: Some("192.168.1.10".to_string()),
+                announce_hostname: Some("localhost".to_string()),
+                network_mode: Some("host".to_string()),
+                with_insight: true,
+                insight_port: Some(8004),
+                shell: true,
+            }),
+        ),
+        deployment(
+            "ha",
+            DeploymentConfig::Sentinel(SentinelConfig {
+                masters: Some(2),
+                master_names: vec!["orders".to_string(), "sessions".to_string()],
+                sentinels: Some(5),
+                quorum: Some(3),
+                down_after_ms: Some(2000),
+                failover_timeout: Some(5000),
+                parallel_syncs: Some(2),
+                redis_port_base: Some(6390),
+                sentinel_port_base: Some(26390),
+                password: Some("secret".to_string()),
+                persist: true,
+                memory: Some("64m".to_string()),
+                cpus: Some(0.5),
+                cpu_shares: Some(128),
+                blkio_limit: Some(1000),
+                restart_policy: Some("no".to_string()),
+                pull: Some("always".to_string()),
+                offline: true,
+                platform: Some("linux/arm64".to_string()),
+                retries: Some(1),
+                reuse: true,
+                recreate_on_drift: true,
+                warn_on_drift: true,
+                ipv6: true,
+                with_readonly_port: true,
+                with_lb: true,
+                with_insight: true,
+                insight_port: Some(8005),
+            }),
+        ),
+        deployment(
+            "re",
+            DeploymentConfig::Enterprise(EnterpriseConfig {
+                nodes: Some(1),
+                port_base: Some(8444),
+                create_db: Some("cache".to_string()),
+                db_port: Some(12100),
+                memory: Some("4g".to_string()),
+                force: true,
+                cpus: Some(2.0),
+                cpu_shares: Some(2048),
+                blkio_limit: Some(900),
+                restart_policy: Some("unless-stopped".to_string()),
+                pull: Some("missing".to_string()),
+                offline: true,
+                platform: Some("linux/amd64".to_string()),
+                retries: Some(2),
+                reuse: true,
+                recreate_on_drift: true,
+                warn_on_drift: true,
+                persist: true,
+                containers_only: true,
+                active_active: true,
+                participants: Some(3),
+                with_insight: true,
+                insight_port: Some(8006),
+            }),
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_examples_validate_and_cover_every_key() {
+        for (filename, content) in files().unwrap() {
+            validate(&content).unwrap_or_else(|e| panic!("{}: {:#}", filename, e));
+        }
+
+        // With every key set, the kitchen sink passes the schema checks
+        let full = kitchen_sink(false).unwrap();
+        let (config, legacy) = yaml::parse_yaml(serde_yaml::from_str(&full).unwrap(), None)
+            .unwrap_or_else(|e| panic!("{:#}", e));
+        assert!(legacy.is_empty());
+        assert_eq!(config.deployments.len(), every_field().len());
+        for (parsed, expected) in config.deployments.iter().zip(every_field()) {
+            assert_eq!(parsed.config, expected.config);
+        }
+
+        let schema = schema();
+        let variants = schema["properties"]["deployments"]["items"]["anyOf"]
+            .as_array()
+            .unwrap();
+        let cli = Cli::command();
+        for (variant, deployment) in variants.iter().zip(every_field()) {
+            let start = yaml::start_command(&cli, deployment.config.kind());
+            for key in yaml::start_keys(start) {
+                assert!(
+                    variant["properties"].get(&key).is_some(),
+                    "no schema for {}",
+                    key
+                );
+            }
+        }
+        assert_eq!(variants[0]["properties"]["port"]["type"], "integer");
+        assert_eq!(variants[0]["properties"]["networks"]["type"], "array");
+    }
+}
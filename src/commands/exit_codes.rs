@@ -0,0 +1,36 @@
+//! List the process exit codes scripts can branch on
+
+use anyhow::Result;
+use colored::*;
+
+use crate::error::EXIT_CODES;
+
+pub fn handle_exit_codes(json: bool) -> Result<()> {
+    if json {
+        let codes: Vec<serde_json::Value> = EXIT_CODES
+            .iter()
+            .map(|(code, name, meaning)| {
+                serde_json::json!({"code": code, "name": name, "meaning": meaning})
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&codes)?);
+        return Ok(());
+    }
+
+    println!("{}", "Exit codes:".bold().cyan());
+    let width = EXIT_CODES
+        .iter()
+        .map(|(_, name, _)| name.len())
+        .max()
+        .unwrap_or(0);
+    for (code, name, meaning) in EXIT_CODES {
+        println!(
+            "  {:>3}  {:<width$}  {}",
+            code,
+            name.bold(),
+            meaning,
+            width = width
+        );
+    }
+    Ok(())
+}
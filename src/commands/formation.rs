@@ -0,0 +1,413 @@
+//! Cluster node launch and formation
+//!
+//! Nodes are started here rather than by the cluster template, which starts
+//! them one after another, sleeps a fixed two seconds before forming the
+//! cluster and can't pass extra server arguments. Every command is built once
+//! into a [`Formation`], which `--dry-run` prints and a start runs. The node
+//! command line is shared by every node, so persistence settings (and
+//! emulation) and resource limits apply at boot. All nodes are launched at
+//! once, each is polled until it answers PING, and one `redis-cli --cluster
+//! create` assigns the slots.
+//!
+//! Nodes and network use the template's names, so its `remove` cleans up a
+//! failed attempt.
+//!
+//! By default nodes advertise their container IPs, which only resolve on the
+//! cluster network. An announced address makes every node advertise it with
+//! its published ports instead, so clients on the host, other hosts or other
+//! containers can follow redirections; the nodes reach each other (gossip
+//! and replication) through the same published ports. On the host's network
+//! nodes listen on their host ports directly and meet over loopback, so they
+//! advertise addresses the host can reach without announcing anything.
+
+use anyhow::{Context, Result};
+use docker_wrapper::{DockerCommand, ExecCommand, NetworkCreateCommand, RunCommand};
+use std::time::{Duration, Instant};
+use tokio::task::JoinSet;
+use tracing::Instrument;
+
+use crate::cli::{Emulation, LimitArgs};
+use crate::commands::plan::Plan;
+use crate::commands::server::{self, Directive};
+use crate::commands::{emulate, host, images, limits, passthrough, tls};
+use crate::error::Failure;
+
+/// How long a freshly started node gets to answer PING
+const NODE_READY_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Delay between readiness checks
+pub const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How a cluster's nodes are run
+pub struct NodeSpec<'a> {
+    pub name: &'a str,
+    pub image: &'a str,
+    pub masters: usize,
+    pub replicas: usize,
+    pub port_base: u16,
+    pub password: &'a str,
+    pub memory: Option<&'a str>,
+    pub persist: bool,
+    pub platform: Option<&'a str>,
+    pub directives: &'a [Directive],
+    pub emulate: Option<Emulation>,
+    pub announce: Option<Announce<'a>>,
+    pub host_network: bool,
+    pub limits: &'a LimitArgs,
+}
+
+/// Address nodes advertise instead of their container IPs
+pub struct Announce<'a> {
+    pub ip: &'a str,
+    pub hostname: Option<&'a str>,
+}
+
+impl NodeSpec<'_> {
+    pub fn total_nodes(&self) -> usize {
+        self.masters + self.masters * self.replicas
+    }
+}
+
+/// Container names of a cluster's nodes, which are also their host names
+pub fn node_hosts(name: &str, total_nodes: usize) -> Vec<String> {
+    (0..total_nodes)
+        .map(|i| format!("{}-node-{}", name, i))
+        .collect()
+}
+
+/// Container port that host ports are published to
+pub fn client_port(emulate: Option<Emulation>) -> u16 {
+    match emulate {
+        Some(_) => emulate::TLS_PORT,
+        None => 6379,
+    }
+}
+
+/// redis-server command line shared by every node
+///
+/// Directives go on the command line so nodes boot with them; emulated nodes
+/// have no CONFIG command to apply them later.
+pub fn node_args(
+    password: &str,
+    directives: &[Directive],
+    emulate: Option<Emulation>,
+) -> Vec<String> {
+    let mut args: Vec<String> = [
+        "redis-server",
+        "--port",
+        "6379",
+        "--cluster-enabled",
+        "yes",
+        "--cluster-config-file",
+        "nodes.conf",
+        "--cluster-node-timeout",
+        "5000",
+        "--appendonly",
+        "yes",
+        "--requirepass",
+        password,
+        "--masterauth",
+        password,
+    ]
+    .iter()
+    .map(|a| a.to_string())
+    .collect();
+    if emulate.is_some() {
+        args.extend(emulate::tls_args());
+    }
+    args.extend(server::to_cli_args(directives));
+    if let Some(mode) = emulate {
+        args.extend(server::to_cli_args(&server::rename_directives(
+            &emulate::renames(mode),
+        )));
+    }
+    args
+}
+
+/// Per-node arguments advertising `announce` and the node's published ports
+pub fn announce_args(announce: &Announce<'_>, port: u16) -> Vec<String> {
+    let mut args = vec![
+        "--cluster-announce-ip".to_string(),
+        announce.ip.to_string(),
+        "--cluster-announce-port".to_string(),
+        port.to_string(),
+        "--cluster-announce-bus-port".to_string(),
+        (port + 10000).to_string(),
+    ];
+    if let Some(hostname) = announce.hostname {
+        args.extend([
+            "--cluster-announce-hostname".to_string(),
+            hostname.to_string(),
+            "--cluster-preferred-endpoint-type".to_string(),
+            "hostname".to_string(),
+        ]);
+    }
+    args
+}
+
+/// Reject announced IPs the nodes can't reach each other through
+pub fn validate_announce_ip(ip: &str) -> Result<()> {
+    let parsed: std::net::IpAddr = ip
+        .parse()
+        .with_context(|| format!("Invalid --announce-ip '{}': expected an IP address", ip))?;
+    if parsed.is_loopback() || parsed.is_unspecified() {
+        anyhow::bail!(
+            "--announce-ip {} would point every node at itself; use an address containers can reach, such as the host's LAN IP, and --announce-hostname localhost for clients on this machine",
+            ip
+        );
+    }
+    Ok(())
+}
+
+/// The Docker commands that bring a cluster up, built once from a [`NodeSpec`]
+pub struct Formation {
+    name: String,
+    image: String,
+    password: String,
+    /// Created unless the nodes run on the host's network
+    network: Option<NetworkCreateCommand>,
+    /// Container name and `docker run` of each node
+    nodes: Vec<(String, RunCommand)>,
+    /// `redis-cli --cluster create`, run on the first node
+    create: ExecCommand,
+    summary: String,
+}
+
+impl NodeSpec<'_> {
+    /// Build the network, node and cluster-create commands
+    pub fn formation(&self) -> Result<Formation> {
+        let network = format!("{}-network", self.name);
+        let args = node_args(self.password, self.directives, self.emulate);
+        let certs = match self.emulate {
+            Some(_) => Some(host::mount_source(&tls::dir(self.name)?)),
+            None => None,
+        };
+        let hosts = node_hosts(self.name, self.total_nodes());
+
+        let mut nodes = Vec::new();
+        for (i, host) in hosts.iter().enumerate() {
+            let port = self.port_base + i as u16;
+            let mut cmd = passthrough::apply(RunCommand::new(images::resolve(self.image)))
+                .name(host)
+                .detach();
+            cmd = if self.host_network {
+                server::use_host_network(cmd, port)
+            } else {
+                cmd.network(&network)
+                    .port(port, client_port(self.emulate))
+                    .port(port + 10000, 16379)
+            };
+            if let Some(certs) = &certs {
+                cmd = cmd.volume_ro(certs, tls::CONTAINER_DIR);
+            }
+            if let Some(memory) = self.memory {
+                cmd = cmd.memory(memory);
+            }
+            if self.persist {
+                cmd = cmd.volume(format!("{}-data-{}", self.name, i), "/data");
+            }
+            if let Some(platform) = self.platform {
+                cmd = cmd.platform(platform);
+            }
+            let mut node_args = args.clone();
+            if self.host_network {
+                // Overrides the shared 6379; the bus port follows at +10000
+                node_args.extend(["--port".to_string(), port.to_string()]);
+            }
+            if let Some(announce) = &self.announce {
+                node_args.extend(announce_args(announce, port));
+            }
+            let cmd = limits::apply_to_run(cmd.cmd(node_args), self.limits);
+            nodes.push((host.clone(), cmd));
+        }
+
+        let mut create = vec![
+            "redis-cli".to_string(),
+            "--cluster".to_string(),
+            "create".to_string(),
+        ];
+        create.extend(create_addresses(self, &hosts));
+        if self.replicas > 0 {
+            create.extend(["--cluster-replicas".to_string(), self.replicas.to_string()]);
+        }
+        create.extend([
+            "-a".to_string(),
+            self.password.to_string(),
+            "--no-auth-warning".to_string(),
+            "--cluster-yes".to_string(),
+        ]);
+
+        let mut summary = format!(
+            "Redis Cluster '{}' started with {} nodes ({} masters, {} replicas)",
+            self.name,
+            hosts.len(),
+            self.masters,
+            self.masters * self.replicas
+        );
+        if let Some(mode) = self.emulate {
+            summary.push_str(&format!(", emulating {}", mode));
+        }
+
+        Ok(Formation {
+            name: self.name.to_string(),
+            image: self.image.to_string(),
+            password: self.password.to_string(),
+            network: (!self.host_network)
+                .then(|| NetworkCreateCommand::new(&network).driver("bridge")),
+            create: ExecCommand::new(&hosts[0], create),
+            nodes,
+            summary,
+        })
+    }
+}
+
+impl Formation {
+    /// Add the formation to a dry-run plan
+    pub fn plan(&self, plan: &mut Plan) {
+        if let Some(ref network) = self.network {
+            plan.docker(network);
+        }
+        for (_, cmd) in &self.nodes {
+            plan.run(&self.image, cmd, None);
+        }
+        plan.docker(&self.create);
+    }
+
+    /// Create the network and nodes, then form the cluster over the plain ports
+    #[tracing::instrument(
+        name = "cluster_formation",
+        skip_all,
+        fields(name = %self.name, nodes = self.nodes.len())
+    )]
+    pub async fn start(&self) -> Result<String> {
+        if let Some(ref network) = self.network {
+            network.execute().await?;
+        }
+
+        let mut launches = JoinSet::new();
+        for (host, cmd) in &self.nodes {
+            let (host, cmd) = (host.clone(), cmd.clone());
+            launches.spawn(
+                async move {
+                    server::run_container(&cmd, None)
+                        .await
+                        .with_context(|| format!("Failed to start {}", host))
+                }
+                .in_current_span(),
+            );
+        }
+        join_all(launches).await?;
+
+        let mut checks = JoinSet::new();
+        for (host, _) in &self.nodes {
+            let (host, password) = (host.clone(), self.password.clone());
+            checks.spawn(async move { wait_for_ping(&host, &password).await }.in_current_span());
+        }
+        join_all(checks).await?;
+
+        self.create
+            .execute()
+            .await
+            .context("Failed to form the cluster")?;
+
+        Ok(self.summary.clone())
+    }
+}
+
+/// Node addresses `redis-cli --cluster create` introduces the nodes by
+///
+/// Nodes advertise the address they were met at, and on the host's network
+/// there are no container names to resolve, so they meet over loopback.
+fn create_addresses(spec: &NodeSpec<'_>, hosts: &[String]) -> Vec<String> {
+    if spec.host_network {
+        (0..hosts.len())
+            .map(|i| format!("127.0.0.1:{}", spec.port_base + i as u16))
+            .collect()
+    } else {
+        hosts.iter().map(|host| format!("{}:6379", host)).collect()
+    }
+}
+
+/// Wait for every task, returning the first error after all have finished
+async fn join_all<T: 'static>(mut set: JoinSet<Result<T>>) -> Result<()> {
+    let mut result = Ok(());
+    while let Some(joined) = set.join_next().await {
+        let finished = joined
+            .context("Node task panicked")
+            .and_then(|r| r.map(|_| ()));
+        if result.is_ok() {
+            result = finished;
+        }
+    }
+    result
+}
+
+#[tracing::instrument(name = "readiness_wait", skip(password))]
+async fn wait_for_ping(container: &str, password: &str) -> Result<()> {
+    let started = Instant::now();
+    while started.elapsed() < NODE_READY_TIMEOUT {
+        if let Ok(reply) = server::redis_cli(container, Some(password), &["PING"]).await {
+            if reply.trim() == "PONG" {
+                return Ok(());
+            }
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+    Err(Failure::Timeout(format!(
+        "{} did not answer PING within {}s",
+        container,
+        NODE_READY_TIMEOUT.as_secs()
+    ))
+    .into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_node_args() {
+        let directives = vec![("save".to_string(), String::new())];
+        let args = node_args("secret", &directives, None);
+        assert!(args
+            .join(" ")
+            .ends_with("--requirepass secret --masterauth secret --save "));
+        assert!(!args.iter().any(|a| a == "--tls-port"));
+        assert_eq!(client_port(None), 6379);
+
+        let args = node_args("secret", &directives, Some(Emulation::Elasticache));
+        let joined = args.join(" ");
+        assert!(joined.contains("--tls-port 6380 --tls-cluster yes"));
+        assert!(joined.contains("--save  --rename-command"));
+        // Each disabled command is renamed to the empty string
+        let at = args.iter().position(|a| a == "CONFIG").unwrap();
+        assert_eq!(args[at - 1], "--rename-command");
+        assert_eq!(args[at + 1], "");
+        assert!(!args.iter().any(|a| a == "PSYNC"));
+        assert_eq!(node_hosts("c", 2), vec!["c-node-0", "c-node-1"]);
+    }
+
+    #[test]
+    fn test_announce_args() {
+        let announce = Announce {
+            ip: "192.168.1.20",
+            hostname: None,
+        };
+        assert_eq!(
+            announce_args(&announce, 7001).join(" "),
+            "--cluster-announce-ip 192.168.1.20 --cluster-announce-port 7001 --cluster-announce-bus-port 17001"
+        );
+        let announce = Announce {
+            hostname: Some("localhost"),
+            ..announce
+        };
+        assert!(announce_args(&announce, 7001).join(" ").ends_with(
+            "--cluster-announce-hostname localhost --cluster-preferred-endpoint-type hostname"
+        ));
+
+        assert!(validate_announce_ip("192.168.1.20").is_ok());
+        assert!(validate_announce_ip("127.0.0.1").is_err());
+        assert!(validate_announce_ip("::1").is_err());
+        assert!(validate_announce_ip("my-host").is_err());
+    }
+}
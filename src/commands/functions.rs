@@ -0,0 +1,302 @@
+//! Redis 7 function libraries on managed instances
+//!
+//! Deployed libraries are kept in instance metadata, code included, so they
+//! can be loaded again after a restart wipes the server's function store.
+
+use anyhow::{Context, Result};
+use colored::*;
+use redis::Value;
+use serde::{Deserialize, Serialize};
+
+use crate::cli::{FunctionsAction, FunctionsDeleteArgs, FunctionsDeployArgs};
+use crate::commands::client;
+use crate::config::{Config, InstanceInfo};
+
+/// A library tracked in the `functions` metadata entry
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrackedLibrary {
+    pub library: String,
+    pub file: String,
+    pub code: String,
+}
+
+pub async fn handle_action(action: FunctionsAction, verbose: bool) -> Result<()> {
+    match action {
+        FunctionsAction::Deploy(args) => deploy(args, verbose).await,
+        FunctionsAction::List { name } => list(&name).await,
+        FunctionsAction::Delete(args) => delete(args, verbose).await,
+        FunctionsAction::Restore { name } => restore(&name, verbose).await,
+    }
+}
+
+/// Libraries recorded for an instance
+pub fn tracked(instance: &InstanceInfo) -> Vec<TrackedLibrary> {
+    instance
+        .metadata
+        .get("functions")
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+        .unwrap_or_default()
+}
+
+fn save_tracked(name: &str, libraries: &[TrackedLibrary]) -> Result<()> {
+    let mut config = Config::load()?;
+    let instance = config
+        .get_instance_mut(name)
+        .with_context(|| format!("Instance '{}' not found", name))?;
+    if libraries.is_empty() {
+        instance.metadata.remove("functions");
+    } else {
+        instance
+            .metadata
+            .insert("functions".to_string(), serde_json::to_value(libraries)?);
+    }
+    config.save()
+}
+
+fn load_instance(name: &str) -> Result<InstanceInfo> {
+    Config::load()?
+        .get_instance(name)
+        .cloned()
+        .with_context(|| format!("Instance '{}' not found", name))
+}
+
+/// FUNCTION LOAD REPLACE on every primary, returning the library name
+async fn load_library(instance: &InstanceInfo, code: &str, verbose: bool) -> Result<String> {
+    let mut library = String::new();
+    for (endpoint, mut con) in client::primaries(instance).await? {
+        library = redis::cmd("FUNCTION")
+            .arg("LOAD")
+            .arg("REPLACE")
+            .arg(code)
+            .query_async(&mut con)
+            .await
+            .with_context(|| format!("FUNCTION LOAD failed on {}", endpoint))?;
+        if verbose {
+            println!("{} Loaded {} on {}", "Functions:".cyan(), library, endpoint);
+        }
+    }
+    Ok(library)
+}
+
+async fn deploy(args: FunctionsDeployArgs, verbose: bool) -> Result<()> {
+    let instance = load_instance(&args.name)?;
+    let code = tokio::fs::read_to_string(&args.file)
+        .await
+        .with_context(|| format!("Failed to read library: {}", args.file.display()))?;
+
+    let library = load_library(&instance, &code, verbose).await?;
+
+    let mut libraries = tracked(&instance);
+    libraries.retain(|l| l.library != library);
+    libraries.push(TrackedLibrary {
+        library: library.clone(),
+        file: args.file.display().to_string(),
+        code,
+    });
+    save_tracked(&args.name, &libraries)?;
+
+    println!(
+        "{} Library '{}' deployed to {}",
+        "Success:".green(),
+        library.bold(),
+        args.name.bold()
+    );
+    Ok(())
+}
+
+async fn list(name: &str) -> Result<()> {
+    let instance = load_instance(name)?;
+    let tracked = tracked(&instance);
+
+    let (_, mut con) = client::primaries(&instance)
+        .await?
+        .into_iter()
+        .next()
+        .with_context(|| format!("Instance '{}' has no reachable endpoints", name))?;
+    let reply: Value = redis::cmd("FUNCTION")
+        .arg("LIST")
+        .query_async(&mut con)
+        .await
+        .context("FUNCTION LIST failed")?;
+    let loaded = parse_function_list(&reply);
+
+    println!("{} {}", "Functions:".bold().cyan(), name.bold());
+    if loaded.is_empty() && tracked.is_empty() {
+        println!("  No libraries loaded");
+        return Ok(());
+    }
+
+    for (library, engine, functions) in &loaded {
+        let source = tracked
+            .iter()
+            .find(|t| &t.library == library)
+            .map(|t| format!(" from {}", t.file).dimmed())
+            .unwrap_or_default();
+        println!("  {} ({}){}", library.bold(), engine, source);
+        for function in functions {
+            println!("    - {}", function);
+        }
+    }
+
+    let missing: Vec<_> = tracked
+        .iter()
+        .filter(|t| !loaded.iter().any(|(library, _, _)| *library == t.library))
+        .collect();
+    if !missing.is_empty() {
+        println!();
+        for library in &missing {
+            println!(
+                "  {} {} is tracked but not loaded",
+                "Warning:".yellow(),
+                library.library.bold()
+            );
+        }
+        println!(
+            "  Run 'redis-up functions restore {}' to load it again",
+            name
+        );
+    }
+
+    Ok(())
+}
+
+async fn delete(args: FunctionsDeleteArgs, verbose: bool) -> Result<()> {
+    let instance = load_instance(&args.name)?;
+
+    for (endpoint, mut con) in client::primaries(&instance).await? {
+        let result: redis::RedisResult<()> = redis::cmd("FUNCTION")
+            .arg("DELETE")
+            .arg(&args.library)
+            .query_async(&mut con)
+            .await;
+        // A library that was never reloaded after a restart is already gone
+        if let Err(e) = result {
+            if verbose {
+                println!("{} {}: {}", "Warning:".yellow(), endpoint, e);
+            }
+        }
+    }
+
+    let mut libraries = tracked(&instance);
+    libraries.retain(|l| l.library != args.library);
+    save_tracked(&args.name, &libraries)?;
+
+    println!(
+        "{} Library '{}' deleted from {}",
+        "Success:".green(),
+        args.library.bold(),
+        args.name.bold()
+    );
+    Ok(())
+}
+
+async fn restore(name: &str, verbose: bool) -> Result<()> {
+    let instance = load_instance(name)?;
+    let libraries = tracked(&instance);
+    if libraries.is_empty() {
+        println!("{} No libraries tracked for {}", "Info:".blue(), name);
+        return Ok(());
+    }
+
+    for library in &libraries {
+        load_library(&instance, &library.code, verbose).await?;
+        println!("  {} {}", "✓".green(), library.library);
+    }
+    println!(
+        "{} Restored {} library(ies) on {}",
+        "Success:".green(),
+        libraries.len(),
+        name.bold()
+    );
+    Ok(())
+}
+
+/// (library, engine, function names) from a FUNCTION LIST reply
+fn parse_function_list(reply: &Value) -> Vec<(String, String, Vec<String>)> {
+    let Value::Array(libraries) = reply else {
+        return Vec::new();
+    };
+
+    libraries
+        .iter()
+        .map(|library| {
+            let fields = pairs(library);
+            let functions = match fields.iter().find(|(k, _)| k == "functions") {
+                Some((_, Value::Array(functions))) => functions
+                    .iter()
+                    .filter_map(|f| text(pairs(f).iter().find(|(k, _)| k == "name")?.1))
+                    .collect(),
+                _ => Vec::new(),
+            };
+            let field = |key: &str| {
+                fields
+                    .iter()
+                    .find(|(k, _)| k == key)
+                    .and_then(|(_, v)| text(v))
+                    .unwrap_or_default()
+            };
+            (field("library_name"), field("engine"), functions)
+        })
+        .collect()
+}
+
+/// Key/value pairs from a RESP2 flat array or a RESP3 map
+fn pairs(value: &Value) -> Vec<(String, &Value)> {
+    match value {
+        Value::Array(items) => items
+            .chunks(2)
+            .filter_map(|pair| match pair {
+                [k, v] => Some((text(k)?, v)),
+                _ => None,
+            })
+            .collect(),
+        Value::Map(items) => items
+            .iter()
+            .filter_map(|(k, v)| Some((text(k)?, v)))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn text(value: &Value) -> Option<String> {
+    match value {
+        Value::BulkString(bytes) => Some(String::from_utf8_lossy(bytes).into_owned()),
+        Value::SimpleString(s) => Some(s.clone()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bulk(s: &str) -> Value {
+        Value::BulkString(s.as_bytes().to_vec())
+    }
+
+    #[test]
+    fn test_parse_function_list() {
+        let reply = Value::Array(vec![Value::Array(vec![
+            bulk("library_name"),
+            bulk("ratelimit"),
+            bulk("engine"),
+            bulk("LUA"),
+            bulk("functions"),
+            Value::Array(vec![Value::Array(vec![
+                bulk("name"),
+                bulk("rl_check"),
+                bulk("description"),
+                Value::Nil,
+            ])]),
+        ])]);
+
+        assert_eq!(
+            parse_function_list(&reply),
+            vec![(
+                "ratelimit".to_string(),
+                "LUA".to_string(),
+                vec!["rl_check".to_string()]
+            )]
+        );
+    }
+}
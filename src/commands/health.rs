@@ -0,0 +1,344 @@
+//! `redis-up health`: one-shot probes for Makefiles and CI gates
+//!
+//! An instance is healthy when every container runs (and Docker does not
+//! report it unhealthy), every Redis endpoint answers PING, each server has
+//! the role it should, and clusters report `cluster_state:ok` with the
+//! expected number of masters. Sentinel-managed servers may have failed over,
+//! so their roles are shown but not checked. Any unhealthy instance makes the
+//! command exit with code 15.
+
+use anyhow::{bail, Context, Result};
+use colored::*;
+use std::time::Duration;
+use tokio::task::JoinSet;
+use tracing::Instrument;
+
+use crate::cli::HealthArgs;
+use crate::commands::client::{self, Endpoint};
+use crate::commands::{replication, uptime};
+use crate::config::{Config, InstanceInfo, InstanceType};
+use crate::error::Failure;
+
+/// What the probes found for one instance
+struct Report {
+    name: String,
+    instance_type: InstanceType,
+    running: usize,
+    containers: usize,
+    answered: usize,
+    probed: usize,
+    role: String,
+    problems: Vec<String>,
+}
+
+/// What one Redis server answered
+struct Probe {
+    role: String,
+    cluster_state: Option<String>,
+}
+
+pub async fn handle_health(args: HealthArgs) -> Result<()> {
+    let config = Config::load()?;
+    let instances: Vec<InstanceInfo> = match &args.name {
+        Some(name) => vec![config
+            .get_instance(name)
+            .with_context(|| format!("Instance '{}' not found", name))?
+            .clone()],
+        None => {
+            let mut all: Vec<InstanceInfo> = config.instances.values().cloned().collect();
+            all.sort_by(|a, b| a.name.cmp(&b.name));
+            all
+        }
+    };
+    if instances.is_empty() {
+        println!("{} No Redis instances to check", "Info:".blue());
+        return Ok(());
+    }
+
+    let mut checks = JoinSet::new();
+    for (i, instance) in instances.into_iter().enumerate() {
+        let timeout = args.timeout;
+        checks.spawn(async move { (i, check(&instance, timeout).await) }.in_current_span());
+    }
+    let mut reports = Vec::new();
+    while let Some(joined) = checks.join_next().await {
+        let (i, report) = joined.context("Health check panicked")?;
+        // Without a daemon every instance would look down; say what's wrong instead
+        reports.push((i, report?));
+    }
+    reports.sort_by_key(|(i, _)| *i);
+    let reports: Vec<Report> = reports.into_iter().map(|(_, report)| report).collect();
+
+    print_table(&reports);
+
+    let unhealthy = reports.iter().filter(|r| !r.problems.is_empty()).count();
+    if unhealthy > 0 {
+        return Err(Failure::Unhealthy(format!(
+            "{} of {} instance(s) unhealthy",
+            unhealthy,
+            reports.len()
+        ))
+        .into());
+    }
+    println!(
+        "{} {} instance(s) healthy",
+        "Success:".green(),
+        reports.len()
+    );
+    Ok(())
+}
+
+#[tracing::instrument(name = "health_check", skip_all, fields(name = %instance.name))]
+async fn check(instance: &InstanceInfo, timeout: Duration) -> Result<Report> {
+    let mut problems = Vec::new();
+
+    let running = match uptime::inspect(&instance.containers).await {
+        Ok(states) => {
+            for container in &instance.containers {
+                match states.get(container) {
+                    None => problems.push(format!("{} does not exist", container)),
+                    Some(state) if !state.running => {
+                        problems.push(format!("{} is not running", container))
+                    }
+                    Some(state) if state.health.as_deref() == Some("unhealthy") => {
+                        problems.push(format!("{} reports unhealthy", container))
+                    }
+                    Some(_) => {}
+                }
+            }
+            states.values().filter(|state| state.running).count()
+        }
+        Err(e) if matches!(e.downcast_ref(), Some(Failure::DockerUnavailable(_))) => return Err(e),
+        Err(e) => {
+            problems.push(format!("Could not inspect containers: {}", e));
+            0
+        }
+    };
+
+    let endpoints = match endpoints(instance).await {
+        Ok(endpoints) => endpoints,
+        Err(e) => {
+            problems.push(e.to_string());
+            Vec::new()
+        }
+    };
+    let cluster = instance.instance_type == InstanceType::Cluster;
+    let mut probes = Vec::new();
+    for endpoint in &endpoints {
+        match tokio::time::timeout(timeout, probe(endpoint, cluster)).await {
+            Ok(Ok(found)) => probes.push((endpoint, found)),
+            Ok(Err(e)) => problems.push(format!("{:#}", e)),
+            Err(_) => problems.push(format!(
+                "{} did not answer within {}",
+                endpoint,
+                humantime::format_duration(timeout)
+            )),
+        }
+    }
+
+    let roles: Vec<&str> = probes.iter().map(|(_, p)| p.role.as_str()).collect();
+    match instance.instance_type {
+        InstanceType::Basic | InstanceType::Stack | InstanceType::Enterprise => {
+            for (endpoint, found) in &probes {
+                if found.role != "master" {
+                    problems.push(format!("{} is a {}, expected master", endpoint, found.role));
+                }
+            }
+        }
+        InstanceType::Cluster => {
+            for (endpoint, found) in &probes {
+                if let Some(state) = found.cluster_state.as_deref().filter(|s| *s != "ok") {
+                    problems.push(format!("{} reports cluster_state:{}", endpoint, state));
+                }
+            }
+            let expected = instance.metadata.get("masters").and_then(|v| v.as_u64());
+            let masters = roles.iter().filter(|role| **role == "master").count() as u64;
+            if let Some(expected) = expected.filter(|_| probes.len() == endpoints.len()) {
+                if masters != expected {
+                    problems.push(format!("{} masters, expected {}", masters, expected));
+                }
+            }
+        }
+        InstanceType::Sentinel => {}
+    }
+
+    Ok(Report {
+        name: instance.name.clone(),
+        instance_type: instance.instance_type.clone(),
+        running,
+        containers: instance.containers.len(),
+        answered: probes.len(),
+        probed: endpoints.len(),
+        role: role_summary(&roles),
+        problems,
+    })
+}
+
+/// Redis servers to probe; an Enterprise instance without a database has none
+async fn endpoints(instance: &InstanceInfo) -> Result<Vec<Endpoint>> {
+    match instance.instance_type {
+        InstanceType::Sentinel => replication::masters(instance).await,
+        InstanceType::Enterprise if !instance.metadata.contains_key("database_name") => {
+            Ok(Vec::new())
+        }
+        _ => Ok(client::endpoints(instance)),
+    }
+}
+
+async fn probe(endpoint: &Endpoint, cluster: bool) -> Result<Probe> {
+    let mut con = client::connect(endpoint).await?;
+    let pong: String = redis::cmd("PING")
+        .query_async(&mut con)
+        .await
+        .with_context(|| format!("PING failed on {}", endpoint))?;
+    if pong != "PONG" {
+        bail!("{} answered PING with {}", endpoint, pong);
+    }
+    let replication: String = redis::cmd("INFO")
+        .arg("replication")
+        .query_async(&mut con)
+        .await
+        .with_context(|| format!("INFO failed on {}", endpoint))?;
+    let cluster_state = if cluster {
+        let info: String = redis::cmd("CLUSTER")
+            .arg("INFO")
+            .query_async(&mut con)
+            .await
+            .with_context(|| format!("CLUSTER INFO failed on {}", endpoint))?;
+        Some(
+            field(&info, "cluster_state")
+                .unwrap_or("unknown")
+                .to_string(),
+        )
+    } else {
+        None
+    };
+    Ok(Probe {
+        role: field(&replication, "role").unwrap_or("unknown").to_string(),
+        cluster_state,
+    })
+}
+
+/// Value of a `key:value` line in INFO-style output
+fn field<'a>(info: &'a str, key: &str) -> Option<&'a str> {
+    info.lines()
+        .find_map(|line| line.trim().strip_prefix(key)?.strip_prefix(':'))
+}
+
+/// `master`, or counts per role when several servers answered
+fn role_summary(roles: &[&str]) -> String {
+    match roles {
+        [] => "-".to_string(),
+        [role] => role.to_string(),
+        _ => {
+            let mut counts: Vec<(&str, usize)> = Vec::new();
+            for role in roles {
+                match counts.iter_mut().find(|(seen, _)| seen == role) {
+                    Some((_, count)) => *count += 1,
+                    None => counts.push((role, 1)),
+                }
+            }
+            counts
+                .iter()
+                .map(|(role, count)| format!("{} {}", count, role))
+                .collect::<Vec<_>>()
+                .join(", ")
+        }
+    }
+}
+
+fn print_table(reports: &[Report]) {
+    let rows: Vec<[String; 5]> = reports
+        .iter()
+        .map(|r| {
+            [
+                r.name.clone(),
+                r.instance_type.to_string(),
+                format!("{}/{}", r.running, r.containers),
+                if r.probed == 0 {
+                    "-".to_string()
+                } else {
+                    format!("{}/{}", r.answered, r.probed)
+                },
+                r.role.clone(),
+            ]
+        })
+        .collect();
+    let headers = ["NAME", "TYPE", "RUNNING", "PING", "ROLE"];
+    let mut widths = headers.map(str::len);
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+
+    println!("{}", "Health:".bold().cyan());
+    println!(
+        "  {}",
+        format!(
+            "{:<w0$}  {:<w1$}  {:<w2$}  {:<w3$}  {:<w4$}  STATUS",
+            headers[0],
+            headers[1],
+            headers[2],
+            headers[3],
+            headers[4],
+            w0 = widths[0],
+            w1 = widths[1],
+            w2 = widths[2],
+            w3 = widths[3],
+            w4 = widths[4]
+        )
+        .dimmed()
+    );
+    for (report, row) in reports.iter().zip(&rows) {
+        let status = if report.problems.is_empty() {
+            "healthy".green()
+        } else {
+            "UNHEALTHY".red()
+        };
+        println!(
+            "  {}  {:<w1$}  {:<w2$}  {:<w3$}  {:<w4$}  {}",
+            format!("{:<w$}", row[0], w = widths[0]).bold(),
+            row[1],
+            row[2],
+            row[3],
+            row[4],
+            status,
+            w1 = widths[1],
+            w2 = widths[2],
+            w3 = widths[3],
+            w4 = widths[4]
+        );
+    }
+
+    let unhealthy: Vec<&Report> = reports.iter().filter(|r| !r.problems.is_empty()).collect();
+    println!();
+    for report in unhealthy {
+        for problem in &report.problems {
+            println!("  {} {}: {}", "✗".red(), report.name.bold(), problem);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_role_summary() {
+        let info = "# Replication\r\nrole:master\r\nconnected_slaves:1\r\n";
+        assert_eq!(field(info, "role"), Some("master"));
+        assert_eq!(
+            field("cluster_state:fail\r\n", "cluster_state"),
+            Some("fail")
+        );
+        assert_eq!(field(info, "master_host"), None);
+
+        assert_eq!(role_summary(&[]), "-");
+        assert_eq!(role_summary(&["master"]), "master");
+        assert_eq!(
+            role_summary(&["master", "slave", "master", "slave"]),
+            "2 master, 2 slave"
+        );
+    }
+}
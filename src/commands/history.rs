@@ -0,0 +1,307 @@
+//! Local start/stop history and the `usage` report built from it
+//!
+//! Every start and stop appends one JSON line to `history.jsonl` in the config
+//! directory. Nothing leaves the machine; flag values are never recorded, only
+//! flag names, so passwords given on the command line stay out of the file.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use colored::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::process::Command as ProcessCommand;
+use tracing::debug;
+
+use crate::cli::UsageArgs;
+use crate::config::{get_state_dir, Config, InstanceInfo, InstanceType};
+
+const HISTORY_FILE: &str = "history.jsonl";
+
+/// How many flags the report lists
+const TOP_FLAGS: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EventKind {
+    Start,
+    Stop,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Event {
+    pub at: DateTime<Utc>,
+    pub event: EventKind,
+    pub name: String,
+    pub instance_type: InstanceType,
+    /// Flag names from the command line that started the instance
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub flags: Vec<String>,
+}
+
+fn history_path() -> Result<PathBuf> {
+    Ok(get_state_dir()?.join(HISTORY_FILE))
+}
+
+/// Record that an instance was started by this process
+pub fn record_start(instance: &InstanceInfo) {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    record(Event {
+        at: Utc::now(),
+        event: EventKind::Start,
+        name: instance.name.clone(),
+        instance_type: instance.instance_type.clone(),
+        flags: flag_names(&args),
+    });
+}
+
+/// Record that an instance was stopped or cleaned up
+pub fn record_stop(instance: &InstanceInfo) {
+    record(Event {
+        at: Utc::now(),
+        event: EventKind::Stop,
+        name: instance.name.clone(),
+        instance_type: instance.instance_type.clone(),
+        flags: Vec::new(),
+    });
+}
+
+/// History is best effort and never fails the command that triggered it
+fn record(event: Event) {
+    let result = (|| -> Result<()> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(history_path()?)?;
+        writeln!(file, "{}", serde_json::to_string(&event)?)?;
+        Ok(())
+    })();
+    if let Err(e) = result {
+        debug!("Could not write history: {:#}", e);
+    }
+}
+
+fn load() -> Result<Vec<Event>> {
+    let path = history_path()?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content =
+        fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+    // Lines from a newer or interrupted write are skipped rather than fatal
+    Ok(content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// `--port 6380 -p=x --persist` gives `--port`, `-p`, `--persist`
+fn flag_names(args: &[String]) -> Vec<String> {
+    args.iter()
+        .take_while(|arg| *arg != "--")
+        .filter(|arg| arg.starts_with('-') && arg.len() > 1 && !is_number(arg))
+        .map(|arg| arg.split('=').next().unwrap_or(arg).to_string())
+        .collect()
+}
+
+fn is_number(arg: &str) -> bool {
+    arg[1..].parse::<f64>().is_ok()
+}
+
+/// Starts, finished lifetimes and flag counts over a set of events
+#[derive(Debug, Default, PartialEq)]
+struct Summary {
+    starts: BTreeMap<String, usize>,
+    lifetimes: BTreeMap<String, Vec<Duration>>,
+    flags: Vec<(String, usize)>,
+}
+
+fn summarize(events: &[Event]) -> Summary {
+    let mut summary = Summary::default();
+    let mut started: HashMap<&str, DateTime<Utc>> = HashMap::new();
+    let mut flag_counts: HashMap<&str, usize> = HashMap::new();
+
+    for event in events {
+        let kind = event.instance_type.to_string();
+        match event.event {
+            EventKind::Start => {
+                *summary.starts.entry(kind).or_default() += 1;
+                started.insert(&event.name, event.at);
+                // Count each flag once per start
+                let unique: HashSet<&str> = event.flags.iter().map(String::as_str).collect();
+                for flag in unique {
+                    *flag_counts.entry(flag).or_default() += 1;
+                }
+            }
+            EventKind::Stop => {
+                if let Some(at) = started.remove(event.name.as_str()) {
+                    let lifetime = (event.at - at).to_std().unwrap_or_default();
+                    summary.lifetimes.entry(kind).or_default().push(lifetime);
+                }
+            }
+        }
+    }
+
+    let mut flags: Vec<(String, usize)> = flag_counts
+        .into_iter()
+        .map(|(flag, count)| (flag.to_string(), count))
+        .collect();
+    flags.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    flags.truncate(TOP_FLAGS);
+    summary.flags = flags;
+    summary
+}
+
+pub async fn handle_usage(args: UsageArgs, verbose: bool) -> Result<()> {
+    let mut events = load()?;
+    if let Some(since) = args.since {
+        let cutoff = Utc::now() - chrono::Duration::from_std(since)?;
+        events.retain(|e| e.at >= cutoff);
+    }
+    let summary = summarize(&events);
+
+    println!("{}", "Usage (local history only):".bold().cyan());
+    if events.is_empty() {
+        println!("  No starts recorded yet");
+    }
+
+    if !summary.starts.is_empty() {
+        println!("\n  {}", "Starts by type".bold());
+        for (kind, count) in &summary.starts {
+            let average = summary
+                .lifetimes
+                .get(kind)
+                .filter(|l| !l.is_empty())
+                .map(|l| {
+                    let total: Duration = l.iter().sum();
+                    let average = Duration::from_secs(total.as_secs() / l.len() as u64);
+                    format!("average lifetime {}", humantime::format_duration(average))
+                })
+                .unwrap_or_else(|| "none stopped yet".to_string());
+            println!("    {:<12} {:>5}  {}", kind, count, average.dimmed());
+        }
+    }
+
+    if !summary.flags.is_empty() {
+        println!("\n  {}", "Most used flags".bold());
+        for (flag, count) in &summary.flags {
+            println!("    {:<24} {:>5}", flag, count);
+        }
+    }
+
+    println!("\n  {}", "Data volumes".bold());
+    let config = Config::load()?;
+    let mut names: HashSet<String> = events.iter().map(|e| e.name.clone()).collect();
+    names.extend(config.list_instances().iter().map(|i| i.name.clone()));
+    match volume_sizes().await {
+        Ok(sizes) => {
+            let volumes: Vec<&(String, String)> = sizes
+                .iter()
+                .filter(|(volume, _)| {
+                    volume
+                        .strip_suffix("-data")
+                        .is_some_and(|name| names.contains(name))
+                })
+                .collect();
+            if volumes.is_empty() {
+                println!("    None");
+            }
+            for (volume, size) in volumes {
+                println!("    {:<32} {}", volume, size);
+            }
+        }
+        Err(e) => {
+            println!("    {}", "Unavailable (is Docker running?)".dimmed());
+            if verbose {
+                println!("    {:#}", e);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// (volume, size) for every Docker volume, as `docker system df -v` reports them
+async fn volume_sizes() -> Result<Vec<(String, String)>> {
+    let output = ProcessCommand::new("docker")
+        .args(["system", "df", "-v", "--format", "{{json .Volumes}}"])
+        .output()
+        .await
+        .context("Failed to run docker system df")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "docker system df failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    let volumes: Vec<serde_json::Value> = serde_json::from_slice(&output.stdout)
+        .context("Failed to parse docker system df output")?;
+    let mut sizes: Vec<(String, String)> = volumes
+        .iter()
+        .filter_map(|v| {
+            let name = v.get("Name")?.as_str()?.to_string();
+            let size = v.get("Size")?.as_str()?.to_string();
+            Some((name, size))
+        })
+        .collect();
+    sizes.sort();
+    Ok(sizes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(at: &str, event: EventKind, name: &str, flags: &[&str]) -> Event {
+        Event {
+            at: at.parse().unwrap(),
+            event,
+            name: name.to_string(),
+            instance_type: InstanceType::Basic,
+            flags: flags.iter().map(|f| f.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_summarize() {
+        let args: Vec<String> = [
+            "basic",
+            "start",
+            "--port=6380",
+            "-p",
+            "x",
+            "--persist",
+            "-1",
+        ]
+        .iter()
+        .map(|a| a.to_string())
+        .collect();
+        assert_eq!(flag_names(&args), vec!["--port", "-p", "--persist"]);
+
+        let events = vec![
+            event(
+                "2026-01-01T10:00:00Z",
+                EventKind::Start,
+                "a",
+                &["--persist"],
+            ),
+            event("2026-01-01T10:30:00Z", EventKind::Stop, "a", &[]),
+            event(
+                "2026-01-01T11:00:00Z",
+                EventKind::Start,
+                "b",
+                &["--persist", "--port"],
+            ),
+        ];
+        let summary = summarize(&events);
+        assert_eq!(summary.starts["basic"], 2);
+        assert_eq!(summary.lifetimes["basic"], vec![Duration::from_secs(1800)]);
+        assert_eq!(
+            summary.flags,
+            vec![("--persist".to_string(), 2), ("--port".to_string(), 1)]
+        );
+    }
+}
@@ -0,0 +1,64 @@
+//! Differences between Docker hosts: Linux engines and Docker Desktop
+//!
+//! Host paths handed to `docker run -v` are written with forward slashes, so a
+//! Windows path such as `C:\Users\me\.config\redis-up\tls\c` reaches
+//! Docker Desktop as `C:/Users/me/...` rather than being split at the
+//! drive colon or mangled by backslashes. Containers reach ports published on
+//! the host through `host.docker.internal`, which Docker Desktop provides and
+//! Linux engines map with `host-gateway`.
+
+use std::path::Path;
+
+/// Host name containers use to reach ports published on the host
+pub const GATEWAY_HOST: &str = "host.docker.internal";
+
+/// `--add-host` mapping that makes [`GATEWAY_HOST`] resolve on Linux engines
+pub const GATEWAY_MAPPING: &str = "host.docker.internal:host-gateway";
+
+/// A host path as the source of a bind mount
+pub fn mount_source(path: &Path) -> String {
+    mount_path(&path.display().to_string(), cfg!(windows))
+}
+
+fn mount_path(path: &str, windows: bool) -> String {
+    if !windows {
+        return path.to_string();
+    }
+    // `canonicalize` adds the verbatim prefix, which Docker rejects
+    let path = path.strip_prefix(r"\\?\").unwrap_or(path);
+    path.replace('\\', "/")
+}
+
+/// Address a container uses for a server clients reach at `host`
+///
+/// Loopback only means the host itself from outside a container; other
+/// addresses, such as a `--bind` IP, work from containers as they are.
+pub fn from_container(host: &str) -> String {
+    match host {
+        "localhost" | "127.0.0.1" | "::1" | "[::1]" => GATEWAY_HOST.to_string(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mount_path() {
+        assert_eq!(
+            mount_path("/tmp/ha-sentinel-1.conf", false),
+            "/tmp/ha-sentinel-1.conf"
+        );
+        assert_eq!(
+            mount_path(r"C:\Users\me\AppData\Local\Temp\ha-sentinel-1.conf", true),
+            "C:/Users/me/AppData/Local/Temp/ha-sentinel-1.conf"
+        );
+        assert_eq!(
+            mount_path(r"\\?\C:\Users\me\.config\redis-up\tls\c", true),
+            "C:/Users/me/.config/redis-up/tls/c"
+        );
+        assert_eq!(from_container("localhost"), GATEWAY_HOST);
+        assert_eq!(from_container("192.168.1.20"), "192.168.1.20");
+    }
+}
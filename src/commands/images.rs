@@ -0,0 +1,382 @@
+//! Docker images used by redis-up, pull policy and pre-fetching
+
+use anyhow::{Context, Result};
+use colored::*;
+use docker_wrapper::{DockerCommand, InspectCommand, PullCommand};
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+use crate::cli::{ImagesAction, ImagesArgs, PullArgs, PullPolicy};
+use crate::commands::progress::{Progress, ProgressEvent};
+use crate::settings::Settings;
+
+pub const REDIS_IMAGE: &str = "redis:7-alpine";
+pub const STACK_IMAGE: &str = "redis/redis-stack:latest";
+pub const STACK_SERVER_IMAGE: &str = "redis/redis-stack-server:latest";
+/// Redis 8 bundles the former Stack modules
+pub const REDIS8_IMAGE: &str = "redis:8";
+pub const INSIGHT_IMAGE: &str = "redis/redisinsight:latest";
+pub const ENTERPRISE_IMAGE: &str = "redislabs/redis:latest";
+pub const EXPORTER_IMAGE: &str = "oliver006/redis_exporter:latest";
+pub const PROMETHEUS_IMAGE: &str = "prom/prometheus:latest";
+pub const GRAFANA_IMAGE: &str = "grafana/grafana:latest";
+/// Network toolbox used by `redis-up chaos` to shape traffic with tc
+pub const NETSHOOT_IMAGE: &str = "nicolaka/netshoot:latest";
+/// Load balancer in front of Sentinel masters (`sentinel start --with-lb`)
+pub const HAPROXY_IMAGE: &str = "haproxy:2.9-alpine";
+/// A few kilobytes pulled by `redis-up doctor` to test registry access
+pub const PROBE_IMAGE: &str = "hello-world:latest";
+
+/// Image for a basic instance, `redis:<version>-alpine` when a version is given
+pub fn redis_image(version: Option<&str>) -> String {
+    match version {
+        Some(version) => format!("redis:{}-alpine", version),
+        None => REDIS_IMAGE.to_string(),
+    }
+}
+
+/// Image sources from the settings, read once per run
+struct Sources {
+    mirror: Option<String>,
+    overrides: BTreeMap<String, String>,
+}
+
+fn sources() -> &'static Sources {
+    static SOURCES: OnceLock<Sources> = OnceLock::new();
+    SOURCES.get_or_init(|| {
+        // A broken settings file is reported by the commands that load it
+        let settings = Settings::load().unwrap_or_default();
+        Sources {
+            mirror: settings.registry_mirror,
+            overrides: settings.images,
+        }
+    })
+}
+
+/// The image to run in place of a default one
+///
+/// An `images` entry for the repository wins (keeping the default tag when it
+/// has none); otherwise the `registry-mirror` is put in front.
+pub fn resolve(image: &str) -> String {
+    let sources = sources();
+    resolve_with(image, sources.mirror.as_deref(), &sources.overrides)
+}
+
+/// [`resolve`], split into repository and tag for templates that take them apart
+pub fn resolve_parts(image: &str) -> (String, String) {
+    let resolved = resolve(image);
+    let (repository, tag) = split_tag(&resolved);
+    (repository.to_string(), tag.unwrap_or("latest").to_string())
+}
+
+/// Swap default images in a `docker run` argument list for their resolved ones
+///
+/// For containers built from docker-wrapper templates, which pick the image themselves.
+pub fn resolve_args(args: &mut [String]) {
+    for arg in args.iter_mut() {
+        if is_default_image(arg) {
+            *arg = resolve(arg);
+        }
+    }
+}
+
+fn is_default_image(arg: &str) -> bool {
+    CORE_IMAGES.contains(&arg)
+        || OBSERVABILITY_IMAGES.contains(&arg)
+        || arg == NETSHOOT_IMAGE
+        || split_tag(arg).0 == "redis" && arg.ends_with("-alpine")
+}
+
+fn resolve_with(image: &str, mirror: Option<&str>, overrides: &BTreeMap<String, String>) -> String {
+    let (repository, tag) = split_tag(image);
+    if let Some(replacement) = overrides.get(repository) {
+        return match (split_tag(replacement).1, tag) {
+            (None, Some(tag)) => format!("{}:{}", replacement, tag),
+            _ => replacement.clone(),
+        };
+    }
+    match mirror {
+        Some(mirror) => format!("{}/{}", mirror.trim_end_matches('/'), image),
+        None => image.to_string(),
+    }
+}
+
+/// Repository and tag; a registry port (`host:5000/redis`) is not a tag
+fn split_tag(image: &str) -> (&str, Option<&str>) {
+    match image.rsplit_once(':') {
+        Some((repository, tag)) if !tag.contains('/') => (repository, Some(tag)),
+        _ => (image, None),
+    }
+}
+
+/// Images the start commands may run
+const CORE_IMAGES: &[&str] = &[
+    REDIS_IMAGE,
+    STACK_IMAGE,
+    STACK_SERVER_IMAGE,
+    REDIS8_IMAGE,
+    INSIGHT_IMAGE,
+    ENTERPRISE_IMAGE,
+    HAPROXY_IMAGE,
+];
+
+/// Images used by `redis-up observability`
+const OBSERVABILITY_IMAGES: &[&str] = &[EXPORTER_IMAGE, PROMETHEUS_IMAGE, GRAFANA_IMAGE];
+
+/// Images published without an arm64 variant
+const AMD64_ONLY_IMAGES: &[&str] = &[ENTERPRISE_IMAGE];
+
+const AMD64: &str = "linux/amd64";
+
+pub async fn handle_action(action: ImagesAction, verbose: bool) -> Result<()> {
+    match action {
+        ImagesAction::Pull(args) => pull_images(args, verbose).await,
+        ImagesAction::List(args) => list_images(args).await,
+    }
+}
+
+fn selected_images(args: &ImagesArgs) -> Vec<&'static str> {
+    let mut images = CORE_IMAGES.to_vec();
+    if args.observability {
+        images.extend_from_slice(OBSERVABILITY_IMAGES);
+    }
+    images
+}
+
+async fn pull_images(args: ImagesArgs, verbose: bool) -> Result<()> {
+    let images = selected_images(&args);
+    let mut failed = Vec::new();
+
+    for default in &images {
+        let image = resolve(default);
+        println!("{} {}", "Pulling".cyan(), image.bold());
+        let platform = fallback_platform(host_platform(), &[default]);
+        match pull(&image, platform.as_deref()).await {
+            Ok(()) => {
+                if verbose {
+                    println!("  {} {}", "Pulled".green(), image);
+                }
+            }
+            Err(e) => {
+                println!("  {} {:#}", "Error:".red(), e);
+                failed.push(image);
+            }
+        }
+    }
+
+    if !failed.is_empty() {
+        anyhow::bail!("Failed to pull {}", failed.join(", "));
+    }
+
+    println!();
+    println!(
+        "{} {} images available for offline use",
+        "Success:".green(),
+        images.len()
+    );
+    Ok(())
+}
+
+async fn list_images(args: ImagesArgs) -> Result<()> {
+    println!("{}", "Images:".bold().underline());
+    for image in selected_images(&args).into_iter().map(resolve) {
+        let status = if is_present(&image).await {
+            "present".green()
+        } else {
+            "missing".yellow()
+        };
+        println!("  {:<36} {}", image, status);
+    }
+    Ok(())
+}
+
+/// Use `--pull`/`--offline` when given, otherwise the `pull` setting
+fn resolve_policy(args: &PullArgs) -> Result<PullPolicy> {
+    if args.offline {
+        return Ok(PullPolicy::Never);
+    }
+    if let Some(policy) = args.pull {
+        return Ok(policy);
+    }
+    Ok(Settings::load()?.pull.unwrap_or_default())
+}
+
+/// Platform for the containers of one instance type
+///
+/// `--platform` wins, then the `platforms` entry for the type in the settings
+/// file. Otherwise images without a native build fall back to linux/amd64.
+pub fn resolve_platform(args: &PullArgs, kind: &str, images: &[&str]) -> Result<Option<String>> {
+    if let Some(ref platform) = args.platform {
+        return Ok(Some(platform.clone()));
+    }
+    if let Some(platform) = Settings::load()?.platforms.get(kind) {
+        return Ok(Some(platform.clone()));
+    }
+    Ok(fallback_platform(host_platform(), images))
+}
+
+/// Docker platform matching this machine
+pub fn host_platform() -> &'static str {
+    match std::env::consts::ARCH {
+        "aarch64" => "linux/arm64",
+        _ => AMD64,
+    }
+}
+
+fn fallback_platform(host: &str, images: &[&str]) -> Option<String> {
+    let needs_amd64 = images.iter().any(|image| AMD64_ONLY_IMAGES.contains(image));
+    (host != AMD64 && needs_amd64).then(|| AMD64.to_string())
+}
+
+/// Apply the pull policy to the images a command is about to run
+///
+/// With `missing`, Docker pulls absent images itself when the container is
+/// created, unless progress is being reported and the pull should show up as a step.
+pub async fn ensure_images(
+    images: &[&str],
+    args: &PullArgs,
+    platform: Option<&str>,
+    progress: Option<&Progress>,
+    verbose: bool,
+) -> Result<()> {
+    if let Some(platform) = platform {
+        if platform != host_platform() {
+            println!(
+                "{} Running {} images on a {} host under emulation; expect slower startup. Use --platform to override.",
+                "Warning:".yellow(),
+                platform,
+                host_platform()
+            );
+        }
+    }
+
+    let images: Vec<String> = images.iter().map(|image| resolve(image)).collect();
+    match resolve_policy(args)? {
+        PullPolicy::Missing => {
+            let Some(progress) = progress else {
+                return Ok(());
+            };
+            for image in &images {
+                if !is_present(image).await {
+                    progress.emit(ProgressEvent::PullingImage {
+                        image: image.to_string(),
+                    });
+                    pull(image, platform).await?;
+                }
+            }
+            Ok(())
+        }
+        PullPolicy::Always => {
+            for image in &images {
+                if let Some(progress) = progress {
+                    progress.emit(ProgressEvent::PullingImage {
+                        image: image.to_string(),
+                    });
+                } else if verbose {
+                    println!("{} {}", "Pulling".cyan(), image);
+                }
+                pull(image, platform).await?;
+            }
+            Ok(())
+        }
+        PullPolicy::Never => {
+            let mut missing = Vec::new();
+            for image in &images {
+                if !is_present(image).await {
+                    missing.push(image.as_str());
+                }
+            }
+            if !missing.is_empty() {
+                anyhow::bail!(
+                    "Image(s) not available locally and pulling is disabled: {}. Run 'redis-up images pull' while online, or use --pull missing.",
+                    missing.join(", ")
+                );
+            }
+            Ok(())
+        }
+    }
+}
+
+#[tracing::instrument(name = "image_pull", skip(platform))]
+pub async fn pull(image: &str, platform: Option<&str>) -> Result<()> {
+    let mut command = PullCommand::new(image).quiet();
+    if let Some(platform) = platform {
+        command = command.platform(platform);
+    }
+    command
+        .execute()
+        .await
+        .with_context(|| format!("Failed to pull {}{}", image, proxy_hint()))?;
+    Ok(())
+}
+
+/// Why a pull may fail behind a proxy, for pull errors
+///
+/// The daemon pulls images itself and never sees this shell's proxy variables.
+pub fn proxy_hint() -> String {
+    let proxy = ["HTTPS_PROXY", "https_proxy", "HTTP_PROXY", "http_proxy"]
+        .into_iter()
+        .find(|name| std::env::var(name).is_ok_and(|value| !value.is_empty()));
+    match proxy {
+        Some(name) => format!(
+            " ({} is set here, but the Docker daemon pulls images itself and ignores it: configure the \
+             daemon's proxy (Docker Desktop: Settings > Resources > Proxies; Linux: \"proxies\" in \
+             /etc/docker/daemon.json) or a registry-mirror in settings.yaml)",
+            name
+        ),
+        None => String::new(),
+    }
+}
+
+pub async fn is_present(image: &str) -> bool {
+    InspectCommand::new(image)
+        .object_type("image")
+        .execute()
+        .await
+        .is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fallback_platform() {
+        assert_eq!(
+            fallback_platform("linux/arm64", &[ENTERPRISE_IMAGE]),
+            Some("linux/amd64".to_string())
+        );
+        assert_eq!(fallback_platform("linux/arm64", &[REDIS_IMAGE]), None);
+        assert_eq!(fallback_platform("linux/amd64", &[ENTERPRISE_IMAGE]), None);
+    }
+
+    #[test]
+    fn test_resolve_with() {
+        let overrides: BTreeMap<String, String> = [
+            (
+                "redis/redis-stack".to_string(),
+                "acme/stack:7.2".to_string(),
+            ),
+            (
+                "redis".to_string(),
+                "registry.acme.com:5000/redis".to_string(),
+            ),
+        ]
+        .into();
+        assert_eq!(
+            resolve_with(STACK_IMAGE, Some("mirror.acme.com"), &overrides),
+            "acme/stack:7.2"
+        );
+        // An override without a tag keeps the default one
+        assert_eq!(
+            resolve_with("redis:7.2-alpine", None, &overrides),
+            "registry.acme.com:5000/redis:7.2-alpine"
+        );
+        assert_eq!(
+            resolve_with(ENTERPRISE_IMAGE, Some("mirror.acme.com/hub/"), &overrides),
+            "mirror.acme.com/hub/redislabs/redis:latest"
+        );
+        assert_eq!(resolve_with(INSIGHT_IMAGE, None, &overrides), INSIGHT_IMAGE);
+        assert_eq!(split_tag("host:5000/redis"), ("host:5000/redis", None));
+    }
+}
@@ -0,0 +1,287 @@
+//! `include` and `extends` in deployment files
+//!
+//! A file can pull in the deployments of shared files with a top-level
+//! `include: [common.yaml]`; a deployment of the same name in the including
+//! file overrides the included one key by key. A deployment can also start
+//! from another one with `extends:` and change only what differs:
+//!
+//! - `extends: cache`: the deployment named `cache` in this file or its includes
+//! - `extends: team.yaml`: the deployment of the same name in `team.yaml`
+//! - `extends: team.yaml#cache`: the deployment named `cache` in `team.yaml`
+//!
+//! Merging is shallow: a key on the deployment replaces the base's value,
+//! lists included. Paths are relative to the file that names them. Everything
+//! is resolved before the file is checked, and a file or deployment that ends
+//! up including or extending itself is an error showing the chain.
+
+use anyhow::{bail, Context, Result};
+use serde_yaml::{Mapping, Value};
+use std::path::Path;
+
+use crate::commands::post_start::{self, Step};
+
+/// Read a deployment file with its includes and extends resolved
+pub fn load(path: &Path) -> Result<Value> {
+    Loader::default().file(path)
+}
+
+#[derive(Default)]
+struct Loader {
+    /// Files and deployments being resolved, outermost first
+    chain: Vec<(String, String)>,
+}
+
+impl Loader {
+    /// Resolve a file: its includes' deployments, then its own on top
+    fn file(&mut self, path: &Path) -> Result<Value> {
+        let key = std::fs::canonicalize(path)
+            .unwrap_or_else(|_| path.to_path_buf())
+            .display()
+            .to_string();
+        self.enter(key, path.display().to_string())?;
+
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read YAML file: {}", path.display()))?;
+        let mut document: Value = serde_yaml::from_str(&content)
+            .with_context(|| format!("Failed to parse YAML file: {}", path.display()))?;
+        let base = path.parent().unwrap_or(Path::new("."));
+
+        let Some(fields) = document.as_mapping_mut() else {
+            bail!("{}: expected a mapping with `deployments`", path.display());
+        };
+        let includes: Vec<String> = match fields.remove("include") {
+            Some(value) => serde_yaml::from_value(value).with_context(|| {
+                format!("{}: `include` must be a list of files", path.display())
+            })?,
+            None => Vec::new(),
+        };
+        let own: Vec<Value> = match fields.remove("deployments") {
+            Some(value) => serde_yaml::from_value(value)
+                .with_context(|| format!("{}: `deployments` must be a list", path.display()))?,
+            None => Vec::new(),
+        };
+
+        let mut merged = Vec::new();
+        for include in &includes {
+            let included = self.file(&base.join(include))?;
+            for deployment in deployments(&included) {
+                add(&mut merged, deployment.clone());
+            }
+        }
+        let own: Vec<Value> = own
+            .into_iter()
+            .map(|deployment| with_script_paths(deployment, base))
+            .collect();
+        let included = merged.clone();
+        for deployment in &own {
+            let resolved = self.deployment(path, deployment, &own, &included)?;
+            add(&mut merged, resolved);
+        }
+
+        fields.insert("deployments".into(), Value::Sequence(merged));
+        self.chain.pop();
+        Ok(document)
+    }
+
+    /// Resolve one deployment of `path`, following its `extends`
+    fn deployment(
+        &mut self,
+        path: &Path,
+        deployment: &Value,
+        own: &[Value],
+        included: &[Value],
+    ) -> Result<Value> {
+        let Some(fields) = deployment.as_mapping() else {
+            // Left for the schema check to report
+            return Ok(deployment.clone());
+        };
+        let name = name(deployment).unwrap_or_default().to_string();
+        let mut fields = fields.clone();
+        let extends = match fields.remove("extends") {
+            None => return Ok(deployment.clone()),
+            Some(Value::String(extends)) => extends,
+            Some(_) => bail!("Deployment '{}': `extends` must be a string", name),
+        };
+        self.enter(
+            format!("{}#{}", path.display(), name),
+            format!("{}#{}", path.display(), name),
+        )?;
+
+        let base_dir = path.parent().unwrap_or(Path::new("."));
+        let (file, base_name) = match extends.split_once('#') {
+            Some((file, base_name)) => (Some(file), base_name.to_string()),
+            None if extends.ends_with(".yaml") || extends.ends_with(".yml") => {
+                (Some(extends.as_str()), name.clone())
+            }
+            None => (None, extends.clone()),
+        };
+        let base = match file {
+            Some(file) => {
+                let document = self.file(&base_dir.join(file))?;
+                find(deployments(&document), &base_name)
+                    .with_context(|| {
+                        format!(
+                            "Deployment '{}' extends '{}', but {} has no deployment '{}'",
+                            name, extends, file, base_name
+                        )
+                    })?
+                    .clone()
+            }
+            // A deployment extending its own name means the included one
+            None => match own
+                .iter()
+                .filter(|other| !std::ptr::eq(*other, deployment))
+                .find(|other| self::name(other) == Some(base_name.as_str()))
+            {
+                Some(base) => self.deployment(path, base, own, included)?,
+                None => find(included, &base_name)
+                    .with_context(|| {
+                        format!(
+                            "Deployment '{}' extends '{}', which is not in this file or its includes",
+                            name, extends
+                        )
+                    })?
+                    .clone(),
+            },
+        };
+
+        if let (Some(kind), Some(base_kind)) = (fields.get("type"), base.get("type")) {
+            if kind != base_kind {
+                bail!(
+                    "Deployment '{}' can't change the type of '{}' it extends",
+                    name,
+                    extends
+                );
+            }
+        }
+        self.chain.pop();
+        Ok(overlay(&base, &fields))
+    }
+
+    fn enter(&mut self, key: String, label: String) -> Result<()> {
+        if self.chain.iter().any(|(entered, _)| *entered == key) {
+            let chain: Vec<&str> = self
+                .chain
+                .iter()
+                .map(|(_, label)| label.as_str())
+                .chain([label.as_str()])
+                .collect();
+            bail!("Include cycle: {}", chain.join(" -> "));
+        }
+        self.chain.push((key, label));
+        Ok(())
+    }
+}
+
+fn name(deployment: &Value) -> Option<&str> {
+    deployment.get("name").and_then(Value::as_str)
+}
+
+fn deployments(document: &Value) -> &[Value] {
+    document["deployments"]
+        .as_sequence()
+        .map_or(&[], Vec::as_slice)
+}
+
+fn find<'a>(deployments: &'a [Value], name: &str) -> Option<&'a Value> {
+    deployments
+        .iter()
+        .find(|deployment| self::name(deployment) == Some(name))
+}
+
+/// Add a deployment, merging it over an earlier one of the same name
+fn add(deployments: &mut Vec<Value>, deployment: Value) {
+    let existing = name(&deployment).and_then(|name| {
+        deployments
+            .iter()
+            .position(|earlier| self::name(earlier) == Some(name))
+    });
+    match (existing, deployment.as_mapping()) {
+        (Some(i), Some(fields)) => deployments[i] = overlay(&deployments[i], fields),
+        _ => deployments.push(deployment),
+    }
+}
+
+/// The base's keys with the deployment's on top; either spelling of a key replaces the other
+pub fn overlay(base: &Value, fields: &Mapping) -> Value {
+    let mut merged = base.as_mapping().cloned().unwrap_or_default();
+    for (key, value) in fields {
+        if let Some(key) = key.as_str() {
+            merged.remove(key.replace('-', "_").as_str());
+            merged.remove(key.replace('_', "-").as_str());
+        }
+        merged.insert(key.clone(), value.clone());
+    }
+    Value::Mapping(merged)
+}
+
+/// Make `post-start` script paths relative to the file that names them
+fn with_script_paths(mut deployment: Value, base: &Path) -> Value {
+    for key in ["post-start", "post_start"] {
+        let Some(value) = deployment.get_mut(key) else {
+            continue;
+        };
+        // Malformed steps are left for the schema check to report
+        if let Ok(mut steps) = serde_yaml::from_value::<Vec<Step>>(value.clone()) {
+            post_start::resolve(&mut steps, base);
+            *value = serde_yaml::to_value(steps).unwrap_or_default();
+        }
+    }
+    deployment
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn write(dir: &Path, file: &str, content: &str) -> PathBuf {
+        let path = dir.join(file);
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_include_extends_and_cycles() {
+        let dir = tempfile::tempdir().unwrap();
+        write(
+            dir.path(),
+            "team.yaml",
+            "deployments:\n  - {name: cache, type: basic, port: 6379, memory: 256m}\n  - {name: search, type: stack, port: 6380}\n",
+        );
+        let path = write(
+            dir.path(),
+            "alice.yaml",
+            "include: [team.yaml]\ndeployments:\n  - {name: search, port: 7380}\n  - {name: alice-cache, extends: 'team.yaml#cache', port: 7379}\n  - {name: scratch, extends: alice-cache, with_insight: true}\n",
+        );
+        let document = load(&path).unwrap();
+        let deployments = document["deployments"].as_sequence().unwrap();
+        let names: Vec<_> = deployments.iter().filter_map(name).collect();
+        assert_eq!(names, ["cache", "search", "alice-cache", "scratch"]);
+        assert_eq!(deployments[1]["type"], "stack");
+        assert_eq!(deployments[1]["port"], 7380);
+        assert_eq!(deployments[2]["memory"], "256m");
+        assert_eq!(deployments[2]["port"], 7379);
+        assert_eq!(deployments[3]["port"], 7379);
+        assert!(deployments[3].get("extends").is_none());
+
+        write(dir.path(), "a.yaml", "include: [b.yaml]\ndeployments: []\n");
+        let path = write(dir.path(), "b.yaml", "include: [a.yaml]\ndeployments: []\n");
+        let err = load(&path).unwrap_err().to_string();
+        assert!(
+            err.contains("b.yaml -> ") && err.ends_with("b.yaml"),
+            "{}",
+            err
+        );
+
+        let path = write(
+            dir.path(),
+            "loop.yaml",
+            "deployments:\n  - {name: x, extends: y}\n  - {name: y, extends: x}\n",
+        );
+        assert!(load(&path)
+            .unwrap_err()
+            .to_string()
+            .contains("Include cycle"));
+    }
+}
@@ -0,0 +1,277 @@
+//! Interactive setup wizard that writes a deployment file
+
+use anyhow::{Context, Result};
+use colored::*;
+use dialoguer::theme::ColorfulTheme;
+use dialoguer::{Confirm, Input, Select};
+use std::io::{self, IsTerminal};
+
+use crate::cli::InitArgs;
+use crate::commands::yaml::{
+    self, BasicConfig, ClusterConfig, Deployment, DeploymentConfig, SentinelConfig, StackConfig,
+    YamlConfig,
+};
+use crate::commands::{capacity, names};
+
+/// Environments the wizard knows how to propose
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Environment {
+    Cache,
+    HighAvailability,
+    Cluster,
+    Search,
+}
+
+impl Environment {
+    const ALL: [Environment; 4] = [
+        Environment::Cache,
+        Environment::HighAvailability,
+        Environment::Cluster,
+        Environment::Search,
+    ];
+
+    fn description(self) -> &'static str {
+        match self {
+            Environment::Cache => "Cache - a single Redis instance",
+            Environment::HighAvailability => "High availability - a master watched by Sentinels",
+            Environment::Cluster => "Cluster - data sharded across several masters",
+            Environment::Search => "Search - Redis Stack with JSON, Search and TimeSeries",
+        }
+    }
+
+    fn default_name(self) -> &'static str {
+        match self {
+            Environment::Cache => "cache",
+            Environment::HighAvailability => "ha",
+            Environment::Cluster => "cluster",
+            Environment::Search => "search",
+        }
+    }
+}
+
+/// Everything the wizard asked
+#[derive(Debug, Clone)]
+struct Answers {
+    environment: Environment,
+    name: String,
+    persist: bool,
+    memory: Option<String>,
+    with_insight: bool,
+    masters: u8,
+    replicas: u8,
+    sentinels: u8,
+}
+
+pub async fn handle_init(args: InitArgs, verbose: bool) -> Result<()> {
+    if args.file.exists() && !args.force {
+        anyhow::bail!(
+            "{} already exists. Use --force to overwrite it.",
+            args.file.display()
+        );
+    }
+
+    if !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+        anyhow::bail!("No terminal available; run 'redis-up init' from an interactive terminal");
+    }
+
+    println!("{}", "redis-up setup".bold().underline());
+    println!();
+
+    let answers = ask_questions()?;
+    let config = YamlConfig {
+        api_version: "v1".to_string(),
+        deployments: vec![propose(&answers)],
+    };
+    let content = serde_yaml::to_string(&config)?;
+
+    println!();
+    println!("{} {}", "Proposed:".bold().cyan(), args.file.display());
+    println!();
+    println!("{}", content.dimmed());
+
+    if !confirm("Write this file?", true)? {
+        println!("Setup cancelled.");
+        return Ok(());
+    }
+    tokio::fs::write(&args.file, &content)
+        .await
+        .with_context(|| format!("Failed to write {}", args.file.display()))?;
+    println!("{} Wrote {}", "Success:".green(), args.file.display());
+
+    if confirm("Deploy it now?", false)? {
+        println!();
+        yaml::deploy_from_yaml(&args.file, None, verbose).await?;
+    } else {
+        println!(
+            "{} Run 'redis-up deploy {}' when you're ready",
+            "Next:".cyan(),
+            args.file.display()
+        );
+    }
+
+    Ok(())
+}
+
+fn ask_questions() -> Result<Answers> {
+    let theme = ColorfulTheme::default();
+
+    let descriptions: Vec<_> = Environment::ALL.iter().map(|e| e.description()).collect();
+    let choice = Select::with_theme(&theme)
+        .with_prompt("What do you need?")
+        .items(&descriptions)
+        .default(0)
+        .interact()?;
+    let environment = Environment::ALL[choice];
+
+    let name: String = Input::with_theme(&theme)
+        .with_prompt("Name")
+        .default(environment.default_name().to_string())
+        .validate_with(|name: &String| names::validate(name).map_err(|e| e.to_string()))
+        .interact_text()?;
+    let (masters, replicas, sentinels) = match environment {
+        Environment::Cluster => (
+            Input::with_theme(&theme)
+                .with_prompt("Number of masters")
+                .default(3)
+                .validate_with(|n: &u8| match n {
+                    3.. => Ok(()),
+                    _ => Err("A cluster needs at least 3 masters"),
+                })
+                .interact_text()?,
+            Input::with_theme(&theme)
+                .with_prompt("Replicas per master")
+                .default(1)
+                .interact_text()?,
+            0,
+        ),
+        Environment::HighAvailability => (
+            1,
+            0,
+            Input::with_theme(&theme)
+                .with_prompt("Number of sentinels")
+                .default(3)
+                .validate_with(|n: &u8| match n {
+                    1.. => Ok(()),
+                    _ => Err("At least one sentinel is needed"),
+                })
+                .interact_text()?,
+        ),
+        _ => (1, 0, 0),
+    };
+    let persist = Confirm::with_theme(&theme)
+        .with_prompt("Keep data across restarts?")
+        .default(environment != Environment::Cache)
+        .interact()?;
+    let memory: String = Input::with_theme(&theme)
+        .with_prompt("Memory limit per container (e.g. 512m, blank for none)")
+        .allow_empty(true)
+        .validate_with(|memory: &String| match memory.trim() {
+            "" => Ok(()),
+            size => capacity::parse_size(size)
+                .map(|_| ())
+                .map_err(|e| e.to_string()),
+        })
+        .interact_text()?;
+    let memory = Some(memory.trim().to_string()).filter(|m| !m.is_empty());
+    let with_insight = Confirm::with_theme(&theme)
+        .with_prompt("Include RedisInsight?")
+        .default(false)
+        .interact()?;
+
+    Ok(Answers {
+        environment,
+        name,
+        persist,
+        memory,
+        with_insight,
+        masters,
+        replicas,
+        sentinels,
+    })
+}
+
+/// Turn the answers into a deployment
+fn propose(answers: &Answers) -> Deployment {
+    let Answers {
+        name,
+        persist,
+        memory,
+        with_insight,
+        ..
+    } = answers.clone();
+
+    let config = match answers.environment {
+        Environment::Cache => DeploymentConfig::Basic(BasicConfig {
+            persist,
+            memory,
+            with_insight,
+            ..Default::default()
+        }),
+        Environment::Search => DeploymentConfig::Stack(StackConfig {
+            persist,
+            memory,
+            with_insight,
+            ..Default::default()
+        }),
+        Environment::Cluster => DeploymentConfig::Cluster(ClusterConfig {
+            masters: Some(answers.masters.into()),
+            replicas: Some(answers.replicas.into()),
+            persist,
+            memory,
+            with_insight,
+            ..Default::default()
+        }),
+        Environment::HighAvailability => DeploymentConfig::Sentinel(SentinelConfig {
+            sentinels: Some(answers.sentinels.into()),
+            persist,
+            memory,
+            with_insight,
+            ..Default::default()
+        }),
+    };
+
+    Deployment {
+        name,
+        description: None,
+        post_start: Vec::new(),
+        config,
+    }
+}
+
+fn confirm(question: &str, default: bool) -> Result<bool> {
+    Ok(Confirm::with_theme(&ColorfulTheme::default())
+        .with_prompt(question)
+        .default(default)
+        .interact()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_propose_cluster() {
+        let answers = Answers {
+            environment: Environment::Cluster,
+            name: "orders".to_string(),
+            persist: true,
+            memory: Some("256m".to_string()),
+            with_insight: false,
+            masters: 6,
+            replicas: 1,
+            sentinels: 0,
+        };
+
+        let deployment = propose(&answers);
+        assert_eq!(deployment.name, "orders");
+        assert!(matches!(
+            deployment.config,
+            DeploymentConfig::Cluster(ClusterConfig {
+                masters: Some(6),
+                replicas: Some(1),
+                persist: true,
+                ..
+            })
+        ));
+    }
+}
@@ -2,14 +2,23 @@
 
 use anyhow::{Context, Result};
 use colored::*;
-use docker_wrapper::{DockerCommand, RunCommand};
-use std::collections::HashMap;
+use docker_wrapper::{
+    DockerCommand, NetworkConnectCommand, NetworkCreateCommand, NetworkDisconnectCommand,
+    NetworkRmCommand, RunCommand,
+};
+use std::collections::{HashMap, HashSet};
+
+use crate::cli::{InsightAction, InsightStartArgs};
+use crate::commands::plan::{Operation, Plan};
+use crate::commands::{host, images, passthrough, server};
+use crate::config::{Config, InstanceInfo};
 
 /// Redis Insight configuration
 pub struct InsightConfig {
     pub name: String,
     pub port: u16,
     pub network: Option<String>,
+    pub platform: Option<String>,
 }
 
 impl InsightConfig {
@@ -19,19 +28,93 @@ impl InsightConfig {
             name: name.into(),
             port,
             network: None,
+            platform: None,
         }
     }
 
     /// Set the network for Insight to connect to
-    #[allow(dead_code)]
     pub fn with_network(mut self, network: impl Into<String>) -> Self {
         self.network = Some(network.into());
         self
     }
+
+    /// Set the image platform, e.g. linux/amd64
+    pub fn with_platform(mut self, platform: Option<&str>) -> Self {
+        self.platform = platform.map(String::from);
+        self
+    }
+}
+
+/// Pick a host port for a new RedisInsight container
+///
+/// Skips ports held by other managed instances or already bound on the host,
+/// so a second `--with-insight` on the default port doesn't collide.
+pub fn resolve_port(config: &Config, requested: u16) -> u16 {
+    let mut taken: HashSet<u16> = HashSet::new();
+    for instance in config.instances.values() {
+        taken.extend(instance.ports.iter().copied());
+        taken.extend(instance.connection_info.additional_ports.values().copied());
+        if let Some(port) = instance
+            .metadata
+            .get("insight_port")
+            .and_then(|v| v.as_u64())
+        {
+            taken.insert(port as u16);
+        }
+    }
+
+    let port = next_free_port(requested, &taken, |port| {
+        std::net::TcpListener::bind(("0.0.0.0", port)).is_ok()
+    });
+    if port != requested {
+        println!(
+            "{} RedisInsight port {} is in use, using {} instead",
+            "Note:".yellow(),
+            requested,
+            port
+        );
+    }
+    port
+}
+
+/// First port at or above `requested` that is neither taken nor bound
+fn next_free_port(requested: u16, taken: &HashSet<u16>, is_free: impl Fn(u16) -> bool) -> u16 {
+    (requested..=u16::MAX)
+        .find(|port| !taken.contains(port) && is_free(*port))
+        .unwrap_or(requested)
+}
+
+impl InsightConfig {
+    /// The `docker run` that starts this RedisInsight
+    pub fn run_command(&self) -> RunCommand {
+        let mut cmd = passthrough::apply(RunCommand::new(images::resolve(images::INSIGHT_IMAGE)))
+            .name(format!("{}-insight", self.name))
+            .port(self.port, 5540) // RedisInsight runs on port 5540 inside container
+            .detach();
+
+        // Add network if specified
+        if let Some(network) = &self.network {
+            cmd = cmd.network(network);
+        }
+
+        if let Some(platform) = &self.platform {
+            cmd = cmd.platform(platform);
+        }
+
+        // Set environment variables for Redis Insight
+        cmd = cmd
+            .env("REDISINSIGHT_PORT", "5540")
+            .env("REDISINSIGHT_HOST", "0.0.0.0")
+            .env("REDISINSIGHT_LOG_LEVEL", "warning") // Reduce log noise
+            .env("RI_ACCEPT_TERMS_AND_CONDITIONS", "true");
+
+        // Lets standalone databases published on the host be reached on Linux too
+        cmd.add_host(host::GATEWAY_MAPPING)
+    }
 }
 
 /// Start a Redis Insight container
-pub async fn start_insight(config: InsightConfig, verbose: bool) -> Result<String> {
+pub async fn start_insight(config: &InsightConfig, verbose: bool) -> Result<String> {
     let container_name = format!("{}-insight", config.name);
 
     if verbose {
@@ -42,24 +125,7 @@ pub async fn start_insight(config: InsightConfig, verbose: bool) -> Result<Strin
         );
     }
 
-    let mut cmd = RunCommand::new("redis/redisinsight:latest")
-        .name(&container_name)
-        .port(config.port, 5540) // RedisInsight runs on port 5540 inside container
-        .detach();
-
-    // Add network if specified
-    if let Some(network) = &config.network {
-        cmd = cmd.network(network);
-    }
-
-    // Set environment variables for Redis Insight
-    cmd = cmd
-        .env("REDISINSIGHT_PORT", "5540")
-        .env("REDISINSIGHT_HOST", "0.0.0.0")
-        .env("REDISINSIGHT_LOG_LEVEL", "warning"); // Reduce log noise
-
-    let container_id = cmd
-        .execute()
+    let container_id = server::run_container(&config.run_command(), None)
         .await
         .context("Failed to start Redis Insight container")?;
 
@@ -71,7 +137,66 @@ pub async fn start_insight(config: InsightConfig, verbose: bool) -> Result<Strin
         );
     }
 
-    Ok(container_id.0)
+    Ok(container_id)
+}
+
+/// The RedisInsight a `--with-insight` start registers its databases with
+pub enum Insight {
+    /// The shared RedisInsight, on this host port
+    Shared(u16),
+    /// A RedisInsight of the instance's own
+    Own(InsightConfig),
+}
+
+impl Insight {
+    /// The shared RedisInsight if it is running, or a new one on a free port
+    pub async fn resolve(
+        config: &Config,
+        name: &str,
+        requested_port: u16,
+        network: Option<String>,
+        platform: Option<&str>,
+    ) -> Self {
+        if let Some(port) = shared_insight_port().await {
+            return Insight::Shared(port);
+        }
+        let mut own =
+            InsightConfig::new(name, resolve_port(config, requested_port)).with_platform(platform);
+        own.network = network;
+        Insight::Own(own)
+    }
+
+    /// Host port the GUI is published on
+    pub fn port(&self) -> u16 {
+        match self {
+            Insight::Shared(port) => *port,
+            Insight::Own(own) => own.port,
+        }
+    }
+
+    /// Add starting or attaching to this RedisInsight to a dry-run plan
+    ///
+    /// `instance_network` and `attach` are as for [`register_with_shared`].
+    pub fn plan(&self, plan: &mut Plan, instance_network: Option<&str>, attach: &[String]) {
+        match self {
+            Insight::Shared(_) => match instance_network {
+                Some(network) => plan.docker(&NetworkConnectCommand::new(
+                    network,
+                    format!("{}-insight", SHARED_INSIGHT),
+                )),
+                None => {
+                    for container in attach {
+                        plan.docker(&NetworkConnectCommand::new(SHARED_NETWORK, container));
+                    }
+                }
+            },
+            Insight::Own(own) => plan.run(images::INSIGHT_IMAGE, &own.run_command(), None),
+        }
+        plan.push(Operation::Api {
+            method: "POST".to_string(),
+            url: format!("http://localhost:{}/api/databases", self.port()),
+        });
+    }
 }
 
 /// Stop a Redis Insight container
@@ -115,10 +240,14 @@ pub fn print_insight_instructions(insight_port: u16, connections: Vec<RedisConne
                     println!("    - Host: {}", conn.host);
                     println!("    - Port: {}", conn.port);
                 }
-                ConnectionType::Sentinel { sentinel_port } => {
+                ConnectionType::Sentinel {
+                    sentinel_port,
+                    ref master_name,
+                } => {
                     println!("    - Connection Type: Sentinel");
                     println!("    - Sentinel Host: {}", conn.host);
                     println!("    - Sentinel Port: {}", sentinel_port);
+                    println!("    - Master Group: {}", master_name);
                 }
             }
             if let Some(ref pwd) = conn.password {
@@ -143,7 +272,10 @@ pub struct RedisConnection {
 pub enum ConnectionType {
     Standalone,
     Cluster,
-    Sentinel { sentinel_port: u16 },
+    Sentinel {
+        sentinel_port: u16,
+        master_name: String,
+    },
     Enterprise,
 }
 
@@ -164,8 +296,142 @@ pub fn create_redis_connection(
     }
 }
 
+/// Register databases with RedisInsight, falling back to manual instructions
+pub async fn provision_connections(
+    insight_port: u16,
+    connections: Vec<RedisConnection>,
+    verbose: bool,
+) {
+    match register_connections(insight_port, &connections, verbose).await {
+        Ok(()) => {
+            println!("\n{}", "RedisInsight GUI:".bold().underline());
+            println!(
+                "  {} http://localhost:{}",
+                "Access at:".cyan(),
+                insight_port
+            );
+            for conn in &connections {
+                println!("  {} {}", "Registered:".cyan(), conn.name);
+            }
+        }
+        Err(e) => {
+            println!(
+                "{} Could not register databases with RedisInsight: {:#}",
+                "Warning:".yellow(),
+                e
+            );
+            print_insight_instructions(insight_port, connections);
+        }
+    }
+}
+
+/// Add databases to a running RedisInsight through its REST API
+pub async fn register_connections(
+    insight_port: u16,
+    connections: &[RedisConnection],
+    verbose: bool,
+) -> Result<()> {
+    let base_url = format!("http://localhost:{}/api", insight_port);
+    // Local port; a corporate HTTP(S)_PROXY can't reach it
+    let client = reqwest::Client::builder()
+        .no_proxy()
+        .timeout(std::time::Duration::from_secs(10))
+        .build()
+        .context("Failed to create HTTP client")?;
+
+    // The API comes up a few seconds after the container starts
+    let mut ready = false;
+    for _ in 0..30 {
+        if let Ok(response) = client.get(format!("{}/health", base_url)).send().await {
+            if response.status().is_success() {
+                ready = true;
+                break;
+            }
+        }
+        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+    }
+    if !ready {
+        anyhow::bail!(
+            "RedisInsight API did not become ready on port {}",
+            insight_port
+        );
+    }
+
+    // Databases can't be added until the EULA is accepted
+    client
+        .patch(format!("{}/settings", base_url))
+        .json(&serde_json::json!({
+            "agreements": {
+                "eula": true,
+                "analytics": false,
+                "notifications": false,
+                "encryption": false,
+            }
+        }))
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .context("Failed to accept the RedisInsight EULA")?;
+
+    for conn in connections {
+        let response = client
+            .post(format!("{}/databases", base_url))
+            .json(&database_payload(conn))
+            .send()
+            .await
+            .with_context(|| format!("Failed to register {}", conn.name))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            anyhow::bail!("Registering {} failed ({}): {}", conn.name, status, body);
+        }
+        if verbose {
+            println!(
+                "  {} Registered {} in RedisInsight",
+                "Insight:".cyan(),
+                conn.name
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Request body for RedisInsight's `POST /api/databases`
+///
+/// Insight detects OSS clusters from the seed node itself; Sentinel
+/// deployments point at a sentinel and name the monitored master group.
+fn database_payload(conn: &RedisConnection) -> serde_json::Value {
+    let mut payload = serde_json::json!({
+        "name": conn.name,
+        "host": conn.host,
+        "port": conn.port,
+    });
+
+    match conn.connection_type {
+        ConnectionType::Sentinel {
+            sentinel_port,
+            ref master_name,
+        } => {
+            payload["port"] = serde_json::json!(sentinel_port);
+            let mut master = serde_json::json!({ "name": master_name });
+            if let Some(ref password) = conn.password {
+                master["password"] = serde_json::json!(password);
+            }
+            payload["sentinelMaster"] = master;
+        }
+        _ => {
+            if let Some(ref password) = conn.password {
+                payload["password"] = serde_json::json!(password);
+            }
+        }
+    }
+
+    payload
+}
+
 /// Check if Redis Insight container is running
-#[allow(dead_code)]
 pub async fn is_insight_running(name: &str) -> Result<bool> {
     use docker_wrapper::PsCommand;
 
@@ -180,7 +446,6 @@ pub async fn is_insight_running(name: &str) -> Result<bool> {
 }
 
 /// Get Redis Insight container info
-#[allow(dead_code)]
 pub async fn get_insight_info(name: &str) -> Result<HashMap<String, String>> {
     use docker_wrapper::InspectCommand;
 
@@ -229,3 +494,239 @@ pub async fn get_insight_info(name: &str) -> Result<HashMap<String, String>> {
 
     Ok(info)
 }
+
+/// Name prefix of the shared RedisInsight (container `redis-up-insight`)
+pub const SHARED_INSIGHT: &str = "redis-up";
+
+/// Network the shared RedisInsight and standalone instances meet on
+pub const SHARED_NETWORK: &str = "redis-up-shared";
+
+pub async fn handle_action(action: InsightAction, verbose: bool) -> Result<()> {
+    match action {
+        InsightAction::Start(args) => start_shared(args, verbose).await,
+        InsightAction::Stop => stop_shared(verbose).await,
+        InsightAction::Status => status_shared(verbose).await,
+    }
+}
+
+async fn start_shared(args: InsightStartArgs, verbose: bool) -> Result<()> {
+    if let Some(port) = shared_insight_port().await {
+        println!(
+            "{} Shared RedisInsight is already running at http://localhost:{}",
+            "Info:".cyan(),
+            port
+        );
+        return Ok(());
+    }
+
+    let platform = images::resolve_platform(&args.pull, "insight", &[images::INSIGHT_IMAGE])?;
+    images::ensure_images(
+        &[images::INSIGHT_IMAGE],
+        &args.pull,
+        platform.as_deref(),
+        None,
+        verbose,
+    )
+    .await?;
+
+    if let Err(e) = NetworkCreateCommand::new(SHARED_NETWORK).execute().await {
+        if !e.to_string().contains("already exists") {
+            return Err(e).context("Failed to create the shared Insight network");
+        }
+    }
+
+    let port = resolve_port(&Config::load()?, args.port);
+    let insight_config = InsightConfig::new(SHARED_INSIGHT, port)
+        .with_network(SHARED_NETWORK)
+        .with_platform(platform.as_deref());
+    start_insight(&insight_config, verbose).await?;
+
+    println!("{} Shared RedisInsight started", "Success:".green());
+    println!("  {}: http://localhost:{}", "URL".bold(), port);
+    println!("  {}: {}", "Network".bold(), SHARED_NETWORK.cyan());
+    println!(
+        "\nInstances started with {} will now be registered here.",
+        "--with-insight".bold()
+    );
+
+    Ok(())
+}
+
+async fn stop_shared(verbose: bool) -> Result<()> {
+    stop_insight(SHARED_INSIGHT).await?;
+
+    // Fails while standalone instances are still attached; they keep working without it
+    if let Err(e) = NetworkRmCommand::new(SHARED_NETWORK).execute().await {
+        if verbose {
+            println!(
+                "{} Network {} not removed: {}",
+                "Warning:".yellow(),
+                SHARED_NETWORK,
+                e
+            );
+        }
+    }
+
+    println!("{} Shared RedisInsight stopped", "Success:".green());
+    Ok(())
+}
+
+async fn status_shared(verbose: bool) -> Result<()> {
+    let port = match shared_insight_port().await {
+        Some(port) => port,
+        None => {
+            println!(
+                "{} Shared RedisInsight is not running. Start it with 'redis-up insight start'.",
+                "Status:".yellow()
+            );
+            return Ok(());
+        }
+    };
+
+    println!("{}", "Shared RedisInsight".bold().underline());
+    println!("  {}: {}", "Status".bold(), "running".green());
+    println!("  {}: http://localhost:{}", "URL".bold(), port);
+    println!("  {}: {}", "Network".bold(), SHARED_NETWORK.cyan());
+
+    let databases: serde_json::Value =
+        reqwest::get(format!("http://localhost:{}/api/databases", port))
+            .await
+            .context("Failed to query RedisInsight")?
+            .json()
+            .await
+            .context("Failed to parse RedisInsight database list")?;
+
+    let databases = databases.as_array().cloned().unwrap_or_default();
+    println!("\n{} ({})", "Databases".bold().underline(), databases.len());
+    for db in &databases {
+        let field = |key: &str| db.get(key).cloned().unwrap_or_default();
+        println!(
+            "  - {} ({}:{})",
+            field("name").as_str().unwrap_or("unnamed"),
+            field("host").as_str().unwrap_or("?"),
+            field("port")
+        );
+        if verbose {
+            if let Some(kind) = db.get("connectionType").and_then(|v| v.as_str()) {
+                println!("    {}: {}", "Type".dimmed(), kind);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Host port of the shared RedisInsight, if it is running
+pub async fn shared_insight_port() -> Option<u16> {
+    if !is_insight_running(SHARED_INSIGHT).await.unwrap_or(false) {
+        return None;
+    }
+    get_insight_info(SHARED_INSIGHT)
+        .await
+        .ok()?
+        .get("port")?
+        .parse()
+        .ok()
+}
+
+/// Make an instance reachable from the shared RedisInsight and register it
+///
+/// Deployments with their own network (stack, cluster, sentinel) get the shared
+/// Insight attached to that network so cluster redirects and sentinel-announced
+/// hostnames resolve; standalone containers join the shared network instead.
+pub async fn register_with_shared(
+    insight_port: u16,
+    instance_network: Option<&str>,
+    containers: &[String],
+    connections: Vec<RedisConnection>,
+    verbose: bool,
+) {
+    let container_name = format!("{}-insight", SHARED_INSIGHT);
+    let attached = match instance_network {
+        Some(network) => {
+            crate::commands::network::connect_containers(network, &[container_name]).await
+        }
+        None => crate::commands::network::connect_containers(SHARED_NETWORK, containers).await,
+    };
+
+    if let Err(e) = attached {
+        println!(
+            "{} Could not connect to the shared RedisInsight: {}",
+            "Warning:".yellow(),
+            e
+        );
+        return;
+    }
+
+    provision_connections(insight_port, connections, verbose).await;
+}
+
+/// Detach the shared RedisInsight from an instance network so it can be removed
+pub async fn detach_shared(instance: &InstanceInfo) {
+    let shared = instance
+        .metadata
+        .get("shared_insight")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    if shared {
+        let network = format!("{}-network", instance.name);
+        NetworkDisconnectCommand::new(network, format!("{}-insight", SHARED_INSIGHT))
+            .force()
+            .execute()
+            .await
+            .ok();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_free_port() {
+        let taken: HashSet<u16> = [8001, 8002].into_iter().collect();
+        assert_eq!(next_free_port(8001, &taken, |_| true), 8003);
+        assert_eq!(next_free_port(8001, &taken, |port| port != 8003), 8004);
+        assert_eq!(next_free_port(9000, &taken, |_| true), 9000);
+    }
+
+    #[test]
+    fn test_database_payload() {
+        let standalone = create_redis_connection(
+            "cache".to_string(),
+            "host.docker.internal".to_string(),
+            6379,
+            Some("secret".to_string()),
+            ConnectionType::Standalone,
+        );
+        assert_eq!(
+            database_payload(&standalone),
+            serde_json::json!({
+                "name": "cache",
+                "host": "host.docker.internal",
+                "port": 6379,
+                "password": "secret",
+            })
+        );
+
+        let sentinel = create_redis_connection(
+            "ha".to_string(),
+            "ha-sentinel-1".to_string(),
+            6379,
+            Some("secret".to_string()),
+            ConnectionType::Sentinel {
+                sentinel_port: 26379,
+                master_name: "master-1".to_string(),
+            },
+        );
+        assert_eq!(
+            database_payload(&sentinel),
+            serde_json::json!({
+                "name": "ha",
+                "host": "ha-sentinel-1",
+                "port": 26379,
+                "sentinelMaster": { "name": "master-1", "password": "secret" },
+            })
+        );
+    }
+}
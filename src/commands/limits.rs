@@ -0,0 +1,63 @@
+//! CPU and block IO limits and restart policy shared by start commands
+
+use docker_wrapper::RunCommand;
+use std::collections::HashMap;
+
+use crate::cli::LimitArgs;
+
+/// Add the limits to a run command
+pub fn apply_to_run(mut cmd: RunCommand, limits: &LimitArgs) -> RunCommand {
+    if let Some(cpus) = limits.cpus {
+        cmd = cmd.cpus(cpus.to_string());
+    }
+    if let Some(shares) = limits.cpu_shares {
+        cmd = cmd.cpu_shares(shares.into());
+    }
+    if let Some(weight) = limits.blkio_limit {
+        cmd = cmd.blkio_weight(weight);
+    }
+    if let Some(policy) = limits.restart_policy {
+        cmd = cmd.restart(policy.to_string());
+    }
+    cmd
+}
+
+/// Record the limits in instance metadata
+pub fn record_limits(metadata: &mut HashMap<String, serde_json::Value>, limits: &LimitArgs) {
+    if let Some(cpus) = limits.cpus {
+        metadata.insert("cpus".to_string(), serde_json::json!(cpus));
+    }
+    if let Some(shares) = limits.cpu_shares {
+        metadata.insert("cpu_shares".to_string(), serde_json::json!(shares));
+    }
+    if let Some(weight) = limits.blkio_limit {
+        metadata.insert("blkio_weight".to_string(), serde_json::json!(weight));
+    }
+    if let Some(policy) = limits.restart_policy {
+        metadata.insert(
+            "restart_policy".to_string(),
+            serde_json::json!(policy.to_string()),
+        );
+    }
+}
+
+/// Recorded resource limits as (label, value) pairs for info output
+pub fn limit_lines(metadata: &HashMap<String, serde_json::Value>) -> Vec<(&'static str, String)> {
+    let mut lines = Vec::new();
+    if let Some(memory) = metadata.get("memory").and_then(|v| v.as_str()) {
+        lines.push(("Memory Limit", memory.to_string()));
+    }
+    for (key, label) in [
+        ("cpus", "CPU Limit"),
+        ("cpu_shares", "CPU Shares"),
+        ("blkio_weight", "Block IO Weight"),
+    ] {
+        if let Some(value) = metadata.get(key) {
+            lines.push((label, value.to_string()));
+        }
+    }
+    if let Some(policy) = metadata.get("restart_policy").and_then(|v| v.as_str()) {
+        lines.push(("Restart Policy", policy.to_string()));
+    }
+    lines
+}
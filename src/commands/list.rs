@@ -1,60 +1,431 @@
 //! List all Redis instances
 
-use anyhow::Result;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
 use colored::*;
+use std::collections::HashMap;
 
-use crate::config::{Config, InstanceType};
+use crate::cli::{ListArgs, ListSort};
+use crate::commands::uptime::{self, ContainerState};
+use crate::commands::{annotate, cleanup, observability};
+use crate::config::{self, Config, InstanceInfo, InstanceType, PortPool};
+use crate::settings;
 
-pub async fn handle_list(filter_type: Option<String>, verbose: bool) -> Result<()> {
-    let config = Config::load()?;
+/// Cluster size the free `--port-base` hint is computed for (3 masters, 1 replica each)
+const CLUSTER_BLOCK: u16 = 6;
 
-    let instances = if let Some(type_filter) = filter_type {
-        let instance_type = match type_filter.to_lowercase().as_str() {
-            "basic" => InstanceType::Basic,
-            "stack" => InstanceType::Stack,
-            "cluster" => InstanceType::Cluster,
-            "sentinel" => InstanceType::Sentinel,
-            "enterprise" => InstanceType::Enterprise,
-            _ => {
-                println!("{} Invalid type filter: {}. Valid types: basic, stack, cluster, sentinel, enterprise", 
+/// Group order for the compact view
+const TYPE_ORDER: [InstanceType; 5] = [
+    InstanceType::Basic,
+    InstanceType::Stack,
+    InstanceType::Cluster,
+    InstanceType::Sentinel,
+    InstanceType::Enterprise,
+];
+
+pub async fn handle_list(args: ListArgs, verbose: bool) -> Result<()> {
+    if args.ports {
+        return list_ports().await;
+    }
+    if !args.all_namespaces {
+        return list_namespace(&args, verbose).await;
+    }
+
+    let current = settings::namespace();
+    let mut result = Ok(());
+    for namespace in config::namespaces()? {
+        settings::set_namespace(&namespace);
+        if !args.quiet {
+            println!("{} {}", "Namespace:".bold(), namespace.cyan());
+        }
+        result = list_namespace(&args, verbose).await;
+        if result.is_err() {
+            break;
+        }
+        if !args.quiet {
+            println!();
+        }
+    }
+    if let Some(current) = current {
+        settings::set_namespace(&current);
+    }
+    result
+}
+
+async fn list_namespace(args: &ListArgs, verbose: bool) -> Result<()> {
+    let mut config = Config::load()?;
+    let filters = args
+        .filter
+        .iter()
+        .map(|f| parse_filter(f))
+        .collect::<Result<Vec<Filter>>>()?;
+
+    let instance_type = match &args.r#type {
+        Some(type_filter) => {
+            let Some(instance_type) = parse_type(type_filter) else {
+                println!("{} Invalid type filter: {}. Valid types: basic, stack, cluster, sentinel, enterprise",
                     "Warning:".yellow(), type_filter.red());
                 return Ok(());
-            }
-        };
-        config.list_instances_by_type(&instance_type)
+            };
+            Some(instance_type)
+        }
+        None => None,
+    };
+
+    // Only ask Docker when a status is shown or filtered on
+    let needs_status =
+        (!args.quiet && !args.wide) || filters.iter().any(|f| matches!(f, Filter::Status(_)));
+    let states = if needs_status {
+        let names: Vec<String> = config.instances.keys().cloned().collect();
+        uptime::refresh(&mut config, &names).await
     } else {
-        config.list_instances()
+        None
     };
 
+    let instances = match instance_type {
+        Some(instance_type) => config.list_instances_by_type(&instance_type),
+        None => config.list_instances(),
+    };
+    let statuses: HashMap<String, Status> = if needs_status {
+        // Without Docker instances are still listed, just without a status
+        instances
+            .iter()
+            .map(|instance| {
+                let status = states
+                    .as_ref()
+                    .map_or(Status::Unknown, |states| status(instance, states));
+                (instance.name.clone(), status)
+            })
+            .collect()
+    } else {
+        HashMap::new()
+    };
+
+    let mut instances: Vec<&InstanceInfo> = instances
+        .into_iter()
+        .filter(|instance| {
+            filters
+                .iter()
+                .all(|f| f.matches(instance, statuses.get(&instance.name)))
+        })
+        .collect();
+    sort_instances(&mut instances, args.sort);
+
+    if args.quiet {
+        for instance in &instances {
+            println!("{}", instance.name);
+        }
+        return Ok(());
+    }
+
     if instances.is_empty() {
-        println!("{} No Redis instances found", "Info:".blue());
-        println!("  Start one with: {}", "redis-up basic start".green());
+        if filters.is_empty() {
+            println!("{} No Redis instances found", "Info:".blue());
+            println!("  Start one with: {}", "redis-up basic start".green());
+        } else {
+            println!("{} No instances match the filters", "Info:".blue());
+        }
         return Ok(());
     }
 
     println!("{} Redis Instances", "List:".bold().cyan());
     println!();
 
-    // Sort by creation time (newest first)
-    let mut sorted_instances = instances.clone();
-    sorted_instances.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    if args.wide {
+        print_wide(&instances, verbose);
+    } else {
+        print_compact(&instances, &statuses);
+    }
+
+    println!("Total: {} instances", instances.len().to_string().bold());
+
+    let stale = instances
+        .iter()
+        .filter(|i| is_stale(statuses.get(&i.name)))
+        .count();
+    if stale > 0 {
+        println!(
+            "{} {} stale (exited over {} ago); remove with {}",
+            "Hint:".dimmed(),
+            stale,
+            humantime::format_duration(uptime::DEFAULT_STALE),
+            "redis-up cleanup --stale".green()
+        );
+    }
+
+    Ok(())
+}
+
+/// `--ports`: every host port in use, lowest first, with the next free blocks
+///
+/// Covers every namespace, since they share the Docker host, plus the
+/// observability bundle's published ports.
+async fn list_ports() -> Result<()> {
+    let mut map = Config::load()?.port_map_all();
+    for (port, container) in observability::published_ports().await {
+        map.entry(port)
+            .or_default()
+            .push((container, "observability".to_string()));
+    }
+
+    if map.is_empty() {
+        println!("{} No host ports in use", "Info:".blue());
+    } else {
+        println!("{} Host ports in use", "List:".bold().cyan());
+        println!();
+        println!(
+            "  {:<6} {:<30} {}",
+            "PORT".bold(),
+            "OWNER".bold(),
+            "ROLE".bold()
+        );
+        for (port, holders) in &map {
+            for (owner, role) in holders {
+                let line = format!("  {:<6} {:<30} {}", port, owner, role);
+                if holders.len() > 1 {
+                    println!("{} {}", line, "conflict".red());
+                } else {
+                    println!("{}", line);
+                }
+            }
+        }
+        println!();
+        println!("Total: {} ports", map.len().to_string().bold());
+    }
+
+    // Searched from the start commands' default ports
+    let free = |pool: PortPool, default: u16, count: u16| {
+        config::allocate_ports(&map, pool, default, count).map_or_else(
+            || "none".red().to_string(),
+            |port| port.to_string().green().to_string(),
+        )
+    };
+    println!(
+        "Next free: {} {} (basic, stack), {} {} (6-node cluster), {} {} (sentinel)",
+        "--port".dimmed(),
+        free(PortPool::Redis, 6379, 1),
+        "--port-base".dimmed(),
+        free(PortPool::Cluster, 7000, CLUSTER_BLOCK),
+        "--sentinel-port-base".dimmed(),
+        free(PortPool::Sentinel, 26379, 3)
+    );
+    Ok(())
+}
+
+/// State of an instance's containers
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Status {
+    Running,
+    /// Running containers out of the total
+    Partial(usize, usize),
+    /// Every container exited, at the given time when Docker reported one
+    Stopped(Option<DateTime<Utc>>),
+    /// None of the containers exist any more
+    Missing,
+    /// Docker could not be asked
+    Unknown,
+}
+
+impl Status {
+    /// Name used by `--filter status=...`
+    fn key(&self) -> &'static str {
+        match self {
+            Status::Running => "running",
+            Status::Partial(..) => "partial",
+            Status::Stopped(_) => "stopped",
+            Status::Missing => "missing",
+            Status::Unknown => "unknown",
+        }
+    }
+}
+
+impl std::fmt::Display for Status {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Status::Partial(running, total) => write!(f, "running {}/{}", running, total),
+            Status::Stopped(Some(at)) => {
+                write!(f, "exited {} ago", uptime::format_age(*at, Utc::now()))
+            }
+            _ => write!(f, "{}", self.key()),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Filter {
+    Status(String),
+    Type(InstanceType),
+    Name(String),
+}
+
+impl Filter {
+    fn matches(&self, instance: &InstanceInfo, status: Option<&Status>) -> bool {
+        match self {
+            Filter::Status(wanted) => status.is_some_and(|s| s.key() == wanted),
+            Filter::Type(instance_type) => instance.instance_type == *instance_type,
+            Filter::Name(pattern) => cleanup::glob_match(pattern, &instance.name),
+        }
+    }
+}
 
-    for instance in sorted_instances {
-        let type_color = match instance.instance_type {
-            InstanceType::Basic => "basic".cyan(),
-            InstanceType::Stack => "stack".magenta(),
-            InstanceType::Cluster => "cluster".yellow(),
-            InstanceType::Sentinel => "sentinel".blue(),
-            InstanceType::Enterprise => "enterprise".red(),
-        };
+fn is_stale(status: Option<&Status>) -> bool {
+    let threshold = chrono::Duration::from_std(uptime::DEFAULT_STALE).unwrap_or_default();
+    matches!(status, Some(Status::Stopped(Some(at))) if Utc::now() - *at >= threshold)
+}
+
+fn parse_filter(filter: &str) -> Result<Filter> {
+    let (key, value) = filter
+        .split_once('=')
+        .with_context(|| format!("Invalid filter '{}': expected KEY=VALUE", filter))?;
+    match key {
+        "status" => {
+            const STATUSES: [&str; 5] = ["running", "partial", "stopped", "missing", "unknown"];
+            let value = value.to_lowercase();
+            if !STATUSES.contains(&value.as_str()) {
+                anyhow::bail!(
+                    "Invalid status '{}': use one of {}",
+                    value,
+                    STATUSES.join(", ")
+                );
+            }
+            Ok(Filter::Status(value))
+        }
+        "type" => parse_type(value).map(Filter::Type).with_context(|| {
+            format!(
+                "Invalid type '{}': use basic, stack, cluster, sentinel or enterprise",
+                value
+            )
+        }),
+        "name" => Ok(Filter::Name(value.to_string())),
+        _ => anyhow::bail!("Unknown filter key '{}': use status, type or name", key),
+    }
+}
+
+fn parse_type(value: &str) -> Option<InstanceType> {
+    match value.to_lowercase().as_str() {
+        "basic" => Some(InstanceType::Basic),
+        "stack" => Some(InstanceType::Stack),
+        "cluster" => Some(InstanceType::Cluster),
+        "sentinel" => Some(InstanceType::Sentinel),
+        "enterprise" => Some(InstanceType::Enterprise),
+        _ => None,
+    }
+}
+
+fn sort_instances(instances: &mut [&InstanceInfo], sort: ListSort) {
+    let type_rank = |t: &InstanceType| TYPE_ORDER.iter().position(|o| o == t);
+    match sort {
+        ListSort::Name => instances.sort_by(|a, b| a.name.cmp(&b.name)),
+        ListSort::Created => instances.sort_by(|a, b| b.created_at.cmp(&a.created_at)),
+        ListSort::Type => instances.sort_by(|a, b| {
+            type_rank(&a.instance_type)
+                .cmp(&type_rank(&b.instance_type))
+                .then_with(|| a.name.cmp(&b.name))
+        }),
+        ListSort::Port => instances.sort_by_key(|i| i.ports.iter().min().copied()),
+    }
+}
 
+/// One table row per instance, grouped under a header per type
+fn print_compact(instances: &[&InstanceInfo], statuses: &HashMap<String, Status>) {
+    let now = Utc::now();
+
+    let rows: Vec<[String; 5]> = instances
+        .iter()
+        .map(|instance| {
+            [
+                instance.name.clone(),
+                port_summary(&instance.ports),
+                match statuses.get(&instance.name) {
+                    Some(Status::Running | Status::Partial(..)) => uptime::uptime(instance, now),
+                    _ => None,
+                }
+                .unwrap_or_else(|| "-".to_string()),
+                statuses
+                    .get(&instance.name)
+                    .unwrap_or(&Status::Unknown)
+                    .to_string(),
+                insight_url(instance).unwrap_or_default(),
+            ]
+        })
+        .collect();
+    let headers = ["NAME", "PORTS", "UPTIME", "STATUS", "INSIGHT"];
+    let mut widths = headers.map(str::len);
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+
+    for instance_type in &TYPE_ORDER {
+        let group: Vec<(&&InstanceInfo, &[String; 5])> = instances
+            .iter()
+            .zip(&rows)
+            .filter(|(instance, _)| instance.instance_type == *instance_type)
+            .collect();
+        if group.is_empty() {
+            continue;
+        }
+
+        println!(
+            "{} {} ({})",
+            get_type_icon(instance_type),
+            type_label(instance_type).bold(),
+            group.len()
+        );
+        println!(
+            "  {}",
+            format!(
+                "{:<w0$}  {:<w1$}  {:<w2$}  {:<w3$}  {}",
+                headers[0],
+                headers[1],
+                headers[2],
+                headers[3],
+                headers[4],
+                w0 = widths[0],
+                w1 = widths[1],
+                w2 = widths[2],
+                w3 = widths[3]
+            )
+            .dimmed()
+        );
+        for (instance, row) in group {
+            // Pad before coloring so escape codes don't skew the columns
+            let status = format!("{:<w$}", row[3], w = widths[3]);
+            let status = match statuses.get(&instance.name) {
+                Some(Status::Running) => status.green(),
+                Some(Status::Unknown) | None => status.dimmed(),
+                _ => status.yellow(),
+            };
+            println!(
+                "  {}  {:<w1$}  {:<w2$}  {}  {}",
+                format!("{:<w$}", row[0], w = widths[0]).bold().green(),
+                row[1],
+                row[2],
+                status,
+                row[4].cyan(),
+                w1 = widths[1],
+                w2 = widths[2]
+            );
+        }
+        println!();
+    }
+}
+
+/// The multi-line view: one block per instance
+fn print_wide(instances: &[&InstanceInfo], verbose: bool) {
+    for instance in instances {
         println!(
             "  {} {} ({})",
             get_type_icon(&instance.instance_type),
             instance.name.bold().green(),
-            type_color
+            type_color(&instance.instance_type)
         );
 
+        if let Some(description) = annotate::description(instance) {
+            println!("    {}", description.italic());
+        }
+
         println!(
             "    {}: {}:{}",
             "Address".dimmed(),
@@ -81,14 +452,87 @@ pub async fn handle_list(filter_type: Option<String>, verbose: bool) -> Result<(
                     instance.connection_info.additional_ports
                 );
             }
+
+            for note in annotate::notes(instance) {
+                println!("    {}: {}", "Note".dimmed(), note.text);
+            }
         }
 
         println!();
     }
+}
 
-    println!("Total: {} instances", instances.len().to_string().bold());
+/// Status from the state of each of the instance's containers
+fn status(instance: &InstanceInfo, states: &HashMap<String, ContainerState>) -> Status {
+    let found: Vec<&ContainerState> = instance
+        .containers
+        .iter()
+        .filter_map(|container| states.get(container))
+        .collect();
+    let running = found.iter().filter(|s| s.running).count();
+    let total = instance.containers.len();
+    if found.is_empty() {
+        Status::Missing
+    } else if running == total {
+        Status::Running
+    } else if running == 0 {
+        Status::Stopped(uptime::exited_at(instance, states))
+    } else {
+        Status::Partial(running, total)
+    }
+}
 
-    Ok(())
+/// `6379`, `7000-7005`, or a comma list when the ports are not contiguous
+fn port_summary(ports: &[u16]) -> String {
+    let mut sorted = ports.to_vec();
+    sorted.sort_unstable();
+    match (sorted.first(), sorted.last()) {
+        (Some(first), Some(last)) if first == last => first.to_string(),
+        (Some(first), Some(last)) if sorted.windows(2).all(|w| w[1] == w[0] + 1) => {
+            format!("{}-{}", first, last)
+        }
+        _ => sorted
+            .iter()
+            .map(u16::to_string)
+            .collect::<Vec<_>>()
+            .join(","),
+    }
+}
+
+fn insight_url(instance: &InstanceInfo) -> Option<String> {
+    let port = instance
+        .connection_info
+        .additional_ports
+        .get("redisinsight")
+        .copied()
+        .or_else(|| {
+            instance
+                .metadata
+                .get("insight_port")
+                .and_then(|v| v.as_u64())
+                .map(|p| p as u16)
+        })?;
+    Some(format!("http://localhost:{}", port))
+}
+
+fn type_label(instance_type: &InstanceType) -> &'static str {
+    match instance_type {
+        InstanceType::Basic => "Basic",
+        InstanceType::Stack => "Stack",
+        InstanceType::Cluster => "Cluster",
+        InstanceType::Sentinel => "Sentinel",
+        InstanceType::Enterprise => "Enterprise",
+    }
+}
+
+fn type_color(instance_type: &InstanceType) -> ColoredString {
+    match instance_type {
+        InstanceType::Basic => "basic".cyan(),
+        InstanceType::Stack => "stack".magenta(),
+        InstanceType::Cluster => "cluster".yellow(),
+        InstanceType::Sentinel => "sentinel".blue(),
+        InstanceType::Enterprise => "enterprise".red(),
+    }
 }
 
 fn get_type_icon(instance_type: &InstanceType) -> &'static str {
@@ -100,3 +544,59 @@ fn get_type_icon(instance_type: &InstanceType) -> &'static str {
         InstanceType::Enterprise => "[E]",
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compact_columns() {
+        assert_eq!(port_summary(&[6379]), "6379");
+        assert_eq!(port_summary(&[7002, 7000, 7001]), "7000-7002");
+        assert_eq!(port_summary(&[8000, 26379]), "8000,26379");
+        assert_eq!(port_summary(&[]), "");
+    }
+
+    #[test]
+    fn test_filters() {
+        assert_eq!(
+            parse_filter("status=Running").unwrap(),
+            Filter::Status("running".to_string())
+        );
+        assert_eq!(
+            parse_filter("type=cluster").unwrap(),
+            Filter::Type(InstanceType::Cluster)
+        );
+        assert!(parse_filter("status=up").is_err());
+        assert!(parse_filter("port=6379").is_err());
+        assert!(parse_filter("running").is_err());
+
+        let instance: InstanceInfo = serde_json::from_value(serde_json::json!({
+            "name": "cache-1",
+            "instance_type": "basic",
+            "created_at": "",
+            "ports": [6379],
+            "containers": ["cache-1"],
+            "connection_info": {
+                "host": "localhost",
+                "port": 6379,
+                "password": null,
+                "url": "",
+                "additional_ports": {}
+            },
+            "metadata": {}
+        }))
+        .unwrap();
+        let partial = Status::Partial(1, 2);
+        assert!(parse_filter("status=partial")
+            .unwrap()
+            .matches(&instance, Some(&partial)));
+        assert!(parse_filter("name=cache-*")
+            .unwrap()
+            .matches(&instance, None));
+        assert!(!parse_filter("status=running")
+            .unwrap()
+            .matches(&instance, None));
+        assert_eq!(partial.to_string(), "running 1/2");
+    }
+}
@@ -0,0 +1,158 @@
+//! Live refresh for `info --watch`
+
+use anyhow::{Context, Result};
+use colored::*;
+use std::future::Future;
+use std::io::Write;
+use std::time::Duration;
+
+use crate::cli::InfoArgs;
+use crate::commands::client;
+use crate::config::{Config, InstanceInfo, InstanceType};
+
+/// How long to wait on an instance before showing it as unreachable
+const QUERY_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Fields read from the running instance
+#[derive(Debug, Default, PartialEq)]
+struct LiveStats {
+    role: Option<String>,
+    connected_clients: Option<String>,
+    used_memory: Option<String>,
+    cluster_state: Option<String>,
+}
+
+/// Render an info panel once, or keep re-rendering it with `--watch`
+pub async fn info<F, Fut>(
+    args: InfoArgs,
+    instance_type: InstanceType,
+    verbose: bool,
+    render: F,
+) -> Result<()>
+where
+    F: Fn(InfoArgs, bool) -> Fut,
+    Fut: Future<Output = Result<()>>,
+{
+    let Some(interval) = args.watch else {
+        return render(args, verbose).await;
+    };
+
+    loop {
+        // Clear the screen and move the cursor home
+        print!("\x1b[2J\x1b[H");
+        std::io::stdout().flush().ok();
+
+        render(args.clone(), verbose).await?;
+
+        if args.format != "json" {
+            let config = Config::load()?;
+            let instance = match args.name {
+                Some(ref name) => config.get_instance(name),
+                None => config.get_latest_instance(&instance_type),
+            }
+            .context("Instance not found")?;
+            print_live(instance).await;
+
+            println!();
+            println!(
+                "{}",
+                format!(
+                    "Refreshing every {} (Ctrl+C to stop)",
+                    humantime::format_duration(interval)
+                )
+                .dimmed()
+            );
+        }
+
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => return Ok(()),
+            _ = tokio::time::sleep(interval) => {}
+        }
+    }
+}
+
+async fn print_live(instance: &InstanceInfo) {
+    println!();
+    println!("{}", "Live:".bold().underline());
+
+    let Some(endpoint) = client::endpoints(instance).into_iter().next() else {
+        return;
+    };
+    let cluster = instance.instance_type == InstanceType::Cluster;
+
+    match tokio::time::timeout(QUERY_TIMEOUT, fetch(&endpoint, cluster)).await {
+        Ok(Ok(stats)) => {
+            let fields = [
+                ("Role", stats.role),
+                ("Connected Clients", stats.connected_clients),
+                ("Memory Used", stats.used_memory),
+                ("Cluster State", stats.cluster_state),
+            ];
+            for (label, value) in fields {
+                if let Some(value) = value {
+                    let value = match value.as_str() {
+                        "ok" => value.green(),
+                        "fail" => value.red(),
+                        _ => value.cyan(),
+                    };
+                    println!("  {}: {}", label.bold(), value);
+                }
+            }
+        }
+        Ok(Err(e)) => println!("  {} {:#}", "Unavailable:".yellow(), e),
+        Err(_) => println!("  {} no reply from {}", "Unavailable:".yellow(), endpoint),
+    }
+}
+
+async fn fetch(endpoint: &client::Endpoint, cluster: bool) -> Result<LiveStats> {
+    let mut con = client::connect(endpoint).await?;
+    let info: String = redis::cmd("INFO").query_async(&mut con).await?;
+    let mut stats = parse_stats(&info);
+
+    if cluster {
+        let cluster_info: String = redis::cmd("CLUSTER")
+            .arg("INFO")
+            .query_async(&mut con)
+            .await?;
+        stats.cluster_state = field(&cluster_info, "cluster_state");
+    }
+    Ok(stats)
+}
+
+fn parse_stats(info: &str) -> LiveStats {
+    LiveStats {
+        role: field(info, "role"),
+        connected_clients: field(info, "connected_clients"),
+        used_memory: field(info, "used_memory_human"),
+        cluster_state: None,
+    }
+}
+
+/// Value of a `key:value` line in INFO-style output
+fn field(info: &str, key: &str) -> Option<String> {
+    info.lines()
+        .filter_map(|line| line.trim().split_once(':'))
+        .find(|(k, _)| *k == key)
+        .map(|(_, v)| v.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_stats() {
+        let info = "# Server\r\nredis_version:7.2.4\r\n# Clients\r\nconnected_clients:3\r\n\
+                    # Memory\r\nused_memory:1048576\r\nused_memory_human:1.00M\r\n\
+                    # Replication\r\nrole:master\r\n";
+        assert_eq!(
+            parse_stats(info),
+            LiveStats {
+                role: Some("master".to_string()),
+                connected_clients: Some("3".to_string()),
+                used_memory: Some("1.00M".to_string()),
+                cluster_state: None,
+            }
+        );
+    }
+}
@@ -0,0 +1,390 @@
+//! Built-in workload generator
+
+use anyhow::{Context, Result};
+use colored::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use redis::aio::MultiplexedConnection;
+use redis::Value;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::cli::{LoadArgs, LoadPattern};
+use crate::commands::client;
+use crate::config::{Config, InstanceType};
+
+/// Scheduling granularity; ops are issued in small batches every tick
+const TICK: Duration = Duration::from_millis(10);
+
+/// Number of hash slots in a Redis Cluster
+const CLUSTER_SLOTS: usize = 16384;
+
+/// Sends each key to the connection that owns it
+struct Router {
+    connections: Vec<MultiplexedConnection>,
+    /// Connection index per hash slot, for clusters
+    slots: Option<Vec<usize>>,
+}
+
+impl Router {
+    fn connection(&self, key: &str) -> MultiplexedConnection {
+        let index = match self.slots {
+            Some(ref slots) => slots[key_slot(key)],
+            None => 0,
+        };
+        self.connections[index].clone()
+    }
+}
+
+/// What one worker measured
+#[derive(Default)]
+struct WorkerStats {
+    latencies_us: Vec<u64>,
+    errors: u64,
+    last_error: Option<String>,
+}
+
+pub async fn handle_load(args: LoadArgs, verbose: bool) -> Result<()> {
+    let config = Config::load()?;
+    let instance = config
+        .get_instance(&args.name)
+        .with_context(|| format!("Instance '{}' not found", args.name))?;
+
+    let router = Arc::new(if instance.instance_type == InstanceType::Cluster {
+        cluster_router(instance).await?
+    } else {
+        let endpoint = client::endpoints(instance)
+            .into_iter()
+            .next()
+            .context("Instance has no endpoints")?;
+        Router {
+            connections: vec![client::connect(&endpoint).await?],
+            slots: None,
+        }
+    });
+
+    println!(
+        "{} {} pattern against {} at {} ops/s for {} ({} workers)",
+        "Load:".bold().cyan(),
+        args.pattern,
+        args.name.bold(),
+        args.rate,
+        humantime::format_duration(args.duration),
+        args.workers
+    );
+
+    let workers = args.workers.max(1);
+    let per_tick = args.rate as f64 * TICK.as_secs_f64() / workers as f64;
+    let deadline = Instant::now() + args.duration;
+    let started = Instant::now();
+
+    let mut handles = Vec::new();
+    for worker in 0..workers {
+        let router = router.clone();
+        let pattern = args.pattern;
+        let keyspace = args.keyspace.max(1);
+        handles.push(tokio::spawn(async move {
+            run_worker(worker, router, pattern, keyspace, per_tick, deadline).await
+        }));
+    }
+
+    let mut stats = WorkerStats::default();
+    let collect = async {
+        for handle in handles {
+            let worker = handle.await?;
+            stats.latencies_us.extend(worker.latencies_us);
+            stats.errors += worker.errors;
+            if worker.last_error.is_some() {
+                stats.last_error = worker.last_error;
+            }
+        }
+        Ok::<_, anyhow::Error>(())
+    };
+    tokio::select! {
+        result = collect => result?,
+        _ = tokio::signal::ctrl_c() => {
+            println!("{} Interrupted", "Load:".yellow());
+            return Ok(());
+        }
+    }
+
+    report(&stats, started.elapsed(), verbose);
+    Ok(())
+}
+
+async fn run_worker(
+    worker: usize,
+    router: Arc<Router>,
+    pattern: LoadPattern,
+    keyspace: u64,
+    per_tick: f64,
+    deadline: Instant,
+) -> WorkerStats {
+    let mut rng = StdRng::from_entropy();
+    let mut stats = WorkerStats::default();
+    let mut ticker = tokio::time::interval(TICK);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    let mut budget = 0.0;
+
+    while Instant::now() < deadline {
+        ticker.tick().await;
+        budget += per_tick;
+        while budget >= 1.0 {
+            budget -= 1.0;
+            let (key, pipeline) = next_op(pattern, keyspace, worker, &mut rng);
+            let mut con = router.connection(&key);
+
+            let start = Instant::now();
+            let result: redis::RedisResult<Value> = pipeline.query_async(&mut con).await;
+            stats
+                .latencies_us
+                .push(start.elapsed().as_micros().min(u64::MAX as u128) as u64);
+            if let Err(e) = result {
+                stats.errors += 1;
+                stats.last_error = Some(e.to_string());
+            }
+        }
+    }
+
+    stats
+}
+
+/// Pick the next operation for a pattern, returning the routing key and the commands
+fn next_op(
+    pattern: LoadPattern,
+    keyspace: u64,
+    worker: usize,
+    rng: &mut StdRng,
+) -> (String, redis::Pipeline) {
+    let mut pipe = redis::pipe();
+    let roll: u32 = rng.gen_range(0..100);
+    let n = rng.gen_range(0..keyspace);
+
+    let key = match pattern {
+        LoadPattern::Cache => {
+            let key = format!("load:cache:{}", n);
+            match roll {
+                0..=79 => {
+                    pipe.cmd("GET").arg(&key);
+                }
+                80..=94 => {
+                    pipe.cmd("SET")
+                        .arg(&key)
+                        .arg(payload(rng, 64))
+                        .arg("EX")
+                        .arg(60);
+                }
+                _ => {
+                    // A burst of writes sharing a hash tag so it stays on one node
+                    let tag = format!("load:cache:{{burst{}}}", n % 64);
+                    for i in 0..10 {
+                        pipe.cmd("SET")
+                            .arg(format!("{}:{}", tag, i))
+                            .arg(payload(rng, 64))
+                            .arg("EX")
+                            .arg(60)
+                            .ignore();
+                    }
+                    pipe.cmd("EXISTS").arg(format!("{}:0", tag));
+                    return (tag, pipe);
+                }
+            }
+            key
+        }
+        LoadPattern::Queue => {
+            let key = format!("load:queue:{}", worker);
+            match roll {
+                0..=49 => {
+                    pipe.cmd("LPUSH").arg(&key).arg(payload(rng, 128));
+                }
+                50..=94 => {
+                    pipe.cmd("RPOP").arg(&key);
+                }
+                _ => {
+                    pipe.cmd("LLEN").arg(&key);
+                }
+            }
+            key
+        }
+        LoadPattern::Session => {
+            let key = format!("load:session:{}", n);
+            match roll {
+                0..=39 => {
+                    pipe.cmd("HSET")
+                        .arg(&key)
+                        .arg("user")
+                        .arg(n)
+                        .arg("token")
+                        .arg(payload(rng, 32))
+                        .arg("seen")
+                        .arg(chrono::Utc::now().timestamp())
+                        .ignore();
+                    pipe.cmd("EXPIRE").arg(&key).arg(1800);
+                }
+                40..=89 => {
+                    pipe.cmd("HGETALL").arg(&key);
+                }
+                _ => {
+                    pipe.cmd("DEL").arg(&key);
+                }
+            }
+            key
+        }
+    };
+
+    (key, pipe)
+}
+
+fn payload(rng: &mut StdRng, len: usize) -> String {
+    rng.sample_iter(&rand::distributions::Alphanumeric)
+        .take(len)
+        .map(char::from)
+        .collect()
+}
+
+fn report(stats: &WorkerStats, elapsed: Duration, verbose: bool) {
+    let mut latencies = stats.latencies_us.clone();
+    latencies.sort_unstable();
+    let total = latencies.len();
+
+    println!();
+    println!("{}", "Results:".bold().underline());
+    println!(
+        "  {}: {} in {:.1}s ({:.0} ops/s)",
+        "Operations".bold(),
+        total,
+        elapsed.as_secs_f64(),
+        total as f64 / elapsed.as_secs_f64().max(f64::EPSILON)
+    );
+    let errors = if stats.errors > 0 {
+        stats.errors.to_string().red()
+    } else {
+        "0".green()
+    };
+    println!("  {}: {}", "Errors".bold(), errors);
+    if let Some(ref e) = stats.last_error {
+        if verbose || stats.errors > 0 {
+            println!("  {}: {}", "Last error".bold(), e.dimmed());
+        }
+    }
+
+    if total == 0 {
+        return;
+    }
+    println!("  {}:", "Latency".bold());
+    for (label, p) in [("p50", 50.0), ("p90", 90.0), ("p99", 99.0), ("p99.9", 99.9)] {
+        println!(
+            "    {:<6} {}",
+            label,
+            format_micros(percentile(&latencies, p))
+        );
+    }
+    println!("    {:<6} {}", "max", format_micros(latencies[total - 1]));
+}
+
+/// Nearest-rank percentile of sorted samples
+fn percentile(sorted: &[u64], p: f64) -> u64 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+fn format_micros(us: u64) -> String {
+    if us >= 1000 {
+        format!("{:.2}ms", us as f64 / 1000.0)
+    } else {
+        format!("{}µs", us)
+    }
+}
+
+/// Connect to every master and map hash slots to them
+async fn cluster_router(instance: &crate::config::InstanceInfo) -> Result<Router> {
+    let mut connections = Vec::new();
+    let mut ids = Vec::new();
+    for (endpoint, mut con) in client::primaries(instance).await? {
+        let id: String = redis::cmd("CLUSTER")
+            .arg("MYID")
+            .query_async(&mut con)
+            .await
+            .with_context(|| format!("CLUSTER MYID failed on {}", endpoint))?;
+        ids.push(id);
+        connections.push(con);
+    }
+    let first = connections
+        .first_mut()
+        .context("Cluster has no reachable masters")?;
+
+    let ranges: Vec<(u16, u16, Vec<Value>)> = redis::cmd("CLUSTER")
+        .arg("SLOTS")
+        .query_async(first)
+        .await
+        .context("CLUSTER SLOTS failed")?;
+
+    let mut slots = vec![0; CLUSTER_SLOTS];
+    for (start, end, master) in ranges {
+        let id = master.get(2).and_then(|v| match v {
+            Value::BulkString(bytes) => Some(String::from_utf8_lossy(bytes).into_owned()),
+            _ => None,
+        });
+        if let Some(index) = id.and_then(|id| ids.iter().position(|i| *i == id)) {
+            for slot in start..=end {
+                slots[slot as usize] = index;
+            }
+        }
+    }
+
+    Ok(Router {
+        connections,
+        slots: Some(slots),
+    })
+}
+
+/// Hash slot of a key, honouring `{hash tags}`
+fn key_slot(key: &str) -> usize {
+    let bytes = key.as_bytes();
+    let hashed = match bytes.iter().position(|b| *b == b'{') {
+        Some(open) => match bytes[open + 1..].iter().position(|b| *b == b'}') {
+            Some(len) if len > 0 => &bytes[open + 1..open + 1 + len],
+            _ => bytes,
+        },
+        None => bytes,
+    };
+    crc16(hashed) as usize % CLUSTER_SLOTS
+}
+
+/// CRC16-CCITT (XMODEM), as used for cluster key hashing
+fn crc16(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0;
+    for byte in data {
+        crc ^= (*byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_slot_and_percentile() {
+        // Values from the Redis Cluster specification and CLUSTER KEYSLOT
+        assert_eq!(crc16(b"123456789"), 0x31C3);
+        assert_eq!(key_slot("foo"), 12182);
+        assert_eq!(key_slot("{user1000}.following"), key_slot("user1000"));
+        assert_eq!(key_slot("foo{}{bar}"), key_slot("foo{}{bar}"));
+
+        let samples: Vec<u64> = (1..=100).collect();
+        assert_eq!(percentile(&samples, 50.0), 50);
+        assert_eq!(percentile(&samples, 99.0), 99);
+        assert_eq!(percentile(&samples, 99.9), 100);
+    }
+}
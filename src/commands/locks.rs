@@ -0,0 +1,153 @@
+//! Advisory file locks that keep concurrent redis-up runs from racing
+//!
+//! A start holds a lock on its instance name (or, without `--name`, on its
+//! deployment type, since generated names are reserved from shared counters) until
+//! the instance is recorded, so a second `start --name foo` waits for the
+//! first and then reuses or refuses the finished instance instead of
+//! half-creating containers next to it. Cluster starts also hold a global
+//! formation lock: port blocks are reserved from the instance file, which
+//! isn't updated until the cluster has formed.
+//!
+//! Locks are OS file locks under `<state dir>/locks`, so they are released
+//! when the process exits, however it exits.
+
+use anyhow::{Context, Result};
+use colored::*;
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::time::Duration;
+
+use crate::commands::uptime;
+use crate::config::{get_state_dir, Config, InstanceType};
+use crate::error::Failure;
+
+/// How often a waiting run retries the lock
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// A held lock, released when dropped
+#[derive(Debug)]
+pub struct Guard {
+    _file: File,
+}
+
+/// Lock a start of `name`, or of an unnamed instance of `kind`
+pub async fn start(name: Option<&str>, kind: &InstanceType) -> Result<Guard> {
+    match name {
+        Some(name) => {
+            acquire(
+                &format!("instance-{}", name),
+                &format!("starting '{}'", name),
+            )
+            .await
+        }
+        None => {
+            acquire(
+                &format!("unnamed-{}", kind),
+                &format!("starting an unnamed {} instance", kind),
+            )
+            .await
+        }
+    }
+}
+
+/// Lock cluster formation across every redis-up run
+pub async fn formation() -> Result<Guard> {
+    acquire("cluster-formation", "forming a cluster").await
+}
+
+/// Refuse to start over an instance that is already running
+///
+/// Called once the start lock is held and reuse didn't apply, so the
+/// instance belongs to an earlier, finished run.
+pub async fn ensure_not_running(name: Option<&str>) -> Result<()> {
+    let Some(name) = name else {
+        return Ok(());
+    };
+    let config = Config::load()?;
+    let Some(instance) = config.get_instance(name) else {
+        return Ok(());
+    };
+    let states = uptime::inspect(&instance.containers)
+        .await
+        .unwrap_or_default();
+    if states.values().any(|state| state.running) {
+        return Err(Failure::NameConflict(format!(
+            "'{}' is already running; use --reuse to keep it, stop it, or pick another --name",
+            name
+        ))
+        .into());
+    }
+    Ok(())
+}
+
+async fn acquire(key: &str, what: &str) -> Result<Guard> {
+    let dir = get_state_dir()?.join("locks");
+    fs::create_dir_all(&dir)
+        .with_context(|| format!("Failed to create lock directory: {}", dir.display()))?;
+    let path = dir.join(format!("{}.lock", sanitize(key)));
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&path)
+        .with_context(|| format!("Failed to open lock file: {}", path.display()))?;
+
+    let mut waiting = false;
+    loop {
+        match file.try_lock() {
+            Ok(()) => return Ok(Guard { _file: file }),
+            Err(TryLockError::WouldBlock) => {
+                if !waiting {
+                    println!(
+                        "{} Another redis-up is {}; waiting for it to finish",
+                        "Waiting:".yellow(),
+                        what
+                    );
+                    waiting = true;
+                }
+                tokio::time::sleep(POLL_INTERVAL).await;
+            }
+            Err(TryLockError::Error(e)) => {
+                return Err(e).with_context(|| format!("Failed to lock {}", path.display()));
+            }
+        }
+    }
+}
+
+/// Lock file name for a key; instance names are free-form
+fn sanitize(key: &str) -> String {
+    key.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lock_excludes_second_holder() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.lock");
+        let open = || {
+            OpenOptions::new()
+                .create(true)
+                .truncate(false)
+                .write(true)
+                .open(&path)
+                .unwrap()
+        };
+        let first = open();
+        first.try_lock().unwrap();
+        assert!(matches!(open().try_lock(), Err(TryLockError::WouldBlock)));
+        drop(first);
+        open().try_lock().unwrap();
+
+        assert_eq!(sanitize("instance-my/app cache"), "instance-my_app_cache");
+    }
+}
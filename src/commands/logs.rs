@@ -2,15 +2,20 @@
 
 use anyhow::{Context, Result};
 use colored::*;
+use std::process::Stdio;
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
 use tokio::process::Command;
 
-use crate::config::Config;
+use crate::cli::NodeRole;
+use crate::commands::server;
+use crate::config::{Config, InstanceInfo, InstanceType};
 
 pub async fn handle_logs(
     name: Option<String>,
     follow: bool,
     tail: u32,
     timestamps: bool,
+    role: Option<NodeRole>,
     verbose: bool,
 ) -> Result<()> {
     let config = Config::load()?;
@@ -56,8 +61,20 @@ pub async fn handle_logs(
         println!();
     }
 
-    // For cluster instances, show logs from the first container
-    let container_name = &instance.containers[0];
+    // For cluster instances, show logs from the first container unless a role is asked for
+    let containers = match role {
+        Some(role) => {
+            let containers = nodes_with_role(instance, role).await?;
+            if containers.is_empty() {
+                anyhow::bail!("No {} nodes found in '{}'", role, instance_name);
+            }
+            if verbose {
+                println!("  {} nodes: {}", role, containers.join(", ").purple());
+            }
+            containers
+        }
+        None => vec![instance.containers[0].clone()],
+    };
 
     // Show appropriate message
     if follow {
@@ -84,7 +101,47 @@ pub async fn handle_logs(
     println!("{} Redis typically produces few logs after startup unless there are connections or errors.", "Note:".dimmed());
     println!();
 
-    // Build and execute docker logs command directly
+    if let [container_name] = containers.as_slice() {
+        let status = logs_command(container_name, follow, tail, timestamps)
+            .status()
+            .await
+            .context("Failed to execute docker logs command")?;
+
+        if !status.success() {
+            anyhow::bail!(
+                "Docker logs command failed for container '{}'",
+                container_name
+            );
+        }
+        return Ok(());
+    }
+
+    // Several nodes: interleave their output, each line tagged with its container
+    let mut tasks = Vec::new();
+    for container_name in containers {
+        let mut child = logs_command(&container_name, follow, tail, timestamps)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .context("Failed to execute docker logs command")?;
+        let tag = format!("[{}]", container_name);
+        let stdout = child.stdout.take().context("No stdout from docker logs")?;
+        let stderr = child.stderr.take().context("No stderr from docker logs")?;
+        tasks.push(tokio::spawn(async move {
+            tokio::join!(print_tagged(stdout, &tag), print_tagged(stderr, &tag));
+            child.wait().await
+        }));
+    }
+
+    for task in tasks {
+        task.await?
+            .context("Failed to execute docker logs command")?;
+    }
+
+    Ok(())
+}
+
+fn logs_command(container_name: &str, follow: bool, tail: u32, timestamps: bool) -> Command {
     let mut cmd = Command::new("docker");
     cmd.arg("logs");
 
@@ -98,19 +155,76 @@ pub async fn handle_logs(
 
     cmd.arg("--tail").arg(tail.to_string());
     cmd.arg(container_name);
+    cmd
+}
 
-    // Execute the command
-    let status = cmd
-        .status()
-        .await
-        .context("Failed to execute docker logs command")?;
+async fn print_tagged(output: impl AsyncRead + Unpin, tag: &str) {
+    let mut lines = BufReader::new(output).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        println!("{} {}", tag.cyan(), line);
+    }
+}
 
-    if !status.success() {
-        anyhow::bail!(
-            "Docker logs command failed for container '{}'",
-            container_name
-        );
+/// Cluster node containers currently in `role`, as each node reports itself in CLUSTER NODES
+///
+/// Failovers move roles around, so the container index says nothing about them.
+async fn nodes_with_role(instance: &InstanceInfo, role: NodeRole) -> Result<Vec<String>> {
+    if instance.instance_type != InstanceType::Cluster {
+        anyhow::bail!("--role is only supported for cluster instances");
     }
 
-    Ok(())
+    let password = instance.connection_info.password.as_deref();
+    let node_prefix = format!("{}-node-", instance.name);
+    let mut matching = Vec::new();
+    for container in &instance.containers {
+        if !container.starts_with(&node_prefix) {
+            continue;
+        }
+        let Ok(nodes) = server::redis_cli(container, password, &["CLUSTER", "NODES"]).await else {
+            continue;
+        };
+        if own_role(&nodes) == Some(role) {
+            matching.push(container.clone());
+        }
+    }
+    Ok(matching)
+}
+
+/// Role of the node answering CLUSTER NODES, from the flags on its `myself` line
+fn own_role(nodes: &str) -> Option<NodeRole> {
+    let flags = nodes
+        .lines()
+        .filter_map(|line| line.split_whitespace().nth(2))
+        .find(|flags| flags.split(',').any(|flag| flag == "myself"))?;
+    let flags: Vec<&str> = flags.split(',').collect();
+    if flags.contains(&"master") {
+        Some(NodeRole::Master)
+    } else if flags.contains(&"slave") {
+        Some(NodeRole::Replica)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_own_role() {
+        let nodes = "\
+07c37dfeb235213a872192d90877d0cd55635b91 172.18.0.3:6379@16379 slave e7d1eecce10fd6bb5eb35b9f99a514335d9ba9ca 0 1426238317239 4 connected
+e7d1eecce10fd6bb5eb35b9f99a514335d9ba9ca 172.18.0.2:6379@16379 myself,master - 0 0 1 connected 0-5460
+";
+        assert_eq!(own_role(nodes), Some(NodeRole::Master));
+        assert_eq!(
+            own_role(&nodes.replace("myself,master", "master").replacen(
+                "slave",
+                "myself,slave",
+                1
+            )),
+            Some(NodeRole::Replica)
+        );
+        assert_eq!(own_role(""), None);
+    }
 }
@@ -0,0 +1,302 @@
+//! Run a command against one basic instance per Redis version
+
+use anyhow::{Context, Result};
+use colored::*;
+use std::process::{ExitStatus, Stdio};
+use std::time::{Duration, Instant};
+use tokio::process::Command as ProcessCommand;
+use tokio::task::JoinSet;
+
+use crate::cli::{BasicStartArgs, MatrixArgs, RedisAction, StopArgs};
+use crate::commands::{basic, env};
+use crate::config::Config;
+use crate::error::Failure;
+
+/// One version's run
+struct Cell {
+    version: String,
+    name: String,
+    port: u16,
+    started: bool,
+    outcome: Outcome,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Outcome {
+    Pending,
+    Passed(Duration),
+    Failed(Duration, Option<i32>),
+    Error(String),
+}
+
+pub async fn handle_matrix(args: MatrixArgs, verbose: bool) -> Result<()> {
+    let config = Config::load()?;
+    let mut cells = Vec::new();
+    for (i, version) in args.versions.iter().enumerate() {
+        validate_version(version)?;
+        let name = instance_name(version, crate::settings::name_prefix().as_deref());
+        if cells.iter().any(|c: &Cell| c.name == name) {
+            anyhow::bail!("Version '{}' is listed more than once", version);
+        }
+        if config.get_instance(&name).is_some() {
+            return Err(Failure::NameConflict(format!(
+                "Instance '{}' already exists; stop it before running the matrix",
+                name
+            ))
+            .into());
+        }
+        let port = args
+            .port_base
+            .checked_add(i as u16)
+            .context("Port range overflows; lower --port-base")?;
+        cells.push(Cell {
+            version: version.clone(),
+            name,
+            port,
+            started: false,
+            outcome: Outcome::Pending,
+        });
+    }
+
+    // Starts go one at a time since each one updates instances.json
+    for cell in &mut cells {
+        println!(
+            "{} Redis {} as {} on port {}",
+            "Matrix:".bold().cyan(),
+            cell.version.bold(),
+            cell.name,
+            cell.port
+        );
+        match basic::handle_action(RedisAction::Start(start_args(cell)), verbose).await {
+            Ok(()) => cell.started = true,
+            Err(e) => cell.outcome = Outcome::Error(format!("start failed: {:#}", e)),
+        }
+    }
+
+    let config = Config::load()?;
+    let mut jobs = Vec::new();
+    for cell in cells.iter().filter(|c| c.started) {
+        let instance = config
+            .get_instance(&cell.name)
+            .with_context(|| format!("Instance '{}' not found after start", cell.name))?;
+        let mut vars = env::variables(instance)?;
+        vars.push(("REDIS_VERSION".to_string(), cell.version.clone()));
+        jobs.push((cell.version.clone(), vars));
+    }
+
+    if args.parallel {
+        let mut set = JoinSet::new();
+        for (version, vars) in jobs {
+            let run = args.run.clone();
+            set.spawn(async move {
+                let result = run_command(&run, &vars, true).await;
+                (version, result)
+            });
+        }
+        let mut finished = Vec::new();
+        while let Some(joined) = set.join_next().await {
+            finished.push(joined.context("Matrix job panicked")?);
+        }
+        // Print captured output in version order rather than completion order
+        for cell in &mut cells {
+            if let Some((_, result)) = finished.iter().find(|(v, _)| *v == cell.version) {
+                println!(
+                    "\n{} {}",
+                    "──".dimmed(),
+                    format!("Redis {}", cell.version).bold()
+                );
+                cell.outcome = record(result);
+            }
+        }
+    } else {
+        for (version, vars) in jobs {
+            println!(
+                "\n{} {}",
+                "──".dimmed(),
+                format!("Redis {}", version).bold()
+            );
+            let result = run_command(&args.run, &vars, false).await;
+            if let Some(cell) = cells.iter_mut().find(|c| c.version == version) {
+                cell.outcome = record(&result);
+            }
+        }
+    }
+
+    if !args.keep {
+        for cell in cells.iter().filter(|c| c.started) {
+            let stop = StopArgs {
+                name: Some(cell.name.clone()),
+                keep_data: false,
+                timeout: None,
+            };
+            if let Err(e) = basic::handle_action(RedisAction::Stop(stop), verbose).await {
+                println!(
+                    "{} Could not stop {}: {:#}",
+                    "Warning:".yellow(),
+                    cell.name,
+                    e
+                );
+            }
+        }
+    }
+
+    println!("\n{}", "Matrix results:".bold().cyan());
+    let width = cells.iter().map(|c| c.version.len()).max().unwrap_or(0);
+    for cell in &cells {
+        let status = match &cell.outcome {
+            Outcome::Passed(elapsed) => {
+                format!("{} in {:.1}s", "PASS".green(), elapsed.as_secs_f64())
+            }
+            Outcome::Failed(elapsed, code) => format!(
+                "{} in {:.1}s (exit {})",
+                "FAIL".red(),
+                elapsed.as_secs_f64(),
+                code.map_or("signal".to_string(), |c| c.to_string())
+            ),
+            Outcome::Error(e) => format!("{} {}", "ERROR".red(), e),
+            Outcome::Pending => "skipped".dimmed().to_string(),
+        };
+        println!("  {:<width$}  {}", cell.version, status, width = width);
+    }
+    if args.keep {
+        println!(
+            "\n{} Instances left running: {}",
+            "Info:".blue(),
+            cells
+                .iter()
+                .filter(|c| c.started)
+                .map(|c| c.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+
+    let failed = cells
+        .iter()
+        .filter(|c| !matches!(c.outcome, Outcome::Passed(_)))
+        .count();
+    if failed == cells.len() {
+        anyhow::bail!("All {} versions failed", failed);
+    } else if failed > 0 {
+        return Err(
+            Failure::Partial(format!("{} of {} versions failed", failed, cells.len())).into(),
+        );
+    }
+    Ok(())
+}
+
+/// Run the user's command through the shell with the connection variables set
+async fn run_command(
+    run: &str,
+    vars: &[(String, String)],
+    capture: bool,
+) -> Result<(Duration, ExitStatus, String)> {
+    let started = Instant::now();
+    let mut command = ProcessCommand::new("sh");
+    command.arg("-c").arg(run);
+    command.envs(vars.iter().map(|(k, v)| (k.as_str(), v.as_str())));
+    if capture {
+        command.stdin(Stdio::null());
+        let output = command
+            .output()
+            .await
+            .with_context(|| format!("Failed to run '{}'", run))?;
+        let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+        text.push_str(&String::from_utf8_lossy(&output.stderr));
+        Ok((started.elapsed(), output.status, text))
+    } else {
+        let status = command
+            .status()
+            .await
+            .with_context(|| format!("Failed to run '{}'", run))?;
+        Ok((started.elapsed(), status, String::new()))
+    }
+}
+
+/// Print any captured output and turn a run into an outcome
+fn record(result: &Result<(Duration, ExitStatus, String)>) -> Outcome {
+    match result {
+        Ok((elapsed, status, output)) => {
+            print!("{}", output);
+            if status.success() {
+                Outcome::Passed(*elapsed)
+            } else {
+                Outcome::Failed(*elapsed, status.code())
+            }
+        }
+        Err(e) => Outcome::Error(format!("{:#}", e)),
+    }
+}
+
+fn start_args(cell: &Cell) -> BasicStartArgs {
+    BasicStartArgs {
+        name: Some(cell.name.clone()),
+        redis_version: Some(cell.version.clone()),
+        description: Some(format!("redis-up matrix, Redis {}", cell.version)),
+        port: cell.port,
+        password: None,
+        no_auth: false,
+        persist: false,
+        persistence: None,
+        save: None,
+        appendfsync: None,
+        databases: None,
+        load_rdb: None,
+        data_volume: None,
+        networks: Vec::new(),
+        bind: None,
+        allow_exposed: false,
+        ipv6: false,
+        network_mode: Default::default(),
+        memory: None,
+        limits: Default::default(),
+        commands: Default::default(),
+        retries: None,
+        reuse: false,
+        recreate_on_drift: false,
+        warn_on_drift: false,
+        pull: Default::default(),
+        with_insight: false,
+        insight_port: 8001,
+        shell: false,
+        count: 1,
+        shared_password: false,
+    }
+}
+
+/// Versions become image tags and container names, so keep them to tag characters
+fn validate_version(version: &str) -> Result<()> {
+    let valid = !version.is_empty()
+        && version
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-');
+    if !valid {
+        anyhow::bail!(
+            "Invalid Redis version '{}': use an image tag such as 7.2 or 8.0",
+            version
+        );
+    }
+    Ok(())
+}
+
+fn instance_name(version: &str, prefix: Option<&str>) -> String {
+    let name = format!("matrix-{}", version.replace('.', "-"));
+    match prefix {
+        Some(prefix) => format!("{}-{}", prefix, name),
+        None => name,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matrix_names() {
+        assert_eq!(instance_name("7.2", None), "matrix-7-2");
+        assert_eq!(instance_name("6.2", Some("ci")), "ci-matrix-6-2");
+        assert!(validate_version("7.4-rc1").is_ok());
+        assert!(validate_version("7.2; rm -rf").is_err());
+        assert!(validate_version("").is_err());
+    }
+}
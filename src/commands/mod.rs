@@ -1,12 +1,71 @@
 //! Command handlers for redis-up CLI
 
+pub mod annotate;
+pub mod anonymize;
 pub mod basic;
+pub mod capacity;
+pub mod chaos;
+pub mod ci;
 pub mod cleanup;
+pub mod client;
 pub mod cluster;
+pub mod config;
+pub mod config_diff;
+pub mod config_set;
+pub mod dbsize;
+pub mod diff;
+pub mod doctor;
+pub mod emulate;
 pub mod enterprise;
+pub mod enterprise_status;
+pub mod env;
+pub mod examples;
+pub mod exit_codes;
+pub mod formation;
+pub mod functions;
+pub mod health;
+pub mod history;
+pub mod host;
+pub mod images;
+pub mod includes;
+pub mod init;
 pub mod insight;
+pub mod limits;
 pub mod list;
+pub mod live;
+pub mod load;
+pub mod locks;
 pub mod logs;
+pub mod matrix;
+pub mod names;
+pub mod network;
+pub mod observability;
+pub mod passthrough;
+pub mod plan;
+pub mod ports;
+pub mod post_start;
+pub mod profile;
+pub mod progress;
+pub mod reconcile;
+pub mod record;
+pub mod replication;
+pub mod restore;
+pub mod retry;
+pub mod reuse;
+pub mod revive;
+pub mod script;
 pub mod sentinel;
+pub mod server;
+pub mod shell;
+pub mod shutdown;
+pub mod slow;
+pub mod snippets;
 pub mod stack;
+pub mod tls;
+pub mod tunnel;
+pub mod unlock;
+pub mod uptime;
+pub mod volume;
+pub mod warm;
+pub mod watch;
 pub mod yaml;
@@ -0,0 +1,112 @@
+//! Instance name validation
+//!
+//! Instance names become container, volume and network names, and redis-up
+//! derives further names from them (`{name}-node-1`, `{name}-insight`, ...).
+//! Names are checked up front so a bad `--name` fails with a suggestion
+//! instead of deep inside Docker, and so one instance's name can't collide
+//! with another instance's derived containers.
+
+use anyhow::{bail, Result};
+
+/// Fragments redis-up puts in the names of containers derived from an instance
+const RESERVED_FRAGMENTS: &[&str] = &["-node-", "-master-", "-sentinel-", "-replica-"];
+const RESERVED_SUFFIXES: &[&str] = &["-insight", "-lb"];
+
+/// Refuse names Docker would reject or that clash with derived names
+pub fn validate(name: &str) -> Result<()> {
+    if name.is_empty() {
+        bail!("The instance name can't be empty");
+    }
+    let mut chars = name.chars();
+    let valid = chars.next().is_some_and(|c| c.is_ascii_alphanumeric())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'));
+    if !valid {
+        bail!(
+            "Invalid name '{}': use letters, digits, '_', '.' or '-', starting with a letter or digit (try --name {})",
+            name,
+            normalize(name)
+        );
+    }
+    if let Some(reserved) = reserved_part(name) {
+        bail!(
+            "Invalid name '{}': '{}' is used for the containers redis-up derives from instance names (try --name {})",
+            name,
+            reserved,
+            normalize(name)
+        );
+    }
+    Ok(())
+}
+
+/// Check an optional `--name`; generated names are always valid
+pub fn validate_opt(name: Option<&str>) -> Result<()> {
+    name.map_or(Ok(()), validate)
+}
+
+/// The closest valid name: invalid characters become '-', reserved parts are dropped
+pub fn normalize(name: &str) -> String {
+    let mut normalized: String = name
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-') {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    loop {
+        // Collapse the runs of '-' left behind by replaced characters
+        while normalized.contains("--") {
+            normalized = normalized.replace("--", "-");
+        }
+        normalized = normalized.trim_matches(['_', '.', '-']).to_string();
+        match reserved_part(&normalized) {
+            Some(reserved) => normalized = normalized.replacen(reserved, "-", 1),
+            None => break,
+        }
+    }
+    if normalized.is_empty() {
+        "redis".to_string()
+    } else {
+        normalized.to_string()
+    }
+}
+
+fn reserved_part(name: &str) -> Option<&'static str> {
+    RESERVED_FRAGMENTS
+        .iter()
+        .find(|fragment| name.contains(*fragment))
+        .or_else(|| {
+            RESERVED_SUFFIXES
+                .iter()
+                .find(|suffix| name.ends_with(*suffix))
+        })
+        .copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_and_normalize() {
+        assert!(validate("my-redis").is_ok());
+        assert!(validate("cache_1.v2").is_ok());
+        assert!(validate("").is_err());
+        assert!(validate("my redis").is_err());
+        assert!(validate("team/cache").is_err());
+        assert!(validate("-cache").is_err());
+        assert!(validate("app-insight").is_err());
+        assert!(validate("shop-node-1").is_err());
+        assert!(validate("my-cluster").is_ok());
+
+        assert_eq!(normalize("My Redis"), "my-redis");
+        assert_eq!(normalize("team/cache"), "team-cache");
+        assert_eq!(normalize("app-insight"), "app");
+        assert_eq!(normalize("shop-node-1"), "shop-1");
+        assert_eq!(normalize("/"), "redis");
+        assert!(validate(&normalize("  weird//Name-lb ")).is_ok());
+    }
+}
@@ -0,0 +1,159 @@
+//! Docker network attachment for managed instances
+
+use anyhow::{Context, Result};
+use colored::*;
+use docker_wrapper::{
+    DockerCommand, NetworkConnectCommand, NetworkDisconnectCommand, NetworkInspectCommand,
+};
+use std::collections::HashMap;
+
+use crate::cli::{NetworkAction, NetworkAttachArgs};
+use crate::config::Config;
+
+pub async fn handle_action(action: NetworkAction, verbose: bool) -> Result<()> {
+    match action {
+        NetworkAction::Attach(args) => attach(args, verbose).await,
+        NetworkAction::Detach(args) => detach(args, verbose).await,
+    }
+}
+
+async fn attach(args: NetworkAttachArgs, verbose: bool) -> Result<()> {
+    let mut config = Config::load()?;
+    let instance = config
+        .get_instance(&args.name)
+        .with_context(|| format!("Instance '{}' not found", args.name))?
+        .clone();
+
+    ensure_network_exists(&args.network).await?;
+
+    if verbose {
+        println!(
+            "{} Attaching {} to network {}",
+            "Network".cyan(),
+            args.name.bold(),
+            args.network.bold()
+        );
+    }
+
+    connect_containers(&args.network, &instance.containers).await?;
+
+    if let Some(instance) = config.get_instance_mut(&args.name) {
+        record_network(&mut instance.metadata, &args.network);
+    }
+    config.save()?;
+
+    println!(
+        "{} Instance '{}' attached to network '{}'",
+        "Success:".green(),
+        args.name.bold(),
+        args.network.bold()
+    );
+    println!(
+        "  {}: {}",
+        "Reachable as".bold(),
+        instance.containers.join(", ").cyan()
+    );
+
+    Ok(())
+}
+
+async fn detach(args: NetworkAttachArgs, verbose: bool) -> Result<()> {
+    let mut config = Config::load()?;
+    let instance = config
+        .get_instance(&args.name)
+        .with_context(|| format!("Instance '{}' not found", args.name))?
+        .clone();
+
+    if verbose {
+        println!(
+            "{} Detaching {} from network {}",
+            "Network".cyan(),
+            args.name.bold(),
+            args.network.bold()
+        );
+    }
+
+    for container in &instance.containers {
+        NetworkDisconnectCommand::new(&args.network, container)
+            .execute()
+            .await
+            .with_context(|| {
+                format!(
+                    "Failed to disconnect {} from network {}",
+                    container, args.network
+                )
+            })?;
+    }
+
+    if let Some(instance) = config.get_instance_mut(&args.name) {
+        forget_network(&mut instance.metadata, &args.network);
+    }
+    config.save()?;
+
+    println!(
+        "{} Instance '{}' detached from network '{}'",
+        "Success:".green(),
+        args.name.bold(),
+        args.network.bold()
+    );
+
+    Ok(())
+}
+
+/// Fail early with a helpful message when a network does not exist
+pub async fn ensure_network_exists(network: &str) -> Result<()> {
+    if NetworkInspectCommand::new(network).execute().await.is_err() {
+        anyhow::bail!(
+            "Docker network '{}' not found. Create it with 'docker network create {}' or start your compose project first.",
+            network,
+            network
+        );
+    }
+    Ok(())
+}
+
+/// Connect each container to a network
+pub async fn connect_containers(network: &str, containers: &[String]) -> Result<()> {
+    for container in containers {
+        NetworkConnectCommand::new(network, container)
+            .execute()
+            .await
+            .with_context(|| format!("Failed to connect {} to network {}", container, network))?;
+    }
+    Ok(())
+}
+
+/// Record an attached network in instance metadata
+pub fn record_network(metadata: &mut HashMap<String, serde_json::Value>, network: &str) {
+    let mut networks = attached_networks(metadata);
+    if !networks.iter().any(|n| n == network) {
+        networks.push(network.to_string());
+    }
+    metadata.insert("networks".to_string(), serde_json::json!(networks));
+}
+
+/// Remove an attached network from instance metadata
+fn forget_network(metadata: &mut HashMap<String, serde_json::Value>, network: &str) {
+    let networks: Vec<String> = attached_networks(metadata)
+        .into_iter()
+        .filter(|n| n != network)
+        .collect();
+    if networks.is_empty() {
+        metadata.remove("networks");
+    } else {
+        metadata.insert("networks".to_string(), serde_json::json!(networks));
+    }
+}
+
+/// Networks an instance has been attached to
+pub fn attached_networks(metadata: &HashMap<String, serde_json::Value>) -> Vec<String> {
+    metadata
+        .get("networks")
+        .and_then(|v| v.as_array())
+        .map(|list| {
+            list.iter()
+                .filter_map(|n| n.as_str().map(|s| s.to_string()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
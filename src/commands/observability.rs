@@ -0,0 +1,533 @@
+//! Prometheus + Grafana + redis_exporter bundle for managed instances
+
+use anyhow::{Context, Result};
+use colored::*;
+use docker_wrapper::{
+    DockerCommand, NetworkCreateCommand, NetworkRmCommand, PsCommand, RmCommand, RunCommand,
+};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::cli::{ObservabilityAction, ObservabilityStartArgs};
+use crate::commands::{client, host, images, passthrough, server};
+use crate::config::{get_config_dir, Config};
+use crate::secrets;
+
+const NETWORK: &str = "redis-up-observability";
+const EXPORTER: &str = "redis-up-exporter";
+const PROMETHEUS: &str = "redis-up-prometheus";
+const GRAFANA: &str = "redis-up-grafana";
+
+/// A single Redis endpoint scraped through the exporter
+#[derive(Debug, Clone, PartialEq)]
+struct ScrapeTarget {
+    instance: String,
+    uri: String,
+    password: Option<String>,
+}
+
+pub async fn handle_action(action: ObservabilityAction, verbose: bool) -> Result<()> {
+    match action {
+        ObservabilityAction::Start(args) => start_bundle(args, verbose).await,
+        ObservabilityAction::Stop => stop_bundle(verbose).await,
+    }
+}
+
+async fn start_bundle(mut args: ObservabilityStartArgs, verbose: bool) -> Result<()> {
+    let config = Config::load()?;
+    let targets = select_targets(&config, &args.instances)?;
+
+    let running = PsCommand::new()
+        .filter(format!("name={}", PROMETHEUS))
+        .quiet()
+        .execute()
+        .await?;
+    if !running.stdout.trim().is_empty() {
+        anyhow::bail!(
+            "The observability bundle is already running. Run 'redis-up observability stop' first to change its targets."
+        );
+    }
+
+    let required = [
+        images::EXPORTER_IMAGE,
+        images::PROMETHEUS_IMAGE,
+        images::GRAFANA_IMAGE,
+    ];
+    args.pull.platform = images::resolve_platform(&args.pull, "observability", &required)?;
+    images::ensure_images(
+        &required,
+        &args.pull,
+        args.pull.platform.as_deref(),
+        None,
+        verbose,
+    )
+    .await?;
+
+    let dir = bundle_dir()?;
+    write_bundle_files(&dir, &targets)?;
+    if verbose {
+        println!(
+            "{} Wrote configuration to {}",
+            "Observability:".cyan(),
+            dir.display()
+        );
+    }
+
+    if let Err(e) = NetworkCreateCommand::new(NETWORK).execute().await {
+        if !e.to_string().contains("already exists") {
+            return Err(e).context("Failed to create the observability network");
+        }
+    }
+
+    if let Err(e) = run_containers(&args, &dir, verbose).await {
+        remove_containers().await;
+        NetworkRmCommand::new(NETWORK).execute().await.ok();
+        return Err(e);
+    }
+
+    println!("{} Observability bundle started", "Success:".green());
+    println!(
+        "  {}: http://localhost:{} (anonymous admin access)",
+        "Grafana".bold(),
+        args.grafana_port.to_string().cyan()
+    );
+    println!(
+        "  {}: http://localhost:{}",
+        "Prometheus".bold(),
+        args.prometheus_port.to_string().cyan()
+    );
+    println!("  {}: Redis Overview", "Dashboard".bold());
+    println!("\n{}", "Scrape targets:".bold().underline());
+    for target in &targets {
+        println!("  - {} ({})", target.instance.bold(), target.uri);
+    }
+    println!("\n  {} redis-up observability stop", "Stop:".yellow());
+
+    Ok(())
+}
+
+async fn stop_bundle(verbose: bool) -> Result<()> {
+    remove_containers().await;
+
+    if let Err(e) = NetworkRmCommand::new(NETWORK).execute().await {
+        if verbose {
+            println!(
+                "{} Network {} not removed: {}",
+                "Warning:".yellow(),
+                NETWORK,
+                e
+            );
+        }
+    }
+
+    let dir = bundle_dir()?;
+    if dir.exists() {
+        std::fs::remove_dir_all(&dir)
+            .with_context(|| format!("Failed to remove {}", dir.display()))?;
+    }
+
+    println!(
+        "{} Observability bundle stopped and removed",
+        "Success:".green()
+    );
+    Ok(())
+}
+
+/// Host ports the running bundle publishes, as (port, container)
+pub async fn published_ports() -> Vec<(u16, String)> {
+    let Ok(output) = PsCommand::new()
+        .filter("name=redis-up-")
+        .format_template("{{.Names}}\t{{.Ports}}")
+        .execute()
+        .await
+    else {
+        return Vec::new();
+    };
+    parse_published(&output.stdout)
+}
+
+/// Parse `docker ps` lines like `redis-up-grafana\t0.0.0.0:3000->3000/tcp, :::3000->3000/tcp`
+fn parse_published(ps: &str) -> Vec<(u16, String)> {
+    let mut ports = Vec::new();
+    for line in ps.lines() {
+        let Some((name, published)) = line.split_once('\t') else {
+            continue;
+        };
+        if ![EXPORTER, PROMETHEUS, GRAFANA].contains(&name) {
+            continue;
+        }
+        for mapping in published.split(", ") {
+            let port = mapping
+                .split_once("->")
+                .and_then(|(host, _)| host.rsplit(':').next())
+                .and_then(|port| port.parse().ok());
+            if let Some(port) = port {
+                if !ports.contains(&(port, name.to_string())) {
+                    ports.push((port, name.to_string()));
+                }
+            }
+        }
+    }
+    ports
+}
+
+/// Scrape targets for the selected instances, or every managed instance
+fn select_targets(config: &Config, names: &[String]) -> Result<Vec<ScrapeTarget>> {
+    let instances: Vec<_> = if names.is_empty() {
+        let mut all: Vec<_> = config.instances.values().collect();
+        all.sort_by(|a, b| a.name.cmp(&b.name));
+        all
+    } else {
+        names
+            .iter()
+            .map(|name| {
+                config
+                    .get_instance(name)
+                    .with_context(|| format!("Instance '{}' not found", name))
+            })
+            .collect::<Result<_>>()?
+    };
+
+    let targets: Vec<ScrapeTarget> = instances
+        .into_iter()
+        .flat_map(|instance| {
+            client::endpoints(instance)
+                .into_iter()
+                .map(move |endpoint| ScrapeTarget {
+                    instance: instance.name.clone(),
+                    // Published ports are reached through the host gateway
+                    uri: format!(
+                        "redis://{}:{}",
+                        host::from_container(&endpoint.host),
+                        endpoint.port
+                    ),
+                    password: endpoint.password,
+                })
+        })
+        .collect();
+
+    if targets.is_empty() {
+        anyhow::bail!("No managed instances to monitor. Start an instance first.");
+    }
+    Ok(targets)
+}
+
+/// Directory holding the generated Prometheus and Grafana configuration
+fn bundle_dir() -> Result<PathBuf> {
+    Ok(get_config_dir()?.join("observability"))
+}
+
+/// `uid:gid` of a file, for running a container as the user who owns it
+#[cfg(unix)]
+fn owner(path: &Path) -> Option<String> {
+    use std::os::unix::fs::MetadataExt;
+    let metadata = std::fs::metadata(path).ok()?;
+    Some(format!("{}:{}", metadata.uid(), metadata.gid()))
+}
+
+#[cfg(not(unix))]
+fn owner(_path: &Path) -> Option<String> {
+    None
+}
+
+fn write_bundle_files(dir: &Path, targets: &[ScrapeTarget]) -> Result<()> {
+    let write = |relative: &str, contents: String| -> Result<()> {
+        let path = dir.join(relative);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        std::fs::write(&path, contents)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    };
+
+    write(
+        "prometheus.yml",
+        serde_yaml::to_string(&prometheus_config(targets))?,
+    )?;
+
+    let passwords: BTreeMap<&str, &str> = targets
+        .iter()
+        .filter_map(|t| Some((t.uri.as_str(), t.password.as_deref()?)))
+        .collect();
+    // Created empty and owner-only before the passwords go in
+    write("passwords.json", String::new())?;
+    secrets::restrict(&dir.join("passwords.json"), 0o600)?;
+    write("passwords.json", serde_json::to_string_pretty(&passwords)?)?;
+
+    write(
+        "grafana/datasources/redis-up.yaml",
+        serde_yaml::to_string(&serde_json::json!({
+            "apiVersion": 1,
+            "datasources": [{
+                "name": "Prometheus",
+                "uid": "redis-up-prometheus",
+                "type": "prometheus",
+                "access": "proxy",
+                "url": format!("http://{}:9090", PROMETHEUS),
+                "isDefault": true,
+            }],
+        }))?,
+    )?;
+    write(
+        "grafana/dashboards/redis-up.yaml",
+        serde_yaml::to_string(&serde_json::json!({
+            "apiVersion": 1,
+            "providers": [{
+                "name": "redis-up",
+                "type": "file",
+                "options": { "path": "/var/lib/grafana/dashboards" },
+            }],
+        }))?,
+    )?;
+    write(
+        "dashboards/redis.json",
+        serde_json::to_string_pretty(&dashboard())?,
+    )?;
+
+    Ok(())
+}
+
+async fn run_containers(args: &ObservabilityStartArgs, dir: &Path, verbose: bool) -> Result<()> {
+    let path = |relative: &str| host::mount_source(&dir.join(relative));
+    let run = |image: &str| {
+        let cmd = passthrough::apply(RunCommand::new(images::resolve(image)));
+        match args.pull.platform {
+            Some(ref platform) => cmd.platform(platform),
+            None => cmd,
+        }
+    };
+
+    // One exporter scrapes every target via the multi-target /scrape endpoint
+    let mut cmd = run(images::EXPORTER_IMAGE)
+        .name(EXPORTER)
+        .network(NETWORK)
+        .add_host(host::GATEWAY_MAPPING)
+        .volume_ro(path("passwords.json"), "/etc/redis_exporter/passwords.json")
+        .env("REDIS_PASSWORD_FILE", "/etc/redis_exporter/passwords.json")
+        .detach();
+    // passwords.json is owner-only, so the exporter runs as its owner to read it
+    if let Some(owner) = owner(&dir.join("passwords.json")) {
+        cmd = cmd.user(owner);
+    }
+    server::run_container(&cmd, None)
+        .await
+        .context("Failed to start redis_exporter")?;
+    if verbose {
+        println!("  {} {}", "Started".green(), EXPORTER);
+    }
+
+    let cmd = run(images::PROMETHEUS_IMAGE)
+        .name(PROMETHEUS)
+        .network(NETWORK)
+        .port(args.prometheus_port, 9090)
+        .volume_ro(path("prometheus.yml"), "/etc/prometheus/prometheus.yml")
+        .detach();
+    server::run_container(&cmd, None)
+        .await
+        .context("Failed to start Prometheus")?;
+    if verbose {
+        println!("  {} {}", "Started".green(), PROMETHEUS);
+    }
+
+    let cmd = run(images::GRAFANA_IMAGE)
+        .name(GRAFANA)
+        .network(NETWORK)
+        .port(args.grafana_port, 3000)
+        .volume_ro(path("grafana"), "/etc/grafana/provisioning")
+        .volume_ro(path("dashboards"), "/var/lib/grafana/dashboards")
+        .env("GF_AUTH_ANONYMOUS_ENABLED", "true")
+        .env("GF_AUTH_ANONYMOUS_ORG_ROLE", "Admin")
+        .env("GF_AUTH_DISABLE_LOGIN_FORM", "true")
+        .env(
+            "GF_DASHBOARDS_DEFAULT_HOME_DASHBOARD_PATH",
+            "/var/lib/grafana/dashboards/redis.json",
+        )
+        .detach();
+    server::run_container(&cmd, None)
+        .await
+        .context("Failed to start Grafana")?;
+    if verbose {
+        println!("  {} {}", "Started".green(), GRAFANA);
+    }
+
+    Ok(())
+}
+
+async fn remove_containers() {
+    for container in [GRAFANA, PROMETHEUS, EXPORTER] {
+        RmCommand::new(container).force().execute().await.ok();
+    }
+}
+
+/// Prometheus configuration scraping each target through the exporter
+fn prometheus_config(targets: &[ScrapeTarget]) -> serde_json::Value {
+    let static_configs: Vec<serde_json::Value> = targets
+        .iter()
+        .map(|t| {
+            serde_json::json!({
+                "targets": [t.uri],
+                "labels": { "redis_up_instance": t.instance },
+            })
+        })
+        .collect();
+
+    serde_json::json!({
+        "global": { "scrape_interval": "5s" },
+        "scrape_configs": [
+            {
+                "job_name": "redis",
+                "metrics_path": "/scrape",
+                "static_configs": static_configs,
+                "relabel_configs": [
+                    { "source_labels": ["__address__"], "target_label": "__param_target" },
+                    { "source_labels": ["__param_target"], "target_label": "instance" },
+                    { "target_label": "__address__", "replacement": format!("{}:9121", EXPORTER) },
+                ],
+            },
+            {
+                "job_name": "redis_exporter",
+                "static_configs": [{ "targets": [format!("{}:9121", EXPORTER)] }],
+            },
+        ],
+    })
+}
+
+/// Grafana dashboard with the usual Redis overview panels
+fn dashboard() -> serde_json::Value {
+    let selector = "redis_up_instance=~\"$instance\"";
+    let panels = [
+        (
+            "Commands / sec",
+            format!(
+                "sum by (redis_up_instance) (rate(redis_commands_processed_total{{{}}}[1m]))",
+                selector
+            ),
+            "ops",
+        ),
+        (
+            "Memory used",
+            format!(
+                "sum by (redis_up_instance) (redis_memory_used_bytes{{{}}})",
+                selector
+            ),
+            "bytes",
+        ),
+        (
+            "Connected clients",
+            format!(
+                "sum by (redis_up_instance) (redis_connected_clients{{{}}})",
+                selector
+            ),
+            "short",
+        ),
+        (
+            "Keys",
+            format!(
+                "sum by (redis_up_instance) (redis_db_keys{{{}}})",
+                selector
+            ),
+            "short",
+        ),
+        (
+            "Hit ratio",
+            format!(
+                "sum by (redis_up_instance) (rate(redis_keyspace_hits_total{{{0}}}[1m])) / (sum by (redis_up_instance) (rate(redis_keyspace_hits_total{{{0}}}[1m])) + sum by (redis_up_instance) (rate(redis_keyspace_misses_total{{{0}}}[1m])))",
+                selector
+            ),
+            "percentunit",
+        ),
+        (
+            "Evicted and expired keys / sec",
+            format!(
+                "sum by (redis_up_instance) (rate(redis_evicted_keys_total{{{0}}}[1m]) + rate(redis_expired_keys_total{{{0}}}[1m]))",
+                selector
+            ),
+            "ops",
+        ),
+    ];
+
+    let panels: Vec<serde_json::Value> = panels
+        .iter()
+        .enumerate()
+        .map(|(i, (title, expr, unit))| {
+            serde_json::json!({
+                "id": i + 1,
+                "type": "timeseries",
+                "title": title,
+                "datasource": { "type": "prometheus", "uid": "redis-up-prometheus" },
+                "gridPos": { "h": 8, "w": 12, "x": (i % 2) * 12, "y": (i / 2) * 8 },
+                "fieldConfig": { "defaults": { "unit": unit }, "overrides": [] },
+                "targets": [{ "refId": "A", "expr": expr, "legendFormat": "{{redis_up_instance}}" }],
+            })
+        })
+        .collect();
+
+    serde_json::json!({
+        "uid": "redis-up-overview",
+        "title": "Redis Overview",
+        "schemaVersion": 39,
+        "refresh": "10s",
+        "time": { "from": "now-15m", "to": "now" },
+        "templating": {
+            "list": [{
+                "name": "instance",
+                "type": "query",
+                "datasource": { "type": "prometheus", "uid": "redis-up-prometheus" },
+                "query": "label_values(redis_up_instance)",
+                "includeAll": true,
+                "multi": true,
+                "current": { "text": "All", "value": "$__all" },
+            }],
+        },
+        "panels": panels,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prometheus_config() {
+        let targets = vec![
+            ScrapeTarget {
+                instance: "cache".to_string(),
+                uri: "redis://host.docker.internal:6379".to_string(),
+                password: Some("secret".to_string()),
+            },
+            ScrapeTarget {
+                instance: "sessions".to_string(),
+                uri: "redis://host.docker.internal:6380".to_string(),
+                password: None,
+            },
+        ];
+
+        let config = prometheus_config(&targets);
+        let redis_job = &config["scrape_configs"][0];
+        assert_eq!(redis_job["metrics_path"], "/scrape");
+        assert_eq!(
+            redis_job["static_configs"][1],
+            serde_json::json!({
+                "targets": ["redis://host.docker.internal:6380"],
+                "labels": { "redis_up_instance": "sessions" },
+            })
+        );
+        assert_eq!(
+            redis_job["relabel_configs"][2]["replacement"],
+            "redis-up-exporter:9121"
+        );
+    }
+
+    #[test]
+    fn test_parse_published() {
+        let ps = "redis-up-grafana\t0.0.0.0:3000->3000/tcp, :::3000->3000/tcp\n\
+                  redis-up-exporter\t9121/tcp\n\
+                  redis-up-prometheus\t0.0.0.0:9090->9090/tcp\n\
+                  bob-redis-up-cache\t0.0.0.0:6379->6379/tcp";
+        assert_eq!(
+            parse_published(ps),
+            vec![(3000, GRAFANA.to_string()), (9090, PROMETHEUS.to_string())]
+        );
+    }
+}
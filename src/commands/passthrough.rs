@@ -0,0 +1,143 @@
+//! Environment and extra `docker run` arguments for the containers redis-up creates
+//!
+//! `--pass-env NAME=VALUE` (or the `pass-env` setting) sets a variable in
+//! every container of the run; a bare `NAME` copies the value from this
+//! shell, the way `docker build --build-arg` does, so proxy settings can
+//! follow the user into RedisInsight or Enterprise nodes without being
+//! spelled out.
+//!
+//! `--docker-arg` is the escape hatch for Docker features redis-up has no flag
+//! for: each value goes into `docker run` after the generated options, just
+//! before the image, so it also wins over them.
+//!
+//! Bootstrapped Enterprise nodes are created by a docker-wrapper template that
+//! runs Docker itself, so neither reaches them: `enterprise start` refuses
+//! `--docker-arg` there and warns that the variables are left out.
+
+use anyhow::{bail, Result};
+use docker_wrapper::{DockerCommand, RunCommand};
+use std::sync::OnceLock;
+
+use crate::settings::Settings;
+
+/// Variables for this run, resolved once at startup
+static ENV: OnceLock<Vec<(String, String)>> = OnceLock::new();
+
+/// `--docker-arg` values for this run
+static DOCKER_ARGS: OnceLock<Vec<String>> = OnceLock::new();
+
+/// Resolve the `pass-env` setting and `--pass-env` flags (flags win on
+/// duplicates), and check the `--docker-arg` values
+pub fn init(env_flags: &[String], docker_args: &[String]) -> Result<()> {
+    let configured = Settings::load()?.pass_env;
+    let mut env: Vec<(String, String)> = Vec::new();
+    for entry in configured.iter().chain(env_flags) {
+        let Some((name, value)) = parse(entry, |name| std::env::var(name).ok())? else {
+            continue;
+        };
+        env.retain(|(existing, _)| *existing != name);
+        env.push((name, value));
+    }
+    let _ = ENV.set(env);
+
+    for arg in docker_args {
+        if !arg.starts_with('-') {
+            bail!(
+                "Invalid --docker-arg '{}': pass one option per flag, with its value after '=' (e.g. --docker-arg=--ulimit=nofile=65535:65535)",
+                arg
+            );
+        }
+    }
+    let _ = DOCKER_ARGS.set(docker_args.to_vec());
+    Ok(())
+}
+
+/// The variables in effect
+pub fn env() -> &'static [(String, String)] {
+    ENV.get().map_or(&[], Vec::as_slice)
+}
+
+/// The `--docker-arg` values in effect
+pub fn docker_args() -> &'static [String] {
+    DOCKER_ARGS.get().map_or(&[], Vec::as_slice)
+}
+
+/// Add the variables to a run command
+pub fn apply(mut cmd: RunCommand) -> RunCommand {
+    for (name, value) in env() {
+        cmd = cmd.env(name, value);
+    }
+    cmd
+}
+
+/// Arguments of a run command with the `--docker-arg` values in front of the image
+pub fn run_args(cmd: &RunCommand) -> Vec<String> {
+    with_extra_args(cmd, docker_args())
+}
+
+fn with_extra_args(cmd: &RunCommand, extra: &[String]) -> Vec<String> {
+    let mut args = cmd.build_command_args();
+    // Without its command, a run line ends with the image
+    let image = cmd.clone().cmd(Vec::new()).build_command_args().len() - 1;
+    args.splice(image..image, extra.iter().cloned());
+    args
+}
+
+/// `NAME=VALUE`, or `NAME` looked up in the environment; unset names are skipped
+fn parse(entry: &str, lookup: impl Fn(&str) -> Option<String>) -> Result<Option<(String, String)>> {
+    let (name, value) = match entry.split_once('=') {
+        Some((name, value)) => (name, Some(value.to_string())),
+        None => (entry, None),
+    };
+    let valid = name
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid {
+        bail!(
+            "Invalid --pass-env '{}': expected NAME or NAME=VALUE with a variable name like HTTPS_PROXY",
+            entry
+        );
+    }
+    Ok(value
+        .or_else(|| lookup(name))
+        .map(|value| (name.to_string(), value)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extra_args_precede_image() {
+        let cmd = RunCommand::new("redis:7-alpine")
+            .name("cache")
+            .detach()
+            .cmd(vec![
+                "redis-server".to_string(),
+                "--port".to_string(),
+                "6379".to_string(),
+            ]);
+        let args = with_extra_args(&cmd, &["--ulimit=nofile=65535:65535".to_string()]);
+        let image = args.iter().position(|arg| arg == "redis:7-alpine").unwrap();
+        assert_eq!(args[image - 1], "--ulimit=nofile=65535:65535");
+        assert_eq!(args[image + 1..], ["redis-server", "--port", "6379"]);
+    }
+
+    #[test]
+    fn test_parse() {
+        let lookup = |name: &str| (name == "HTTPS_PROXY").then(|| "http://proxy:3128".to_string());
+        assert_eq!(
+            parse("HTTPS_PROXY", lookup).unwrap(),
+            Some(("HTTPS_PROXY".to_string(), "http://proxy:3128".to_string()))
+        );
+        assert_eq!(parse("HTTP_PROXY", lookup).unwrap(), None);
+        assert_eq!(
+            parse("NO_PROXY=localhost,.internal", lookup).unwrap(),
+            Some(("NO_PROXY".to_string(), "localhost,.internal".to_string()))
+        );
+        assert!(parse("1BAD", lookup).is_err());
+        assert!(parse("=value", lookup).is_err());
+    }
+}
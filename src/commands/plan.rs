@@ -0,0 +1,376 @@
+//! Operation plans for `--dry-run`
+//!
+//! Mutating commands describe the Docker operations they would perform as a
+//! [`Plan`], which `--dry-run` prints instead of executing.
+
+use anyhow::{Context, Result};
+use colored::*;
+use docker_wrapper::{DockerCommand, RunCommand};
+use std::borrow::Cow;
+use std::fmt;
+use std::net::IpAddr;
+
+use crate::cli::{
+    ClusterAction, Commands, EnterpriseAction, RedisAction, SentinelAction, StackAction, StopArgs,
+};
+use crate::commands::{
+    basic, cleanup, cluster, enterprise, images, names, sentinel, server, stack, volume, yaml,
+};
+use crate::config::{Config, InstanceInfo, InstanceType};
+
+/// A single Docker or API operation
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operation {
+    /// A Docker command, argument for argument
+    Docker(Vec<String>),
+    /// A `docker run`, with the image and platform `warm` pulls for it
+    Run {
+        image: String,
+        platform: Option<String>,
+        args: Vec<String>,
+    },
+    Api {
+        method: String,
+        url: String,
+    },
+    Redis {
+        command: String,
+    },
+    StopContainer {
+        name: String,
+    },
+    RemoveContainer {
+        name: String,
+        volumes: bool,
+    },
+    RemoveVolume {
+        name: String,
+    },
+    RemoveNetwork {
+        name: String,
+    },
+    SaveState {
+        description: String,
+    },
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Operation::Docker(args) | Operation::Run { args, .. } => {
+                write!(f, "docker")?;
+                for arg in args {
+                    write!(f, " {}", quote(arg))?;
+                }
+                Ok(())
+            }
+            Operation::Api { method, url } => write!(f, "{} {}", method, url),
+            Operation::Redis { command } => write!(f, "redis> {}", command),
+            Operation::StopContainer { name } => write!(f, "docker stop {}", name),
+            Operation::RemoveContainer { name, volumes } => {
+                write!(
+                    f,
+                    "docker rm -f {}{}",
+                    if *volumes { "-v " } else { "" },
+                    name
+                )
+            }
+            Operation::RemoveVolume { name } => write!(f, "docker volume rm -f {}", name),
+            Operation::RemoveNetwork { name } => write!(f, "docker network rm {}", name),
+            Operation::SaveState { description } => write!(f, "# {}", description),
+        }
+    }
+}
+
+/// An argument as it would have to be typed into a shell
+fn quote(arg: &str) -> Cow<'_, str> {
+    let plain = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:=@%+,".contains(c));
+    if plain {
+        Cow::Borrowed(arg)
+    } else {
+        Cow::Owned(format!("'{}'", arg.replace('\'', r"'\''")))
+    }
+}
+
+/// The operations one command would perform
+#[derive(Debug, Clone, PartialEq)]
+pub struct Plan {
+    pub title: String,
+    pub operations: Vec<Operation>,
+    /// Platform for every container the plan runs
+    pub platform: Option<String>,
+    /// Values printed as `<password>`
+    pub secrets: Vec<String>,
+}
+
+impl Plan {
+    pub fn new(title: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            operations: Vec::new(),
+            platform: None,
+            secrets: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, operation: Operation) {
+        self.operations.push(operation);
+    }
+
+    /// Add a Docker command, as its `execute` would run it
+    pub fn docker(&mut self, cmd: &impl DockerCommand) {
+        self.push(Operation::Docker(cmd.build_command_args()));
+    }
+
+    /// Add a container start, as `server::run_container` would run it
+    pub fn run(&mut self, image: &str, cmd: &RunCommand, bind: Option<IpAddr>) {
+        self.push(Operation::Run {
+            image: images::resolve(image),
+            platform: self.platform.clone(),
+            args: server::run_args(cmd, bind),
+        });
+    }
+
+    /// Print `secret` as `<password>`; a generated one differs on the real run anyway
+    pub fn hide(&mut self, secret: &str) {
+        if !secret.is_empty() {
+            self.secrets.push(secret.to_string());
+        }
+    }
+
+    pub fn print(&self) {
+        println!("{} {}", "Plan:".bold().cyan(), self.title.bold());
+        for operation in &self.operations {
+            let mut line = operation.to_string();
+            for secret in &self.secrets {
+                line = line.replace(secret.as_str(), "<password>");
+            }
+            println!("  {}", line);
+        }
+    }
+}
+
+/// Build the plan for a command, or `None` if it has no dry-run support
+///
+/// `profile` selects a deployment file's profile for `deploy`.
+pub async fn plan_command(
+    command: &Commands,
+    prefix: Option<&str>,
+    profile: Option<&str>,
+) -> Result<Option<Vec<Plan>>> {
+    let mut config = Config::load()?;
+    if let Some(name) = start_name(command) {
+        names::validate(name)?;
+    }
+
+    let plans = match command {
+        Commands::Basic { action } => match action {
+            RedisAction::Start(args) => basic::plan_many(args, &mut config).await?,
+            RedisAction::Stop(args) => vec![plan_stop(&config, args, InstanceType::Basic)?],
+            RedisAction::Info(_) => return Ok(None),
+        },
+        Commands::Stack { action } => match action {
+            StackAction::Start(args) => vec![stack::plan_start(args, &mut config).await?],
+            StackAction::Stop(args) => vec![plan_stop(&config, args, InstanceType::Stack)?],
+            StackAction::Info(_) => return Ok(None),
+        },
+        Commands::Cluster { action } => match action {
+            ClusterAction::Start(args) => vec![cluster::plan_start(args, &mut config).await?],
+            ClusterAction::Stop(args) => vec![plan_stop(&config, args, InstanceType::Cluster)?],
+            ClusterAction::Info(_) => return Ok(None),
+        },
+        Commands::Sentinel { action } => match action {
+            SentinelAction::Start(args) => vec![sentinel::plan_start(args, &mut config).await?],
+            SentinelAction::Stop(args) => {
+                vec![plan_stop(&config, args, InstanceType::Sentinel)?]
+            }
+            SentinelAction::Info(_) | SentinelAction::Url(_) | SentinelAction::Tune(_) => {
+                return Ok(None)
+            }
+        },
+        Commands::Enterprise { action } => match action {
+            EnterpriseAction::Start(args) => vec![enterprise::plan_start(args, &mut config).await?],
+            EnterpriseAction::Stop(args) => {
+                vec![plan_stop(&config, args, InstanceType::Enterprise)?]
+            }
+            EnterpriseAction::Info(_) | EnterpriseAction::Api(_) => return Ok(None),
+        },
+        Commands::Cleanup(args) => cleanup::select_instances(&config, args, prefix)
+            .await?
+            .iter()
+            .map(|instance| plan_removal(instance, args.keep_data))
+            .collect(),
+        Commands::Deploy { file } => {
+            let deployments = yaml::load_yaml(file, profile).await?.deployments;
+            let mut plans = Vec::new();
+            for deployment in &deployments {
+                names::validate(&deployment.name)?;
+                let mut plan = match yaml::start_request(deployment)? {
+                    yaml::StartRequest::Basic(args) => {
+                        basic::plan_start(&args, &mut config).await?
+                    }
+                    yaml::StartRequest::Stack(args) => {
+                        stack::plan_start(&args, &mut config).await?
+                    }
+                    yaml::StartRequest::Cluster(args) => {
+                        cluster::plan_start(&args, &mut config).await?
+                    }
+                    yaml::StartRequest::Sentinel(args) => {
+                        sentinel::plan_start(&args, &mut config).await?
+                    }
+                    yaml::StartRequest::Enterprise(args) => {
+                        enterprise::plan_start(&args, &mut config).await?
+                    }
+                };
+                for step in &deployment.post_start {
+                    plan.push(Operation::Redis {
+                        command: step.to_string(),
+                    });
+                }
+                plans.push(plan);
+            }
+            plans
+        }
+        _ => return Ok(None),
+    };
+
+    Ok(Some(plans))
+}
+
+fn plan_stop(config: &Config, args: &StopArgs, instance_type: InstanceType) -> Result<Plan> {
+    let instance = match args.name {
+        Some(ref name) => config.get_instance(name),
+        None => config.get_latest_instance(&instance_type),
+    }
+    .with_context(|| format!("No {} instance found", instance_type))?;
+    Ok(plan_removal(instance, args.keep_data))
+}
+
+/// Operations that stop and remove an instance
+pub fn plan_removal(instance: &InstanceInfo, keep_data: bool) -> Plan {
+    let keep_data = volume::keep_data(instance, keep_data);
+    let mut plan = Plan::new(format!(
+        "remove {} instance {}",
+        instance.instance_type, instance.name
+    ));
+
+    for container in &instance.containers {
+        plan.push(Operation::StopContainer {
+            name: container.clone(),
+        });
+        plan.push(Operation::RemoveContainer {
+            name: container.clone(),
+            volumes: !keep_data,
+        });
+    }
+
+    if instance.instance_type == InstanceType::Enterprise && !keep_data {
+        for cluster in enterprise::cluster_names(instance) {
+            for suffix in ["persistent", "ephemeral"] {
+                plan.push(Operation::RemoveVolume {
+                    name: format!("{}-{}", cluster, suffix),
+                });
+            }
+        }
+    }
+
+    let network = match instance.metadata.get("network").and_then(|v| v.as_str()) {
+        Some(network) => Some(network.to_string()),
+        None if matches!(
+            instance.instance_type,
+            InstanceType::Cluster | InstanceType::Stack
+        ) =>
+        {
+            Some(format!("{}-network", instance.name))
+        }
+        None => None,
+    };
+    if let Some(name) = network {
+        plan.push(Operation::RemoveNetwork { name });
+    }
+
+    plan.push(Operation::SaveState {
+        description: format!("remove {} from instances.json", instance.name),
+    });
+    plan
+}
+
+/// The `--name` of a start command, if it has one
+fn start_name(command: &Commands) -> Option<&str> {
+    match command {
+        Commands::Basic {
+            action: RedisAction::Start(args),
+        } => args.name.as_deref(),
+        Commands::Stack {
+            action: StackAction::Start(args),
+        } => args.name.as_deref(),
+        Commands::Cluster {
+            action: ClusterAction::Start(args),
+        } => args.name.as_deref(),
+        Commands::Sentinel {
+            action: SentinelAction::Start(args),
+        } => args.name.as_deref(),
+        Commands::Enterprise {
+            action: EnterpriseAction::Start(args),
+        } => args.name.as_deref(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_operation_display() {
+        let run = RunCommand::new("redis:7-alpine")
+            .name("cache")
+            .port(6380, 6379)
+            .env("REDIS_ARGS", "--save 60 1")
+            .detach();
+        let mut plan = Plan::new("cache");
+        plan.run("redis:7-alpine", &run, Some("127.0.0.1".parse().unwrap()));
+        assert_eq!(
+            plan.operations[0].to_string(),
+            "docker run --detach --name cache --env 'REDIS_ARGS=--save 60 1' --publish 127.0.0.1:6380:6379 redis:7-alpine"
+        );
+        assert_eq!(quote(""), "''");
+        assert_eq!(quote("it's"), r"'it'\''s'");
+    }
+
+    #[test]
+    fn test_plan_removal_keeps_persisted_data() {
+        let instance = InstanceInfo {
+            name: "search".to_string(),
+            instance_type: InstanceType::Enterprise,
+            created_at: String::new(),
+            ports: vec![8443],
+            containers: vec!["search-enterprise".to_string()],
+            connection_info: crate::config::ConnectionInfo {
+                host: "localhost".to_string(),
+                port: 12000,
+                password: None,
+                url: String::new(),
+                additional_ports: Default::default(),
+                ipv6_host: None,
+                ipv6_url: None,
+            },
+            metadata: [("persist".to_string(), serde_json::json!(true))].into(),
+            started_at: None,
+            last_seen: None,
+        };
+
+        let plan = plan_removal(&instance, false);
+        assert!(plan.operations.contains(&Operation::RemoveContainer {
+            name: "search-enterprise".to_string(),
+            volumes: false,
+        }));
+        assert!(!plan
+            .operations
+            .iter()
+            .any(|op| matches!(op, Operation::RemoveVolume { .. })));
+    }
+}
@@ -0,0 +1,152 @@
+//! Host port allocation across instances, and the `ports` map
+
+use anyhow::Result;
+use colored::*;
+
+use crate::config::{self, Config, PortMap, PortPool};
+use crate::error::Failure;
+
+/// First of `count` consecutive host ports for a new instance: `requested` when
+/// nothing managed holds them, otherwise the first free block in the pool's range
+pub fn reserve(
+    config: &Config,
+    pool: PortPool,
+    requested: u16,
+    count: usize,
+    what: &str,
+) -> Result<u16> {
+    let taken = config.port_map_all();
+    let count = u16::try_from(count).unwrap_or(u16::MAX);
+    let Some(base) = config::allocate_ports(&taken, pool, requested, count) else {
+        let range = pool.range();
+        return Err(Failure::PortConflict(format!(
+            "No {} free consecutive ports left in the {} range {}-{}; see `redis-up ports`",
+            count,
+            pool,
+            range.start(),
+            range.end()
+        ))
+        .into());
+    };
+    if base != requested {
+        let holder = (requested..requested.saturating_add(count.max(1)))
+            .find_map(|port| taken.get(&port))
+            .and_then(|holders| holders.first())
+            .map_or_else(String::new, |(name, _)| format!(" by {}", name));
+        println!(
+            "{} {} port {} is taken{}, using {} instead",
+            "Note:".yellow(),
+            what,
+            requested,
+            holder,
+            base
+        );
+    }
+    Ok(base)
+}
+
+/// Fail when a managed instance already holds one of `ports`, for deployments
+/// whose ports are fixed rather than allocated from a pool
+pub fn check_free(config: &Config, ports: &[u16], what: &str, flags: &str) -> Result<()> {
+    let taken = config.port_map_all();
+    let held: Vec<String> = ports
+        .iter()
+        .filter_map(|port| {
+            let (name, _) = taken.get(port)?.first()?;
+            Some(format!("{} (held by {})", port, name))
+        })
+        .collect();
+    if held.is_empty() {
+        return Ok(());
+    }
+    Err(Failure::PortConflict(format!(
+        "{} port(s) already in use: {}; choose others with {}, see `redis-up ports`",
+        what,
+        held.join(", "),
+        flags
+    ))
+    .into())
+}
+
+pub fn handle_ports(json: bool) -> Result<()> {
+    let map = Config::load()?.port_map_all();
+
+    if json {
+        let ports: Vec<serde_json::Value> = map
+            .iter()
+            .map(|(port, holders)| {
+                serde_json::json!({
+                    "port": port,
+                    "range": pool_of(*port).map(|pool| pool.to_string()),
+                    "holders": holders
+                        .iter()
+                        .map(|(name, role)| serde_json::json!({"instance": name, "role": role}))
+                        .collect::<Vec<_>>(),
+                })
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&ports)?);
+        return Ok(());
+    }
+
+    println!("{}", "Port allocation:".bold().cyan());
+    for pool in PortPool::ALL {
+        let range = pool.range();
+        let used: PortMap = map
+            .iter()
+            .filter(|(port, _)| range.contains(port))
+            .map(|(port, holders)| (*port, holders.clone()))
+            .collect();
+        println!();
+        println!(
+            "  {} {}-{} ({} in use)",
+            format!("{:<9}", pool.to_string()).bold(),
+            range.start(),
+            range.end(),
+            used.len()
+        );
+        print_ports(&used);
+    }
+
+    let other: PortMap = map
+        .iter()
+        .filter(|(port, _)| pool_of(**port).is_none())
+        .map(|(port, holders)| (*port, holders.clone()))
+        .collect();
+    if !other.is_empty() {
+        println!();
+        println!("  {}", "other".bold());
+        print_ports(&other);
+    }
+
+    let conflicts = map.values().filter(|holders| holders.len() > 1).count();
+    if conflicts > 0 {
+        println!();
+        println!(
+            "{} {} ports are claimed by more than one instance",
+            "Warning:".yellow(),
+            conflicts
+        );
+    }
+    Ok(())
+}
+
+fn print_ports(ports: &PortMap) {
+    for (port, holders) in ports {
+        let conflict = holders.len() > 1;
+        for (name, role) in holders {
+            let line = format!("    {:<6} {:<30} {}", port, name, role);
+            if conflict {
+                println!("{} {}", line, "conflict".red());
+            } else {
+                println!("{}", line);
+            }
+        }
+    }
+}
+
+fn pool_of(port: u16) -> Option<PortPool> {
+    PortPool::ALL
+        .into_iter()
+        .find(|pool| pool.range().contains(&port))
+}
@@ -0,0 +1,228 @@
+//! `post-start` steps for YAML deployments
+//!
+//! A deployment can list Redis commands and Lua scripts to run once it is up,
+//! so `redis-up deploy` leaves indexes created, keys seeded and ACL users in
+//! place. Steps run in order after every server answers PING; the first one
+//! that fails stops the rest and fails the deployment, leaving the instance
+//! running so the step can be tried by hand.
+//!
+//! In a cluster a step goes to the master that owns its keys (the others
+//! answer MOVED), while node-local commands such as ACL and FUNCTION run on
+//! every master.
+
+use anyhow::{bail, Context, Result};
+use colored::*;
+use redis::aio::MultiplexedConnection;
+use redis::Value;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::commands::client::{self, Endpoint};
+use crate::commands::{script, warm};
+use crate::config::{InstanceInfo, InstanceType};
+
+/// Commands whose effect stays on the node that runs them
+const NODE_LOCAL: &[&str] = &["ACL", "CONFIG", "FUNCTION", "SCRIPT"];
+
+/// One `post-start` entry
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(
+    untagged,
+    expecting = "a command line, or a mapping with `script` and optional `keys` and `args`"
+)]
+pub enum Step {
+    /// A command line, quoted like redis-cli: `SET greeting "hello world"`
+    Command(String),
+    /// A Lua script, relative to the deployment file, run with EVAL
+    Script {
+        script: PathBuf,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        keys: Vec<String>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        args: Vec<String>,
+    },
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Step::Command(line) => write!(f, "{}", line),
+            Step::Script { script, keys, args } => {
+                write!(f, "EVAL <{}> {}", script.display(), keys.len())?;
+                for word in keys.iter().chain(args) {
+                    write!(f, " {}", word)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Check that every command step can be split into words
+pub fn check(steps: &[Step]) -> Result<()> {
+    for step in steps {
+        if let Step::Command(line) = step {
+            split(line)?;
+        }
+    }
+    Ok(())
+}
+
+/// Make relative script paths relative to the directory of the deployment file
+pub fn resolve(steps: &mut [Step], base: &Path) {
+    for step in steps {
+        if let Step::Script { script, .. } = step {
+            if script.is_relative() {
+                *script = base.join(&*script);
+            }
+        }
+    }
+}
+
+/// Wait for the instance to answer, then run its steps in order
+pub async fn run(instance: &InstanceInfo, steps: &[Step], verbose: bool) -> Result<()> {
+    if steps.is_empty() {
+        return Ok(());
+    }
+    if instance.instance_type == InstanceType::Enterprise
+        && !instance.metadata.contains_key("database_port")
+    {
+        bail!("post-start needs a database, but none was created (see create-db)");
+    }
+
+    warm::wait_for_servers(instance).await?;
+    let mut primaries = client::primaries(instance).await?;
+
+    for (i, step) in steps.iter().enumerate() {
+        let (cmd, everywhere) = command(step).await?;
+        let reply = execute(&mut primaries, &cmd, everywhere)
+            .await
+            .with_context(|| format!("post-start step {} failed: {}", i + 1, step))?;
+        if verbose {
+            println!(
+                "    {} {} {}",
+                "post-start:".cyan(),
+                step,
+                format!("-> {}", script::format_value(&reply, 0)).dimmed()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// The Redis command for a step, and whether it must run on every master
+async fn command(step: &Step) -> Result<(redis::Cmd, bool)> {
+    match step {
+        Step::Command(line) => {
+            let words = split(line)?;
+            let everywhere = NODE_LOCAL.contains(&words[0].to_ascii_uppercase().as_str());
+            let mut cmd = redis::cmd(&words[0]);
+            cmd.arg(&words[1..]);
+            Ok((cmd, everywhere))
+        }
+        Step::Script { script, keys, args } => {
+            let source = tokio::fs::read_to_string(script)
+                .await
+                .with_context(|| format!("Failed to read script: {}", script.display()))?;
+            let mut cmd = redis::cmd("EVAL");
+            cmd.arg(source).arg(keys.len()).arg(keys).arg(args);
+            Ok((cmd, false))
+        }
+    }
+}
+
+async fn execute(
+    primaries: &mut [(Endpoint, MultiplexedConnection)],
+    cmd: &redis::Cmd,
+    everywhere: bool,
+) -> Result<Value> {
+    let mut reply = None;
+    let mut moved = None;
+    for (endpoint, con) in primaries.iter_mut() {
+        match cmd.query_async::<Value>(con).await {
+            Ok(value) if everywhere => reply = Some(value),
+            Ok(value) => return Ok(value),
+            // In a cluster, keep looking for the master that owns the keys
+            Err(e) if e.kind() == redis::ErrorKind::Moved => moved = Some(e),
+            Err(e) => bail!("{} on {}", e, endpoint),
+        }
+    }
+    match (reply, moved) {
+        (Some(value), _) => Ok(value),
+        (None, Some(e)) => bail!("{}", e),
+        (None, None) => bail!("no reachable endpoints"),
+    }
+}
+
+/// Split a command line into words, honouring single and double quotes
+///
+/// Inside double quotes a backslash escapes the next character, as in redis-cli.
+pub fn split(line: &str) -> Result<Vec<String>> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => words.extend(word.take()),
+            '"' | '\'' => {
+                let current = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some(end) if end == c => break,
+                        Some('\\') if c == '"' => match chars.next() {
+                            Some(escaped) => current.push(escaped),
+                            None => bail!("Unterminated quote in '{}'", line),
+                        },
+                        Some(other) => current.push(other),
+                        None => bail!("Unterminated quote in '{}'", line),
+                    }
+                }
+            }
+            c => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    words.extend(word);
+    if words.is_empty() {
+        bail!("Empty post-start command");
+    }
+    Ok(words)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split() {
+        assert_eq!(
+            split(r#"SET greeting "hello \"world\"""#).unwrap(),
+            ["SET", "greeting", r#"hello "world""#]
+        );
+        assert_eq!(
+            split("FT.CREATE idx ON HASH PREFIX 1 'user:' SCHEMA name TEXT").unwrap(),
+            [
+                "FT.CREATE",
+                "idx",
+                "ON",
+                "HASH",
+                "PREFIX",
+                "1",
+                "user:",
+                "SCHEMA",
+                "name",
+                "TEXT"
+            ]
+        );
+        assert_eq!(split("SET empty ''").unwrap(), ["SET", "empty", ""]);
+        assert!(split("SET key \"open").is_err());
+        assert!(split("   ").is_err());
+
+        let mut steps: Vec<Step> = serde_yaml::from_str(
+            "- ACL SETUSER app on >secret ~* +@all\n- script: seed.lua\n  keys: [counter]\n",
+        )
+        .unwrap();
+        resolve(&mut steps, Path::new("envs"));
+        assert_eq!(steps[1].to_string(), "EVAL <envs/seed.lua> 1 counter");
+    }
+}
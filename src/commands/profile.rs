@@ -0,0 +1,337 @@
+//! Named start flag bundles from settings.yaml, applied with `--profile`
+//!
+//! A profile maps long flag names to values. `--profile cache` inserts the
+//! profile's flags into the start command line before it is parsed, skipping
+//! any flag the command line already sets.
+
+use anyhow::{bail, Context, Result};
+use clap::CommandFactory;
+use colored::*;
+use serde_yaml::{Mapping, Value};
+use std::fs;
+
+use crate::cli::{
+    Cli, ClusterAction, Commands, EnterpriseAction, ProfileAction, RedisAction, SentinelAction,
+    StackAction,
+};
+use crate::settings::{get_settings_path, Profile, Settings};
+
+/// Instance types whose `start` accepts a profile
+const START_KINDS: [&str; 5] = ["basic", "stack", "cluster", "sentinel", "enterprise"];
+
+pub fn handle_action(action: ProfileAction) -> Result<()> {
+    match action {
+        ProfileAction::List => list(),
+        ProfileAction::Show { name } => show(&name),
+        ProfileAction::Add { name, flags } => add(&name, &flags),
+    }
+}
+
+fn list() -> Result<()> {
+    let settings = Settings::load()?;
+    if settings.profiles.is_empty() {
+        println!("{} No profiles defined", "Info:".blue());
+        println!(
+            "  Add one with: {}",
+            "redis-up profile add cache --memory 256m".green()
+        );
+        return Ok(());
+    }
+    println!("{}", "Profiles:".bold().cyan());
+    let width = settings.profiles.keys().map(String::len).max().unwrap_or(0);
+    for (name, profile) in &settings.profiles {
+        println!(
+            "  {}  {}",
+            format!("{:<width$}", name, width = width).bold(),
+            command_line(profile)?.dimmed()
+        );
+    }
+    Ok(())
+}
+
+fn show(name: &str) -> Result<()> {
+    let settings = Settings::load()?;
+    let profile = find(&settings, name)?;
+    println!("{} {}", "Profile:".bold().cyan(), name.bold());
+    print!("{}", serde_yaml::to_string(profile)?);
+    println!();
+    println!(
+        "  {} redis-up basic start {}",
+        "Adds:".dimmed(),
+        command_line(profile)?
+    );
+    Ok(())
+}
+
+fn add(name: &str, flags: &[String]) -> Result<()> {
+    let profile = parse_flags(flags)?;
+    let path = get_settings_path()?;
+
+    // Edit the document rather than `Settings` so unknown keys survive
+    let mut document: Value = if path.exists() {
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read settings file: {}", path.display()))?;
+        serde_yaml::from_str(&content)
+            .with_context(|| format!("Failed to parse settings file: {}", path.display()))?
+    } else {
+        Value::Null
+    };
+    if document.is_null() {
+        document = Value::Mapping(Mapping::new());
+    }
+    let root = document
+        .as_mapping_mut()
+        .context("settings.yaml is not a mapping")?;
+    let profiles = root
+        .entry(Value::from("profiles"))
+        .or_insert_with(|| Value::Mapping(Mapping::new()))
+        .as_mapping_mut()
+        .context("`profiles` in settings.yaml is not a mapping")?;
+    let replaced = profiles
+        .insert(Value::from(name), serde_yaml::to_value(&profile)?)
+        .is_some();
+
+    fs::write(&path, serde_yaml::to_string(&document)?)
+        .with_context(|| format!("Failed to write settings file: {}", path.display()))?;
+    println!(
+        "{} {} profile '{}': {}",
+        "Success:".green(),
+        if replaced { "Replaced" } else { "Added" },
+        name.bold(),
+        command_line(&profile)?
+    );
+    Ok(())
+}
+
+fn find<'a>(settings: &'a Settings, name: &str) -> Result<&'a Profile> {
+    settings
+        .profiles
+        .get(name)
+        .with_context(|| format!("Unknown profile '{}'; see `redis-up profile list`", name))
+}
+
+/// Insert the flags of `--profile NAME` after `<type> start`, leaving out flags
+/// the command line already sets
+pub fn expand(argv: Vec<String>) -> Result<Vec<String>> {
+    // Everything after `--` belongs to another command
+    let end = argv.iter().position(|a| a == "--").unwrap_or(argv.len());
+    let Some(name) = profile_name(&argv[..end]) else {
+        return Ok(argv);
+    };
+    let Some(at) = argv[..end].iter().position(|a| a == "start") else {
+        return Ok(argv);
+    };
+    let Some(kind) = at
+        .checked_sub(1)
+        .map(|i| argv[i].as_str())
+        .filter(|kind| START_KINDS.contains(kind))
+    else {
+        return Ok(argv);
+    };
+
+    let cli = Cli::command();
+    let Some(start) = cli
+        .find_subcommand(kind)
+        .and_then(|command| command.find_subcommand("start"))
+    else {
+        return Ok(argv);
+    };
+    let settings = Settings::load()?;
+    let profile = find(&settings, &name)?;
+
+    let given: Vec<String> = argv[at + 1..end]
+        .iter()
+        .filter_map(|token| given_flag(start, token))
+        .collect();
+    let mut inserted = Vec::new();
+    for (flag, value) in profile {
+        let flag = normalize(flag);
+        let Some(arg) = start.get_arguments().find(|a| a.get_long() == Some(&flag)) else {
+            bail!(
+                "Profile '{}' sets --{}, which `redis-up {} start` doesn't take",
+                name,
+                flag,
+                kind
+            );
+        };
+        if given.contains(&flag) {
+            continue;
+        }
+        inserted.extend(tokens(&flag, value, arg.get_action().takes_values())?);
+    }
+
+    let mut argv = argv;
+    argv.splice(at + 1..at + 1, inserted);
+    Ok(argv)
+}
+
+/// Whether `--profile` can apply to this command: a start, `warm`, or `deploy`,
+/// which selects a profile of the deployment file instead
+pub fn takes_profile(command: &Option<Commands>) -> bool {
+    matches!(
+        command,
+        Some(
+            Commands::Basic {
+                action: RedisAction::Start(_)
+            } | Commands::Stack {
+                action: StackAction::Start(_)
+            } | Commands::Cluster {
+                action: ClusterAction::Start(_)
+            } | Commands::Sentinel {
+                action: SentinelAction::Start(_)
+            } | Commands::Enterprise {
+                action: EnterpriseAction::Start(_)
+            } | Commands::Deploy { .. }
+                | Commands::Warm(_)
+        )
+    )
+}
+
+fn profile_name(args: &[String]) -> Option<String> {
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == "--profile" {
+            return iter.next().cloned();
+        }
+        if let Some(name) = arg.strip_prefix("--profile=") {
+            return Some(name.to_string());
+        }
+    }
+    None
+}
+
+/// Long name of the flag a command line token sets, resolving short flags
+fn given_flag(start: &clap::Command, token: &str) -> Option<String> {
+    if let Some(long) = token.strip_prefix("--") {
+        return Some(long.split('=').next().unwrap_or(long).to_string());
+    }
+    let short = token.strip_prefix('-')?.chars().next()?;
+    start
+        .get_arguments()
+        .find(|arg| arg.get_short() == Some(short))
+        .and_then(|arg| arg.get_long())
+        .map(String::from)
+}
+
+/// `--memory`, `memory` and `memory_limit`-style keys all name the long flag
+fn normalize(key: &str) -> String {
+    key.trim_start_matches('-').replace('_', "-")
+}
+
+/// Command line tokens for one flag setting; `false` and null leave the flag out
+pub fn tokens(flag: &str, value: &Value, takes_value: bool) -> Result<Vec<String>> {
+    let option = format!("--{}", flag);
+    Ok(match value {
+        Value::Null => Vec::new(),
+        Value::Bool(false) if !takes_value => Vec::new(),
+        Value::Bool(true) if !takes_value => vec![option],
+        Value::Sequence(items) => {
+            let mut tokens = Vec::new();
+            for item in items {
+                tokens.push(option.clone());
+                tokens.push(scalar(flag, item)?);
+            }
+            tokens
+        }
+        value => vec![option, scalar(flag, value)?],
+    })
+}
+
+fn scalar(flag: &str, value: &Value) -> Result<String> {
+    Ok(match value {
+        Value::String(s) => s.clone(),
+        Value::Number(n) => n.to_string(),
+        Value::Bool(b) => b.to_string(),
+        _ => bail!("Value for --{} must be a string, number or boolean", flag),
+    })
+}
+
+/// The profile as it would appear on a command line
+fn command_line(profile: &Profile) -> Result<String> {
+    let mut parts = Vec::new();
+    for (flag, value) in profile {
+        let takes_value = !matches!(value, Value::Bool(_) | Value::Null);
+        parts.extend(tokens(&normalize(flag), value, takes_value)?);
+    }
+    Ok(parts.join(" "))
+}
+
+/// `--memory 256m --persist --network a --network b` as a profile
+fn parse_flags(flags: &[String]) -> Result<Profile> {
+    let cli = Cli::command();
+    let known = |flag: &str| {
+        START_KINDS.iter().any(|kind| {
+            cli.find_subcommand(kind)
+                .and_then(|command| command.find_subcommand("start"))
+                .is_some_and(|start| start.get_arguments().any(|a| a.get_long() == Some(flag)))
+        })
+    };
+
+    let mut profile = Profile::new();
+    let mut iter = flags.iter().peekable();
+    while let Some(token) = iter.next() {
+        let Some(flag) = token.strip_prefix("--") else {
+            bail!("Expected a --flag, got '{}'", token);
+        };
+        let (flag, value) = match flag.split_once('=') {
+            Some((flag, value)) => (flag, Value::from(value)),
+            None => match iter.next_if(|next| !next.starts_with("--")) {
+                Some(value) => (flag, Value::from(value.as_str())),
+                None => (flag, Value::Bool(true)),
+            },
+        };
+        if flag == "profile" || !known(flag) {
+            bail!("--{} is not a start flag", flag);
+        }
+        // Repeated flags become a list
+        match profile.remove(flag) {
+            Some(Value::Sequence(mut items)) => {
+                items.push(value);
+                profile.insert(flag.to_string(), Value::Sequence(items));
+            }
+            Some(previous) => {
+                profile.insert(flag.to_string(), Value::Sequence(vec![previous, value]));
+            }
+            None => {
+                profile.insert(flag.to_string(), value);
+            }
+        }
+    }
+    Ok(profile)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn argv(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_profile_flags() {
+        let profile =
+            parse_flags(&argv("--memory 256m --persist --network a --network b")).unwrap();
+        assert_eq!(profile["memory"], "256m");
+        assert_eq!(profile["persist"], true);
+        assert_eq!(
+            command_line(&profile).unwrap(),
+            "--memory 256m --network a --network b --persist"
+        );
+        assert!(parse_flags(&argv("--no-such-flag")).is_err());
+
+        // Flags already on the command line, long or short, are left alone
+        let start = Cli::command();
+        let start = start
+            .find_subcommand("basic")
+            .unwrap()
+            .find_subcommand("start")
+            .unwrap();
+        assert_eq!(given_flag(start, "-p").as_deref(), Some("port"));
+        assert_eq!(given_flag(start, "--memory=1g").as_deref(), Some("memory"));
+        assert_eq!(
+            tokens("persist", &Value::Bool(false), false).unwrap(),
+            Vec::<String>::new()
+        );
+    }
+}
@@ -0,0 +1,179 @@
+//! Progress reporting for long-running startups
+//!
+//! Progress goes to stderr so stdout keeps the usual connection summary: an
+//! indicatif spinner on a terminal, one line per step otherwise, or JSON
+//! lines with `--output json`.
+
+use colored::*;
+use indicatif::{ProgressBar, ProgressStyle};
+use serde::Serialize;
+use std::io::IsTerminal;
+use std::time::{Duration, Instant};
+
+use crate::cli::OutputFormat;
+
+/// A step in starting an instance
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ProgressEvent {
+    PullingImage {
+        image: String,
+    },
+    CreatingNode {
+        node: usize,
+        total: usize,
+    },
+    WaitingForCluster,
+    BootstrappingEnterprise {
+        cluster: String,
+    },
+    WaitingForEnterprise {
+        cluster: String,
+    },
+    CreatingDatabase {
+        database: String,
+    },
+    RetryingDatabase {
+        database: String,
+        attempt: u32,
+        attempts: u32,
+        reason: String,
+    },
+    Ready {
+        name: String,
+    },
+}
+
+impl std::fmt::Display for ProgressEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProgressEvent::PullingImage { image } => write!(f, "Pulling image {}", image),
+            ProgressEvent::CreatingNode { node, total } => {
+                write!(f, "Creating node {}/{}", node, total)
+            }
+            ProgressEvent::WaitingForCluster => write!(f, "Waiting for cluster state ok"),
+            ProgressEvent::BootstrappingEnterprise { cluster } => {
+                write!(f, "Bootstrapping Enterprise cluster {}", cluster)
+            }
+            ProgressEvent::WaitingForEnterprise { cluster } => {
+                write!(f, "Waiting for Enterprise cluster {} to be ready", cluster)
+            }
+            ProgressEvent::CreatingDatabase { database } => {
+                write!(f, "Creating database {}", database)
+            }
+            ProgressEvent::RetryingDatabase {
+                database,
+                attempt,
+                attempts,
+                reason,
+            } => write!(
+                f,
+                "Database {} not created yet ({}), attempt {}/{}",
+                database, reason, attempt, attempts
+            ),
+            ProgressEvent::Ready { name } => write!(f, "{} is ready", name),
+        }
+    }
+}
+
+/// JSON line written for each event
+#[derive(Serialize)]
+struct ProgressLine<'a> {
+    #[serde(flatten)]
+    event: &'a ProgressEvent,
+    elapsed_ms: u128,
+}
+
+/// Renders progress events for one startup
+pub struct Progress {
+    format: OutputFormat,
+    started: Instant,
+    spinner: Option<ProgressBar>,
+}
+
+impl Progress {
+    /// Start reporting; the spinner is only used on an interactive, non-verbose terminal
+    pub fn new(format: OutputFormat, verbose: bool) -> Self {
+        let spinner = (format == OutputFormat::Text && !verbose && std::io::stderr().is_terminal())
+            .then(|| {
+                let spinner = ProgressBar::new_spinner();
+                spinner.set_style(
+                    ProgressStyle::with_template("{spinner:.cyan} {msg} ({elapsed})")
+                        .expect("valid spinner template"),
+                );
+                spinner
+            });
+
+        Self {
+            format,
+            started: Instant::now(),
+            spinner,
+        }
+    }
+
+    pub fn emit(&self, event: ProgressEvent) {
+        match (self.format, &self.spinner) {
+            (OutputFormat::Json, _) => {
+                let line = ProgressLine {
+                    event: &event,
+                    elapsed_ms: self.started.elapsed().as_millis(),
+                };
+                if let Ok(json) = serde_json::to_string(&line) {
+                    eprintln!("{}", json);
+                }
+            }
+            (OutputFormat::Text, Some(spinner)) => {
+                // Ticking starts with the first message, so nothing is drawn before it
+                if spinner.message().is_empty() {
+                    spinner.enable_steady_tick(Duration::from_millis(100));
+                }
+                spinner.set_message(event.to_string());
+            }
+            (OutputFormat::Text, None) => {
+                eprintln!(
+                    "{} {} ({}s)",
+                    "Progress:".cyan(),
+                    event,
+                    self.started.elapsed().as_secs()
+                );
+            }
+        }
+    }
+
+    /// Stop the spinner and clear its line
+    pub fn finish(mut self) {
+        self.stop_spinner();
+    }
+
+    fn stop_spinner(&mut self) {
+        if let Some(spinner) = self.spinner.take() {
+            spinner.finish_and_clear();
+        }
+    }
+}
+
+impl Drop for Progress {
+    // Early returns on errors must not leave a half-drawn spinner behind
+    fn drop(&mut self) {
+        self.stop_spinner();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_event_json() {
+        let event = ProgressEvent::CreatingNode { node: 2, total: 6 };
+        let line = ProgressLine {
+            event: &event,
+            elapsed_ms: 1500,
+        };
+        assert_eq!(
+            serde_json::to_string(&line).unwrap(),
+            r#"{"event":"creating_node","node":2,"total":6,"elapsed_ms":1500}"#
+        );
+        assert_eq!(event.to_string(), "Creating node 2/6");
+    }
+}
@@ -0,0 +1,156 @@
+//! Noticing instances that stopped behind redis-up's back
+//!
+//! After a reboot or a Docker daemon restart the registry still lists every
+//! instance while their containers sit exited. Before a command that works
+//! with instances, their containers are inspected (which also refreshes the
+//! recorded start and last-seen times), and instances whose containers all
+//! exist but are not all running are handled as the `auto-revive` setting
+//! says: `warn` mentions them, `ask` offers to start them, `always` starts
+//! them and `off` skips the check. Instances `warm --create` stopped on
+//! purpose are left alone.
+//!
+//! Everything is printed to stderr so output such as `eval $(redis-up env ...)`
+//! stays clean, and nothing here fails the command.
+
+use colored::*;
+use std::io::{self, IsTerminal, Write};
+
+use crate::cli::{
+    ClusterAction, Commands, EnterpriseAction, RedisAction, SentinelAction, StackAction,
+};
+use crate::commands::{revive, uptime, warm};
+use crate::config::{Config, InstanceInfo};
+use crate::settings::{AutoRevive, Settings};
+
+/// Check for stopped instances before running `command`
+pub async fn reconcile(command: &Commands, verbose: bool) {
+    let mode = Settings::load().map(|s| s.auto_revive).unwrap_or_default();
+    if mode == AutoRevive::Off || !applies(command) {
+        return;
+    }
+    let Ok(mut config) = Config::load() else {
+        return;
+    };
+    let names: Vec<String> = config.instances.keys().cloned().collect();
+    // Without Docker there is nothing to compare against
+    let Some(states) = uptime::refresh(&mut config, &names).await else {
+        return;
+    };
+
+    let mut stopped: Vec<(&InstanceInfo, Vec<&String>)> = config
+        .instances
+        .values()
+        .filter(|instance| !instance.metadata.contains_key(warm::WARMED))
+        .filter_map(|instance| {
+            let (stopped, missing) = revive::stopped_and_missing(&instance.containers, &states);
+            (missing.is_empty() && !stopped.is_empty()).then_some((instance, stopped))
+        })
+        .collect();
+    if stopped.is_empty() {
+        return;
+    }
+    stopped.sort_by(|a, b| a.0.name.cmp(&b.0.name));
+    let listed = stopped
+        .iter()
+        .map(|(instance, _)| instance.name.as_str())
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let start = match mode {
+        AutoRevive::Always => true,
+        AutoRevive::Ask if io::stdin().is_terminal() && io::stderr().is_terminal() => {
+            eprint!(
+                "{} {} instance(s) are stopped ({}). Start them now? [y/N] ",
+                "Notice:".yellow(),
+                stopped.len(),
+                listed
+            );
+            io::stderr().flush().ok();
+            let mut input = String::new();
+            io::stdin().read_line(&mut input).ok();
+            matches!(input.trim().to_lowercase().as_str(), "y" | "yes")
+        }
+        _ => {
+            eprintln!(
+                "{} {} instance(s) are stopped, e.g. after a reboot ({}); `redis-up revive` starts them",
+                "Notice:".yellow(),
+                stopped.len(),
+                listed
+            );
+            false
+        }
+    };
+    if !start {
+        return;
+    }
+
+    for (instance, containers) in &stopped {
+        match revive::revive(instance, containers).await {
+            Ok(()) => eprintln!("{} Revived {}", "Success:".green(), instance.name.bold()),
+            Err(e) if verbose => eprintln!(
+                "{} Failed to revive {}: {:#}",
+                "Warning:".yellow(),
+                instance.name.bold(),
+                e
+            ),
+            Err(e) => eprintln!(
+                "{} Failed to revive {}: {}",
+                "Warning:".yellow(),
+                instance.name.bold(),
+                e
+            ),
+        }
+    }
+}
+
+/// Commands that work with registered instances without stopping them
+fn applies(command: &Commands) -> bool {
+    !matches!(
+        command,
+        Commands::Basic {
+            action: RedisAction::Stop(_)
+        } | Commands::Stack {
+            action: StackAction::Stop(_)
+        } | Commands::Cluster {
+            action: ClusterAction::Stop(_)
+        } | Commands::Sentinel {
+            action: SentinelAction::Stop(_)
+        } | Commands::Enterprise {
+            action: EnterpriseAction::Stop(_)
+        } | Commands::Revive(_)
+            | Commands::Cleanup(_)
+            | Commands::RestoreState(_)
+            | Commands::Health(_)
+            | Commands::Chaos { .. }
+            | Commands::Warm(_)
+            | Commands::Ci { .. }
+            | Commands::Lock
+            | Commands::Unlock(_)
+            | Commands::Profile { .. }
+            | Commands::Config { .. }
+            | Commands::Doctor
+            | Commands::Images { .. }
+            | Commands::Init(_)
+            | Commands::Usage(_)
+            | Commands::ExitCodes { .. }
+            | Commands::Examples { .. }
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    use crate::cli::Cli;
+
+    #[test]
+    fn test_applies() {
+        let command = |args: &[&str]| Cli::parse_from(args).command.unwrap();
+        assert!(applies(&command(&["redis-up", "list"])));
+        assert!(applies(&command(&["redis-up", "basic", "info", "cache"])));
+        assert!(!applies(&command(&["redis-up", "basic", "stop", "cache"])));
+        assert!(!applies(&command(&["redis-up", "revive"])));
+        assert!(!applies(&command(&["redis-up", "health", "cache"])));
+    }
+}
@@ -0,0 +1,458 @@
+//! Lightweight stats recorder for reproducing leaks and slowdowns
+//!
+//! Samples are written as one JSON object per line and per container, so a
+//! recording can be tailed, grepped, or summarized later with `--report`.
+
+use anyhow::{Context, Result};
+use colored::*;
+use docker_wrapper::StatsCommand;
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::cli::RecordArgs;
+use crate::commands::server;
+use crate::config::{Config, InstanceInfo, InstanceType};
+
+pub async fn handle_record(args: RecordArgs, verbose: bool) -> Result<()> {
+    if let Some(ref report) = args.report {
+        return print_report(report);
+    }
+
+    let name = args
+        .name
+        .clone()
+        .context("Instance name is required unless --report is given")?;
+    let config = Config::load()?;
+    let instance = config
+        .get_instance(&name)
+        .with_context(|| format!("Instance '{}' not found", name))?;
+
+    let containers = redis_containers(instance)?;
+    let password = instance.connection_info.password.clone();
+    record(&name, &containers, password.as_deref(), &args, verbose).await
+}
+
+/// Containers running redis-server for an instance
+fn redis_containers(instance: &InstanceInfo) -> Result<Vec<String>> {
+    match instance.instance_type {
+        InstanceType::Basic | InstanceType::Stack => Ok(vec![instance.name.clone()]),
+        InstanceType::Cluster => Ok(instance
+            .containers
+            .iter()
+            .filter(|c| !c.ends_with("-insight"))
+            .cloned()
+            .collect()),
+        InstanceType::Sentinel => {
+            let masters = instance
+                .metadata
+                .get("masters")
+                .and_then(|v| v.as_u64())
+                .unwrap_or(1);
+            Ok((1..=masters)
+                .map(|i| format!("{}-master-{}", instance.name, i))
+                .collect())
+        }
+        InstanceType::Enterprise => {
+            anyhow::bail!("Recording is not supported for Redis Enterprise instances")
+        }
+    }
+}
+
+async fn record(
+    name: &str,
+    containers: &[String],
+    password: Option<&str>,
+    args: &RecordArgs,
+    verbose: bool,
+) -> Result<()> {
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&args.output)
+        .with_context(|| format!("Failed to open {}", args.output.display()))?;
+
+    println!(
+        "{} Sampling {} every {} into {} (Ctrl+C to stop)",
+        "Recording:".bold().cyan(),
+        name.bold(),
+        humantime::format_duration(args.interval),
+        args.output.display()
+    );
+
+    let deadline = args.duration.map(|d| tokio::time::Instant::now() + d);
+    let mut ticker = tokio::time::interval(args.interval);
+    let mut samples = 0;
+
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {}
+            _ = tokio::signal::ctrl_c() => break,
+        }
+        if deadline.is_some_and(|d| tokio::time::Instant::now() >= d) {
+            break;
+        }
+
+        for container in containers {
+            match sample(name, container, password, &args.sections).await {
+                Ok(line) => {
+                    writeln!(file, "{}", line)
+                        .with_context(|| format!("Failed to write {}", args.output.display()))?;
+                    samples += 1;
+                }
+                Err(e) => println!(
+                    "{} Failed to sample {}: {}",
+                    "Warning:".yellow(),
+                    container,
+                    e
+                ),
+            }
+        }
+
+        if verbose {
+            println!("  {} {} sample(s) written", "Sampled".green(), samples);
+        }
+    }
+
+    println!();
+    println!(
+        "{} Wrote {} sample(s) to {}",
+        "Success:".green(),
+        samples,
+        args.output.display()
+    );
+    println!(
+        "  Summarize with: {}",
+        format!("redis-up record --report {}", args.output.display()).cyan()
+    );
+
+    Ok(())
+}
+
+/// Take one sample of INFO and docker stats for a container
+async fn sample(
+    name: &str,
+    container: &str,
+    password: Option<&str>,
+    sections: &[String],
+) -> Result<Value> {
+    let mut info_args = vec!["INFO"];
+    info_args.extend(sections.iter().map(|s| s.as_str()));
+    let info = server::redis_cli(container, password, &info_args).await?;
+
+    let docker = StatsCommand::new()
+        .container(container)
+        .no_stream()
+        .format("json")
+        .run()
+        .await
+        .ok()
+        .and_then(|result| result.parsed_stats.into_iter().next())
+        .map(|stats| {
+            json!({
+                "cpu_percent": stats.cpu_percentage(),
+                "memory_usage": stats.memory_usage,
+                "memory_percent": stats.memory_percentage(),
+                "network_io": stats.network_io,
+                "block_io": stats.block_io,
+                "pids": stats.pid_count(),
+            })
+        });
+
+    Ok(json!({
+        "timestamp": chrono::Utc::now().to_rfc3339(),
+        "instance": name,
+        "container": container,
+        "info": parse_info(&info),
+        "docker": docker,
+    }))
+}
+
+/// Parse INFO output into a flat JSON object, keeping numbers numeric
+fn parse_info(info: &str) -> Map<String, Value> {
+    let mut map = Map::new();
+    for line in info.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some((key, value)) = line.split_once(':') {
+            let parsed = if let Ok(n) = value.parse::<i64>() {
+                json!(n)
+            } else if let Ok(f) = value.parse::<f64>() {
+                json!(f)
+            } else {
+                json!(value)
+            };
+            map.insert(key.to_string(), parsed);
+        }
+    }
+    map
+}
+
+/// Summary of one container's samples
+#[derive(Debug, Default)]
+struct Summary {
+    samples: usize,
+    first: Option<chrono::DateTime<chrono::FixedOffset>>,
+    last: Option<chrono::DateTime<chrono::FixedOffset>>,
+    ops_total: f64,
+    ops_peak: f64,
+    commands_start: Option<f64>,
+    commands_end: Option<f64>,
+    memory_start: Option<f64>,
+    memory_end: Option<f64>,
+    memory_peak: f64,
+    evicted_start: Option<f64>,
+    evicted_end: Option<f64>,
+    expired_start: Option<f64>,
+    expired_end: Option<f64>,
+    cpu_total: f64,
+    cpu_samples: usize,
+    cpu_peak: f64,
+}
+
+impl Summary {
+    fn elapsed_secs(&self) -> f64 {
+        match (self.first, self.last) {
+            (Some(first), Some(last)) => (last - first).num_milliseconds() as f64 / 1000.0,
+            _ => 0.0,
+        }
+    }
+
+    fn commands_per_sec(&self) -> Option<f64> {
+        let elapsed = self.elapsed_secs();
+        match (self.commands_start, self.commands_end) {
+            (Some(start), Some(end)) if elapsed > 0.0 => Some((end - start) / elapsed),
+            _ => None,
+        }
+    }
+
+    fn memory_growth(&self) -> Option<f64> {
+        Some(self.memory_end? - self.memory_start?)
+    }
+
+    fn evictions(&self) -> Option<f64> {
+        Some(self.evicted_end? - self.evicted_start?)
+    }
+
+    fn expirations(&self) -> Option<f64> {
+        Some(self.expired_end? - self.expired_start?)
+    }
+}
+
+/// Summarize recorded samples per container
+fn summarize(lines: &[Value]) -> BTreeMap<String, Summary> {
+    let mut summaries: BTreeMap<String, Summary> = BTreeMap::new();
+
+    for line in lines {
+        let container = line["container"].as_str().unwrap_or("unknown").to_string();
+        let summary = summaries.entry(container).or_default();
+        summary.samples += 1;
+
+        if let Some(ts) = line["timestamp"]
+            .as_str()
+            .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+        {
+            summary.first.get_or_insert(ts);
+            summary.last = Some(ts);
+        }
+
+        let info = &line["info"];
+        if let Some(ops) = info["instantaneous_ops_per_sec"].as_f64() {
+            summary.ops_total += ops;
+            summary.ops_peak = summary.ops_peak.max(ops);
+        }
+        if let Some(commands) = info["total_commands_processed"].as_f64() {
+            summary.commands_start.get_or_insert(commands);
+            summary.commands_end = Some(commands);
+        }
+        if let Some(memory) = info["used_memory"].as_f64() {
+            summary.memory_start.get_or_insert(memory);
+            summary.memory_end = Some(memory);
+            summary.memory_peak = summary.memory_peak.max(memory);
+        }
+        if let Some(evicted) = info["evicted_keys"].as_f64() {
+            summary.evicted_start.get_or_insert(evicted);
+            summary.evicted_end = Some(evicted);
+        }
+        if let Some(expired) = info["expired_keys"].as_f64() {
+            summary.expired_start.get_or_insert(expired);
+            summary.expired_end = Some(expired);
+        }
+        if let Some(cpu) = line["docker"]["cpu_percent"].as_f64() {
+            summary.cpu_total += cpu;
+            summary.cpu_samples += 1;
+            summary.cpu_peak = summary.cpu_peak.max(cpu);
+        }
+    }
+
+    summaries
+}
+
+fn print_report(path: &Path) -> Result<()> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read recording: {}", path.display()))?;
+
+    let mut lines = Vec::new();
+    for (i, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let value: Value = serde_json::from_str(line)
+            .with_context(|| format!("Invalid JSON on line {} of {}", i + 1, path.display()))?;
+        lines.push(value);
+    }
+
+    if lines.is_empty() {
+        anyhow::bail!("No samples found in {}", path.display());
+    }
+
+    println!(
+        "{} {}",
+        "Report:".bold().cyan(),
+        path.display().to_string().bold()
+    );
+
+    for (container, summary) in summarize(&lines) {
+        println!();
+        println!("{}", container.bold().green());
+        println!(
+            "  {}: {} over {}",
+            "Samples".bold(),
+            summary.samples,
+            humantime::format_duration(Duration::from_secs(summary.elapsed_secs() as u64))
+        );
+
+        if summary.samples > 0 {
+            let avg_ops = summary.ops_total / summary.samples as f64;
+            println!(
+                "  {}: {:.0} avg, {:.0} peak",
+                "Ops/sec".bold(),
+                avg_ops,
+                summary.ops_peak
+            );
+        }
+        if let Some(rate) = summary.commands_per_sec() {
+            println!("  {}: {:.1}/sec", "Commands".bold(), rate);
+        }
+        if let (Some(start), Some(end)) = (summary.memory_start, summary.memory_end) {
+            let growth = summary.memory_growth().unwrap_or_default();
+            let growth_text = if growth >= 0.0 {
+                format!("+{}", format_bytes(growth))
+            } else {
+                format_bytes(growth)
+            };
+            println!(
+                "  {}: {} -> {} (peak {}, {})",
+                "Memory".bold(),
+                format_bytes(start),
+                format_bytes(end),
+                format_bytes(summary.memory_peak),
+                if growth > 0.0 {
+                    growth_text.yellow()
+                } else {
+                    growth_text.normal()
+                }
+            );
+            let minutes = summary.elapsed_secs() / 60.0;
+            if minutes > 0.0 {
+                println!(
+                    "  {}: {}/min",
+                    "Memory Growth".bold(),
+                    format_bytes(growth / minutes)
+                );
+            }
+        }
+        if let Some(evictions) = summary.evictions() {
+            let text = format!("{:.0}", evictions);
+            println!(
+                "  {}: {}",
+                "Evictions".bold(),
+                if evictions > 0.0 {
+                    text.red()
+                } else {
+                    text.normal()
+                }
+            );
+        }
+        if let Some(expirations) = summary.expirations() {
+            println!("  {}: {:.0}", "Expired Keys".bold(), expirations);
+        }
+        if summary.cpu_samples > 0 {
+            println!(
+                "  {}: {:.1}% avg, {:.1}% peak",
+                "Container CPU".bold(),
+                summary.cpu_total / summary.cpu_samples as f64,
+                summary.cpu_peak
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Format a byte count for display
+pub fn format_bytes(bytes: f64) -> String {
+    let sign = if bytes < 0.0 { "-" } else { "" };
+    let bytes = bytes.abs();
+    let units = ["B", "KB", "MB", "GB"];
+    let mut value = bytes;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < units.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{}{:.0}{}", sign, value, units[unit])
+    } else {
+        format!("{}{:.1}{}", sign, value, units[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_info() {
+        let info = "# Memory\r\nused_memory:1024\r\nmem_fragmentation_ratio:1.5\r\n# Keyspace\r\ndb0:keys=1,expires=0\r\n";
+        let map = parse_info(info);
+        assert_eq!(map["used_memory"], json!(1024));
+        assert_eq!(map["mem_fragmentation_ratio"], json!(1.5));
+        assert_eq!(map["db0"], json!("keys=1,expires=0"));
+        assert!(!map.contains_key("# Memory"));
+    }
+
+    #[test]
+    fn test_summarize() {
+        let lines = vec![
+            json!({
+                "timestamp": "2024-01-01T00:00:00Z",
+                "container": "redis-basic-1",
+                "info": {"instantaneous_ops_per_sec": 100, "total_commands_processed": 1000,
+                         "used_memory": 1000, "evicted_keys": 0},
+                "docker": {"cpu_percent": 10.0}
+            }),
+            json!({
+                "timestamp": "2024-01-01T00:01:00Z",
+                "container": "redis-basic-1",
+                "info": {"instantaneous_ops_per_sec": 300, "total_commands_processed": 7000,
+                         "used_memory": 4000, "evicted_keys": 5},
+                "docker": {"cpu_percent": 30.0}
+            }),
+        ];
+
+        let summaries = summarize(&lines);
+        let summary = &summaries["redis-basic-1"];
+        assert_eq!(summary.samples, 2);
+        assert_eq!(summary.elapsed_secs(), 60.0);
+        assert_eq!(summary.commands_per_sec(), Some(100.0));
+        assert_eq!(summary.memory_growth(), Some(3000.0));
+        assert_eq!(summary.evictions(), Some(5.0));
+        assert_eq!(summary.ops_peak, 300.0);
+        assert_eq!(summary.cpu_peak, 30.0);
+    }
+}
@@ -0,0 +1,184 @@
+//! Replication offsets and lag for sentinel and cluster instances
+
+use anyhow::{Context, Result};
+use colored::*;
+
+use crate::cli::ReplicationArgs;
+use crate::commands::client::{self, Endpoint};
+use crate::config::{Config, InstanceInfo, InstanceType};
+
+/// A replica as reported by its master's INFO replication
+#[derive(Debug, Clone, PartialEq)]
+struct Replica {
+    address: String,
+    state: String,
+    offset: u64,
+    lag_secs: u64,
+}
+
+/// A master's replication state
+#[derive(Debug, Clone, PartialEq)]
+struct MasterReplication {
+    offset: u64,
+    replicas: Vec<Replica>,
+}
+
+pub async fn handle_replication(args: ReplicationArgs, verbose: bool) -> Result<()> {
+    let config = Config::load()?;
+    let instance = config
+        .get_instance(&args.name)
+        .with_context(|| format!("Instance '{}' not found", args.name))?;
+
+    let expected_replicas = match instance.instance_type {
+        InstanceType::Cluster => instance
+            .metadata
+            .get("replicas")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0) as usize,
+        InstanceType::Sentinel => 0,
+        _ => anyhow::bail!(
+            "Replication checks apply to sentinel and cluster instances, '{}' is {}",
+            args.name,
+            instance.instance_type
+        ),
+    };
+
+    println!("{} {}", "Replication:".bold().cyan(), args.name.bold());
+
+    let mut problems = 0;
+    for endpoint in masters(instance).await? {
+        let mut con = client::connect(&endpoint).await?;
+        let info: String = redis::cmd("INFO")
+            .arg("replication")
+            .query_async(&mut con)
+            .await
+            .with_context(|| format!("INFO failed on {}", endpoint))?;
+        let master = parse_replication(&info);
+
+        println!();
+        println!(
+            "  {} {}  offset {}",
+            "master".bold(),
+            endpoint,
+            master.offset
+        );
+
+        for replica in &master.replicas {
+            let behind = master.offset.saturating_sub(replica.offset);
+            let status = if replica.state != "online" {
+                problems += 1;
+                replica.state.to_uppercase().red()
+            } else if behind > args.max_lag_bytes || replica.lag_secs > args.max_lag_secs {
+                problems += 1;
+                "LAGGING".yellow()
+            } else {
+                "ok".green()
+            };
+            println!(
+                "    replica {}  offset {}  behind {} bytes  last ack {}s ago  {}",
+                replica.address, replica.offset, behind, replica.lag_secs, status
+            );
+        }
+
+        if master.replicas.len() < expected_replicas {
+            let missing = expected_replicas - master.replicas.len();
+            problems += missing;
+            println!(
+                "    {} {} of {} replica(s) not connected",
+                "DISCONNECTED".red(),
+                missing,
+                expected_replicas
+            );
+        } else if master.replicas.is_empty() && verbose {
+            println!("    {}", "no replicas".dimmed());
+        }
+    }
+
+    println!();
+    if problems > 0 {
+        println!(
+            "{} {} replica(s) lagging or disconnected",
+            "Warning:".yellow(),
+            problems
+        );
+    } else {
+        println!("{} All replicas in sync", "Success:".green());
+    }
+    Ok(())
+}
+
+/// Host-side endpoints of every master in the instance
+pub async fn masters(instance: &InstanceInfo) -> Result<Vec<Endpoint>> {
+    if instance.instance_type == InstanceType::Cluster {
+        return Ok(client::primaries(instance)
+            .await?
+            .into_iter()
+            .map(|(endpoint, _)| endpoint)
+            .collect());
+    }
+
+    // Sentinel masters are published on consecutive ports from the base
+    let count = instance
+        .metadata
+        .get("masters")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(1) as u16;
+    let info = &instance.connection_info;
+    Ok((0..count)
+        .map(|i| Endpoint {
+            host: info.host.clone(),
+            port: info.port + i,
+            password: info.password.clone(),
+        })
+        .collect())
+}
+
+/// Parse the `# Replication` section of INFO on a master
+fn parse_replication(info: &str) -> MasterReplication {
+    let mut offset = 0;
+    let mut replicas = Vec::new();
+
+    for line in info.lines() {
+        let Some((key, value)) = line.trim().split_once(':') else {
+            continue;
+        };
+        if key == "master_repl_offset" {
+            offset = value.parse().unwrap_or(0);
+        } else if key.starts_with("slave") && key[5..].parse::<u32>().is_ok() {
+            let field = |name: &str| {
+                value
+                    .split(',')
+                    .find_map(|kv| kv.strip_prefix(name)?.strip_prefix('='))
+                    .unwrap_or_default()
+            };
+            replicas.push(Replica {
+                address: format!("{}:{}", field("ip"), field("port")),
+                state: field("state").to_string(),
+                offset: field("offset").parse().unwrap_or(0),
+                lag_secs: field("lag").parse().unwrap_or(0),
+            });
+        }
+    }
+
+    MasterReplication { offset, replicas }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_replication() {
+        let info = "# Replication\r\nrole:master\r\nconnected_slaves:2\r\n\
+                    slave0:ip=172.18.0.3,port=6379,state=online,offset=1500,lag=0\r\n\
+                    slave1:ip=172.18.0.4,port=6379,state=wait_bgsave,offset=0,lag=3\r\n\
+                    master_failover_state:no-failover\r\nmaster_repl_offset:1500\r\n";
+
+        let master = parse_replication(info);
+        assert_eq!(master.offset, 1500);
+        assert_eq!(master.replicas.len(), 2);
+        assert_eq!(master.replicas[0].address, "172.18.0.3:6379");
+        assert_eq!(master.replicas[1].state, "wait_bgsave");
+        assert_eq!(master.replicas[1].lag_secs, 3);
+    }
+}
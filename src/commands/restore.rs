@@ -0,0 +1,400 @@
+//! Snapshots taken before cleanup and `redis-up restore-state`
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use colored::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::cli::{Cli, Commands, RestoreStateArgs};
+use crate::commands::{basic, cluster, config_set, enterprise, network, sentinel, stack};
+use crate::config::{ensure_config_dir, get_state_dir, Config, InstanceInfo, InstanceType};
+use crate::error::Failure;
+
+/// Snapshot file name
+const SNAPSHOT_FILE: &str = "last-cleanup.json";
+
+/// Instances removed by the most recent cleanup
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub taken_at: String,
+    pub instances: Vec<InstanceInfo>,
+}
+
+fn snapshot_path() -> Result<PathBuf> {
+    Ok(get_state_dir()?.join(SNAPSHOT_FILE))
+}
+
+/// Record instances that are about to be cleaned up, replacing any earlier snapshot
+pub fn save_snapshot(instances: &[InstanceInfo]) -> Result<()> {
+    ensure_config_dir()?;
+    let path = snapshot_path()?;
+    let mut snapshot = Snapshot {
+        taken_at: chrono::Utc::now().to_rfc3339(),
+        instances: instances.to_vec(),
+    };
+    crate::secrets::seal(snapshot.instances.iter_mut())?;
+    let content = serde_json::to_string_pretty(&snapshot)?;
+    fs::write(&path, content)
+        .with_context(|| format!("Failed to write snapshot: {}", path.display()))
+}
+
+fn load_snapshot() -> Result<Option<Snapshot>> {
+    let path = snapshot_path()?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read snapshot: {}", path.display()))?;
+    let mut snapshot: Snapshot = serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse snapshot: {}", path.display()))?;
+    crate::secrets::open(snapshot.instances.iter_mut())?;
+    Ok(Some(snapshot))
+}
+
+pub async fn handle_restore(args: RestoreStateArgs, verbose: bool) -> Result<()> {
+    let Some(mut snapshot) = load_snapshot()? else {
+        println!("{} No cleanup snapshot to restore", "Info:".blue());
+        return Ok(());
+    };
+
+    let selected: Vec<InstanceInfo> = snapshot
+        .instances
+        .iter()
+        .filter(|i| args.names.is_empty() || args.names.contains(&i.name))
+        .cloned()
+        .collect();
+    if selected.is_empty() {
+        anyhow::bail!("None of the requested instances are in the cleanup snapshot");
+    }
+
+    println!(
+        "{} {} instance(s) from the cleanup at {}",
+        "Restoring:".bold().cyan(),
+        selected.len(),
+        snapshot.taken_at
+    );
+
+    let mut failed = 0;
+    for instance in &selected {
+        if Config::load()?.get_instance(&instance.name).is_some() {
+            println!(
+                "  {} {} already exists, skipping",
+                "Warning:".yellow(),
+                instance.name.bold()
+            );
+            continue;
+        }
+
+        let argv = start_argv(instance);
+        if verbose {
+            println!("  {} redis-up {}", "Running:".cyan(), argv[1..].join(" "));
+        }
+
+        match run_start(&argv, verbose).await {
+            Ok(()) => {
+                carry_over(instance, verbose).await?;
+                snapshot.instances.retain(|i| i.name != instance.name);
+            }
+            Err(e) => {
+                failed += 1;
+                println!(
+                    "  {} Failed to restore {}: {:#}",
+                    "Error:".red(),
+                    instance.name.bold(),
+                    e
+                );
+            }
+        }
+    }
+
+    // Keep whatever could not be restored for another attempt
+    if snapshot.instances.is_empty() {
+        fs::remove_file(snapshot_path()?).ok();
+    } else {
+        save_snapshot(&snapshot.instances)?;
+    }
+
+    if failed == selected.len() {
+        anyhow::bail!("No instances could be restored");
+    } else if failed > 0 {
+        return Err(Failure::Partial(format!(
+            "{} of {} instance(s) could not be restored",
+            failed,
+            selected.len()
+        ))
+        .into());
+    }
+    Ok(())
+}
+
+/// Metadata with no start flag, copied onto the re-created instance
+const CARRIED_OVER: [&str; 2] = ["notes", "config_overrides"];
+
+/// Copy notes and tracked config overrides over, applying the overrides again
+async fn carry_over(previous: &InstanceInfo, verbose: bool) -> Result<()> {
+    let mut config = Config::load()?;
+    let Some(instance) = config.get_instance_mut(&previous.name) else {
+        return Ok(());
+    };
+    let mut changed = false;
+    for key in CARRIED_OVER {
+        if let Some(value) = previous.metadata.get(key) {
+            instance.metadata.insert(key.to_string(), value.clone());
+            changed = true;
+        }
+    }
+    if !changed {
+        return Ok(());
+    }
+    let instance = instance.clone();
+    config.save()?;
+
+    let overrides = config_set::tracked(&instance);
+    if !overrides.is_empty() {
+        if let Err(e) = config_set::apply(&instance, &overrides, verbose).await {
+            println!(
+                "  {} Could not re-apply config overrides on {}: {:#}",
+                "Warning:".yellow(),
+                instance.name.bold(),
+                e
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Run a `redis-up <type> start ...` command line
+pub async fn run_start(argv: &[String], verbose: bool) -> Result<()> {
+    let argv = crate::commands::profile::expand(argv.to_vec())?;
+    let cli = Cli::try_parse_from(&argv)
+        .map_err(|e| anyhow::anyhow!("Invalid start arguments: {}", e))?;
+    match cli.command {
+        Some(Commands::Basic { action }) => basic::handle_action(action, verbose).await,
+        Some(Commands::Stack { action }) => stack::handle_action(action, verbose).await,
+        Some(Commands::Cluster { action }) => cluster::handle_action(action, verbose).await,
+        Some(Commands::Sentinel { action }) => sentinel::handle_action(action, verbose).await,
+        Some(Commands::Enterprise { action }) => enterprise::handle_action(action, verbose).await,
+        _ => anyhow::bail!("Not a start command: {}", argv.join(" ")),
+    }
+}
+
+/// Command line that starts an instance like the recorded one
+///
+/// Passwords are reused so existing clients keep working. Named data volumes
+/// survive cleanup, so `--persist` instances come back with their data.
+fn start_argv(instance: &InstanceInfo) -> Vec<String> {
+    let meta = &instance.metadata;
+    let text = |key: &str| meta.get(key).and_then(|v| v.as_str()).map(String::from);
+    let number = |key: &str| meta.get(key).and_then(|v| v.as_u64());
+    let flag = |key: &str| meta.get(key).and_then(|v| v.as_bool()).unwrap_or(false);
+
+    let mut argv = vec![
+        "redis-up".to_string(),
+        instance.instance_type.to_string(),
+        "start".to_string(),
+        "--name".to_string(),
+        instance.name.clone(),
+    ];
+    let mut push = |args: &[&str]| argv.extend(args.iter().map(|a| a.to_string()));
+
+    match instance.instance_type {
+        InstanceType::Basic | InstanceType::Stack => {
+            push(&["--port", &instance.connection_info.port.to_string()]);
+            if let Some(databases) = number("databases") {
+                push(&["--databases", &databases.to_string()]);
+            }
+            if let Some(existing) = text("data_volume") {
+                push(&["--data-volume", &existing]);
+            }
+            if let Some(bind) = text("bind") {
+                push(&["--bind", &bind]);
+            }
+            for network in network::attached_networks(meta) {
+                push(&["--network", &network]);
+            }
+            if let Some(version) = text("redis_version") {
+                push(&["--redis-version", &version]);
+            }
+            if let Some(flavor) = text("flavor") {
+                push(&["--flavor", &flavor]);
+            }
+        }
+        InstanceType::Cluster => {
+            for key in ["masters", "replicas", "port_base"] {
+                if let Some(value) = number(key) {
+                    push(&[&format!("--{}", key.replace('_', "-")), &value.to_string()]);
+                }
+            }
+            if flag("stack") {
+                push(&["--stack"]);
+            }
+            if let Some(ip) = text("announce_ip") {
+                push(&["--announce-ip", &ip]);
+            }
+            if let Some(hostname) = text("announce_hostname") {
+                push(&["--announce-hostname", &hostname]);
+            }
+        }
+        InstanceType::Sentinel => {
+            push(&[
+                "--redis-port-base",
+                &instance.connection_info.port.to_string(),
+            ]);
+            for key in ["masters", "sentinels"] {
+                if let Some(value) = number(key) {
+                    push(&[&format!("--{}", key), &value.to_string()]);
+                }
+            }
+            for master_name in sentinel::monitored_names(instance) {
+                push(&["--master-name", &master_name]);
+            }
+            // Start flags set every master alike; the first master's tuning stands in
+            if let Some(tuning) = sentinel::monitored_names(instance)
+                .first()
+                .and_then(|master| sentinel::tuning(instance).remove(master))
+            {
+                push(&["--quorum", &tuning.quorum.to_string()]);
+                push(&["--down-after-ms", &tuning.down_after_ms.to_string()]);
+                push(&["--failover-timeout", &tuning.failover_timeout.to_string()]);
+                push(&["--parallel-syncs", &tuning.parallel_syncs.to_string()]);
+            }
+            if let Some(port) = instance
+                .connection_info
+                .additional_ports
+                .get("sentinel_base")
+            {
+                push(&["--sentinel-port-base", &port.to_string()]);
+            }
+            if flag("readonly_replicas") {
+                push(&["--with-readonly-port"]);
+            }
+            if flag("lb") {
+                push(&["--with-lb"]);
+            }
+        }
+        InstanceType::Enterprise => {
+            if let Some(port) = number("ui_port") {
+                push(&["--port-base", &port.to_string()]);
+            }
+            if let Some(port) = number("database_port") {
+                push(&["--db-port", &port.to_string()]);
+            }
+            if let Some(db) = text("database_name") {
+                push(&["--create-db", &db]);
+            }
+            if flag("active_active") {
+                let participants = meta
+                    .get("participants")
+                    .and_then(|v| v.as_array())
+                    .map_or(2, |p| p.len());
+                push(&[
+                    "--active-active",
+                    "--participants",
+                    &participants.to_string(),
+                ]);
+            }
+        }
+    }
+
+    if flag("persist") {
+        push(&["--persist"]);
+    }
+    if let Some(mode) = text("network_mode") {
+        push(&["--network-mode", &mode]);
+    }
+    if instance.connection_info.ipv6_host.is_some() {
+        push(&["--ipv6"]);
+    }
+    if instance.instance_type != InstanceType::Enterprise {
+        if let Some(ref password) = instance.connection_info.password {
+            push(&["--password", password]);
+        }
+    }
+    if let Some(memory) = text("memory") {
+        push(&["--memory", &memory]);
+    }
+    if let Some(description) = text("description") {
+        push(&["--description", &description]);
+    }
+    for (key, option) in [
+        ("persistence", "--persistence"),
+        ("save", "--save"),
+        ("appendfsync", "--appendfsync"),
+        ("restart_policy", "--restart-policy"),
+    ] {
+        if let Some(value) = text(key) {
+            push(&[option, &value]);
+        }
+    }
+    if let Some(cpus) = meta.get("cpus").and_then(|v| v.as_f64()) {
+        push(&["--cpus", &cpus.to_string()]);
+    }
+    if let Some(shares) = number("cpu_shares") {
+        push(&["--cpu-shares", &shares.to_string()]);
+    }
+    if let Some(weight) = number("blkio_weight") {
+        push(&["--blkio-limit", &weight.to_string()]);
+    }
+
+    let insight_port = number("insight_port").or_else(|| {
+        instance
+            .connection_info
+            .additional_ports
+            .get("redisinsight")
+            .map(|p| *p as u64)
+    });
+    if flag("insight") || flag("shared_insight") || insight_port.is_some() {
+        push(&["--with-insight"]);
+        if let Some(port) = insight_port {
+            push(&["--insight-port", &port.to_string()]);
+        }
+    }
+
+    argv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ConnectionInfo;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_start_argv() {
+        let mut metadata = HashMap::new();
+        metadata.insert("masters".to_string(), serde_json::json!(3));
+        metadata.insert("replicas".to_string(), serde_json::json!(1));
+        metadata.insert("port_base".to_string(), serde_json::json!(7100));
+        metadata.insert("persist".to_string(), serde_json::json!(true));
+        metadata.insert("memory".to_string(), serde_json::json!("256m"));
+        let instance = InstanceInfo {
+            name: "orders".to_string(),
+            instance_type: InstanceType::Cluster,
+            created_at: String::new(),
+            ports: vec![7100, 7101, 7102],
+            containers: Vec::new(),
+            connection_info: ConnectionInfo {
+                host: "localhost".to_string(),
+                port: 7100,
+                password: Some("secret".to_string()),
+                url: String::new(),
+                additional_ports: HashMap::from([("redisinsight".to_string(), 8002)]),
+                ipv6_host: None,
+                ipv6_url: None,
+            },
+            metadata,
+            started_at: None,
+            last_seen: None,
+        };
+
+        let argv = start_argv(&instance);
+        assert_eq!(
+            argv.join(" "),
+            "redis-up cluster start --name orders --masters 3 --replicas 1 --port-base 7100 \
+             --persist --password secret --memory 256m --with-insight --insight-port 8002"
+        );
+        assert!(Cli::try_parse_from(&argv).is_ok());
+    }
+}
@@ -0,0 +1,128 @@
+//! Retries with exponential backoff for flaky container starts
+
+use anyhow::Result;
+use colored::*;
+use docker_wrapper::{DockerCommand, RmCommand};
+use std::future::Future;
+use std::time::Duration;
+use tracing::Instrument;
+
+use crate::settings::Settings;
+use crate::telemetry;
+
+/// Delay before the first retry; doubles on every attempt after that
+const BASE_DELAY: Duration = Duration::from_secs(2);
+
+/// Upper bound on the delay between attempts
+const MAX_DELAY: Duration = Duration::from_secs(30);
+
+/// Errors that waiting will not fix
+const PERMANENT_ERRORS: &[&str] = &[
+    "port is already allocated",
+    "address already in use",
+    "is already in use by container",
+    "No such image",
+    "pull access denied",
+];
+
+/// How many times a failed start is retried
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    pub retries: u32,
+}
+
+impl RetryPolicy {
+    /// Use `--retries` when given, otherwise the `retries` setting
+    pub fn resolve(flag: Option<u32>) -> Result<Self> {
+        let retries = match flag {
+            Some(retries) => retries,
+            None => Settings::load()?.retries,
+        };
+        Ok(Self { retries })
+    }
+
+    /// Delay before retry number `attempt` (starting at 1)
+    fn delay(attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        BASE_DELAY.saturating_mul(factor).min(MAX_DELAY)
+    }
+
+    /// Run `op`, calling `cleanup` and backing off between failed attempts
+    ///
+    /// Every attempt gets its own span, named after `what`.
+    pub async fn run<T, Op, OpFut, Clean, CleanFut>(
+        &self,
+        what: &str,
+        mut op: Op,
+        mut cleanup: Clean,
+    ) -> Result<T>
+    where
+        Op: FnMut() -> OpFut,
+        OpFut: Future<Output = Result<T>>,
+        Clean: FnMut() -> CleanFut,
+        CleanFut: Future<Output = ()>,
+    {
+        let mut attempt = 0;
+        loop {
+            let span = tracing::info_span!(
+                "attempt",
+                otel.name = %what,
+                attempt = attempt + 1,
+                otel.status_description = tracing::field::Empty
+            );
+            let result = op().instrument(span.clone()).await;
+            if let Err(ref e) = result {
+                telemetry::record_error(&span, e);
+            }
+            match result {
+                Ok(value) => return Ok(value),
+                Err(e) if attempt < self.retries && is_retryable(&e) => {
+                    attempt += 1;
+                    let delay = Self::delay(attempt);
+                    println!(
+                        "{} {} failed: {}",
+                        "Retry:".yellow(),
+                        what,
+                        format!("{:#}", e).lines().next().unwrap_or_default()
+                    );
+                    println!(
+                        "  Retrying in {}s (attempt {} of {})",
+                        delay.as_secs(),
+                        attempt,
+                        self.retries
+                    );
+                    cleanup().await;
+                    tokio::time::sleep(delay).await;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+/// Force-remove whatever a failed attempt left behind
+pub async fn remove_container(name: &str) {
+    RmCommand::new(name).force().execute().await.ok();
+}
+
+fn is_retryable(error: &anyhow::Error) -> bool {
+    let message = format!("{:#}", error);
+    !PERMANENT_ERRORS.iter().any(|m| message.contains(m))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff() {
+        let delays: Vec<u64> = (1..=6).map(|a| RetryPolicy::delay(a).as_secs()).collect();
+        assert_eq!(delays, vec![2, 4, 8, 16, 30, 30]);
+        assert_eq!(RetryPolicy::delay(100), MAX_DELAY);
+
+        assert!(is_retryable(&anyhow::anyhow!("connection reset by peer")));
+        assert!(!is_retryable(&anyhow::anyhow!(
+            "Bind for 0.0.0.0:6379 failed: port is already allocated"
+        )));
+    }
+}
@@ -0,0 +1,448 @@
+//! `--reuse`: idempotent starts for setup scripts
+//!
+//! With `--reuse`, a start whose name belongs to a running instance of the
+//! same type succeeds without touching it and prints its connection details,
+//! provided the instance was started with the settings the command asks for.
+//! A setting that differs (drift) is an error naming it, so a script never
+//! quietly gets another setup than it asked for; `--warn-on-drift` reuses the
+//! instance anyway and `--recreate-on-drift` replaces it. Ports left at their
+//! default are not compared, since those are picked from the free range.
+//!
+//! Without `--reuse`, only a stopped instance is picked up again (see `warm`).
+
+use anyhow::Result;
+use colored::*;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::cli::{
+    AppendFsync, BasicStartArgs, ClusterStartArgs, EnterpriseStartArgs, NetworkMode,
+    PersistenceMode, SentinelStartArgs, StackStartArgs,
+};
+use crate::commands::{cleanup, sentinel, uptime, warm};
+use crate::config::{Config, InstanceInfo, InstanceType};
+use crate::error::Failure;
+
+/// A setting the request and the existing instance must agree on
+pub struct Setting {
+    flag: &'static str,
+    requested: Value,
+    recorded: Value,
+}
+
+impl Setting {
+    fn new(flag: &'static str, requested: impl Serialize, recorded: Option<&Value>) -> Self {
+        Self {
+            flag,
+            requested: serde_json::to_value(requested).unwrap_or_default(),
+            recorded: recorded.cloned().unwrap_or_default(),
+        }
+    }
+
+    fn matches(&self) -> bool {
+        self.requested == self.recorded
+    }
+}
+
+/// What a start does with an existing instance of the same name
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mode {
+    /// No --reuse: only stopped containers are started again
+    Off,
+    /// Reuse a matching instance, fail on drift
+    Fail,
+    /// Reuse the instance, warning about drift
+    Warn,
+    /// Reuse a matching instance, recreate a drifted one
+    Recreate,
+}
+
+impl Mode {
+    pub fn new(reuse: bool, recreate_on_drift: bool, warn_on_drift: bool) -> Self {
+        match (reuse, recreate_on_drift, warn_on_drift) {
+            (false, _, _) => Mode::Off,
+            (true, true, _) => Mode::Recreate,
+            (true, false, true) => Mode::Warn,
+            (true, false, false) => Mode::Fail,
+        }
+    }
+}
+
+/// Use an existing instance instead of starting a new one, if the start allows it
+///
+/// Returns true when the existing instance stands in for the requested one.
+pub async fn existing(
+    name: Option<&str>,
+    mode: Mode,
+    kind: InstanceType,
+    settings: impl FnOnce(&InstanceInfo) -> Vec<Setting>,
+    verbose: bool,
+) -> Result<bool> {
+    if mode == Mode::Off {
+        return warm::reuse_stopped(name, verbose).await;
+    }
+    let Some(name) = name else {
+        return Ok(false);
+    };
+    let config = Config::load()?;
+    let Some(instance) = config.get_instance(name) else {
+        return Ok(false);
+    };
+    if instance.instance_type != kind {
+        return Err(Failure::NameConflict(format!(
+            "'{}' is a {} instance, not {}; pick another --name",
+            name, instance.instance_type, kind
+        ))
+        .into());
+    }
+    let differences = differences(&settings(instance));
+    if !differences.is_empty() {
+        match mode {
+            Mode::Warn => println!(
+                "{} '{}' differs from the requested settings: {}",
+                "Warning:".yellow(),
+                name,
+                differences.join(", ")
+            ),
+            Mode::Recreate => {
+                println!(
+                    "{} Recreating '{}', whose settings differ: {}",
+                    "Drift:".yellow(),
+                    name,
+                    differences.join(", ")
+                );
+                cleanup::remove_instance(instance.clone(), verbose).await?;
+                return Ok(false);
+            }
+            _ => {
+                return Err(Failure::NameConflict(format!(
+                    "'{}' is running with other settings ({}); use --recreate-on-drift, \
+                     stop it or pick another --name",
+                    name,
+                    differences.join(", ")
+                ))
+                .into())
+            }
+        }
+    }
+
+    let states = uptime::inspect(&instance.containers)
+        .await
+        .unwrap_or_default();
+    let running = instance
+        .containers
+        .iter()
+        .all(|container| states.get(container).is_some_and(|state| state.running));
+    if !running {
+        return warm::reuse_stopped(Some(name), verbose).await;
+    }
+
+    println!(
+        "{} {} is already running; reusing it",
+        "Success:".green(),
+        name.bold()
+    );
+    let info = &instance.connection_info;
+    println!("  {}: {}:{}", "Address".bold(), info.host, info.port);
+    println!("  {}: {}", "URL".bold(), info.url.blue());
+    Ok(true)
+}
+
+/// `--flag requested (running: recorded)` for every setting that differs
+fn differences(settings: &[Setting]) -> Vec<String> {
+    settings
+        .iter()
+        .filter(|setting| !setting.matches())
+        .map(|setting| {
+            format!(
+                "{} {} (running: {})",
+                setting.flag,
+                describe(&setting.requested),
+                describe(&setting.recorded)
+            )
+        })
+        .collect()
+}
+
+fn describe(value: &Value) -> String {
+    match value {
+        Value::Null => "unset".to_string(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn meta<'a>(instance: &'a InstanceInfo, key: &str) -> Option<&'a Value> {
+    instance.metadata.get(key)
+}
+
+/// Password and --no-auth, when the start gives either
+fn auth(password: &Option<String>, no_auth: bool, instance: &InstanceInfo) -> Vec<Setting> {
+    let recorded = instance
+        .connection_info
+        .password
+        .as_ref()
+        .map(|p| Value::String(p.clone()));
+    let mut settings = vec![Setting::new(
+        "--no-auth",
+        no_auth,
+        Some(&Value::Bool(recorded.is_none())),
+    )];
+    if password.is_some() {
+        settings.push(Setting::new("--password", password, recorded.as_ref()));
+    }
+    settings
+}
+
+/// Persistence mode, save points and fsync policy
+fn persistence(
+    mode: Option<PersistenceMode>,
+    save: &Option<String>,
+    appendfsync: Option<AppendFsync>,
+    instance: &InstanceInfo,
+) -> [Setting; 3] {
+    [
+        Setting::new(
+            "--persistence",
+            mode.map(|mode| mode.to_string()),
+            meta(instance, "persistence"),
+        ),
+        Setting::new("--save", save, meta(instance, "save")),
+        Setting::new(
+            "--appendfsync",
+            appendfsync.map(|policy| policy.to_string()),
+            meta(instance, "appendfsync"),
+        ),
+    ]
+}
+
+/// Network mode; bridge networking is not recorded
+fn network_mode(mode: NetworkMode, instance: &InstanceInfo) -> Setting {
+    Setting::new(
+        "--network-mode",
+        (mode == NetworkMode::Host).then(|| mode.to_string()),
+        meta(instance, "network_mode"),
+    )
+}
+
+/// Port, unless left at its default
+fn port(requested: u16, default: u16, recorded: u16) -> Option<Setting> {
+    (requested != default).then(|| Setting::new("--port", requested, Some(&recorded.into())))
+}
+
+pub fn basic(args: &BasicStartArgs, instance: &InstanceInfo) -> Vec<Setting> {
+    let persist = args.persist || args.load_rdb.is_some() || args.data_volume.is_some();
+    let mut settings = auth(&args.password, args.no_auth, instance);
+    settings.extend(port(args.port, 6379, instance.connection_info.port));
+    settings.extend([
+        Setting::new(
+            "--redis-version",
+            &args.redis_version,
+            meta(instance, "redis_version"),
+        ),
+        Setting::new("--persist", persist, meta(instance, "persist")),
+        Setting::new("--memory", &args.memory, meta(instance, "memory")),
+        network_mode(args.network_mode, instance),
+    ]);
+    settings.extend(persistence(
+        args.persistence,
+        &args.save,
+        args.appendfsync,
+        instance,
+    ));
+    settings
+}
+
+pub fn stack(args: &StackStartArgs, instance: &InstanceInfo) -> Vec<Setting> {
+    let persist = args.persist || args.load_rdb.is_some() || args.data_volume.is_some();
+    let mut settings = auth(&args.password, args.no_auth, instance);
+    settings.extend(port(args.port, 6379, instance.connection_info.port));
+    settings.extend([
+        Setting::new(
+            "--flavor",
+            args.flavor.to_string(),
+            meta(instance, "flavor"),
+        ),
+        Setting::new("--persist", persist, meta(instance, "persist")),
+        Setting::new("--memory", &args.memory, meta(instance, "memory")),
+        network_mode(args.network_mode, instance),
+    ]);
+    settings.extend(persistence(
+        args.persistence,
+        &args.save,
+        args.appendfsync,
+        instance,
+    ));
+    settings
+}
+
+pub fn cluster(args: &ClusterStartArgs, instance: &InstanceInfo) -> Vec<Setting> {
+    let mut settings = auth(&args.password, false, instance);
+    if args.port_base != 7000 {
+        settings.push(Setting::new(
+            "--port-base",
+            args.port_base,
+            meta(instance, "port_base"),
+        ));
+    }
+    settings.extend([
+        Setting::new("--masters", args.masters, meta(instance, "masters")),
+        Setting::new("--replicas", args.replicas, meta(instance, "replicas")),
+        Setting::new("--stack", args.stack, meta(instance, "stack")),
+        Setting::new(
+            "--emulate",
+            args.emulate.map(|mode| mode.to_string()),
+            meta(instance, "emulate"),
+        ),
+        Setting::new(
+            "--announce-ip",
+            &args.announce_ip,
+            meta(instance, "announce_ip"),
+        ),
+        Setting::new(
+            "--announce-hostname",
+            &args.announce_hostname,
+            meta(instance, "announce_hostname"),
+        ),
+        Setting::new("--persist", args.persist, meta(instance, "persist")),
+        Setting::new("--memory", &args.memory, meta(instance, "memory")),
+        network_mode(args.network_mode, instance),
+    ]);
+    settings.extend(persistence(
+        args.persistence,
+        &args.save,
+        args.appendfsync,
+        instance,
+    ));
+    settings
+}
+
+pub fn sentinel(args: &SentinelStartArgs, instance: &InstanceInfo) -> Vec<Setting> {
+    let masters = sentinel::master_count(args.masters, &args.master_names);
+    let mut settings = auth(&args.password, false, instance);
+    settings.extend([
+        Setting::new("--masters", masters, meta(instance, "masters")),
+        Setting::new(
+            "--master-name",
+            sentinel::master_names(masters, &args.master_names),
+            meta(instance, "master_names"),
+        ),
+        Setting::new(
+            "--sentinels",
+            args.sentinels.max(1),
+            meta(instance, "sentinels"),
+        ),
+        Setting::new("--persist", args.persist, meta(instance, "persist")),
+        Setting::new("--memory", &args.memory, meta(instance, "memory")),
+        // Only recorded when on
+        Setting::new(
+            "--with-readonly-port",
+            args.with_readonly_port.then_some(true),
+            meta(instance, "readonly_replicas"),
+        ),
+        Setting::new(
+            "--with-lb",
+            args.with_lb.then_some(true),
+            meta(instance, "lb"),
+        ),
+    ]);
+    settings
+}
+
+pub fn enterprise(args: &EnterpriseStartArgs, instance: &InstanceInfo) -> Vec<Setting> {
+    let mut settings = Vec::new();
+    if args.port_base != 8443 {
+        settings.push(Setting::new(
+            "--port-base",
+            args.port_base,
+            meta(instance, "ui_port"),
+        ));
+    }
+    if args.create_db.is_some() {
+        settings.push(Setting::new(
+            "--create-db",
+            &args.create_db,
+            meta(instance, "database_name"),
+        ));
+    }
+    settings.extend([
+        Setting::new(
+            "--active-active",
+            args.active_active,
+            Some(meta(instance, "active_active").unwrap_or(&Value::Bool(false))),
+        ),
+        Setting::new("--persist", args.persist, meta(instance, "persist")),
+        Setting::new("--memory", &args.memory, meta(instance, "memory")),
+    ]);
+    settings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[derive(Parser)]
+    struct Cli {
+        #[command(flatten)]
+        args: BasicStartArgs,
+    }
+
+    #[test]
+    fn test_basic_differences() {
+        let instance: InstanceInfo = serde_json::from_value(serde_json::json!({
+            "name": "cache",
+            "instance_type": "basic",
+            "created_at": "2024-01-01T00:00:00Z",
+            "ports": [6380],
+            "containers": ["cache"],
+            "connection_info": {
+                "host": "localhost",
+                "port": 6380,
+                "password": "pw",
+                "url": "redis://default:pw@localhost:6380",
+                "additional_ports": {}
+            },
+            "metadata": {"persist": true, "redis_version": "7.2", "persistence": "aof"}
+        }))
+        .unwrap();
+
+        // The default port was moved to a free one, so it isn't compared
+        let args = Cli::parse_from([
+            "start",
+            "--name",
+            "cache",
+            "--persist",
+            "--redis-version",
+            "7.2",
+            "--persistence",
+            "aof",
+        ])
+        .args;
+        assert!(differences(&basic(&args, &instance)).is_empty());
+
+        let args = Cli::parse_from([
+            "start",
+            "--name",
+            "cache",
+            "--password",
+            "other",
+            "-p",
+            "6390",
+        ])
+        .args;
+        assert_eq!(
+            differences(&basic(&args, &instance)),
+            vec![
+                "--password other (running: pw)",
+                "--port 6390 (running: 6380)",
+                "--redis-version unset (running: 7.2)",
+                "--persist false (running: true)",
+                "--persistence unset (running: aof)",
+            ]
+        );
+        assert_eq!(Mode::new(false, false, false), Mode::Off);
+        assert_eq!(Mode::new(true, true, false), Mode::Recreate);
+        assert_eq!(Mode::new(true, false, true), Mode::Warn);
+    }
+}
@@ -0,0 +1,156 @@
+//! `redis-up revive`: bring instances back after a reboot
+//!
+//! Containers started without a restart policy stay stopped after the Docker
+//! daemon or the machine restarts. Revive starts the stopped containers of
+//! every registered instance (or the named ones) and waits until their
+//! servers answer PING. Instances whose containers are gone have to be
+//! stopped and started again.
+
+use anyhow::{Context, Result};
+use colored::*;
+use docker_wrapper::{DockerCommand, StartCommand};
+use std::collections::HashMap;
+
+use crate::cli::ReviveArgs;
+use crate::commands::uptime::{self, ContainerState};
+use crate::commands::warm;
+use crate::config::{Config, InstanceInfo};
+use crate::error::Failure;
+
+pub async fn handle_revive(args: ReviveArgs, verbose: bool) -> Result<()> {
+    let config = Config::load()?;
+    let instances: Vec<&InstanceInfo> = if args.names.is_empty() {
+        let mut all: Vec<&InstanceInfo> = config.instances.values().collect();
+        all.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+        all
+    } else {
+        args.names
+            .iter()
+            .map(|name| {
+                config
+                    .get_instance(name)
+                    .with_context(|| format!("Instance '{}' not found", name))
+            })
+            .collect::<Result<_>>()?
+    };
+
+    let containers: Vec<String> = instances
+        .iter()
+        .flat_map(|instance| instance.containers.iter().cloned())
+        .collect();
+    let states = uptime::inspect(&containers).await?;
+
+    let (mut revived, mut failed) = (0, 0);
+    for instance in &instances {
+        let (stopped, missing) = stopped_and_missing(&instance.containers, &states);
+        if !missing.is_empty() {
+            println!(
+                "{} {}: {} container(s) no longer exist; `redis-up {} stop {}` and start it again",
+                "Warning:".yellow(),
+                instance.name.bold(),
+                missing.len(),
+                instance.instance_type,
+                instance.name
+            );
+            failed += 1;
+            continue;
+        }
+        if stopped.is_empty() {
+            if verbose {
+                println!("  {} {} is running", "Skipped:".dimmed(), instance.name);
+            }
+            continue;
+        }
+
+        match revive(instance, &stopped).await {
+            Ok(()) => {
+                println!(
+                    "{} Revived {} ({} container(s))",
+                    "Success:".green(),
+                    instance.name.bold(),
+                    stopped.len()
+                );
+                revived += 1;
+            }
+            Err(e) => {
+                println!(
+                    "{} Failed to revive {}: {:#}",
+                    "Error:".red(),
+                    instance.name.bold(),
+                    e
+                );
+                failed += 1;
+            }
+        }
+    }
+
+    if failed > 0 {
+        return Err(Failure::Partial(format!(
+            "{} of {} instance(s) could not be revived",
+            failed,
+            instances.len()
+        ))
+        .into());
+    }
+    if revived == 0 {
+        println!(
+            "{} Nothing to revive; every instance is running",
+            "Info:".blue()
+        );
+    }
+    Ok(())
+}
+
+pub async fn revive(instance: &InstanceInfo, stopped: &[&String]) -> Result<()> {
+    for container in stopped {
+        StartCommand::new(*container)
+            .execute()
+            .await
+            .with_context(|| format!("Failed to start {}", container))?;
+    }
+    warm::wait_for_servers(instance).await?;
+    warm::mark_started(&instance.name)
+}
+
+/// An instance's containers that exist but are stopped, and those that don't exist
+pub fn stopped_and_missing<'a>(
+    containers: &'a [String],
+    states: &HashMap<String, ContainerState>,
+) -> (Vec<&'a String>, Vec<&'a String>) {
+    let stopped = containers
+        .iter()
+        .filter(|container| states.get(*container).is_some_and(|state| !state.running))
+        .collect();
+    let missing = containers
+        .iter()
+        .filter(|container| !states.contains_key(*container))
+        .collect();
+    (stopped, missing)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stopped_and_missing() {
+        let state = |running| ContainerState {
+            running,
+            started_at: None,
+            finished_at: None,
+            health: None,
+        };
+        let states = HashMap::from([
+            ("c-node-0".to_string(), state(true)),
+            ("c-node-1".to_string(), state(false)),
+        ]);
+        let containers = vec![
+            "c-node-0".to_string(),
+            "c-node-1".to_string(),
+            "c-node-2".to_string(),
+        ];
+        let (stopped, missing) = stopped_and_missing(&containers, &states);
+        assert_eq!(stopped, vec!["c-node-1"]);
+        assert_eq!(missing, vec!["c-node-2"]);
+    }
+}
@@ -0,0 +1,196 @@
+//! Lua script development loop
+
+use anyhow::{Context, Result};
+use colored::*;
+use redis::Value;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use crate::cli::{ScriptAction, ScriptArgs};
+use crate::commands::client;
+use crate::config::Config;
+
+/// How often `script watch` checks the file for changes
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+pub async fn handle_action(action: ScriptAction, verbose: bool) -> Result<()> {
+    match action {
+        ScriptAction::Load(args) => load(args, verbose).await,
+        ScriptAction::Run(args) => {
+            let source = read_script(&args.file).await?;
+            eval(&args, &source, verbose).await
+        }
+        ScriptAction::Watch(args) => watch(args, verbose).await,
+    }
+}
+
+async fn read_script(path: &Path) -> Result<String> {
+    tokio::fs::read_to_string(path)
+        .await
+        .with_context(|| format!("Failed to read script: {}", path.display()))
+}
+
+async fn load(args: ScriptArgs, verbose: bool) -> Result<()> {
+    let source = read_script(&args.file).await?;
+    let endpoints = endpoints(&args.name)?;
+
+    // Every node needs the script cached for EVALSHA to work on any key
+    let mut sha = String::new();
+    for endpoint in &endpoints {
+        let mut con = client::connect(endpoint).await?;
+        sha = redis::cmd("SCRIPT")
+            .arg("LOAD")
+            .arg(&source)
+            .query_async(&mut con)
+            .await
+            .with_context(|| format!("SCRIPT LOAD failed on {}", endpoint))?;
+        if verbose {
+            println!("{} Loaded on {}", "Script:".cyan(), endpoint);
+        }
+    }
+
+    println!("{}", sha);
+    Ok(())
+}
+
+/// Evaluate a script, printing the reply or the script error
+async fn eval(args: &ScriptArgs, source: &str, verbose: bool) -> Result<()> {
+    let endpoints = endpoints(&args.name)?;
+
+    let mut last_error = None;
+    for endpoint in &endpoints {
+        let mut con = client::connect(endpoint).await?;
+        let reply: redis::RedisResult<Value> = redis::cmd("EVAL")
+            .arg(source)
+            .arg(args.keys.len())
+            .arg(&args.keys)
+            .arg(&args.args)
+            .query_async(&mut con)
+            .await;
+
+        match reply {
+            Ok(value) => {
+                println!("{}", format_value(&value, 0));
+                return Ok(());
+            }
+            // In a cluster, keep looking for the node that owns the keys
+            Err(e) if e.kind() == redis::ErrorKind::Moved => {
+                if verbose {
+                    println!("{} {} redirected: {}", "Script:".cyan(), endpoint, e);
+                }
+                last_error = Some(e);
+            }
+            Err(e) => anyhow::bail!("{}", e),
+        }
+    }
+
+    match last_error {
+        Some(e) => anyhow::bail!("{}", e),
+        None => anyhow::bail!("Instance '{}' has no reachable endpoints", args.name),
+    }
+}
+
+async fn watch(args: ScriptArgs, verbose: bool) -> Result<()> {
+    println!(
+        "{} Watching {} (Ctrl+C to stop)",
+        "Script:".bold().cyan(),
+        args.file.display()
+    );
+
+    let mut last_modified: Option<SystemTime> = None;
+    loop {
+        let modified = tokio::fs::metadata(&args.file)
+            .await
+            .and_then(|m| m.modified())
+            .ok();
+
+        if modified.is_some() && modified != last_modified {
+            last_modified = modified;
+            println!();
+            println!(
+                "{} {}",
+                chrono::Local::now().format("%H:%M:%S").to_string().dimmed(),
+                args.file.display()
+            );
+
+            // Errors are part of the loop; report them and wait for the next save
+            let result = match read_script(&args.file).await {
+                Ok(source) => eval(&args, &source, verbose).await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                println!("{} {:#}", "Error:".red(), e);
+            }
+        }
+
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => return Ok(()),
+            _ = tokio::time::sleep(POLL_INTERVAL) => {}
+        }
+    }
+}
+
+fn endpoints(name: &str) -> Result<Vec<client::Endpoint>> {
+    let config = Config::load()?;
+    let instance = config
+        .get_instance(name)
+        .with_context(|| format!("Instance '{}' not found", name))?;
+    Ok(client::endpoints(instance))
+}
+
+/// Render a reply the way redis-cli does
+pub fn format_value(value: &Value, indent: usize) -> String {
+    let items = |values: &[Value]| {
+        if values.is_empty() {
+            return "(empty array)".to_string();
+        }
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, v)| {
+                format!(
+                    "{}{}) {}",
+                    if i == 0 {
+                        String::new()
+                    } else {
+                        " ".repeat(indent)
+                    },
+                    i + 1,
+                    format_value(v, indent + 3)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    match value {
+        Value::Nil => "(nil)".to_string(),
+        Value::Int(n) => format!("(integer) {}", n),
+        Value::BulkString(bytes) => format!("\"{}\"", String::from_utf8_lossy(bytes)),
+        Value::SimpleString(s) => s.clone(),
+        Value::Okay => "OK".to_string(),
+        Value::Double(d) => format!("(double) {}", d),
+        Value::Boolean(b) => format!("({})", b),
+        Value::Array(values) | Value::Set(values) => items(values),
+        other => format!("{:?}", other),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_value() {
+        assert_eq!(format_value(&Value::Int(3), 0), "(integer) 3");
+        assert_eq!(format_value(&Value::Nil, 0), "(nil)");
+        let nested = Value::Array(vec![
+            Value::BulkString(b"a".to_vec()),
+            Value::Array(vec![Value::Int(1), Value::Int(2)]),
+        ]);
+        assert_eq!(
+            format_value(&nested, 0),
+            "1) \"a\"\n2) 1) (integer) 1\n   2) (integer) 2"
+        );
+    }
+}
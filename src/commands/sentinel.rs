@@ -2,57 +2,187 @@
 
 use anyhow::{Context, Result};
 use colored::*;
-use docker_wrapper::{DockerCommand, NetworkCreateCommand, RedisTemplate, Template};
+use docker_wrapper::{DockerCommand, NetworkCreateCommand, RedisTemplate, RunCommand, Template};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use tracing::Instrument;
 
-use crate::cli::{InfoArgs, SentinelAction, SentinelStartArgs, StopArgs};
-use crate::config::{generate_password, Config, ConnectionInfo, InstanceInfo, InstanceType};
+use crate::cli::{
+    InfoArgs, SentinelAction, SentinelClient, SentinelStartArgs, SentinelTuneArgs, SentinelUrlArgs,
+    StopArgs,
+};
+use crate::commands::insight::{self, Insight};
+use crate::commands::plan::{Operation, Plan};
+use crate::commands::retry::{remove_container, RetryPolicy};
+use crate::commands::snippets::{self, SentinelTarget};
+use crate::commands::{
+    annotate, history, images, limits, live, locks, names, passthrough, ports, reuse, server,
+    shutdown, uptime, volume,
+};
+use crate::config::{
+    generate_password, Config, ConnectionInfo, InstanceInfo, InstanceType, PortPool,
+};
 
 pub async fn handle_action(action: SentinelAction, verbose: bool) -> Result<()> {
     match action {
-        SentinelAction::Start(args) => start_sentinel(args, verbose).await,
+        SentinelAction::Start(args) => {
+            names::validate_opt(args.name.as_deref())?;
+            let _lock = locks::start(args.name.as_deref(), &InstanceType::Sentinel).await?;
+            let reused = reuse::existing(
+                args.name.as_deref(),
+                reuse::Mode::new(args.reuse, args.recreate_on_drift, args.warn_on_drift),
+                InstanceType::Sentinel,
+                |instance| reuse::sentinel(&args, instance),
+                verbose,
+            )
+            .await?;
+            if reused {
+                return Ok(());
+            }
+            locks::ensure_not_running(args.name.as_deref()).await?;
+            start_sentinel(args, verbose).await
+        }
         SentinelAction::Stop(args) => stop_sentinel(args, verbose).await,
-        SentinelAction::Info(args) => info_sentinel(args, verbose).await,
+        SentinelAction::Info(args) => {
+            let master = args.master;
+            live::info(
+                args.info,
+                InstanceType::Sentinel,
+                verbose,
+                |args, verbose| info_sentinel(args, master.clone(), verbose),
+            )
+            .await
+        }
+        SentinelAction::Url(args) => url_sentinel(args).await,
+        SentinelAction::Tune(args) => tune_sentinel(args).await,
     }
 }
 
-async fn start_sentinel(args: SentinelStartArgs, verbose: bool) -> Result<()> {
-    let mut config = Config::load()?;
+/// How Sentinel decides a master is down and fails it over
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Tuning {
+    pub quorum: usize,
+    pub down_after_ms: u64,
+    pub failover_timeout: u64,
+    pub parallel_syncs: u32,
+}
 
-    // Generate name if not provided
-    let name = args
-        .name
-        .unwrap_or_else(|| config.generate_name(&InstanceType::Sentinel));
+impl Tuning {
+    /// The values setups were started with before the flags existed
+    fn default_for(sentinels: usize) -> Self {
+        Self {
+            quorum: sentinels / 2 + 1,
+            down_after_ms: 5000,
+            failover_timeout: 10000,
+            parallel_syncs: 1,
+        }
+    }
 
-    if verbose {
-        println!(
-            "{} Starting Redis Sentinel setup: {}",
-            "Starting".cyan(),
-            name.bold()
-        );
+    /// `SENTINEL SET` option names and values
+    fn options(&self) -> [(&'static str, String); 4] {
+        [
+            ("quorum", self.quorum.to_string()),
+            ("down-after-milliseconds", self.down_after_ms.to_string()),
+            ("failover-timeout", self.failover_timeout.to_string()),
+            ("parallel-syncs", self.parallel_syncs.to_string()),
+        ]
+    }
+}
+
+/// A Sentinel start resolved down to the Docker commands it runs
+///
+/// Built once by [`prepare`], then run by `start_sentinel` or printed by `--dry-run`.
+struct Launch {
+    name: String,
+    password: String,
+    masters: usize,
+    sentinels: usize,
+    monitored: Vec<String>,
+    tuning: Tuning,
+    required: Vec<&'static str>,
+    platform: Option<String>,
+    network: NetworkCreateCommand,
+    /// Container name and `docker run` of each master, Sentinel and read-only replica
+    master_cmds: Vec<(String, RunCommand)>,
+    sentinel_cmds: Vec<(String, RunCommand)>,
+    replica_cmds: Vec<(String, RunCommand)>,
+    /// The load balancer and the port it serves each master on
+    lb: Option<(RunCommand, Vec<u16>)>,
+    insight: Option<Insight>,
+}
+
+/// Validate a start and resolve its name, ports, password and commands
+///
+/// Nothing is created; `args` is updated with the resolved ports.
+async fn prepare(args: &mut SentinelStartArgs, config: &mut Config) -> Result<Launch> {
+    validate_master_names(&args.master_names)?;
+    let sentinels = args.sentinels.max(1);
+    let tuning = Tuning {
+        quorum: args.quorum.unwrap_or(sentinels / 2 + 1),
+        down_after_ms: args.down_after_ms,
+        failover_timeout: args.failover_timeout,
+        parallel_syncs: args.parallel_syncs,
+    };
+    validate_quorum(tuning.quorum, sentinels)?;
+    let mut required = vec![images::REDIS_IMAGE];
+    if args.with_lb {
+        required.push(images::HAPROXY_IMAGE);
+    }
+    if args.with_insight {
+        required.push(images::INSIGHT_IMAGE);
     }
+    let platform = images::resolve_platform(&args.pull, "sentinel", &required)?;
+
+    // Generate name if not provided
+    let name = match args.name {
+        Some(ref name) => name.clone(),
+        None => config.reserve_name(&InstanceType::Sentinel),
+    };
+    let masters = master_count(args.masters, &args.master_names);
+    // Read-only replicas and load balancer ports follow the masters'
+    args.redis_port_base = ports::reserve(
+        config,
+        PortPool::Redis,
+        args.redis_port_base,
+        redis_port_count(masters, args.with_readonly_port, args.with_lb),
+        "Redis",
+    )?;
+    args.sentinel_port_base = ports::reserve(
+        config,
+        PortPool::Sentinel,
+        args.sentinel_port_base,
+        args.sentinels,
+        "Sentinel",
+    )?;
 
     // Generate password if not provided
-    let password = args.password.unwrap_or_else(generate_password);
+    let password = args.password.clone().unwrap_or_else(generate_password);
 
-    // Create network for Sentinel setup
+    // Network for the Sentinel setup
     let network_name = format!("{}-network", name);
-    NetworkCreateCommand::new(&network_name)
-        .driver("bridge")
-        .execute()
-        .await
-        .context("Failed to create network for Sentinel setup")?;
+    let mut network = NetworkCreateCommand::new(&network_name).driver("bridge");
+    if args.ipv6 {
+        network = network.ipv6();
+    }
 
-    let mut container_ids = Vec::new();
-    let mut ports_used = Vec::new();
+    // Every container gets the same image platform and resource limits
+    let container = |image: &str| {
+        let mut cmd = passthrough::apply(RunCommand::new(images::resolve(image)));
+        if let Some(ref platform) = platform {
+            cmd = cmd.platform(platform);
+        }
+        cmd
+    };
 
-    // Start Redis master(s)
-    let masters = args.masters.max(1);
+    let monitored = master_names(masters, &args.master_names);
+    let mut master_cmds = Vec::new();
     for i in 0..masters {
         let master_name = format!("{}-master-{}", name, i + 1);
         let master_port = args.redis_port_base + i as u16;
 
+        let (image, tag) = images::resolve_parts(images::REDIS_IMAGE);
         let mut master = RedisTemplate::new(&master_name)
+            .custom_image(image, tag)
             .port(master_port)
             .password(&password)
             .network(&network_name);
@@ -65,8 +195,175 @@ async fn start_sentinel(args: SentinelStartArgs, verbose: bool) -> Result<()> {
             master = master.memory_limit(memory);
         }
 
-        let container_id = master
-            .start()
+        if let Some(ref platform) = platform {
+            master = master.platform(platform);
+        }
+
+        let cmd = limits::apply_to_run(passthrough::apply(master.build_command()), &args.limits);
+        master_cmds.push((master_name, cmd));
+    }
+
+    let mut sentinel_cmds = Vec::new();
+    for i in 0..sentinels {
+        let sentinel_name = format!("{}-sentinel-{}", name, i + 1);
+        let sentinel_port = args.sentinel_port_base + i as u16;
+
+        let sentinel_config = sentinel_config(
+            sentinel_port,
+            &name,
+            &monitored,
+            args.redis_port_base,
+            &tuning,
+            &password,
+        );
+
+        let cmd = container(images::REDIS_IMAGE)
+            .name(&sentinel_name)
+            .network(&network_name)
+            .port(sentinel_port, sentinel_port)
+            .env(CONFIG_ENV, sentinel_config)
+            .cmd(sentinel_command())
+            .detach();
+        sentinel_cmds.push((sentinel_name, limits::apply_to_run(cmd, &args.limits)));
+    }
+
+    // Read-only replicas, started after the Sentinels so the port list keeps
+    // masters first; Sentinel finds them through their masters
+    let mut replica_cmds = Vec::new();
+    if args.with_readonly_port {
+        for i in 0..masters {
+            let replica_name = format!("{}-replica-{}", name, i + 1);
+            let replica_port = args.redis_port_base + (masters + i) as u16;
+
+            let mut cmd = container(images::REDIS_IMAGE);
+            if let Some(ref memory) = args.memory {
+                cmd = cmd.memory(memory);
+            }
+            let cmd = cmd
+                .name(&replica_name)
+                .network(&network_name)
+                .port(replica_port, 6379)
+                .cmd(replica_command(
+                    &format!("{}-master-{}", name, i + 1),
+                    &password,
+                ))
+                .detach();
+            replica_cmds.push((replica_name, limits::apply_to_run(cmd, &args.limits)));
+        }
+    }
+
+    // HAProxy routes each master's port to whichever server reports role:master
+    let lb = if args.with_lb {
+        let mut cmd = container(images::HAPROXY_IMAGE);
+        let mut frontends = Vec::new();
+        for i in 0..monitored.len() {
+            let lb_port =
+                lb_port_base(args.redis_port_base, masters, args.with_readonly_port) + i as u16;
+            cmd = cmd.port(lb_port, lb_port);
+            frontends.push(lb_port);
+        }
+        let cmd = cmd
+            .name(format!("{}-lb", name))
+            .network(&network_name)
+            .env(
+                LB_CONFIG_ENV,
+                lb_config(&name, &frontends, args.with_readonly_port, &password),
+            )
+            .cmd(lb_command())
+            .detach();
+        Some((limits::apply_to_run(cmd, &args.limits), frontends))
+    } else {
+        None
+    };
+
+    // RedisInsight joins the Sentinel network
+    let insight = if args.with_insight {
+        Some(
+            Insight::resolve(
+                config,
+                &name,
+                args.insight_port,
+                Some(network_name),
+                platform.as_deref(),
+            )
+            .await,
+        )
+    } else {
+        None
+    };
+
+    Ok(Launch {
+        name,
+        password,
+        masters,
+        sentinels,
+        monitored,
+        tuning,
+        required,
+        platform,
+        network,
+        master_cmds,
+        sentinel_cmds,
+        replica_cmds,
+        lb,
+        insight,
+    })
+}
+
+async fn start_sentinel(mut args: SentinelStartArgs, verbose: bool) -> Result<()> {
+    let policy = RetryPolicy::resolve(args.retries)?;
+    let mut config = Config::load()?;
+    let Launch {
+        name,
+        password,
+        masters,
+        sentinels,
+        monitored,
+        tuning,
+        mut required,
+        platform,
+        network,
+        master_cmds,
+        sentinel_cmds,
+        replica_cmds,
+        lb,
+        insight,
+    } = prepare(&mut args, &mut config).await?;
+    if matches!(insight, Some(Insight::Shared(_))) {
+        required.retain(|image| *image != images::INSIGHT_IMAGE);
+    }
+    images::ensure_images(&required, &args.pull, platform.as_deref(), None, verbose).await?;
+
+    if verbose {
+        println!(
+            "{} Starting Redis Sentinel setup: {}",
+            "Starting".cyan(),
+            name.bold()
+        );
+    }
+
+    // Create network for Sentinel setup
+    let network_name = format!("{}-network", name);
+    network
+        .execute()
+        .instrument(tracing::info_span!("network_create", network = %network_name))
+        .await
+        .context("Failed to create network for Sentinel setup")?;
+
+    let mut container_ids = Vec::new();
+    let mut ports_used = Vec::new();
+
+    // Start Redis master(s); the span covers every master's start attempts
+    let masters_span = tracing::info_span!("sentinel_masters", masters);
+    for (i, (master_name, cmd)) in master_cmds.iter().enumerate() {
+        let master_port = args.redis_port_base + i as u16;
+        let container_id = policy
+            .run(
+                &format!("Start of master {}", i + 1),
+                || async { Ok(server::run_container(cmd, None).await?) },
+                || remove_container(master_name),
+            )
+            .instrument(masters_span.clone())
             .await?;
 
         container_ids.push(container_id);
@@ -82,77 +379,26 @@ async fn start_sentinel(args: SentinelStartArgs, verbose: bool) -> Result<()> {
         }
     }
 
-    // Start Sentinel nodes
-    let sentinels = args.sentinels.max(1);
+    drop(masters_span);
+
+    // Start Sentinel nodes, under one span that includes the settle time
     let mut sentinel_containers = Vec::new();
+    let sentinels_span = tracing::info_span!("sentinel_nodes", sentinels);
 
-    for i in 0..sentinels {
-        let sentinel_name = format!("{}-sentinel-{}", name, i + 1);
+    for (i, (sentinel_name, cmd)) in sentinel_cmds.iter().enumerate() {
         let sentinel_port = args.sentinel_port_base + i as u16;
-
-        // Create Sentinel configuration
-        let mut sentinel_config = String::new();
-        sentinel_config.push_str(&format!("port {}\n", sentinel_port));
-        sentinel_config.push_str("sentinel announce-hostnames yes\n");
-        sentinel_config.push_str("sentinel resolve-hostnames yes\n");
-
-        // Monitor all masters
-        for j in 0..masters {
-            let master_name = format!("{}-master-{}", name, j + 1);
-            let master_port = args.redis_port_base + j as u16;
-            let quorum = (sentinels / 2) + 1; // Majority quorum
-
-            sentinel_config.push_str(&format!(
-                "sentinel monitor master-{} {} {} {}\n",
-                j + 1,
-                master_name,
-                master_port,
-                quorum
-            ));
-
-            if !password.is_empty() {
-                sentinel_config.push_str(&format!(
-                    "sentinel auth-pass master-{} {}\n",
-                    j + 1,
-                    password
-                ));
-            }
-
-            sentinel_config.push_str(&format!(
-                "sentinel down-after-milliseconds master-{} 5000\n",
-                j + 1
-            ));
-            sentinel_config.push_str(&format!(
-                "sentinel failover-timeout master-{} 10000\n",
-                j + 1
-            ));
-            sentinel_config.push_str(&format!("sentinel parallel-syncs master-{} 1\n", j + 1));
-        }
-
-        // Create a temporary config file
-        let config_path = std::env::temp_dir().join(format!("{}.conf", sentinel_name));
-        std::fs::write(&config_path, sentinel_config).context("Failed to write Sentinel config")?;
-
-        // Start Sentinel container
-        use docker_wrapper::RunCommand;
-        let sentinel_cmd = RunCommand::new("redis:7-alpine")
-            .name(&sentinel_name)
-            .network(&network_name)
-            .port(sentinel_port, sentinel_port)
-            .volume(config_path.to_str().unwrap(), "/etc/redis/sentinel.conf")
-            .cmd(vec![
-                "redis-sentinel".to_string(),
-                "/etc/redis/sentinel.conf".to_string(),
-            ])
-            .detach();
-
-        let container_id = sentinel_cmd
-            .execute()
+        let container_id = policy
+            .run(
+                &format!("Start of Sentinel {}", i + 1),
+                || async { Ok(server::run_container(cmd, None).await?) },
+                || remove_container(sentinel_name),
+            )
+            .instrument(sentinels_span.clone())
             .await
             .context(format!("Failed to start Sentinel {}", i + 1))?;
 
-        sentinel_containers.push(container_id.0.clone());
-        container_ids.push(container_id.0);
+        sentinel_containers.push(container_id.clone());
+        container_ids.push(container_id);
         ports_used.push(sentinel_port);
 
         if verbose {
@@ -165,20 +411,128 @@ async fn start_sentinel(args: SentinelStartArgs, verbose: bool) -> Result<()> {
         }
 
         // Give Sentinel time to start
-        tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+        tokio::time::sleep(tokio::time::Duration::from_secs(1))
+            .instrument(sentinels_span.clone())
+            .await;
+    }
+    drop(sentinels_span);
+
+    let mut replica_ports = HashMap::new();
+    for (i, (replica_name, cmd)) in replica_cmds.iter().enumerate() {
+        let replica_port = args.redis_port_base + (masters + i) as u16;
+        let container_id = policy
+            .run(
+                &format!("Start of read-only replica {}", i + 1),
+                || async { Ok(server::run_container(cmd, None).await?) },
+                || remove_container(replica_name),
+            )
+            .await
+            .context(format!("Failed to start read-only replica {}", i + 1))?;
+
+        container_ids.push(container_id);
+        ports_used.push(replica_port);
+        replica_ports.insert(format!("replica-read-{}", i + 1), replica_port);
+
+        if verbose {
+            println!(
+                "  {} Read-only replica {} on port {}",
+                "Started".green(),
+                i + 1,
+                replica_port
+            );
+        }
     }
 
+    let mut lb_ports = HashMap::new();
+    if let Some((ref lb_cmd, ref frontends)) = lb {
+        let lb_name = format!("{}-lb", name);
+        let container_id = policy
+            .run(
+                "Start of load balancer",
+                || async { Ok(server::run_container(lb_cmd, None).await?) },
+                || remove_container(&lb_name),
+            )
+            .await
+            .context("Failed to start load balancer")?;
+        container_ids.push(container_id);
+        ports_used.extend(frontends);
+        for (monitored_name, port) in monitored.iter().zip(frontends) {
+            lb_ports.insert(format!("lb-{}", monitored_name), *port);
+        }
+
+        if verbose {
+            println!(
+                "  {} Load balancer on port(s) {}",
+                "Started".green(),
+                frontends
+                    .iter()
+                    .map(u16::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        }
+    }
+
+    // Start RedisInsight on the Sentinel network if requested
+    let mut insight_port = None;
+    match insight {
+        Some(Insight::Shared(port)) => insight_port = Some(port),
+        Some(Insight::Own(ref insight_config)) => {
+            args.insight_port = insight_config.port;
+            match insight::start_insight(insight_config, verbose).await {
+                Ok(_) => {
+                    insight_port = Some(args.insight_port);
+                    container_ids.push(format!("{}-insight", name));
+                }
+                Err(e) => println!(
+                    "{} RedisInsight failed to start: {}",
+                    "Warning:".yellow(),
+                    e
+                ),
+            }
+        }
+        None => {}
+    }
+    let shared_insight = match insight {
+        Some(Insight::Shared(port)) => Some(port),
+        _ => None,
+    };
+
     // Save instance information
     let mut metadata = HashMap::new();
     metadata.insert("masters".to_string(), serde_json::json!(masters));
+    metadata.insert("master_names".to_string(), serde_json::json!(monitored));
     metadata.insert("sentinels".to_string(), serde_json::json!(sentinels));
+    let tunings: HashMap<&String, Tuning> = monitored.iter().map(|m| (m, tuning)).collect();
+    metadata.insert("tuning".to_string(), serde_json::json!(tunings));
     metadata.insert("network".to_string(), serde_json::json!(network_name));
+    metadata.insert("persist".to_string(), serde_json::json!(args.persist));
+    if args.ipv6 {
+        metadata.insert("ipv6".to_string(), serde_json::json!(true));
+    }
+    if args.with_readonly_port {
+        metadata.insert("readonly_replicas".to_string(), serde_json::json!(true));
+    }
+    if args.with_lb {
+        metadata.insert("lb".to_string(), serde_json::json!(true));
+    }
+    if let Some(ref memory) = args.memory {
+        metadata.insert("memory".to_string(), serde_json::json!(memory));
+    }
+    limits::record_limits(&mut metadata, &args.limits);
+    metadata.insert(
+        "insight".to_string(),
+        serde_json::json!(insight_port.is_some()),
+    );
+    if shared_insight.is_some() {
+        metadata.insert("shared_insight".to_string(), serde_json::json!(true));
+    }
     metadata.insert(
         "sentinel_containers".to_string(),
         serde_json::json!(sentinel_containers),
     );
 
-    let instance = InstanceInfo {
+    let mut instance = InstanceInfo {
         name: name.clone(),
         instance_type: InstanceType::Sentinel,
         created_at: chrono::Utc::now().to_rfc3339(),
@@ -192,12 +546,25 @@ async fn start_sentinel(args: SentinelStartArgs, verbose: bool) -> Result<()> {
             additional_ports: {
                 let mut ports = HashMap::new();
                 ports.insert("sentinel_base".to_string(), args.sentinel_port_base);
+                ports.extend(replica_ports.clone());
+                ports.extend(lb_ports.clone());
+                if let Some(port) = insight_port {
+                    ports.insert("redisinsight".to_string(), port);
+                }
                 ports
             },
+            ipv6_host: args.ipv6.then(|| "::1".to_string()),
+            ipv6_url: args
+                .ipv6
+                .then(|| format!("redis://:{}@[::1]:{}", password, args.redis_port_base)),
         },
         metadata,
+        started_at: None,
+        last_seen: None,
     };
 
+    annotate::set_description(&mut instance, args.description.as_deref());
+    history::record_start(&instance);
     config.add_instance(instance);
     config.save()?;
 
@@ -212,14 +579,76 @@ async fn start_sentinel(args: SentinelStartArgs, verbose: bool) -> Result<()> {
         password,
         args.redis_port_base
     );
+    if args.ipv6 {
+        println!(
+            "  {} redis://:{}@[::1]:{}",
+            "Master (IPv6):".cyan(),
+            password,
+            args.redis_port_base
+        );
+    }
     println!(
         "  {} localhost:{}",
         "Sentinel:".cyan(),
         args.sentinel_port_base
     );
+    for (label, port) in readonly_ports(&replica_ports) {
+        println!(
+            "  {} redis://:{}@localhost:{}",
+            format!("Read-only ({}):", label).cyan(),
+            password,
+            port
+        );
+    }
+    for monitored_name in &monitored {
+        if let Some(port) = lb_ports.get(&format!("lb-{}", monitored_name)) {
+            println!(
+                "  {} redis://:{}@localhost:{}",
+                format!("Load balancer ({}):", monitored_name).cyan(),
+                password,
+                port
+            );
+        }
+    }
     println!("\n{}", "Components:".bold().underline());
-    println!("  - {} Redis master(s)", masters);
+    println!(
+        "  - {} Redis master(s), monitored as {}",
+        masters,
+        monitored.join(", ")
+    );
     println!("  - {} Sentinel node(s)", sentinels);
+    if args.with_readonly_port {
+        println!("  - {} read-only replica(s), one per master", masters);
+    }
+    if args.with_lb {
+        println!("  - HAProxy load balancer, one port per master");
+    }
+
+    if let Some(port) = insight_port {
+        let connections = monitored
+            .iter()
+            .enumerate()
+            .map(|(i, monitored_name)| {
+                insight::create_redis_connection(
+                    format!("{}-master-{}", name, i + 1),
+                    format!("{}-sentinel-1", name),
+                    args.sentinel_port_base,
+                    Some(password.clone()),
+                    insight::ConnectionType::Sentinel {
+                        sentinel_port: args.sentinel_port_base,
+                        master_name: monitored_name.clone(),
+                    },
+                )
+            })
+            .collect();
+        if shared_insight.is_some() {
+            insight::register_with_shared(port, Some(&network_name), &[], connections, verbose)
+                .await;
+        } else {
+            insight::provision_connections(port, connections, verbose).await;
+        }
+    }
+
     println!("\n{}", "Quick Commands:".bold().underline());
     println!(
         "  {} redis-cli -p {} -a {}",
@@ -237,6 +666,450 @@ async fn start_sentinel(args: SentinelStartArgs, verbose: bool) -> Result<()> {
     Ok(())
 }
 
+/// Operations `start_sentinel` would perform
+pub async fn plan_start(args: &SentinelStartArgs, config: &mut Config) -> Result<Plan> {
+    let mut args = args.clone();
+    let launch = prepare(&mut args, config).await?;
+    let mut plan = Plan::new(format!("start sentinel setup {}", launch.name));
+    plan.platform = launch.platform.clone();
+    plan.hide(&launch.password);
+
+    plan.docker(&launch.network);
+    for (_, cmd) in launch
+        .master_cmds
+        .iter()
+        .chain(&launch.sentinel_cmds)
+        .chain(&launch.replica_cmds)
+    {
+        plan.run(images::REDIS_IMAGE, cmd, None);
+    }
+    if let Some((ref cmd, _)) = launch.lb {
+        plan.run(images::HAPROXY_IMAGE, cmd, None);
+    }
+    if let Some(ref insight) = launch.insight {
+        let network = format!("{}-network", launch.name);
+        insight.plan(&mut plan, Some(&network), &[]);
+    }
+    plan.push(Operation::SaveState {
+        description: format!("add {} to instances.json", launch.name),
+    });
+    Ok(plan)
+}
+
+/// Host ports to reserve for masters, their read-only replicas and load balancer
+fn redis_port_count(masters: usize, readonly: bool, lb: bool) -> usize {
+    masters * (1 + usize::from(readonly) + usize::from(lb))
+}
+
+/// First load balancer port, after the masters' and read-only replicas'
+fn lb_port_base(redis_port_base: u16, masters: usize, readonly: bool) -> u16 {
+    redis_port_base + redis_port_count(masters, readonly, false) as u16
+}
+
+/// HAProxy configuration, passed in the container's environment
+const LB_CONFIG_ENV: &str = "HAPROXY_CONFIG";
+
+/// HAProxy configuration with one frontend per master
+///
+/// Each backend lists the master and its read-only replica, if any, and a
+/// health check that only passes on the server reporting `role:master`, so
+/// traffic follows Sentinel failovers. Servers are resolved through Docker's
+/// DNS so restarted containers are picked up.
+fn lb_config(name: &str, ports: &[u16], replicas: bool, password: &str) -> String {
+    let mut config = String::from(
+        "global\n    maxconn 1024\n\n\
+         resolvers docker\n    nameserver dns 127.0.0.11:53\n\n\
+         defaults\n    mode tcp\n    timeout connect 3s\n    timeout client 1h\n    \
+         timeout server 1h\n    timeout check 2s\n    \
+         default-server init-addr last,libc,none resolvers docker check inter 1s\n",
+    );
+    for (i, port) in ports.iter().enumerate() {
+        let n = i + 1;
+        config.push_str(&format!(
+            "\nfrontend master-{n}\n    bind :{port}\n    default_backend master-{n}\n\n\
+             backend master-{n}\n    option tcp-check\n    tcp-check connect\n    \
+             tcp-check send \"AUTH {password}\\r\\n\"\n    tcp-check expect string +OK\n    \
+             tcp-check send \"INFO replication\\r\\n\"\n    tcp-check expect string role:master\n    \
+             tcp-check send \"QUIT\\r\\n\"\n    tcp-check expect string +OK\n    \
+             server master-{n} {name}-master-{n}:6379\n"
+        ));
+        if replicas {
+            config.push_str(&format!("    server replica-{n} {name}-replica-{n}:6379\n"));
+        }
+    }
+    config
+}
+
+/// Write the HAProxy configuration from the environment and start HAProxy
+fn lb_command() -> Vec<String> {
+    vec![
+        "sh".to_string(),
+        "-c".to_string(),
+        format!(
+            "printf '%s' \"${}\" > /tmp/haproxy.cfg && exec haproxy -f /tmp/haproxy.cfg",
+            LB_CONFIG_ENV
+        ),
+    ]
+}
+
+/// Command for a read-only replica of one master
+fn replica_command(master: &str, password: &str) -> Vec<String> {
+    [
+        "redis-server",
+        "--requirepass",
+        password,
+        "--masterauth",
+        password,
+        "--replicaof",
+        master,
+        "6379",
+        "--replica-read-only",
+        "yes",
+    ]
+    .iter()
+    .map(|arg| arg.to_string())
+    .collect()
+}
+
+/// `replica-read-N` ports in replica order
+pub fn readonly_ports(ports: &HashMap<String, u16>) -> Vec<(String, u16)> {
+    let mut readonly: Vec<(usize, String, u16)> = ports
+        .iter()
+        .filter_map(|(label, port)| {
+            let n = label.strip_prefix("replica-read-")?.parse().ok()?;
+            Some((n, label.clone(), *port))
+        })
+        .collect();
+    readonly.sort();
+    readonly
+        .into_iter()
+        .map(|(_, label, port)| (label, port))
+        .collect()
+}
+
+/// Sentinel configuration, passed in the container's environment
+const CONFIG_ENV: &str = "SENTINEL_CONFIG";
+
+/// Where the configuration lives inside the Sentinel container
+const CONFIG_PATH: &str = "/etc/redis/sentinel.conf";
+
+/// Configuration for one Sentinel monitoring every master
+fn sentinel_config(
+    port: u16,
+    name: &str,
+    monitored: &[String],
+    redis_port_base: u16,
+    tuning: &Tuning,
+    password: &str,
+) -> String {
+    let mut config = String::new();
+    config.push_str(&format!("port {}\n", port));
+    config.push_str("sentinel announce-hostnames yes\n");
+    config.push_str("sentinel resolve-hostnames yes\n");
+
+    for (j, monitored_name) in monitored.iter().enumerate() {
+        let master_name = format!("{}-master-{}", name, j + 1);
+        let master_port = redis_port_base + j as u16;
+        config.push_str(&format!(
+            "sentinel monitor {} {} {} {}\n",
+            monitored_name, master_name, master_port, tuning.quorum
+        ));
+        if !password.is_empty() {
+            config.push_str(&format!(
+                "sentinel auth-pass {} {}\n",
+                monitored_name, password
+            ));
+        }
+        // The quorum is part of the monitor line
+        for (option, value) in &tuning.options()[1..] {
+            config.push_str(&format!(
+                "sentinel {} {} {}\n",
+                option, monitored_name, value
+            ));
+        }
+    }
+    config
+}
+
+/// Write the configuration inside the container, then run Sentinel on it
+///
+/// Nothing is written on the host, so this works against remote Docker hosts
+/// and leaves no files behind. The file is only written on first boot: after a
+/// restart Sentinel keeps the state it rewrote into it, such as a failover.
+fn sentinel_command() -> Vec<String> {
+    vec![
+        "sh".to_string(),
+        "-c".to_string(),
+        format!(
+            "mkdir -p $(dirname {path}) && ([ -f {path} ] || printf '%s' \"${env}\" > {path}) && exec redis-sentinel {path}",
+            path = CONFIG_PATH,
+            env = CONFIG_ENV
+        ),
+    ]
+}
+
+/// Masters to start: `--masters`, or more when more `--master-name`s are given
+pub fn master_count(masters: usize, names: &[String]) -> usize {
+    masters.max(names.len()).max(1)
+}
+
+/// Names Sentinel monitors the masters under: the given names in order, then `master-N`
+pub fn master_names(masters: usize, names: &[String]) -> Vec<String> {
+    (0..masters)
+        .map(|i| {
+            names
+                .get(i)
+                .cloned()
+                .unwrap_or_else(|| format!("master-{}", i + 1))
+        })
+        .collect()
+}
+
+fn validate_quorum(quorum: usize, sentinels: usize) -> Result<()> {
+    if quorum == 0 || quorum > sentinels {
+        anyhow::bail!(
+            "Invalid quorum {}: it must be between 1 and the number of sentinels ({})",
+            quorum,
+            sentinels
+        );
+    }
+    Ok(())
+}
+
+/// Tuning per monitored master, as last started or tuned
+pub fn tuning(instance: &InstanceInfo) -> HashMap<String, Tuning> {
+    let mut tunings: HashMap<String, Tuning> = instance
+        .metadata
+        .get("tuning")
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+        .unwrap_or_default();
+    let default = Tuning::default_for(sentinel_count(instance));
+    for master in monitored_names(instance) {
+        tunings.entry(master).or_insert(default);
+    }
+    tunings
+}
+
+fn sentinel_count(instance: &InstanceInfo) -> usize {
+    instance
+        .metadata
+        .get("sentinels")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(1) as usize
+}
+
+fn validate_master_names(names: &[String]) -> Result<()> {
+    for (i, name) in names.iter().enumerate() {
+        if name.is_empty() || name.chars().any(char::is_whitespace) {
+            anyhow::bail!(
+                "Invalid master name '{}': names can't be empty or contain spaces",
+                name
+            );
+        }
+        if names[..i].contains(name) {
+            anyhow::bail!("Master name '{}' is given more than once", name);
+        }
+    }
+    Ok(())
+}
+
+/// Names a setup's masters are monitored under, in master order
+///
+/// Setups started before names were recorded use `master-1..N`.
+pub fn monitored_names(instance: &InstanceInfo) -> Vec<String> {
+    if let Some(names) = instance
+        .metadata
+        .get("master_names")
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+    {
+        return names;
+    }
+    let masters = instance
+        .metadata
+        .get("masters")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(1) as usize;
+    master_names(masters, &[])
+}
+
+/// Sentinel endpoints and credentials for one monitored master (default: the first)
+pub fn sentinel_target(instance: &InstanceInfo, master: Option<&str>) -> Result<SentinelTarget> {
+    let monitored = monitored_names(instance);
+    let master_name = match master {
+        Some(master) if monitored.iter().any(|m| m == master) => master.to_string(),
+        Some(master) => anyhow::bail!(
+            "'{}' monitors no master named '{}' (available: {})",
+            instance.name,
+            master,
+            monitored.join(", ")
+        ),
+        None => monitored.first().cloned().context("No monitored masters")?,
+    };
+
+    let base = instance
+        .connection_info
+        .additional_ports
+        .get("sentinel_base")
+        .copied()
+        .unwrap_or(26379);
+    let count = instance
+        .metadata
+        .get("sentinels")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(1) as u16;
+
+    Ok(SentinelTarget {
+        master_name,
+        sentinels: (0..count)
+            .map(|i| (instance.connection_info.host.clone(), base + i))
+            .collect(),
+        password: instance.connection_info.password.clone(),
+    })
+}
+
+async fn url_sentinel(args: SentinelUrlArgs) -> Result<()> {
+    let config = Config::load()?;
+    let name = args
+        .name
+        .or_else(|| {
+            config
+                .get_latest_instance(&InstanceType::Sentinel)
+                .map(|i| i.name.clone())
+        })
+        .context("No Sentinel instance found. Specify a name or start one first.")?;
+    let instance = config
+        .get_instance(&name)
+        .filter(|i| i.instance_type == InstanceType::Sentinel)
+        .with_context(|| format!("Sentinel instance '{}' not found", name))?;
+
+    let target = sentinel_target(instance, args.master.as_deref())?;
+
+    let clients = match args.client {
+        Some(client) => vec![client],
+        None => vec![
+            SentinelClient::Ioredis,
+            SentinelClient::Lettuce,
+            SentinelClient::GoRedis,
+            SentinelClient::RedisPy,
+        ],
+    };
+
+    if args.client.is_none() {
+        println!("{} {}\n", "Sentinel URL:".cyan(), target.uri());
+    }
+    for client in clients {
+        if args.client.is_none() {
+            println!("{}", client.to_string().bold().underline());
+        }
+        println!("{}", snippets::sentinel(client, &target));
+    }
+    if args.client.is_none() {
+        println!(
+            "{} Sentinel reports masters by container hostname ({}-master-N); outside Docker, map those names to 127.0.0.1",
+            "Note:".dimmed(),
+            instance.name
+        );
+    }
+
+    Ok(())
+}
+
+async fn tune_sentinel(args: SentinelTuneArgs) -> Result<()> {
+    let mut config = Config::load()?;
+    let name = args
+        .name
+        .or_else(|| {
+            config
+                .get_latest_instance(&InstanceType::Sentinel)
+                .map(|i| i.name.clone())
+        })
+        .context("No Sentinel instance found. Specify a name or start one first.")?;
+    let instance = config
+        .get_instance(&name)
+        .filter(|i| i.instance_type == InstanceType::Sentinel)
+        .with_context(|| format!("Sentinel instance '{}' not found", name))?
+        .clone();
+
+    let monitored = monitored_names(&instance);
+    let masters = match args.master {
+        Some(master) if monitored.contains(&master) => vec![master],
+        Some(master) => anyhow::bail!(
+            "'{}' monitors no master named '{}' (available: {})",
+            name,
+            master,
+            monitored.join(", ")
+        ),
+        None => monitored,
+    };
+    let sentinels = sentinel_count(&instance);
+    if let Some(quorum) = args.quorum {
+        validate_quorum(quorum, sentinels)?;
+    }
+    let base = instance
+        .connection_info
+        .additional_ports
+        .get("sentinel_base")
+        .copied()
+        .unwrap_or(26379);
+
+    let mut tunings = tuning(&instance);
+    for master in &masters {
+        let current = tunings[master];
+        let tuned = Tuning {
+            quorum: args.quorum.unwrap_or(current.quorum),
+            down_after_ms: args.down_after_ms.unwrap_or(current.down_after_ms),
+            failover_timeout: args.failover_timeout.unwrap_or(current.failover_timeout),
+            parallel_syncs: args.parallel_syncs.unwrap_or(current.parallel_syncs),
+        };
+        let changed: Vec<(&str, String)> = tuned
+            .options()
+            .into_iter()
+            .zip(current.options())
+            .filter(|(new, old)| new != old)
+            .map(|(new, _)| new)
+            .collect();
+        if changed.is_empty() {
+            println!("  {} {} is already tuned so", "Skipped:".dimmed(), master);
+            continue;
+        }
+
+        // Each sentinel keeps its own copy of the settings
+        let mut command = vec!["SENTINEL", "SET", master.as_str()];
+        for (option, value) in &changed {
+            command.extend([*option, value.as_str()]);
+        }
+        for i in 0..sentinels {
+            let container = format!("{}-sentinel-{}", name, i + 1);
+            let port = (base + i as u16).to_string();
+            let mut cli_args = vec!["-p", port.as_str()];
+            cli_args.extend(&command);
+            server::redis_cli(&container, None, &cli_args)
+                .await
+                .with_context(|| format!("Failed to tune {} on {}", master, container))?;
+        }
+        tunings.insert(master.clone(), tuned);
+
+        println!(
+            "{} Tuned {} on {} sentinel(s): {}",
+            "Success:".green(),
+            master.bold(),
+            sentinels,
+            changed
+                .iter()
+                .map(|(option, value)| format!("{} {}", option, value))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+
+    if let Some(instance) = config.instances.get_mut(&name) {
+        instance
+            .metadata
+            .insert("tuning".to_string(), serde_json::json!(tunings));
+    }
+    config.save()
+}
+
 async fn stop_sentinel(args: StopArgs, verbose: bool) -> Result<()> {
     let mut config = Config::load()?;
 
@@ -263,18 +1136,23 @@ async fn stop_sentinel(args: StopArgs, verbose: bool) -> Result<()> {
         );
     }
 
-    // Stop all containers
-    use docker_wrapper::{RmCommand, StopCommand};
+    // Sentinels go first so they don't fail over masters that are shutting down
+    // Errors are ignored for already stopped containers
+    shutdown::stop_containers(&instance, args.timeout, verbose).await;
+
+    let keep_data = volume::keep_data(&instance, args.keep_data);
     for container_id in &instance.containers {
         // Extract container name from ID (if needed)
         let container_name = container_id.split(':').next().unwrap_or(container_id);
 
-        StopCommand::new(container_name).execute().await.ok(); // Ignore errors for already stopped containers
-
-        RmCommand::new(container_name).force().execute().await.ok();
+        volume::remove_command(container_name, keep_data)
+            .execute()
+            .await
+            .ok();
     }
 
     // Remove network
+    insight::detach_shared(&instance).await;
     if let Some(network) = instance.metadata.get("network") {
         if let Some(network_name) = network.as_str() {
             use docker_wrapper::NetworkRmCommand;
@@ -283,7 +1161,9 @@ async fn stop_sentinel(args: StopArgs, verbose: bool) -> Result<()> {
     }
 
     // Remove from config
-    config.instances.remove(&name);
+    if let Some(removed) = config.remove_instance(&name) {
+        history::record_stop(&removed);
+    }
     config.save()?;
 
     println!(
@@ -291,12 +1171,13 @@ async fn stop_sentinel(args: StopArgs, verbose: bool) -> Result<()> {
         "Success:".green().bold(),
         name
     );
+    volume::report_kept(&name, keep_data);
 
     Ok(())
 }
 
-async fn info_sentinel(args: InfoArgs, verbose: bool) -> Result<()> {
-    let config = Config::load()?;
+async fn info_sentinel(args: InfoArgs, master: Option<String>, verbose: bool) -> Result<()> {
+    let mut config = Config::load()?;
 
     // Find the instance
     let name = args.name.or_else(|| {
@@ -307,14 +1188,33 @@ async fn info_sentinel(args: InfoArgs, verbose: bool) -> Result<()> {
 
     let name = name.context("No Sentinel instance found. Specify a name or start one first.")?;
 
+    let uptime_lines = uptime::info_lines(&mut config, &name).await;
     let instance = config
         .instances
         .get(&name)
         .context(format!("Sentinel instance '{}' not found", name))?;
 
+    let monitored = monitored_names(instance);
+    if let Some(ref master) = master {
+        if !monitored.contains(master) {
+            anyhow::bail!(
+                "'{}' monitors no master named '{}' (available: {})",
+                name,
+                master,
+                monitored.join(", ")
+            );
+        }
+    }
+
     println!("{}", "Redis Sentinel Information".bold().underline());
     println!("{} {}", "Name:".cyan(), instance.name);
     println!("{} {}", "Created:".cyan(), instance.created_at);
+    for (label, value) in &uptime_lines {
+        println!("{} {}", format!("{}:", label).cyan(), value);
+    }
+    for (label, value) in annotate::annotations(instance) {
+        println!("{} {}", format!("{}:", label).cyan(), value);
+    }
     println!(
         "{} {} masters, {} sentinels",
         "Configuration:".cyan(),
@@ -344,19 +1244,95 @@ async fn info_sentinel(args: InfoArgs, verbose: bool) -> Result<()> {
         println!("  - {}", port);
     }
 
+    // Master ports come first in the port list, in master order
+    println!("\n{}", "Masters:".bold().underline());
+    let tunings = tuning(instance);
+    for (monitored_name, port) in monitored.iter().zip(&instance.ports) {
+        if master.as_ref().is_some_and(|m| m != monitored_name) {
+            continue;
+        }
+        println!(
+            "  {} localhost:{}",
+            format!("{}:", monitored_name).cyan(),
+            port
+        );
+        if let Some(tuning) = tunings.get(monitored_name) {
+            println!(
+                "    {}",
+                format!(
+                    "quorum {}, down after {}ms, failover timeout {}ms, parallel syncs {}",
+                    tuning.quorum,
+                    tuning.down_after_ms,
+                    tuning.failover_timeout,
+                    tuning.parallel_syncs
+                )
+                .dimmed()
+            );
+        }
+    }
+
     println!("\n{}", "Connection:".bold().underline());
     println!(
         "  {} {}",
         "Master URL:".cyan(),
         instance.connection_info.url
     );
-    if let Some(sentinel_port) = instance
+    let sentinel_port = instance
         .connection_info
         .additional_ports
         .get("sentinel_base")
-    {
+        .copied();
+    if let Some(sentinel_port) = sentinel_port {
         println!("  {} localhost:{}", "Sentinel:".cyan(), sentinel_port);
     }
+    // A failover can promote these, after which they take writes
+    for (label, port) in readonly_ports(&instance.connection_info.additional_ports) {
+        println!(
+            "  {} localhost:{}",
+            format!("Read-only ({}):", label).cyan(),
+            port
+        );
+    }
+    for monitored_name in &monitored {
+        if let Some(port) = instance
+            .connection_info
+            .additional_ports
+            .get(&format!("lb-{}", monitored_name))
+        {
+            println!(
+                "  {} localhost:{}",
+                format!("Load balancer ({}):", monitored_name).cyan(),
+                port
+            );
+        }
+    }
+    if let Ok(target) = sentinel_target(instance, master.as_deref()) {
+        println!("  {} {}", "Sentinel URL:".cyan(), target.uri());
+        println!(
+            "  {} redis-up sentinel url {}",
+            "Client config:".cyan(),
+            instance.name
+        );
+    }
+    if let Some(insight_port) = instance
+        .connection_info
+        .additional_ports
+        .get("redisinsight")
+    {
+        println!(
+            "  {} http://localhost:{}",
+            "RedisInsight:".cyan(),
+            insight_port
+        );
+    }
+
+    let limit_lines = limits::limit_lines(&instance.metadata);
+    if !limit_lines.is_empty() {
+        println!("\n{}", "Limits:".bold().underline());
+        for (label, value) in limit_lines {
+            println!("  {} {}", format!("{}:", label).cyan(), value);
+        }
+    }
 
     if verbose {
         println!("\n{}", "Containers:".bold().underline());
@@ -371,18 +1347,17 @@ async fn info_sentinel(args: InfoArgs, verbose: bool) -> Result<()> {
             if !containers.is_empty() {
                 if let Some(first_sentinel) = containers.first().and_then(|v| v.as_str()) {
                     use docker_wrapper::{DockerCommand, ExecCommand};
-                    let status = ExecCommand::new(
-                        first_sentinel,
-                        vec![
-                            "redis-cli".to_string(),
-                            "-p".to_string(),
-                            "26379".to_string(),
-                            "sentinel".to_string(),
-                            "masters".to_string(),
-                        ],
-                    )
-                    .execute()
-                    .await;
+                    let mut command = vec![
+                        "redis-cli".to_string(),
+                        "-p".to_string(),
+                        sentinel_port.unwrap_or(26379).to_string(),
+                        "sentinel".to_string(),
+                    ];
+                    match master {
+                        Some(ref master) => command.extend(["master".to_string(), master.clone()]),
+                        None => command.push("masters".to_string()),
+                    }
+                    let status = ExecCommand::new(first_sentinel, command).execute().await;
 
                     if let Ok(result) = status {
                         if !result.stdout.is_empty() {
@@ -415,3 +1390,20 @@ async fn info_sentinel(args: InfoArgs, verbose: bool) -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_master_names() {
+        assert_eq!(master_names(2, &[]), vec!["master-1", "master-2"]);
+        assert_eq!(
+            master_names(2, &["mymaster".to_string()]),
+            vec!["mymaster", "master-2"]
+        );
+        assert!(validate_master_names(&["a".to_string(), "a".to_string()]).is_err());
+        assert!(validate_master_names(&["my master".to_string()]).is_err());
+        assert!(validate_master_names(&["mymaster".to_string()]).is_ok());
+    }
+}
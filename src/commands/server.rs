@@ -0,0 +1,623 @@
+//! Redis server configuration shared by start commands
+//!
+//! Settings are collected as `(directive, value)` pairs so they can either be
+//! passed to `redis-server` at boot or applied to running nodes via CONFIG SET.
+
+use anyhow::{Context, Result};
+use colored::*;
+use docker_wrapper::{
+    CommandExecutor, DockerCommand, ExecCommand, RedisTemplate, RunCommand, Template,
+};
+use std::collections::{BTreeMap, HashMap};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use crate::cli::{AppendFsync, CommandArgs, PersistenceMode};
+use crate::commands::{images, passthrough};
+use crate::config::{generate_password, InstanceInfo};
+
+/// Redis default RDB save points, used when `--save` is not given
+const DEFAULT_SAVE: &str = "3600 1 300 100 60 10000";
+
+/// A single redis-server configuration directive
+pub type Directive = (String, String);
+
+/// Commands `--hardened` disables; it also renames CONFIG
+const HARDENED_DISABLED: [&str; 4] = ["DEBUG", "FLUSHALL", "FLUSHDB", "KEYS"];
+
+/// Build the directives for the requested persistence mode
+pub fn persistence_directives(
+    mode: Option<PersistenceMode>,
+    save: Option<&str>,
+    appendfsync: Option<AppendFsync>,
+) -> Result<Vec<Directive>> {
+    let Some(mode) = mode else {
+        if save.is_some() || appendfsync.is_some() {
+            anyhow::bail!("--save and --appendfsync require --persistence to be set");
+        }
+        return Ok(Vec::new());
+    };
+
+    let rdb = matches!(mode, PersistenceMode::Rdb | PersistenceMode::Both);
+    let aof = matches!(mode, PersistenceMode::Aof | PersistenceMode::Both);
+
+    if save.is_some() && !rdb {
+        anyhow::bail!("--save requires --persistence rdb or both (got {})", mode);
+    }
+    if appendfsync.is_some() && !aof {
+        anyhow::bail!(
+            "--appendfsync requires --persistence aof or both (got {})",
+            mode
+        );
+    }
+
+    let mut directives = vec![(
+        "appendonly".to_string(),
+        if aof { "yes" } else { "no" }.to_string(),
+    )];
+
+    let save_points = if rdb {
+        save.unwrap_or(DEFAULT_SAVE).trim().to_string()
+    } else {
+        String::new()
+    };
+    directives.push(("save".to_string(), save_points));
+
+    if aof {
+        directives.push((
+            "appendfsync".to_string(),
+            appendfsync.unwrap_or(AppendFsync::Everysec).to_string(),
+        ));
+    }
+
+    Ok(directives)
+}
+
+/// The `databases` directive for `--databases`
+///
+/// `databases` cannot be changed with CONFIG SET, so it only applies at boot.
+pub fn databases_directive(databases: Option<u32>) -> Option<Directive> {
+    databases.map(|n| ("databases".to_string(), n.to_string()))
+}
+
+/// New command names from `--hardened` and `--rename-command`, by command
+///
+/// An empty name disables the command. Explicit renames win over the preset.
+pub fn command_renames(args: &CommandArgs) -> Result<BTreeMap<String, String>> {
+    let mut renames = BTreeMap::new();
+    if args.hardened {
+        for command in HARDENED_DISABLED {
+            renames.insert(command.to_string(), String::new());
+        }
+        renames.insert(
+            "CONFIG".to_string(),
+            format!("CONFIG_{}", generate_password()),
+        );
+    }
+    for spec in &args.rename_commands {
+        let Some((command, name)) = spec.split_once('=') else {
+            anyhow::bail!(
+                "--rename-command takes CMD=NEW, or CMD= to disable the command (got '{}')",
+                spec
+            );
+        };
+        let command = command.trim().to_uppercase();
+        let name = name.trim();
+        if command.is_empty() || command.contains(char::is_whitespace) {
+            anyhow::bail!("--rename-command needs a command name (got '{}')", spec);
+        }
+        if name.contains(char::is_whitespace) || name == "\"\"" {
+            anyhow::bail!(
+                "New name for {} must be a single word, or empty to disable it",
+                command
+            );
+        }
+        renames.insert(command, name.to_string());
+    }
+    Ok(renames)
+}
+
+/// `rename-command` directives; disabled commands get the quoted empty name
+pub fn rename_directives(renames: &BTreeMap<String, String>) -> Vec<Directive> {
+    renames
+        .iter()
+        .map(|(command, name)| {
+            let name = if name.is_empty() { "\"\"" } else { name };
+            (
+                "rename-command".to_string(),
+                format!("{} {}", command, name),
+            )
+        })
+        .collect()
+}
+
+/// Record command renames in instance metadata
+pub fn record_renames(
+    metadata: &mut HashMap<String, serde_json::Value>,
+    renames: &BTreeMap<String, String>,
+) {
+    if !renames.is_empty() {
+        metadata.insert("renamed_commands".to_string(), serde_json::json!(renames));
+    }
+}
+
+/// Command renames recorded for an instance
+pub fn recorded_renames(metadata: &HashMap<String, serde_json::Value>) -> BTreeMap<String, String> {
+    metadata
+        .get("renamed_commands")
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+        .unwrap_or_default()
+}
+
+/// `FLUSHALL disabled, CONFIG as CONFIG_x` for start and info output
+pub fn describe_renames(renames: &BTreeMap<String, String>) -> String {
+    let disabled: Vec<&str> = renames
+        .iter()
+        .filter(|(_, name)| name.is_empty())
+        .map(|(command, _)| command.as_str())
+        .collect();
+    let mut parts: Vec<String> = renames
+        .iter()
+        .filter(|(_, name)| !name.is_empty())
+        .map(|(command, name)| format!("{} as {}", command, name))
+        .collect();
+    if !disabled.is_empty() {
+        parts.insert(0, format!("{} disabled", disabled.join(", ")));
+    }
+    parts.join("; ")
+}
+
+/// The name a command goes by on an instance, failing when it is disabled
+pub fn command_name(instance: &InstanceInfo, command: &str) -> Result<String> {
+    match recorded_renames(&instance.metadata).get(command) {
+        None => Ok(command.to_string()),
+        Some(name) if name.is_empty() => {
+            anyhow::bail!("{} is disabled on '{}'", command, instance.name)
+        }
+        Some(name) => Ok(name.clone()),
+    }
+}
+
+/// Record the persistence settings in instance metadata
+pub fn record_persistence(
+    metadata: &mut HashMap<String, serde_json::Value>,
+    mode: Option<PersistenceMode>,
+    save: Option<&str>,
+    appendfsync: Option<AppendFsync>,
+) {
+    if let Some(mode) = mode {
+        metadata.insert(
+            "persistence".to_string(),
+            serde_json::json!(mode.to_string()),
+        );
+    }
+    if let Some(save) = save {
+        metadata.insert("save".to_string(), serde_json::json!(save));
+    }
+    if let Some(appendfsync) = appendfsync {
+        metadata.insert(
+            "appendfsync".to_string(),
+            serde_json::json!(appendfsync.to_string()),
+        );
+    }
+}
+
+/// Convert directives into redis-server command line arguments
+///
+/// A `""` word stands for an empty argument, as in `rename-command KEYS ""`.
+pub fn to_cli_args(directives: &[Directive]) -> Vec<String> {
+    let mut args = Vec::new();
+    for (key, value) in directives {
+        args.push(format!("--{}", key));
+        if value.is_empty() {
+            args.push(String::new());
+        } else {
+            args.extend(value.split_whitespace().map(|v| match v {
+                "\"\"" => String::new(),
+                v => v.to_string(),
+            }));
+        }
+    }
+    args
+}
+
+/// Build the run command for a basic or stack template with extra directives
+///
+/// Without directives this is exactly the template's own command. Without a
+/// password the image's default of protected mode off is kept, since protected
+/// mode would refuse connections arriving through Docker's port forwarding.
+pub fn build_run_command(
+    template: &RedisTemplate,
+    password: Option<&str>,
+    stack: bool,
+    directives: &[Directive],
+) -> RunCommand {
+    let cmd = passthrough::apply(template.build_command());
+    if directives.is_empty() {
+        return cmd;
+    }
+
+    if stack {
+        // Redis Stack reads extra server arguments from REDIS_ARGS
+        let mut redis_args: Vec<String> = password
+            .map(|password| format!("--requirepass {}", password))
+            .into_iter()
+            .collect();
+        for (key, value) in directives {
+            if value.is_empty() {
+                redis_args.push(format!("--{} \"\"", key));
+            } else {
+                redis_args.push(format!("--{} {}", key, value));
+            }
+        }
+        cmd.env("REDIS_ARGS", redis_args.join(" "))
+    } else {
+        let mut args = Vec::new();
+        if let Some(password) = password {
+            args.extend([
+                "--requirepass".to_string(),
+                password.to_string(),
+                "--protected-mode".to_string(),
+                "yes".to_string(),
+            ]);
+        }
+        args.extend(to_cli_args(directives));
+        cmd.cmd(args)
+    }
+}
+
+/// redis-cli command line for [`redis_cli`]
+///
+/// It runs through `sh` so servers on the host's network, which listen on
+/// their host port rather than 6379, are reached through [`PORT_ENV`]. A
+/// `-p` in `args` comes later and still wins.
+fn cli_command(password: Option<&str>, args: &[&str]) -> Vec<String> {
+    let mut command = vec![
+        "sh".to_string(),
+        "-c".to_string(),
+        format!("exec redis-cli -p \"${{{}:-6379}}\" \"$@\"", PORT_ENV),
+        "redis-cli".to_string(),
+    ];
+    if let Some(password) = password {
+        command.extend([
+            "-a".to_string(),
+            password.to_string(),
+            "--no-auth-warning".to_string(),
+        ]);
+    }
+    command.extend(args.iter().map(|a| a.to_string()));
+    command
+}
+
+/// Environment variable with the port a host-networked server listens on
+pub const PORT_ENV: &str = "REDIS_UP_PORT";
+
+/// Directives that make a host-networked server listen on its host port
+///
+/// Nothing is published on the host's network, so `bind` (such as the
+/// loopback address `--no-auth` defaults to) becomes the server's own bind.
+pub fn host_network_directives(port: u16, bind: Option<IpAddr>) -> Vec<Directive> {
+    let mut directives = vec![("port".to_string(), port.to_string())];
+    if let Some(ip) = bind {
+        directives.push(("bind".to_string(), ip.to_string()));
+    }
+    directives
+}
+
+/// Warn that host networking outside Linux means the Docker VM's network
+pub fn warn_host_network() {
+    if !cfg!(target_os = "linux") {
+        println!(
+            "{} --network-mode host attaches to the Docker VM's network; clients on this machine may not reach it",
+            "Warning:".yellow()
+        );
+    }
+}
+
+/// Run `cmd` on the host's network, where Docker discards published ports
+pub fn use_host_network(cmd: RunCommand, port: u16) -> RunCommand {
+    cmd.network("host")
+        .env(PORT_ENV, port.to_string())
+        .health_cmd(format!("redis-cli -p {} ping", port))
+}
+
+/// Host address to publish on, keeping `--no-auth` instances off the network
+///
+/// Without a password the port goes to loopback unless `--bind` is given, and
+/// a non-loopback address is refused unless `--i-know-what-im-doing` is set.
+pub fn exposure_bind(
+    no_auth: bool,
+    bind: Option<IpAddr>,
+    ipv6: bool,
+    allow_exposed: bool,
+) -> Result<Option<IpAddr>> {
+    if !no_auth {
+        return Ok(bind);
+    }
+    match bind {
+        None if ipv6 => Ok(Some(IpAddr::V6(Ipv6Addr::LOCALHOST))),
+        None => Ok(Some(IpAddr::V4(Ipv4Addr::LOCALHOST))),
+        Some(ip) if ip.is_loopback() => Ok(Some(ip)),
+        Some(ip) => {
+            let danger = format!(
+                "--no-auth with --bind {} lets anyone who can reach this host read, change and delete its data",
+                ip
+            );
+            if !allow_exposed {
+                anyhow::bail!(
+                    "{}. Bind to 127.0.0.1, set a password, or pass --i-know-what-im-doing",
+                    danger
+                );
+            }
+            eprintln!("{} {}", "WARNING:".bold().red(), danger.bold());
+            Ok(Some(ip))
+        }
+    }
+}
+
+/// Run a container, optionally binding its published ports to one host address
+///
+/// Returns the new container's ID.
+pub async fn run_container(
+    cmd: &RunCommand,
+    bind: Option<IpAddr>,
+) -> docker_wrapper::Result<String> {
+    let mut args = run_args(cmd, bind);
+    let command = args.remove(0);
+    let output = CommandExecutor::new()
+        .execute_command(&command, args)
+        .await?;
+    Ok(output.stdout.trim().to_string())
+}
+
+/// Arguments `run_container` passes to docker, starting with `run`
+pub fn run_args(cmd: &RunCommand, bind: Option<IpAddr>) -> Vec<String> {
+    let mut args = passthrough::run_args(cmd);
+    if let Some(ip) = bind {
+        args = bind_published_ports(args, ip);
+    }
+    // Templates pick their default image; run the configured one instead
+    images::resolve_args(&mut args);
+    args
+}
+
+/// Prefix `--publish host:container` arguments with a host address
+pub fn bind_published_ports(args: Vec<String>, ip: IpAddr) -> Vec<String> {
+    let host = match ip {
+        IpAddr::V4(v4) => v4.to_string(),
+        IpAddr::V6(v6) => format!("[{}]", v6),
+    };
+
+    let mut bound = Vec::with_capacity(args.len());
+    let mut publish_value = false;
+    for arg in args {
+        if publish_value && arg.matches(':').count() == 1 {
+            bound.push(format!("{}:{}", host, arg));
+        } else {
+            bound.push(arg.clone());
+        }
+        publish_value = arg == "--publish" || arg == "-p";
+    }
+    bound
+}
+
+/// Host clients should use to reach ports bound to `bind`
+pub fn connect_host(bind: Option<IpAddr>) -> String {
+    match bind {
+        Some(ip) if !ip.is_unspecified() => ip.to_string(),
+        _ => "localhost".to_string(),
+    }
+}
+
+/// IPv6 host for an instance started with `--ipv6`
+pub fn ipv6_host(ipv6: bool, bind: Option<IpAddr>) -> Result<Option<String>> {
+    if !ipv6 {
+        return Ok(None);
+    }
+    match bind {
+        Some(IpAddr::V4(ip)) => anyhow::bail!(
+            "--ipv6 cannot be combined with IPv4 bind address {} (use --bind :: for all interfaces)",
+            ip
+        ),
+        Some(IpAddr::V6(ip)) if !ip.is_unspecified() => Ok(Some(ip.to_string())),
+        _ => Ok(Some("::1".to_string())),
+    }
+}
+
+/// Build a redis:// URL for a host, bracketing IPv6 addresses
+pub fn redis_url(password: Option<&str>, host: &str, port: u16) -> String {
+    let auth = password
+        .map(|password| format!("default:{}@", password))
+        .unwrap_or_default();
+    if host.contains(':') {
+        format!("redis://{}[{}]:{}", auth, host, port)
+    } else {
+        format!("redis://{}{}:{}", auth, host, port)
+    }
+}
+
+/// Run a redis-cli command inside a container and return its output
+///
+/// redis-cli exits successfully even when the server replies with an error,
+/// so error replies are turned into errors here.
+pub async fn redis_cli(container: &str, password: Option<&str>, args: &[&str]) -> Result<String> {
+    let output = ExecCommand::new(container, cli_command(password, args))
+        .execute()
+        .await
+        .with_context(|| format!("Failed to run redis-cli in {}", container))?;
+
+    let reply = output.stdout.trim();
+    let is_error = ["ERR", "WRONGPASS", "NOAUTH", "NOPERM", "WRONGTYPE"]
+        .iter()
+        .any(|prefix| reply.starts_with(prefix));
+    if is_error {
+        anyhow::bail!("{}", reply);
+    }
+
+    Ok(output.stdout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_persistence_mode() {
+        assert!(persistence_directives(None, None, None).unwrap().is_empty());
+        assert!(persistence_directives(None, Some("60 1000"), None).is_err());
+    }
+
+    #[test]
+    fn test_persistence_modes() {
+        let rdb =
+            persistence_directives(Some(PersistenceMode::Rdb), Some("60 1000"), None).unwrap();
+        assert_eq!(
+            to_cli_args(&rdb),
+            vec!["--appendonly", "no", "--save", "60", "1000"]
+        );
+
+        let aof = persistence_directives(Some(PersistenceMode::Aof), None, None).unwrap();
+        assert_eq!(
+            to_cli_args(&aof),
+            vec![
+                "--appendonly",
+                "yes",
+                "--save",
+                "",
+                "--appendfsync",
+                "everysec"
+            ]
+        );
+
+        let none = persistence_directives(Some(PersistenceMode::None), None, None).unwrap();
+        assert_eq!(to_cli_args(&none), vec!["--appendonly", "no", "--save", ""]);
+    }
+
+    #[test]
+    fn test_cli_command() {
+        let command = cli_command(Some("pw"), &["-p", "26379", "PING"]);
+        assert_eq!(command[..2], ["sh", "-c"]);
+        assert_eq!(
+            command[2],
+            "exec redis-cli -p \"${REDIS_UP_PORT:-6379}\" \"$@\""
+        );
+        assert_eq!(
+            command[3..],
+            [
+                "redis-cli",
+                "-a",
+                "pw",
+                "--no-auth-warning",
+                "-p",
+                "26379",
+                "PING"
+            ]
+        );
+        assert_eq!(
+            host_network_directives(7000, Some(IpAddr::V4(Ipv4Addr::LOCALHOST))),
+            vec![
+                ("port".to_string(), "7000".to_string()),
+                ("bind".to_string(), "127.0.0.1".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_command_renames() {
+        let args = CommandArgs {
+            rename_commands: vec!["flushall=".to_string(), "CONFIG=CFG_X9".to_string()],
+            hardened: true,
+        };
+        let renames = command_renames(&args).unwrap();
+        assert_eq!(renames["CONFIG"], "CFG_X9");
+        assert_eq!(renames["KEYS"], "");
+        assert_eq!(
+            to_cli_args(&rename_directives(&renames))[..6],
+            [
+                "--rename-command",
+                "CONFIG",
+                "CFG_X9",
+                "--rename-command",
+                "DEBUG",
+                ""
+            ]
+        );
+        assert_eq!(
+            describe_renames(&renames),
+            "DEBUG, FLUSHALL, FLUSHDB, KEYS disabled; CONFIG as CFG_X9"
+        );
+        assert!(command_renames(&CommandArgs {
+            rename_commands: vec!["FLUSHALL".to_string()],
+            hardened: false,
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn test_bind_published_ports() {
+        let args = vec![
+            "run".to_string(),
+            "--publish".to_string(),
+            "6379:6379".to_string(),
+            "redis:7-alpine".to_string(),
+        ];
+
+        let v4 = bind_published_ports(args.clone(), "192.168.1.10".parse().unwrap());
+        assert_eq!(v4[2], "192.168.1.10:6379:6379");
+        assert_eq!(v4[3], "redis:7-alpine");
+
+        let v6 = bind_published_ports(args, "::1".parse().unwrap());
+        assert_eq!(v6[2], "[::1]:6379:6379");
+    }
+
+    #[test]
+    fn test_connect_host_and_url() {
+        assert_eq!(connect_host(None), "localhost");
+        assert_eq!(connect_host(Some("0.0.0.0".parse().unwrap())), "localhost");
+        assert_eq!(connect_host(Some("10.0.0.5".parse().unwrap())), "10.0.0.5");
+        assert_eq!(
+            redis_url(Some("pw"), "10.0.0.5", 6379),
+            "redis://default:pw@10.0.0.5:6379"
+        );
+        assert_eq!(
+            redis_url(Some("pw"), "::1", 6379),
+            "redis://default:pw@[::1]:6379"
+        );
+        assert_eq!(redis_url(None, "localhost", 6379), "redis://localhost:6379");
+    }
+
+    #[test]
+    fn test_exposure_bind() {
+        let any: IpAddr = "0.0.0.0".parse().unwrap();
+        assert_eq!(exposure_bind(false, None, false, false).unwrap(), None);
+        assert_eq!(
+            exposure_bind(true, None, false, false).unwrap(),
+            Some("127.0.0.1".parse().unwrap())
+        );
+        assert_eq!(
+            exposure_bind(true, None, true, false).unwrap(),
+            Some("::1".parse().unwrap())
+        );
+        assert!(exposure_bind(true, Some(any), false, false).is_err());
+        assert_eq!(
+            exposure_bind(true, Some(any), false, true).unwrap(),
+            Some(any)
+        );
+    }
+
+    #[test]
+    fn test_persistence_tunable_validation() {
+        assert!(persistence_directives(Some(PersistenceMode::Aof), Some("60 1"), None).is_err());
+        assert!(persistence_directives(
+            Some(PersistenceMode::Rdb),
+            None,
+            Some(AppendFsync::Always)
+        )
+        .is_err());
+        assert!(persistence_directives(
+            Some(PersistenceMode::Both),
+            Some("60 1"),
+            Some(AppendFsync::Always)
+        )
+        .is_ok());
+    }
+}
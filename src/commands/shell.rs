@@ -0,0 +1,255 @@
+//! Interactive redis-cli and container shell sessions for managed instances
+
+use anyhow::{Context, Result};
+use colored::*;
+use std::io::IsTerminal;
+use tokio::process::Command as ProcessCommand;
+
+use crate::cli::{ShArgs, ShellArgs};
+use crate::commands::client::{self, Endpoint};
+use crate::config::{Config, InstanceInfo, InstanceType};
+use crate::error::Failure;
+
+pub async fn handle_shell(args: ShellArgs, verbose: bool) -> Result<()> {
+    let config = Config::load()?;
+    let instance = config
+        .get_instance(&args.name)
+        .with_context(|| format!("Instance '{}' not found", args.name))?;
+
+    let endpoint = shell_endpoint(instance, args.node)?;
+    let cluster_mode = args.cluster_mode || instance.instance_type == InstanceType::Cluster;
+
+    let mut cli_args = vec![
+        "-h".to_string(),
+        endpoint.host.clone(),
+        "-p".to_string(),
+        endpoint.port.to_string(),
+    ];
+    if cluster_mode {
+        cli_args.push("-c".to_string());
+    }
+
+    println!(
+        "{} Connecting to {} at {}{}...",
+        "Shell:".bold().green(),
+        instance.name.bold(),
+        endpoint,
+        if cluster_mode { " (cluster mode)" } else { "" }
+    );
+    if verbose {
+        println!("  {} redis-cli {}", "Running:".cyan(), cli_args.join(" "));
+    }
+    println!();
+
+    let mut command = ProcessCommand::new("redis-cli");
+    command.args(&cli_args);
+    // Passed through the environment so it stays out of ps and redis-cli's -a warning
+    if let Some(ref password) = endpoint.password {
+        command.env("REDISCLI_AUTH", password);
+    }
+    let status = command
+        .status()
+        .await
+        .context("Failed to start redis-cli; is it installed and on PATH?")?;
+
+    if !status.success() {
+        println!("{} redis-cli exited with error", "Warning:".yellow());
+    }
+    Ok(())
+}
+
+pub async fn handle_sh(args: ShArgs, verbose: bool) -> Result<()> {
+    let config = Config::load()?;
+    let instance = config
+        .get_instance(&args.name)
+        .with_context(|| format!("Instance '{}' not found", args.name))?;
+
+    let containers = node_containers(instance);
+    let node = args.node.unwrap_or(0);
+    let container = containers.get(node).with_context(|| {
+        format!(
+            "'{}' has no node {} (it has {}, counting from 0: {})",
+            instance.name,
+            node,
+            containers.len(),
+            containers.join(", ")
+        )
+    })?;
+
+    let mut docker_args = vec!["exec".to_string(), "-i".to_string()];
+    // A TTY can only be allocated when we have one ourselves
+    if std::io::stdin().is_terminal() {
+        docker_args.push("-t".to_string());
+    }
+    docker_args.push(container.clone());
+    docker_args.push(args.shell.clone());
+
+    if verbose {
+        println!("  {} docker {}", "Running:".cyan(), docker_args.join(" "));
+    }
+    println!(
+        "{} {} in {}",
+        "Shell:".bold().green(),
+        args.shell,
+        container.bold()
+    );
+
+    let status = ProcessCommand::new("docker")
+        .args(&docker_args)
+        .status()
+        .await
+        .map_err(|e| Failure::DockerUnavailable(format!("Failed to run docker exec: {}", e)))?;
+
+    // 126/127 are docker exec's "could not run the command" codes
+    match status.code() {
+        Some(126 | 127) => anyhow::bail!(
+            "{} is not available in {}; try --shell /bin/bash or another shell",
+            args.shell,
+            container
+        ),
+        _ => Ok(()),
+    }
+}
+
+/// Containers running a Redis process, in `--node` order
+///
+/// Sentinel setups list masters followed by sentinels, matching the port order
+/// `redis-up shell --node` uses.
+fn node_containers(instance: &InstanceInfo) -> Vec<String> {
+    let count = |key: &str| {
+        instance
+            .metadata
+            .get(key)
+            .and_then(|v| v.as_u64())
+            .unwrap_or(1)
+    };
+    match instance.instance_type {
+        InstanceType::Basic | InstanceType::Stack => vec![instance.name.clone()],
+        InstanceType::Cluster | InstanceType::Enterprise => instance
+            .containers
+            .iter()
+            .filter(|c| !c.ends_with("-insight"))
+            .cloned()
+            .collect(),
+        InstanceType::Sentinel => (1..=count("masters"))
+            .map(|i| format!("{}-master-{}", instance.name, i))
+            .chain((1..=count("sentinels")).map(|i| format!("{}-sentinel-{}", instance.name, i)))
+            .collect(),
+    }
+}
+
+/// Redis server to open a shell on
+///
+/// `node` indexes the instance's ports: cluster nodes in order, or for
+/// sentinel setups the masters followed by the sentinels. Without it the
+/// primary connection address is used.
+fn shell_endpoint(instance: &InstanceInfo, node: Option<usize>) -> Result<Endpoint> {
+    if instance.instance_type == InstanceType::Enterprise
+        && instance.connection_info.url.starts_with('<')
+    {
+        anyhow::bail!(
+            "'{}' has no database yet; start it with --create-db to get one",
+            instance.name
+        );
+    }
+
+    let mut endpoint = client::endpoints(instance)
+        .into_iter()
+        .next()
+        .context("Instance has no Redis endpoint")?;
+
+    let Some(node) = node else {
+        return Ok(endpoint);
+    };
+    match instance.instance_type {
+        InstanceType::Cluster | InstanceType::Sentinel => {}
+        _ => anyhow::bail!(
+            "--node only applies to cluster and sentinel instances, '{}' is {}",
+            instance.name,
+            instance.instance_type
+        ),
+    }
+    endpoint.port = *instance.ports.get(node).with_context(|| {
+        format!(
+            "'{}' has no node {} (it has {}, counting from 0)",
+            instance.name,
+            node,
+            instance.ports.len()
+        )
+    })?;
+
+    // Sentinels don't require the data password
+    let sentinel_base = instance
+        .connection_info
+        .additional_ports
+        .get("sentinel_base");
+    if instance.instance_type == InstanceType::Sentinel
+        && sentinel_base.is_some_and(|base| endpoint.port >= *base)
+    {
+        endpoint.password = None;
+    }
+    Ok(endpoint)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ConnectionInfo;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_shell_endpoint() {
+        let instance = InstanceInfo {
+            name: "ha".to_string(),
+            instance_type: InstanceType::Sentinel,
+            created_at: String::new(),
+            ports: vec![8000, 26379, 26380],
+            containers: Vec::new(),
+            connection_info: ConnectionInfo {
+                host: "localhost".to_string(),
+                port: 8000,
+                password: Some("secret".to_string()),
+                url: String::new(),
+                additional_ports: HashMap::from([("sentinel_base".to_string(), 26379)]),
+                ipv6_host: None,
+                ipv6_url: None,
+            },
+            metadata: HashMap::new(),
+            started_at: None,
+            last_seen: None,
+        };
+
+        let master = shell_endpoint(&instance, None).unwrap();
+        assert_eq!(
+            (master.port, master.password.as_deref()),
+            (8000, Some("secret"))
+        );
+        let sentinel = shell_endpoint(&instance, Some(2)).unwrap();
+        assert_eq!((sentinel.port, sentinel.password), (26380, None));
+        assert!(shell_endpoint(&instance, Some(3)).is_err());
+    }
+
+    #[test]
+    fn test_node_containers() {
+        let instance: InstanceInfo = serde_json::from_value(serde_json::json!({
+            "name": "ha",
+            "instance_type": "sentinel",
+            "created_at": "",
+            "ports": [8000, 26379, 26380],
+            "containers": ["id1", "id2", "id3", "ha-insight"],
+            "connection_info": {
+                "host": "localhost",
+                "port": 8000,
+                "password": null,
+                "url": "",
+                "additional_ports": {}
+            },
+            "metadata": {"masters": 1, "sentinels": 2}
+        }))
+        .unwrap();
+        assert_eq!(
+            node_containers(&instance),
+            vec!["ha-master-1", "ha-sentinel-1", "ha-sentinel-2"]
+        );
+    }
+}
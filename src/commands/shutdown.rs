@@ -0,0 +1,193 @@
+//! Graceful shutdown of multi-container instances
+//!
+//! Containers are stopped sentinels first, then replicas, then masters, with
+//! RedisInsight last, so Sentinel doesn't start a failover while the setup
+//! is being torn down. Redis servers with persistence get `SHUTDOWN SAVE`
+//! before `docker stop` so their last writes reach disk. Containers with the
+//! same role are stopped concurrently.
+
+use anyhow::Error;
+use colored::*;
+use docker_wrapper::{DockerCommand, StopCommand};
+use tokio::task::JoinSet;
+use tracing::Instrument;
+
+use crate::commands::server;
+use crate::config::{InstanceInfo, InstanceType};
+
+/// Containers are stopped in this order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Role {
+    Sentinel,
+    Replica,
+    Master,
+    Insight,
+}
+
+/// Stop an instance's containers in shutdown order, returning the ones that failed
+///
+/// `timeout` is the `docker stop` grace period in seconds (Docker's default is 10).
+#[tracing::instrument(name = "stop", skip_all, fields(name = %instance.name))]
+pub async fn stop_containers(
+    instance: &InstanceInfo,
+    timeout: Option<u32>,
+    verbose: bool,
+) -> Vec<(String, Error)> {
+    // Roles are looked up concurrently; the index keeps the recorded order
+    let mut lookups = JoinSet::new();
+    for (index, container) in instance.containers.iter().enumerate() {
+        let (instance, container) = (instance.clone(), container.clone());
+        lookups.spawn(
+            async move { (role(&instance, &container).await, index, container) }.in_current_span(),
+        );
+    }
+    let mut containers = Vec::new();
+    while let Some(joined) = lookups.join_next().await {
+        if let Ok(found) = joined {
+            containers.push(found);
+        }
+    }
+    containers.sort();
+
+    let save = persistence_on(instance);
+    let password = instance.connection_info.password.clone();
+    let mut failed = Vec::new();
+
+    // Containers with the same role go down together, each role after the one before
+    for group in containers.chunk_by(|a, b| a.0 == b.0) {
+        let mut stops = JoinSet::new();
+        for (role, _, container) in group {
+            let (role, container, password) = (*role, container.clone(), password.clone());
+            stops.spawn(
+                async move {
+                    let result = stop_one(
+                        role,
+                        &container,
+                        save,
+                        password.as_deref(),
+                        timeout,
+                        verbose,
+                    )
+                    .await;
+                    (container, result)
+                }
+                .in_current_span(),
+            );
+        }
+        while let Some(joined) = stops.join_next().await {
+            if let Ok((container, Err(e))) = joined {
+                failed.push((container, e));
+            }
+        }
+    }
+
+    failed
+}
+
+/// Save if needed, then `docker stop` one container
+async fn stop_one(
+    role: Role,
+    container: &str,
+    save: bool,
+    password: Option<&str>,
+    timeout: Option<u32>,
+    verbose: bool,
+) -> Result<(), Error> {
+    if save && matches!(role, Role::Replica | Role::Master) {
+        // The connection drops as the server exits, so the reply is never an answer
+        server::redis_cli(container, password, &["SHUTDOWN", "SAVE"])
+            .await
+            .ok();
+        if verbose {
+            println!(
+                "  {} SHUTDOWN SAVE on {}",
+                "Saving:".cyan(),
+                container.dimmed()
+            );
+        }
+    }
+
+    let mut stop = StopCommand::new(container);
+    if let Some(timeout) = timeout {
+        stop = stop.timeout(timeout);
+    }
+    stop.execute().await?;
+    if verbose {
+        println!("  {} {}", "Stopped:".green(), container.dimmed());
+    }
+    Ok(())
+}
+
+/// Whether the instance writes data that a clean shutdown should save
+fn persistence_on(instance: &InstanceInfo) -> bool {
+    let persist = instance
+        .metadata
+        .get("persist")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    let mode = instance
+        .metadata
+        .get("persistence")
+        .and_then(|v| v.as_str());
+    persist || mode.is_some_and(|mode| mode != "none")
+}
+
+/// Role of a container, asking Redis for replication roles since failovers move them
+async fn role(instance: &InstanceInfo, container: &str) -> Role {
+    if let Some(role) = static_role(instance, container) {
+        return role;
+    }
+    if instance.instance_type == InstanceType::Enterprise {
+        return Role::Master;
+    }
+    let password = instance.connection_info.password.as_deref();
+    match server::redis_cli(container, password, &["ROLE"]).await {
+        Ok(reply) if reply.trim_start().starts_with("master") => Role::Master,
+        // Replicas and servers that no longer answer have nothing to hand over
+        _ => Role::Replica,
+    }
+}
+
+/// Roles known from the instance record alone
+fn static_role(instance: &InstanceInfo, container: &str) -> Option<Role> {
+    if container.ends_with("-insight") {
+        return Some(Role::Insight);
+    }
+    let is_sentinel = instance
+        .metadata
+        .get("sentinel_containers")
+        .and_then(|v| v.as_array())
+        .is_some_and(|ids| ids.iter().any(|id| id.as_str() == Some(container)));
+    is_sentinel.then_some(Role::Sentinel)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_static_role() {
+        let instance: InstanceInfo = serde_json::from_value(serde_json::json!({
+            "name": "ha",
+            "instance_type": "sentinel",
+            "created_at": "2024-01-01T00:00:00Z",
+            "ports": [6379],
+            "containers": ["m1", "s1", "ha-insight"],
+            "connection_info": {
+                "host": "localhost",
+                "port": 6379,
+                "password": null,
+                "url": "redis://localhost:6379",
+                "additional_ports": {}
+            },
+            "metadata": {"sentinel_containers": ["s1"], "persistence": "none"}
+        }))
+        .unwrap();
+
+        assert_eq!(static_role(&instance, "s1"), Some(Role::Sentinel));
+        assert_eq!(static_role(&instance, "ha-insight"), Some(Role::Insight));
+        assert_eq!(static_role(&instance, "m1"), None);
+        assert!(Role::Sentinel < Role::Replica && Role::Master < Role::Insight);
+        assert!(!persistence_on(&instance));
+    }
+}
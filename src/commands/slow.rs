@@ -0,0 +1,238 @@
+//! Per-command latency through a local proxy
+//!
+//! `redis-up slow my-redis --command GET --ms 20` listens on a local port and
+//! forwards everything to the instance, holding back matching commands for
+//! the given time before they reach Redis. Other commands, and every other
+//! client of the instance, are unaffected, so an application's timeout
+//! handling can be exercised one operation at a time.
+
+use anyhow::{Context, Result};
+use colored::*;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::cli::SlowArgs;
+use crate::commands::client;
+use crate::config::{Config, InstanceType};
+
+/// Which commands to delay and by how much
+struct Delay {
+    commands: Vec<String>,
+    delay: Duration,
+    delayed: AtomicU64,
+}
+
+impl Delay {
+    fn applies(&self, command: &str) -> bool {
+        self.commands
+            .iter()
+            .any(|c| c.eq_ignore_ascii_case(command))
+    }
+}
+
+pub async fn handle_slow(args: SlowArgs, verbose: bool) -> Result<()> {
+    let config = Config::load()?;
+    let instance = config
+        .get_instance(&args.name)
+        .with_context(|| format!("Instance '{}' not found", args.name))?;
+
+    // Cluster redirects and sentinel discovery would send clients around the proxy
+    if matches!(
+        instance.instance_type,
+        InstanceType::Cluster | InstanceType::Sentinel
+    ) {
+        anyhow::bail!(
+            "slow proxies a single endpoint, '{}' is {} (clients would be redirected around it)",
+            args.name,
+            instance.instance_type
+        );
+    }
+    let endpoint = client::endpoints(instance)
+        .into_iter()
+        .next()
+        .context("Instance has no endpoint")?;
+    let upstream = format!("{}:{}", endpoint.host, endpoint.port);
+
+    let listener = TcpListener::bind(("127.0.0.1", args.port))
+        .await
+        .with_context(|| format!("Failed to listen on 127.0.0.1:{}", args.port))?;
+    let port = listener.local_addr()?.port();
+
+    let delay = Arc::new(Delay {
+        commands: args.command.clone(),
+        delay: Duration::from_millis(args.ms),
+        delayed: AtomicU64::new(0),
+    });
+
+    println!(
+        "{} Delaying {} by {}ms on {}",
+        "Slow:".bold().cyan(),
+        args.command.join(", ").bold(),
+        args.ms,
+        args.name.bold()
+    );
+    let proxied = client::Endpoint {
+        host: "127.0.0.1".to_string(),
+        port,
+        password: endpoint.password.clone(),
+    };
+    println!("  {}: {}", "Connect to".bold(), proxied.url());
+    match args.duration {
+        Some(duration) => println!(
+            "  {}: {}",
+            "Duration".bold(),
+            humantime::format_duration(duration)
+        ),
+        None => println!("  Press Ctrl-C to stop"),
+    }
+
+    let deadline = async {
+        match args.duration {
+            Some(duration) => tokio::time::sleep(duration).await,
+            None => std::future::pending().await,
+        }
+    };
+    tokio::pin!(deadline);
+
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => {
+                let (socket, peer) = accepted?;
+                if verbose {
+                    println!("  Client connected from {}", peer);
+                }
+                let upstream = upstream.clone();
+                let delay = Arc::clone(&delay);
+                tokio::spawn(async move {
+                    if let Err(e) = proxy(socket, &upstream, &delay).await {
+                        if verbose {
+                            eprintln!("  {} {}: {}", "Warning:".yellow(), peer, e);
+                        }
+                    }
+                });
+            }
+            _ = &mut deadline => break,
+            _ = tokio::signal::ctrl_c() => break,
+        }
+    }
+
+    println!();
+    println!(
+        "{} Proxy closed after delaying {} command(s)",
+        "Success:".green(),
+        delay.delayed.load(Ordering::Relaxed)
+    );
+    Ok(())
+}
+
+/// Forward one client connection, delaying matching requests
+async fn proxy(client: TcpStream, upstream: &str, delay: &Delay) -> Result<()> {
+    let server = TcpStream::connect(upstream)
+        .await
+        .with_context(|| format!("Failed to connect to {}", upstream))?;
+    let (mut client_read, mut client_write) = client.into_split();
+    let (mut server_read, mut server_write) = server.into_split();
+
+    let replies = async {
+        tokio::io::copy(&mut server_read, &mut client_write).await?;
+        client_write.shutdown().await
+    };
+
+    let requests = async {
+        let mut pending = Vec::new();
+        let mut chunk = [0u8; 16 * 1024];
+        loop {
+            let read = client_read.read(&mut chunk).await?;
+            if read == 0 {
+                return server_write.shutdown().await;
+            }
+            pending.extend_from_slice(&chunk[..read]);
+
+            // Forward whole commands in order, pausing before the slow ones
+            let mut start = 0;
+            while let Some((len, name)) = next_command(&pending[start..]) {
+                if name.as_deref().is_some_and(|name| delay.applies(name)) {
+                    server_write.write_all(&pending[..start]).await?;
+                    pending.drain(..start);
+                    start = 0;
+                    tokio::time::sleep(delay.delay).await;
+                    delay.delayed.fetch_add(1, Ordering::Relaxed);
+                }
+                start += len;
+            }
+            server_write.write_all(&pending[..start]).await?;
+            pending.drain(..start);
+        }
+    };
+
+    tokio::try_join!(replies, requests)?;
+    Ok(())
+}
+
+/// Length and name of the first complete command in a buffer
+///
+/// Handles RESP arrays, which every client library sends, and inline commands
+/// as typed into telnet. Returns `None` until a whole command has arrived.
+fn next_command(buf: &[u8]) -> Option<(usize, Option<String>)> {
+    let line_end = |from: usize| {
+        buf.get(from..)?
+            .windows(2)
+            .position(|w| w == b"\r\n")
+            .map(|i| from + i)
+    };
+
+    if buf.first() != Some(&b'*') {
+        let end = buf.iter().position(|&b| b == b'\n')?;
+        let line = String::from_utf8_lossy(&buf[..end]);
+        let name = line.split_whitespace().next().map(str::to_string);
+        return Some((end + 1, name));
+    }
+
+    let end = line_end(0)?;
+    let count: usize = std::str::from_utf8(&buf[1..end]).ok()?.parse().ok()?;
+    let mut pos = end + 2;
+    let mut name = None;
+    for i in 0..count {
+        if buf.get(pos) != Some(&b'$') {
+            return None;
+        }
+        let end = line_end(pos)?;
+        let len: usize = std::str::from_utf8(&buf[pos + 1..end]).ok()?.parse().ok()?;
+        let data = end + 2;
+        if buf.len() < data + len + 2 {
+            return None;
+        }
+        if i == 0 {
+            name = Some(String::from_utf8_lossy(&buf[data..data + len]).into_owned());
+        }
+        pos = data + len + 2;
+    }
+    Some((pos, name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_command() {
+        let buf = b"*2\r\n$3\r\nGET\r\n$3\r\nfoo\r\n*1\r\n$4\r\nPING\r\n";
+        assert_eq!(next_command(buf), Some((22, Some("GET".to_string()))));
+        assert_eq!(
+            next_command(&buf[22..]),
+            Some((14, Some("PING".to_string())))
+        );
+
+        // Partial commands wait for more bytes
+        assert_eq!(next_command(&buf[..20]), None);
+        assert_eq!(next_command(b"*2\r\n$3\r\nGE"), None);
+
+        assert_eq!(
+            next_command(b"get foo\r\n"),
+            Some((9, Some("get".to_string())))
+        );
+    }
+}
@@ -0,0 +1,296 @@
+//! Ready-to-paste client connection code
+
+use anyhow::{Context, Result};
+use colored::*;
+
+use crate::cli::{ConnectSnippetArgs, SentinelClient, SnippetLang};
+use crate::commands::client::{self, Endpoint};
+use crate::commands::sentinel;
+use crate::config::{Config, InstanceType};
+
+pub async fn handle_connect_snippet(args: ConnectSnippetArgs, verbose: bool) -> Result<()> {
+    let config = Config::load()?;
+    let instance = config
+        .get_instance(&args.name)
+        .with_context(|| format!("Instance '{}' not found", args.name))?;
+
+    let snippet = match instance.instance_type {
+        InstanceType::Cluster => cluster(args.lang, &client::endpoints(instance)),
+        InstanceType::Sentinel => {
+            let target = sentinel::sentinel_target(instance, None)?;
+            match args.lang {
+                SnippetLang::Rust => rust_sentinel(&target),
+                SnippetLang::Python => sentinel(SentinelClient::RedisPy, &target),
+                SnippetLang::Node => sentinel(SentinelClient::Ioredis, &target),
+                SnippetLang::Java => sentinel(SentinelClient::Lettuce, &target),
+                SnippetLang::Go => sentinel(SentinelClient::GoRedis, &target),
+            }
+        }
+        _ => {
+            let endpoint = client::endpoints(instance)
+                .into_iter()
+                .next()
+                .context("Instance has no Redis endpoint")?;
+            standalone(args.lang, &endpoint)
+        }
+    };
+
+    if verbose {
+        eprintln!(
+            "{} {} client for {} ({})",
+            "Snippet:".dimmed(),
+            client_name(args.lang),
+            instance.name,
+            instance.instance_type
+        );
+    }
+    print!("{}", snippet);
+    Ok(())
+}
+
+/// The client library each language's snippet is written for
+fn client_name(lang: SnippetLang) -> &'static str {
+    match lang {
+        SnippetLang::Rust => "redis-rs",
+        SnippetLang::Python => "redis-py",
+        SnippetLang::Node => "ioredis",
+        SnippetLang::Java => "Lettuce",
+        SnippetLang::Go => "go-redis",
+    }
+}
+
+/// Connection code for a single Redis server
+pub fn standalone(lang: SnippetLang, endpoint: &Endpoint) -> String {
+    let password = endpoint.password.as_deref();
+    match lang {
+        SnippetLang::Rust => format!(
+            "let client = redis::Client::open(\"{}\")?;\nlet mut con = client.get_connection()?;\n",
+            endpoint.url()
+        ),
+        SnippetLang::Python => format!(
+            "import redis\n\nr = redis.Redis(host=\"{}\", port={}{})\n",
+            endpoint.host,
+            endpoint.port,
+            password
+                .map(|p| format!(", password=\"{}\"", p))
+                .unwrap_or_default()
+        ),
+        SnippetLang::Node => format!(
+            "const Redis = require(\"ioredis\");\n\nconst redis = new Redis({{ host: \"{}\", port: {}{} }});\n",
+            endpoint.host,
+            endpoint.port,
+            password
+                .map(|p| format!(", password: \"{}\"", p))
+                .unwrap_or_default()
+        ),
+        SnippetLang::Java => format!(
+            "RedisClient client = RedisClient.create(\"{}\");\nStatefulRedisConnection<String, String> connection = client.connect();\n",
+            endpoint.url()
+        ),
+        SnippetLang::Go => format!(
+            "rdb := redis.NewClient(&redis.Options{{\n\tAddr:     \"{}:{}\",\n{}}})\n",
+            endpoint.host,
+            endpoint.port,
+            password
+                .map(|p| format!("\tPassword: \"{}\",\n", p))
+                .unwrap_or_default()
+        ),
+    }
+}
+
+/// Connection code for a cluster, seeded with every node
+pub fn cluster(lang: SnippetLang, nodes: &[Endpoint]) -> String {
+    let password = nodes.first().and_then(|n| n.password.as_deref());
+    let list = |f: &dyn Fn(&Endpoint) -> String, sep: &str| {
+        nodes.iter().map(f).collect::<Vec<_>>().join(sep)
+    };
+    match lang {
+        SnippetLang::Rust => format!(
+            "use redis::cluster::ClusterClient;\n\nlet client = ClusterClient::new(vec![\n{}\n])?;\nlet mut con = client.get_connection()?;\n",
+            list(&|n| format!("    \"{}\",", n.url()), "\n")
+        ),
+        SnippetLang::Python => format!(
+            "from redis.cluster import ClusterNode, RedisCluster\n\nrc = RedisCluster(\n    startup_nodes=[{}],\n{})\n",
+            list(&|n| format!("ClusterNode(\"{}\", {})", n.host, n.port), ", "),
+            password
+                .map(|p| format!("    password=\"{}\",\n", p))
+                .unwrap_or_default()
+        ),
+        SnippetLang::Node => format!(
+            "const Redis = require(\"ioredis\");\n\nconst cluster = new Redis.Cluster(\n  [\n{}\n  ],\n  {{ redisOptions: {{ {} }} }}\n);\n",
+            list(&|n| format!("    {{ host: \"{}\", port: {} }},", n.host, n.port), "\n"),
+            password
+                .map(|p| format!("password: \"{}\"", p))
+                .unwrap_or_default()
+        ),
+        SnippetLang::Java => format!(
+            "RedisClusterClient client = RedisClusterClient.create(Arrays.asList(\n{}));\nStatefulRedisClusterConnection<String, String> connection = client.connect();\n",
+            list(&|n| format!("    RedisURI.create(\"{}\")", n.url()), ",\n")
+        ),
+        SnippetLang::Go => format!(
+            "rdb := redis.NewClusterClient(&redis.ClusterOptions{{\n\tAddrs:    []string{{{}}},\n{}}})\n",
+            list(&|n| format!("\"{}:{}\"", n.host, n.port), ", "),
+            password
+                .map(|p| format!("\tPassword: \"{}\",\n", p))
+                .unwrap_or_default()
+        ),
+    }
+}
+
+/// redis-rs has no URI form for Sentinel, so build the client explicitly
+fn rust_sentinel(target: &SentinelTarget) -> String {
+    let sentinels: Vec<String> = target
+        .addrs()
+        .iter()
+        .map(|addr| format!("        \"redis://{}\".to_string(),", addr))
+        .collect();
+    let node_info = match target.password {
+        Some(ref password) => format!(
+            "Some(SentinelNodeConnectionInfo {{\n        tls_mode: None,\n        redis_connection_info: Some(RedisConnectionInfo {{\n            password: Some(\"{}\".to_string()),\n            ..Default::default()\n        }}),\n    }})",
+            password
+        ),
+        None => "None".to_string(),
+    };
+    format!(
+        "use redis::sentinel::{{SentinelClient, SentinelNodeConnectionInfo, SentinelServerType}};\nuse redis::RedisConnectionInfo;\n\nlet mut client = SentinelClient::build(\n    vec![\n{}\n    ],\n    \"{}\".to_string(),\n    {},\n    SentinelServerType::Master,\n)?;\nlet mut con = client.get_connection()?;\n",
+        sentinels.join("\n"),
+        target.master_name,
+        node_info
+    )
+}
+
+/// What a Sentinel-aware client needs to find a master
+#[derive(Debug, Clone)]
+pub struct SentinelTarget {
+    pub master_name: String,
+    pub sentinels: Vec<(String, u16)>,
+    pub password: Option<String>,
+}
+
+impl SentinelTarget {
+    fn addrs(&self) -> Vec<String> {
+        self.sentinels
+            .iter()
+            .map(|(host, port)| format!("{}:{}", host, port))
+            .collect()
+    }
+
+    /// Sentinel URI in the `redis-sentinel://` form Lettuce and others accept
+    pub fn uri(&self) -> String {
+        let auth = self
+            .password
+            .as_ref()
+            .map(|p| format!("{}@", p))
+            .unwrap_or_default();
+        format!(
+            "redis-sentinel://{}{}/0#{}",
+            auth,
+            self.addrs().join(","),
+            self.master_name
+        )
+    }
+}
+
+/// Connection code for a Sentinel setup
+pub fn sentinel(client: SentinelClient, target: &SentinelTarget) -> String {
+    let password = target.password.as_deref();
+    match client {
+        SentinelClient::Ioredis => {
+            let sentinels: Vec<String> = target
+                .sentinels
+                .iter()
+                .map(|(host, port)| format!("    {{ host: \"{}\", port: {} }},", host, port))
+                .collect();
+            let password = password
+                .map(|p| format!("  password: \"{}\",\n", p))
+                .unwrap_or_default();
+            format!(
+                "const Redis = require(\"ioredis\");\n\nconst redis = new Redis({{\n  sentinels: [\n{}\n  ],\n  name: \"{}\",\n{}}});\n",
+                sentinels.join("\n"),
+                target.master_name,
+                password
+            )
+        }
+        SentinelClient::Lettuce => format!(
+            "RedisClient client = RedisClient.create(\n    RedisURI.create(\"{}\"));\nStatefulRedisConnection<String, String> connection = client.connect();\n",
+            target.uri()
+        ),
+        SentinelClient::GoRedis => {
+            let addrs: Vec<String> = target
+                .addrs()
+                .iter()
+                .map(|addr| format!("\"{}\"", addr))
+                .collect();
+            let password = password
+                .map(|p| format!("\tPassword:      \"{}\",\n", p))
+                .unwrap_or_default();
+            format!(
+                "rdb := redis.NewFailoverClient(&redis.FailoverOptions{{\n\tMasterName:    \"{}\",\n\tSentinelAddrs: []string{{{}}},\n{}}})\n",
+                target.master_name,
+                addrs.join(", "),
+                password
+            )
+        }
+        SentinelClient::RedisPy => {
+            let sentinels: Vec<String> = target
+                .sentinels
+                .iter()
+                .map(|(host, port)| format!("(\"{}\", {})", host, port))
+                .collect();
+            let password = password
+                .map(|p| format!(", password=\"{}\"", p))
+                .unwrap_or_default();
+            format!(
+                "from redis.sentinel import Sentinel\n\nsentinel = Sentinel([{}])\nmaster = sentinel.master_for(\"{}\"{})\n",
+                sentinels.join(", "),
+                target.master_name,
+                password
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sentinel_snippets() {
+        let target = SentinelTarget {
+            master_name: "mymaster".to_string(),
+            sentinels: vec![
+                ("localhost".to_string(), 26379),
+                ("localhost".to_string(), 26380),
+            ],
+            password: Some("pw".to_string()),
+        };
+        assert_eq!(
+            target.uri(),
+            "redis-sentinel://pw@localhost:26379,localhost:26380/0#mymaster"
+        );
+        let go = sentinel(SentinelClient::GoRedis, &target);
+        assert!(go.contains("[]string{\"localhost:26379\", \"localhost:26380\"}"));
+        let py = sentinel(SentinelClient::RedisPy, &target);
+        assert!(py.contains("master_for(\"mymaster\", password=\"pw\")"));
+        assert!(rust_sentinel(&target).contains("\"redis://localhost:26380\""));
+    }
+
+    #[test]
+    fn test_cluster_snippets() {
+        let nodes: Vec<Endpoint> = [7000, 7001]
+            .into_iter()
+            .map(|port| Endpoint {
+                host: "localhost".to_string(),
+                port,
+                password: Some("pw".to_string()),
+            })
+            .collect();
+        let go = cluster(SnippetLang::Go, &nodes);
+        assert!(go.contains("[]string{\"localhost:7000\", \"localhost:7001\"}"));
+        assert!(go.contains("Password: \"pw\""));
+        let rust = cluster(SnippetLang::Rust, &nodes);
+        assert!(rust.contains("\"redis://default:pw@localhost:7001\","));
+        let java = standalone(SnippetLang::Java, &nodes[0]);
+        assert!(java.contains("RedisClient.create(\"redis://default:pw@localhost:7000\")"));
+    }
+}
@@ -0,0 +1,303 @@
+//! Self-signed certificates for TLS-enabled instances
+//!
+//! Each instance gets its own CA and a server certificate signed by it, made
+//! with the host's `openssl` and kept under `tls/<name>` in the state
+//! directory. The directory is mounted read-only into the containers.
+//!
+//! A client certificate from the same CA goes into a `client` bundle next to
+//! it, with a ready redis-cli wrapper and an stunnel config for clients that
+//! can't speak TLS themselves.
+
+use anyhow::{bail, Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tokio::process::Command as ProcessCommand;
+
+use crate::config::get_state_dir;
+
+/// Where the certificate directory is mounted in containers
+pub const CONTAINER_DIR: &str = "/tls";
+
+pub const CA_CERT: &str = "ca.crt";
+pub const SERVER_CERT: &str = "redis.crt";
+pub const SERVER_KEY: &str = "redis.key";
+pub const CLIENT_CERT: &str = "client.crt";
+pub const CLIENT_KEY: &str = "client.key";
+
+/// Client bundle directory inside the certificate directory
+const CLIENT_DIR: &str = "client";
+
+/// stunnel listens for plain connections on the TLS port plus this offset
+const STUNNEL_OFFSET: u16 = 20000;
+
+/// Validity of generated certificates, in days
+const DAYS: &str = "825";
+
+/// Certificate directory of an instance
+pub fn dir(name: &str) -> Result<PathBuf> {
+    Ok(get_state_dir()?.join("tls").join(name))
+}
+
+/// Path of one of the certificate files inside containers
+pub fn container_path(file: &str) -> String {
+    format!("{}/{}", CONTAINER_DIR, file)
+}
+
+/// Create a CA and a server certificate valid for `hosts` plus localhost
+pub async fn generate(name: &str, hosts: &[String]) -> Result<PathBuf> {
+    let dir = dir(name)?;
+    fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+
+    openssl(
+        &dir,
+        &[
+            "req",
+            "-x509",
+            "-new",
+            "-nodes",
+            "-newkey",
+            "rsa:2048",
+            "-keyout",
+            "ca.key",
+            "-out",
+            CA_CERT,
+            "-days",
+            DAYS,
+            "-subj",
+            &format!("/CN=redis-up {} CA", name),
+        ],
+    )
+    .await?;
+    openssl(
+        &dir,
+        &[
+            "req",
+            "-new",
+            "-nodes",
+            "-newkey",
+            "rsa:2048",
+            "-keyout",
+            SERVER_KEY,
+            "-out",
+            "redis.csr",
+            "-subj",
+            &format!("/CN={}", name),
+        ],
+    )
+    .await?;
+    fs::write(dir.join("redis.ext"), san_extension(hosts))?;
+    sign(&dir, "redis.csr", SERVER_CERT, "redis.ext").await?;
+
+    let client = dir.join(CLIENT_DIR);
+    fs::create_dir_all(&client)
+        .with_context(|| format!("Failed to create {}", client.display()))?;
+    openssl(
+        &dir,
+        &[
+            "req",
+            "-new",
+            "-nodes",
+            "-newkey",
+            "rsa:2048",
+            "-keyout",
+            &format!("{}/{}", CLIENT_DIR, CLIENT_KEY),
+            "-out",
+            "client.csr",
+            "-subj",
+            &format!("/CN={}-client", name),
+        ],
+    )
+    .await?;
+    fs::write(
+        dir.join("client.ext"),
+        "basicConstraints=CA:FALSE\nextendedKeyUsage=clientAuth\n",
+    )?;
+    sign(
+        &dir,
+        "client.csr",
+        &format!("{}/{}", CLIENT_DIR, CLIENT_CERT),
+        "client.ext",
+    )
+    .await?;
+    fs::copy(dir.join(CA_CERT), client.join(CA_CERT))?;
+
+    for scratch in [
+        "redis.csr",
+        "redis.ext",
+        "client.csr",
+        "client.ext",
+        "ca.srl",
+    ] {
+        fs::remove_file(dir.join(scratch)).ok();
+    }
+
+    // The server runs as the image's redis user, which must be able to read the key
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(dir.join(SERVER_KEY), fs::Permissions::from_mode(0o644))?;
+    }
+    Ok(dir)
+}
+
+/// Remove an instance's certificates, if it has any
+pub fn remove(name: &str) -> Result<()> {
+    let dir = dir(name)?;
+    if dir.exists() {
+        fs::remove_dir_all(&dir).with_context(|| format!("Failed to remove {}", dir.display()))?;
+    }
+    Ok(())
+}
+
+/// Client bundle of an instance: CA, client certificate and key, redis-cli
+/// wrapper and stunnel config for the given host ports
+///
+/// The wrapper takes the password from `REDISCLI_AUTH` in the caller's
+/// environment rather than storing it next to the certificates.
+pub fn write_client_bundle(name: &str, ports: &[u16], cluster: bool) -> Result<PathBuf> {
+    let client = dir(name)?.join(CLIENT_DIR);
+    let script = client.join("redis-cli.sh");
+    fs::write(&script, cli_script(&client, name, ports[0], cluster))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o700))?;
+    }
+    fs::write(client.join("stunnel.conf"), stunnel_config(&client, ports))?;
+    Ok(client)
+}
+
+/// redis-cli with the client certificate, passing further arguments through
+fn cli_script(client: &Path, name: &str, port: u16, cluster: bool) -> String {
+    let path = |file: &str| shell_quote(&client.join(file).display().to_string());
+    format!(
+        "#!/bin/sh\n\
+         : \"${{REDISCLI_AUTH:?set REDISCLI_AUTH to the password of {} (shown by info)}}\"\n\
+         exec redis-cli -h localhost -p {}{} --tls --cacert {} --cert {} --key {} \"$@\"\n",
+        name,
+        port,
+        if cluster { " -c" } else { "" },
+        path(CA_CERT),
+        path(CLIENT_CERT),
+        path(CLIENT_KEY)
+    )
+}
+
+/// Single-quote a word for sh, so any character in it is taken literally
+fn shell_quote(word: &str) -> String {
+    format!("'{}'", word.replace('\'', r"'\''"))
+}
+
+/// stunnel client services: plain 127.0.0.1:<port + 20000> to TLS <port>
+fn stunnel_config(client: &Path, ports: &[u16]) -> String {
+    let mut config = format!(
+        "; Plain-text local ports for clients without TLS support: run `stunnel {}`\n\
+         foreground = yes\n\
+         client = yes\n\
+         CAfile = {}\n\
+         cert = {}\n\
+         key = {}\n\
+         verifyChain = yes\n\
+         checkHost = localhost\n",
+        client.join("stunnel.conf").display(),
+        client.join(CA_CERT).display(),
+        client.join(CLIENT_CERT).display(),
+        client.join(CLIENT_KEY).display()
+    );
+    for port in ports {
+        let Some(local) = port.checked_add(STUNNEL_OFFSET) else {
+            continue;
+        };
+        config.push_str(&format!(
+            "\n[redis-{}]\naccept = 127.0.0.1:{}\nconnect = localhost:{}\n",
+            port, local, port
+        ));
+    }
+    config
+}
+
+/// Sign a request with the instance CA
+async fn sign(dir: &Path, csr: &str, cert: &str, extensions: &str) -> Result<()> {
+    openssl(
+        dir,
+        &[
+            "x509",
+            "-req",
+            "-in",
+            csr,
+            "-CA",
+            CA_CERT,
+            "-CAkey",
+            "ca.key",
+            "-CAcreateserial",
+            "-out",
+            cert,
+            "-days",
+            DAYS,
+            "-extfile",
+            extensions,
+        ],
+    )
+    .await
+}
+
+async fn openssl(dir: &Path, args: &[&str]) -> Result<()> {
+    let output = ProcessCommand::new("openssl")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .await
+        .context("Failed to run openssl; it is needed to create TLS certificates")?;
+    if !output.status.success() {
+        bail!(
+            "openssl {} failed: {}",
+            args[0],
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+/// x509 extensions naming every host the server certificate is valid for
+fn san_extension(hosts: &[String]) -> String {
+    let mut names = vec!["DNS:localhost".to_string(), "IP:127.0.0.1".to_string()];
+    for host in hosts {
+        if host.parse::<std::net::IpAddr>().is_ok() {
+            names.push(format!("IP:{}", host));
+        } else {
+            names.push(format!("DNS:{}", host));
+        }
+    }
+    format!(
+        "basicConstraints=CA:FALSE\nkeyUsage=digitalSignature,keyEncipherment\n\
+         extendedKeyUsage=serverAuth,clientAuth\nsubjectAltName={}\n",
+        names.join(",")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_san_extension() {
+        let ext = san_extension(&["cache-node-0".to_string(), "10.0.0.5".to_string()]);
+        assert!(ext
+            .ends_with("subjectAltName=DNS:localhost,IP:127.0.0.1,DNS:cache-node-0,IP:10.0.0.5\n"));
+        assert_eq!(container_path(CA_CERT), "/tls/ca.crt");
+    }
+
+    #[test]
+    fn test_client_bundle_files() {
+        let client = Path::new("/state/tls/c/client");
+        let script = cli_script(client, "c", 7000, true);
+        assert!(script.contains("${REDISCLI_AUTH:?set REDISCLI_AUTH to the password of c"));
+        assert!(script.contains("-p 7000 -c --tls --cacert '/state/tls/c/client/ca.crt'"));
+        assert_eq!(shell_quote("it's"), r"'it'\''s'");
+
+        let config = stunnel_config(client, &[7000, 65000]);
+        assert!(config.contains("[redis-7000]\naccept = 127.0.0.1:27000\nconnect = localhost:7000"));
+        // No local port fits above 65535
+        assert!(!config.contains("[redis-65000]"));
+    }
+}
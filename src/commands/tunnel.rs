@@ -0,0 +1,398 @@
+//! SSH local port-forwards to instances on a remote Docker host
+//!
+//! `redis-up tunnel <name> --via user@devbox` looks the instance up in the
+//! registry of the redis-up on the remote host (or the local one, for adopted
+//! instances), starts `ssh -N -L ...` in the background and records its
+//! process in `tunnels.json` so `tunnel stop` can close it later.
+
+use anyhow::{bail, Context, Result};
+use chrono::Utc;
+use colored::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+use tokio::process::Command as ProcessCommand;
+
+use crate::cli::{TunnelAction, TunnelArgs};
+use crate::config::{get_state_dir, Config, InstanceInfo};
+use crate::error::Failure;
+
+/// Open tunnels, by instance name
+const TUNNELS_FILE: &str = "tunnels.json";
+
+/// How long ssh gets to connect and start listening
+const OPEN_TIMEOUT: Duration = Duration::from_secs(15);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Tunnel {
+    pid: u32,
+    via: String,
+    /// (local port, remote port) pairs
+    forwards: Vec<(u16, u16)>,
+    started_at: String,
+    /// Connection URL through the tunnel
+    url: String,
+}
+
+pub async fn handle_tunnel(args: TunnelArgs, verbose: bool) -> Result<()> {
+    match args.action {
+        Some(TunnelAction::Stop { name, all }) => stop(name.as_deref(), all),
+        Some(TunnelAction::List) => list(),
+        None => {
+            let (Some(name), Some(via)) = (args.name, args.via) else {
+                bail!("Usage: redis-up tunnel <NAME> --via <DESTINATION>");
+            };
+            open(&name, &via, args.local_port, verbose).await
+        }
+    }
+}
+
+async fn open(name: &str, via: &str, local_port: Option<u16>, verbose: bool) -> Result<()> {
+    let mut tunnels = load()?;
+    if let Some(tunnel) = tunnels.get(name) {
+        if is_running(tunnel.pid) {
+            bail!(
+                "A tunnel to '{}' is already open via {} (pid {}); close it with `redis-up tunnel stop {}`",
+                name,
+                tunnel.via,
+                tunnel.pid,
+                name
+            );
+        }
+    }
+
+    let instance = match Config::load()?.get_instance(name) {
+        Some(instance) => instance.clone(),
+        None => remote_instance(via, name, verbose).await?,
+    };
+    let forwards = forwards(&instance, local_port)?;
+    for (local, _) in &forwards {
+        if TcpListener::bind(("127.0.0.1", *local)).is_err() {
+            return Err(Failure::PortConflict(format!(
+                "Local port {} is already in use; pick another with --local-port",
+                local
+            ))
+            .into());
+        }
+    }
+
+    let log_path = log_path(name)?;
+    let log = fs::File::create(&log_path)
+        .with_context(|| format!("Failed to create {}", log_path.display()))?;
+    let mut ssh = Command::new("ssh");
+    ssh.args([
+        "-N",
+        "-o",
+        "ExitOnForwardFailure=yes",
+        "-o",
+        "BatchMode=yes",
+    ])
+    .args(["-o", "ServerAliveInterval=30"]);
+    for (local, remote) in &forwards {
+        ssh.arg("-L")
+            .arg(format!("127.0.0.1:{}:localhost:{}", local, remote));
+    }
+    ssh.arg(via)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(log);
+    // Keep Ctrl-C in this terminal from reaching the tunnel later
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut ssh, 0);
+    if verbose {
+        println!("{} {:?}", "Running:".cyan(), ssh);
+    }
+    let mut child = ssh.spawn().context("Failed to run ssh; is it installed?")?;
+
+    // Ready once ssh listens on the first local port
+    let started = Instant::now();
+    let (first, _) = forwards[0];
+    loop {
+        if let Some(status) = child.try_wait()? {
+            let output = fs::read_to_string(&log_path).unwrap_or_default();
+            let _ = fs::remove_file(&log_path);
+            bail!("ssh to {} exited ({}): {}", via, status, output.trim());
+        }
+        if TcpStream::connect(("127.0.0.1", first)).is_ok() {
+            break;
+        }
+        if started.elapsed() > OPEN_TIMEOUT {
+            let _ = child.kill();
+            return Err(Failure::Timeout(format!(
+                "ssh to {} did not open port {} within {}s",
+                via,
+                first,
+                OPEN_TIMEOUT.as_secs()
+            ))
+            .into());
+        }
+        tokio::time::sleep(Duration::from_millis(200)).await;
+    }
+
+    let url = match &instance.connection_info.password {
+        Some(password) => format!("redis://:{}@localhost:{}", password, first),
+        None => format!("redis://localhost:{}", first),
+    };
+    tunnels.insert(
+        name.to_string(),
+        Tunnel {
+            pid: child.id(),
+            via: via.to_string(),
+            forwards: forwards.clone(),
+            started_at: Utc::now().to_rfc3339(),
+            url: url.clone(),
+        },
+    );
+    save(&tunnels)?;
+
+    println!(
+        "{} Tunnel to {} via {} is open",
+        "Success:".bold().green(),
+        name.bold(),
+        via.cyan()
+    );
+    for (local, remote) in &forwards {
+        println!(
+            "  localhost:{} -> {}:{}",
+            local.to_string().cyan(),
+            via,
+            remote
+        );
+    }
+    println!("  {}: {}", "URL".bold(), url.blue());
+    println!(
+        "  Close it with: {}",
+        format!("redis-up tunnel stop {}", name).green()
+    );
+    Ok(())
+}
+
+/// The instance as the redis-up on the remote host records it
+async fn remote_instance(via: &str, name: &str, verbose: bool) -> Result<InstanceInfo> {
+    if verbose {
+        println!(
+            "{} Reading instances from redis-up on {}",
+            "Tunnel:".cyan(),
+            via
+        );
+    }
+    let output = ProcessCommand::new("ssh")
+        .args(["-o", "BatchMode=yes", via, "redis-up", "config", "export"])
+        .stdin(Stdio::null())
+        .output()
+        .await
+        .context("Failed to run ssh; is it installed?")?;
+    if !output.status.success() {
+        bail!(
+            "Could not read instances from redis-up on {}: {}",
+            via,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    let config = Config::parse(&String::from_utf8_lossy(&output.stdout))
+        .with_context(|| format!("Unexpected `redis-up config export` output from {}", via))?;
+    config.get_instance(name).cloned().with_context(|| {
+        format!(
+            "No instance '{}' on {}; see `ssh {} redis-up list`",
+            name, via, via
+        )
+    })
+}
+
+/// (local, remote) port pairs; `--local-port` only renames a single port
+fn forwards(instance: &InstanceInfo, local_port: Option<u16>) -> Result<Vec<(u16, u16)>> {
+    let mut ports = instance.ports.clone();
+    ports.dedup();
+    match (ports.as_slice(), local_port) {
+        ([], _) => bail!("Instance '{}' has no published ports", instance.name),
+        ([port], Some(local)) => Ok(vec![(local, *port)]),
+        (_, Some(_)) => bail!(
+            "'{}' has {} ports, which keep their numbers; --local-port only applies to single-port instances",
+            instance.name,
+            ports.len()
+        ),
+        (ports, None) => Ok(ports.iter().map(|port| (*port, *port)).collect()),
+    }
+}
+
+fn stop(name: Option<&str>, all: bool) -> Result<()> {
+    let mut tunnels = load()?;
+    let names: Vec<String> = if all {
+        tunnels.keys().cloned().collect()
+    } else {
+        let name = name.unwrap_or_default();
+        if !tunnels.contains_key(name) {
+            bail!("No tunnel to '{}'; see `redis-up tunnel list`", name);
+        }
+        vec![name.to_string()]
+    };
+    if names.is_empty() {
+        println!("{} No tunnels open", "Info:".blue());
+        return Ok(());
+    }
+
+    for name in names {
+        let Some(tunnel) = tunnels.remove(&name) else {
+            continue;
+        };
+        if is_running(tunnel.pid) {
+            kill(tunnel.pid)?;
+            println!(
+                "{} Closed tunnel to {} via {}",
+                "Success:".green(),
+                name.bold(),
+                tunnel.via
+            );
+        } else {
+            println!(
+                "{} Tunnel to {} had already exited",
+                "Info:".blue(),
+                name.bold()
+            );
+        }
+        let _ = fs::remove_file(log_path(&name)?);
+    }
+    save(&tunnels)
+}
+
+fn list() -> Result<()> {
+    let tunnels = load()?;
+    if tunnels.is_empty() {
+        println!("{} No tunnels open", "Info:".blue());
+        return Ok(());
+    }
+    println!("{}", "Tunnels:".bold().cyan());
+    for (name, tunnel) in &tunnels {
+        let ports: Vec<String> = tunnel
+            .forwards
+            .iter()
+            .map(|(local, remote)| {
+                if local == remote {
+                    local.to_string()
+                } else {
+                    format!("{}->{}", local, remote)
+                }
+            })
+            .collect();
+        let status = if is_running(tunnel.pid) {
+            "open".green()
+        } else {
+            "exited".red()
+        };
+        println!(
+            "  {} via {}  ports {}  pid {}  {}",
+            name.bold(),
+            tunnel.via.cyan(),
+            ports.join(","),
+            tunnel.pid,
+            status
+        );
+    }
+    Ok(())
+}
+
+fn tunnels_path() -> Result<PathBuf> {
+    Ok(get_state_dir()?.join(TUNNELS_FILE))
+}
+
+fn log_path(name: &str) -> Result<PathBuf> {
+    Ok(get_state_dir()?.join(format!("tunnel-{}.log", name)))
+}
+
+fn load() -> Result<BTreeMap<String, Tunnel>> {
+    let path = tunnels_path()?;
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    let content =
+        fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+    serde_json::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+fn save(tunnels: &BTreeMap<String, Tunnel>) -> Result<()> {
+    let path = tunnels_path()?;
+    fs::write(&path, serde_json::to_string_pretty(tunnels)?)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Whether the process is alive and still ssh, so a reused pid is never killed
+#[cfg(unix)]
+fn is_running(pid: u32) -> bool {
+    Command::new("ps")
+        .args(["-p", &pid.to_string(), "-o", "comm="])
+        .output()
+        .is_ok_and(|output| {
+            String::from_utf8_lossy(&output.stdout)
+                .trim()
+                .ends_with("ssh")
+        })
+}
+
+#[cfg(windows)]
+fn is_running(pid: u32) -> bool {
+    Command::new("tasklist")
+        .args(["/FI", &format!("PID eq {}", pid), "/NH"])
+        .output()
+        .is_ok_and(|output| String::from_utf8_lossy(&output.stdout).contains("ssh"))
+}
+
+#[cfg(unix)]
+fn kill(pid: u32) -> Result<()> {
+    let status = Command::new("kill").arg(pid.to_string()).status()?;
+    if !status.success() {
+        bail!("Failed to stop ssh (pid {})", pid);
+    }
+    Ok(())
+}
+
+#[cfg(windows)]
+fn kill(pid: u32) -> Result<()> {
+    let status = Command::new("taskkill")
+        .args(["/PID", &pid.to_string(), "/F"])
+        .status()?;
+    if !status.success() {
+        bail!("Failed to stop ssh (pid {})", pid);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_forwards() {
+        let mut instance: InstanceInfo = serde_json::from_value(serde_json::json!({
+            "name": "cache",
+            "instance_type": "basic",
+            "created_at": "",
+            "ports": [6379],
+            "containers": [],
+            "connection_info": {
+                "host": "localhost",
+                "port": 6379,
+                "password": null,
+                "url": "",
+                "additional_ports": {}
+            },
+            "metadata": {}
+        }))
+        .unwrap();
+        assert_eq!(forwards(&instance, None).unwrap(), vec![(6379, 6379)]);
+        assert_eq!(
+            forwards(&instance, Some(16379)).unwrap(),
+            vec![(16379, 6379)]
+        );
+
+        instance.ports = vec![7000, 7001];
+        assert_eq!(
+            forwards(&instance, None).unwrap(),
+            vec![(7000, 7000), (7001, 7001)]
+        );
+        assert!(forwards(&instance, Some(17000)).is_err());
+    }
+}
@@ -0,0 +1,74 @@
+//! Unlock and lock encrypted instance secrets
+
+use anyhow::Result;
+use colored::*;
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+
+use crate::cli::UnlockArgs;
+use crate::config::Config;
+use crate::secrets;
+use crate::settings::Settings;
+
+pub async fn handle_unlock(args: UnlockArgs) -> Result<()> {
+    let passphrase = match std::env::var(secrets::PASSPHRASE_ENV) {
+        Ok(passphrase) => passphrase,
+        Err(_) => prompt_passphrase()?,
+    };
+    let cipher = secrets::unlock_key(&passphrase)?;
+    let expires_at = secrets::start_session(&cipher, args.ttl)?;
+
+    let settings = Settings::load()?;
+    if settings.encrypt_secrets {
+        // Encrypts any secrets still stored in plain text
+        Config::load()?.save()?;
+    } else {
+        println!(
+            "{} encrypt-secrets is off in settings, so new passwords are stored in plain text",
+            "Note:".dimmed()
+        );
+    }
+
+    println!(
+        "{} Secrets unlocked until {}",
+        "Unlocked:".green(),
+        expires_at
+            .with_timezone(&chrono::Local)
+            .format("%Y-%m-%d %H:%M")
+    );
+    Ok(())
+}
+
+pub async fn handle_lock() -> Result<()> {
+    if secrets::end_session()? {
+        println!("{} Unlock session removed", "Locked:".green());
+    } else {
+        println!("{} Secrets were not unlocked", "Info:".blue());
+    }
+    Ok(())
+}
+
+fn prompt_passphrase() -> Result<String> {
+    print!("{} ", "Passphrase:".bold());
+    io::stdout().flush()?;
+
+    let hidden = set_echo(false);
+    let mut input = String::new();
+    let read = io::stdin().read_line(&mut input);
+    if hidden {
+        set_echo(true);
+        println!();
+    }
+    read?;
+
+    Ok(input.trim_end_matches(['\r', '\n']).to_string())
+}
+
+/// Turn terminal echo on or off, returning whether it worked
+fn set_echo(on: bool) -> bool {
+    Command::new("stty")
+        .arg(if on { "echo" } else { "-echo" })
+        .stdin(Stdio::inherit())
+        .status()
+        .is_ok_and(|status| status.success())
+}
@@ -0,0 +1,309 @@
+//! Container uptime, last-seen tracking and stale instance detection
+//!
+//! `list`, `info` and `cleanup --stale` ask Docker for the state of each
+//! instance's containers, and the start time and last time the instance was
+//! seen running are kept on the instance so they survive the containers.
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use colored::*;
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::process::Command as ProcessCommand;
+
+use crate::config::{Config, InstanceInfo};
+use crate::error::{self, Failure};
+
+/// Granularity of the recorded last-seen time
+pub const LAST_SEEN_STEP: Duration = Duration::from_secs(60);
+
+/// How long an instance's containers must have been exited to count as stale
+pub const DEFAULT_STALE: Duration = Duration::from_secs(24 * 3600);
+
+/// State of one container as `docker inspect` reports it
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContainerState {
+    pub running: bool,
+    pub started_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+    /// Health check status, for containers that have one
+    pub health: Option<String>,
+}
+
+/// State of every listed container that still exists, keyed the way the
+/// container was given (name or full or short ID)
+pub async fn inspect(containers: &[String]) -> Result<HashMap<String, ContainerState>> {
+    if containers.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let output = ProcessCommand::new("docker")
+        .arg("inspect")
+        .arg("--format")
+        .arg(
+            "{{.Id}}|{{.Name}}|{{.State.Status}}|{{.State.StartedAt}}|{{.State.FinishedAt}}|\
+             {{if .State.Health}}{{.State.Health.Status}}{{end}}",
+        )
+        .args(containers)
+        .output()
+        .await
+        .map_err(|e| Failure::DockerUnavailable(format!("Failed to run docker inspect: {}", e)))?;
+    // Missing containers make inspect fail but still print the others
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success() && error::daemon_unreachable(&stderr) {
+        return Err(Failure::DockerUnavailable(format!(
+            "docker inspect failed: {}",
+            stderr.trim()
+        ))
+        .into());
+    }
+    if !output.status.success() && stdout.trim().is_empty() && !stderr.contains("No such") {
+        anyhow::bail!("docker inspect failed: {}", stderr.trim());
+    }
+    Ok(parse_inspect(&stdout, containers))
+}
+
+fn parse_inspect(stdout: &str, containers: &[String]) -> HashMap<String, ContainerState> {
+    let mut states = HashMap::new();
+    for line in stdout.lines() {
+        let fields: Vec<&str> = line.trim().split('|').collect();
+        let [id, name, status, started, finished, health] = fields[..] else {
+            continue;
+        };
+        let name = name.trim_start_matches('/');
+        let Some(key) = containers
+            .iter()
+            .find(|c| c.as_str() == name || id.starts_with(c.as_str()))
+        else {
+            continue;
+        };
+        states.insert(
+            key.clone(),
+            ContainerState {
+                running: status == "running",
+                started_at: timestamp(started),
+                finished_at: timestamp(finished),
+                health: (!health.is_empty()).then(|| health.to_string()),
+            },
+        );
+    }
+    states
+}
+
+/// Docker reports never-set times as the zero time
+fn timestamp(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|t| t.with_timezone(&Utc))
+        .filter(|t| t.timestamp() > 0)
+}
+
+/// Update an instance's start time and last-seen time from its container
+/// states, returning whether either changed
+///
+/// The last-seen time only moves in steps of [`LAST_SEEN_STEP`], so listing
+/// running instances doesn't rewrite the instance file every time.
+pub fn observe(
+    instance: &mut InstanceInfo,
+    states: &HashMap<String, ContainerState>,
+    now: DateTime<Utc>,
+) -> bool {
+    let running: Vec<&ContainerState> = instance
+        .containers
+        .iter()
+        .filter_map(|c| states.get(c))
+        .filter(|s| s.running)
+        .collect();
+    if running.is_empty() {
+        return false;
+    }
+    let mut changed = false;
+    // Uptime counts from the earliest container that is still up
+    if let Some(started) = running.iter().filter_map(|s| s.started_at).min() {
+        let started = Some(started.to_rfc3339());
+        changed |= instance.started_at != started;
+        instance.started_at = started;
+    }
+    let healthy = running
+        .iter()
+        .all(|s| s.health.as_deref() != Some("unhealthy"));
+    let stale = parse(instance.last_seen.as_ref())
+        .is_none_or(|seen| now - seen >= chrono::Duration::from_std(LAST_SEEN_STEP).unwrap());
+    if running.len() == instance.containers.len() && healthy && stale {
+        instance.last_seen = Some(now.to_rfc3339());
+        changed = true;
+    }
+    changed
+}
+
+/// When every container of the instance exited, if they all exist and none is running
+pub fn exited_at(
+    instance: &InstanceInfo,
+    states: &HashMap<String, ContainerState>,
+) -> Option<DateTime<Utc>> {
+    let found: Vec<&ContainerState> = instance
+        .containers
+        .iter()
+        .filter_map(|c| states.get(c))
+        .collect();
+    if found.is_empty()
+        || found.len() != instance.containers.len()
+        || found.iter().any(|s| s.running)
+    {
+        return None;
+    }
+    found.iter().filter_map(|s| s.finished_at).max()
+}
+
+/// Inspect the named instances' containers, record what was seen and save
+///
+/// Docker being unavailable is not an error: nothing is updated and `None`
+/// is returned.
+pub async fn refresh(
+    config: &mut Config,
+    names: &[String],
+) -> Option<HashMap<String, ContainerState>> {
+    let containers: Vec<String> = names
+        .iter()
+        .filter_map(|name| config.get_instance(name))
+        .flat_map(|instance| instance.containers.clone())
+        .collect();
+    let states = inspect(&containers).await.ok()?;
+    let now = Utc::now();
+    let updated = Config::update(|fresh| {
+        let mut changed = false;
+        for name in names {
+            if let Some(instance) = fresh.get_instance_mut(name) {
+                changed |= observe(instance, &states, now);
+            }
+        }
+        Ok(changed)
+    });
+    match updated {
+        Ok(fresh) => *config = fresh,
+        // Stderr, so `list --json` and `eval $(redis-up env ...)` stay parseable
+        Err(e) => eprintln!(
+            "{} Could not record instance start and last-seen times: {:#}",
+            "Warning:".yellow(),
+            e
+        ),
+    }
+    Some(states)
+}
+
+/// Time between two instants, to the largest two units (`3h 12m`)
+pub fn format_age(from: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let seconds = (now - from).num_seconds().max(0) as u64;
+    // Drop the units the two-unit display would cut off anyway
+    let rounded = match seconds {
+        0..=59 => seconds,
+        60..=86399 => seconds / 60 * 60,
+        _ => seconds / 3600 * 3600,
+    };
+    let text = humantime::format_duration(Duration::from_secs(rounded)).to_string();
+    text.split(' ').take(2).collect::<Vec<_>>().join(" ")
+}
+
+fn parse(value: Option<&String>) -> Option<DateTime<Utc>> {
+    value
+        .and_then(|v| DateTime::parse_from_rfc3339(v).ok())
+        .map(|t| t.with_timezone(&Utc))
+}
+
+/// Uptime since the containers last started, once they have been seen running
+pub fn uptime(instance: &InstanceInfo, now: DateTime<Utc>) -> Option<String> {
+    parse(instance.started_at.as_ref()).map(|started| format_age(started, now))
+}
+
+/// `Uptime` or `Last seen` line for info output, after refreshing what was seen
+pub async fn info_lines(config: &mut Config, name: &str) -> Vec<(&'static str, String)> {
+    let states = refresh(config, &[name.to_string()]).await;
+    let Some(instance) = config.get_instance(name) else {
+        return Vec::new();
+    };
+    let running = states.is_some_and(|states| {
+        instance
+            .containers
+            .iter()
+            .any(|c| states.get(c).is_some_and(|s| s.running))
+    });
+    let now = Utc::now();
+    let mut lines = Vec::new();
+    if running {
+        if let Some(uptime) = uptime(instance, now) {
+            lines.push(("Uptime", uptime));
+        }
+    } else if let Some(seen) = parse(instance.last_seen.as_ref()) {
+        lines.push(("Last seen", format!("{} ago", format_age(seen, now))));
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_observe_and_exited() {
+        let containers = vec!["cache".to_string(), "abc123".to_string()];
+        let stdout = "\
+abc123def|/cache-replica|exited|2026-01-01T10:00:00.5Z|2026-01-02T08:00:00Z|
+ffff|/cache|running|2026-01-01T09:00:00Z|0001-01-01T00:00:00Z|healthy
+";
+        let states = parse_inspect(stdout, &containers);
+        assert_eq!(states.len(), 2);
+        assert!(states["cache"].running);
+        assert_eq!(states["cache"].finished_at, None);
+        assert_eq!(states["cache"].health.as_deref(), Some("healthy"));
+
+        let mut instance: InstanceInfo = serde_json::from_value(serde_json::json!({
+            "name": "cache",
+            "instance_type": "basic",
+            "created_at": "",
+            "ports": [6379],
+            "containers": containers,
+            "connection_info": {
+                "host": "localhost",
+                "port": 6379,
+                "password": null,
+                "url": "",
+                "additional_ports": {}
+            },
+            "metadata": {}
+        }))
+        .unwrap();
+        let now: DateTime<Utc> = "2026-01-02T12:00:00Z".parse().unwrap();
+        assert!(observe(&mut instance, &states, now));
+        assert_eq!(uptime(&instance, now).unwrap(), "1day 3h");
+        // One of the two containers is down, so the instance was not fully seen
+        assert_eq!(instance.last_seen, None);
+        assert!(!observe(&mut instance, &states, now));
+
+        // Seen again within a step: nothing to record
+        let mut all_up = states.clone();
+        all_up.get_mut("abc123").unwrap().running = true;
+        let mut seen = instance.clone();
+        assert!(observe(&mut seen, &all_up, now));
+        let later = now + chrono::Duration::seconds(30);
+        assert!(!observe(&mut seen, &all_up, later));
+        assert!(observe(
+            &mut seen,
+            &all_up,
+            now + chrono::Duration::seconds(90)
+        ));
+        assert_eq!(exited_at(&instance, &states), None);
+
+        let mut stopped = states.clone();
+        stopped.get_mut("cache").unwrap().running = false;
+        stopped.get_mut("cache").unwrap().finished_at =
+            Some("2026-01-02T09:00:00Z".parse().unwrap());
+        assert_eq!(
+            exited_at(&instance, &stopped),
+            Some("2026-01-02T09:00:00Z".parse().unwrap())
+        );
+        assert_eq!(
+            format_age("2026-01-02T11:59:30Z".parse().unwrap(), now),
+            "30s"
+        );
+    }
+}
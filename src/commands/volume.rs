@@ -0,0 +1,147 @@
+//! Data volume helpers shared by start commands
+
+use anyhow::{Context, Result};
+use colored::*;
+use docker_wrapper::{
+    CpCommand, CreateCommand, DockerCommand, RmCommand, VolumeCreateCommand, VolumeInspectCommand,
+};
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+use crate::commands::images;
+use crate::commands::plan::Plan;
+use crate::config::InstanceInfo;
+
+/// Volume holding an instance's data: the one given with `--data-volume`, or `<name>-data`
+pub fn data_volume_name(name: &str, existing: Option<&str>) -> String {
+    existing.map_or_else(|| format!("{}-data", name), String::from)
+}
+
+/// Check that a volume passed with `--data-volume` exists
+///
+/// Docker would otherwise create an empty volume and the instance would
+/// silently start without the expected data.
+pub async fn ensure_volume_exists(volume: &str) -> Result<()> {
+    VolumeInspectCommand::new(volume)
+        .execute()
+        .await
+        .with_context(|| {
+            format!(
+                "Data volume '{}' not found. List volumes with 'docker volume ls'.",
+                volume
+            )
+        })?;
+    Ok(())
+}
+
+/// Whether stopping an instance should leave its data volumes in place
+///
+/// Instances started with `--persist` keep their data unless told otherwise,
+/// so the next start with the same name picks it up again.
+pub fn keep_data(instance: &InstanceInfo, keep_flag: bool) -> bool {
+    keep_flag
+        || instance
+            .metadata
+            .get("persist")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false)
+}
+
+/// Tell the user where their data went after a stop
+pub fn report_kept(name: &str, kept: bool) {
+    if kept {
+        println!(
+            "{} Data volumes kept; start '{}' again to reattach them",
+            "Info:".blue(),
+            name
+        );
+    }
+}
+
+/// Force-remove a container, deleting its volumes unless data is kept
+pub fn remove_command(container: &str, keep_data: bool) -> RmCommand {
+    let rm = RmCommand::new(container).force();
+    if keep_data {
+        rm
+    } else {
+        rm.volumes()
+    }
+}
+
+/// Check that an RDB file given on the command line can be loaded
+pub fn validate_rdb(path: &Path) -> Result<()> {
+    if !path.is_file() {
+        anyhow::bail!("RDB file not found: {}", path.display());
+    }
+    Ok(())
+}
+
+/// Commands that copy an RDB file into a data volume so Redis loads it on first boot
+///
+/// The file is copied through a stopped helper container, which also works
+/// against remote Docker hosts where bind mounts would not.
+pub struct RdbPreload {
+    volume: String,
+    helper: String,
+    path: PathBuf,
+    create_volume: VolumeCreateCommand,
+    create_helper: CreateCommand,
+    copy: CpCommand,
+    remove_helper: RmCommand,
+}
+
+impl RdbPreload {
+    pub fn new(volume: &str, image: &str, path: &Path) -> Self {
+        let helper = format!("{}-rdb-loader", volume);
+        Self {
+            volume: volume.to_string(),
+            path: path.to_path_buf(),
+            create_volume: VolumeCreateCommand::new().name(volume),
+            create_helper: CreateCommand::new(images::resolve(image))
+                .name(&helper)
+                .volume(format!("{}:/data", volume)),
+            copy: CpCommand::from_host(path).to_container(&helper, "/data/dump.rdb"),
+            remove_helper: RmCommand::new(&helper).force(),
+            helper,
+        }
+    }
+
+    /// The RDB file being loaded
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Add the copy to a dry-run plan
+    pub fn plan(&self, plan: &mut Plan) {
+        plan.docker(&self.create_volume);
+        plan.docker(&self.create_helper);
+        plan.docker(&self.copy);
+        plan.docker(&self.remove_helper);
+    }
+
+    pub async fn execute(&self) -> Result<()> {
+        self.create_volume
+            .execute()
+            .await
+            .with_context(|| format!("Failed to create data volume: {}", self.volume))?;
+
+        self.create_helper
+            .execute()
+            .await
+            .with_context(|| format!("Failed to create helper container: {}", self.helper))?;
+
+        let copied = self.copy.execute().await.with_context(|| {
+            format!(
+                "Failed to copy {} into {}",
+                self.path.display(),
+                self.volume
+            )
+        });
+
+        if let Err(e) = self.remove_helper.execute().await {
+            warn!("Failed to remove helper container {}: {}", self.helper, e);
+        }
+
+        copied.map(|_| ())
+    }
+}
@@ -0,0 +1,314 @@
+//! `redis-up warm`: get ready for a fast start
+//!
+//! Warming pulls every image that a deployment file (or `--type` with the
+//! global `--profile`) would run, found from the same plan `--dry-run` prints.
+//! With `--create` the instances are also started once and stopped again.
+//!
+//! Starting a registered instance whose containers all exist but are stopped,
+//! warmed or not, starts those containers instead of creating new ones.
+
+use anyhow::{bail, Context, Result};
+use clap::Parser;
+use colored::*;
+use docker_wrapper::{DockerCommand, StartCommand};
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+use crate::cli::{Cli, Commands, WarmArgs};
+use crate::commands::plan::{self, Operation, Plan};
+use crate::commands::{images, profile, restore, server, shutdown, uptime, yaml};
+use crate::config::{Config, InstanceInfo, InstanceType};
+use crate::error::Failure;
+
+/// Metadata flag of instances that `warm --create` left stopped
+pub const WARMED: &str = "warmed";
+
+/// How long reused containers get to answer PING
+const READY_TIMEOUT: Duration = Duration::from_secs(30);
+
+pub async fn handle_warm(args: WarmArgs, profile: Option<&str>, verbose: bool) -> Result<()> {
+    let (command, argv, mut names) = match (&args.file, &args.kind) {
+        (Some(file), _) => {
+            let names: Vec<String> = yaml::load_yaml(file, profile)
+                .await?
+                .deployments
+                .into_iter()
+                .map(|d| d.name)
+                .collect();
+            (Commands::Deploy { file: file.clone() }, Vec::new(), names)
+        }
+        (None, Some(kind)) => {
+            let argv = start_argv(kind, profile, args.name.as_deref());
+            let cli = Cli::try_parse_from(profile::expand(argv.clone())?)
+                .map_err(|e| anyhow::anyhow!("Invalid start arguments: {}", e))?;
+            let command = cli.command.context("No start command to warm up for")?;
+            (command, argv, args.name.iter().cloned().collect())
+        }
+        (None, None) => bail!("Give a deployment file or --type"),
+    };
+
+    let plans = plan::plan_command(&command, None, profile)
+        .await?
+        .context("Nothing to warm up for")?;
+    let wanted = plan_images(&plans);
+    let mut pulled = 0;
+    for (image, platform) in &wanted {
+        if images::is_present(image).await {
+            if verbose {
+                println!("  {} {}", "Present".green(), image);
+            }
+            continue;
+        }
+        println!("{} {}", "Pulling".cyan(), image.bold());
+        images::pull(image, platform.as_deref()).await?;
+        pulled += 1;
+    }
+    println!(
+        "{} {} image(s) ready ({} pulled, {} already present)",
+        "Success:".green(),
+        wanted.len(),
+        pulled,
+        wanted.len() - pulled
+    );
+
+    if !args.create {
+        return Ok(());
+    }
+
+    let before: HashSet<String> = Config::load()?.instances.keys().cloned().collect();
+    match &command {
+        Commands::Deploy { file } => yaml::deploy_from_yaml(file, profile, verbose).await?,
+        _ => restore::run_start(&argv, verbose).await?,
+    }
+    let mut config = Config::load()?;
+    names.extend(
+        config
+            .instances
+            .keys()
+            .filter(|name| !before.contains(*name))
+            .cloned(),
+    );
+    names.sort();
+    names.dedup();
+
+    println!();
+    let mut warmed = Vec::new();
+    for name in &names {
+        let Some(instance) = config.get_instance(name) else {
+            continue;
+        };
+        let failed = shutdown::stop_containers(instance, None, verbose).await;
+        if let Some((container, e)) = failed.first() {
+            println!(
+                "{} Could not stop {} of {}: {}",
+                "Warning:".yellow(),
+                container,
+                name.bold(),
+                e
+            );
+            continue;
+        }
+        println!(
+            "{} {} is created and stopped; `redis-up {} start --name {}` starts it",
+            "Warmed:".green(),
+            name.bold(),
+            instance.instance_type,
+            name
+        );
+        warmed.push(name);
+    }
+
+    // Stopped on purpose, so the check for instances stopped by a reboot leaves them be
+    for name in warmed {
+        if let Some(instance) = config.get_instance_mut(name) {
+            instance
+                .metadata
+                .insert(WARMED.to_string(), serde_json::Value::Bool(true));
+        }
+    }
+    config.save()
+}
+
+/// `redis-up <kind> start` with the profile and name to warm up for
+fn start_argv(kind: &str, profile: Option<&str>, name: Option<&str>) -> Vec<String> {
+    let mut argv = vec![
+        "redis-up".to_string(),
+        kind.to_string(),
+        "start".to_string(),
+    ];
+    if let Some(profile) = profile {
+        argv.extend(["--profile".to_string(), profile.to_string()]);
+    }
+    if let Some(name) = name {
+        argv.extend(["--name".to_string(), name.to_string()]);
+    }
+    argv
+}
+
+/// Images the plans run, each once, with the platform it runs under
+fn plan_images(plans: &[Plan]) -> Vec<(String, Option<String>)> {
+    let mut wanted: Vec<(String, Option<String>)> = Vec::new();
+    for operation in plans.iter().flat_map(|plan| &plan.operations) {
+        if let Operation::Run {
+            image, platform, ..
+        } = operation
+        {
+            if !wanted.iter().any(|(wanted, _)| wanted == image) {
+                wanted.push((image.clone(), platform.clone()));
+            }
+        }
+    }
+    wanted
+}
+
+/// Start a registered instance whose containers all exist but are stopped
+///
+/// Returns false when there is nothing to reuse and the instance should be created.
+pub async fn reuse_stopped(name: Option<&str>, verbose: bool) -> Result<bool> {
+    let Some(name) = name else {
+        return Ok(false);
+    };
+    let config = Config::load()?;
+    let Some(instance) = config.get_instance(name) else {
+        return Ok(false);
+    };
+    // Without Docker there is nothing to reuse; the normal start reports the problem
+    let states = uptime::inspect(&instance.containers)
+        .await
+        .unwrap_or_default();
+    let stopped = instance
+        .containers
+        .iter()
+        .all(|container| states.get(container).is_some_and(|state| !state.running));
+    if instance.containers.is_empty() || !stopped {
+        return Ok(false);
+    }
+
+    if verbose {
+        println!(
+            "{} Reusing the stopped containers of {}",
+            "Starting".cyan(),
+            name.bold()
+        );
+    }
+    for container in &instance.containers {
+        StartCommand::new(container)
+            .execute()
+            .await
+            .with_context(|| format!("Failed to start {}", container))?;
+    }
+    wait_for_servers(instance).await?;
+    mark_started(name)?;
+
+    println!(
+        "{} Started {} from its stopped containers",
+        "Success:".green(),
+        name.bold()
+    );
+    println!(
+        "  {}: {}",
+        "URL".bold(),
+        instance.connection_info.url.blue()
+    );
+    println!(
+        "  {}",
+        format!(
+            "Start flags were not applied; `redis-up {} stop {}` first to recreate it",
+            instance.instance_type, name
+        )
+        .dimmed()
+    );
+    Ok(true)
+}
+
+/// Clear the warmed flag of an instance whose containers were started again
+pub fn mark_started(name: &str) -> Result<()> {
+    let mut config = Config::load()?;
+    let cleared = config
+        .get_instance_mut(name)
+        .and_then(|instance| instance.metadata.remove(WARMED));
+    if cleared.is_some() {
+        config.save()?;
+    }
+    Ok(())
+}
+
+/// Wait until every Redis server of the instance answers PING
+pub async fn wait_for_servers(instance: &InstanceInfo) -> Result<()> {
+    if instance.instance_type == InstanceType::Enterprise {
+        return Ok(());
+    }
+    let sentinels: Vec<&str> = instance
+        .metadata
+        .get("sentinel_containers")
+        .and_then(|v| v.as_array())
+        .map(|ids| ids.iter().filter_map(|id| id.as_str()).collect())
+        .unwrap_or_default();
+    let password = instance.connection_info.password.as_deref();
+    let started = Instant::now();
+    for container in &instance.containers {
+        if container.ends_with("-insight") || sentinels.contains(&container.as_str()) {
+            continue;
+        }
+        loop {
+            let reply = server::redis_cli(container, password, &["PING"]).await;
+            if reply.is_ok_and(|reply| reply.trim() == "PONG") {
+                break;
+            }
+            if started.elapsed() > READY_TIMEOUT {
+                return Err(Failure::Timeout(format!(
+                    "{} did not answer PING within {}s",
+                    container,
+                    READY_TIMEOUT.as_secs()
+                ))
+                .into());
+            }
+            tokio::time::sleep(Duration::from_millis(200)).await;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use docker_wrapper::RunCommand;
+
+    #[test]
+    fn test_plan_images() {
+        let mut plan = Plan::new("cluster");
+        plan.platform = Some("linux/amd64".to_string());
+        plan.run(
+            "redis:7-alpine",
+            &RunCommand::new("redis:7-alpine").name("c-node-0"),
+            None,
+        );
+        plan.run(
+            "redis:7-alpine",
+            &RunCommand::new("redis:7-alpine").name("c-node-1"),
+            None,
+        );
+        plan.run(
+            "redis/redisinsight:latest",
+            &RunCommand::new("redis/redisinsight:latest").name("c-insight"),
+            None,
+        );
+        assert_eq!(
+            plan_images(&[plan]),
+            vec![
+                (
+                    "redis:7-alpine".to_string(),
+                    Some("linux/amd64".to_string())
+                ),
+                (
+                    "redis/redisinsight:latest".to_string(),
+                    Some("linux/amd64".to_string())
+                ),
+            ]
+        );
+        assert_eq!(
+            start_argv("basic", Some("cache"), None),
+            vec!["redis-up", "basic", "start", "--profile", "cache"]
+        );
+    }
+}
@@ -0,0 +1,254 @@
+//! Crash notifications for managed containers
+
+use anyhow::{Context, Result};
+use colored::*;
+use docker_wrapper::{DockerCommand, LogsCommand};
+use std::collections::HashSet;
+use std::process::Stdio;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::process::Command;
+
+use crate::cli::{WatchArgs, WebhookFormat};
+use crate::config::{Config, InstanceInfo};
+
+/// A container `kill` or `die` event reported by `docker events`
+#[derive(Debug, Clone, PartialEq)]
+struct ContainerEvent {
+    action: String,
+    id: String,
+    name: String,
+    exit_code: Option<i32>,
+    time: i64,
+}
+
+pub async fn handle_watch(args: WatchArgs, verbose: bool) -> Result<()> {
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(10))
+        .build()
+        .context("Failed to create HTTP client")?;
+
+    let mut child = Command::new("docker")
+        .args([
+            "events",
+            "--filter",
+            "type=container",
+            "--filter",
+            "event=kill",
+            "--filter",
+            "event=die",
+            "--format",
+            "{{json .}}",
+        ])
+        .stdout(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .context("Failed to run 'docker events'")?;
+    let stdout = child
+        .stdout
+        .take()
+        .context("No output from 'docker events'")?;
+    let mut lines = BufReader::new(stdout).lines();
+
+    println!(
+        "{} Watching managed containers (press Ctrl+C to exit)",
+        "Watch:".bold().cyan()
+    );
+    if let Some(ref url) = args.webhook {
+        println!("  {}: {}", "Webhook".bold(), url);
+    }
+
+    // `docker stop`, `docker kill` and `rm -f` signal the container before it dies
+    let mut signalled = HashSet::new();
+
+    loop {
+        let line = tokio::select! {
+            line = lines.next_line() => line?,
+            _ = tokio::signal::ctrl_c() => break,
+        };
+        let Some(line) = line else {
+            anyhow::bail!("'docker events' exited unexpectedly");
+        };
+
+        let Some(exit) = parse_event(&line) else {
+            continue;
+        };
+        if exit.action == "kill" {
+            signalled.insert(exit.id);
+            continue;
+        }
+        let requested = signalled.remove(&exit.id);
+
+        // Reload so instances started after the watch began are covered
+        let config = Config::load()?;
+        let Some(instance) = owning_instance(&config, &exit) else {
+            continue;
+        };
+
+        if requested || exit.exit_code == Some(0) {
+            if verbose {
+                println!(
+                    "{} {} ({}) stopped cleanly",
+                    "Info:".cyan(),
+                    exit.name,
+                    instance.name
+                );
+            }
+            continue;
+        }
+
+        println!(
+            "{} Container {} of instance {} exited with code {}",
+            "Crash:".red().bold(),
+            exit.name.bold(),
+            instance.name.bold(),
+            exit_code_label(&exit)
+        );
+
+        let logs = recent_logs(&exit.name, args.lines).await;
+        if verbose {
+            for line in &logs {
+                println!("  {}", line.dimmed());
+            }
+        }
+
+        if let Some(ref url) = args.webhook {
+            let body = payload(args.format, &exit, instance, &logs);
+            match client
+                .post(url)
+                .json(&body)
+                .send()
+                .await
+                .and_then(|r| r.error_for_status())
+            {
+                Ok(_) => println!("  {} Notification sent", "Webhook:".green()),
+                Err(e) => println!("  {} Failed to notify: {}", "Warning:".yellow(), e),
+            }
+        }
+    }
+
+    child.kill().await.ok();
+    println!();
+    println!("{} Stopped watching", "Watch:".bold().cyan());
+    Ok(())
+}
+
+/// Parse one line of `docker events --format '{{json .}}'`
+fn parse_event(line: &str) -> Option<ContainerEvent> {
+    let event: serde_json::Value = serde_json::from_str(line).ok()?;
+    let action = event.get("Action")?.as_str()?;
+    if action != "die" && action != "kill" {
+        return None;
+    }
+
+    let actor = event.get("Actor")?;
+    let attributes = actor.get("Attributes")?;
+    Some(ContainerEvent {
+        action: action.to_string(),
+        id: actor.get("ID")?.as_str()?.to_string(),
+        name: attributes.get("name")?.as_str()?.to_string(),
+        exit_code: attributes
+            .get("exitCode")
+            .and_then(|c| c.as_str())
+            .and_then(|c| c.parse().ok()),
+        time: event.get("time").and_then(|t| t.as_i64()).unwrap_or(0),
+    })
+}
+
+fn exit_code_label(exit: &ContainerEvent) -> String {
+    exit.exit_code
+        .map(|c| c.to_string())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// The managed instance a container belongs to
+///
+/// Most instances track container names; some track container IDs.
+fn owning_instance<'a>(config: &'a Config, exit: &ContainerEvent) -> Option<&'a InstanceInfo> {
+    config.instances.values().find(|instance| {
+        instance
+            .containers
+            .iter()
+            .any(|c| *c == exit.name || (!c.is_empty() && exit.id.starts_with(c.as_str())))
+    })
+}
+
+async fn recent_logs(container: &str, lines: usize) -> Vec<String> {
+    match LogsCommand::new(container)
+        .tail(lines.to_string())
+        .execute()
+        .await
+    {
+        Ok(output) => output
+            .stdout
+            .lines()
+            .chain(output.stderr.lines())
+            .map(|l| l.to_string())
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Webhook body for a crash
+fn payload(
+    format: WebhookFormat,
+    exit: &ContainerEvent,
+    instance: &InstanceInfo,
+    logs: &[String],
+) -> serde_json::Value {
+    let time = chrono::DateTime::from_timestamp(exit.time, 0)
+        .map(|t| t.to_rfc3339())
+        .unwrap_or_default();
+
+    match format {
+        WebhookFormat::Json => serde_json::json!({
+            "event": "container_exited",
+            "instance": instance.name,
+            "instance_type": instance.instance_type.to_string(),
+            "container": exit.name,
+            "exit_code": exit.exit_code,
+            "time": time,
+            "logs": logs,
+        }),
+        WebhookFormat::Slack => {
+            let mut text = format!(
+                ":rotating_light: *redis-up*: container `{}` of {} instance `{}` exited with code {}",
+                exit.name,
+                instance.instance_type,
+                instance.name,
+                exit_code_label(exit)
+            );
+            if !logs.is_empty() {
+                text.push_str(&format!("\n```\n{}\n```", logs.join("\n")));
+            }
+            serde_json::json!({ "text": text })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_event() {
+        let line = r#"{"status":"die","id":"abc123","Type":"container","Action":"die","Actor":{"ID":"abc123","Attributes":{"exitCode":"137","image":"redis:7-alpine","name":"redis-basic-1"}},"scope":"local","time":1700000000}"#;
+        assert_eq!(
+            parse_event(line).unwrap(),
+            ContainerEvent {
+                action: "die".to_string(),
+                id: "abc123".to_string(),
+                name: "redis-basic-1".to_string(),
+                exit_code: Some(137),
+                time: 1700000000,
+            }
+        );
+
+        let killed = r#"{"Type":"container","Action":"kill","Actor":{"ID":"abc123","Attributes":{"name":"redis-basic-1","signal":"15"}},"time":1700000000}"#;
+        let event = parse_event(killed).unwrap();
+        assert_eq!(event.action, "kill");
+        assert_eq!(event.exit_code, None);
+
+        let started = line.replace("\"Action\":\"die\"", "\"Action\":\"start\"");
+        assert!(parse_event(&started).is_none());
+    }
+}
@@ -1,18 +1,24 @@
 //! YAML configuration support for declarative Redis deployments
 
 use anyhow::{Context, Result};
+use clap::{CommandFactory, Parser};
 use colored::*;
 use serde::{Deserialize, Serialize};
-use std::path::Path;
-use tokio::fs;
+use std::path::{Path, PathBuf};
 
 use crate::cli::{
-    BasicStartArgs, ClusterStartArgs, EnterpriseStartArgs, SentinelStartArgs, StackStartArgs,
+    BasicStartArgs, Cli, ClusterAction, ClusterStartArgs, Commands, EnterpriseAction,
+    EnterpriseStartArgs, RedisAction, SentinelAction, SentinelStartArgs, StackAction,
+    StackStartArgs,
 };
+use crate::commands::includes;
+use crate::commands::post_start::{self, Step};
+use crate::commands::profile;
+use crate::error::Failure;
 
 /// YAML configuration for Redis deployments
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "kebab-case")]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
 pub struct YamlConfig {
     /// API version for compatibility
     #[serde(default = "default_api_version")]
@@ -27,203 +33,409 @@ fn default_api_version() -> String {
 }
 
 /// A single deployment configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct Deployment {
     /// Name of the deployment
     pub name: String,
 
-    /// Type of Redis deployment
-    #[serde(rename = "type")]
-    pub deployment_type: DeploymentType,
+    /// Free-form description shown in list and info
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// Redis commands and Lua scripts to run once the deployment is up
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub post_start: Vec<Step>,
 
-    /// Configuration specific to the deployment type
+    /// Type of Redis deployment and its settings
     #[serde(flatten)]
     pub config: DeploymentConfig,
 }
 
-/// Types of Redis deployments
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "kebab-case")]
-pub enum DeploymentType {
-    Basic,
-    Stack,
-    Cluster,
-    Sentinel,
-    Enterprise,
-}
-
-/// Configuration for different deployment types
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(untagged)]
+/// Configuration for different deployment types, selected by the `type` key
+///
+/// Keys are the long flags of `redis-up <type> start`; a key left out gets the
+/// flag's default, so a file means what the same command line would.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
 pub enum DeploymentConfig {
-    Basic {
-        #[serde(default = "default_port")]
-        port: u16,
-        #[serde(default)]
-        password: Option<String>,
-        #[serde(default)]
-        persist: bool,
-        #[serde(default)]
-        memory: Option<String>,
-        #[serde(default)]
-        with_insight: bool,
-        #[serde(default = "default_insight_port")]
-        insight_port: u16,
-        #[serde(default)]
-        shell: bool,
-    },
-    Stack {
-        #[serde(default = "default_port")]
-        port: u16,
-        #[serde(default)]
-        password: Option<String>,
-        #[serde(default)]
-        persist: bool,
-        #[serde(default)]
-        memory: Option<String>,
-        #[serde(default)]
-        with_insight: bool,
-        #[serde(default = "default_insight_port")]
-        insight_port: u16,
-        #[serde(default)]
-        shell: bool,
-    },
-    Cluster {
-        #[serde(default = "default_masters")]
-        masters: u8,
-        #[serde(default = "default_replicas")]
-        replicas: u8,
-        #[serde(default = "default_cluster_port")]
-        port_base: u16,
-        #[serde(default)]
-        password: Option<String>,
-        #[serde(default)]
-        persist: bool,
-        #[serde(default)]
-        memory: Option<String>,
-        #[serde(default)]
-        stack: bool,
-        #[serde(default)]
-        with_insight: bool,
-        #[serde(default = "default_insight_port")]
-        insight_port: u16,
-        #[serde(default)]
-        shell: bool,
-    },
-    Sentinel {
-        #[serde(default = "default_sentinels")]
-        sentinels: u8,
-        #[serde(default = "default_port")]
-        redis_port_base: u16,
-        #[serde(default = "default_sentinel_port")]
-        sentinel_port_base: u16,
-        #[serde(default)]
-        password: Option<String>,
-        #[serde(default)]
-        persist: bool,
-        #[serde(default)]
-        memory: Option<String>,
-        #[serde(default)]
-        with_insight: bool,
-        #[serde(default = "default_insight_port")]
-        insight_port: u16,
-    },
-    Enterprise {
-        #[serde(default = "default_nodes")]
-        nodes: u8,
-        #[serde(default = "default_admin_port")]
-        port_base: u16,
-        #[serde(default)]
-        create_db: Option<String>,
-        #[serde(default = "default_db_port")]
-        db_port: u16,
-        #[serde(default)]
-        memory: Option<String>,
-        #[serde(default)]
-        persist: bool,
-        #[serde(default)]
-        with_insight: bool,
-        #[serde(default = "default_insight_port")]
-        insight_port: u16,
-    },
+    Basic(BasicConfig),
+    Stack(StackConfig),
+    Cluster(ClusterConfig),
+    Sentinel(SentinelConfig),
+    Enterprise(EnterpriseConfig),
 }
 
-// Default values for various fields
-fn default_port() -> u16 {
-    6379
-}
-
-fn default_cluster_port() -> u16 {
-    7000
-}
-
-fn default_sentinel_port() -> u16 {
-    26379
-}
-
-fn default_admin_port() -> u16 {
-    8443
-}
-
-fn default_insight_port() -> u16 {
-    8001
+/// Values of the `type` key, which are also the `redis-up` subcommands
+const DEPLOYMENT_TYPES: &[&str] = &["basic", "stack", "cluster", "sentinel", "enterprise"];
+
+impl DeploymentConfig {
+    /// The `type` of this deployment
+    pub fn kind(&self) -> &'static str {
+        match self {
+            DeploymentConfig::Basic(_) => "basic",
+            DeploymentConfig::Stack(_) => "stack",
+            DeploymentConfig::Cluster(_) => "cluster",
+            DeploymentConfig::Sentinel(_) => "sentinel",
+            DeploymentConfig::Enterprise(_) => "enterprise",
+        }
+    }
 }
 
-fn default_masters() -> u8 {
-    3
+/// `basic start` flags
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct BasicConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub redis_version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub count: Option<u16>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub shared_password: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub no_auth: bool,
+    pub persist: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub persistence: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub save: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub appendfsync: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub databases: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub load_rdb: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data_volume: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub networks: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bind: Option<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub allow_exposed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network_mode: Option<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub ipv6: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpus: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu_shares: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blkio_limit: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub restart_policy: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub rename_commands: Vec<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub hardened: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pull: Option<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub offline: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub platform: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retries: Option<u32>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub reuse: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub recreate_on_drift: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub warn_on_drift: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub shell: bool,
+    pub with_insight: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub insight_port: Option<u16>,
 }
 
-fn default_replicas() -> u8 {
-    1
+/// `stack start` flags; `demo-bundle` defaults to true here
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct StackConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub no_auth: bool,
+    pub persist: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub persistence: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub save: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub appendfsync: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub databases: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub load_rdb: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data_volume: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub networks: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bind: Option<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub allow_exposed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network_mode: Option<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub ipv6: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpus: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu_shares: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blkio_limit: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub restart_policy: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub rename_commands: Vec<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub hardened: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pull: Option<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub offline: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub platform: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retries: Option<u32>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub reuse: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub recreate_on_drift: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub warn_on_drift: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub flavor: Option<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub with_json: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub with_search: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub with_timeseries: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub with_graph: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub with_bloom: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub demo_bundle: Option<bool>,
+    pub with_insight: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub insight_port: Option<u16>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub shell: bool,
 }
 
-fn default_sentinels() -> u8 {
-    3
+/// `cluster start` flags
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct ClusterConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub masters: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replicas: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub port_base: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    pub persist: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub persistence: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub save: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub appendfsync: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory: Option<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub force: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpus: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu_shares: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blkio_limit: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub restart_policy: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pull: Option<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub offline: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub platform: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retries: Option<u32>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub reuse: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub recreate_on_drift: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub warn_on_drift: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub stack: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub emulate: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub announce_ip: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub announce_hostname: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network_mode: Option<String>,
+    pub with_insight: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub insight_port: Option<u16>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub shell: bool,
 }
 
-fn default_nodes() -> u8 {
-    3
+/// `sentinel start` flags
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct SentinelConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub masters: Option<usize>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub master_names: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sentinels: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quorum: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub down_after_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failover_timeout: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parallel_syncs: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub redis_port_base: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sentinel_port_base: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    pub persist: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpus: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu_shares: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blkio_limit: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub restart_policy: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pull: Option<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub offline: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub platform: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retries: Option<u32>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub reuse: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub recreate_on_drift: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub warn_on_drift: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub ipv6: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub with_readonly_port: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub with_lb: bool,
+    pub with_insight: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub insight_port: Option<u16>,
 }
 
-fn default_db_port() -> u16 {
-    12000
+/// `enterprise start` flags; `create-db` defaults to `mydb` here
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct EnterpriseConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nodes: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub port_base: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub create_db: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub db_port: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory: Option<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub force: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpus: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu_shares: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blkio_limit: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub restart_policy: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pull: Option<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub offline: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub platform: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retries: Option<u32>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub reuse: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub recreate_on_drift: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub warn_on_drift: bool,
+    pub persist: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub containers_only: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub active_active: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub participants: Option<u16>,
+    pub with_insight: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub insight_port: Option<u16>,
 }
 
 /// Deploy Redis instances from a YAML configuration file
-pub async fn deploy_from_yaml(path: &Path, verbose: bool) -> Result<()> {
-    // Read the YAML file
-    let content = fs::read_to_string(path)
-        .await
-        .with_context(|| format!("Failed to read YAML file: {}", path.display()))?;
-
-    // Parse the YAML
-    let config: YamlConfig = serde_yaml::from_str(&content)
-        .with_context(|| format!("Failed to parse YAML file: {}", path.display()))?;
-
-    // Validate API version
-    if config.api_version != "v1" {
-        anyhow::bail!(
-            "Unsupported API version: {}. Expected: v1",
-            config.api_version
-        );
+pub async fn deploy_from_yaml(path: &Path, profile: Option<&str>, verbose: bool) -> Result<()> {
+    let config = load_yaml(path, profile).await?;
+    // Catch bad names before anything starts, not halfway through the file
+    for deployment in &config.deployments {
+        crate::commands::names::validate(&deployment.name)?;
     }
 
     println!(
-        "{} Deploying {} instance(s) from {}",
+        "{} Deploying {} instance(s) from {}{}",
         "Deploying:".bold().cyan(),
         config.deployments.len(),
-        path.display()
+        path.display(),
+        profile.map_or(String::new(), |profile| format!(" (profile {})", profile))
     );
 
     // Deploy each instance
+    let total = config.deployments.len();
+    let mut failed = Vec::new();
     for deployment in config.deployments {
         if verbose {
             println!(
                 "  {} {} ({})",
                 "Starting:".yellow(),
                 deployment.name.bold(),
-                format!("{:?}", deployment.deployment_type).dimmed()
+                deployment.config.kind().dimmed()
             );
         }
 
@@ -237,297 +449,572 @@ pub async fn deploy_from_yaml(path: &Path, verbose: bool) -> Result<()> {
             }
             Err(e) => {
                 println!(
-                    "  {} Failed to deploy {}: {}",
+                    "  {} Failed to deploy {}: {:#}",
                     "✗".red(),
                     deployment.name.bold(),
                     e
                 );
                 // Continue with other deployments even if one fails
+                failed.push(deployment.name);
             }
         }
     }
 
     println!();
+    if !failed.is_empty() {
+        return Err(Failure::Partial(format!(
+            "{} of {} deployment(s) failed: {}",
+            failed.len(),
+            total,
+            failed.join(", ")
+        ))
+        .into());
+    }
     println!("{} All deployments complete", "Done:".bold().green());
 
     Ok(())
 }
 
+/// Read and validate a deployment file, applying a profile when given
+pub async fn load_yaml(path: &Path, profile: Option<&str>) -> Result<YamlConfig> {
+    // Includes and extends are merged in before anything is checked
+    let document = includes::load(path)?;
+
+    let (config, legacy) = parse_yaml(document, profile)
+        .with_context(|| format!("Failed to parse YAML file: {}", path.display()))?;
+    if !legacy.is_empty() {
+        println!(
+            "{} {} uses snake_case keys ({}); they still work, but kebab-case keys like the start flags are the documented form",
+            "Note:".yellow(),
+            path.display(),
+            legacy.join(", ")
+        );
+    }
+
+    Ok(config)
+}
+
+/// Check a resolved deployment file, returning the snake_case keys it used
+///
+/// Each deployment is checked against its type's start flags first, so a
+/// mistake names the deployment and the key instead of failing somewhere
+/// inside serde. Every profile is checked too, not only the one applied, so a
+/// typo in the `ci` profile shows up on a laptop.
+pub fn parse_yaml(
+    mut document: serde_yaml::Value,
+    profile: Option<&str>,
+) -> Result<(YamlConfig, Vec<String>)> {
+    let mut legacy = Vec::new();
+    check_top_level(&mut document, &mut legacy)?;
+    let mut defined = Vec::new();
+    if let Some(deployments) = document
+        .get_mut("deployments")
+        .and_then(serde_yaml::Value::as_sequence_mut)
+    {
+        for (i, deployment) in deployments.iter_mut().enumerate() {
+            let profiles = take_profiles(i, deployment)?;
+            for (name, overrides) in &profiles {
+                let mut applied = includes::overlay(deployment, overrides);
+                check_deployment(i, &mut applied, &mut legacy)
+                    .with_context(|| format!("In profile '{}'", name))?;
+                if !defined.contains(name) {
+                    defined.push(name.clone());
+                }
+            }
+            if let Some((_, overrides)) = profiles
+                .iter()
+                .find(|(name, _)| Some(name.as_str()) == profile)
+            {
+                *deployment = includes::overlay(deployment, overrides);
+            }
+            check_deployment(i, deployment, &mut legacy)?;
+        }
+    }
+    if let Some(profile) = profile {
+        if !defined.iter().any(|name| name == profile) {
+            if defined.is_empty() {
+                anyhow::bail!(
+                    "Profile '{}' not found: the file defines no profiles",
+                    profile
+                );
+            }
+            anyhow::bail!(
+                "Profile '{}' not found (the file defines {})",
+                profile,
+                defined.join(", ")
+            );
+        }
+    }
+
+    let config: YamlConfig = serde_yaml::from_value(document)?;
+
+    // Validate API version
+    if config.api_version != "v1" {
+        anyhow::bail!(
+            "Unsupported API version: {}. Expected: v1",
+            config.api_version
+        );
+    }
+    for deployment in &config.deployments {
+        post_start::check(&deployment.post_start)
+            .with_context(|| format!("Deployment '{}': invalid post-start", deployment.name))?;
+    }
+
+    Ok((config, legacy))
+}
+
+/// Remove a deployment's `profiles`, returning each profile's overrides
+fn take_profiles(
+    index: usize,
+    deployment: &mut serde_yaml::Value,
+) -> Result<Vec<(String, serde_yaml::Mapping)>> {
+    let label = match deployment.get("name").and_then(serde_yaml::Value::as_str) {
+        Some(name) => format!("'{}'", name),
+        None => format!("#{}", index + 1),
+    };
+    let Some(profiles) = deployment
+        .as_mapping_mut()
+        .and_then(|fields| fields.remove("profiles"))
+    else {
+        return Ok(Vec::new());
+    };
+    let profiles: Vec<(String, serde_yaml::Mapping)> = profiles
+        .as_mapping()
+        .and_then(|profiles| {
+            profiles
+                .iter()
+                .map(|(name, overrides)| {
+                    Some((name.as_str()?.to_string(), overrides.as_mapping()?.clone()))
+                })
+                .collect()
+        })
+        .with_context(|| {
+            format!(
+                "Deployment {}: `profiles` must map profile names to the keys they override",
+                label
+            )
+        })?;
+    for (name, overrides) in &profiles {
+        for key in overrides.keys().filter_map(serde_yaml::Value::as_str) {
+            if matches!(key, "name" | "type" | "profiles" | "extends") {
+                anyhow::bail!(
+                    "Deployment {}: profile '{}' can't set '{}'",
+                    label,
+                    name,
+                    key
+                );
+            }
+        }
+    }
+    Ok(profiles)
+}
+
+/// Check one deployment's type and keys, renaming snake_case keys from older files
+/// Keys a deployment file may have besides `include`, which is resolved first
+const TOP_LEVEL_KEYS: &[&str] = &["api-version", "deployments"];
+
+/// Check the file's own keys, so a misspelt `deployments` isn't read as none
+fn check_top_level(document: &mut serde_yaml::Value, legacy: &mut Vec<String>) -> Result<()> {
+    let Some(fields) = document.as_mapping_mut() else {
+        anyhow::bail!("Expected a mapping with `deployments`");
+    };
+    if fields.contains_key("api_version") && !fields.contains_key("api-version") {
+        let value = fields.remove("api_version").unwrap_or_default();
+        fields.insert("api-version".into(), value);
+        legacy.push("api_version".to_string());
+    }
+    for key in fields.keys() {
+        let key = key.as_str().unwrap_or_default();
+        if !TOP_LEVEL_KEYS.contains(&key) {
+            anyhow::bail!(
+                "Unknown top-level key '{}' (expected {}, include)",
+                key,
+                TOP_LEVEL_KEYS.join(", ")
+            );
+        }
+    }
+    Ok(())
+}
+
+fn check_deployment(
+    index: usize,
+    deployment: &mut serde_yaml::Value,
+    legacy: &mut Vec<String>,
+) -> Result<()> {
+    let label = match deployment.get("name").and_then(serde_yaml::Value::as_str) {
+        Some(name) => format!("'{}'", name),
+        None => format!("#{}", index + 1),
+    };
+    let Some(fields) = deployment.as_mapping_mut() else {
+        anyhow::bail!("Deployment {} must be a mapping of keys", label);
+    };
+
+    // Files written before keys followed the flags used field names
+    let renames: Vec<(String, String)> = fields
+        .keys()
+        .filter_map(serde_yaml::Value::as_str)
+        .filter(|key| key.contains('_'))
+        .map(|key| (key.to_string(), key.replace('_', "-")))
+        .filter(|(_, kebab)| !fields.contains_key(kebab.as_str()))
+        .collect();
+    for (key, kebab) in renames {
+        let value = fields.remove(key.as_str()).unwrap_or_default();
+        fields.insert(kebab.into(), value);
+        if !legacy.contains(&key) {
+            legacy.push(key);
+        }
+    }
+
+    let kind = match fields.get("type") {
+        Some(serde_yaml::Value::String(kind)) if DEPLOYMENT_TYPES.contains(&kind.as_str()) => {
+            kind.clone()
+        }
+        Some(serde_yaml::Value::String(kind)) => anyhow::bail!(
+            "Deployment {}: unknown type '{}' (expected {})",
+            label,
+            kind,
+            DEPLOYMENT_TYPES.join(", ")
+        ),
+        _ => anyhow::bail!(
+            "Deployment {}: missing type (one of {})",
+            label,
+            DEPLOYMENT_TYPES.join(", ")
+        ),
+    };
+
+    let cli = Cli::command();
+    let keys = start_keys(start_command(&cli, &kind));
+    for key in fields.keys() {
+        let key = key.as_str().unwrap_or_default();
+        if !matches!(key, "name" | "description" | "type" | "post-start")
+            && !keys.iter().any(|k| k == key)
+        {
+            anyhow::bail!(
+                "Deployment {} ({}): unknown key '{}'; keys are the long flags of `redis-up {} start`",
+                label,
+                kind,
+                key,
+                kind
+            );
+        }
+    }
+
+    // Deserialize key by key so a bad value names its key
+    if let Err(e) = serde_yaml::from_value::<Deployment>(deployment.clone()) {
+        let fields = deployment.as_mapping().cloned().unwrap_or_default();
+        for (key, value) in &fields {
+            let mut single = serde_yaml::Mapping::new();
+            single.insert("name".into(), "check".into());
+            single.insert("type".into(), kind.as_str().into());
+            single.insert(key.clone(), value.clone());
+            if let Err(e) = serde_yaml::from_value::<Deployment>(single.into()) {
+                anyhow::bail!(
+                    "Deployment {} ({}): invalid '{}': {}",
+                    label,
+                    kind,
+                    key.as_str().unwrap_or_default(),
+                    e
+                );
+            }
+        }
+        anyhow::bail!("Deployment {} ({}): {}", label, kind, e);
+    }
+
+    Ok(())
+}
+
+/// The `start` subcommand for a deployment type
+pub fn start_command<'a>(cli: &'a clap::Command, kind: &str) -> &'a clap::Command {
+    cli.find_subcommand(kind)
+        .and_then(|command| command.find_subcommand("start"))
+        .expect("every deployment type has a start command")
+}
+
+/// YAML keys a start command takes: its arguments, kebab-cased
+pub fn start_keys(start: &clap::Command) -> Vec<String> {
+    start
+        .get_arguments()
+        // Hidden flags only exist to refuse with a reason, so they get no key
+        .filter(|arg| !arg.is_hide_set())
+        .map(|arg| arg.get_id().as_str().replace('_', "-"))
+        .filter(|key| !matches!(key.as_str(), "name" | "description" | "output"))
+        .collect()
+}
+
 /// Deploy a single instance from configuration
+#[tracing::instrument(name = "deploy", skip_all, fields(name = %deployment.name))]
 async fn deploy_single(deployment: &Deployment, verbose: bool) -> Result<()> {
-    match (&deployment.deployment_type, &deployment.config) {
-        (
-            DeploymentType::Basic,
-            DeploymentConfig::Basic {
-                port,
-                password,
-                persist,
-                memory,
-                with_insight,
-                insight_port,
-                shell,
-            },
-        ) => {
-            let args = BasicStartArgs {
-                name: Some(deployment.name.clone()),
-                port: *port,
-                password: password.clone(),
-                persist: *persist,
-                memory: memory.clone(),
-                with_insight: *with_insight,
-                insight_port: *insight_port,
-                shell: *shell,
-            };
+    start_single(deployment, verbose).await?;
+
+    if !deployment.post_start.is_empty() {
+        let config = crate::config::Config::load()?;
+        let instance = config
+            .get_instance(&deployment.name)
+            .with_context(|| format!("Instance '{}' not found", deployment.name))?;
+        post_start::run(instance, &deployment.post_start, verbose).await?;
+    }
+    Ok(())
+}
+
+async fn start_single(deployment: &Deployment, verbose: bool) -> Result<()> {
+    match start_request(deployment)? {
+        StartRequest::Basic(args) => {
             crate::commands::basic::handle_action(crate::cli::RedisAction::Start(args), verbose)
                 .await
         }
-        (
-            DeploymentType::Stack,
-            DeploymentConfig::Stack {
-                port,
-                password,
-                persist,
-                memory,
-                with_insight,
-                insight_port,
-                shell,
-            },
-        ) => {
-            let args = StackStartArgs {
-                name: Some(deployment.name.clone()),
-                port: *port,
-                password: password.clone(),
-                persist: *persist,
-                memory: memory.clone(),
-                with_json: false,
-                with_search: false,
-                with_timeseries: false,
-                with_graph: false,
-                with_bloom: false,
-                demo_bundle: true, // Enable common modules by default for Stack
-                with_insight: *with_insight,
-                insight_port: *insight_port,
-                shell: *shell,
-            };
+        StartRequest::Stack(args) => {
             crate::commands::stack::handle_action(crate::cli::StackAction::Start(args), verbose)
                 .await
         }
-        (
-            DeploymentType::Cluster,
-            DeploymentConfig::Cluster {
-                masters,
-                replicas,
-                port_base,
-                password,
-                persist,
-                memory,
-                stack,
-                with_insight,
-                insight_port,
-                shell,
-            },
-        ) => {
-            let args = ClusterStartArgs {
-                name: Some(deployment.name.clone()),
-                masters: *masters as usize,
-                replicas: *replicas as usize,
-                port_base: *port_base,
-                password: password.clone(),
-                persist: *persist,
-                memory: memory.clone(),
-                stack: *stack,
-                with_insight: *with_insight,
-                insight_port: *insight_port,
-                shell: *shell,
-            };
+        StartRequest::Cluster(args) => {
             crate::commands::cluster::handle_action(crate::cli::ClusterAction::Start(args), verbose)
                 .await
         }
-        (
-            DeploymentType::Sentinel,
-            DeploymentConfig::Sentinel {
-                sentinels,
-                redis_port_base,
-                sentinel_port_base,
-                password,
-                persist,
-                memory,
-                with_insight,
-                insight_port,
-            },
-        ) => {
-            let args = SentinelStartArgs {
-                name: Some(deployment.name.clone()),
-                masters: 1, // Sentinel typically monitors 1 master with replicas
-                sentinels: *sentinels as usize,
-                redis_port_base: *redis_port_base,
-                sentinel_port_base: *sentinel_port_base,
-                password: password.clone(),
-                persist: *persist,
-                memory: memory.clone(),
-                with_insight: *with_insight,
-                insight_port: *insight_port,
-            };
+        StartRequest::Sentinel(args) => {
             crate::commands::sentinel::handle_action(
                 crate::cli::SentinelAction::Start(args),
                 verbose,
             )
             .await
         }
-        (
-            DeploymentType::Enterprise,
-            DeploymentConfig::Enterprise {
-                nodes,
-                port_base,
-                create_db,
-                db_port,
-                memory,
-                persist,
-                with_insight,
-                insight_port,
-            },
-        ) => {
-            let args = EnterpriseStartArgs {
-                name: Some(deployment.name.clone()),
-                nodes: *nodes as usize,
-                port_base: *port_base,
-                create_db: create_db.clone().or_else(|| Some("mydb".to_string())),
-                db_port: *db_port,
-                memory: memory.clone(),
-                persist: *persist,
-                containers_only: false,
-                with_insight: *with_insight,
-                insight_port: *insight_port,
-            };
+        StartRequest::Enterprise(args) => {
             crate::commands::enterprise::handle_action(
                 crate::cli::EnterpriseAction::Start(args),
                 verbose,
             )
             .await
         }
-        _ => {
-            anyhow::bail!("Configuration mismatch for deployment: {}", deployment.name)
-        }
     }
 }
 
-/// Generate example YAML configuration files
-pub async fn generate_examples(dir: &Path) -> Result<()> {
-    // Create directory if it doesn't exist
-    fs::create_dir_all(dir)
-        .await
-        .with_context(|| format!("Failed to create directory: {}", dir.display()))?;
+/// Start arguments for one deployment, as if given on the command line
+pub enum StartRequest {
+    Basic(BasicStartArgs),
+    Stack(StackStartArgs),
+    Cluster(ClusterStartArgs),
+    Sentinel(SentinelStartArgs),
+    Enterprise(EnterpriseStartArgs),
+}
 
-    // Basic example
-    let basic_example = r#"api-version: v1
-deployments:
-  - name: my-redis
-    type: basic
-    port: 6379
-    persist: true
-    memory: "512m"
-    with-insight: true
-"#;
+/// Translate a deployment into start arguments, as the same command line would
+pub fn start_request(deployment: &Deployment) -> Result<StartRequest> {
+    let argv = command_line(deployment)?;
+    let cli = Cli::try_parse_from(&argv).map_err(|e| {
+        let message = e.to_string();
+        let reason = message.lines().next().unwrap_or_default();
+        anyhow::anyhow!(
+            "Invalid deployment '{}': {}",
+            deployment.name,
+            reason.trim_start_matches("error: ")
+        )
+    })?;
+
+    Ok(match cli.command {
+        Some(Commands::Basic {
+            action: RedisAction::Start(args),
+        }) => StartRequest::Basic(args),
+        Some(Commands::Stack {
+            action: StackAction::Start(args),
+        }) => StartRequest::Stack(args),
+        Some(Commands::Cluster {
+            action: ClusterAction::Start(args),
+        }) => StartRequest::Cluster(args),
+        Some(Commands::Sentinel {
+            action: SentinelAction::Start(args),
+        }) => StartRequest::Sentinel(args),
+        Some(Commands::Enterprise {
+            action: EnterpriseAction::Start(args),
+        }) => StartRequest::Enterprise(args),
+        _ => unreachable!("command_line always builds a start command"),
+    })
+}
 
-    // Stack example
-    let stack_example = r#"api-version: v1
-deployments:
-  - name: my-stack
-    type: stack
-    port: 6380
-    persist: true
-    memory: "1g"
-    with-insight: true
-    insight-port: 8002
-"#;
+/// The `redis-up <type> start` command line a deployment stands for
+pub fn command_line(deployment: &Deployment) -> Result<Vec<String>> {
+    let kind = deployment.config.kind();
+    let fields = match &deployment.config {
+        DeploymentConfig::Basic(config) => serde_yaml::to_value(config)?,
+        DeploymentConfig::Stack(config) => {
+            // Deployments have always enabled the common modules
+            let mut config = config.clone();
+            config.demo_bundle.get_or_insert(true);
+            serde_yaml::to_value(config)?
+        }
+        DeploymentConfig::Cluster(config) => serde_yaml::to_value(config)?,
+        DeploymentConfig::Sentinel(config) => serde_yaml::to_value(config)?,
+        DeploymentConfig::Enterprise(config) => {
+            let mut config = config.clone();
+            config.create_db.get_or_insert_with(|| "mydb".to_string());
+            serde_yaml::to_value(config)?
+        }
+    };
 
-    // Cluster example
-    let cluster_example = r#"api-version: v1
-deployments:
-  - name: my-cluster
-    type: cluster
-    masters: 3
-    replicas: 1
-    port-base: 7000
-    persist: true
-    memory: "256m"
-    stack: false
-    with-insight: true
-"#;
+    let cli = Cli::command();
+    let start = start_command(&cli, kind);
 
-    // Sentinel example
-    let sentinel_example = r#"api-version: v1
-deployments:
-  - name: my-sentinel
-    type: sentinel
-    sentinels: 3
-    redis-port-base: 6379
-    sentinel-port-base: 26379
-    persist: true
-    memory: "512m"
-"#;
+    let mut argv: Vec<String> = ["redis-up", kind, "start", "--name", &deployment.name]
+        .map(String::from)
+        .to_vec();
+    if let Some(ref description) = deployment.description {
+        argv.extend(["--description".to_string(), description.clone()]);
+    }
+    for (key, value) in fields.as_mapping().into_iter().flatten() {
+        let key = key.as_str().unwrap_or_default();
+        // Keys are named after the argument, so plural lists like `networks` find `--network`
+        let id = key.replace('-', "_");
+        let arg = start
+            .get_arguments()
+            .find(|arg| arg.get_id() == id.as_str())
+            .with_context(|| format!("'{}' is not a `redis-up {} start` flag", key, kind))?;
+        let flag = arg.get_long().unwrap_or(key);
+        argv.extend(profile::tokens(
+            flag,
+            value,
+            arg.get_action().takes_values(),
+        )?);
+    }
+    Ok(argv)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::examples;
+
+    #[test]
+    fn test_round_trip_and_flag_parity() {
+        for deployment in examples::every_field() {
+            let yaml = serde_yaml::to_string(&deployment).unwrap();
+            let parsed: Deployment = serde_yaml::from_str(&yaml).unwrap();
+            assert_eq!(parsed, deployment, "round trip of {}", yaml);
+
+            // Every start flag but the ones only a terminal needs has a key
+            let kind = deployment.config.kind();
+            let line = command_line(&deployment).unwrap();
+            let cli = Cli::command();
+            for arg in start_command(&cli, kind).get_arguments() {
+                if arg.get_id() == "output" || arg.is_hide_set() {
+                    continue;
+                }
+                let flag = format!("--{}", arg.get_long().unwrap());
+                assert!(line.contains(&flag), "{} start: no key for {}", kind, flag);
+            }
+        }
+    }
 
-    // Enterprise example
-    let enterprise_example = r#"api-version: v1
+    #[test]
+    fn test_start_request_uses_cli_defaults() {
+        let config: YamlConfig = serde_yaml::from_str(
+            r#"
 deployments:
-  - name: my-enterprise
-    type: enterprise
-    nodes: 3
-    port-base: 8443
-    create-db: "mydb"
-    db-port: 12000
-    memory: "4g"
-    persist: false
-    with-insight: true
-"#;
-
-    // Multi-deployment example
-    let multi_example = r#"api-version: v1
+  - name: ha
+    type: sentinel
+    masters: 2
+    sentinels: 5
+    down-after-ms: 2000
+    with-lb: true
+"#,
+        )
+        .unwrap();
+        let Ok(StartRequest::Sentinel(args)) = start_request(&config.deployments[0]) else {
+            panic!("expected a sentinel start");
+        };
+        assert_eq!(args.masters, 2);
+        assert_eq!(args.sentinels, 5);
+        assert_eq!(args.down_after_ms, 2000);
+        assert!(args.with_lb);
+        assert_eq!(args.failover_timeout, 10000);
+        assert_eq!(args.redis_port_base, 6379);
+
+        let config: YamlConfig = serde_yaml::from_str(
+            r#"
 deployments:
-  - name: cache-redis
+  - name: cache
     type: basic
-    port: 6379
-    memory: "256m"
-    
-  - name: analytics-stack
-    type: stack
-    port: 6380
-    persist: true
-    memory: "1g"
-    with-insight: true
-    
-  - name: session-cluster
-    type: cluster
-    masters: 3
-    replicas: 1
-    port-base: 7000
-    memory: "512m"
-"#;
-
-    // Write example files
-    let examples = vec![
-        ("basic.yaml", basic_example),
-        ("stack.yaml", stack_example),
-        ("cluster.yaml", cluster_example),
-        ("sentinel.yaml", sentinel_example),
-        ("enterprise.yaml", enterprise_example),
-        ("multi-deployment.yaml", multi_example),
-    ];
-
-    for (filename, content) in examples {
-        let path = dir.join(filename);
-        fs::write(&path, content)
-            .await
-            .with_context(|| format!("Failed to write example: {}", path.display()))?;
-        println!("  {} Created: {}", "✓".green(), path.display());
+    count: 2
+    shell: true
+"#,
+        )
+        .unwrap();
+        let err = start_request(&config.deployments[0]).err().unwrap();
+        assert!(err.to_string().starts_with("Invalid deployment 'cache'"));
     }
 
-    println!();
-    println!(
-        "{} Example YAML files created in {}",
-        "Success:".bold().green(),
-        dir.display()
-    );
+    #[test]
+    fn test_parse_yaml_errors_and_legacy_keys() {
+        let parse = |deployment: &str| {
+            parse_yaml(
+                serde_yaml::from_str(&format!("deployments:\n  - {}", deployment)).unwrap(),
+                None,
+            )
+        };
+
+        // Only defaults: still the type the file names
+        let (config, _) = parse("name: orders\n    type: cluster").unwrap();
+        assert!(matches!(
+            config.deployments[0].config,
+            DeploymentConfig::Cluster(_)
+        ));
+
+        let message = |deployment: &str| parse(deployment).unwrap_err().to_string();
+        assert_eq!(
+            message("name: orders\n    type: cluster\n    maters: 3"),
+            "Deployment 'orders' (cluster): unknown key 'maters'; keys are the long flags of `redis-up cluster start`"
+        );
+        assert!(
+            message("name: orders\n    type: cluster\n    masters: three")
+                .starts_with("Deployment 'orders' (cluster): invalid 'masters':")
+        );
+        assert!(message("name: orders\n    masters: 3")
+            .starts_with("Deployment 'orders': missing type"));
+        assert!(message("type: clustr").starts_with("Deployment #1: unknown type 'clustr'"));
+        for file in ["deploymnts: []", "includes: [team.yaml]\ndeployments: []"] {
+            let err = parse_yaml(serde_yaml::from_str(file).unwrap(), None)
+                .unwrap_err()
+                .to_string();
+            assert!(err.starts_with("Unknown top-level key '"), "{}", err);
+        }
 
-    Ok(())
+        let (config, legacy) =
+            parse("name: cache\n    type: basic\n    with_insight: true\n    insight_port: 8002")
+                .unwrap();
+        assert_eq!(legacy, ["with_insight", "insight_port"]);
+        let DeploymentConfig::Basic(ref basic) = config.deployments[0].config else {
+            panic!("expected a basic deployment");
+        };
+        assert!(basic.with_insight);
+        assert_eq!(basic.insight_port, Some(8002));
+    }
+
+    #[test]
+    fn test_profiles() {
+        let file = "deployments:
+  - name: orders
+    type: cluster
+    masters: 6
+    persist: true
+    profiles:
+      ci: {masters: 3, persist: false}
+      demo: {with-insight: true}
+";
+        let parse = |file: &str, profile| parse_yaml(serde_yaml::from_str(file).unwrap(), profile);
+
+        let cluster = |config: YamlConfig| match config.deployments[0].config {
+            DeploymentConfig::Cluster(ref cluster) => cluster.clone(),
+            _ => panic!("expected a cluster deployment"),
+        };
+        let laptop = cluster(parse(file, None).unwrap().0);
+        assert_eq!((laptop.masters, laptop.persist), (Some(6), true));
+        let ci = cluster(parse(file, Some("ci")).unwrap().0);
+        assert_eq!((ci.masters, ci.persist), (Some(3), false));
+
+        assert_eq!(
+            parse(file, Some("prod")).unwrap_err().to_string(),
+            "Profile 'prod' not found (the file defines ci, demo)"
+        );
+        let typo = file.replace("masters: 3,", "maters: 3,");
+        assert!(format!("{:#}", parse(&typo, None).unwrap_err())
+            .starts_with("In profile 'ci': Deployment 'orders' (cluster): unknown key 'maters'"));
+        assert!(parse(&file.replace("demo: {", "demo: {type: basic, "), None).is_err());
+    }
 }
@@ -2,9 +2,10 @@
 
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
-use std::path::PathBuf;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
 
 /// Configuration directory name
 const CONFIG_DIR: &str = "redis-up";
@@ -12,6 +13,25 @@ const CONFIG_DIR: &str = "redis-up";
 /// Configuration file name
 const CONFIG_FILE: &str = "instances.json";
 
+/// Directory holding one state directory per namespace
+const NAMESPACES_DIR: &str = "namespaces";
+
+/// Per-namespace files that lived directly in the config directory before namespaces
+const LEGACY_STATE_FILES: [&str; 3] = [CONFIG_FILE, "history.jsonl", "last-cleanup.json"];
+
+/// Schema version written to `instances.json`
+pub const CONFIG_VERSION: u32 = 1;
+
+/// A migration upgrades a config from its index to the next version
+type Migration = fn(&mut serde_json::Value) -> Result<()>;
+
+const MIGRATIONS: [Migration; CONFIG_VERSION as usize] = [migrate_v0];
+
+/// Version 0 predates the `version` field; the layout is otherwise the same
+fn migrate_v0(_config: &mut serde_json::Value) -> Result<()> {
+    Ok(())
+}
+
 /// Instance types
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
@@ -45,6 +65,12 @@ pub struct InstanceInfo {
     pub containers: Vec<String>,
     pub connection_info: ConnectionInfo,
     pub metadata: HashMap<String, serde_json::Value>,
+    /// When the containers last started, as seen by `list` and `info`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<String>,
+    /// Last time every container was seen running and not unhealthy
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_seen: Option<String>,
 }
 
 /// Connection information for an instance
@@ -55,33 +81,99 @@ pub struct ConnectionInfo {
     pub password: Option<String>,
     pub url: String,
     pub additional_ports: HashMap<String, u16>,
+    /// IPv6 address for instances started with `--ipv6`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ipv6_host: Option<String>,
+    /// IPv6 connection URL for instances started with `--ipv6`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ipv6_url: Option<String>,
 }
 
 /// Configuration state
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
+    /// Schema version, see [`CONFIG_VERSION`]
+    #[serde(default)]
+    pub version: u32,
     pub instances: HashMap<String, InstanceInfo>,
     pub counters: HashMap<String, u32>,
+    /// Names handed out by [`Config::reserve_name`] but not registered yet
+    #[serde(skip)]
+    reserved: HashMap<String, u32>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            version: CONFIG_VERSION,
+            instances: HashMap::new(),
+            counters: HashMap::new(),
+            reserved: HashMap::new(),
+        }
+    }
 }
 
 impl Config {
     /// Load configuration from file
     pub fn load() -> Result<Self> {
-        let config_path = get_config_path()?;
+        Self::load_from(&get_config_path()?)
+    }
+
+    /// Load the configuration of another namespace
+    pub fn load_namespace(namespace: &str) -> Result<Self> {
+        Self::load_from(&namespace_dir(namespace)?.join(CONFIG_FILE))
+    }
 
+    fn load_from(config_path: &Path) -> Result<Self> {
         if !config_path.exists() {
             return Ok(Config::default());
         }
 
-        let content = fs::read_to_string(&config_path)
+        let content = fs::read_to_string(config_path)
             .with_context(|| format!("Failed to read config file: {}", config_path.display()))?;
 
-        let config: Config =
-            serde_json::from_str(&content).with_context(|| "Failed to parse config file")?;
+        let version = file_version(&content);
+        let mut config = Self::parse(&content)?;
+
+        // Older binaries can't read the upgraded file, so keep the original around
+        if version < CONFIG_VERSION {
+            let backup = config_path.with_extension(format!("json.v{}.bak", version));
+            if !backup.exists() {
+                fs::copy(config_path, &backup).with_context(|| {
+                    format!("Failed to back up config file to {}", backup.display())
+                })?;
+            }
+        }
+
+        crate::secrets::open(config.instances.values_mut())?;
 
         Ok(config)
     }
 
+    /// Parse a config, upgrading older versions; secrets are left as stored
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut value: serde_json::Value =
+            serde_json::from_str(content).with_context(|| "Failed to parse config file")?;
+        if !value.is_object() {
+            anyhow::bail!("Failed to parse config file: expected a JSON object");
+        }
+
+        let version = file_version(content);
+        if version > CONFIG_VERSION {
+            anyhow::bail!(
+                "Config file has version {}, but this redis-up only supports up to version {}. Upgrade redis-up to use it.",
+                version,
+                CONFIG_VERSION
+            );
+        }
+        for migration in &MIGRATIONS[version as usize..] {
+            migration(&mut value)?;
+        }
+        value["version"] = serde_json::json!(CONFIG_VERSION);
+
+        serde_json::from_value(value).with_context(|| "Failed to parse config file")
+    }
+
     /// Save configuration to file
     pub fn save(&self) -> Result<()> {
         // Ensure the config directory exists before writing
@@ -89,8 +181,13 @@ impl Config {
 
         let config_path = get_config_path()?;
 
+        // Secrets are only encrypted on disk, never in memory
+        let mut sealed = self.clone();
+        sealed.version = CONFIG_VERSION;
+        crate::secrets::seal(sealed.instances.values_mut())?;
+
         let content =
-            serde_json::to_string_pretty(self).with_context(|| "Failed to serialize config")?;
+            serde_json::to_string_pretty(&sealed).with_context(|| "Failed to serialize config")?;
 
         fs::write(&config_path, content)
             .with_context(|| format!("Failed to write config file: {}", config_path.display()))?;
@@ -98,8 +195,33 @@ impl Config {
         Ok(())
     }
 
+    /// Re-load the instance file under an exclusive lock, apply `change`, and
+    /// save only when it reports a change
+    ///
+    /// For updates made after slow work such as docker inspect or a teardown:
+    /// changing a copy loaded before that work would overwrite instances other
+    /// runs registered or removed meanwhile. Returns the up-to-date config.
+    pub fn update(change: impl FnOnce(&mut Config) -> Result<bool>) -> Result<Config> {
+        let _lock = lock_instances()?;
+        let mut config = Config::load()?;
+        if change(&mut config)? {
+            config.save()?;
+        }
+        Ok(config)
+    }
+
     /// Add an instance to the configuration
+    ///
+    /// Registering an instance under a generated name is what uses the name up:
+    /// the counter for its type only advances here.
     pub fn add_instance(&mut self, info: InstanceInfo) {
+        if let Some(number) = generated_number(&info.name, &info.instance_type) {
+            let counter = self
+                .counters
+                .entry(info.instance_type.to_string())
+                .or_insert(0);
+            *counter = (*counter).max(number);
+        }
         self.instances.insert(info.name.clone(), info);
     }
 
@@ -113,6 +235,11 @@ impl Config {
         self.instances.get(name)
     }
 
+    /// Get a mutable reference to an instance by name
+    pub fn get_instance_mut(&mut self, name: &str) -> Option<&mut InstanceInfo> {
+        self.instances.get_mut(name)
+    }
+
     /// List all instances
     pub fn list_instances(&self) -> Vec<&InstanceInfo> {
         self.instances.values().collect()
@@ -126,11 +253,28 @@ impl Config {
             .collect()
     }
 
-    /// Generate a unique name for an instance type
-    pub fn generate_name(&mut self, instance_type: &InstanceType) -> String {
-        let counter = self.counters.entry(instance_type.to_string()).or_insert(0);
-        *counter += 1;
-        format!("redis-{}-{}", instance_type, counter)
+    /// Pick the next generated name for an instance type, with the configured prefix or namespace
+    ///
+    /// Nothing is persisted: the counter advances when an instance with the name
+    /// is registered through [`Config::add_instance`], so a failed start leaves
+    /// the counter as it was. Names reserved on the same `Config` are distinct.
+    pub fn reserve_name(&mut self, instance_type: &InstanceType) -> String {
+        let kind = instance_type.to_string();
+        let mut number = self
+            .counters
+            .get(&kind)
+            .copied()
+            .unwrap_or(0)
+            .max(self.reserved.get(&kind).copied().unwrap_or(0));
+        let name = loop {
+            number += 1;
+            let name = generated_name(instance_type, number);
+            if !self.instances.contains_key(&name) {
+                break name;
+            }
+        };
+        self.reserved.insert(kind, number);
+        name
     }
 
     /// Get the latest instance of a type (highest counter)
@@ -149,18 +293,225 @@ impl Config {
     }
 }
 
+fn generated_name(instance_type: &InstanceType, number: u32) -> String {
+    let name = format!("redis-{}-{}", instance_type, number);
+    match crate::settings::name_prefix() {
+        Some(prefix) => format!("{}-{}", prefix, name),
+        None => name,
+    }
+}
+
+/// The counter value in a generated name such as `bob-redis-basic-3`
+fn generated_number(name: &str, instance_type: &InstanceType) -> Option<u32> {
+    let (stem, number) = name.rsplit_once('-')?;
+    let base = format!("redis-{}", instance_type);
+    let prefixed = stem
+        .strip_suffix(&base)
+        .is_some_and(|prefix| prefix.is_empty() || prefix.ends_with('-'));
+    if prefixed {
+        number.parse().ok()
+    } else {
+        None
+    }
+}
+
+/// Host port ranges reserved per kind of port; a start whose requested ports are
+/// taken moves to the first free block in its range
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PortPool {
+    /// Basic and Stack instances, and Sentinel masters
+    Redis,
+    /// Cluster nodes; each also needs its bus port 10000 above
+    Cluster,
+    /// Sentinel nodes
+    Sentinel,
+}
+
+impl PortPool {
+    pub const ALL: [PortPool; 3] = [PortPool::Redis, PortPool::Cluster, PortPool::Sentinel];
+
+    pub fn range(self) -> RangeInclusive<u16> {
+        match self {
+            PortPool::Redis => 6379..=6999,
+            PortPool::Cluster => 7000..=7999,
+            PortPool::Sentinel => 26000..=26999,
+        }
+    }
+
+    /// Offsets of the host ports each allocated port occupies
+    fn offsets(self) -> &'static [u16] {
+        match self {
+            PortPool::Cluster => &[0, 10000],
+            _ => &[0],
+        }
+    }
+}
+
+impl std::fmt::Display for PortPool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PortPool::Redis => write!(f, "redis"),
+            PortPool::Cluster => write!(f, "cluster"),
+            PortPool::Sentinel => write!(f, "sentinel"),
+        }
+    }
+}
+
+/// Host ports held by managed instances, each with its holders as (instance, role);
+/// more than one holder is a collision
+pub type PortMap = BTreeMap<u16, Vec<(String, String)>>;
+
+impl Config {
+    /// Host ports held by this config's instances
+    pub fn port_map(&self) -> PortMap {
+        let mut map = PortMap::new();
+        for instance in self.instances.values() {
+            add_instance_ports(&mut map, instance, &instance.name);
+        }
+        map
+    }
+
+    /// Host ports held by instances of every namespace, since they share one Docker host;
+    /// other namespaces' instances are shown as `namespace/name`
+    pub fn port_map_all(&self) -> PortMap {
+        let mut map = self.port_map();
+        let current = crate::settings::namespace();
+        for namespace in namespaces().unwrap_or_default() {
+            if current.as_ref() == Some(&namespace) {
+                continue;
+            }
+            let Ok(config) = Config::load_namespace(&namespace) else {
+                continue;
+            };
+            for instance in config.instances.values() {
+                let holder = format!("{}/{}", namespace, instance.name);
+                add_instance_ports(&mut map, instance, &holder);
+            }
+        }
+        map
+    }
+}
+
+fn add_instance_ports(map: &mut PortMap, instance: &InstanceInfo, holder: &str) {
+    let mut add = |port: u16, role: &str| {
+        let holders = map.entry(port).or_default();
+        if !holders.iter().any(|(name, _)| name == holder) {
+            holders.push((holder.to_string(), role.to_string()));
+        }
+    };
+    let role = instance.instance_type.to_string();
+    for &port in &instance.ports {
+        add(port, &role);
+        if instance.instance_type == InstanceType::Cluster {
+            if let Some(bus) = port.checked_add(10000) {
+                add(bus, "cluster bus");
+            }
+        }
+    }
+    let mut additional: Vec<_> = instance.connection_info.additional_ports.iter().collect();
+    additional.sort();
+    for (role, &port) in additional {
+        add(port, role);
+    }
+    if let Some(port) = instance
+        .metadata
+        .get("insight_port")
+        .and_then(|v| v.as_u64())
+    {
+        add(port as u16, "redisinsight");
+    }
+}
+
+/// First port of `count` consecutive free ports: `requested` when its block is free,
+/// otherwise the first free block in the pool's range at or after it, then before it
+pub fn allocate_ports(taken: &PortMap, pool: PortPool, requested: u16, count: u16) -> Option<u16> {
+    let free = |base: u16| {
+        (0..count.max(1)).all(|i| {
+            pool.offsets().iter().all(|offset| {
+                base.checked_add(i)
+                    .and_then(|port| port.checked_add(*offset))
+                    .is_some_and(|port| !taken.contains_key(&port))
+            })
+        })
+    };
+    if free(requested) {
+        return Some(requested);
+    }
+    let range = pool.range();
+    let last = range.end().checked_sub(count.max(1) - 1)?;
+    let bases = *range.start()..=last;
+    bases
+        .clone()
+        .filter(|base| *base >= requested)
+        .chain(bases.filter(|base| *base < requested))
+        .find(|base| free(*base))
+}
+
+/// Version of a config file; files without one are version 0
+fn file_version(content: &str) -> u32 {
+    serde_json::from_str::<serde_json::Value>(content)
+        .ok()
+        .and_then(|value| value.get("version")?.as_u64())
+        .map_or(0, |version| version.min(u32::MAX as u64) as u32)
+}
+
 /// Get the configuration directory path
 pub fn get_config_dir() -> Result<PathBuf> {
     let home = dirs::home_dir().context("Could not determine home directory")?;
     Ok(home.join(".config").join(CONFIG_DIR))
 }
 
+/// Directory for the current namespace's instances, history and cleanup snapshot
+///
+/// Settings stay in the config directory itself and apply to every namespace.
+pub fn get_state_dir() -> Result<PathBuf> {
+    match crate::settings::namespace() {
+        Some(namespace) => namespace_dir(&namespace),
+        None => get_config_dir(),
+    }
+}
+
+/// Hold the instance file's lock until the returned file is dropped
+fn lock_instances() -> Result<fs::File> {
+    ensure_config_dir()?;
+    let path = get_state_dir()?.join(format!("{}.lock", CONFIG_FILE));
+    let file = fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&path)
+        .with_context(|| format!("Failed to open lock file: {}", path.display()))?;
+    file.lock()
+        .with_context(|| format!("Failed to lock {}", path.display()))?;
+    Ok(file)
+}
+
+fn namespace_dir(namespace: &str) -> Result<PathBuf> {
+    Ok(get_config_dir()?.join(NAMESPACES_DIR).join(namespace))
+}
+
+/// Every namespace that has state on this machine, sorted
+pub fn namespaces() -> Result<Vec<String>> {
+    let dir = get_config_dir()?.join(NAMESPACES_DIR);
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut names: Vec<String> = fs::read_dir(&dir)
+        .with_context(|| format!("Failed to read {}", dir.display()))?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .collect();
+    names.sort();
+    Ok(names)
+}
+
 /// Get the configuration file path
 pub fn get_config_path() -> Result<PathBuf> {
-    Ok(get_config_dir()?.join(CONFIG_FILE))
+    Ok(get_state_dir()?.join(CONFIG_FILE))
 }
 
-/// Ensure the configuration directory exists
+/// Ensure the configuration and namespace state directories exist
 pub fn ensure_config_dir() -> Result<()> {
     let config_dir = get_config_dir()?;
 
@@ -173,6 +524,28 @@ pub fn ensure_config_dir() -> Result<()> {
         })?;
     }
 
+    let state_dir = get_state_dir()?;
+    if !state_dir.exists() {
+        // The first namespace created adopts state from before namespaces existed
+        let adopt = namespaces()?.is_empty();
+        fs::create_dir_all(&state_dir).with_context(|| {
+            format!(
+                "Failed to create namespace directory: {}",
+                state_dir.display()
+            )
+        })?;
+        if adopt {
+            for file in LEGACY_STATE_FILES {
+                let legacy = config_dir.join(file);
+                if legacy.exists() {
+                    fs::rename(&legacy, state_dir.join(file)).with_context(|| {
+                        format!("Failed to move {} into {}", file, state_dir.display())
+                    })?;
+                }
+            }
+        }
+    }
+
     Ok(())
 }
 
@@ -207,16 +580,34 @@ mod tests {
     fn test_config_name_generation() {
         let mut config = Config::default();
 
-        // Test that counters increment properly
-        let name1 = config.generate_name(&InstanceType::Basic);
+        // Reservations on one config are distinct but don't touch the counter
+        let name1 = config.reserve_name(&InstanceType::Basic);
         assert_eq!(name1, "redis-basic-1");
 
-        let name2 = config.generate_name(&InstanceType::Basic);
+        let name2 = config.reserve_name(&InstanceType::Basic);
         assert_eq!(name2, "redis-basic-2");
+        assert!(config.counters.is_empty());
 
         // Different types have separate counters
-        let cluster1 = config.generate_name(&InstanceType::Cluster);
+        let cluster1 = config.reserve_name(&InstanceType::Cluster);
         assert_eq!(cluster1, "redis-cluster-1");
+
+        // Registering commits the counter; an abandoned reservation is reused
+        let mut config = Config::default();
+        let name = config.reserve_name(&InstanceType::Basic);
+        assert_eq!(Config::default().reserve_name(&InstanceType::Basic), name);
+        assert_eq!(
+            generated_number("bob-redis-basic-7", &InstanceType::Basic),
+            Some(7)
+        );
+        assert_eq!(
+            generated_number("myredis-basic-7", &InstanceType::Basic),
+            None
+        );
+        assert_eq!(
+            generated_number("redis-stack-7", &InstanceType::Basic),
+            None
+        );
     }
 
     #[test]
@@ -236,8 +627,12 @@ mod tests {
                 password: None,
                 url: "redis://localhost:6379".to_string(),
                 additional_ports: HashMap::new(),
+                ipv6_host: None,
+                ipv6_url: None,
             },
             metadata: HashMap::new(),
+            started_at: None,
+            last_seen: None,
         };
 
         let instance2 = InstanceInfo {
@@ -252,8 +647,12 @@ mod tests {
                 password: None,
                 url: "redis://localhost:6380".to_string(),
                 additional_ports: HashMap::new(),
+                ipv6_host: None,
+                ipv6_url: None,
             },
             metadata: HashMap::new(),
+            started_at: None,
+            last_seen: None,
         };
 
         config.add_instance(instance1);
@@ -263,6 +662,10 @@ mod tests {
         let latest = config.get_latest_instance(&InstanceType::Basic);
         assert!(latest.is_some());
         assert_eq!(latest.unwrap().name, "redis-basic-5");
+
+        // Registered generated names advance the counter
+        assert_eq!(config.counters["basic"], 5);
+        assert_eq!(config.reserve_name(&InstanceType::Basic), "redis-basic-6");
     }
 
     #[test]
@@ -281,4 +684,45 @@ mod tests {
             assert_eq!(password.len(), 16);
         }
     }
+
+    #[test]
+    fn test_config_versions() {
+        let legacy = r#"{"instances": {}, "counters": {"basic": 2}}"#;
+        let config = Config::parse(legacy).unwrap();
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert_eq!(config.counters["basic"], 2);
+
+        let newer = format!(
+            r#"{{"version": {}, "instances": {{}}, "counters": {{}}}}"#,
+            CONFIG_VERSION + 1
+        );
+        let err = Config::parse(&newer).unwrap_err().to_string();
+        assert!(err.contains("Upgrade redis-up"), "{}", err);
+    }
+
+    #[test]
+    fn test_allocate_ports() {
+        let mut taken = PortMap::new();
+        for port in [6379, 7000, 7001, 7002, 17003] {
+            taken.insert(port, vec![("a".to_string(), "basic".to_string())]);
+        }
+        assert_eq!(allocate_ports(&taken, PortPool::Redis, 6380, 1), Some(6380));
+        assert_eq!(allocate_ports(&taken, PortPool::Redis, 6379, 1), Some(6380));
+        // 7003 is free but its bus port is not
+        assert_eq!(
+            allocate_ports(&taken, PortPool::Cluster, 7000, 3),
+            Some(7004)
+        );
+        assert_eq!(allocate_ports(&taken, PortPool::Cluster, 7000, 1000), None);
+        // Ports outside the range are kept when free, and fall back into it when not
+        assert_eq!(
+            allocate_ports(&taken, PortPool::Sentinel, 30000, 3),
+            Some(30000)
+        );
+        taken.insert(30000, Vec::new());
+        assert_eq!(
+            allocate_ports(&taken, PortPool::Sentinel, 30000, 3),
+            Some(26000)
+        );
+    }
 }
@@ -0,0 +1,165 @@
+//! Typed failures and the process exit codes they map to
+//!
+//! Commands keep returning `anyhow::Result`; failures that scripts may want to
+//! branch on are raised as a [`Failure`] somewhere in the error chain, and
+//! `main` turns the first one it finds into the process exit code.
+
+use std::fmt;
+use std::process::ExitCode;
+
+/// Any failure without a more specific code
+pub const GENERAL: u8 = 1;
+
+/// Invalid command line (reported by clap)
+pub const USAGE: u8 = 2;
+
+/// Failures with their own exit code
+#[derive(Debug)]
+pub enum Failure {
+    /// A host port is already taken
+    PortConflict(String),
+    /// The docker CLI is missing or the daemon is not running
+    DockerUnavailable(String),
+    /// An instance or container with that name already exists
+    NameConflict(String),
+    /// Something did not become ready in time
+    Timeout(String),
+    /// Some, but not all, of a multi-instance operation failed
+    Partial(String),
+    /// An instance failed its health checks
+    Unhealthy(String),
+}
+
+impl Failure {
+    pub fn code(&self) -> u8 {
+        match self {
+            Failure::PortConflict(_) => 10,
+            Failure::DockerUnavailable(_) => 11,
+            Failure::NameConflict(_) => 12,
+            Failure::Timeout(_) => 13,
+            Failure::Partial(_) => 14,
+            Failure::Unhealthy(_) => 15,
+        }
+    }
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Failure::PortConflict(message)
+            | Failure::DockerUnavailable(message)
+            | Failure::NameConflict(message)
+            | Failure::Timeout(message)
+            | Failure::Partial(message)
+            | Failure::Unhealthy(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for Failure {}
+
+/// Every exit code with its name and meaning, as `redis-up exit-codes` lists them
+pub const EXIT_CODES: &[(u8, &str, &str)] = &[
+    (0, "success", "The command completed"),
+    (GENERAL, "error", "Any failure without a more specific code"),
+    (USAGE, "usage", "Invalid arguments or options"),
+    (10, "port-conflict", "A host port is already in use"),
+    (
+        11,
+        "docker-unavailable",
+        "The docker CLI is missing or the daemon is not running",
+    ),
+    (
+        12,
+        "name-conflict",
+        "An instance or container with that name already exists",
+    ),
+    (13, "timeout", "An instance did not become ready in time"),
+    (
+        14,
+        "partial-failure",
+        "Some instances or versions failed while others succeeded",
+    ),
+    (15, "unhealthy", "An instance failed its health checks"),
+];
+
+/// Exit code for an error returned from a command
+pub fn exit_code(error: &anyhow::Error) -> ExitCode {
+    ExitCode::from(code_for(error))
+}
+
+fn code_for(error: &anyhow::Error) -> u8 {
+    // Finds a Failure whether it was returned as the error or attached as context
+    if let Some(failure) = error.downcast_ref::<Failure>() {
+        return failure.code();
+    }
+    error
+        .chain()
+        .filter_map(|cause| cause.downcast_ref::<docker_wrapper::Error>())
+        .find_map(docker_code)
+        .unwrap_or(GENERAL)
+}
+
+/// Codes for docker-wrapper errors that were not turned into a [`Failure`]
+fn docker_code(error: &docker_wrapper::Error) -> Option<u8> {
+    use docker_wrapper::Error;
+    match error {
+        Error::DockerNotFound | Error::DaemonNotRunning => Some(11),
+        // Raised when the docker binary could not be spawned at all
+        Error::Custom { message } if message.starts_with("Failed to execute docker") => Some(11),
+        Error::CommandFailed { stderr, .. } => {
+            if daemon_unreachable(stderr) {
+                Some(11)
+            } else if stderr.contains("port is already allocated")
+                || stderr.contains("address already in use")
+            {
+                Some(10)
+            } else if stderr.contains("is already in use by container") {
+                Some(12)
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Whether docker's stderr says the daemon could not be reached
+pub fn daemon_unreachable(stderr: &str) -> bool {
+    stderr.contains("Cannot connect to the Docker daemon")
+        || stderr.contains("error during connect")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn test_code_for() {
+        let plain = anyhow::anyhow!("something broke");
+        assert_eq!(code_for(&plain), GENERAL);
+
+        let wrapped = Err::<(), _>(Failure::Timeout("not ready".to_string()))
+            .context("Failed to start 'cache'")
+            .unwrap_err();
+        assert_eq!(code_for(&wrapped), 13);
+
+        let as_context = Err::<(), _>(anyhow::anyhow!("connection refused"))
+            .context(Failure::Partial("1 of 2 failed".to_string()))
+            .context("Matrix run")
+            .unwrap_err();
+        assert_eq!(code_for(&as_context), 14);
+
+        let docker = anyhow::Error::from(docker_wrapper::Error::command_failed(
+            "docker run",
+            125,
+            "",
+            "Bind for 0.0.0.0:6379 failed: port is already allocated",
+        ));
+        assert_eq!(code_for(&docker), 10);
+
+        let codes: Vec<u8> = EXIT_CODES.iter().map(|(code, _, _)| *code).collect();
+        assert!(codes.windows(2).all(|w| w[0] < w[1]));
+    }
+}
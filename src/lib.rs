@@ -6,6 +6,11 @@
 pub mod cli;
 pub mod commands;
 pub mod config;
+pub mod error;
+pub mod logging;
+pub mod secrets;
+pub mod settings;
+pub mod telemetry;
 
 // Re-export commonly used types
 pub use cli::{Cli, Commands};
@@ -0,0 +1,141 @@
+//! Tracing setup for diagnostics
+//!
+//! `--log-format json` writes one JSON object per event to stderr, so verbose
+//! diagnostics can be parsed without mixing into the command's own output.
+//! `--otel-endpoint` adds the span exporter from [`crate::telemetry`].
+
+use chrono::Utc;
+use serde_json::{Map, Value};
+use std::fmt;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields};
+use tracing_subscriber::layer::{Layer, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Registry};
+
+use crate::cli::LogFormat;
+use crate::telemetry;
+
+/// Install the global tracing subscriber
+pub fn init(verbose: bool, format: LogFormat, otel_endpoint: Option<&str>) {
+    let env_filter = if verbose {
+        "redis_up=debug"
+    } else {
+        "redis_up=info"
+    };
+    let fmt = tracing_subscriber::fmt::layer().with_target(false);
+    let fmt: Box<dyn Layer<Registry> + Send + Sync> = match format {
+        LogFormat::Text => fmt
+            .with_ansi(colored::control::SHOULD_COLORIZE.should_colorize())
+            .boxed(),
+        LogFormat::Json => fmt
+            .with_writer(std::io::stderr)
+            .event_format(JsonFormat)
+            .boxed(),
+    };
+
+    tracing_subscriber::registry()
+        .with(fmt)
+        .with(otel_endpoint.and_then(telemetry::layer))
+        .with(EnvFilter::new(env_filter))
+        .init();
+}
+
+/// Formats each event as a single-line JSON object
+struct JsonFormat;
+
+impl<S, N> FormatEvent<S, N> for JsonFormat
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        _ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let metadata = event.metadata();
+        let mut fields = JsonFields::default();
+        event.record(&mut fields);
+
+        let mut line = Map::new();
+        line.insert("timestamp".into(), Utc::now().to_rfc3339().into());
+        line.insert("level".into(), metadata.level().as_str().into());
+        line.insert("target".into(), metadata.target().into());
+        line.extend(fields.0);
+
+        writeln!(writer, "{}", Value::Object(line))
+    }
+}
+
+/// Collects event and span fields, keeping numbers and booleans typed
+#[derive(Default)]
+struct JsonFields(Map<String, Value>);
+
+impl Visit for JsonFields {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0
+            .insert(field.name().into(), format!("{:?}", value).into());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_json_format() {
+        let buffer = Buffer::default();
+        let writer = buffer.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(move || writer.clone())
+            .event_format(JsonFormat)
+            .finish();
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::warn!(port = 6379, retry = true, "Failed to remove network");
+        });
+
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let line: Value = serde_json::from_str(output.trim()).unwrap();
+        assert_eq!(line["level"], "WARN");
+        assert_eq!(line["message"], "Failed to remove network");
+        assert_eq!(line["port"], 6379);
+        assert_eq!(line["retry"], true);
+    }
+}
@@ -3,33 +3,106 @@
 //! A command-line tool for quickly spinning up Redis development environments
 //! including basic Redis, Redis Stack, Redis Cluster, Redis Sentinel, and Redis Enterprise.
 
-use anyhow::Result;
-use clap::Parser;
+use anyhow::{Context, Result};
+use clap::{ArgMatches, CommandFactory, FromArgMatches};
 use colored::*;
+use std::process::ExitCode;
+use tracing::Instrument;
 
 mod cli;
 mod commands;
 mod config;
+mod error;
+mod logging;
+mod secrets;
+mod settings;
+mod telemetry;
 
 use cli::{Cli, Commands};
 
 #[tokio::main]
-async fn main() -> Result<()> {
-    let cli = Cli::parse();
+async fn main() -> ExitCode {
+    let result = run().await;
+    telemetry::flush().await;
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {:?}", e);
+            error::exit_code(&e)
+        }
+    }
+}
+
+async fn run() -> Result<()> {
+    let matches =
+        Cli::command().get_matches_from(commands::profile::expand(std::env::args().collect())?);
+    commands::env::check_args(&matches)?;
+    let cli = Cli::from_arg_matches(&matches)?;
+    if cli.profile.is_some() && !commands::profile::takes_profile(&cli.command) {
+        anyhow::bail!(
+            "--profile only applies to basic, stack, cluster, sentinel and enterprise start, deploy and warm"
+        );
+    }
+
+    if cli.no_color {
+        colored::control::set_override(false);
+    }
 
     // Initialize tracing
-    let env_filter = if cli.verbose {
-        "redis_up=debug"
-    } else {
-        "redis_up=info"
-    };
-    tracing_subscriber::fmt()
-        .with_env_filter(env_filter)
-        .with_target(false)
-        .init();
+    logging::init(cli.verbose, cli.log_format, cli.otel_endpoint.as_deref());
 
+    // Root of this invocation's trace when spans are exported
+    let span = tracing::info_span!(
+        "command",
+        otel.name = %command_name(&matches),
+        otel.status_description = tracing::field::Empty
+    );
+    let result = execute(cli).instrument(span.clone()).await;
+    if let Err(ref e) = result {
+        telemetry::record_error(&span, e);
+    }
+    result
+}
+
+/// `redis-up cluster start` for the subcommands that were run
+fn command_name(matches: &ArgMatches) -> String {
+    let mut name = String::from("redis-up");
+    let mut matches = matches;
+    while let Some((subcommand, sub_matches)) = matches.subcommand() {
+        name.push(' ');
+        name.push_str(subcommand);
+        matches = sub_matches;
+    }
+    name
+}
+
+async fn execute(cli: Cli) -> Result<()> {
     // Initialize configuration
+    settings::init_namespace(cli.namespace.as_deref())?;
     config::ensure_config_dir()?;
+    settings::init_prefix(cli.prefix.as_deref())?;
+    commands::passthrough::init(&cli.pass_env, &cli.docker_arg)?;
+
+    if cli.dry_run {
+        let command = cli.command.as_ref().context("--dry-run needs a command")?;
+        let plans =
+            commands::plan::plan_command(command, cli.prefix.as_deref(), cli.profile.as_deref())
+                .await?
+                .context(
+                    "--dry-run is only supported for start, stop, cleanup and deploy commands",
+                )?;
+        if plans.is_empty() {
+            println!("{} Nothing to do", "Plan:".bold().cyan());
+        }
+        for plan in &plans {
+            plan.print();
+        }
+        return Ok(());
+    }
+
+    if let Some(command) = &cli.command {
+        commands::reconcile::reconcile(command, cli.verbose).await;
+    }
 
     match cli.command {
         Some(Commands::Basic { action }) => {
@@ -47,25 +120,140 @@ async fn main() -> Result<()> {
         Some(Commands::Enterprise { action }) => {
             commands::enterprise::handle_action(action, cli.verbose).await?;
         }
-        Some(Commands::List { r#type }) => {
-            commands::list::handle_list(r#type, cli.verbose).await?;
+        Some(Commands::Insight { action }) => {
+            commands::insight::handle_action(action, cli.verbose).await?;
+        }
+        Some(Commands::Network { action }) => {
+            commands::network::handle_action(action, cli.verbose).await?;
+        }
+        Some(Commands::List(args)) => {
+            commands::list::handle_list(args, cli.verbose).await?;
+        }
+        Some(Commands::Cleanup(args)) => {
+            commands::cleanup::handle_cleanup(args, cli.prefix.as_deref(), cli.verbose).await?;
         }
-        Some(Commands::Cleanup { force, r#type }) => {
-            commands::cleanup::handle_cleanup(force, r#type, cli.verbose).await?;
+        Some(Commands::RestoreState(args)) => {
+            commands::restore::handle_restore(args, cli.verbose).await?;
+        }
+        Some(Commands::Revive(args)) => {
+            commands::revive::handle_revive(args, cli.verbose).await?;
+        }
+        Some(Commands::Annotate(args)) => {
+            commands::annotate::handle_annotate(args).await?;
+        }
+        Some(Commands::Unlock(args)) => {
+            commands::unlock::handle_unlock(args).await?;
+        }
+        Some(Commands::Lock) => {
+            commands::unlock::handle_lock().await?;
+        }
+        Some(Commands::Profile { action }) => {
+            commands::profile::handle_action(action)?;
+        }
+        Some(Commands::Config { action }) => {
+            commands::config::handle_action(action, cli.verbose).await?;
         }
         Some(Commands::Logs {
             name,
             follow,
             tail,
             timestamps,
+            role,
         }) => {
-            commands::logs::handle_logs(name, follow, tail, timestamps, cli.verbose).await?;
+            commands::logs::handle_logs(name, follow, tail, timestamps, role, cli.verbose).await?;
+        }
+        Some(Commands::Record(args)) => {
+            commands::record::handle_record(args, cli.verbose).await?;
+        }
+        Some(Commands::Dbsize(args)) => {
+            commands::dbsize::handle_dbsize(args, cli.verbose).await?;
+        }
+        Some(Commands::Shell(args)) => {
+            commands::shell::handle_shell(args, cli.verbose).await?;
+        }
+        Some(Commands::Sh(args)) => {
+            commands::shell::handle_sh(args, cli.verbose).await?;
+        }
+        Some(Commands::ConfigDiff(args)) => {
+            commands::config_diff::handle_config_diff(args, cli.verbose).await?;
+        }
+        Some(Commands::ConfigSet(args)) => {
+            commands::config_set::handle_config_set(args, cli.verbose).await?;
+        }
+        Some(Commands::ConnectSnippet(args)) => {
+            commands::snippets::handle_connect_snippet(args, cli.verbose).await?;
+        }
+        Some(Commands::Env(args)) => {
+            commands::env::handle_env(args).await?;
+        }
+        Some(Commands::Matrix(args)) => {
+            commands::matrix::handle_matrix(args, cli.verbose).await?;
+        }
+        Some(Commands::Replication(args)) => {
+            commands::replication::handle_replication(args, cli.verbose).await?;
+        }
+        Some(Commands::Health(args)) => {
+            commands::health::handle_health(args).await?;
+        }
+        Some(Commands::Chaos { action }) => {
+            commands::chaos::handle_action(action, cli.verbose).await?;
+        }
+        Some(Commands::Load(args)) => {
+            commands::load::handle_load(args, cli.verbose).await?;
+        }
+        Some(Commands::Script { action }) => {
+            commands::script::handle_action(action, cli.verbose).await?;
+        }
+        Some(Commands::Functions { action }) => {
+            commands::functions::handle_action(action, cli.verbose).await?;
+        }
+        Some(Commands::Diff(args)) => {
+            commands::diff::handle_diff(args, cli.verbose).await?;
+        }
+        Some(Commands::Anonymize(args)) => {
+            commands::anonymize::handle_anonymize(args, cli.verbose).await?;
+        }
+        Some(Commands::Slow(args)) => {
+            commands::slow::handle_slow(args, cli.verbose).await?;
+        }
+        Some(Commands::Watch(args)) => {
+            commands::watch::handle_watch(args, cli.verbose).await?;
+        }
+        Some(Commands::Doctor) => {
+            commands::doctor::handle_doctor(cli.verbose).await?;
+        }
+        Some(Commands::Observability { action }) => {
+            commands::observability::handle_action(action, cli.verbose).await?;
+        }
+        Some(Commands::Images { action }) => {
+            commands::images::handle_action(action, cli.verbose).await?;
+        }
+        Some(Commands::Ci { action }) => {
+            commands::ci::handle_action(action, cli.verbose).await?;
         }
         Some(Commands::Deploy { file }) => {
-            commands::yaml::deploy_from_yaml(&file, cli.verbose).await?;
+            commands::yaml::deploy_from_yaml(&file, cli.profile.as_deref(), cli.verbose).await?;
+        }
+        Some(Commands::Init(args)) => {
+            commands::init::handle_init(args, cli.verbose).await?;
+        }
+        Some(Commands::Usage(args)) => {
+            commands::history::handle_usage(args, cli.verbose).await?;
+        }
+        Some(Commands::Warm(args)) => {
+            commands::warm::handle_warm(args, cli.profile.as_deref(), cli.verbose).await?;
+        }
+        Some(Commands::Tunnel(args)) => {
+            commands::tunnel::handle_tunnel(args, cli.verbose).await?;
+        }
+        Some(Commands::Ports { json }) => {
+            commands::ports::handle_ports(json)?;
+        }
+        Some(Commands::ExitCodes { json }) => {
+            commands::exit_codes::handle_exit_codes(json)?;
         }
-        Some(Commands::Examples { dir }) => {
-            commands::yaml::generate_examples(&dir).await?;
+        Some(Commands::Examples { dir, format }) => {
+            commands::examples::handle_examples(&dir, format).await?;
         }
         None => {
             println!("{}", "Redis Developer Tool".bold().cyan());
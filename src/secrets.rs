@@ -0,0 +1,350 @@
+//! Encryption of instance secrets
+//!
+//! Encrypted values are stored as `age:<base64>`, an age file encrypted to an
+//! X25519 key. Passwords and the connection URLs that embed them are the only
+//! encrypted fields, so the rest of a config stays readable.
+//!
+//! With `encrypt-secrets: true` in settings, the registry is saved with its
+//! secrets encrypted to the key in `secrets.key`, which is itself an age file
+//! encrypted with a passphrase. The key is unlocked with `REDIS_UP_PASSPHRASE`
+//! or by `redis-up unlock`, which keeps the unlocked key (never the
+//! passphrase) in the OS keyring until the session expires.
+
+use age::secrecy::{ExposeSecret, SecretString};
+use age::x25519;
+use anyhow::{Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::config::{ensure_config_dir, get_config_dir, InstanceInfo};
+use crate::settings::Settings;
+
+/// Environment variable holding the passphrase, e.g. for CI
+pub const PASSPHRASE_ENV: &str = "REDIS_UP_PASSPHRASE";
+
+/// The registry's key, encrypted with the passphrase
+const KEY_FILE: &str = "secrets.key";
+
+/// Keyring service the unlock session is stored under
+const KEYRING_SERVICE: &str = "redis-up";
+
+/// Marks an encrypted value
+const PREFIX: &str = "age:";
+
+/// The key once unlocked, so a command unlocks it at most once
+static UNLOCKED: Mutex<Option<Cipher>> = Mutex::new(None);
+
+/// Whether a stored value is encrypted
+pub fn is_encrypted(value: &str) -> bool {
+    value.starts_with(PREFIX)
+}
+
+/// Encrypts and decrypts values with one X25519 key
+#[derive(Clone)]
+pub struct Cipher {
+    identity: x25519::Identity,
+}
+
+impl Cipher {
+    /// A new random key
+    pub fn generate() -> Self {
+        Self {
+            identity: x25519::Identity::generate(),
+        }
+    }
+
+    /// Decrypt a key written by [`Cipher::wrap`]
+    pub fn unwrap_with(wrapped: &str, passphrase: &str) -> Result<Self> {
+        let identity = age::scrypt::Identity::new(checked(passphrase)?);
+        let key = age::decrypt(&identity, wrapped.as_bytes()).map_err(|e| match e {
+            age::DecryptError::DecryptionFailed | age::DecryptError::NoMatchingKeys => {
+                anyhow::anyhow!("Wrong passphrase")
+            }
+            e => anyhow::anyhow!("Failed to decrypt the secrets key: {}", e),
+        })?;
+        let identity = String::from_utf8(key)?
+            .trim()
+            .parse()
+            .map_err(|e| anyhow::anyhow!("Secrets key is corrupted: {}", e))?;
+        Ok(Self { identity })
+    }
+
+    /// The key encrypted with `passphrase`, as an armored age file
+    pub fn wrap(&self, passphrase: &str) -> Result<String> {
+        let recipient = age::scrypt::Recipient::new(checked(passphrase)?);
+        age::encrypt_and_armor(
+            &recipient,
+            self.identity.to_string().expose_secret().as_bytes(),
+        )
+        .context("Failed to encrypt the secrets key")
+    }
+
+    pub fn encrypt(&self, plaintext: &str) -> Result<String> {
+        let sealed = age::encrypt(&self.identity.to_public(), plaintext.as_bytes())
+            .context("Failed to encrypt secret")?;
+        Ok(format!("{}{}", PREFIX, STANDARD.encode(sealed)))
+    }
+
+    /// Decrypt an encrypted value; plain values are returned unchanged
+    pub fn decrypt(&self, value: &str) -> Result<String> {
+        let Some(encoded) = value.strip_prefix(PREFIX) else {
+            return Ok(value.to_string());
+        };
+        let sealed = STANDARD
+            .decode(encoded)
+            .context("Encrypted secret is not valid base64")?;
+        let plaintext = age::decrypt(&self.identity, &sealed)
+            .map_err(|e| anyhow::anyhow!("Wrong key or corrupted secret: {}", e))?;
+        Ok(String::from_utf8(plaintext)?)
+    }
+}
+
+fn checked(passphrase: &str) -> Result<SecretString> {
+    if passphrase.is_empty() {
+        anyhow::bail!("The passphrase must not be empty");
+    }
+    Ok(SecretString::from(passphrase.to_string()))
+}
+
+/// Apply `f` to every secret of an instance: the password and the URLs embedding it
+fn map_secrets(
+    instance: &mut InstanceInfo,
+    mut f: impl FnMut(&str) -> Result<String>,
+) -> Result<()> {
+    let info = &mut instance.connection_info;
+    if let Some(password) = &info.password {
+        info.password = Some(f(password)?);
+    }
+    info.url = f(&info.url)?;
+    if let Some(url) = &info.ipv6_url {
+        info.ipv6_url = Some(f(url)?);
+    }
+    Ok(())
+}
+
+/// Encrypt an instance's secrets in place, leaving already encrypted ones alone
+///
+/// URLs are only encrypted when they carry a password.
+pub fn encrypt_instance(instance: &mut InstanceInfo, cipher: &Cipher) -> Result<()> {
+    if instance.connection_info.password.is_none() {
+        return Ok(());
+    }
+    map_secrets(instance, |value| {
+        if is_encrypted(value) {
+            Ok(value.to_string())
+        } else {
+            cipher.encrypt(value)
+        }
+    })
+}
+
+/// Decrypt an instance's secrets in place
+pub fn decrypt_instance(instance: &mut InstanceInfo, cipher: &Cipher) -> Result<()> {
+    let name = instance.name.clone();
+    map_secrets(instance, |value| cipher.decrypt(value))
+        .with_context(|| format!("Failed to decrypt secrets of '{}'", name))
+}
+
+/// Whether any of an instance's secrets are encrypted
+pub fn has_encrypted(instance: &InstanceInfo) -> bool {
+    let info = &instance.connection_info;
+    info.password.as_deref().is_some_and(is_encrypted)
+        || is_encrypted(&info.url)
+        || info.ipv6_url.as_deref().is_some_and(is_encrypted)
+}
+
+/// Encrypt secrets before they are written to disk, when `encrypt-secrets` is on
+pub fn seal<'a>(instances: impl IntoIterator<Item = &'a mut InstanceInfo>) -> Result<()> {
+    if !Settings::load()?.encrypt_secrets {
+        return Ok(());
+    }
+    let mut instances: Vec<_> = instances.into_iter().collect();
+    if instances
+        .iter()
+        .all(|i| i.connection_info.password.is_none())
+    {
+        return Ok(());
+    }
+    let cipher = unlocked_cipher()?;
+    for instance in instances.iter_mut() {
+        encrypt_instance(instance, &cipher)?;
+    }
+    Ok(())
+}
+
+/// Decrypt secrets read from disk, whatever the current setting
+pub fn open<'a>(instances: impl IntoIterator<Item = &'a mut InstanceInfo>) -> Result<()> {
+    let mut instances: Vec<_> = instances.into_iter().collect();
+    if !instances.iter().any(|i| has_encrypted(i)) {
+        return Ok(());
+    }
+    let cipher = unlocked_cipher()?;
+    for instance in instances.iter_mut() {
+        decrypt_instance(instance, &cipher)?;
+    }
+    Ok(())
+}
+
+/// The registry key, from the environment's passphrase or an unlock session
+fn unlocked_cipher() -> Result<Cipher> {
+    let mut unlocked = UNLOCKED.lock().unwrap();
+    if let Some(cipher) = unlocked.as_ref() {
+        return Ok(cipher.clone());
+    }
+    let cipher = match std::env::var(PASSPHRASE_ENV) {
+        Ok(passphrase) => unlock_key(&passphrase)?,
+        Err(_) => session()?.with_context(|| {
+            format!(
+                "Instance secrets are encrypted and locked; run 'redis-up unlock' or set {}",
+                PASSPHRASE_ENV
+            )
+        })?,
+    };
+    *unlocked = Some(cipher.clone());
+    Ok(cipher)
+}
+
+/// Decrypt the registry key with `passphrase`, creating the key on first use
+pub fn unlock_key(passphrase: &str) -> Result<Cipher> {
+    let path = key_path()?;
+    if !path.exists() {
+        ensure_config_dir()?;
+        let cipher = Cipher::generate();
+        let wrapped = cipher.wrap(passphrase)?;
+        // Created exclusively, so a key another command just wrote is never replaced
+        match fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+        {
+            Ok(mut file) => {
+                restrict(&path, 0o600)?;
+                file.write_all(wrapped.as_bytes())
+                    .with_context(|| format!("Failed to write {}", path.display()))?;
+                return Ok(cipher);
+            }
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => {}
+            Err(e) => {
+                return Err(e).with_context(|| format!("Failed to create {}", path.display()))
+            }
+        }
+    }
+    let wrapped =
+        fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+    Cipher::unwrap_with(&wrapped, passphrase)
+        .with_context(|| format!("Failed to unlock {}", path.display()))
+}
+
+fn key_path() -> Result<PathBuf> {
+    Ok(get_config_dir()?.join(KEY_FILE))
+}
+
+/// An unlock session, as kept in the OS keyring
+#[derive(Debug, Serialize, Deserialize)]
+struct Session {
+    key: String,
+    expires_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// One session per config directory, so separate homes don't share one
+fn session_entry() -> Result<keyring::Entry> {
+    keyring::Entry::new(KEYRING_SERVICE, &key_path()?.display().to_string())
+        .context("Failed to open the OS keyring")
+}
+
+/// The key from an unexpired unlock session
+fn session() -> Result<Option<Cipher>> {
+    let entry = session_entry()?;
+    let content = match entry.get_password() {
+        Ok(content) => content,
+        Err(keyring::Error::NoEntry) => return Ok(None),
+        Err(e) => {
+            return Err(e).with_context(|| {
+                format!(
+                    "Failed to read the unlock session from the OS keyring; set {} instead",
+                    PASSPHRASE_ENV
+                )
+            })
+        }
+    };
+    let session: Session =
+        serde_json::from_str(&content).context("Failed to parse the unlock session")?;
+    if session.expires_at <= chrono::Utc::now() {
+        entry.delete_credential().ok();
+        return Ok(None);
+    }
+    let identity = session
+        .key
+        .parse()
+        .map_err(|e| anyhow::anyhow!("Unlock session is corrupted: {}", e))?;
+    Ok(Some(Cipher { identity }))
+}
+
+/// Keep the unlocked key for `ttl`, returning when the session expires
+pub fn start_session(cipher: &Cipher, ttl: Duration) -> Result<chrono::DateTime<chrono::Utc>> {
+    let session = Session {
+        key: cipher.identity.to_string().expose_secret().to_string(),
+        expires_at: chrono::Utc::now() + chrono::Duration::from_std(ttl)?,
+    };
+    let entry = session_entry()?;
+    if let Err(e) = entry.set_password(&serde_json::to_string(&session)?) {
+        // A store can fail after the key went in, e.g. linking it to the persistent keyring
+        entry.delete_credential().ok();
+        return Err(e).with_context(|| {
+            format!(
+                "Failed to store the unlock session in the OS keyring; set {} instead",
+                PASSPHRASE_ENV
+            )
+        });
+    }
+    Ok(session.expires_at)
+}
+
+/// Forget the unlocked key, returning whether a session existed
+pub fn end_session() -> Result<bool> {
+    match session_entry()?.delete_credential() {
+        Ok(()) => Ok(true),
+        Err(keyring::Error::NoEntry) => Ok(false),
+        Err(e) => Err(e).context("Failed to remove the unlock session from the OS keyring"),
+    }
+}
+
+/// Set a file's permission bits (a no-op off Unix)
+#[cfg(unix)]
+pub fn restrict(path: &std::path::Path, mode: u32) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(mode))
+        .with_context(|| format!("Failed to restrict permissions of {}", path.display()))
+}
+
+#[cfg(not(unix))]
+pub fn restrict(_path: &std::path::Path, _mode: u32) -> Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let cipher = Cipher::generate();
+        let sealed = cipher.encrypt("s3cret").unwrap();
+        assert!(is_encrypted(&sealed));
+        assert_ne!(sealed, cipher.encrypt("s3cret").unwrap());
+        assert_eq!(cipher.decrypt(&sealed).unwrap(), "s3cret");
+        assert_eq!(cipher.decrypt("plain").unwrap(), "plain");
+        assert!(Cipher::generate().decrypt(&sealed).is_err());
+
+        let wrapped = cipher.wrap("correct horse").unwrap();
+        assert!(!wrapped.contains("AGE-SECRET-KEY"));
+        let unwrapped = Cipher::unwrap_with(&wrapped, "correct horse").unwrap();
+        assert_eq!(unwrapped.decrypt(&sealed).unwrap(), "s3cret");
+        assert!(Cipher::unwrap_with(&wrapped, "battery staple").is_err());
+    }
+}
@@ -0,0 +1,259 @@
+//! User settings for redis-up
+//!
+//! Defaults that apply to every command live in `~/.config/redis-up/settings.yaml`.
+//! Command-line flags always take precedence over these values.
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{OnceLock, RwLock};
+
+use crate::cli::PullPolicy;
+use crate::config::get_config_dir;
+
+/// Settings file name
+const SETTINGS_FILE: &str = "settings.yaml";
+
+/// User-level defaults
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct Settings {
+    /// How many times to retry a failed container start
+    pub retries: u32,
+
+    /// When to pull images; `never` keeps an air-gapped machine offline
+    pub pull: Option<PullPolicy>,
+
+    /// Default `--platform` per instance type, e.g. `enterprise: linux/amd64`
+    pub platforms: HashMap<String, String>,
+
+    /// Prefix for generated instance names, e.g. `acme` gives `acme-redis-basic-1`
+    pub prefix: Option<String>,
+
+    /// Keep passwords in `instances.json` encrypted; see `redis-up unlock`
+    pub encrypt_secrets: bool,
+
+    /// Namespace used when `--namespace` is not given (defaults to $USER)
+    pub namespace: Option<String>,
+
+    /// Named start flag bundles applied with `--profile`
+    pub profiles: BTreeMap<String, Profile>,
+
+    /// What to do about instances found stopped, e.g. after a reboot
+    pub auto_revive: AutoRevive,
+
+    /// Registry every image is pulled through, e.g. `artifactory.acme.com/docker-remote`
+    pub registry_mirror: Option<String>,
+
+    /// Replacement images by repository, e.g. `redis/redis-stack: registry.acme.com/redis-stack:7.2`
+    pub images: BTreeMap<String, String>,
+
+    /// Environment variables passed into every container, as for `--pass-env`
+    pub pass_env: Vec<String>,
+}
+
+/// Handling of registered instances whose containers exist but are stopped
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AutoRevive {
+    /// Don't check
+    Off,
+    /// Mention them and suggest `redis-up revive`
+    #[default]
+    Warn,
+    /// Offer to start them when run from a terminal
+    Ask,
+    /// Start them before running the command
+    Always,
+}
+
+/// Start flags by long name, e.g. `memory: 256m` or `persist: true`
+pub type Profile = BTreeMap<String, serde_yaml::Value>;
+
+/// Name prefix in effect for this run, resolved once at startup
+static PREFIX: OnceLock<Option<String>> = OnceLock::new();
+
+/// Resolve the name prefix from `--prefix`, falling back to the `prefix` setting
+pub fn init_prefix(flag: Option<&str>) -> Result<()> {
+    let prefix = match flag {
+        Some(prefix) => Some(prefix.to_string()),
+        None => Settings::load()?.prefix,
+    };
+    if let Some(prefix) = &prefix {
+        validate_prefix(prefix)?;
+    }
+    let _ = PREFIX.set(prefix.filter(|p| !p.is_empty()));
+    Ok(())
+}
+
+/// The name prefix in effect, if any
+pub fn prefix() -> Option<&'static str> {
+    PREFIX.get().and_then(|p| p.as_deref())
+}
+
+/// Namespace in effect; `--all-namespaces` switches it while visiting each one
+static NAMESPACE: RwLock<Option<String>> = RwLock::new(None);
+
+/// Resolve the namespace from `--namespace`, the `namespace` setting or $USER
+pub fn init_namespace(flag: Option<&str>) -> Result<()> {
+    let namespace = match flag {
+        Some(namespace) => namespace.to_string(),
+        None => match Settings::load()?.namespace {
+            Some(namespace) => namespace,
+            None => default_namespace(),
+        },
+    };
+    if namespace.is_empty() {
+        bail!("The namespace can't be empty");
+    }
+    validate_prefix(&namespace).context("Invalid namespace")?;
+    set_namespace(&namespace);
+    Ok(())
+}
+
+/// $USER (or %USERNAME% on Windows), made safe for Docker names
+fn default_namespace() -> String {
+    let user = std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_default();
+    namespace_for_user(&user)
+}
+
+fn namespace_for_user(user: &str) -> String {
+    let cleaned: String = user
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-') {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    let cleaned = cleaned.trim_start_matches(['_', '.', '-']);
+    if cleaned.is_empty() {
+        "default".to_string()
+    } else {
+        cleaned.to_string()
+    }
+}
+
+/// The namespace in effect; `None` only before startup has resolved it
+pub fn namespace() -> Option<String> {
+    NAMESPACE.read().ok().and_then(|ns| ns.clone())
+}
+
+pub fn set_namespace(namespace: &str) {
+    if let Ok(mut current) = NAMESPACE.write() {
+        *current = Some(namespace.to_string());
+    }
+}
+
+/// Prefix for generated names: the `--prefix` when set, otherwise the namespace
+pub fn name_prefix() -> Option<String> {
+    prefix().map(String::from).or_else(namespace)
+}
+
+/// Docker only accepts `[a-zA-Z0-9][a-zA-Z0-9_.-]*` in container, volume and network names
+fn validate_prefix(prefix: &str) -> Result<()> {
+    let mut chars = prefix.chars();
+    let valid = match chars.next() {
+        None => true,
+        Some(first) => {
+            first.is_ascii_alphanumeric()
+                && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'))
+        }
+    };
+    if !valid {
+        bail!(
+            "Invalid prefix '{}': use letters, digits, '_', '.' or '-', starting with a letter or digit",
+            prefix
+        );
+    }
+    Ok(())
+}
+
+impl Settings {
+    /// Load settings, falling back to defaults when no file exists
+    pub fn load() -> Result<Self> {
+        let path = get_settings_path()?;
+
+        if !path.exists() {
+            return Ok(Settings::default());
+        }
+
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read settings file: {}", path.display()))?;
+
+        Self::parse(&content)
+            .with_context(|| format!("Failed to parse settings file: {}", path.display()))
+    }
+
+    fn parse(content: &str) -> Result<Self> {
+        // An empty file deserializes to null rather than an empty map
+        if content.trim().is_empty() {
+            return Ok(Settings::default());
+        }
+        Ok(serde_yaml::from_str(content)?)
+    }
+}
+
+/// Get the settings file path
+pub fn get_settings_path() -> Result<PathBuf> {
+    Ok(get_config_dir()?.join(SETTINGS_FILE))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_settings() {
+        assert_eq!(Settings::parse("").unwrap(), Settings::default());
+        assert_eq!(Settings::parse("retries: 3\n").unwrap().retries, 3);
+        assert!(Settings::parse("retries: many\n").is_err());
+
+        let settings = Settings::parse("pull: never\n").unwrap();
+        assert_eq!(settings.pull, Some(PullPolicy::Never));
+        assert_eq!(settings.retries, 0);
+
+        let settings = Settings::parse("platforms:\n  enterprise: linux/amd64\n").unwrap();
+        assert_eq!(settings.platforms["enterprise"], "linux/amd64");
+
+        let settings = Settings::parse("auto-revive: always\n").unwrap();
+        assert_eq!(settings.auto_revive, AutoRevive::Always);
+        assert_eq!(Settings::default().auto_revive, AutoRevive::Warn);
+
+        let settings = Settings::parse(
+            "registry-mirror: mirror.acme.com\nimages:\n  redislabs/redis: acme/rs:7.4\n",
+        )
+        .unwrap();
+        assert_eq!(settings.registry_mirror.as_deref(), Some("mirror.acme.com"));
+        assert_eq!(settings.images["redislabs/redis"], "acme/rs:7.4");
+
+        let settings = Settings::parse("prefix: acme\n").unwrap();
+        assert_eq!(settings.prefix.as_deref(), Some("acme"));
+
+        let settings =
+            Settings::parse("profiles:\n  cache:\n    memory: 256m\n    persist: false\n").unwrap();
+        assert_eq!(settings.profiles["cache"]["memory"], "256m");
+    }
+
+    #[test]
+    fn test_validate_prefix() {
+        assert!(validate_prefix("acme").is_ok());
+        assert!(validate_prefix("team_1.dev-x").is_ok());
+        assert!(validate_prefix("-acme").is_err());
+        assert!(validate_prefix("acme corp").is_err());
+    }
+
+    #[test]
+    fn test_namespace_for_user() {
+        assert_eq!(namespace_for_user("alice"), "alice");
+        assert_eq!(namespace_for_user("Jane Doe"), "jane-doe");
+        assert_eq!(namespace_for_user("DOMAIN\\bob"), "domain-bob");
+        assert_eq!(namespace_for_user(""), "default");
+    }
+}
@@ -0,0 +1,151 @@
+//! OpenTelemetry export of redis-up's tracing spans
+//!
+//! With `--otel-endpoint`, every span (the command itself, image pulls,
+//! container starts, cluster formation, Enterprise bootstrap, readiness waits,
+//! stops) goes through tracing-opentelemetry to a batching OTLP/HTTP exporter,
+//! which sends them in the background while the command runs; `flush` sends
+//! the rest before exit. All spans of one invocation share a trace.
+//!
+//! A span's `otel.name` field replaces its name in the export, and
+//! [`record_error`] marks it failed.
+
+use colored::*;
+use opentelemetry::trace::TracerProvider;
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+use opentelemetry_sdk::trace::{SdkTracer, SdkTracerProvider};
+use opentelemetry_sdk::Resource;
+use std::sync::OnceLock;
+use std::time::Duration;
+use tracing::{Span, Subscriber};
+use tracing_opentelemetry::OpenTelemetryLayer;
+use tracing_subscriber::registry::LookupSpan;
+
+/// How long one export may take, including the last one holding up the exit
+const EXPORT_TIMEOUT: Duration = Duration::from_secs(5);
+
+static PROVIDER: OnceLock<SdkTracerProvider> = OnceLock::new();
+
+/// The layer for `--otel-endpoint`, or `None` (with a warning) if the exporter can't be built
+pub fn layer<S>(endpoint: &str) -> Option<OpenTelemetryLayer<S, SdkTracer>>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    let url = traces_url(endpoint);
+    let exporter = match SpanExporter::builder()
+        .with_http()
+        .with_endpoint(&url)
+        .with_timeout(EXPORT_TIMEOUT)
+        .build()
+    {
+        Ok(exporter) => exporter,
+        Err(e) => {
+            eprintln!(
+                "{} Not exporting traces to {}: {}",
+                "Warning:".yellow(),
+                url,
+                e
+            );
+            return None;
+        }
+    };
+    let provider = SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(
+            Resource::builder()
+                .with_service_name("redis-up")
+                .with_attribute(KeyValue::new("service.version", env!("CARGO_PKG_VERSION")))
+                .build(),
+        )
+        .build();
+    let layer = layer_for(&provider);
+    PROVIDER.get_or_init(|| provider);
+    Some(layer)
+}
+
+fn layer_for<S>(provider: &SdkTracerProvider) -> OpenTelemetryLayer<S, SdkTracer>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    tracing_opentelemetry::layer().with_tracer(provider.tracer("redis-up"))
+}
+
+/// Mark `span` failed with `error` as its status message
+pub fn record_error(span: &Span, error: &anyhow::Error) {
+    span.record("otel.status_description", error.to_string().as_str());
+}
+
+/// Send the spans still queued; export problems are reported, never fatal
+pub async fn flush() {
+    let Some(provider) = PROVIDER.get().cloned() else {
+        return;
+    };
+    // The exporter's HTTP client blocks, so keep it off the runtime's threads;
+    // shutting down alone would drop the last export's error
+    let result = tokio::task::spawn_blocking(move || {
+        let flushed = provider.force_flush();
+        let _ = provider.shutdown();
+        flushed
+    })
+    .await;
+    if let Ok(Err(e)) = result {
+        eprintln!("{} Failed to export trace: {}", "Warning:".yellow(), e);
+    }
+}
+
+/// OTLP/HTTP endpoints are given as a base URL, as OTEL_EXPORTER_OTLP_ENDPOINT is
+fn traces_url(endpoint: &str) -> String {
+    let endpoint = endpoint.trim_end_matches('/');
+    if endpoint.ends_with("/v1/traces") {
+        endpoint.to_string()
+    } else {
+        format!("{}/v1/traces", endpoint)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry::trace::Status;
+    use opentelemetry_sdk::trace::InMemorySpanExporter;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[test]
+    fn test_exports_spans() {
+        let exporter = InMemorySpanExporter::default();
+        let provider = SdkTracerProvider::builder()
+            .with_simple_exporter(exporter.clone())
+            .build();
+        let subscriber = tracing_subscriber::registry().with(layer_for(&provider));
+        tracing::subscriber::with_default(subscriber, || {
+            let command = tracing::info_span!(
+                "command",
+                otel.name = "cluster start",
+                otel.status_description = tracing::field::Empty
+            );
+            let _command = command.enter();
+            tracing::info_span!("image_pull", image = "redis:7-alpine").in_scope(|| {});
+            record_error(&command, &anyhow::anyhow!("timed out"));
+        });
+
+        let spans = exporter.get_finished_spans().unwrap();
+        assert_eq!(spans.len(), 2);
+        let (pull, command) = (&spans[0], &spans[1]);
+        assert_eq!(pull.name, "image_pull");
+        assert_eq!(pull.parent_span_id, command.span_context.span_id());
+        assert_eq!(
+            pull.span_context.trace_id(),
+            command.span_context.trace_id()
+        );
+        assert!(pull
+            .attributes
+            .iter()
+            .any(|kv| kv.key.as_str() == "image" && kv.value.as_str() == "redis:7-alpine"));
+        assert_eq!(command.name, "cluster start");
+        assert_eq!(command.status, Status::error("timed out"));
+        assert_eq!(
+            traces_url("http://localhost:4318/"),
+            "http://localhost:4318/v1/traces"
+        );
+    }
+}